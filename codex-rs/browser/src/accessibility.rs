@@ -0,0 +1,259 @@
+//! Compact accessibility-tree snapshots with stable element refs.
+//!
+//! `Accessibility.getFullAXTree` returns a flat list of nodes that is far too
+//! verbose to hand to a model. We keep nodes that carry meaning (a role other
+//! than a generic container, a name, or a value), hoist the children of
+//! everything else, and render the result as an indented outline. Every
+//! rendered node that maps to a DOM node gets a ref of the form `e<id>`,
+//! where `<id>` is the node's `backendDOMNodeId`. Backend ids are stable for
+//! the lifetime of a document, so a ref taken from one snapshot keeps
+//! pointing at the same element until the page navigates.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+/// Default upper bound on the number of nodes rendered in a snapshot.
+pub const DEFAULT_MAX_NODES: usize = 600;
+
+/// Maximum number of characters kept from a node's name or value.
+const MAX_TEXT_CHARS: usize = 120;
+
+/// Roles that only group other nodes and are dropped unless they are named.
+const STRUCTURAL_ROLES: &[&str] = &[
+    "none",
+    "presentation",
+    "generic",
+    "GenericContainer",
+    "group",
+    "Section",
+    "LayoutTable",
+    "LayoutTableRow",
+    "LayoutTableCell",
+    "paragraph",
+    "div",
+    "Div",
+];
+
+/// Boolean or tri-state AX properties worth surfacing next to a node.
+const STATE_PROPERTIES: &[&str] = &[
+    "checked", "disabled", "expanded", "focused", "pressed", "required", "selected",
+];
+
+/// A rendered snapshot of the page's accessibility tree.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AccessibilitySnapshot {
+    /// Indented outline, one node per line.
+    pub text: String,
+    /// Number of rendered nodes that carry a ref.
+    pub refs: usize,
+    /// Whether nodes were omitted because of the node limit.
+    pub truncated: bool,
+}
+
+/// Formats a ref for a DOM node's backend id.
+pub fn element_ref(backend_node_id: i64) -> String {
+    format!("e{backend_node_id}")
+}
+
+/// Parses a ref produced by [`element_ref`] back into a backend node id.
+/// Accepts `e42`, `ref=e42` and `[ref=e42]` so refs can be pasted straight
+/// from a snapshot line.
+pub fn parse_element_ref(r: &str) -> Option<i64> {
+    let r = r.trim().trim_start_matches('[').trim_end_matches(']');
+    let r = r.strip_prefix("ref=").unwrap_or(r);
+    r.strip_prefix('e')?.parse().ok().filter(|id| *id > 0)
+}
+
+/// Renders the `nodes` array returned by `Accessibility.getFullAXTree`.
+pub fn render_ax_tree(nodes: &[Value], max_nodes: usize) -> AccessibilitySnapshot {
+    let by_id: HashMap<&str, &Value> = nodes
+        .iter()
+        .filter_map(|n| Some((n.get("nodeId")?.as_str()?, n)))
+        .collect();
+    // The root is the first node without a parent (normally RootWebArea).
+    let root = nodes.iter().find(|n| n.get("parentId").is_none());
+
+    let mut out = AccessibilitySnapshot {
+        text: String::new(),
+        refs: 0,
+        truncated: false,
+    };
+    let mut rendered = 0usize;
+    if let Some(root) = root {
+        render_node(root, &by_id, 0, max_nodes, &mut rendered, &mut out);
+    }
+    out
+}
+
+fn render_node(
+    node: &Value,
+    by_id: &HashMap<&str, &Value>,
+    depth: usize,
+    max_nodes: usize,
+    rendered: &mut usize,
+    out: &mut AccessibilitySnapshot,
+) {
+    let mut child_depth = depth;
+    if let Some(line) = describe_node(node) {
+        if *rendered >= max_nodes {
+            out.truncated = true;
+            return;
+        }
+        *rendered += 1;
+        out.text.push_str(&"  ".repeat(depth));
+        out.text.push_str("- ");
+        out.text.push_str(&line);
+        if let Some(id) = node.get("backendDOMNodeId").and_then(Value::as_i64) {
+            out.text.push_str(&format!(" [ref={}]", element_ref(id)));
+            out.refs += 1;
+        }
+        out.text.push('\n');
+        child_depth += 1;
+    }
+
+    let parent_name = ax_string(node, "name");
+    let children = node
+        .get("childIds")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|id| by_id.get(id.as_str()?));
+    for child in children {
+        // Text that merely repeats the parent's accessible name adds nothing.
+        if role(child) == "StaticText" && ax_string(child, "name") == parent_name {
+            continue;
+        }
+        render_node(child, by_id, child_depth, max_nodes, rendered, out);
+        if out.truncated {
+            return;
+        }
+    }
+}
+
+/// Returns the outline text for `node`, or `None` when the node should be
+/// skipped and its children hoisted into its parent.
+fn describe_node(node: &Value) -> Option<String> {
+    if node
+        .get("ignored")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+    {
+        return None;
+    }
+    let role = role(node);
+    let name = ax_string(node, "name").unwrap_or_default();
+    let value = ax_string(node, "value").unwrap_or_default();
+    if role == "InlineTextBox" || role == "LineBreak" {
+        return None;
+    }
+    if role == "StaticText" {
+        return (!name.trim().is_empty()).then(|| format!("text {}", quote(&name)));
+    }
+    if STRUCTURAL_ROLES.contains(&role) && name.is_empty() && value.is_empty() {
+        return None;
+    }
+
+    let mut line = role.to_string();
+    if !name.is_empty() {
+        line.push(' ');
+        line.push_str(&quote(&name));
+    }
+    if !value.is_empty() {
+        line.push_str(&format!(" value={}", quote(&value)));
+    }
+    for prop in node
+        .get("properties")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let Some(prop_name) = prop.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        let prop_value = prop.get("value").and_then(|v| v.get("value"));
+        if prop_name == "level" {
+            if let Some(level) = prop_value.and_then(Value::as_i64) {
+                line.push_str(&format!(" [level={level}]"));
+            }
+        } else if STATE_PROPERTIES.contains(&prop_name) {
+            match prop_value {
+                Some(Value::Bool(true)) => line.push_str(&format!(" [{prop_name}]")),
+                Some(Value::String(s)) if s == "mixed" => {
+                    line.push_str(&format!(" [{prop_name}=mixed]"))
+                }
+                Some(Value::String(s)) if s == "true" => line.push_str(&format!(" [{prop_name}]")),
+                _ => {}
+            }
+        }
+    }
+    Some(line)
+}
+
+fn role(node: &Value) -> &str {
+    node.get("role")
+        .and_then(|r| r.get("value"))
+        .and_then(Value::as_str)
+        .unwrap_or("none")
+}
+
+fn ax_string(node: &Value, key: &str) -> Option<String> {
+    let value = node.get(key)?.get("value")?;
+    let s = match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => return None,
+    };
+    let s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!s.is_empty()).then_some(s)
+}
+
+fn quote(s: &str) -> String {
+    let mut text: String = s.chars().take(MAX_TEXT_CHARS).collect();
+    if s.chars().count() > MAX_TEXT_CHARS {
+        text.push('…');
+    }
+    format!("{text:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renders_meaningful_nodes_with_refs() {
+        let nodes = vec![
+            json!({"nodeId": "1", "role": {"value": "RootWebArea"}, "name": {"value": "Login"}, "childIds": ["2"], "backendDOMNodeId": 1}),
+            json!({"nodeId": "2", "parentId": "1", "role": {"value": "generic"}, "childIds": ["3", "4", "6"], "backendDOMNodeId": 5}),
+            json!({"nodeId": "3", "parentId": "2", "role": {"value": "heading"}, "name": {"value": "Sign in"}, "properties": [{"name": "level", "value": {"type": "integer", "value": 1}}], "childIds": ["7"], "backendDOMNodeId": 8}),
+            json!({"nodeId": "7", "parentId": "3", "role": {"value": "StaticText"}, "name": {"value": "Sign in"}, "childIds": []}),
+            json!({"nodeId": "4", "parentId": "2", "role": {"value": "textbox"}, "name": {"value": "Email"}, "value": {"value": "a@b.c"}, "properties": [{"name": "focused", "value": {"type": "booleanOrUndefined", "value": true}}], "backendDOMNodeId": 12}),
+            json!({"nodeId": "6", "parentId": "2", "role": {"value": "button"}, "name": {"value": "Continue"}, "ignored": false, "properties": [{"name": "disabled", "value": {"type": "boolean", "value": false}}], "backendDOMNodeId": 15}),
+        ];
+        let snapshot = render_ax_tree(&nodes, DEFAULT_MAX_NODES);
+        assert_eq!(
+            snapshot.text,
+            "- RootWebArea \"Login\" [ref=e1]\n  \
+             - heading \"Sign in\" [level=1] [ref=e8]\n  \
+             - textbox \"Email\" value=\"a@b.c\" [focused] [ref=e12]\n  \
+             - button \"Continue\" [ref=e15]\n"
+        );
+        assert_eq!(snapshot.refs, 4);
+        assert!(!snapshot.truncated);
+
+        let truncated = render_ax_tree(&nodes, 2);
+        assert_eq!(truncated.refs, 2);
+        assert!(truncated.truncated);
+    }
+
+    #[test]
+    fn parses_refs() {
+        assert_eq!(parse_element_ref("e42"), Some(42));
+        assert_eq!(parse_element_ref("[ref=e42]"), Some(42));
+        assert_eq!(parse_element_ref(&element_ref(7)), Some(7));
+        assert_eq!(parse_element_ref("42"), None);
+        assert_eq!(parse_element_ref("e0"), None);
+    }
+}
@@ -1,3 +1,7 @@
+use crate::dialog::DialogPolicy;
+use crate::environment::ColorScheme;
+use crate::environment::Geolocation;
+use crate::responsive::DevicePreset;
 use serde::Deserialize;
 use serde::Serialize;
 use std::path::PathBuf;
@@ -47,18 +51,52 @@ pub struct BrowserConfig {
     pub persist_profile: bool,
 
     /// "Human" env hints applied via CDP immediately after page creation.
-    #[serde(default)]
-    pub locale: Option<String>, // e.g. Some("en-AU".into())
+    /// Locale and timezone default to the host's (`LANG`, and `TZ` or `/etc/localtime`).
+    #[serde(default = "crate::environment::host_locale")]
+    pub locale: Option<String>, // e.g. Some("en-US".into())
+
+    #[serde(default = "crate::environment::host_timezone")]
+    pub timezone: Option<String>, // e.g. Some("America/New_York".into())
 
+    #[serde(default = "default_accept_language")]
+    pub accept_language: Option<String>, // e.g. Some("en-US,en;q=0.9".into())
+
+    /// Position reported by `navigator.geolocation`; the permission is
+    /// granted to every origin while set.
     #[serde(default)]
-    pub timezone: Option<String>, // e.g. Some("Australia/Brisbane".into())
+    pub geolocation: Option<Geolocation>,
 
+    /// Emulated `prefers-color-scheme`; `None` follows the browser.
     #[serde(default)]
-    pub accept_language: Option<String>, // e.g. Some("en-AU,en;q=0.9".into())
+    pub color_scheme: Option<ColorScheme>,
 
     #[serde(default)]
     pub user_agent: Option<String>, // leave None to let Chrome decide
 
+    /// Origins (e.g. "http://localhost:3000") whose cookies and web storage
+    /// are captured when saving a storage state, in addition to the current
+    /// page's origin.
+    #[serde(default)]
+    pub storage_origins: Vec<String>,
+
+    /// Extra device presets for responsive sweeps; a preset named like a
+    /// built-in one ("mobile", "tablet", "desktop", ...) replaces it.
+    #[serde(default)]
+    pub device_presets: Vec<DevicePreset>,
+
+    /// Devices captured by a responsive sweep that names none. Empty means
+    /// mobile, tablet and desktop.
+    #[serde(default)]
+    pub responsive_devices: Vec<String>,
+
+    /// How pages answer alert/confirm/prompt dialogs as they open.
+    #[serde(default)]
+    pub dialog_policy: DialogPolicy,
+
+    /// Where downloads are saved; defaults to a per-session temp directory.
+    #[serde(default)]
+    pub downloads_dir: Option<PathBuf>,
+
     // --- Connection tuning (CDP attach) ---
     /// Per-attempt timeout for WS connect to Chrome (milliseconds)
     #[serde(default = "default_connect_attempt_timeout_ms")]
@@ -84,10 +122,17 @@ impl Default for BrowserConfig {
             connect_port: None,
             user_data_dir: None,
             persist_profile: default_persist_profile(),
-            locale: Some("en-AU".into()),
-            timezone: Some("Australia/Brisbane".into()),
-            accept_language: Some("en-AU,en;q=0.9".into()),
+            locale: crate::environment::host_locale(),
+            timezone: crate::environment::host_timezone(),
+            accept_language: default_accept_language(),
+            geolocation: None,
+            color_scheme: None,
             user_agent: None,
+            storage_origins: Vec::new(),
+            device_presets: Vec::new(),
+            responsive_devices: Vec::new(),
+            dialog_policy: DialogPolicy::default(),
+            downloads_dir: None,
             connect_attempt_timeout_ms: default_connect_attempt_timeout_ms(),
             connect_attempts: default_connect_attempts(),
         }
@@ -133,6 +178,10 @@ fn default_wait() -> WaitStrategy {
     WaitStrategy::Event("networkidle".to_string())
 }
 
+fn default_accept_language() -> Option<String> {
+    crate::environment::host_locale().map(|locale| crate::environment::accept_language_for(&locale))
+}
+
 fn default_segments_max() -> usize {
     8
 }
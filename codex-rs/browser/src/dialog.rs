@@ -0,0 +1,193 @@
+//! JavaScript dialogs (`alert`, `confirm`, `prompt`, `beforeunload`).
+//!
+//! An open dialog blocks the page's main thread, so every later script,
+//! screenshot or navigation hangs until it is answered. Each page answers
+//! dialogs as soon as they open according to its [`DialogPolicy`]; under
+//! [`DialogPolicy::Manual`] the dialog is left open until the model accepts
+//! or dismisses it. Recent dialogs are kept so the model can see what was
+//! shown and how it was answered.
+
+use std::collections::VecDeque;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Dialogs kept per page, oldest dropped first.
+const MAX_DIALOGS: usize = 20;
+
+/// How a page answers dialogs when they open.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DialogPolicy {
+    /// Accept every dialog; prompts receive their default text.
+    #[default]
+    Accept,
+    /// Dismiss every dialog, as if Cancel was pressed.
+    Dismiss,
+    /// Leave dialogs open until `handle_dialog` answers them.
+    Manual,
+}
+
+impl DialogPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "accept" => Some(Self::Accept),
+            "dismiss" => Some(Self::Dismiss),
+            "manual" => Some(Self::Manual),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for DialogPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Accept => "accept",
+            Self::Dismiss => "dismiss",
+            Self::Manual => "manual",
+        })
+    }
+}
+
+/// A dialog a page opened and, once answered, how.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsDialog {
+    /// `alert`, `confirm`, `prompt` or `beforeunload`.
+    pub kind: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_prompt: Option<String>,
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<DialogOutcome>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DialogOutcome {
+    pub accepted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_text: Option<String>,
+    /// Answered by the policy rather than explicitly.
+    pub automatic: bool,
+}
+
+impl std::fmt::Display for JsDialog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}({:?})", self.kind, self.message)?;
+        match &self.outcome {
+            None => write!(f, " is open"),
+            Some(outcome) => {
+                let verb = if outcome.accepted {
+                    "accepted"
+                } else {
+                    "dismissed"
+                };
+                write!(f, " was {verb}")?;
+                if let Some(text) = &outcome.prompt_text {
+                    write!(f, " with {text:?}")?;
+                }
+                if outcome.automatic {
+                    write!(f, " automatically")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Dialogs seen by one page.
+#[derive(Debug, Default)]
+pub struct DialogLog {
+    dialogs: VecDeque<JsDialog>,
+}
+
+impl DialogLog {
+    pub fn record(&mut self, dialog: JsDialog) {
+        if self.dialogs.len() == MAX_DIALOGS {
+            self.dialogs.pop_front();
+        }
+        self.dialogs.push_back(dialog);
+    }
+
+    /// The dialog currently waiting for an answer, if any.
+    pub fn pending(&self) -> Option<&JsDialog> {
+        self.dialogs.back().filter(|d| d.outcome.is_none())
+    }
+
+    /// Marks the open dialog answered; returns it.
+    pub fn resolve(&mut self, outcome: DialogOutcome) -> Option<JsDialog> {
+        let dialog = self.dialogs.back_mut().filter(|d| d.outcome.is_none())?;
+        dialog.outcome = Some(outcome);
+        Some(dialog.clone())
+    }
+
+    /// Dialogs closed without an answer from us (e.g. by navigation).
+    pub fn closed_externally(&mut self, accepted: bool) {
+        if let Some(dialog) = self.dialogs.back_mut().filter(|d| d.outcome.is_none()) {
+            dialog.outcome = Some(DialogOutcome {
+                accepted,
+                prompt_text: None,
+                automatic: true,
+            });
+        }
+    }
+
+    pub fn recent(&self) -> Vec<JsDialog> {
+        self.dialogs.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dialog(kind: &str) -> JsDialog {
+        JsDialog {
+            kind: kind.to_string(),
+            message: "Delete item?".to_string(),
+            default_prompt: None,
+            url: "http://localhost/".to_string(),
+            outcome: None,
+        }
+    }
+
+    #[test]
+    fn resolving_answers_only_the_open_dialog() {
+        let mut log = DialogLog::default();
+        assert!(
+            log.resolve(DialogOutcome {
+                accepted: true,
+                prompt_text: None,
+                automatic: false,
+            })
+            .is_none()
+        );
+
+        log.record(dialog("confirm"));
+        assert_eq!(
+            log.pending().map(ToString::to_string).as_deref(),
+            Some("confirm(\"Delete item?\") is open")
+        );
+        let resolved = log.resolve(DialogOutcome {
+            accepted: false,
+            prompt_text: None,
+            automatic: false,
+        });
+        assert_eq!(
+            resolved.map(|d| d.to_string()).as_deref(),
+            Some("confirm(\"Delete item?\") was dismissed")
+        );
+        assert!(log.pending().is_none());
+    }
+
+    #[test]
+    fn keeps_only_recent_dialogs() {
+        let mut log = DialogLog::default();
+        for _ in 0..MAX_DIALOGS + 5 {
+            log.record(dialog("alert"));
+            log.closed_externally(true);
+        }
+        assert_eq!(log.recent().len(), MAX_DIALOGS);
+        assert_eq!(DialogPolicy::parse(" Manual "), Some(DialogPolicy::Manual));
+    }
+}
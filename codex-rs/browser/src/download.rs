@@ -0,0 +1,193 @@
+//! Files downloaded by pages.
+//!
+//! Downloads are routed into one directory per browser session. Chrome saves
+//! each file under its download GUID while it is in progress; once it
+//! completes it is renamed to the page's suggested file name (made unique
+//! within the directory) so the model can find and read it.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+/// Directory downloads go to when `BrowserConfig::downloads_dir` is unset:
+/// one per process under the system temp directory.
+pub fn default_downloads_dir() -> PathBuf {
+    std::env::temp_dir()
+        .join("codex-browser-downloads")
+        .join(std::process::id().to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadState {
+    InProgress,
+    Completed,
+    Canceled,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Download {
+    pub guid: String,
+    pub url: String,
+    pub suggested_filename: String,
+    /// Where the file is, or will be once completed.
+    pub path: PathBuf,
+    pub state: DownloadState,
+    pub received_bytes: u64,
+    pub total_bytes: Option<u64>,
+}
+
+impl std::fmt::Display for Download {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.state {
+            DownloadState::Completed => write!(
+                f,
+                "{} ({} bytes) from {}",
+                self.path.display(),
+                self.received_bytes,
+                self.url
+            ),
+            DownloadState::InProgress => {
+                write!(
+                    f,
+                    "{} downloading: {} bytes",
+                    self.suggested_filename, self.received_bytes
+                )?;
+                if let Some(total) = self.total_bytes {
+                    write!(f, " of {total}")?;
+                }
+                write!(f, " from {}", self.url)
+            }
+            DownloadState::Canceled => {
+                write!(f, "{} canceled, from {}", self.suggested_filename, self.url)
+            }
+        }
+    }
+}
+
+/// Downloads seen in this browser session, in the order they started.
+#[derive(Debug)]
+pub struct DownloadLog {
+    dir: PathBuf,
+    downloads: Vec<Download>,
+}
+
+impl DownloadLog {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            downloads: Vec::new(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn downloads(&self) -> &[Download] {
+        &self.downloads
+    }
+
+    pub(crate) fn record_begin(&mut self, guid: &str, url: &str, suggested_filename: &str) {
+        let suggested_filename = sanitize_filename(suggested_filename);
+        self.downloads.push(Download {
+            guid: guid.to_string(),
+            url: url.to_string(),
+            path: self.dir.join(guid),
+            suggested_filename,
+            state: DownloadState::InProgress,
+            received_bytes: 0,
+            total_bytes: None,
+        });
+    }
+
+    /// Updates a download; on completion moves the file from its GUID name
+    /// to a unique file named after the suggestion.
+    pub(crate) fn record_progress(
+        &mut self,
+        guid: &str,
+        received_bytes: u64,
+        total_bytes: u64,
+        state: DownloadState,
+    ) {
+        let renamed = match self.downloads.iter().find(|d| d.guid == guid) {
+            Some(d) if state == DownloadState::Completed => {
+                Some(self.unique_path(&d.suggested_filename))
+            }
+            _ => None,
+        };
+        let Some(download) = self.downloads.iter_mut().find(|d| d.guid == guid) else {
+            return;
+        };
+        download.received_bytes = received_bytes;
+        download.total_bytes = (total_bytes > 0).then_some(total_bytes);
+        download.state = state;
+        if let Some(path) = renamed {
+            match std::fs::rename(&download.path, &path) {
+                Ok(()) => download.path = path,
+                Err(e) => tracing::warn!(
+                    "Failed to rename download {}: {}",
+                    download.path.display(),
+                    e
+                ),
+            }
+        }
+    }
+
+    fn unique_path(&self, filename: &str) -> PathBuf {
+        let candidate = self.dir.join(filename);
+        if !candidate.exists() {
+            return candidate;
+        }
+        let (stem, ext) = match filename.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+            _ => (filename, String::new()),
+        };
+        (1..)
+            .map(|n| self.dir.join(format!("{stem} ({n}){ext}")))
+            .find(|p| !p.exists())
+            .unwrap_or(candidate)
+    }
+}
+
+/// Keeps only the final path component of a suggested name.
+fn sanitize_filename(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default().trim();
+    if base.is_empty() || base == "." || base == ".." {
+        "download".to_string()
+    } else {
+        base.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn completed_downloads_get_unique_suggested_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = DownloadLog::new(dir.path().to_path_buf());
+        std::fs::write(dir.path().join("report.csv"), "old").unwrap();
+
+        log.record_begin("guid-1", "http://localhost/report", "../report.csv");
+        std::fs::write(dir.path().join("guid-1"), "a,b\n").unwrap();
+        log.record_progress("guid-1", 4, 4, DownloadState::Completed);
+
+        let download = &log.downloads()[0];
+        assert_eq!(download.state, DownloadState::Completed);
+        assert_eq!(download.path, dir.path().join("report (1).csv"));
+        assert_eq!(std::fs::read_to_string(&download.path).unwrap(), "a,b\n");
+    }
+
+    #[test]
+    fn progress_for_unknown_downloads_is_ignored() {
+        let mut log = DownloadLog::new(PathBuf::from("/nonexistent"));
+        log.record_progress("missing", 1, 2, DownloadState::InProgress);
+        assert!(log.downloads().is_empty());
+        assert_eq!(sanitize_filename(".."), "download");
+    }
+}
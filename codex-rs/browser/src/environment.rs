@@ -0,0 +1,253 @@
+//! Locale, timezone, geolocation and color-scheme emulation.
+//!
+//! Unless configured otherwise, pages report the host's locale (`LC_ALL` or
+//! `LANG`) and timezone (`TZ`, else `/etc/localtime`), so dates in screenshots are formatted the way
+//! the user expects. Geolocation and `prefers-color-scheme` are only
+//! emulated when configured.
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::config::BrowserConfig;
+
+/// `prefers-color-scheme` reported to pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+impl ColorScheme {
+    /// Parses `light` or `dark`; `auto` and anything else yield `None`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "light" => Some(Self::Light),
+            "dark" => Some(Self::Dark),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Light => "light",
+            Self::Dark => "dark",
+        }
+    }
+}
+
+impl std::fmt::Display for ColorScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Position reported by `navigator.geolocation`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Geolocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Accuracy in meters.
+    #[serde(default = "default_accuracy")]
+    pub accuracy: f64,
+}
+
+fn default_accuracy() -> f64 {
+    50.0
+}
+
+impl Geolocation {
+    pub fn new(latitude: f64, longitude: f64, accuracy: Option<f64>) -> Option<Self> {
+        let valid = (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude);
+        valid.then(|| Self {
+            latitude,
+            longitude,
+            accuracy: accuracy
+                .filter(|a| *a >= 0.0)
+                .unwrap_or_else(default_accuracy),
+        })
+    }
+}
+
+impl std::fmt::Display for Geolocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.4},{:.4} (±{}m)",
+            self.latitude, self.longitude, self.accuracy
+        )
+    }
+}
+
+/// The host locale as a BCP 47 tag, from `LC_ALL` or `LANG`.
+pub fn host_locale() -> Option<String> {
+    ["LC_ALL", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| locale_from_posix(&value))
+}
+
+/// The host timezone as an IANA name. `TZ` wins when set; otherwise it is
+/// the zone `/etc/localtime` links to, or the name in `/etc/timezone`.
+pub fn host_timezone() -> Option<String> {
+    if let Ok(value) = std::env::var("TZ")
+        && !value.is_empty()
+    {
+        return timezone_from_tz(&value);
+    }
+    std::fs::read_link("/etc/localtime")
+        .ok()
+        .and_then(|link| timezone_from_localtime_link(&link.to_string_lossy()))
+        .or_else(|| {
+            std::fs::read_to_string("/etc/timezone")
+                .ok()
+                .and_then(|value| timezone_from_tz(&value))
+        })
+}
+
+/// `Accept-Language` for `locale`, falling back to its language.
+pub fn accept_language_for(locale: &str) -> String {
+    match locale.split_once('-') {
+        Some((language, _)) => format!("{locale},{language};q=0.9"),
+        None => locale.to_string(),
+    }
+}
+
+/// `en_US.UTF-8` or `de_DE@euro` to `en-US` / `de-DE`. The `C` and `POSIX`
+/// locales carry no language and yield `None`.
+fn locale_from_posix(value: &str) -> Option<String> {
+    let name = value.split(['.', '@']).next().unwrap_or_default().trim();
+    if name.is_empty() || name == "C" || name == "POSIX" {
+        return None;
+    }
+    let tag = name.replace('_', "-");
+    tag.split('-')
+        .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric()))
+        .then_some(tag)
+}
+
+/// `Europe/Berlin`, `:Europe/Berlin` or `/usr/share/zoneinfo/Europe/Berlin`
+/// to `Europe/Berlin`. POSIX rules such as `EST5EDT,M3.2.0,M11.1.0` have no
+/// IANA name and yield `None`.
+fn timezone_from_tz(value: &str) -> Option<String> {
+    let value = value.trim().trim_start_matches(':');
+    let name = match value.rsplit_once("zoneinfo/") {
+        Some((_, name)) => name,
+        None => value,
+    };
+    let valid = !name.is_empty()
+        && !name.starts_with('/')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+'));
+    valid.then(|| name.to_string())
+}
+
+/// `/usr/share/zoneinfo/Europe/Berlin` (or a relative link into any
+/// `zoneinfo` directory) to `Europe/Berlin`. Links elsewhere yield `None`.
+fn timezone_from_localtime_link(link: &str) -> Option<String> {
+    link.contains("zoneinfo/")
+        .then(|| timezone_from_tz(link))
+        .flatten()
+}
+
+/// CDP commands that apply `config`'s environment to a page, clearing any
+/// setting that is unset. Locale and timezone are left untouched unless
+/// `include_locale`, so attached Chrome keeps the user's own.
+pub(crate) fn environment_commands(
+    config: &BrowserConfig,
+    include_locale: bool,
+) -> Vec<(&'static str, Value)> {
+    let mut commands = Vec::new();
+    if include_locale {
+        commands.push((
+            "Emulation.setTimezoneOverride",
+            json!({ "timezoneId": config.timezone.clone().unwrap_or_default() }),
+        ));
+        commands.push((
+            "Emulation.setLocaleOverride",
+            match &config.locale {
+                Some(locale) => json!({ "locale": locale }),
+                None => json!({}),
+            },
+        ));
+    }
+    let scheme = config.color_scheme.map(ColorScheme::as_str).unwrap_or("");
+    commands.push((
+        "Emulation.setEmulatedMedia",
+        json!({ "features": [{ "name": "prefers-color-scheme", "value": scheme }] }),
+    ));
+    commands.push(match config.geolocation {
+        Some(g) => (
+            "Emulation.setGeolocationOverride",
+            json!({ "latitude": g.latitude, "longitude": g.longitude, "accuracy": g.accuracy }),
+        ),
+        None => ("Emulation.clearGeolocationOverride", json!({})),
+    });
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_values_are_normalized() {
+        assert_eq!(locale_from_posix("en_US.UTF-8").as_deref(), Some("en-US"));
+        assert_eq!(locale_from_posix("de_DE@euro").as_deref(), Some("de-DE"));
+        assert_eq!(locale_from_posix("C.UTF-8"), None);
+        assert_eq!(locale_from_posix("POSIX"), None);
+        assert_eq!(
+            timezone_from_tz(":America/New_York").as_deref(),
+            Some("America/New_York")
+        );
+        assert_eq!(
+            timezone_from_tz("/usr/share/zoneinfo/Europe/Berlin").as_deref(),
+            Some("Europe/Berlin")
+        );
+        assert_eq!(timezone_from_tz("EST5EDT,M3.2.0,M11.1.0"), None);
+        assert_eq!(
+            timezone_from_localtime_link("../usr/share/zoneinfo/Asia/Tokyo").as_deref(),
+            Some("Asia/Tokyo")
+        );
+        assert_eq!(
+            timezone_from_localtime_link("/var/db/timezone/zoneinfo/Europe/Paris").as_deref(),
+            Some("Europe/Paris")
+        );
+        assert_eq!(timezone_from_localtime_link("/etc/localtime.local"), None);
+        assert_eq!(accept_language_for("fr-FR"), "fr-FR,fr;q=0.9");
+        assert_eq!(accept_language_for("fr"), "fr");
+    }
+
+    #[test]
+    fn unset_settings_clear_their_overrides() {
+        let config = BrowserConfig {
+            locale: None,
+            timezone: Some("Europe/Berlin".to_string()),
+            color_scheme: Some(ColorScheme::Dark),
+            geolocation: None,
+            ..Default::default()
+        };
+        let commands = environment_commands(&config, true);
+        let methods: Vec<&str> = commands.iter().map(|(m, _)| *m).collect();
+        assert_eq!(
+            methods,
+            vec![
+                "Emulation.setTimezoneOverride",
+                "Emulation.setLocaleOverride",
+                "Emulation.setEmulatedMedia",
+                "Emulation.clearGeolocationOverride",
+            ]
+        );
+        assert_eq!(commands[0].1["timezoneId"], "Europe/Berlin");
+        assert_eq!(commands[1].1, json!({}));
+        assert_eq!(commands[2].1["features"][0]["value"], "dark");
+
+        let external = environment_commands(&config, false);
+        assert_eq!(external.len(), 2);
+        assert_eq!(Geolocation::new(91.0, 0.0, None), None);
+    }
+}
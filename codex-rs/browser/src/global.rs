@@ -8,6 +8,10 @@ use tokio::sync::RwLock;
 static GLOBAL_BROWSER_MANAGER: Lazy<Arc<RwLock<Option<Arc<BrowserManager>>>>> =
     Lazy::new(|| Arc::new(RwLock::new(None)));
 
+/// Config the global browser manager is created with
+static DEFAULT_CONFIG: Lazy<RwLock<BrowserConfig>> =
+    Lazy::new(|| RwLock::new(BrowserConfig::default()));
+
 /// Cache of the last successful external Chrome connection (port/ws)
 static LAST_CONNECTION: Lazy<Arc<RwLock<(Option<u16>, Option<String>)>>> =
     Lazy::new(|| Arc::new(RwLock::new((None, None))));
@@ -24,12 +28,24 @@ pub async fn get_or_create_browser_manager() -> Arc<BrowserManager> {
     if let Some(existing) = w.as_ref() {
         return existing.clone();
     }
-    let config = BrowserConfig::default();
+    let config = DEFAULT_CONFIG.read().await.clone();
     let manager = Arc::new(BrowserManager::new(config));
     *w = Some(manager.clone());
     manager
 }
 
+/// Update the locale, timezone, geolocation or color scheme the global
+/// browser manager starts with, and apply it to the manager if it exists
+pub async fn configure_environment(updates: impl Fn(&mut BrowserConfig)) {
+    updates(&mut *DEFAULT_CONFIG.write().await);
+    let Some(manager) = get_browser_manager().await else {
+        return;
+    };
+    if let Err(e) = manager.update_environment(|config| updates(config)).await {
+        tracing::warn!("Failed to apply browser environment: {}", e);
+    }
+}
+
 /// Get the global browser manager if it exists
 pub async fn get_browser_manager() -> Option<Arc<BrowserManager>> {
     GLOBAL_BROWSER_MANAGER.read().await.as_ref().cloned()
@@ -1,18 +1,38 @@
+pub mod accessibility;
 pub mod assets;
 pub mod config;
+pub mod dialog;
+pub mod download;
+pub mod environment;
 pub mod global;
 pub mod hooks;
 pub mod manager;
+pub mod network;
 pub mod page;
+pub mod perf;
+pub mod responsive;
+pub mod script;
+pub mod storage_state;
 pub mod tools;
 
+pub use accessibility::AccessibilitySnapshot;
 pub use config::BrowserConfig;
 pub use config::ViewportConfig;
 pub use config::WaitStrategy;
+pub use dialog::DialogPolicy;
+pub use environment::ColorScheme;
+pub use environment::Geolocation;
+pub use manager::ActiveTab;
 pub use manager::BrowserManager;
+pub use manager::TabInfo;
+pub use network::NetworkFilter;
+pub use network::RouteRule;
+pub use page::ElementTarget;
 pub use page::Page;
+pub use page::ResolvedElement;
 pub use page::ScreenshotMode;
 pub use page::ScreenshotRegion;
+pub use storage_state::StorageState;
 
 use thiserror::Error;
 
@@ -38,6 +58,9 @@ pub enum BrowserError {
 
     #[error("Asset storage error: {0}")]
     AssetError(String),
+
+    #[error("Script error: {0}")]
+    ScriptError(String),
 }
 
 impl From<chromiumoxide::error::CdpError> for BrowserError {
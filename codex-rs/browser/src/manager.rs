@@ -1,17 +1,51 @@
 use crate::BrowserError;
 use crate::Result;
+use crate::accessibility::AccessibilitySnapshot;
 use crate::config::BrowserConfig;
+use crate::dialog::DialogPolicy;
+use crate::dialog::JsDialog;
+use crate::download::Download;
+use crate::download::DownloadLog;
+use crate::download::DownloadState;
+use crate::environment::environment_commands;
+use crate::network::NetworkEntry;
+use crate::network::NetworkFilter;
+use crate::network::ResponseBody;
+use crate::network::RouteRule;
+use crate::page::ElementTarget;
 use crate::page::Page;
+use crate::page::RawCdpCommand;
+use crate::page::ResolvedElement;
+use crate::page::SetViewportParams;
+use crate::perf::NetworkConditions;
+use crate::perf::PerfReport;
+use crate::perf::TraceSummary;
+use crate::responsive::DevicePreset;
+use crate::responsive::DeviceShot;
+use crate::script::BrowserScript;
+use crate::script::ScriptStep;
+use crate::storage_state::StorageState;
 use chromiumoxide::Browser;
 use chromiumoxide::BrowserConfig as CdpConfig;
 use chromiumoxide::browser::HeadlessMode;
+use chromiumoxide::cdp::browser_protocol::browser::DownloadProgressState;
+use chromiumoxide::cdp::browser_protocol::browser::EventDownloadProgress;
+use chromiumoxide::cdp::browser_protocol::browser::EventDownloadWillBegin;
+use chromiumoxide::cdp::browser_protocol::browser::GrantPermissionsParams;
+use chromiumoxide::cdp::browser_protocol::browser::PermissionType;
+use chromiumoxide::cdp::browser_protocol::browser::SetDownloadBehaviorBehavior;
+use chromiumoxide::cdp::browser_protocol::browser::SetDownloadBehaviorParams;
 use chromiumoxide::cdp::browser_protocol::emulation;
 use chromiumoxide::cdp::browser_protocol::network;
+use chromiumoxide::cdp::browser_protocol::target::EventTargetCreated;
+use chromiumoxide::cdp::browser_protocol::target::EventTargetDestroyed;
 use futures::StreamExt;
 use reqwest::Client;
 use serde::Deserialize;
 use serde_json::Value;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
 use tokio::time::Duration;
@@ -178,10 +212,42 @@ pub struct BrowserManager {
     auto_viewport_correction_enabled: Arc<tokio::sync::RwLock<bool>>,
     /// Track last applied device metrics to avoid redundant overrides
     last_metrics_applied: Arc<Mutex<Option<(i64, i64, f64, bool, std::time::Instant)>>>,
+    /// Every tab we control, in the order they were opened; `page` is the active one
+    tabs: Arc<Mutex<Vec<Tab>>>,
+    /// Popups opened and targets closed since the last page access
+    target_events: Arc<Mutex<Vec<TargetEvent>>>,
+    target_monitor_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    /// Script being recorded from browser actions, if any
+    recording: Arc<Mutex<Option<BrowserScript>>>,
+    /// Downloads routed into the session's download directory
+    downloads: Arc<Mutex<DownloadLog>>,
+    download_monitor_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    /// Whether the current browser granted geolocation to every origin
+    geolocation_granted: Arc<AtomicBool>,
+}
+
+/// A tab we have wrapped in a `Page`.
+#[derive(Clone)]
+struct Tab {
+    page: Arc<Page>,
+    /// Target that opened this tab, for popups.
+    opener_id: Option<String>,
+}
+
+/// Target lifecycle changes reported by the target monitor. They are applied
+/// lazily on the next page access, where `&self` is available to wrap pages.
+#[derive(Debug)]
+enum TargetEvent {
+    Popup { target_id: String, opener_id: String },
+    Closed { target_id: String },
 }
 
 impl BrowserManager {
     pub fn new(config: BrowserConfig) -> Self {
+        let downloads_dir = config
+            .downloads_dir
+            .clone()
+            .unwrap_or_else(crate::download::default_downloads_dir);
         Self {
             config: Arc::new(RwLock::new(config)),
             browser: Arc::new(Mutex::new(None)),
@@ -198,6 +264,13 @@ impl BrowserManager {
             viewport_monitor_handle: Arc::new(Mutex::new(None)),
             auto_viewport_correction_enabled: Arc::new(tokio::sync::RwLock::new(true)),
             last_metrics_applied: Arc::new(Mutex::new(None)),
+            tabs: Arc::new(Mutex::new(Vec::new())),
+            target_events: Arc::new(Mutex::new(Vec::new())),
+            target_monitor_handle: Arc::new(Mutex::new(None)),
+            recording: Arc::new(Mutex::new(None)),
+            downloads: Arc::new(Mutex::new(DownloadLog::new(downloads_dir))),
+            download_monitor_handle: Arc::new(Mutex::new(None)),
+            geolocation_granted: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -423,6 +496,7 @@ impl BrowserManager {
         if self.browser.lock().await.is_some() {
             return Ok(());
         }
+        self.geolocation_granted.store(false, Ordering::Relaxed);
 
         let config = self.config.read().await.clone();
 
@@ -704,6 +778,12 @@ impl BrowserManager {
         }
 
         self.stop_navigation_monitor().await;
+        if let Some(handle) = self.target_monitor_handle.lock().await.take() {
+            handle.abort();
+        }
+        self.stop_download_monitor().await;
+        self.tabs.lock().await.clear();
+        self.target_events.lock().await.clear();
 
         let mut page_guard = self.page.lock().await;
         *page_guard = None;
@@ -754,6 +834,10 @@ impl BrowserManager {
         self.ensure_browser().await?;
         info!("[bm] get_or_create_page: ensure_browser in {:?}", overall_start.elapsed());
         self.update_activity().await;
+        self.ensure_target_monitor().await;
+        self.ensure_download_monitor().await;
+        self.ensure_geolocation_permission().await;
+        self.apply_target_events().await;
 
         let mut page_guard = self.page.lock().await;
         if let Some(page) = page_guard.as_ref() {
@@ -772,6 +856,8 @@ impl BrowserManager {
                 }
                 Ok(Err(e)) => {
                     warn!("Existing page returned error: {}, will create new page", e);
+                    let target_id = page.target_id();
+                    self.tabs.lock().await.retain(|t| t.page.target_id() != target_id);
                     *page_guard = None;
                 }
                 Err(_) => {
@@ -934,6 +1020,7 @@ impl BrowserManager {
 
         let page = Arc::new(Page::new(cdp_page, config.clone()));
         *page_guard = Some(Arc::clone(&page));
+        self.register_tab(Arc::clone(&page), None).await;
 
         // Inject the virtual cursor when page is created
         debug!("Injecting virtual cursor for new page");
@@ -957,6 +1044,425 @@ impl BrowserManager {
         Ok(page)
     }
 
+    /// List every page target in the browser. Tabs opened outside our control
+    /// (e.g. by the user in an attached Chrome) are listed too and can be
+    /// switched to.
+    pub async fn list_tabs(&self) -> Result<Vec<TabInfo>> {
+        let active = self.get_or_create_page().await?.target_id();
+        let tracked = self.tabs.lock().await.clone();
+
+        let mut infos = Vec::with_capacity(tracked.len());
+        for tab in &tracked {
+            let id = tab.page.target_id();
+            infos.push(TabInfo {
+                active: id == active,
+                url: tab.page.get_current_url().await.unwrap_or_default(),
+                title: tab.page.get_title().await,
+                opener_id: tab.opener_id.clone(),
+                id,
+            });
+        }
+
+        let untracked: Vec<chromiumoxide::page::Page> = {
+            let browser_guard = self.browser.lock().await;
+            let browser = browser_guard.as_ref().ok_or(BrowserError::NotInitialized)?;
+            browser.pages().await?
+        };
+        for cdp_page in untracked {
+            let id = cdp_page.target_id().as_ref().to_string();
+            if infos.iter().any(|t| t.id == id) {
+                continue;
+            }
+            let url = tokio::time::timeout(Duration::from_millis(300), cdp_page.url())
+                .await
+                .ok()
+                .and_then(|r| r.ok().flatten())
+                .unwrap_or_default();
+            infos.push(TabInfo {
+                id,
+                url,
+                title: None,
+                active: false,
+                opener_id: None,
+            });
+        }
+        Ok(infos)
+    }
+
+    /// Make the tab with target id `id` the one browser actions apply to.
+    pub async fn switch_tab(&self, id: &str) -> Result<TabInfo> {
+        self.get_or_create_page().await?;
+        let tracked = self
+            .tabs
+            .lock()
+            .await
+            .iter()
+            .find(|t| t.page.target_id() == id)
+            .map(|t| Arc::clone(&t.page));
+        let page = match tracked {
+            Some(page) => page,
+            None => {
+                let cdp_page = {
+                    let browser_guard = self.browser.lock().await;
+                    let browser = browser_guard.as_ref().ok_or(BrowserError::NotInitialized)?;
+                    browser
+                        .get_page(id.to_string().into())
+                        .await
+                        .map_err(|_| BrowserError::CdpError(format!("No tab with id {id}")))?
+                };
+                self.adopt_page(cdp_page, None).await?
+            }
+        };
+        self.activate_tab(Arc::clone(&page)).await;
+        self.tab_info(&page).await
+    }
+
+    /// Open a new tab, optionally navigating it to `url`, and make it active.
+    pub async fn open_tab(&self, url: Option<&str>) -> Result<TabInfo> {
+        let opener = self.get_or_create_page().await?.target_id();
+        let cdp_page = {
+            let browser_guard = self.browser.lock().await;
+            let browser = browser_guard.as_ref().ok_or(BrowserError::NotInitialized)?;
+            browser.new_page(url.unwrap_or("about:blank")).await?
+        };
+        let page = self.adopt_page(cdp_page, Some(opener)).await?;
+        self.activate_tab(Arc::clone(&page)).await;
+        self.tab_info(&page).await
+    }
+
+    /// Close the tab with target id `id`, or the active tab when `None`.
+    /// Returns the tab that is active afterwards, if any.
+    pub async fn close_tab(&self, id: Option<&str>) -> Result<Option<TabInfo>> {
+        let active = self.get_or_create_page().await?.target_id();
+        let id = id.unwrap_or(&active).to_string();
+        self.execute_cdp_browser("Target.closeTarget", serde_json::json!({ "targetId": id }))
+            .await?;
+        self.forget_tab(&id).await;
+        let page = self.page.lock().await.clone();
+        match page {
+            Some(page) => Ok(Some(self.tab_info(&page).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Position of the active tab among the tracked tabs, without creating a
+    /// page. Returns `None` when no page is open.
+    pub async fn active_tab(&self) -> Option<ActiveTab> {
+        let active = self.page.lock().await.as_ref()?.target_id();
+        let tabs = self.tabs.lock().await;
+        let index = tabs.iter().position(|t| t.page.target_id() == active)?;
+        Some(ActiveTab {
+            id: active,
+            index: index + 1,
+            count: tabs.len(),
+        })
+    }
+
+    async fn tab_info(&self, page: &Arc<Page>) -> Result<TabInfo> {
+        let id = page.target_id();
+        let opener_id = self
+            .tabs
+            .lock()
+            .await
+            .iter()
+            .find(|t| t.page.target_id() == id)
+            .and_then(|t| t.opener_id.clone());
+        Ok(TabInfo {
+            url: page.get_current_url().await.unwrap_or_default(),
+            title: page.get_title().await,
+            active: true,
+            opener_id,
+            id,
+        })
+    }
+
+    async fn register_tab(&self, page: Arc<Page>, opener_id: Option<String>) {
+        let mut tabs = self.tabs.lock().await;
+        let id = page.target_id();
+        if !tabs.iter().any(|t| t.page.target_id() == id) {
+            tabs.push(Tab { page, opener_id });
+        }
+    }
+
+    /// Wrap a CDP page the same way `get_or_create_page` does and track it.
+    async fn adopt_page(
+        &self,
+        cdp_page: chromiumoxide::page::Page,
+        opener_id: Option<String>,
+    ) -> Result<Arc<Page>> {
+        self.apply_page_overrides(&cdp_page).await?;
+        let config = self.config.read().await.clone();
+        let page = Arc::new(Page::new(cdp_page, config));
+        if let Err(e) = page.inject_virtual_cursor().await {
+            warn!("Failed to inject virtual cursor on new tab: {}", e);
+        }
+        self.register_tab(Arc::clone(&page), opener_id).await;
+        Ok(page)
+    }
+
+    /// Point browser actions, monitors and screenshots at `page`.
+    async fn activate_tab(&self, page: Arc<Page>) {
+        *self.page.lock().await = Some(Arc::clone(&page));
+        if let Err(e) = page.bring_to_front().await {
+            debug!("bringToFront failed: {}", e);
+        }
+        // The navigation monitor reports the new tab's URL on its first tick,
+        // which refreshes the screenshot shown in the TUI.
+        self.start_navigation_monitor(Arc::clone(&page)).await;
+        self.start_viewport_monitor(page).await;
+    }
+
+    /// Drop a closed tab; if it was active, fall back to its opener or the
+    /// most recently opened remaining tab.
+    async fn forget_tab(&self, id: &str) {
+        let successor = {
+            let mut tabs = self.tabs.lock().await;
+            let Some(pos) = tabs.iter().position(|t| t.page.target_id() == id) else {
+                return;
+            };
+            let closed = tabs.remove(pos);
+            closed
+                .opener_id
+                .and_then(|opener| tabs.iter().find(|t| t.page.target_id() == opener).cloned())
+                .or_else(|| tabs.last().cloned())
+        };
+        let was_active = self
+            .page
+            .lock()
+            .await
+            .as_ref()
+            .is_some_and(|p| p.target_id() == id);
+        if was_active {
+            match successor {
+                Some(tab) => self.activate_tab(tab.page).await,
+                None => {
+                    self.stop_navigation_monitor().await;
+                    *self.page.lock().await = None;
+                }
+            }
+        }
+    }
+
+    /// Listen for popups and closed tabs. Restarted whenever the browser
+    /// connection is replaced, since the old event stream ends with it.
+    async fn ensure_target_monitor(&self) {
+        let mut handle_guard = self.target_monitor_handle.lock().await;
+        if handle_guard.as_ref().is_some_and(|h| !h.is_finished()) {
+            return;
+        }
+        let (created, destroyed) = {
+            let browser_guard = self.browser.lock().await;
+            let Some(browser) = browser_guard.as_ref() else {
+                return;
+            };
+            (
+                browser.event_listener::<EventTargetCreated>().await,
+                browser.event_listener::<EventTargetDestroyed>().await,
+            )
+        };
+        let (Ok(created), Ok(destroyed)) = (created, destroyed) else {
+            warn!("Failed to subscribe to target events; popups will not be tracked");
+            return;
+        };
+
+        let target_events = Arc::clone(&self.target_events);
+        let mut events = futures::stream::select(
+            created.map(|e| {
+                let info = &e.target_info;
+                match (&info.opener_id, info.r#type.as_str()) {
+                    (Some(opener), "page") => Some(TargetEvent::Popup {
+                        target_id: info.target_id.as_ref().to_string(),
+                        opener_id: opener.as_ref().to_string(),
+                    }),
+                    _ => None,
+                }
+            }),
+            destroyed.map(|e| {
+                Some(TargetEvent::Closed {
+                    target_id: e.target_id.as_ref().to_string(),
+                })
+            }),
+        );
+        *handle_guard = Some(tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                if let Some(event) = event {
+                    debug!("Target event: {:?}", event);
+                    target_events.lock().await.push(event);
+                }
+            }
+        }));
+    }
+
+    /// Route downloads into the session's download directory and track
+    /// them until the browser stops.
+    /// Grants geolocation to every origin once per browser while a
+    /// position is configured, so pages can read the emulated one.
+    async fn ensure_geolocation_permission(&self) {
+        if self.geolocation_granted.load(Ordering::Relaxed)
+            || self.config.read().await.geolocation.is_none()
+        {
+            return;
+        }
+        let browser_guard = self.browser.lock().await;
+        let Some(browser) = browser_guard.as_ref() else {
+            return;
+        };
+        let params = GrantPermissionsParams::new(vec![PermissionType::Geolocation]);
+        match browser.execute(params).await {
+            Ok(_) => self.geolocation_granted.store(true, Ordering::Relaxed),
+            Err(e) => warn!("Failed to grant geolocation permission: {}", e),
+        }
+    }
+
+    async fn ensure_download_monitor(&self) {
+        let mut handle_guard = self.download_monitor_handle.lock().await;
+        if handle_guard.as_ref().is_some_and(|h| !h.is_finished()) {
+            return;
+        }
+        let dir = self.downloads.lock().await.dir().to_path_buf();
+        if let Err(e) = tokio::fs::create_dir_all(&dir).await {
+            warn!("Failed to create downloads directory {}: {}", dir.display(), e);
+            return;
+        }
+        let (began, progress) = {
+            let browser_guard = self.browser.lock().await;
+            let Some(browser) = browser_guard.as_ref() else {
+                return;
+            };
+            let behavior = SetDownloadBehaviorParams::builder()
+                .behavior(SetDownloadBehaviorBehavior::AllowAndName)
+                .download_path(dir.to_string_lossy())
+                .events_enabled(true)
+                .build();
+            match behavior {
+                Ok(params) => {
+                    if let Err(e) = browser.execute(params).await {
+                        warn!("Failed to route downloads: {}", e);
+                        return;
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to build download behavior: {}", e);
+                    return;
+                }
+            }
+            (
+                browser.event_listener::<EventDownloadWillBegin>().await,
+                browser.event_listener::<EventDownloadProgress>().await,
+            )
+        };
+        let (Ok(began), Ok(progress)) = (began, progress) else {
+            warn!("Failed to subscribe to download events; downloads will not be tracked");
+            return;
+        };
+
+        enum DownloadEvent {
+            Began(Arc<EventDownloadWillBegin>),
+            Progress(Arc<EventDownloadProgress>),
+        }
+        let downloads = Arc::clone(&self.downloads);
+        let mut events = futures::stream::select(
+            began.map(DownloadEvent::Began),
+            progress.map(DownloadEvent::Progress),
+        );
+        *handle_guard = Some(tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                let mut log = downloads.lock().await;
+                match event {
+                    DownloadEvent::Began(e) => {
+                        log.record_begin(&e.guid, &e.url, &e.suggested_filename)
+                    }
+                    DownloadEvent::Progress(e) => {
+                        let state = match e.state {
+                            DownloadProgressState::InProgress => DownloadState::InProgress,
+                            DownloadProgressState::Completed => DownloadState::Completed,
+                            DownloadProgressState::Canceled => DownloadState::Canceled,
+                        };
+                        log.record_progress(
+                            &e.guid,
+                            e.received_bytes as u64,
+                            e.total_bytes as u64,
+                            state,
+                        );
+                    }
+                }
+            }
+        }));
+    }
+
+    /// Stop tracking downloads; an external Chrome gets its own download
+    /// behavior back.
+    async fn stop_download_monitor(&self) {
+        let Some(handle) = self.download_monitor_handle.lock().await.take() else {
+            return;
+        };
+        handle.abort();
+        let config = self.config.read().await;
+        if config.connect_port.is_none() && config.connect_ws.is_none() {
+            return;
+        }
+        drop(config);
+        if let Some(browser) = self.browser.lock().await.as_ref() {
+            let params = SetDownloadBehaviorParams::new(SetDownloadBehaviorBehavior::Default);
+            if let Err(e) = browser.execute(params).await {
+                warn!("Failed to restore download behavior: {}", e);
+            }
+        }
+    }
+
+    /// Adopt popups opened since the last call (the newest becomes active, as
+    /// it would for a user) and forget tabs that were closed.
+    async fn apply_target_events(&self) {
+        let events: Vec<TargetEvent> = std::mem::take(&mut *self.target_events.lock().await);
+        for event in events {
+            match event {
+                TargetEvent::Popup {
+                    target_id,
+                    opener_id,
+                } => {
+                    // Only popups of tabs we control; others belong to the user.
+                    let known_opener = self
+                        .tabs
+                        .lock()
+                        .await
+                        .iter()
+                        .any(|t| t.page.target_id() == opener_id);
+                    if !known_opener {
+                        continue;
+                    }
+                    // The handler attaches to new targets asynchronously.
+                    let mut cdp_page = None;
+                    for _ in 0..20 {
+                        let found = {
+                            let browser_guard = self.browser.lock().await;
+                            match browser_guard.as_ref() {
+                                Some(b) => b.get_page(target_id.clone().into()).await.ok(),
+                                None => None,
+                            }
+                        };
+                        if found.is_some() {
+                            cdp_page = found;
+                            break;
+                        }
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                    }
+                    let Some(cdp_page) = cdp_page else {
+                        warn!("Popup {} could not be attached", target_id);
+                        continue;
+                    };
+                    match self.adopt_page(cdp_page, Some(opener_id)).await {
+                        Ok(page) => {
+                            info!("Attached popup tab {}", target_id);
+                            self.activate_tab(page).await;
+                        }
+                        Err(e) => warn!("Failed to attach popup {}: {}", target_id, e),
+                    }
+                }
+                TargetEvent::Closed { target_id } => self.forget_tab(&target_id).await,
+            }
+        }
+    }
+
     pub async fn close_page(&self) -> Result<()> {
         let mut page_guard = self.page.lock().await;
         if let Some(page) = page_guard.take() {
@@ -1013,6 +1519,25 @@ impl BrowserManager {
         Ok(())
     }
 
+    /// Changes the locale, timezone, geolocation or color scheme and applies
+    /// the result to every open tab. New tabs pick it up from the config.
+    pub async fn update_environment(
+        &self,
+        updates: impl FnOnce(&mut BrowserConfig),
+    ) -> Result<BrowserConfig> {
+        let config = {
+            let mut config = self.config.write().await;
+            updates(&mut config);
+            config.clone()
+        };
+        self.ensure_geolocation_permission().await;
+        let tabs = self.tabs.lock().await.clone();
+        for tab in tabs {
+            tab.page.apply_environment(&config).await?;
+        }
+        Ok(config)
+    }
+
     pub async fn get_config(&self) -> BrowserConfig {
         self.config.read().await.clone()
     }
@@ -1050,6 +1575,14 @@ impl BrowserManager {
         // Always enable Network domain once
         page.execute(network::EnableParams::default()).await?;
 
+        // Geolocation and color scheme are explicit settings and apply to
+        // attached Chrome too; locale and timezone only to Chrome we launched.
+        for (method, params) in environment_commands(&config, !is_external) {
+            if let Err(e) = page.execute(RawCdpCommand::new(method, params)).await {
+                warn!("Failed to apply {}: {}", method, e);
+            }
+        }
+
         if is_external {
             // External Chrome: set viewport once on connection; skip humanization.
             let w = config.viewport.width as i64;
@@ -1105,19 +1638,6 @@ impl BrowserManager {
                     .map_err(BrowserError::CdpError)?;
                 page.execute(p).await?;
             }
-
-            if let Some(tz) = &config.timezone {
-                page.execute(emulation::SetTimezoneOverrideParams {
-                    timezone_id: tz.clone(),
-                })
-                .await?;
-            }
-            if let Some(locale) = &config.locale {
-                let p = emulation::SetLocaleOverrideParams::builder()
-                    .locale(locale)
-                    .build();
-                page.execute(p).await?;
-            }
         }
 
         Ok(())
@@ -1639,6 +2159,260 @@ impl BrowserManager {
         Ok(())
     }
 
+    /// Capture a compact accessibility tree of the current page
+    pub async fn snapshot(
+        &self,
+        max_nodes: Option<usize>,
+    ) -> Result<AccessibilitySnapshot> {
+        let page = self.get_or_create_page().await?;
+        page.snapshot(max_nodes).await
+    }
+
+    /// Scroll an element into view and return its center point
+    pub async fn resolve_element(
+        &self,
+        target: &ElementTarget,
+    ) -> Result<ResolvedElement> {
+        let page = self.get_or_create_page().await?;
+        page.resolve_element(target).await
+    }
+
+    /// Requests recorded for the current page that match `filter`
+    /// Register the Web Vitals observers on the current page.
+    pub async fn install_vitals_observer(&self) -> Result<()> {
+        let page = self.get_or_create_page().await?;
+        page.install_vitals_observer().await
+    }
+
+    /// Metrics and Web Vitals of the current page, without a trace.
+    pub async fn perf_report(&self) -> Result<PerfReport> {
+        let page = self.get_or_create_page().await?;
+        Ok(PerfReport {
+            url: page.get_current_url().await.unwrap_or_default(),
+            metrics: page.performance_metrics().await?,
+            vitals: page.web_vitals().await?,
+            trace: None,
+        })
+    }
+
+    pub async fn set_throttling(
+        &self,
+        network: Option<NetworkConditions>,
+        cpu_slowdown: Option<f64>,
+    ) -> Result<()> {
+        let page = self.get_or_create_page().await?;
+        page.set_throttling(network, cpu_slowdown).await
+    }
+
+    pub async fn start_trace(&self) -> Result<()> {
+        let page = self.get_or_create_page().await?;
+        page.start_trace().await
+    }
+
+    pub async fn stop_trace(&self, path: &std::path::Path) -> Result<TraceSummary> {
+        let page = self.get_or_create_page().await?;
+        page.stop_trace(path).await
+    }
+
+    /// Dialogs the active tab opened, oldest first.
+    pub async fn dialogs(&self) -> Result<Vec<JsDialog>> {
+        let page = self.get_or_create_page().await?;
+        Ok(page.dialogs().await)
+    }
+
+    /// The dialog the active tab is blocked on, if any. Does not create a
+    /// page when none exists.
+    pub async fn pending_dialog(&self) -> Option<JsDialog> {
+        let page = self.page.lock().await.clone()?;
+        page.pending_dialog().await
+    }
+
+    /// Accept or dismiss the active tab's open dialog.
+    pub async fn handle_dialog(&self, accept: bool, prompt_text: Option<String>) -> Result<JsDialog> {
+        let page = self.get_or_create_page().await?;
+        page.handle_dialog(accept, prompt_text).await
+    }
+
+    /// Change how dialogs are answered, for open tabs and tabs opened later.
+    pub async fn set_dialog_policy(&self, policy: DialogPolicy) {
+        self.config.write().await.dialog_policy = policy;
+        let pages: Vec<Arc<Page>> = self.tabs.lock().await.iter().map(|t| t.page.clone()).collect();
+        for page in pages {
+            page.set_dialog_policy(policy).await;
+        }
+    }
+
+    /// Set the files of a file input on the active tab.
+    pub async fn upload_files(&self, target: &ElementTarget, files: &[std::path::PathBuf]) -> Result<()> {
+        let page = self.get_or_create_page().await?;
+        page.set_input_files(target, files).await
+    }
+
+    /// The download directory and every download seen this session.
+    pub async fn downloads(&self) -> (std::path::PathBuf, Vec<Download>) {
+        let log = self.downloads.lock().await;
+        (log.dir().to_path_buf(), log.downloads().to_vec())
+    }
+
+    /// Capture cookies and web storage for the current page's origin, the
+    /// configured `storage_origins` and `extra_origins`.
+    pub async fn save_storage_state(&self, extra_origins: &[String]) -> Result<StorageState> {
+        let mut origins = self.config.read().await.storage_origins.clone();
+        origins.extend(extra_origins.iter().cloned());
+        let page = self.get_or_create_page().await?;
+        page.capture_storage_state(&origins).await
+    }
+
+    /// Restore a state captured by `save_storage_state` into the browser.
+    pub async fn restore_storage_state(&self, state: &StorageState) -> Result<()> {
+        let page = self.get_or_create_page().await?;
+        page.restore_storage_state(state).await
+    }
+
+    /// Capture the current page as each of `devices`, reloading before each
+    /// capture when `reload` is set, then restore the browser's own
+    /// viewport, touch and user agent settings.
+    pub async fn responsive_sweep(
+        &self,
+        devices: &[DevicePreset],
+        reload: bool,
+        settle: Duration,
+    ) -> Result<Vec<DeviceShot>> {
+        let page = self.get_or_create_page().await?;
+        let user_agent = page
+            .inject_js("navigator.userAgent")
+            .await?
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_default();
+
+        let mut shots = Vec::with_capacity(devices.len());
+        let mut outcome = Ok(());
+        for device in devices {
+            let captured = async {
+                page.emulate_device(device).await?;
+                if reload {
+                    page.execute_cdp_raw("Page.reload", serde_json::json!({}))
+                        .await?;
+                }
+                tokio::time::sleep(settle).await;
+                page.capture_device_screenshot(device.width, device.height)
+                    .await
+            }
+            .await;
+            match captured {
+                Ok(png) => shots.push(DeviceShot {
+                    device: device.clone(),
+                    png,
+                }),
+                Err(e) => {
+                    outcome = Err(e);
+                    break;
+                }
+            }
+        }
+
+        page.clear_device_emulation(&user_agent).await?;
+        let config = self.config.read().await.clone();
+        if config.connect_port.is_some() || config.connect_ws.is_some() {
+            // External Chrome keeps the viewport applied on connect.
+            *self.last_metrics_applied.lock().await = None;
+            page.set_viewport(SetViewportParams {
+                width: config.viewport.width,
+                height: config.viewport.height,
+                device_scale_factor: Some(config.viewport.device_scale_factor),
+                mobile: Some(config.viewport.mobile),
+            })
+            .await?;
+        }
+        if reload {
+            page.execute_cdp_raw("Page.reload", serde_json::json!({}))
+                .await?;
+        }
+        outcome.map(|()| shots)
+    }
+
+    /// Selector that uniquely matches the element, for recording.
+    pub async fn selector_for(&self, backend_node_id: i64) -> Result<String> {
+        let page = self.get_or_create_page().await?;
+        page.selector_for(backend_node_id).await
+    }
+
+    /// Start recording browser actions into a script named `name`,
+    /// discarding any recording in progress.
+    pub async fn start_recording(&self, name: &str) {
+        let viewport = self.get_viewport_size().await;
+        *self.recording.lock().await = Some(BrowserScript {
+            name: name.to_string(),
+            viewport: Some(viewport),
+            steps: Vec::new(),
+        });
+    }
+
+    /// Stop recording and return the recorded script.
+    pub async fn stop_recording(&self) -> Option<BrowserScript> {
+        self.recording.lock().await.take()
+    }
+
+    pub async fn is_recording(&self) -> bool {
+        self.recording.lock().await.is_some()
+    }
+
+    /// Append a step to the recording; does nothing when not recording.
+    pub async fn record_step(&self, step: ScriptStep) {
+        if let Some(script) = self.recording.lock().await.as_mut() {
+            script.steps.push(step);
+        }
+    }
+
+    pub async fn network_entries(&self, filter: &NetworkFilter) -> Result<Vec<NetworkEntry>> {
+        let page = self.get_or_create_page().await?;
+        Ok(page.network_entries(filter).await)
+    }
+
+    /// Look up a recorded request by its CDP request id
+    pub async fn network_entry(&self, id: &str) -> Result<Option<NetworkEntry>> {
+        let page = self.get_or_create_page().await?;
+        Ok(page.network_entry(id).await)
+    }
+
+    /// Fetch the response body of a recorded request
+    pub async fn network_response_body(&self, id: &str) -> Result<ResponseBody> {
+        let page = self.get_or_create_page().await?;
+        page.network_response_body(id).await
+    }
+
+    /// Forget the requests recorded for the current page
+    pub async fn clear_network_log(&self) -> Result<()> {
+        let page = self.get_or_create_page().await?;
+        page.clear_network_log().await;
+        Ok(())
+    }
+
+    /// Export recorded requests as a HAR 1.2 document
+    pub async fn export_har(&self, filter: &NetworkFilter, include_bodies: bool) -> Result<Value> {
+        let page = self.get_or_create_page().await?;
+        Ok(page.export_har(filter, include_bodies).await)
+    }
+
+    /// Mock or block requests matching the rule's pattern
+    pub async fn add_route(&self, rule: RouteRule) -> Result<()> {
+        let page = self.get_or_create_page().await?;
+        page.add_route(rule).await
+    }
+
+    /// Remove one routing rule by pattern, or all of them
+    pub async fn remove_routes(&self, pattern: Option<&str>) -> Result<usize> {
+        let page = self.get_or_create_page().await?;
+        page.remove_routes(pattern).await
+    }
+
+    /// The routing rules installed on the current page
+    pub async fn routes(&self) -> Result<Vec<RouteRule>> {
+        let page = self.get_or_create_page().await?;
+        Ok(page.routes().await)
+    }
+
     /// Get the current cursor position
     pub async fn get_cursor_position(&self) -> Result<(f64, f64)> {
         let page = self.get_or_create_page().await?;
@@ -1915,6 +2689,26 @@ impl BrowserManager {
     }
 }
 
+/// A browser tab as reported by `BrowserManager::list_tabs`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TabInfo {
+    /// CDP target id, used to switch to or close the tab.
+    pub id: String,
+    pub url: String,
+    pub title: Option<String>,
+    pub active: bool,
+    /// Tab that opened this one, for popups and tabs opened via `open_tab`.
+    pub opener_id: Option<String>,
+}
+
+/// Where the active tab sits among the tracked tabs (1-based `index`).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ActiveTab {
+    pub id: String,
+    pub index: usize,
+    pub count: usize,
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct BrowserStatus {
     pub enabled: bool,
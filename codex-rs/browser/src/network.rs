@@ -0,0 +1,696 @@
+//! Network capture, HAR export and request routing for a page.
+//!
+//! `Page` feeds the CDP `Network.*` events it receives into a [`NetworkLog`],
+//! a bounded, in-memory record of the requests the page made and how they
+//! ended. Response bodies are not copied eagerly; they are fetched from
+//! Chrome with `Network.getResponseBody` when a caller asks for them, so
+//! they are only available while Chrome still holds the resource.
+//!
+//! Routing rules are applied through the `Fetch` domain: while at least one
+//! rule is installed every request is paused, matched against the rules in
+//! order, and then fulfilled with a mock response, failed, or continued.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+
+/// Maximum number of requests kept per page; the oldest are dropped first.
+pub const MAX_NETWORK_ENTRIES: usize = 1000;
+
+/// A single request made by the page and, once known, its response.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NetworkEntry {
+    /// CDP `requestId`, used to look up bodies. Redirect hops share an id.
+    pub id: String,
+    pub url: String,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub request_headers: Vec<(String, String)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<String>,
+    /// Wall-clock start time in seconds since the Unix epoch.
+    pub started_at: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub response_headers: Vec<(String, String)>,
+    /// Time from request start to completion or failure, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<f64>,
+    /// Bytes received over the wire, including headers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoded_size: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Set when a routing rule answered or blocked the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub routed: Option<RoutedBy>,
+    /// Monotonic start timestamp reported by CDP, in seconds.
+    #[serde(skip)]
+    start_timestamp: f64,
+}
+
+impl NetworkEntry {
+    /// Whether the request failed outright or returned an HTTP error status.
+    pub fn is_failure(&self) -> bool {
+        self.error.is_some() || self.status.is_some_and(|s| s >= 400)
+    }
+
+    /// One-line summary such as `GET 500 https://x/api (12 ms) [id=42.1]`.
+    pub fn summary(&self) -> String {
+        let status = match (&self.error, self.status) {
+            (Some(err), _) => format!("ERR {err}"),
+            (None, Some(status)) => status.to_string(),
+            (None, None) => "pending".to_string(),
+        };
+        let mut line = format!("{} {} {}", self.method, status, self.url);
+        if let Some(ms) = self.duration_ms {
+            line.push_str(&format!(" ({ms:.0} ms)"));
+        }
+        if let Some(routed) = self.routed {
+            line.push_str(match routed {
+                RoutedBy::Mock => " [mocked]",
+                RoutedBy::Block => " [blocked]",
+            });
+        }
+        line.push_str(&format!(" [id={}]", self.id));
+        line
+    }
+}
+
+/// Which kind of routing rule handled a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutedBy {
+    Mock,
+    Block,
+}
+
+/// Criteria for selecting entries from a [`NetworkLog`].
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NetworkFilter {
+    /// URL pattern: a glob when it contains `*`, otherwise a substring.
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub method: Option<String>,
+    /// CDP resource type, e.g. `XHR`, `Fetch`, `Document` (case-insensitive).
+    #[serde(default)]
+    pub resource_type: Option<String>,
+    /// Only requests that failed or returned a 4xx/5xx status.
+    #[serde(default)]
+    pub failed_only: bool,
+    /// Keep only the most recent `limit` matches.
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl NetworkFilter {
+    pub fn matches(&self, entry: &NetworkEntry) -> bool {
+        self.url
+            .as_deref()
+            .is_none_or(|pattern| url_matches(pattern, &entry.url))
+            && self
+                .method
+                .as_deref()
+                .is_none_or(|m| m.eq_ignore_ascii_case(&entry.method))
+            && self.resource_type.as_deref().is_none_or(|t| {
+                entry
+                    .resource_type
+                    .as_deref()
+                    .is_some_and(|rt| rt.eq_ignore_ascii_case(t))
+            })
+            && (!self.failed_only || entry.is_failure())
+    }
+}
+
+/// Bounded record of the requests a page made, built from CDP events.
+#[derive(Debug, Default)]
+pub struct NetworkLog {
+    entries: VecDeque<NetworkEntry>,
+    /// Routing decisions that arrived before the matching request event.
+    pending_routes: HashMap<String, RoutedBy>,
+}
+
+impl NetworkLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.pending_routes.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries matching `filter`, oldest first.
+    pub fn query(&self, filter: &NetworkFilter) -> Vec<NetworkEntry> {
+        let matches: Vec<&NetworkEntry> =
+            self.entries.iter().filter(|e| filter.matches(e)).collect();
+        let skip = filter
+            .limit
+            .map_or(0, |limit| matches.len().saturating_sub(limit));
+        matches.into_iter().skip(skip).cloned().collect()
+    }
+
+    /// The most recent entry with the given request id.
+    pub fn get(&self, id: &str) -> Option<NetworkEntry> {
+        self.entries.iter().rev().find(|e| e.id == id).cloned()
+    }
+
+    /// Handles `Network.requestWillBeSent`.
+    pub fn record_request(&mut self, event: &Value) {
+        let Some(id) = event.get("requestId").and_then(Value::as_str) else {
+            return;
+        };
+        let timestamp = event
+            .get("timestamp")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0);
+
+        // A redirect reuses the request id: close the previous hop with the
+        // redirect response and start a new entry for the next one.
+        if let (Some(redirect), Some(prev)) = (event.get("redirectResponse"), self.last_mut(id)) {
+            apply_response(prev, redirect);
+            prev.duration_ms = Some(elapsed_ms(prev.start_timestamp, timestamp));
+        }
+        let needs_new = self.last_mut(id).is_none_or(|e| !e.url.is_empty());
+        if needs_new {
+            self.push(NetworkEntry {
+                id: id.to_string(),
+                ..Default::default()
+            });
+        }
+        let routed = self.pending_routes.remove(id);
+        let Some(entry) = self.last_mut(id) else {
+            return;
+        };
+        let request = event.get("request").unwrap_or(&Value::Null);
+        entry.url = str_field(request, "url").unwrap_or_default();
+        entry.method = str_field(request, "method").unwrap_or_else(|| "GET".to_string());
+        entry.request_headers = headers(request.get("headers"));
+        entry.post_data = str_field(request, "postData");
+        entry.resource_type = str_field(event, "type");
+        entry.started_at = event.get("wallTime").and_then(Value::as_f64).unwrap_or(0.0);
+        entry.start_timestamp = timestamp;
+        if routed.is_some() {
+            entry.routed = routed;
+        }
+    }
+
+    /// Handles `Network.responseReceived`.
+    pub fn record_response(&mut self, event: &Value) {
+        let Some(id) = event.get("requestId").and_then(Value::as_str) else {
+            return;
+        };
+        let entry = self.entry(id);
+        if let Some(response) = event.get("response") {
+            apply_response(entry, response);
+        }
+        if entry.resource_type.is_none() {
+            entry.resource_type = str_field(event, "type");
+        }
+    }
+
+    /// Handles `Network.loadingFinished`.
+    pub fn record_finished(&mut self, event: &Value) {
+        let Some(id) = event.get("requestId").and_then(Value::as_str) else {
+            return;
+        };
+        let timestamp = event
+            .get("timestamp")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0);
+        let entry = self.entry(id);
+        entry.encoded_size = event
+            .get("encodedDataLength")
+            .and_then(Value::as_f64)
+            .map(|n| n as i64);
+        entry.duration_ms = Some(elapsed_ms(entry.start_timestamp, timestamp));
+    }
+
+    /// Handles `Network.loadingFailed`.
+    pub fn record_failed(&mut self, event: &Value) {
+        let Some(id) = event.get("requestId").and_then(Value::as_str) else {
+            return;
+        };
+        let timestamp = event
+            .get("timestamp")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0);
+        let entry = self.entry(id);
+        let mut error = str_field(event, "errorText").unwrap_or_else(|| "failed".to_string());
+        if let Some(reason) = str_field(event, "blockedReason") {
+            error.push_str(&format!(" ({reason})"));
+        }
+        entry.error = Some(error);
+        entry.duration_ms = Some(elapsed_ms(entry.start_timestamp, timestamp));
+    }
+
+    /// Records that a routing rule handled the request with network id `id`.
+    pub fn mark_routed(&mut self, id: &str, routed: RoutedBy) {
+        match self.last_mut(id) {
+            Some(entry) => entry.routed = Some(routed),
+            None => {
+                self.pending_routes.insert(id.to_string(), routed);
+            }
+        }
+    }
+
+    fn last_mut(&mut self, id: &str) -> Option<&mut NetworkEntry> {
+        self.entries.iter_mut().rev().find(|e| e.id == id)
+    }
+
+    /// The latest entry for `id`, created empty if the request event has not
+    /// been seen yet (events from different streams can arrive out of order).
+    fn entry(&mut self, id: &str) -> &mut NetworkEntry {
+        if self.last_mut(id).is_none() {
+            self.push(NetworkEntry {
+                id: id.to_string(),
+                ..Default::default()
+            });
+        }
+        #[expect(clippy::expect_used)]
+        self.last_mut(id).expect("entry was just inserted")
+    }
+
+    fn push(&mut self, entry: NetworkEntry) {
+        if self.entries.len() >= MAX_NETWORK_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+/// A response body as returned by `Network.getResponseBody`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResponseBody {
+    pub body: String,
+    pub base64_encoded: bool,
+}
+
+/// Builds a HAR 1.2 document for `entries`. `bodies` maps request ids to
+/// response bodies to embed; entries without one get an empty `content`.
+pub fn to_har(entries: &[NetworkEntry], bodies: &HashMap<String, ResponseBody>) -> Value {
+    let har_entries: Vec<Value> = entries
+        .iter()
+        .filter(|e| !e.url.is_empty())
+        .map(|e| {
+            let started = chrono::DateTime::from_timestamp_millis((e.started_at * 1000.0) as i64)
+                .unwrap_or_default()
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+            let time = e.duration_ms.unwrap_or(0.0);
+            let http_version = e.protocol.clone().unwrap_or_else(|| "HTTP/1.1".to_string());
+            let query: Vec<Value> = url::Url::parse(&e.url)
+                .map(|u| {
+                    u.query_pairs()
+                        .map(|(name, value)| json!({ "name": name, "value": value }))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let mut request = json!({
+                "method": e.method,
+                "url": e.url,
+                "httpVersion": http_version,
+                "headers": har_headers(&e.request_headers),
+                "queryString": query,
+                "cookies": [],
+                "headersSize": -1,
+                "bodySize": e.post_data.as_ref().map_or(0, |d| d.len() as i64),
+            });
+            if let Some(post) = &e.post_data {
+                let mime = header_value(&e.request_headers, "content-type").unwrap_or_default();
+                request["postData"] = json!({ "mimeType": mime, "text": post });
+            }
+
+            let mut content = json!({
+                "size": e.encoded_size.unwrap_or(0),
+                "mimeType": e.mime_type.clone().unwrap_or_default(),
+            });
+            if let Some(body) = bodies.get(&e.id) {
+                content["text"] = json!(body.body);
+                if body.base64_encoded {
+                    content["encoding"] = json!("base64");
+                }
+            }
+
+            let mut entry = json!({
+                "startedDateTime": started,
+                "time": time,
+                "request": request,
+                "response": {
+                    "status": e.status.unwrap_or(0),
+                    "statusText": e.status_text.clone().unwrap_or_default(),
+                    "httpVersion": http_version,
+                    "headers": har_headers(&e.response_headers),
+                    "cookies": [],
+                    "content": content,
+                    "redirectURL": header_value(&e.response_headers, "location").unwrap_or_default(),
+                    "headersSize": -1,
+                    "bodySize": e.encoded_size.unwrap_or(-1),
+                },
+                "cache": {},
+                "timings": { "send": 0, "wait": time, "receive": 0 },
+            });
+            if let Some(err) = &e.error {
+                entry["_error"] = json!(err);
+            }
+            entry
+        })
+        .collect();
+
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "codex-browser", "version": env!("CARGO_PKG_VERSION") },
+            "pages": [],
+            "entries": har_entries,
+        }
+    })
+}
+
+/// A rule that answers or blocks requests whose URL matches `pattern`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteRule {
+    /// A glob when it contains `*`, otherwise a substring of the URL.
+    pub pattern: String,
+    #[serde(flatten)]
+    pub action: RouteAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RouteAction {
+    /// Fail the request as if blocked by the client.
+    Block,
+    /// Answer the request without contacting the server.
+    Mock {
+        #[serde(default = "default_mock_status")]
+        status: u16,
+        #[serde(default)]
+        body: String,
+        #[serde(default)]
+        content_type: Option<String>,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+}
+
+fn default_mock_status() -> u16 {
+    200
+}
+
+impl RouteRule {
+    /// Params for the `Fetch` command that applies this rule to the paused
+    /// request `request_id`, as `(method, params)`.
+    pub fn fetch_command(&self, request_id: &str) -> (&'static str, Value) {
+        match &self.action {
+            RouteAction::Block => (
+                "Fetch.failRequest",
+                json!({ "requestId": request_id, "errorReason": "BlockedByClient" }),
+            ),
+            RouteAction::Mock {
+                status,
+                body,
+                content_type,
+                headers,
+            } => {
+                use base64::Engine as _;
+                let mut response_headers: Vec<Value> = headers
+                    .iter()
+                    .map(|(name, value)| json!({ "name": name, "value": value }))
+                    .collect();
+                if !headers
+                    .keys()
+                    .any(|k| k.eq_ignore_ascii_case("content-type"))
+                {
+                    let ct = content_type
+                        .clone()
+                        .unwrap_or_else(|| guess_content_type(body));
+                    response_headers.push(json!({ "name": "Content-Type", "value": ct }));
+                }
+                (
+                    "Fetch.fulfillRequest",
+                    json!({
+                        "requestId": request_id,
+                        "responseCode": status,
+                        "responseHeaders": response_headers,
+                        "body": base64::engine::general_purpose::STANDARD.encode(body),
+                    }),
+                )
+            }
+        }
+    }
+
+    pub fn routed_by(&self) -> RoutedBy {
+        match self.action {
+            RouteAction::Block => RoutedBy::Block,
+            RouteAction::Mock { .. } => RoutedBy::Mock,
+        }
+    }
+}
+
+/// The first rule in `rules` whose pattern matches `url`.
+pub fn match_route<'a>(rules: &'a [RouteRule], url: &str) -> Option<&'a RouteRule> {
+    rules.iter().find(|r| url_matches(&r.pattern, url))
+}
+
+/// Matches `url` against a glob (`*` matches any run of characters) when the
+/// pattern contains `*`, and as a plain substring otherwise.
+pub fn url_matches(pattern: &str, url: &str) -> bool {
+    if !pattern.contains('*') {
+        return url.contains(pattern);
+    }
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, rest) = parts.split_first().unwrap_or((&"", &[]));
+    let Some(mut remaining) = url.strip_prefix(first) else {
+        return false;
+    };
+    let Some((last, middle)) = rest.split_last() else {
+        return remaining.is_empty();
+    };
+    for part in middle {
+        match remaining.find(part) {
+            Some(pos) => remaining = &remaining[pos + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= last.len() && remaining.ends_with(last)
+}
+
+fn guess_content_type(body: &str) -> String {
+    let trimmed = body.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        "application/json".to_string()
+    } else if trimmed.starts_with('<') {
+        "text/html; charset=utf-8".to_string()
+    } else {
+        "text/plain; charset=utf-8".to_string()
+    }
+}
+
+fn apply_response(entry: &mut NetworkEntry, response: &Value) {
+    entry.status = response
+        .get("status")
+        .and_then(Value::as_f64)
+        .map(|s| s as i64);
+    entry.status_text = str_field(response, "statusText").filter(|s| !s.is_empty());
+    entry.protocol = str_field(response, "protocol").map(|p| p.to_uppercase());
+    entry.mime_type = str_field(response, "mimeType");
+    entry.response_headers = headers(response.get("headers"));
+    if entry.url.is_empty() {
+        entry.url = str_field(response, "url").unwrap_or_default();
+    }
+}
+
+fn elapsed_ms(start: f64, end: f64) -> f64 {
+    if start > 0.0 && end >= start {
+        (end - start) * 1000.0
+    } else {
+        0.0
+    }
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+fn headers(value: Option<&Value>) -> Vec<(String, String)> {
+    value
+        .and_then(Value::as_object)
+        .map(|map| {
+            map.iter()
+                .map(|(k, v)| {
+                    (
+                        k.clone(),
+                        v.as_str().map_or_else(|| v.to_string(), str::to_string),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn header_value(headers: &[(String, String)], name: &str) -> Option<String> {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.clone())
+}
+
+fn har_headers(headers: &[(String, String)]) -> Vec<Value> {
+    headers
+        .iter()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: &str, url: &str, ts: f64) -> Value {
+        json!({
+            "requestId": id,
+            "request": { "url": url, "method": "GET", "headers": { "Accept": "*/*" } },
+            "timestamp": ts,
+            "wallTime": 1_700_000_000.0,
+            "type": "Fetch",
+        })
+    }
+
+    #[test]
+    fn records_lifecycle_and_filters_failures() {
+        let mut log = NetworkLog::new();
+        log.record_request(&request("1", "https://app.test/api/items?page=2", 10.0));
+        log.record_response(&json!({
+            "requestId": "1",
+            "response": { "status": 500, "statusText": "Internal Server Error", "mimeType": "application/json", "headers": {} },
+        }));
+        log.record_finished(
+            &json!({ "requestId": "1", "timestamp": 10.25, "encodedDataLength": 120 }),
+        );
+        // Out-of-order delivery: the response arrives before the request.
+        log.record_response(&json!({
+            "requestId": "2",
+            "response": { "status": 200, "mimeType": "text/css", "headers": {} },
+        }));
+        log.record_request(&request("2", "https://app.test/site.css", 10.1));
+        log.record_failed(
+            &json!({ "requestId": "3", "timestamp": 11.0, "errorText": "net::ERR_FAILED" }),
+        );
+
+        assert_eq!(log.len(), 3);
+        let api = log.get("1").unwrap_or_default();
+        assert_eq!(api.status, Some(500));
+        assert_eq!(api.duration_ms, Some(250.0));
+        assert_eq!(
+            api.summary(),
+            "GET 500 https://app.test/api/items?page=2 (250 ms) [id=1]"
+        );
+        let css = log.get("2").unwrap_or_default();
+        assert_eq!(
+            (css.url.as_str(), css.status),
+            ("https://app.test/site.css", Some(200))
+        );
+
+        let failed = log.query(&NetworkFilter {
+            failed_only: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            failed.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(),
+            vec!["1", "3"]
+        );
+        let api_only = log.query(&NetworkFilter {
+            url: Some("*/api/*".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(api_only.len(), 1);
+
+        let har = to_har(&log.query(&NetworkFilter::default()), &HashMap::new());
+        let entries = har["log"]["entries"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        // The failed request never produced a URL and is left out.
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["response"]["status"], json!(500));
+        assert_eq!(
+            entries[0]["request"]["queryString"][0],
+            json!({ "name": "page", "value": "2" })
+        );
+    }
+
+    #[test]
+    fn redirects_start_a_new_entry() {
+        let mut log = NetworkLog::new();
+        log.record_request(&request("7", "http://app.test/old", 1.0));
+        let mut hop = request("7", "http://app.test/new", 1.5);
+        hop["redirectResponse"] = json!({ "status": 301, "headers": { "Location": "/new" } });
+        log.record_request(&hop);
+
+        let entries = log.query(&NetworkFilter::default());
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].status, Some(301));
+        assert_eq!(entries[0].duration_ms, Some(500.0));
+        assert_eq!(entries[1].url, "http://app.test/new");
+    }
+
+    #[test]
+    fn routes_match_globs_and_substrings() {
+        assert!(url_matches("*/api/*", "https://x.test/api/users"));
+        assert!(url_matches(
+            "https://x.test/*.png",
+            "https://x.test/a/b.png"
+        ));
+        assert!(!url_matches(
+            "https://x.test/*.png",
+            "https://x.test/a/b.png?v=1"
+        ));
+        assert!(url_matches("analytics", "https://cdn.analytics.test/t.js"));
+        assert!(!url_matches("https://y.test/*", "https://x.test/"));
+
+        let rules: Vec<RouteRule> = serde_json::from_value(json!([
+            { "pattern": "*/api/users*", "action": "mock", "status": 503, "body": "{\"error\":true}" },
+            { "pattern": "analytics", "action": "block" },
+        ]))
+        .unwrap_or_default();
+        assert_eq!(rules.len(), 2);
+
+        let rule = match_route(&rules, "https://x.test/api/users?id=1");
+        let (method, params) = rule
+            .map(|r| r.fetch_command("F1"))
+            .unwrap_or(("", Value::Null));
+        assert_eq!(method, "Fetch.fulfillRequest");
+        assert_eq!(params["responseCode"], json!(503));
+        assert_eq!(
+            params["responseHeaders"],
+            json!([{ "name": "Content-Type", "value": "application/json" }])
+        );
+
+        let blocked = match_route(&rules, "https://analytics.test/x").map(RouteRule::routed_by);
+        assert_eq!(blocked, Some(RoutedBy::Block));
+        assert!(match_route(&rules, "https://x.test/index.html").is_none());
+    }
+}
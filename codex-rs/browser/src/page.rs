@@ -1,4 +1,25 @@
 use crate::BrowserError;
+use crate::accessibility;
+use crate::accessibility::AccessibilitySnapshot;
+use crate::dialog::DialogLog;
+use crate::dialog::DialogOutcome;
+use crate::dialog::DialogPolicy;
+use crate::dialog::JsDialog;
+use crate::network;
+use crate::network::NetworkEntry;
+use crate::network::NetworkFilter;
+use crate::network::NetworkLog;
+use crate::network::ResponseBody;
+use crate::network::RouteRule;
+use crate::perf;
+use crate::perf::NetworkConditions;
+use crate::perf::TraceSummary;
+use crate::perf::WebVitals;
+use crate::responsive::DevicePreset;
+use crate::storage_state;
+use crate::storage_state::OriginStorage;
+use crate::storage_state::StorageState;
+use crate::storage_state::StoredCookie;
 use crate::Result;
 use crate::config::BrowserConfig;
 use crate::config::ImageFormat;
@@ -16,8 +37,19 @@ use chromiumoxide::cdp::browser_protocol::emulation::SetDeviceMetricsOverridePar
 use chromiumoxide::cdp::browser_protocol::page::AddScriptToEvaluateOnNewDocumentParams;
 use chromiumoxide::cdp::browser_protocol::page::CaptureScreenshotFormat;
 use chromiumoxide::cdp::browser_protocol::page::CaptureScreenshotParams;
+use chromiumoxide::cdp::browser_protocol::page::EventJavascriptDialogClosed;
+use chromiumoxide::cdp::browser_protocol::page::EventJavascriptDialogOpening;
+use chromiumoxide::cdp::browser_protocol::fetch::EventRequestPaused;
+use chromiumoxide::cdp::browser_protocol::network::EventLoadingFailed;
+use chromiumoxide::cdp::browser_protocol::network::EventLoadingFinished;
+use chromiumoxide::cdp::browser_protocol::network::EventRequestWillBeSent;
+use chromiumoxide::cdp::browser_protocol::network::EventResponseReceived;
+use chromiumoxide::cdp::browser_protocol::tracing::EventTracingComplete;
 use chromiumoxide::page::Page as CdpPage;
+use futures::StreamExt;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 // Use Mutex for cursor state (New)
@@ -55,6 +87,15 @@ pub struct Page {
     //   (preventing repeated long timeouts when minimized). If you change this, ensure visible windows never
     //   start with from_surface(true), and keep a short/cheap probe for hidden/minimized states.
     preflight_cache: Arc<Mutex<Option<(Instant, bool)>>>,
+    // Requests made by this page, fed by the Network.* event listeners.
+    network_log: Arc<Mutex<NetworkLog>>,
+    // Rules applied to paused requests while the Fetch domain is enabled.
+    routes: Arc<Mutex<Vec<RouteRule>>>,
+    // Whether the Web Vitals observers are registered for new documents.
+    vitals_installed: Arc<AtomicBool>,
+    // JavaScript dialogs this page opened, answered per `dialog_policy`.
+    dialogs: Arc<Mutex<DialogLog>>,
+    dialog_policy: Arc<Mutex<DialogPolicy>>,
 }
 
 impl Page {
@@ -67,16 +108,21 @@ impl Page {
             is_mouse_down: false,
         };
 
+        let dialog_policy = config.dialog_policy;
         let page = Self {
             cdp_page: Arc::new(cdp_page),
             config,
             current_url: Arc::new(RwLock::new(None)),
             cursor_state: Arc::new(Mutex::new(initial_cursor)),
             preflight_cache: Arc::new(Mutex::new(None)),
+            network_log: Arc::new(Mutex::new(NetworkLog::new())),
+            routes: Arc::new(Mutex::new(Vec::new())),
+            vitals_installed: Arc::new(AtomicBool::new(false)),
+            dialogs: Arc::new(Mutex::new(DialogLog::default())),
+            dialog_policy: Arc::new(Mutex::new(dialog_policy)),
         };
 
         // Register a unified bootstrap (runs on every new document):
-        //  - Blocks _blank/tab opens
         //  - Installs minimal virtual cursor early
         //  - Hooks SPA history to signal route changes
         let cdp_page_boot = page.cdp_page.clone();
@@ -88,9 +134,170 @@ impl Page {
             }
         });
 
+        // Record network traffic from the start so failures that happened
+        // before anyone asked are still visible.
+        let cdp_page_net = page.cdp_page.clone();
+        let network_log = page.network_log.clone();
+        let routes = page.routes.clone();
+        tokio::spawn(async move {
+            if let Err(e) = Self::run_network_capture(cdp_page_net, network_log, routes).await {
+                warn!("Network capture stopped: {}", e);
+            }
+        });
+
+        // Answer dialogs as they open; an unanswered dialog blocks the page.
+        let cdp_page_dialogs = page.cdp_page.clone();
+        let dialogs = page.dialogs.clone();
+        let dialog_policy = page.dialog_policy.clone();
+        tokio::spawn(async move {
+            if let Err(e) = Self::run_dialog_handler(cdp_page_dialogs, dialogs, dialog_policy).await
+            {
+                warn!("Dialog handler stopped: {}", e);
+            }
+        });
+
         page
     }
 
+    /// Record Page.javascriptDialogOpening events and answer them according
+    /// to the page's policy until the page goes away.
+    async fn run_dialog_handler(
+        cdp_page: Arc<CdpPage>,
+        dialogs: Arc<Mutex<DialogLog>>,
+        dialog_policy: Arc<Mutex<DialogPolicy>>,
+    ) -> Result<()> {
+        let mut opened = cdp_page
+            .event_listener::<EventJavascriptDialogOpening>()
+            .await?;
+        let mut closed = cdp_page
+            .event_listener::<EventJavascriptDialogClosed>()
+            .await?;
+        loop {
+            tokio::select! {
+                Some(event) = opened.next() => {
+                    let dialog = JsDialog {
+                        kind: event.r#type.as_ref().to_string(),
+                        message: event.message.clone(),
+                        default_prompt: event.default_prompt.clone(),
+                        url: event.url.clone(),
+                        outcome: None,
+                    };
+                    dialogs.lock().await.record(dialog);
+                    let accept = match *dialog_policy.lock().await {
+                        DialogPolicy::Accept => true,
+                        DialogPolicy::Dismiss => false,
+                        DialogPolicy::Manual => continue,
+                    };
+                    let prompt_text = event.default_prompt.clone().filter(|_| accept);
+                    let mut params = serde_json::json!({ "accept": accept });
+                    if let Some(text) = &prompt_text {
+                        params["promptText"] = text.clone().into();
+                    }
+                    match cdp_page
+                        .execute(RawCdpCommand::new("Page.handleJavaScriptDialog", params))
+                        .await
+                    {
+                        Ok(_) => {
+                            dialogs.lock().await.resolve(DialogOutcome {
+                                accepted: accept,
+                                prompt_text,
+                                automatic: true,
+                            });
+                        }
+                        Err(e) => warn!("Failed to answer dialog: {}", e),
+                    }
+                }
+                Some(event) = closed.next() => dialogs.lock().await.closed_externally(event.result),
+                else => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Feed Network.* events into the page's log and answer Fetch.requestPaused
+    /// events with the installed routing rules until the page goes away.
+    async fn run_network_capture(
+        cdp_page: Arc<CdpPage>,
+        network_log: Arc<Mutex<NetworkLog>>,
+        routes: Arc<Mutex<Vec<RouteRule>>>,
+    ) -> Result<()> {
+        enum NetEvent {
+            Request(serde_json::Value),
+            Response(serde_json::Value),
+            Finished(serde_json::Value),
+            Failed(serde_json::Value),
+            Paused(serde_json::Value),
+        }
+        fn to_json<T: serde::Serialize>(event: Arc<T>) -> serde_json::Value {
+            serde_json::to_value(&*event).unwrap_or_default()
+        }
+
+        let requests = cdp_page
+            .event_listener::<EventRequestWillBeSent>()
+            .await?
+            .map(|e| NetEvent::Request(to_json(e)));
+        let responses = cdp_page
+            .event_listener::<EventResponseReceived>()
+            .await?
+            .map(|e| NetEvent::Response(to_json(e)));
+        let finished = cdp_page
+            .event_listener::<EventLoadingFinished>()
+            .await?
+            .map(|e| NetEvent::Finished(to_json(e)));
+        let failed = cdp_page
+            .event_listener::<EventLoadingFailed>()
+            .await?
+            .map(|e| NetEvent::Failed(to_json(e)));
+        let paused = cdp_page
+            .event_listener::<EventRequestPaused>()
+            .await?
+            .map(|e| NetEvent::Paused(to_json(e)));
+        cdp_page
+            .execute(RawCdpCommand::new("Network.enable", serde_json::json!({})))
+            .await?;
+
+        let mut events = futures::stream::select_all(vec![
+            requests.boxed(),
+            responses.boxed(),
+            finished.boxed(),
+            failed.boxed(),
+            paused.boxed(),
+        ]);
+        while let Some(event) = events.next().await {
+            match event {
+                NetEvent::Request(v) => network_log.lock().await.record_request(&v),
+                NetEvent::Response(v) => network_log.lock().await.record_response(&v),
+                NetEvent::Finished(v) => network_log.lock().await.record_finished(&v),
+                NetEvent::Failed(v) => network_log.lock().await.record_failed(&v),
+                NetEvent::Paused(v) => {
+                    let request_id = v.get("requestId").and_then(|id| id.as_str()).unwrap_or_default();
+                    let url = v
+                        .get("request")
+                        .and_then(|r| r.get("url"))
+                        .and_then(|u| u.as_str())
+                        .unwrap_or_default();
+                    let rule = network::match_route(&routes.lock().await, url).cloned();
+                    let (method, params) = match &rule {
+                        Some(rule) => rule.fetch_command(request_id),
+                        None => (
+                            "Fetch.continueRequest",
+                            serde_json::json!({ "requestId": request_id }),
+                        ),
+                    };
+                    if let Err(e) = cdp_page.execute(RawCdpCommand::new(method, params)).await {
+                        warn!("{} failed for {}: {}", method, url, e);
+                    }
+                    if let (Some(rule), Some(network_id)) =
+                        (rule, v.get("networkId").and_then(|id| id.as_str()))
+                    {
+                        network_log.lock().await.mark_routed(network_id, rule.routed_by());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Ensure the virtual cursor is present; inject if missing, then update to current position.
     async fn ensure_virtual_cursor(&self) -> Result<bool> {
         // Desired runtime version of the virtual cursor script
@@ -231,52 +438,12 @@ impl Page {
     /// and early console capture so tools like `browser_console` can read logs reliably.
     async fn inject_bootstrap_script(cdp_page: &Arc<CdpPage>) -> Result<()> {
         // This script installs the full virtual cursor on DOM ready for each new document.
-        // It also prevents _blank tabs, hooks SPA history changes, and installs
-        // console/error capture early so logs accumulate from the start of the page.
+        // It also hooks SPA history changes and installs console/error capture early so
+        // logs accumulate from the start of the page. Popups and _blank links are allowed
+        // to open; BrowserManager tracks them as tabs.
         let script = r#"
 (function(){
-  // 1) Tab blocking: override window.open + intercept target="_blank"
-  try {
-    const originalOpen = window.open;
-    const openProxy = new Proxy(originalOpen, {
-      apply(_t, _this, args) {
-        const url = args[0];
-        if (url) location.href = url;
-        return null;
-      }
-    });
-    Object.defineProperty(window, 'open', { value: openProxy, writable: false, configurable: false });
-
-    const urlFrom = n => n?.href ?? n?.getAttribute?.('href') ?? n?.getAttribute?.('post-outbound-link') ?? n?.dataset?.url ?? n?.dataset?.href ?? null;
-    const intercept = e => {
-      const path = e.composedPath?.() ?? [];
-      for (const n of path) {
-        if (!n?.getAttribute) continue;
-        if (n.getAttribute('target') === '_blank') {
-          const url = urlFrom(n);
-          if (url) { e.preventDefault(); e.stopImmediatePropagation(); location.href = url; }
-          return;
-        }
-      }
-    };
-    ['pointerdown','click','auxclick'].forEach(ev => document.addEventListener(ev, intercept, { capture: true }));
-    document.addEventListener('keydown', e => {
-      if ((e.key === 'Enter' || e.key === ' ') && document.activeElement?.getAttribute?.('target') === '_blank') {
-        e.preventDefault(); const url = urlFrom(document.activeElement); if (url) location.href = url;
-      }
-    }, { capture: true });
-    document.addEventListener('submit', e => {
-      if (e.target?.target === '_blank') { e.preventDefault(); e.target.target = '_self'; e.target.submit(); }
-    }, { capture: true });
-    try {
-      const observeTarget = document.documentElement || document;
-      if (observeTarget) {
-        new MutationObserver(muts => muts.forEach(m => m.addedNodes.forEach(n => n && n.shadowRoot && ['pointerdown','click','auxclick'].forEach(ev => n.shadowRoot.addEventListener(ev, intercept, { capture: true })) ))).observe(observeTarget, { subtree: true, childList: true });
-      }
-    } catch (e) { console.warn('Tab block MO failed', e); }
-  } catch (e) { console.warn('Tab blocking failed', e); }
-
-  // 2) SPA history hooks
+  // 1) SPA history hooks
   try {
     const dispatch = () => {
       try {
@@ -293,7 +460,7 @@ impl Page {
     dispatch();
   } catch (e) { console.warn('SPA hook failed', e); }
 
-  // 3) Console capture: install once and persist for the lifetime of the document
+  // 2) Console capture: install once and persist for the lifetime of the document
   try {
     if (!window.__codex_console_logs) {
       window.__codex_console_logs = [];
@@ -345,7 +512,7 @@ impl Page {
     }
   } catch (e) { /* swallow */ }
 
-  // 4) No cursor bootstrap here; full cursor is injected by runtime ensure_virtual_cursor
+  // 3) No cursor bootstrap here; full cursor is injected by runtime ensure_virtual_cursor
 })();
 "#;
 
@@ -1293,6 +1460,18 @@ impl Page {
         Ok(())
     }
 
+    /// CDP target id of this tab.
+    pub fn target_id(&self) -> String {
+        self.cdp_page.target_id().as_ref().to_string()
+    }
+
+    /// Make this tab the visible one in its window.
+    pub async fn bring_to_front(&self) -> Result<()> {
+        self.execute_cdp_raw("Page.bringToFront", serde_json::json!({}))
+            .await?;
+        Ok(())
+    }
+
     pub async fn get_url(&self) -> Result<String> {
         let url_guard = self.current_url.read().await;
         url_guard.clone().ok_or(BrowserError::PageNotLoaded)
@@ -2023,13 +2202,13 @@ impl Page {
 
 // Raw CDP command wrapper to allow executing arbitrary methods with JSON params
 #[derive(Debug, Clone)]
-struct RawCdpCommand {
+pub(crate) struct RawCdpCommand {
     method: String,
     params: serde_json::Value,
 }
 
 impl RawCdpCommand {
-    fn new(method: impl Into<String>, params: serde_json::Value) -> Self {
+    pub(crate) fn new(method: impl Into<String>, params: serde_json::Value) -> Self {
         Self {
             method: method.into(),
             params,
@@ -2068,6 +2247,698 @@ impl Page {
         let resp = self.cdp_page.execute(cmd).await?;
         Ok(resp.result)
     }
+
+    /// Capture a compact accessibility tree of the current document, with a
+    /// ref for every node that can be targeted by `resolve_element`.
+    pub async fn snapshot(&self, max_nodes: Option<usize>) -> Result<AccessibilitySnapshot> {
+        let _ = self
+            .execute_cdp_raw("Accessibility.enable", serde_json::json!({}))
+            .await;
+        let tree = self
+            .execute_cdp_raw("Accessibility.getFullAXTree", serde_json::json!({}))
+            .await?;
+        let nodes = tree
+            .get("nodes")
+            .and_then(|n| n.as_array())
+            .ok_or_else(|| BrowserError::CdpError("getFullAXTree returned no nodes".to_string()))?;
+        Ok(accessibility::render_ax_tree(
+            nodes,
+            max_nodes.unwrap_or(accessibility::DEFAULT_MAX_NODES),
+        ))
+    }
+
+    /// Locate an element by snapshot ref or CSS selector, scroll it into
+    /// view and return the center of its border box in viewport coordinates.
+    pub async fn resolve_element(&self, target: &ElementTarget) -> Result<ResolvedElement> {
+        let backend_node_id = self.backend_node_id(target).await?;
+
+        // Refs outlive navigations in the model's context; report a stale one
+        // clearly instead of surfacing the raw CDP error.
+        let stale = |e: BrowserError| match target {
+            ElementTarget::Ref(r) => BrowserError::CdpError(format!(
+                "Element {r} is no longer in the page ({e}); take a new browser_snapshot"
+            )),
+            ElementTarget::Selector(_) => e,
+        };
+        self.execute_cdp_raw(
+            "DOM.scrollIntoViewIfNeeded",
+            serde_json::json!({ "backendNodeId": backend_node_id }),
+        )
+        .await
+        .map_err(stale)?;
+        let box_model = self
+            .execute_cdp_raw(
+                "DOM.getBoxModel",
+                serde_json::json!({ "backendNodeId": backend_node_id }),
+            )
+            .await
+            .map_err(stale)?;
+        let quad: Vec<f64> = box_model
+            .get("model")
+            .and_then(|m| m.get("border"))
+            .and_then(|q| q.as_array())
+            .map(|q| q.iter().filter_map(|v| v.as_f64()).collect())
+            .unwrap_or_default();
+        if quad.len() != 8 {
+            return Err(BrowserError::CdpError(
+                "Element has no layout box (it may be hidden)".to_string(),
+            ));
+        }
+        let x = (quad[0] + quad[2] + quad[4] + quad[6]) / 4.0;
+        let y = (quad[1] + quad[3] + quad[5] + quad[7]) / 4.0;
+
+        Ok(ResolvedElement {
+            backend_node_id,
+            x,
+            y,
+        })
+    }
+
+    /// The DOM backend node a ref or selector points at, without requiring
+    /// the element to be visible.
+    async fn backend_node_id(&self, target: &ElementTarget) -> Result<i64> {
+        Ok(match target {
+            ElementTarget::Ref(r) => accessibility::parse_element_ref(r).ok_or_else(|| {
+                BrowserError::CdpError(format!(
+                    "Invalid element ref '{r}'; expected a ref like 'e42' from browser_snapshot"
+                ))
+            })?,
+            ElementTarget::Selector(selector) => {
+                let doc = self
+                    .execute_cdp_raw("DOM.getDocument", serde_json::json!({ "depth": 0 }))
+                    .await?;
+                let root = doc
+                    .get("root")
+                    .and_then(|r| r.get("nodeId"))
+                    .and_then(|n| n.as_i64())
+                    .ok_or_else(|| BrowserError::CdpError("DOM.getDocument returned no root".to_string()))?;
+                let found = self
+                    .execute_cdp_raw(
+                        "DOM.querySelector",
+                        serde_json::json!({ "nodeId": root, "selector": selector }),
+                    )
+                    .await?;
+                let node_id = found
+                    .get("nodeId")
+                    .and_then(|n| n.as_i64())
+                    .filter(|id| *id != 0)
+                    .ok_or_else(|| {
+                        BrowserError::CdpError(format!("No element matches selector '{selector}'"))
+                    })?;
+                let described = self
+                    .execute_cdp_raw("DOM.describeNode", serde_json::json!({ "nodeId": node_id }))
+                    .await?;
+                described
+                    .get("node")
+                    .and_then(|n| n.get("backendNodeId"))
+                    .and_then(|n| n.as_i64())
+                    .ok_or_else(|| BrowserError::CdpError("DOM.describeNode returned no backendNodeId".to_string()))?
+            }
+        })
+    }
+
+    /// Builds a CSS selector that matches only the given element, preferring
+    /// test ids, ids, names and labels over a structural path so recorded
+    /// scripts survive unrelated layout changes.
+    pub async fn selector_for(&self, backend_node_id: i64) -> Result<String> {
+        let resolved = self
+            .execute_cdp_raw(
+                "DOM.resolveNode",
+                serde_json::json!({ "backendNodeId": backend_node_id }),
+            )
+            .await?;
+        let object_id = resolved
+            .get("object")
+            .and_then(|o| o.get("objectId"))
+            .and_then(|id| id.as_str())
+            .ok_or_else(|| BrowserError::CdpError("DOM.resolveNode returned no object".to_string()))?;
+        let result = self
+            .execute_cdp_raw(
+                "Runtime.callFunctionOn",
+                serde_json::json!({
+                    "objectId": object_id,
+                    "functionDeclaration": UNIQUE_SELECTOR_JS,
+                    "returnByValue": true,
+                }),
+            )
+            .await?;
+        result
+            .get("result")
+            .and_then(|r| r.get("value"))
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .ok_or_else(|| BrowserError::CdpError("Could not build a selector for the element".to_string()))
+    }
+}
+
+/// Called with `this` bound to an element; returns a selector that matches
+/// only that element.
+const UNIQUE_SELECTOR_JS: &str = r#"function() {
+  const unique = (sel) => { try { return document.querySelectorAll(sel).length === 1; } catch (e) { return false; } };
+  for (const attr of ['data-testid', 'data-test', 'data-cy', 'id', 'name', 'aria-label']) {
+    const v = this.getAttribute && this.getAttribute(attr);
+    if (!v) continue;
+    const sel = attr === 'id' ? '#' + CSS.escape(v) : this.localName + '[' + attr + '="' + v.replace(/"/g, '\\"') + '"]';
+    if (unique(sel)) return sel;
+  }
+  const parts = [];
+  for (let node = this; node && node.nodeType === 1; node = node.parentElement) {
+    let part = node.localName;
+    if (node.id && unique('#' + CSS.escape(node.id))) {
+      part = '#' + CSS.escape(node.id);
+    } else if (node.parentElement) {
+      const same = Array.from(node.parentElement.children).filter((c) => c.localName === node.localName);
+      if (same.length > 1) part += ':nth-of-type(' + (same.indexOf(node) + 1) + ')';
+    }
+    parts.unshift(part);
+    const sel = parts.join(' > ');
+    if (unique(sel)) return sel;
+  }
+  return parts.join(' > ');
+}"#;
+
+impl Page {
+    /// Register the Web Vitals observers for this and every later document.
+    /// Observers use buffered entries, so paints that already happened on
+    /// the current document are still reported.
+    pub async fn install_vitals_observer(&self) -> Result<()> {
+        if !self.vitals_installed.swap(true, Ordering::SeqCst) {
+            let params = AddScriptToEvaluateOnNewDocumentParams::new(perf::VITALS_OBSERVER_JS);
+            if let Err(e) = self.cdp_page.execute(params).await {
+                self.vitals_installed.store(false, Ordering::SeqCst);
+                return Err(e.into());
+            }
+        }
+        self.execute_javascript(perf::VITALS_OBSERVER_JS).await?;
+        Ok(())
+    }
+
+    /// Current Web Vitals; empty until `install_vitals_observer` ran.
+    pub async fn web_vitals(&self) -> Result<WebVitals> {
+        let value = self.execute_javascript(perf::READ_VITALS_JS).await?;
+        Ok(serde_json::from_value(value).unwrap_or_default())
+    }
+
+    /// Selected `Performance.getMetrics` values, keyed by metric name.
+    pub async fn performance_metrics(&self) -> Result<std::collections::BTreeMap<String, f64>> {
+        self.execute_cdp_raw("Performance.enable", serde_json::json!({}))
+            .await?;
+        let result = self
+            .execute_cdp_raw("Performance.getMetrics", serde_json::json!({}))
+            .await?;
+        Ok(result
+            .get("metrics")
+            .and_then(|m| m.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|m| {
+                let name = m.get("name")?.as_str()?;
+                if !perf::REPORTED_METRICS.contains(&name) {
+                    return None;
+                }
+                Some((name.to_string(), m.get("value")?.as_f64()?))
+            })
+            .collect())
+    }
+
+    /// Emulate a slower network and/or CPU. `None` leaves that setting as
+    /// is; `NetworkConditions::preset("none")` and a rate of 1 clear them.
+    pub async fn set_throttling(
+        &self,
+        network: Option<NetworkConditions>,
+        cpu_slowdown: Option<f64>,
+    ) -> Result<()> {
+        if let Some(conditions) = network {
+            self.execute_cdp_raw("Network.enable", serde_json::json!({}))
+                .await?;
+            self.execute_cdp_raw("Network.emulateNetworkConditions", conditions.to_cdp())
+                .await?;
+        }
+        if let Some(rate) = cpu_slowdown {
+            self.execute_cdp_raw(
+                "Emulation.setCPUThrottlingRate",
+                serde_json::json!({ "rate": rate.max(1.0) }),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Start recording a performance trace.
+    pub async fn start_trace(&self) -> Result<()> {
+        self.execute_cdp_raw(
+            "Tracing.start",
+            serde_json::json!({
+                "transferMode": "ReturnAsStream",
+                "traceConfig": {
+                    "recordMode": "recordUntilFull",
+                    "includedCategories": perf::TRACE_CATEGORIES,
+                },
+            }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Stop the trace started by `start_trace`, save it to `path` as a
+    /// Chrome trace file and summarize it.
+    pub async fn stop_trace(&self, path: &std::path::Path) -> Result<TraceSummary> {
+        let mut complete = self
+            .cdp_page
+            .event_listener::<EventTracingComplete>()
+            .await?;
+        self.execute_cdp_raw("Tracing.end", serde_json::json!({}))
+            .await?;
+        let event = tokio::time::timeout(Duration::from_secs(60), complete.next())
+            .await
+            .map_err(|_| BrowserError::CdpError("Timed out waiting for the trace".to_string()))?
+            .ok_or_else(|| BrowserError::CdpError("Trace event stream closed".to_string()))?;
+        let handle = event
+            .stream
+            .as_ref()
+            .map(|h| h.as_ref().to_string())
+            .ok_or_else(|| BrowserError::CdpError("Trace returned no stream".to_string()))?;
+
+        let mut data = Vec::new();
+        loop {
+            let chunk = self
+                .execute_cdp_raw(
+                    "IO.read",
+                    serde_json::json!({ "handle": handle, "size": 1 << 20 }),
+                )
+                .await?;
+            let text = chunk.get("data").and_then(|d| d.as_str()).unwrap_or("");
+            if chunk.get("base64Encoded").and_then(|b| b.as_bool()) == Some(true) {
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(text)
+                    .map_err(|e| BrowserError::CdpError(format!("Invalid trace chunk: {e}")))?;
+                data.extend_from_slice(&bytes);
+            } else {
+                data.extend_from_slice(text.as_bytes());
+            }
+            if chunk.get("eof").and_then(|e| e.as_bool()).unwrap_or(true) {
+                break;
+            }
+        }
+        let _ = self
+            .execute_cdp_raw("IO.close", serde_json::json!({ "handle": handle }))
+            .await;
+        std::fs::write(path, &data)?;
+
+        let trace: serde_json::Value = serde_json::from_slice(&data).unwrap_or_default();
+        let events = trace
+            .get("traceEvents")
+            .or(Some(&trace))
+            .and_then(|e| e.as_array())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        Ok(perf::summarize_trace(events))
+    }
+
+    /// Requests recorded for this page that match `filter`, oldest first.
+    pub async fn network_entries(&self, filter: &NetworkFilter) -> Vec<NetworkEntry> {
+        self.network_log.lock().await.query(filter)
+    }
+
+    /// The most recent request recorded with the given CDP request id.
+    pub async fn network_entry(&self, id: &str) -> Option<NetworkEntry> {
+        self.network_log.lock().await.get(id)
+    }
+
+    /// Forget all recorded requests.
+    pub async fn clear_network_log(&self) {
+        self.network_log.lock().await.clear();
+    }
+
+    /// Fetch a response body from Chrome. Fails once Chrome has evicted the
+    /// resource, e.g. after the page navigated away.
+    pub async fn network_response_body(&self, id: &str) -> Result<ResponseBody> {
+        let resp = self
+            .execute_cdp_raw("Network.getResponseBody", serde_json::json!({ "requestId": id }))
+            .await?;
+        Ok(ResponseBody {
+            body: resp
+                .get("body")
+                .and_then(|b| b.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            base64_encoded: resp
+                .get("base64Encoded")
+                .and_then(|b| b.as_bool())
+                .unwrap_or(false),
+        })
+    }
+
+    /// Export the requests matching `filter` as a HAR 1.2 document, embedding
+    /// response bodies that are still available when `include_bodies` is set.
+    pub async fn export_har(
+        &self,
+        filter: &NetworkFilter,
+        include_bodies: bool,
+    ) -> serde_json::Value {
+        let entries = self.network_entries(filter).await;
+        let mut bodies = std::collections::HashMap::new();
+        if include_bodies {
+            for entry in entries.iter().filter(|e| e.error.is_none() && e.status.is_some()) {
+                if let Ok(body) = self.network_response_body(&entry.id).await {
+                    bodies.insert(entry.id.clone(), body);
+                }
+            }
+        }
+        network::to_har(&entries, &bodies)
+    }
+
+    /// Install a routing rule. Rules are matched in the order they were added,
+    /// and a rule with the same pattern as an existing one replaces it.
+    pub async fn add_route(&self, rule: RouteRule) -> Result<()> {
+        let mut routes = self.routes.lock().await;
+        match routes.iter_mut().find(|r| r.pattern == rule.pattern) {
+            Some(existing) => *existing = rule,
+            None => routes.push(rule),
+        }
+        self.sync_fetch_interception(!routes.is_empty()).await
+    }
+
+    /// Remove the rule for `pattern`, or every rule when `pattern` is `None`.
+    /// Returns the number of rules removed.
+    pub async fn remove_routes(&self, pattern: Option<&str>) -> Result<usize> {
+        let mut routes = self.routes.lock().await;
+        let before = routes.len();
+        match pattern {
+            Some(pattern) => routes.retain(|r| r.pattern != pattern),
+            None => routes.clear(),
+        }
+        self.sync_fetch_interception(!routes.is_empty()).await?;
+        Ok(before - routes.len())
+    }
+
+    /// The installed routing rules, in match order.
+    pub async fn routes(&self) -> Vec<RouteRule> {
+        self.routes.lock().await.clone()
+    }
+
+    // Pausing every request costs a round trip, so the Fetch domain is only
+    // enabled while there is at least one rule to apply.
+    async fn sync_fetch_interception(&self, enabled: bool) -> Result<()> {
+        if enabled {
+            self.execute_cdp_raw(
+                "Fetch.enable",
+                serde_json::json!({ "patterns": [{ "urlPattern": "*", "requestStage": "Request" }] }),
+            )
+            .await?;
+        } else {
+            self.execute_cdp_raw("Fetch.disable", serde_json::json!({}))
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+impl Page {
+    /// Dialogs this page opened, oldest first.
+    pub async fn dialogs(&self) -> Vec<JsDialog> {
+        self.dialogs.lock().await.recent()
+    }
+
+    /// The dialog waiting for an answer, if any.
+    pub async fn pending_dialog(&self) -> Option<JsDialog> {
+        self.dialogs.lock().await.pending().cloned()
+    }
+
+    pub async fn dialog_policy(&self) -> DialogPolicy {
+        *self.dialog_policy.lock().await
+    }
+
+    pub async fn set_dialog_policy(&self, policy: DialogPolicy) {
+        *self.dialog_policy.lock().await = policy;
+    }
+
+    /// Accept or dismiss the open dialog; `prompt_text` answers a prompt.
+    pub async fn handle_dialog(&self, accept: bool, prompt_text: Option<String>) -> Result<JsDialog> {
+        if self.dialogs.lock().await.pending().is_none() {
+            return Err(BrowserError::CdpError("No JavaScript dialog is open".to_string()));
+        }
+        let mut params = serde_json::json!({ "accept": accept });
+        if let Some(text) = &prompt_text {
+            params["promptText"] = text.clone().into();
+        }
+        self.execute_cdp_raw("Page.handleJavaScriptDialog", params)
+            .await?;
+        self.dialogs
+            .lock()
+            .await
+            .resolve(DialogOutcome {
+                accepted: accept,
+                prompt_text,
+                automatic: false,
+            })
+            .ok_or_else(|| BrowserError::CdpError("The dialog was already closed".to_string()))
+    }
+
+    /// Set the files of an `<input type=file>`; the input may be hidden.
+    pub async fn set_input_files(
+        &self,
+        target: &ElementTarget,
+        files: &[std::path::PathBuf],
+    ) -> Result<()> {
+        let backend_node_id = self.backend_node_id(target).await?;
+        let files: Vec<String> = files.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+        self.execute_cdp_raw(
+            "DOM.setFileInputFiles",
+            serde_json::json!({ "files": files, "backendNodeId": backend_node_id }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Emulate `device`: its viewport and pixel ratio, touch input and,
+    /// when the preset has one, its user agent.
+    pub async fn emulate_device(&self, device: &DevicePreset) -> Result<()> {
+        self.execute_cdp_raw(
+            "Emulation.setDeviceMetricsOverride",
+            serde_json::json!({
+                "width": device.width,
+                "height": device.height,
+                "deviceScaleFactor": device.device_scale_factor,
+                "mobile": device.mobile,
+            }),
+        )
+        .await?;
+        self.execute_cdp_raw(
+            "Emulation.setTouchEmulationEnabled",
+            serde_json::json!({ "enabled": device.touch, "maxTouchPoints": 5 }),
+        )
+        .await?;
+        if let Some(user_agent) = &device.user_agent {
+            self.set_user_agent(user_agent).await?;
+        }
+        Ok(())
+    }
+
+    /// Applies the locale, timezone, geolocation and color scheme of
+    /// `config`, replacing any earlier overrides.
+    pub async fn apply_environment(&self, config: &BrowserConfig) -> Result<()> {
+        for (method, params) in crate::environment::environment_commands(config, true) {
+            self.execute_cdp_raw(method, params).await?;
+        }
+        if let Some(accept_language) = &config.accept_language {
+            match &config.user_agent {
+                Some(user_agent) => {
+                    self.execute_cdp_raw(
+                        "Network.setUserAgentOverride",
+                        serde_json::json!({ "userAgent": user_agent, "acceptLanguage": accept_language }),
+                    )
+                    .await?;
+                }
+                None => {
+                    self.execute_cdp_raw(
+                        "Network.setExtraHTTPHeaders",
+                        serde_json::json!({ "headers": { "Accept-Language": accept_language } }),
+                    )
+                    .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Undo `emulate_device`, reporting `user_agent` again.
+    pub async fn clear_device_emulation(&self, user_agent: &str) -> Result<()> {
+        self.execute_cdp_raw("Emulation.clearDeviceMetricsOverride", serde_json::json!({}))
+            .await?;
+        self.execute_cdp_raw(
+            "Emulation.setTouchEmulationEnabled",
+            serde_json::json!({ "enabled": false }),
+        )
+        .await?;
+        self.set_user_agent(user_agent).await
+    }
+
+    async fn set_user_agent(&self, user_agent: &str) -> Result<()> {
+        let mut params = serde_json::json!({ "userAgent": user_agent });
+        if let Some(accept_language) = &self.config.accept_language {
+            params["acceptLanguage"] = accept_language.clone().into();
+        }
+        self.execute_cdp_raw("Network.setUserAgentOverride", params)
+            .await?;
+        Ok(())
+    }
+
+    /// PNG of the top `width`x`height` CSS pixels at the emulated pixel
+    /// ratio. Unlike `screenshot`, the size is not clamped to the configured
+    /// viewport.
+    pub async fn capture_device_screenshot(&self, width: u32, height: u32) -> Result<Vec<u8>> {
+        let params = CaptureScreenshotParams::builder()
+            .format(CaptureScreenshotFormat::Png)
+            .clip(chromiumoxide::cdp::browser_protocol::page::Viewport {
+                x: 0.0,
+                y: 0.0,
+                width: width as f64,
+                height: height as f64,
+                scale: 1.0,
+            });
+        let resp = self.capture_screenshot_with_retry(params).await?;
+        let data_b64: &str = resp.data.as_ref();
+        base64::engine::general_purpose::STANDARD
+            .decode(data_b64.as_bytes())
+            .map_err(|e| BrowserError::ScreenshotError(format!("base64 decode failed: {}", e)))
+    }
+
+    /// Capture the cookies of `origins` plus their web storage. Storage of
+    /// the current document is read directly, so sessionStorage is only
+    /// available for the page's own origin; other origins contribute the
+    /// localStorage Chrome already holds for them.
+    pub async fn capture_storage_state(&self, origins: &[String]) -> Result<StorageState> {
+        let current = self
+            .get_url()
+            .await
+            .ok()
+            .and_then(|url| storage_state::origin_of(&url));
+        let mut wanted: Vec<String> = Vec::new();
+        for origin in current.iter().chain(origins) {
+            let origin = storage_state::origin_of(origin).unwrap_or_else(|| origin.clone());
+            if !wanted.contains(&origin) {
+                wanted.push(origin);
+            }
+        }
+
+        let all = self
+            .execute_cdp_raw("Network.getAllCookies", serde_json::json!({}))
+            .await?;
+        let cookies = all
+            .get("cookies")
+            .and_then(|c| c.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(StoredCookie::from_cdp)
+            .filter(|c| wanted.iter().any(|o| c.matches_origin(o)))
+            .collect();
+
+        let mut state = StorageState {
+            cookies,
+            origins: Vec::new(),
+        };
+        for origin in wanted {
+            let mut entry = OriginStorage {
+                origin: origin.clone(),
+                ..Default::default()
+            };
+            if current.as_deref() == Some(origin.as_str()) {
+                let dump = self.inject_js(storage_state::READ_STORAGE_JS).await?;
+                let read = |key: &str| {
+                    dump.get(key)
+                        .cloned()
+                        .and_then(|v| serde_json::from_value(v).ok())
+                        .unwrap_or_default()
+                };
+                entry.local_storage = read("local");
+                entry.session_storage = read("session");
+            } else {
+                entry.local_storage = self.dom_local_storage(&origin).await;
+            }
+            if !entry.local_storage.is_empty() || !entry.session_storage.is_empty() {
+                state.origins.push(entry);
+            }
+        }
+        Ok(state)
+    }
+
+    /// localStorage Chrome holds for an origin other than the current one;
+    /// empty when the origin has none or Chrome cannot read it.
+    async fn dom_local_storage(&self, origin: &str) -> std::collections::BTreeMap<String, String> {
+        if self
+            .execute_cdp_raw("DOMStorage.enable", serde_json::json!({}))
+            .await
+            .is_err()
+        {
+            return Default::default();
+        }
+        let items = self
+            .execute_cdp_raw(
+                "DOMStorage.getDOMStorageItems",
+                serde_json::json!({
+                    "storageId": { "securityOrigin": origin, "isLocalStorage": true }
+                }),
+            )
+            .await;
+        items
+            .ok()
+            .and_then(|r| r.get("entries").and_then(|e| e.as_array()).cloned())
+            .into_iter()
+            .flatten()
+            .filter_map(|pair| {
+                let key = pair.get(0)?.as_str()?;
+                let value = pair.get(1)?.as_str()?;
+                (key != storage_state::SEEDED_MARKER).then(|| (key.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+
+    /// Restore cookies immediately and seed web storage into the current
+    /// and every later document of a matching origin.
+    pub async fn restore_storage_state(&self, state: &StorageState) -> Result<()> {
+        if !state.cookies.is_empty() {
+            let cookies: Vec<serde_json::Value> =
+                state.cookies.iter().map(StoredCookie::to_cdp).collect();
+            self.execute_cdp_raw("Network.setCookies", serde_json::json!({ "cookies": cookies }))
+                .await?;
+        }
+        if !state.origins.is_empty() {
+            let script = state.seed_script(&uuid::Uuid::new_v4().to_string());
+            self.cdp_page
+                .execute(AddScriptToEvaluateOnNewDocumentParams::new(script.clone()))
+                .await?;
+            self.inject_js(&script).await?;
+        }
+        Ok(())
+    }
+}
+
+/// How a browser action identifies the element it acts on.
+#[derive(Debug, Clone)]
+pub enum ElementTarget {
+    /// A ref such as `e42` taken from a snapshot.
+    Ref(String),
+    /// A CSS selector; the first match is used.
+    Selector(String),
+}
+
+impl std::fmt::Display for ElementTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ElementTarget::Ref(r) => write!(f, "ref {r}"),
+            ElementTarget::Selector(s) => write!(f, "selector '{s}'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedElement {
+    pub backend_node_id: i64,
+    /// Center of the element's border box, in CSS pixels.
+    pub x: f64,
+    pub y: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,372 @@
+//! Page performance: CDP metrics, Core Web Vitals, throttling and traces.
+//!
+//! Core Web Vitals are not exposed over CDP, so a small script registers
+//! `PerformanceObserver`s for LCP, layout shifts (CLS, using the standard
+//! session windows) and event timing (INP, the slowest interaction) on every
+//! new document and keeps the values on `window`. Traces are recorded with
+//! the `Tracing` domain, streamed back through `IO.read` and saved as a
+//! Chrome trace file that DevTools and Perfetto can open; the report only
+//! carries a summary of the main thread's work.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Registers the Web Vitals observers once per document.
+pub(crate) const VITALS_OBSERVER_JS: &str = r#"(() => {
+  if (window.__codexVitals) return;
+  const v = window.__codexVitals = { lcp: null, cls: 0, inp: null, fcp: null, interactions: 0 };
+  const observe = (type, cb, opts) => {
+    try {
+      new PerformanceObserver((list) => list.getEntries().forEach(cb))
+        .observe(Object.assign({ type, buffered: true }, opts || {}));
+    } catch (e) {}
+  };
+  observe('largest-contentful-paint', (e) => { v.lcp = e.renderTime || e.loadTime || e.startTime; });
+  observe('paint', (e) => { if (e.name === 'first-contentful-paint') v.fcp = e.startTime; });
+  let session = 0, sessionStart = 0, last = 0;
+  observe('layout-shift', (e) => {
+    if (e.hadRecentInput) return;
+    if (session && (e.startTime - last > 1000 || e.startTime - sessionStart > 5000)) session = 0;
+    if (!session) sessionStart = e.startTime;
+    session += e.value;
+    last = e.startTime;
+    v.cls = Math.max(v.cls, session);
+  });
+  const interactions = new Map();
+  observe('event', (e) => {
+    if (!e.interactionId) return;
+    const d = Math.max(interactions.get(e.interactionId) || 0, e.duration);
+    interactions.set(e.interactionId, d);
+    v.interactions = interactions.size;
+    v.inp = Math.max(v.inp || 0, d);
+  }, { durationThreshold: 16 });
+})()"#;
+
+/// Reads the values collected by [`VITALS_OBSERVER_JS`] plus TTFB.
+pub(crate) const READ_VITALS_JS: &str = r#"(() => {
+  const v = window.__codexVitals;
+  if (!v) return null;
+  const nav = performance.getEntriesByType('navigation')[0];
+  return Object.assign({}, v, { ttfb: nav ? nav.responseStart : null });
+})()"#;
+
+/// Trace categories that cover main-thread tasks, rendering and loading.
+pub(crate) const TRACE_CATEGORIES: &[&str] = &[
+    "devtools.timeline",
+    "disabled-by-default-devtools.timeline",
+    "disabled-by-default-devtools.timeline.frame",
+    "toplevel",
+    "blink.user_timing",
+    "loading",
+    "latencyInfo",
+];
+
+/// Main-thread tasks longer than this block input (the "long task" limit).
+const LONG_TASK_MS: f64 = 50.0;
+
+/// Core Web Vitals and related paint timings, in milliseconds except CLS.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WebVitals {
+    pub lcp: Option<f64>,
+    pub cls: Option<f64>,
+    pub inp: Option<f64>,
+    pub fcp: Option<f64>,
+    pub ttfb: Option<f64>,
+    /// Interactions observed so far; INP is unset until there is one.
+    #[serde(default)]
+    pub interactions: u32,
+}
+
+/// Google's rating buckets for a vital.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rating {
+    Good,
+    NeedsImprovement,
+    Poor,
+}
+
+impl std::fmt::Display for Rating {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Rating::Good => "good",
+            Rating::NeedsImprovement => "needs improvement",
+            Rating::Poor => "poor",
+        })
+    }
+}
+
+/// Rates a vital by name ("lcp", "cls", "inp", "fcp", "ttfb").
+pub fn rate(metric: &str, value: f64) -> Option<Rating> {
+    let (good, poor) = match metric {
+        "lcp" => (2500.0, 4000.0),
+        "cls" => (0.1, 0.25),
+        "inp" => (200.0, 500.0),
+        "fcp" => (1800.0, 3000.0),
+        "ttfb" => (800.0, 1800.0),
+        _ => return None,
+    };
+    Some(if value <= good {
+        Rating::Good
+    } else if value <= poor {
+        Rating::NeedsImprovement
+    } else {
+        Rating::Poor
+    })
+}
+
+/// Parameters for `Network.emulateNetworkConditions`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetworkConditions {
+    pub offline: bool,
+    pub latency_ms: f64,
+    /// Kilobits per second; negative disables the limit.
+    pub download_kbps: f64,
+    pub upload_kbps: f64,
+}
+
+impl NetworkConditions {
+    /// DevTools' presets: "slow-3g", "fast-3g", "4g", "offline" and "none".
+    pub fn preset(name: &str) -> Option<Self> {
+        let (offline, latency_ms, download_kbps, upload_kbps) =
+            match name.to_ascii_lowercase().as_str() {
+                "none" | "off" => (false, 0.0, -1.0, -1.0),
+                "offline" => (true, 0.0, 0.0, 0.0),
+                "slow-3g" => (false, 2000.0, 400.0, 400.0),
+                "fast-3g" => (false, 563.0, 1474.0, 675.0),
+                "4g" => (false, 150.0, 9000.0, 9000.0),
+                _ => return None,
+            };
+        Some(Self {
+            offline,
+            latency_ms,
+            download_kbps,
+            upload_kbps,
+        })
+    }
+
+    pub(crate) fn to_cdp(self) -> Value {
+        let bytes_per_sec = |kbps: f64| {
+            if kbps < 0.0 {
+                -1.0
+            } else {
+                kbps * 1000.0 / 8.0
+            }
+        };
+        serde_json::json!({
+            "offline": self.offline,
+            "latency": self.latency_ms,
+            "downloadThroughput": bytes_per_sec(self.download_kbps),
+            "uploadThroughput": bytes_per_sec(self.upload_kbps),
+        })
+    }
+}
+
+/// What the main thread did during a trace.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TraceSummary {
+    /// Wall time covered by the trace.
+    pub duration_ms: f64,
+    /// Time spent in top-level main-thread tasks.
+    pub main_thread_busy_ms: f64,
+    /// Tasks longer than 50ms and the part of them beyond 50ms, which is
+    /// what Total Blocking Time adds up.
+    pub long_tasks: usize,
+    pub total_blocking_ms: f64,
+    /// Main-thread events by total duration, largest first. Nested events
+    /// are counted in full, so these overlap.
+    pub top_events: Vec<(String, f64)>,
+}
+
+/// Summarizes the `traceEvents` of a Chrome trace.
+pub fn summarize_trace(events: &[Value]) -> TraceSummary {
+    // The renderer main thread is the one that runs the page's tasks.
+    let main_thread = events
+        .iter()
+        .find(|e| {
+            e.get("name").and_then(Value::as_str) == Some("thread_name")
+                && e.pointer("/args/name").and_then(Value::as_str) == Some("CrRendererMain")
+        })
+        .and_then(|e| Some((e.get("pid")?.as_i64()?, e.get("tid")?.as_i64()?)));
+    let on_main = |e: &Value| match main_thread {
+        Some((pid, tid)) => {
+            e.get("pid").and_then(Value::as_i64) == Some(pid)
+                && e.get("tid").and_then(Value::as_i64) == Some(tid)
+        }
+        None => true,
+    };
+
+    let mut summary = TraceSummary::default();
+    let (mut first_ts, mut last_ts) = (f64::MAX, f64::MIN);
+    let mut by_name: HashMap<String, f64> = HashMap::new();
+    for event in events {
+        let Some(ts) = event.get("ts").and_then(Value::as_f64) else {
+            continue;
+        };
+        let dur_ms = event.get("dur").and_then(Value::as_f64).unwrap_or(0.0) / 1000.0;
+        first_ts = first_ts.min(ts);
+        last_ts = last_ts.max(ts + dur_ms * 1000.0);
+        if event.get("ph").and_then(Value::as_str) != Some("X") || !on_main(event) {
+            continue;
+        }
+        let name = event.get("name").and_then(Value::as_str).unwrap_or("");
+        if name == "RunTask" || name == "ThreadControllerImpl::RunTask" {
+            summary.main_thread_busy_ms += dur_ms;
+            if dur_ms > LONG_TASK_MS {
+                summary.long_tasks += 1;
+                summary.total_blocking_ms += dur_ms - LONG_TASK_MS;
+            }
+        } else if dur_ms > 0.0 {
+            *by_name.entry(name.to_string()).or_default() += dur_ms;
+        }
+    }
+    if first_ts <= last_ts {
+        summary.duration_ms = (last_ts - first_ts) / 1000.0;
+    }
+    let mut top: Vec<(String, f64)> = by_name.into_iter().collect();
+    top.sort_by(|a, b| b.1.total_cmp(&a.1));
+    top.truncate(8);
+    summary.top_events = top;
+    summary
+}
+
+/// Everything `browser_perf` reports for one measurement.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PerfReport {
+    pub url: String,
+    /// Selected values from `Performance.getMetrics`.
+    pub metrics: BTreeMap<String, f64>,
+    pub vitals: WebVitals,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<TraceSummary>,
+}
+
+impl PerfReport {
+    /// Multi-line, human-readable report.
+    pub fn render(&self) -> String {
+        let mut out = format!("Performance of {}\n", self.url);
+        out.push_str("Web Vitals:\n");
+        let vitals = [
+            ("lcp", "LCP", self.vitals.lcp),
+            ("fcp", "FCP", self.vitals.fcp),
+            ("ttfb", "TTFB", self.vitals.ttfb),
+            ("inp", "INP", self.vitals.inp),
+            ("cls", "CLS", self.vitals.cls),
+        ];
+        for (key, label, value) in vitals {
+            match value {
+                Some(v) if key == "cls" => {
+                    out.push_str(&format!("  {label}: {v:.3}"));
+                }
+                Some(v) => out.push_str(&format!("  {label}: {v:.0}ms")),
+                None if key == "inp" => {
+                    out.push_str(&format!("  {label}: n/a (no interactions yet)\n"));
+                    continue;
+                }
+                None => {
+                    out.push_str(&format!("  {label}: n/a\n"));
+                    continue;
+                }
+            }
+            if let Some(rating) = value.and_then(|v| rate(key, v)) {
+                out.push_str(&format!(" ({rating})"));
+            }
+            out.push('\n');
+        }
+
+        if !self.metrics.is_empty() {
+            out.push_str("Metrics:\n");
+            for (name, value) in &self.metrics {
+                out.push_str(&format!("  {name}: {}\n", format_metric(name, *value)));
+            }
+        }
+
+        if let Some(trace) = &self.trace {
+            out.push_str(&format!(
+                "Trace: {:.0}ms, main thread busy {:.0}ms, {} long task(s), total blocking time {:.0}ms\n",
+                trace.duration_ms,
+                trace.main_thread_busy_ms,
+                trace.long_tasks,
+                trace.total_blocking_ms
+            ));
+            for (name, ms) in &trace.top_events {
+                out.push_str(&format!("  {name}: {ms:.1}ms\n"));
+            }
+        }
+        out
+    }
+}
+
+/// `Performance.getMetrics` entries worth reporting. Durations are in
+/// seconds and sizes in bytes.
+pub(crate) const REPORTED_METRICS: &[&str] = &[
+    "Documents",
+    "Nodes",
+    "JSEventListeners",
+    "LayoutCount",
+    "RecalcStyleCount",
+    "LayoutDuration",
+    "RecalcStyleDuration",
+    "ScriptDuration",
+    "TaskDuration",
+    "JSHeapUsedSize",
+    "JSHeapTotalSize",
+];
+
+fn format_metric(name: &str, value: f64) -> String {
+    if name.ends_with("Duration") {
+        format!("{:.1}ms", value * 1000.0)
+    } else if name.ends_with("Size") {
+        format!("{:.1} MB", value / (1024.0 * 1024.0))
+    } else {
+        format!("{value}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rates_vitals_with_standard_thresholds() {
+        assert_eq!(rate("lcp", 2400.0), Some(Rating::Good));
+        assert_eq!(rate("lcp", 3000.0), Some(Rating::NeedsImprovement));
+        assert_eq!(rate("cls", 0.3), Some(Rating::Poor));
+        assert_eq!(rate("inp", 200.0), Some(Rating::Good));
+        assert_eq!(rate("unknown", 1.0), None);
+        assert_eq!(
+            NetworkConditions::preset("slow-3g").map(|c| c.to_cdp()["downloadThroughput"].clone()),
+            Some(json!(50000.0))
+        );
+        assert!(NetworkConditions::preset("dial-up").is_none());
+    }
+
+    #[test]
+    fn summarizes_main_thread_tasks() {
+        let events = vec![
+            json!({"name": "thread_name", "ph": "M", "pid": 1, "tid": 7, "args": {"name": "CrRendererMain"}}),
+            json!({"name": "RunTask", "ph": "X", "pid": 1, "tid": 7, "ts": 1_000_000.0, "dur": 120_000.0}),
+            json!({"name": "RunTask", "ph": "X", "pid": 1, "tid": 7, "ts": 1_200_000.0, "dur": 10_000.0}),
+            json!({"name": "FunctionCall", "ph": "X", "pid": 1, "tid": 7, "ts": 1_000_500.0, "dur": 90_000.0}),
+            json!({"name": "Layout", "ph": "X", "pid": 1, "tid": 7, "ts": 1_200_100.0, "dur": 5_000.0}),
+            // Another thread's work is not main-thread time.
+            json!({"name": "RunTask", "ph": "X", "pid": 1, "tid": 9, "ts": 1_000_000.0, "dur": 500_000.0}),
+        ];
+        let summary = summarize_trace(&events);
+        assert_eq!(summary.duration_ms, 500.0);
+        assert_eq!(summary.main_thread_busy_ms, 130.0);
+        assert_eq!(summary.long_tasks, 1);
+        assert_eq!(summary.total_blocking_ms, 70.0);
+        assert_eq!(
+            summary.top_events,
+            vec![
+                ("FunctionCall".to_string(), 90.0),
+                ("Layout".to_string(), 5.0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! Responsive screenshot sweeps.
+//!
+//! A sweep emulates each [`DevicePreset`] in turn (viewport, device pixel
+//! ratio, touch and user agent), captures the page, and restores the
+//! browser's own settings afterwards. [`compose_grid`] lays the captures out
+//! side by side with a label above each, so layout problems at different
+//! breakpoints can be compared in a single image.
+
+use std::time::Duration;
+
+use image::Rgba;
+use image::RgbaImage;
+use image::imageops::FilterType;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::BrowserError;
+use crate::Result;
+
+/// Devices captured when a sweep does not name any.
+pub const DEFAULT_DEVICES: &[&str] = &["mobile", "tablet", "desktop"];
+
+/// Time given to the page to re-layout after switching devices.
+pub const DEFAULT_SETTLE: Duration = Duration::from_millis(400);
+
+/// Height every capture is scaled to in the grid.
+const TILE_HEIGHT: u32 = 720;
+const GAP: u32 = 16;
+const LABEL_SCALE: u32 = 2;
+const LABEL_HEIGHT: u32 = 7 * LABEL_SCALE + 12;
+const BACKGROUND: Rgba<u8> = Rgba([236, 236, 236, 255]);
+const INK: Rgba<u8> = Rgba([32, 32, 32, 255]);
+
+const IPHONE_UA: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1";
+const IPAD_UA: &str = "Mozilla/5.0 (iPad; CPU OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1";
+const ANDROID_UA: &str = "Mozilla/5.0 (Linux; Android 14; Pixel 7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Mobile Safari/537.36";
+
+/// A device to emulate during a sweep.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DevicePreset {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default = "default_device_scale_factor")]
+    pub device_scale_factor: f64,
+    /// Emulate a mobile device (meta viewport, overlay scrollbars).
+    #[serde(default)]
+    pub mobile: bool,
+    #[serde(default)]
+    pub touch: bool,
+    /// User agent to report; `None` keeps the browser's own.
+    #[serde(default)]
+    pub user_agent: Option<String>,
+}
+
+fn default_device_scale_factor() -> f64 {
+    1.0
+}
+
+impl DevicePreset {
+    fn new(name: &str, width: u32, height: u32, dpr: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            width,
+            height,
+            device_scale_factor: dpr,
+            mobile: false,
+            touch: false,
+            user_agent: None,
+        }
+    }
+
+    fn handheld(mut self, user_agent: &str) -> Self {
+        self.mobile = true;
+        self.touch = true;
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Short description used for grid labels and tool output, e.g.
+    /// `mobile 390x844 @3x touch`.
+    pub fn label(&self) -> String {
+        let mut label = format!(
+            "{} {}x{} @{}x",
+            self.name, self.width, self.height, self.device_scale_factor
+        );
+        if self.touch {
+            label.push_str(" touch");
+        }
+        label
+    }
+}
+
+/// Presets available by name, in addition to any configured ones.
+pub fn builtin_presets() -> Vec<DevicePreset> {
+    vec![
+        DevicePreset::new("mobile", 390, 844, 3.0).handheld(IPHONE_UA),
+        DevicePreset::new("tablet", 820, 1180, 2.0).handheld(IPAD_UA),
+        DevicePreset::new("desktop", 1440, 900, 1.0),
+        DevicePreset::new("iphone-se", 375, 667, 2.0).handheld(IPHONE_UA),
+        DevicePreset::new("pixel-7", 412, 915, 2.625).handheld(ANDROID_UA),
+        DevicePreset::new("ipad-pro", 1024, 1366, 2.0).handheld(IPAD_UA),
+        DevicePreset::new("laptop", 1366, 768, 1.0),
+        DevicePreset::new("desktop-hd", 1920, 1080, 1.0),
+    ]
+}
+
+/// Resolves device names to presets. `custom` presets take precedence over
+/// built-in ones of the same name, and `WIDTHxHEIGHT` describes an ad-hoc
+/// desktop viewport. An empty `names` selects [`DEFAULT_DEVICES`].
+pub fn resolve_devices(names: &[String], custom: &[DevicePreset]) -> Result<Vec<DevicePreset>> {
+    let defaults: Vec<String>;
+    let names = if names.is_empty() {
+        defaults = DEFAULT_DEVICES.iter().map(|n| n.to_string()).collect();
+        &defaults
+    } else {
+        names
+    };
+    let builtin = builtin_presets();
+    names
+        .iter()
+        .map(|name| {
+            let wanted = name.trim().to_ascii_lowercase();
+            if let Some(preset) = custom
+                .iter()
+                .chain(&builtin)
+                .find(|p| p.name.eq_ignore_ascii_case(&wanted))
+            {
+                return Ok(preset.clone());
+            }
+            parse_size(&wanted)
+                .map(|(w, h)| DevicePreset::new(&wanted, w, h, 1.0))
+                .ok_or_else(|| {
+                    let mut known: Vec<&str> = Vec::new();
+                    for preset in custom.iter().chain(&builtin) {
+                        if !known.contains(&preset.name.as_str()) {
+                            known.push(&preset.name);
+                        }
+                    }
+                    BrowserError::ConfigError(format!(
+                        "unknown device '{name}'; use WIDTHxHEIGHT or one of: {}",
+                        known.join(", ")
+                    ))
+                })
+        })
+        .collect()
+}
+
+fn parse_size(s: &str) -> Option<(u32, u32)> {
+    let (w, h) = s.split_once('x')?;
+    let (w, h) = (w.parse().ok()?, h.parse().ok()?);
+    (w > 0 && h > 0).then_some((w, h))
+}
+
+/// One device's capture from a sweep.
+#[derive(Debug, Clone)]
+pub struct DeviceShot {
+    pub device: DevicePreset,
+    /// PNG at the device's full pixel resolution.
+    pub png: Vec<u8>,
+}
+
+/// Lays the captures out left to right, each scaled to the same height and
+/// labelled with its device.
+pub fn compose_grid(shots: &[DeviceShot]) -> Result<RgbaImage> {
+    let mut tiles = Vec::with_capacity(shots.len());
+    for shot in shots {
+        let image = image::load_from_memory(&shot.png)
+            .map_err(|e| BrowserError::ScreenshotError(format!("invalid capture: {e}")))?;
+        let width = (u64::from(image.width()) * u64::from(TILE_HEIGHT)
+            / u64::from(image.height().max(1)))
+        .max(1) as u32;
+        let tile = image
+            .resize_exact(width, TILE_HEIGHT, FilterType::Triangle)
+            .to_rgba8();
+        tiles.push((shot.device.label(), tile));
+    }
+
+    let width = tiles
+        .iter()
+        .map(|(label, tile)| tile.width().max(text_width(label)))
+        .sum::<u32>()
+        + GAP * (tiles.len() as u32 + 1);
+    let height = GAP * 2 + LABEL_HEIGHT + TILE_HEIGHT;
+    let mut grid = RgbaImage::from_pixel(width, height, BACKGROUND);
+    let mut x = GAP;
+    for (label, tile) in &tiles {
+        draw_text(
+            &mut grid,
+            x,
+            GAP + (LABEL_HEIGHT - 7 * LABEL_SCALE) / 2,
+            label,
+        );
+        image::imageops::overlay(&mut grid, tile, i64::from(x), i64::from(GAP + LABEL_HEIGHT));
+        x += tile.width().max(text_width(label)) + GAP;
+    }
+    Ok(grid)
+}
+
+fn text_width(text: &str) -> u32 {
+    text.chars().count() as u32 * 6 * LABEL_SCALE
+}
+
+fn draw_text(image: &mut RgbaImage, x: u32, y: u32, text: &str) {
+    for (i, c) in text.chars().enumerate() {
+        let Some(rows) = glyph(c) else { continue };
+        let left = x + i as u32 * 6 * LABEL_SCALE;
+        for (row, bits) in rows.iter().enumerate() {
+            for (col, bit) in bits.bytes().enumerate() {
+                if bit != b'#' {
+                    continue;
+                }
+                for dy in 0..LABEL_SCALE {
+                    for dx in 0..LABEL_SCALE {
+                        let px = left + col as u32 * LABEL_SCALE + dx;
+                        let py = y + row as u32 * LABEL_SCALE + dy;
+                        if px < image.width() && py < image.height() {
+                            image.put_pixel(px, py, INK);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 5x7 bitmap glyphs for labels; letters render in upper case.
+#[rustfmt::skip]
+fn glyph(c: char) -> Option<[&'static str; 7]> {
+    let rows = match c.to_ascii_uppercase() {
+        'A' => [".###.", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"],
+        'B' => ["####.", "#...#", "#...#", "####.", "#...#", "#...#", "####."],
+        'C' => [".###.", "#...#", "#....", "#....", "#....", "#...#", ".###."],
+        'D' => ["####.", "#...#", "#...#", "#...#", "#...#", "#...#", "####."],
+        'E' => ["#####", "#....", "#....", "####.", "#....", "#....", "#####"],
+        'F' => ["#####", "#....", "#....", "####.", "#....", "#....", "#...."],
+        'G' => [".###.", "#...#", "#....", "#.###", "#...#", "#...#", ".####"],
+        'H' => ["#...#", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"],
+        'I' => [".###.", "..#..", "..#..", "..#..", "..#..", "..#..", ".###."],
+        'J' => ["..###", "...#.", "...#.", "...#.", "...#.", "#..#.", ".##.."],
+        'K' => ["#...#", "#..#.", "#.#..", "##...", "#.#..", "#..#.", "#...#"],
+        'L' => ["#....", "#....", "#....", "#....", "#....", "#....", "#####"],
+        'M' => ["#...#", "##.##", "#.#.#", "#.#.#", "#...#", "#...#", "#...#"],
+        'N' => ["#...#", "#...#", "##..#", "#.#.#", "#..##", "#...#", "#...#"],
+        'O' => [".###.", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."],
+        'P' => ["####.", "#...#", "#...#", "####.", "#....", "#....", "#...."],
+        'Q' => [".###.", "#...#", "#...#", "#...#", "#.#.#", "#..#.", ".##.#"],
+        'R' => ["####.", "#...#", "#...#", "####.", "#.#..", "#..#.", "#...#"],
+        'S' => [".####", "#....", "#....", ".###.", "....#", "....#", "####."],
+        'T' => ["#####", "..#..", "..#..", "..#..", "..#..", "..#..", "..#.."],
+        'U' => ["#...#", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."],
+        'V' => ["#...#", "#...#", "#...#", "#...#", "#...#", ".#.#.", "..#.."],
+        'W' => ["#...#", "#...#", "#...#", "#.#.#", "#.#.#", "#.#.#", ".#.#."],
+        'X' => ["#...#", "#...#", ".#.#.", "..#..", ".#.#.", "#...#", "#...#"],
+        'Y' => ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#..", "..#.."],
+        'Z' => ["#####", "....#", "...#.", "..#..", ".#...", "#....", "#####"],
+        '0' => [".###.", "#...#", "#..##", "#.#.#", "##..#", "#...#", ".###."],
+        '1' => ["..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###."],
+        '2' => [".###.", "#...#", "....#", "...#.", "..#..", ".#...", "#####"],
+        '3' => ["#####", "...#.", "..#..", "...#.", "....#", "#...#", ".###."],
+        '4' => ["...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#."],
+        '5' => ["#####", "#....", "####.", "....#", "....#", "#...#", ".###."],
+        '6' => ["..##.", ".#...", "#....", "####.", "#...#", "#...#", ".###."],
+        '7' => ["#####", "....#", "...#.", "..#..", ".#...", ".#...", ".#..."],
+        '8' => [".###.", "#...#", "#...#", ".###.", "#...#", "#...#", ".###."],
+        '9' => [".###.", "#...#", "#...#", ".####", "....#", "...#.", ".##.."],
+        '-' => [".....", ".....", ".....", "#####", ".....", ".....", "....."],
+        '.' => [".....", ".....", ".....", ".....", ".....", ".##..", ".##.."],
+        '@' => [".###.", "#...#", "#.###", "#.#.#", "#.###", "#....", ".###."],
+        '_' => [".....", ".....", ".....", ".....", ".....", ".....", "#####"],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn shot(name: &str, width: u32, height: u32) -> DeviceShot {
+        let mut png = Vec::new();
+        RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        DeviceShot {
+            device: DevicePreset::new(name, width, height, 1.0),
+            png,
+        }
+    }
+
+    #[test]
+    fn resolves_defaults_custom_presets_and_sizes() {
+        let defaults = resolve_devices(&[], &[]).unwrap();
+        let names: Vec<&str> = defaults.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, DEFAULT_DEVICES);
+        assert!(defaults[0].touch && defaults[0].user_agent.is_some());
+
+        let custom = DevicePreset::new("mobile", 360, 640, 2.0);
+        let resolved = resolve_devices(
+            &["Mobile".to_string(), "800x600".to_string()],
+            &[custom.clone()],
+        )
+        .unwrap();
+        assert_eq!(resolved[0], custom);
+        assert_eq!((resolved[1].width, resolved[1].height), (800, 600));
+
+        assert!(resolve_devices(&["watch".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn grid_scales_tiles_to_a_common_height() {
+        let grid = compose_grid(&[shot("a", 200, 400), shot("b", 800, 400)]).unwrap();
+        assert_eq!(grid.height(), GAP * 2 + LABEL_HEIGHT + TILE_HEIGHT);
+        assert_eq!(grid.width(), 360 + 1440 + GAP * 3);
+    }
+}
@@ -0,0 +1,558 @@
+//! Recorded browser flows that can be replayed as regression checks.
+//!
+//! While a recording is active, every successful browser action is appended
+//! to a [`BrowserScript`] as a [`ScriptStep`]. Element refs from snapshots do
+//! not survive a reload, so clicks and typing are recorded with a CSS
+//! selector that uniquely matches the element instead. Scripts are stored as
+//! JSON under `.code/browser-scripts/` at the repository root and replayed in
+//! a fresh headless browser, where every selector step waits for its element
+//! to appear and assertions check text or selectors on the page.
+//!
+//! Text typed into a password field is never written to a script. The step
+//! names [`PASSWORD_ENV_VAR`] instead, and replay reads the text from it.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::BrowserError;
+use crate::ElementTarget;
+use crate::Result;
+use crate::config::BrowserConfig;
+use crate::manager::BrowserManager;
+
+/// Directory, relative to the repository root, that holds recorded scripts.
+pub const SCRIPTS_DIR: &str = ".code/browser-scripts";
+
+/// How long a replayed step waits for its selector or text to appear.
+pub const DEFAULT_STEP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Environment variable that replay types into password fields.
+pub const PASSWORD_ENV_VAR: &str = "BROWSER_SCRIPT_PASSWORD";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A named sequence of browser actions and assertions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BrowserScript {
+    pub name: String,
+    /// Viewport the flow was recorded at; replay uses the same size.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<(u32, u32)>,
+    pub steps: Vec<ScriptStep>,
+}
+
+/// One action or check in a [`BrowserScript`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ScriptStep {
+    Navigate {
+        url: String,
+    },
+    /// Click the element matching `selector`, or the viewport point `x`,`y`
+    /// when the click was not aimed at an element.
+    Click {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        selector: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        x: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        y: Option<f64>,
+    },
+    /// Type into the element matching `selector` (clicked first to focus it),
+    /// or into whatever has focus. With `env`, the text is read from that
+    /// environment variable on replay instead.
+    Type {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        selector: Option<String>,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        env: Option<String>,
+    },
+    Key {
+        key: String,
+    },
+    Scroll {
+        #[serde(default)]
+        dx: f64,
+        #[serde(default)]
+        dy: f64,
+    },
+    /// Wait until `selector` matches or `text` is on the page, or for `ms`.
+    Wait {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        selector: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ms: Option<u64>,
+    },
+    /// The page's visible text contains (or, with `absent`, lacks) `text`.
+    AssertText {
+        text: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        absent: bool,
+    },
+    /// An element matches (or, with `absent`, nothing matches) `selector`.
+    AssertSelector {
+        selector: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        absent: bool,
+    },
+}
+
+impl std::fmt::Display for ScriptStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptStep::Navigate { url } => write!(f, "navigate to {url}"),
+            ScriptStep::Click {
+                selector: Some(s), ..
+            } => write!(f, "click {s}"),
+            ScriptStep::Click { x, y, .. } => {
+                write!(f, "click at ({}, {})", x.unwrap_or(0.0), y.unwrap_or(0.0))
+            }
+            ScriptStep::Type {
+                selector: Some(s),
+                env: Some(var),
+                ..
+            } => write!(f, "type ${var} into {s}"),
+            ScriptStep::Type { env: Some(var), .. } => write!(f, "type ${var}"),
+            ScriptStep::Type {
+                selector: Some(s),
+                text,
+                ..
+            } => write!(f, "type {text:?} into {s}"),
+            ScriptStep::Type { text, .. } => write!(f, "type {text:?}"),
+            ScriptStep::Key { key } => write!(f, "press {key}"),
+            ScriptStep::Scroll { dx, dy } => write!(f, "scroll by ({dx}, {dy})"),
+            ScriptStep::Wait {
+                selector: Some(s), ..
+            } => write!(f, "wait for {s}"),
+            ScriptStep::Wait { text: Some(t), .. } => write!(f, "wait for text {t:?}"),
+            ScriptStep::Wait { ms, .. } => write!(f, "wait {}ms", ms.unwrap_or(0)),
+            ScriptStep::AssertText { text, absent } => {
+                let not = if *absent { "not " } else { "" };
+                write!(f, "assert text {text:?} is {not}present")
+            }
+            ScriptStep::AssertSelector { selector, absent } => {
+                let not = if *absent { "not " } else { "" };
+                write!(f, "assert {selector} is {not}present")
+            }
+        }
+    }
+}
+
+impl ScriptStep {
+    /// The step for typing `text`. Typing into a password field records a
+    /// reference to [`PASSWORD_ENV_VAR`] rather than the text itself.
+    pub fn typed(selector: Option<String>, text: &str, into_password: bool) -> Self {
+        if into_password {
+            ScriptStep::Type {
+                selector,
+                text: String::new(),
+                env: Some(PASSWORD_ENV_VAR.to_string()),
+            }
+        } else {
+            ScriptStep::Type {
+                selector,
+                text: text.to_string(),
+                env: None,
+            }
+        }
+    }
+}
+
+/// Result of replaying one step.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepOutcome {
+    pub step: ScriptStep,
+    pub elapsed_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of replaying a whole script. Replay stops at the first failing
+/// step, so `steps` may be shorter than the script.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayReport {
+    pub name: String,
+    pub total_steps: usize,
+    pub steps: Vec<StepOutcome>,
+    pub passed: bool,
+}
+
+impl ReplayReport {
+    /// Human-readable report, one line per replayed step.
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "Script '{}' {} ({} of {} steps run)\n",
+            self.name,
+            if self.passed { "passed" } else { "FAILED" },
+            self.steps.len(),
+            self.total_steps
+        );
+        for (i, outcome) in self.steps.iter().enumerate() {
+            match &outcome.error {
+                None => out.push_str(&format!(
+                    "  {}. ok   {} ({}ms)\n",
+                    i + 1,
+                    outcome.step,
+                    outcome.elapsed_ms
+                )),
+                Some(e) => out.push_str(&format!("  {}. FAIL {}: {}\n", i + 1, outcome.step, e)),
+            }
+        }
+        out
+    }
+}
+
+/// Scripts stored under one repository.
+#[derive(Debug, Clone)]
+pub struct ScriptStore {
+    dir: PathBuf,
+}
+
+impl ScriptStore {
+    /// Store rooted at the git repository containing `cwd`, or at `cwd`
+    /// itself outside a repository.
+    pub fn for_workspace(cwd: &Path) -> Self {
+        let root = cwd
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .unwrap_or(cwd);
+        Self {
+            dir: root.join(SCRIPTS_DIR),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn path_for(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", sanitize(name)))
+    }
+
+    pub fn save(&self, script: &BrowserScript) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path_for(&script.name);
+        let json = serde_json::to_string_pretty(script).map_err(|e| failure(e.to_string()))?;
+        std::fs::write(&path, json)?;
+        Ok(path)
+    }
+
+    pub fn load(&self, name: &str) -> Result<BrowserScript> {
+        let path = self.path_for(name);
+        let text = std::fs::read_to_string(&path).map_err(|e| {
+            BrowserError::IoError(std::io::Error::new(
+                e.kind(),
+                format!("failed to read script {}: {e}", path.display()),
+            ))
+        })?;
+        serde_json::from_str(&text)
+            .map_err(|e| failure(format!("invalid script {}: {e}", path.display())))
+    }
+
+    /// Names of all stored scripts, sorted.
+    pub fn list(&self) -> Result<Vec<String>> {
+        let mut names = match std::fs::read_dir(&self.dir) {
+            Ok(rd) => rd
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .filter_map(|p| Some(p.file_stem()?.to_string_lossy().into_owned()))
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        names.sort();
+        Ok(names)
+    }
+}
+
+/// Replays `script` in `manager`, stopping at the first failing step.
+pub async fn replay(
+    manager: &BrowserManager,
+    script: &BrowserScript,
+    step_timeout: Duration,
+) -> ReplayReport {
+    let mut report = ReplayReport {
+        name: script.name.clone(),
+        total_steps: script.steps.len(),
+        steps: Vec::new(),
+        passed: true,
+    };
+    for step in &script.steps {
+        let started = Instant::now();
+        let result = run_step(manager, step, step_timeout).await;
+        let error = result.err().map(|e| e.to_string());
+        let failed = error.is_some();
+        report.steps.push(StepOutcome {
+            step: step.clone(),
+            elapsed_ms: started.elapsed().as_millis(),
+            error,
+        });
+        if failed {
+            report.passed = false;
+            break;
+        }
+    }
+    report
+}
+
+/// Replays `script` in a new headless browser at the script's viewport and
+/// shuts the browser down afterwards.
+pub async fn replay_headless(
+    script: &BrowserScript,
+    step_timeout: Duration,
+) -> Result<ReplayReport> {
+    let mut config = BrowserConfig {
+        enabled: true,
+        headless: true,
+        ..Default::default()
+    };
+    if let Some((width, height)) = script.viewport {
+        config.viewport.width = width;
+        config.viewport.height = height;
+    }
+    let manager = BrowserManager::new(config);
+    manager.start().await?;
+    let report = replay(&manager, script, step_timeout).await;
+    if let Err(e) = manager.stop().await {
+        tracing::warn!("Failed to stop replay browser: {}", e);
+    }
+    Ok(report)
+}
+
+/// Runs a single step against `manager`. Used by replay and to check
+/// assertions before they are added to a recording.
+pub async fn run_step(
+    manager: &BrowserManager,
+    step: &ScriptStep,
+    timeout: Duration,
+) -> Result<()> {
+    match step {
+        ScriptStep::Navigate { url } => manager.goto(url).await.map(|_| ()),
+        ScriptStep::Click {
+            selector: Some(selector),
+            ..
+        } => {
+            let el = wait_for_element(manager, selector, timeout).await?;
+            manager.click(el.x, el.y).await
+        }
+        ScriptStep::Click { x, y, .. } => {
+            manager
+                .click(x.unwrap_or_default(), y.unwrap_or_default())
+                .await
+        }
+        ScriptStep::Type {
+            selector,
+            text,
+            env,
+        } => {
+            let text = match env {
+                Some(var) => std::env::var(var)
+                    .map_err(|_| failure(format!("environment variable {var} is not set")))?,
+                None => text.clone(),
+            };
+            if let Some(selector) = selector {
+                let el = wait_for_element(manager, selector, timeout).await?;
+                manager.click(el.x, el.y).await?;
+            }
+            manager.type_text(&text).await
+        }
+        ScriptStep::Key { key } => manager.press_key(key).await,
+        ScriptStep::Scroll { dx, dy } => manager.scroll_by(*dx, *dy).await,
+        ScriptStep::Wait { selector, text, ms } => {
+            if let Some(ms) = ms {
+                tokio::time::sleep(Duration::from_millis(*ms)).await;
+            }
+            if let Some(selector) = selector {
+                wait_for_element(manager, selector, timeout).await?;
+            }
+            if let Some(text) = text {
+                wait_until(timeout, || page_has_text(manager, text))
+                    .await
+                    .map_err(|_| failure(format!("text {text:?} did not appear")))?;
+            }
+            Ok(())
+        }
+        ScriptStep::AssertText { text, absent } => {
+            let want = !absent;
+            wait_until(timeout, || async move {
+                Ok(page_has_text(manager, text).await? == want)
+            })
+            .await
+            .map_err(|_| {
+                failure(if want {
+                    format!("text {text:?} not found on the page")
+                } else {
+                    format!("text {text:?} is still on the page")
+                })
+            })
+        }
+        ScriptStep::AssertSelector { selector, absent } => {
+            let want = !absent;
+            wait_until(timeout, || async move {
+                Ok((selector_count(manager, selector).await? > 0) == want)
+            })
+            .await
+            .map_err(|_| {
+                failure(if want {
+                    format!("no element matches {selector}")
+                } else {
+                    format!("{selector} still matches an element")
+                })
+            })
+        }
+    }
+}
+
+async fn wait_for_element(
+    manager: &BrowserManager,
+    selector: &str,
+    timeout: Duration,
+) -> Result<crate::ResolvedElement> {
+    let target = ElementTarget::Selector(selector.to_string());
+    let deadline = Instant::now() + timeout;
+    loop {
+        match manager.resolve_element(&target).await {
+            Ok(el) => return Ok(el),
+            Err(e) if Instant::now() >= deadline => {
+                return Err(failure(format!("{selector} not found: {e}")));
+            }
+            Err(_) => tokio::time::sleep(POLL_INTERVAL).await,
+        }
+    }
+}
+
+/// Polls `check` until it returns `Ok(true)` or `timeout` passes.
+async fn wait_until<F, Fut>(timeout: Duration, mut check: F) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<bool>>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Ok(true) = check().await {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(failure("timed out".to_string()));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn page_has_text(manager: &BrowserManager, text: &str) -> Result<bool> {
+    let code = format!(
+        "(document.body ? document.body.innerText : '').includes({})",
+        serde_json::Value::from(text)
+    );
+    Ok(manager.execute_javascript(&code).await?.as_bool() == Some(true))
+}
+
+/// Whether the focused element is an `input[type=password]`.
+pub async fn focused_field_is_password(manager: &BrowserManager) -> bool {
+    let code = "(() => { const el = document.activeElement; \
+                return !!el && el.tagName === 'INPUT' && el.type === 'password'; })()";
+    matches!(
+        manager.execute_javascript(code).await,
+        Ok(serde_json::Value::Bool(true))
+    )
+}
+
+async fn selector_count(manager: &BrowserManager, selector: &str) -> Result<u64> {
+    let code = format!(
+        "document.querySelectorAll({}).length",
+        serde_json::Value::from(selector)
+    );
+    Ok(manager
+        .execute_javascript(&code)
+        .await?
+        .as_u64()
+        .unwrap_or_default())
+}
+
+fn failure(message: String) -> BrowserError {
+    BrowserError::ScriptError(message)
+}
+
+/// Keeps script names usable as a file name.
+fn sanitize(name: &str) -> String {
+    let s: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if s.is_empty() {
+        "default".to_string()
+    } else {
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[expect(clippy::unwrap_used)]
+    #[test]
+    fn scripts_round_trip_through_the_store() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let store = ScriptStore::for_workspace(tmp.path());
+        let script: BrowserScript = serde_json::from_value(serde_json::json!({
+            "name": "login flow",
+            "viewport": [1024, 768],
+            "steps": [
+                { "action": "navigate", "url": "http://localhost:3000/login" },
+                { "action": "type", "selector": "input[name=\"email\"]", "text": "a@b.c" },
+                { "action": "key", "key": "Enter" },
+                { "action": "wait", "text": "Welcome" },
+                { "action": "assert_selector", "selector": ".error", "absent": true },
+            ],
+        }))
+        .unwrap();
+
+        let path = store.save(&script).unwrap();
+        assert_eq!(path, tmp.path().join(SCRIPTS_DIR).join("login_flow.json"));
+        assert_eq!(store.list().unwrap(), vec!["login_flow".to_string()]);
+        assert_eq!(store.load("login flow").unwrap(), script);
+        assert_eq!(script.steps[4].to_string(), "assert .error is not present");
+        // Defaults are omitted when saved.
+        let saved = std::fs::read_to_string(path).unwrap();
+        assert!(!saved.contains("\"x\""));
+    }
+
+    #[expect(clippy::unwrap_used)]
+    #[test]
+    fn passwords_are_recorded_as_an_env_reference() {
+        let step = ScriptStep::typed(Some("#password".to_string()), "hunter2", true);
+        let saved = serde_json::to_value(&step).unwrap();
+        assert_eq!(
+            saved,
+            serde_json::json!({
+                "action": "type",
+                "selector": "#password",
+                "env": PASSWORD_ENV_VAR,
+            })
+        );
+        assert_eq!(
+            step.to_string(),
+            format!("type ${PASSWORD_ENV_VAR} into #password")
+        );
+
+        let step = ScriptStep::typed(None, "a@b.c", false);
+        assert_eq!(step.to_string(), "type \"a@b.c\"");
+    }
+}
@@ -0,0 +1,356 @@
+//! Saved browser sessions for testing behind a login.
+//!
+//! A [`StorageState`] holds the cookies and the localStorage and
+//! sessionStorage entries of a set of origins, so a session logged in once
+//! can be restored in later sessions or by other agents without sharing the
+//! whole Chrome profile. States are named JSON files kept under the Codex
+//! home directory rather than the workspace, which keeps credentials out of
+//! the repository and the code index, and only key counts are ever reported
+//! back to the model.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::BrowserError;
+use crate::Result;
+
+/// Directory, relative to the Codex home, that holds saved states.
+pub const STATE_DIR_NAME: &str = "browser-state";
+
+/// sessionStorage key marking a document that was already seeded, so a
+/// restored state is applied once per tab instead of on every navigation.
+pub(crate) const SEEDED_MARKER: &str = "__codex_state_seeded";
+
+/// Reads both storages of the current document as `{ local, session }`.
+pub(crate) const READ_STORAGE_JS: &str = r#"(() => {
+  const dump = (s) => {
+    const out = {};
+    for (let i = 0; i < s.length; i++) {
+      const k = s.key(i);
+      if (k !== '__codex_state_seeded') out[k] = s.getItem(k);
+    }
+    return out;
+  };
+  try {
+    return { local: dump(localStorage), session: dump(sessionStorage) };
+  } catch (e) {
+    return { local: {}, session: {} };
+  }
+})()"#;
+
+/// Cookies and web storage captured from the browser.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StorageState {
+    #[serde(default)]
+    pub cookies: Vec<StoredCookie>,
+    #[serde(default)]
+    pub origins: Vec<OriginStorage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    /// Expiry in seconds since the epoch; `None` for session cookies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<f64>,
+    #[serde(default)]
+    pub http_only: bool,
+    #[serde(default)]
+    pub secure: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub same_site: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OriginStorage {
+    pub origin: String,
+    #[serde(default)]
+    pub local_storage: BTreeMap<String, String>,
+    #[serde(default)]
+    pub session_storage: BTreeMap<String, String>,
+}
+
+impl StoredCookie {
+    /// Parses a cookie as returned by `Network.getAllCookies`.
+    pub(crate) fn from_cdp(value: &serde_json::Value) -> Option<Self> {
+        let text = |key: &str| value.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        let flag = |key: &str| value.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+        let session = flag("session");
+        Some(Self {
+            name: text("name")?,
+            value: text("value")?,
+            domain: text("domain")?,
+            path: text("path").unwrap_or_else(|| "/".to_string()),
+            expires: value
+                .get("expires")
+                .and_then(|v| v.as_f64())
+                .filter(|e| !session && *e > 0.0),
+            http_only: flag("httpOnly"),
+            secure: flag("secure"),
+            same_site: text("sameSite"),
+        })
+    }
+
+    /// The `Network.CookieParam` used to restore this cookie.
+    pub(crate) fn to_cdp(&self) -> serde_json::Value {
+        let mut param = serde_json::json!({
+            "name": self.name,
+            "value": self.value,
+            "domain": self.domain,
+            "path": self.path,
+            "httpOnly": self.http_only,
+            "secure": self.secure,
+        });
+        if let Some(expires) = self.expires {
+            param["expires"] = expires.into();
+        }
+        if let Some(same_site) = &self.same_site {
+            param["sameSite"] = same_site.clone().into();
+        }
+        param
+    }
+
+    /// Whether the browser would send this cookie to `origin`.
+    pub fn matches_origin(&self, origin: &str) -> bool {
+        let Some(host) = url::Url::parse(origin)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+        else {
+            return false;
+        };
+        let domain = self.domain.trim_start_matches('.').to_ascii_lowercase();
+        host == domain || host.ends_with(&format!(".{domain}"))
+    }
+}
+
+impl StorageState {
+    /// A description of what the state holds that never includes values.
+    pub fn summary(&self) -> String {
+        let mut out = format!("{} cookie(s)", self.cookies.len());
+        for origin in &self.origins {
+            out.push_str(&format!(
+                "\n  {}: {} localStorage, {} sessionStorage key(s)",
+                origin.origin,
+                origin.local_storage.len(),
+                origin.session_storage.len()
+            ));
+        }
+        out
+    }
+
+    /// Script run in every new document that writes the saved storage for
+    /// the document's origin once per tab, tagged with `seed_id`.
+    pub(crate) fn seed_script(&self, seed_id: &str) -> String {
+        let by_origin: serde_json::Map<String, serde_json::Value> = self
+            .origins
+            .iter()
+            .map(|o| {
+                (
+                    o.origin.clone(),
+                    serde_json::json!({ "local": o.local_storage, "session": o.session_storage }),
+                )
+            })
+            .collect();
+        format!(
+            r#"(() => {{
+  const marker = {marker};
+  const seedId = {seed_id};
+  const state = {state};
+  const entry = state[location.origin];
+  if (!entry) return;
+  try {{
+    if (sessionStorage.getItem(marker) === seedId) return;
+    for (const [k, v] of Object.entries(entry.local)) localStorage.setItem(k, v);
+    for (const [k, v] of Object.entries(entry.session)) sessionStorage.setItem(k, v);
+    sessionStorage.setItem(marker, seedId);
+  }} catch (e) {{}}
+}})()"#,
+            marker = serde_json::Value::from(SEEDED_MARKER),
+            seed_id = serde_json::Value::from(seed_id),
+            state = serde_json::Value::Object(by_origin),
+        )
+    }
+}
+
+/// The origin (`scheme://host[:port]`) of `url`, if it has one.
+pub fn origin_of(url: &str) -> Option<String> {
+    let origin = url::Url::parse(url).ok()?.origin();
+    origin.is_tuple().then(|| origin.ascii_serialization())
+}
+
+/// Named storage states in a directory.
+pub struct StateStore {
+    dir: PathBuf,
+}
+
+impl StateStore {
+    /// Store under `codex_home`.
+    pub fn new(codex_home: &Path) -> Self {
+        Self {
+            dir: codex_home.join(STATE_DIR_NAME),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn path_for(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", sanitize(name)))
+    }
+
+    /// Writes `state` as `name`, readable only by the current user.
+    pub fn save(&self, name: &str, state: &StorageState) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path_for(name);
+        let json = serde_json::to_string_pretty(state)
+            .map_err(|e| BrowserError::ConfigError(e.to_string()))?;
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&path)?;
+        std::io::Write::write_all(&mut file, json.as_bytes())?;
+        Ok(path)
+    }
+
+    pub fn load(&self, name: &str) -> Result<StorageState> {
+        let path = self.path_for(name);
+        let text = std::fs::read_to_string(&path).map_err(|e| {
+            BrowserError::IoError(std::io::Error::new(
+                e.kind(),
+                format!("failed to read browser state {}: {e}", path.display()),
+            ))
+        })?;
+        serde_json::from_str(&text).map_err(|e| {
+            BrowserError::ConfigError(format!("invalid browser state {}: {e}", path.display()))
+        })
+    }
+
+    /// Removes the state `name`; returns whether it existed.
+    pub fn delete(&self, name: &str) -> Result<bool> {
+        match std::fs::remove_file(self.path_for(name)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Names of all saved states, sorted.
+    pub fn list(&self) -> Result<Vec<String>> {
+        let mut names = match std::fs::read_dir(&self.dir) {
+            Ok(rd) => rd
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .filter_map(|p| Some(p.file_stem()?.to_string_lossy().into_owned()))
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        names.sort();
+        Ok(names)
+    }
+}
+
+/// Keeps state names usable as a file name.
+fn sanitize(name: &str) -> String {
+    let s: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if s.is_empty() {
+        "default".to_string()
+    } else {
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![expect(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn cookie(domain: &str) -> StoredCookie {
+        StoredCookie {
+            name: "sid".to_string(),
+            value: "secret-token".to_string(),
+            domain: domain.to_string(),
+            path: "/".to_string(),
+            expires: None,
+            http_only: true,
+            secure: false,
+            same_site: None,
+        }
+    }
+
+    #[test]
+    fn cookies_match_their_domain_and_subdomains() {
+        assert!(cookie("example.com").matches_origin("https://example.com"));
+        assert!(cookie(".example.com").matches_origin("https://app.example.com:8443"));
+        assert!(!cookie("example.com").matches_origin("https://notexample.com"));
+        assert!(!cookie("app.example.com").matches_origin("https://example.com"));
+    }
+
+    #[test]
+    fn session_cookies_are_restored_without_expiry() {
+        let cdp = serde_json::json!({
+            "name": "sid", "value": "v", "domain": "localhost", "path": "/",
+            "expires": -1, "size": 4, "httpOnly": false, "secure": false, "session": true
+        });
+        let parsed = StoredCookie::from_cdp(&cdp).unwrap();
+        assert_eq!(parsed.expires, None);
+        assert!(parsed.to_cdp().get("expires").is_none());
+    }
+
+    #[test]
+    fn saved_states_round_trip_and_summaries_hide_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = StateStore::new(dir.path());
+        let state = StorageState {
+            cookies: vec![cookie("example.com")],
+            origins: vec![OriginStorage {
+                origin: "https://example.com".to_string(),
+                local_storage: BTreeMap::from([("token".to_string(), "secret-token".to_string())]),
+                session_storage: BTreeMap::new(),
+            }],
+        };
+
+        let path = store.save("logged in", &state).unwrap();
+        assert!(path.ends_with("browser-state/logged_in.json"));
+        assert_eq!(store.load("logged in").unwrap(), state);
+        assert_eq!(store.list().unwrap(), vec!["logged_in".to_string()]);
+        assert!(!state.summary().contains("secret-token"));
+
+        assert!(store.delete("logged in").unwrap());
+        assert!(!store.delete("logged in").unwrap());
+    }
+
+    #[test]
+    fn origins_exclude_opaque_urls() {
+        assert_eq!(
+            origin_of("http://localhost:3000/login?next=/").as_deref(),
+            Some("http://localhost:3000")
+        );
+        assert_eq!(origin_of("about:blank"), None);
+    }
+}
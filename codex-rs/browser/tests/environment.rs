@@ -0,0 +1,93 @@
+mod common;
+
+use codex_browser::BrowserConfig;
+use codex_browser::BrowserManager;
+use codex_browser::ColorScheme;
+use codex_browser::Geolocation;
+use serde_json::Value;
+use serde_json::json;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+
+use common::start_browser;
+
+const PAGE: &str = "<!doctype html><html><body>environment</body></html>";
+
+/// Reports what the page sees of its environment.
+const PROBE: &str = r#"(async () => {
+  const position = await new Promise((resolve) =>
+    navigator.geolocation.getCurrentPosition(
+      (p) => resolve([p.coords.latitude, p.coords.longitude]),
+      () => resolve(null),
+      { timeout: 2000 }
+    )
+  );
+  return {
+    locale: Intl.DateTimeFormat().resolvedOptions().locale,
+    timeZone: Intl.DateTimeFormat().resolvedOptions().timeZone,
+    dark: matchMedia('(prefers-color-scheme: dark)').matches,
+    position,
+  };
+})()"#;
+
+async fn probe(manager: &BrowserManager) -> Value {
+    let result = manager
+        .execute_cdp(
+            "Runtime.evaluate",
+            json!({ "expression": PROBE, "awaitPromise": true, "returnByValue": true }),
+        )
+        .await
+        .expect("evaluate probe");
+    result["result"]["value"].clone()
+}
+
+#[expect(clippy::unwrap_used)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn configured_environment_reaches_pages_and_can_change() {
+    let Some(manager) = start_browser(Some(BrowserConfig {
+        enabled: true,
+        headless: true,
+        locale: Some("de-DE".to_string()),
+        timezone: Some("Europe/Berlin".to_string()),
+        accept_language: Some("de-DE,de;q=0.9".to_string()),
+        geolocation: Geolocation::new(52.52, 13.405, None),
+        color_scheme: Some(ColorScheme::Dark),
+        ..Default::default()
+    }))
+    .await
+    else {
+        return;
+    };
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(PAGE, "text/html"))
+        .mount(&server)
+        .await;
+
+    manager.goto(&server.uri()).await.unwrap();
+    let seen = probe(&manager).await;
+    assert_eq!(seen["locale"], "de-DE", "{seen}");
+    assert_eq!(seen["timeZone"], "Europe/Berlin", "{seen}");
+    assert_eq!(seen["dark"], true, "{seen}");
+    assert_eq!(seen["position"], json!([52.52, 13.405]), "{seen}");
+
+    manager
+        .update_environment(|config| {
+            config.locale = Some("en-US".to_string());
+            config.timezone = Some("America/New_York".to_string());
+            config.color_scheme = Some(ColorScheme::Light);
+            config.geolocation = Geolocation::new(40.7128, -74.006, None);
+        })
+        .await
+        .unwrap();
+    manager.goto(&server.uri()).await.unwrap();
+    let seen = probe(&manager).await;
+    manager.stop().await.unwrap();
+
+    assert_eq!(seen["locale"], "en-US", "{seen}");
+    assert_eq!(seen["timeZone"], "America/New_York", "{seen}");
+    assert_eq!(seen["dark"], false, "{seen}");
+    assert_eq!(seen["position"], json!([40.7128, -74.006]), "{seen}");
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use std::time::Duration;
+
+use codex_browser::BrowserManager;
+use codex_browser::NetworkFilter;
+use codex_browser::RouteRule;
+use codex_browser::network::NetworkEntry;
+use codex_browser::network::RoutedBy;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+use common::start_browser;
+
+const PAGE: &str = r#"<!doctype html><html><body>
+<script>
+  fetch('/api/ok');
+  fetch('/api/broken');
+</script>
+</body></html>"#;
+
+/// Polls the page's network log until `done` holds for the entries matching `filter`.
+async fn wait_for(
+    manager: &BrowserManager,
+    filter: &NetworkFilter,
+    done: impl Fn(&[NetworkEntry]) -> bool,
+) -> Vec<NetworkEntry> {
+    let mut entries = Vec::new();
+    for _ in 0..50 {
+        entries = manager.network_entries(filter).await.unwrap_or_default();
+        if done(&entries) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    entries
+}
+
+async fn start_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(PAGE, "text/html"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/ok"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(r#"{"ok":true}"#, "application/json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/broken"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("boom"))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[expect(clippy::unwrap_used)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn records_failed_requests_and_exports_har() {
+    let Some(manager) = start_browser(None).await else {
+        return;
+    };
+    let server = start_server().await;
+
+    manager.goto(&server.uri()).await.unwrap();
+    let failed = NetworkFilter {
+        failed_only: true,
+        ..Default::default()
+    };
+    let entries = wait_for(&manager, &failed, |e| !e.is_empty()).await;
+    assert_eq!(entries.len(), 1, "{entries:?}");
+    assert!(entries[0].url.ends_with("/api/broken"));
+    assert_eq!(entries[0].status, Some(500));
+
+    let body = manager.network_response_body(&entries[0].id).await.unwrap();
+    assert_eq!(body.body, "boom");
+
+    let api = NetworkFilter {
+        url: Some("/api/".to_string()),
+        ..Default::default()
+    };
+    let har = manager.export_har(&api, true).await.unwrap();
+    let har_entries = har["log"]["entries"].as_array().unwrap();
+    assert_eq!(har_entries.len(), 2);
+    assert!(
+        har_entries
+            .iter()
+            .any(|e| e["response"]["content"]["text"] == r#"{"ok":true}"#)
+    );
+
+    manager.stop().await.unwrap();
+}
+
+#[expect(clippy::unwrap_used)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn routes_mock_and_block_requests() {
+    let Some(manager) = start_browser(None).await else {
+        return;
+    };
+    let server = start_server().await;
+
+    let rules: Vec<RouteRule> = serde_json::from_value(serde_json::json!([
+        { "pattern": "*/api/broken", "action": "mock", "status": 200, "body": "{\"mocked\":true}" },
+        { "pattern": "/api/ok", "action": "block" },
+    ]))
+    .unwrap();
+    for rule in rules {
+        manager.add_route(rule).await.unwrap();
+    }
+
+    manager.goto(&server.uri()).await.unwrap();
+    let api = NetworkFilter {
+        url: Some("/api/".to_string()),
+        ..Default::default()
+    };
+    let entries = wait_for(&manager, &api, |e| {
+        e.len() == 2
+            && e.iter()
+                .all(|e| e.routed.is_some() && (e.status.is_some() || e.error.is_some()))
+    })
+    .await;
+
+    let broken = entries
+        .iter()
+        .find(|e| e.url.ends_with("/api/broken"))
+        .unwrap();
+    assert_eq!(
+        (broken.status, broken.routed),
+        (Some(200), Some(RoutedBy::Mock))
+    );
+    let ok = entries.iter().find(|e| e.url.ends_with("/api/ok")).unwrap();
+    assert!(ok.error.is_some(), "{ok:?}");
+    assert_eq!(ok.routed, Some(RoutedBy::Block));
+
+    // Only the page itself reached the server.
+    let received = server.received_requests().await.unwrap();
+    assert!(received.iter().all(|r| !r.url.path().starts_with("/api/")));
+
+    assert_eq!(manager.remove_routes(None).await.unwrap(), 2);
+    manager.stop().await.unwrap();
+}
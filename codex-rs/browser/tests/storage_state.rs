@@ -0,0 +1,59 @@
+mod common;
+
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+use common::start_browser;
+
+const LOGIN: &str = r#"<!doctype html><html><body><script>
+document.cookie = "sid=abc123; path=/";
+localStorage.setItem("token", "t-1");
+sessionStorage.setItem("tab", "home");
+</script></body></html>"#;
+
+const BLANK: &str = "<!doctype html><html><body>app</body></html>";
+
+#[expect(clippy::unwrap_used)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn saved_login_is_restored_in_a_fresh_browser() {
+    let Some(first) = start_browser(None).await else {
+        return;
+    };
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/login"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(LOGIN, "text/html"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/app"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(BLANK, "text/html"))
+        .mount(&server)
+        .await;
+
+    first
+        .goto(&format!("{}/login", server.uri()))
+        .await
+        .unwrap();
+    let saved = first.save_storage_state(&[]).await.unwrap();
+    first.stop().await.unwrap();
+
+    assert_eq!(saved.cookies.len(), 1, "{}", saved.summary());
+    assert_eq!(saved.origins.len(), 1, "{}", saved.summary());
+    assert_eq!(saved.origins[0].origin, server.uri());
+    assert_eq!(saved.origins[0].local_storage["token"], "t-1");
+    assert_eq!(saved.origins[0].session_storage["tab"], "home");
+
+    let Some(second) = start_browser(None).await else {
+        return;
+    };
+    second.restore_storage_state(&saved).await.unwrap();
+    second.goto(&format!("{}/app", server.uri())).await.unwrap();
+    let restored = second.save_storage_state(&[]).await.unwrap();
+    second.stop().await.unwrap();
+
+    assert_eq!(restored, saved);
+}
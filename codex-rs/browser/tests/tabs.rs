@@ -0,0 +1,82 @@
+mod common;
+
+use std::time::Duration;
+
+use codex_browser::BrowserManager;
+use codex_browser::TabInfo;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+use common::start_browser;
+
+const OPENER: &str = r#"<!doctype html><html><body style="margin:0">
+<a href="/popup" target="_blank" style="display:block;width:200px;height:200px">open</a>
+</body></html>"#;
+
+const POPUP: &str =
+    "<!doctype html><html><head><title>Popup</title></head><body>popup</body></html>";
+
+/// Polls the tab list until `done` holds.
+async fn wait_for_tabs(
+    manager: &BrowserManager,
+    done: impl Fn(&[TabInfo]) -> bool,
+) -> Vec<TabInfo> {
+    let mut tabs = Vec::new();
+    for _ in 0..50 {
+        tabs = manager.list_tabs().await.unwrap_or_default();
+        if done(&tabs) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    tabs
+}
+
+#[expect(clippy::unwrap_used)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn follows_popups_and_falls_back_to_opener_on_close() {
+    let Some(manager) = start_browser(None).await else {
+        return;
+    };
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(OPENER, "text/html"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/popup"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(POPUP, "text/html"))
+        .mount(&server)
+        .await;
+
+    manager.goto(&server.uri()).await.unwrap();
+    let opener = manager.active_tab().await.unwrap();
+    assert_eq!((opener.index, opener.count), (1, 1));
+
+    manager.click(50.0, 50.0).await.unwrap();
+    let tabs = wait_for_tabs(&manager, |tabs| {
+        tabs.iter().any(|t| t.active && t.url.ends_with("/popup"))
+    })
+    .await;
+    let popup = tabs.iter().find(|t| t.active).unwrap();
+    assert_eq!(
+        popup.opener_id.as_deref(),
+        Some(opener.id.as_str()),
+        "{tabs:?}"
+    );
+    let active = manager.active_tab().await.unwrap();
+    assert_eq!((active.index, active.count), (2, 2));
+
+    let after_close = manager.close_tab(None).await.unwrap().unwrap();
+    assert_eq!(after_close.id, opener.id);
+    assert_eq!(manager.active_tab().await.unwrap().count, 1);
+
+    let switched = manager.switch_tab(&opener.id).await.unwrap();
+    assert!(switched.active);
+
+    manager.stop().await.unwrap();
+}
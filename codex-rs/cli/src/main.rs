@@ -80,6 +80,12 @@ enum Subcommand {
 
     /// Memory utilities (semantic compression helpers).
     Memory(MemoryCommand),
+
+    /// Manage visual regression baselines saved by browser_screenshot.
+    Baselines(BaselinesCommand),
+
+    /// Replay browser flows recorded with browser_script.
+    BrowserScript(BrowserScriptCommand),
 }
 
 #[derive(Debug, Parser)]
@@ -167,6 +173,52 @@ struct MemoryLoginCommand {
 #[derive(Debug, Parser)]
 struct MemoryReindexCommand {}
 
+#[derive(Debug, Parser)]
+struct BaselinesCommand {
+    #[command(subcommand)]
+    action: BaselinesSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum BaselinesSubcommand {
+    /// List saved baselines and whether a failed comparison is pending.
+    List,
+
+    /// Replace baselines with the screenshots from their last failed comparison.
+    Accept {
+        /// Only accept baselines saved under this name.
+        name: Option<String>,
+    },
+
+    /// Delete baselines so the next screenshot can be saved fresh.
+    Reset {
+        /// Only delete baselines saved under this name.
+        name: Option<String>,
+    },
+}
+
+#[derive(Debug, Parser)]
+struct BrowserScriptCommand {
+    #[command(subcommand)]
+    action: BrowserScriptSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum BrowserScriptSubcommand {
+    /// List recorded scripts.
+    List,
+
+    /// Replay scripts in a headless browser; exits non-zero if any fails.
+    Replay {
+        /// Scripts to replay (default: all).
+        names: Vec<String>,
+
+        /// Seconds each step waits for its selector or text.
+        #[arg(long = "timeout", value_name = "SECS", default_value_t = 10)]
+        timeout_secs: u64,
+    },
+}
+
 fn main() -> anyhow::Result<()> {
     arg0_dispatch_or_else(|codex_linux_sandbox_exe| async move {
         cli_main(codex_linux_sandbox_exe).await?;
@@ -255,6 +307,12 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                 }
             }
         }
+        Some(Subcommand::Baselines(baselines_cli)) => {
+            run_baselines(baselines_cli.action)?;
+        }
+        Some(Subcommand::BrowserScript(script_cli)) => {
+            run_browser_script(script_cli.action).await?;
+        }
     }
 
     Ok(())
@@ -324,6 +382,85 @@ async fn memory_reindex(cli_config_overrides: CliConfigOverrides) -> anyhow::Res
     Ok(())
 }
 
+async fn run_browser_script(action: BrowserScriptSubcommand) -> anyhow::Result<()> {
+    use codex_browser::script::ScriptStore;
+
+    let store = ScriptStore::for_workspace(&std::env::current_dir()?);
+    match action {
+        BrowserScriptSubcommand::List => {
+            let names = store.list()?;
+            if names.is_empty() {
+                println!("No scripts in {}", store.dir().display());
+            }
+            for name in names {
+                println!("{name}");
+            }
+        }
+        BrowserScriptSubcommand::Replay {
+            names,
+            timeout_secs,
+        } => {
+            let names = if names.is_empty() { store.list()? } else { names };
+            let mut failed = 0;
+            for name in &names {
+                let script = store.load(name)?;
+                let report = codex_browser::script::replay_headless(
+                    &script,
+                    std::time::Duration::from_secs(timeout_secs),
+                )
+                .await?;
+                print!("{}", report.summary());
+                if !report.passed {
+                    failed += 1;
+                }
+            }
+            if failed > 0 {
+                anyhow::bail!("{failed} of {} script(s) failed", names.len());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_baselines(action: BaselinesSubcommand) -> anyhow::Result<()> {
+    use codex_core::visual_baseline::BaselineStore;
+
+    let store = BaselineStore::for_workspace(&std::env::current_dir()?);
+    match action {
+        BaselinesSubcommand::List => {
+            let entries = store.list(None)?;
+            if entries.is_empty() {
+                println!("No baselines in {}", store.dir().display());
+            }
+            for e in entries {
+                println!(
+                    "{}\t{}x{}\t{}{}",
+                    e.key.name,
+                    e.key.width,
+                    e.key.height,
+                    e.key.url,
+                    if e.pending { "\t(pending)" } else { "" }
+                );
+            }
+        }
+        BaselinesSubcommand::Accept { name } => {
+            let accepted = store.accept(name.as_deref())?;
+            for e in &accepted {
+                println!(
+                    "Accepted {} {} at {}x{}",
+                    e.key.name, e.key.url, e.key.width, e.key.height
+                );
+            }
+            println!("{} baseline(s) updated", accepted.len());
+        }
+        BaselinesSubcommand::Reset { name } => {
+            let removed = store.reset(name.as_deref())?;
+            println!("{removed} baseline(s) removed");
+        }
+    }
+    Ok(())
+}
+
 fn prompt_secret(prompt: &str) -> std::io::Result<String> {
     use std::io::{self, Write};
     print!("{}", prompt);
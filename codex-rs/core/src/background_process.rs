@@ -0,0 +1,762 @@
+//! Long-running commands started by the `background_process` tool.
+//!
+//! Regular `shell` calls block the turn until the command exits or times
+//! out, which rules out dev servers and file watchers. A background process
+//! is spawned under the same sandbox as a `shell` call but returns
+//! immediately; its stdout and stderr are collected into a bounded log that
+//! can be tailed or searched later, and the model can wait for it to start
+//! listening on a port or to print a line matching a pattern.
+//!
+//! Processes belong to the Codex session that started them: every process
+//! group still running when the [`BackgroundProcessManager`] is dropped is
+//! killed.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use async_channel::Sender;
+use serde::Deserialize;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncRead;
+use tokio::io::BufReader;
+use tokio::sync::Notify;
+
+use crate::exec::MAX_STREAM_OUTPUT;
+use crate::exec::SandboxType;
+use crate::exec_snapshot::OverlayMount;
+use crate::openai_tools::JsonSchema;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::ResponsesApiTool;
+use crate::protocol::BackgroundProcessInfo;
+use crate::protocol::BackgroundProcessUpdateEvent;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::SandboxPolicy;
+
+pub const DEFAULT_WAIT_TIMEOUT_MS: u64 = 60_000;
+pub const DEFAULT_TAIL_LINES: usize = 50;
+
+/// Upper bound on concurrently running processes per Codex session.
+const MAX_RUNNING: usize = 8;
+
+/// Exited processes kept around so their final output can still be read;
+/// the oldest ones are forgotten beyond this.
+const MAX_EXITED: usize = 16;
+
+/// Lines retained per process; older lines are dropped.
+const MAX_LOG_LINES: usize = 2_000;
+
+/// Long lines (minified bundles, progress bars) are cut to this many bytes.
+const MAX_LINE_BYTES: usize = 2_048;
+
+/// Time between SIGTERM and SIGKILL when stopping a process.
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+const PORT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackgroundProcessToolCallParams {
+    pub action: String,
+    #[serde(default, alias = "id")]
+    pub process_id: Option<String>,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub workdir: Option<String>,
+    #[serde(default)]
+    pub lines: Option<usize>,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub host: Option<String>,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default, alias = "timeout")]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub with_escalated_permissions: Option<bool>,
+    #[serde(default)]
+    pub justification: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessState {
+    Running,
+    Exited(i32),
+    /// Stopped through the tool (or killed by a signal).
+    Stopped,
+}
+
+impl ProcessState {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ProcessState::Running => "running",
+            ProcessState::Exited(_) => "exited",
+            ProcessState::Stopped => "stopped",
+        }
+    }
+}
+
+#[derive(Default)]
+struct ProcessLog {
+    lines: VecDeque<String>,
+    /// Total number of lines ever received, including dropped ones.
+    total: u64,
+}
+
+impl ProcessLog {
+    fn push(&mut self, stream: &str, line: &str) {
+        let mut line = line.trim_end_matches(['\r', '\n']).to_string();
+        if line.len() > MAX_LINE_BYTES {
+            let mut cut = MAX_LINE_BYTES;
+            while !line.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            line.truncate(cut);
+            line.push_str(" [...]");
+        }
+        if stream == "stderr" {
+            line.insert_str(0, "[stderr] ");
+        }
+        if self.lines.len() == MAX_LOG_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+        self.total += 1;
+    }
+
+    /// Index (in `total` numbering) of the oldest retained line.
+    fn first_index(&self) -> u64 {
+        self.total - self.lines.len() as u64
+    }
+}
+
+pub struct BackgroundProcess {
+    pub id: String,
+    pub command: String,
+    pub cwd: PathBuf,
+    pub pid: Option<u32>,
+    pub started: Instant,
+    log: Mutex<ProcessLog>,
+    state: Mutex<ProcessState>,
+    notify: Notify,
+    /// Exec snapshot overlay the process runs on, until the session has
+    /// recorded what it changed.
+    overlay: Mutex<Option<OverlayMount>>,
+}
+
+impl BackgroundProcess {
+    pub fn state(&self) -> ProcessState {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state() == ProcessState::Running
+    }
+
+    /// Takes the exec snapshot overlay the process ran on, if any.
+    pub fn take_overlay(&self) -> Option<OverlayMount> {
+        self.overlay
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+    }
+
+    /// Returns the last `lines` lines of output.
+    pub fn tail(&self, lines: usize) -> String {
+        let log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        let skip = log.lines.len().saturating_sub(lines);
+        cap_output(log.lines.iter().skip(skip))
+    }
+
+    pub fn info(&self) -> BackgroundProcessInfo {
+        let state = self.state();
+        BackgroundProcessInfo {
+            id: self.id.clone(),
+            command: self.command.clone(),
+            pid: self.pid,
+            status: state.as_str().to_string(),
+            exit_code: match state {
+                ProcessState::Exited(code) => Some(code),
+                _ => None,
+            },
+        }
+    }
+
+    /// Waits until a line printed after `from` matches `pattern`. Returns the
+    /// matching line, or `None` if the process exits or `timeout` elapses
+    /// first.
+    pub async fn wait_for_pattern(
+        &self,
+        pattern: &regex_lite::Regex,
+        from: u64,
+        timeout: Duration,
+    ) -> Option<String> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut next = from;
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            // Read the state before the log: readers are drained before the
+            // state changes, so a stopped process has nothing left to print.
+            let running = self.is_running();
+            {
+                let log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+                let start = next.max(log.first_index());
+                let skip = (start - log.first_index()) as usize;
+                if let Some(line) = log.lines.iter().skip(skip).find(|l| pattern.is_match(l)) {
+                    return Some(line.clone());
+                }
+                next = log.total;
+            }
+            if !running {
+                return None;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return None;
+            }
+        }
+    }
+
+    /// Waits until something accepts TCP connections on `host:port`. Returns
+    /// `false` if the process exits or `timeout` elapses first.
+    pub async fn wait_for_port(&self, host: &str, port: u16, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let attempt = tokio::time::timeout(
+                PORT_POLL_INTERVAL,
+                tokio::net::TcpStream::connect((host, port)),
+            )
+            .await;
+            if matches!(attempt, Ok(Ok(_))) {
+                return true;
+            }
+            if !self.is_running() || tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(PORT_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Number of lines received so far; pass to
+    /// [`BackgroundProcess::wait_for_pattern`] to only match new output.
+    pub fn line_count(&self) -> u64 {
+        self.log.lock().unwrap_or_else(|e| e.into_inner()).total
+    }
+
+    /// Sends SIGTERM to the process group, then SIGKILL if it has not exited
+    /// after a grace period.
+    pub async fn stop(&self) {
+        if !self.is_running() {
+            return;
+        }
+        self.signal(Signal::Terminate);
+        let deadline = tokio::time::Instant::now() + STOP_GRACE_PERIOD;
+        while self.is_running() && tokio::time::Instant::now() < deadline {
+            let notified = self.notify.notified();
+            let _ = tokio::time::timeout_at(deadline, notified).await;
+        }
+        if self.is_running() {
+            self.signal(Signal::Kill);
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if *state == ProcessState::Running {
+            *state = ProcessState::Stopped;
+        }
+    }
+
+    #[cfg(unix)]
+    fn signal(&self, signal: Signal) {
+        let Some(pid) = self.pid else {
+            return;
+        };
+        let sig = match signal {
+            Signal::Terminate => libc::SIGTERM,
+            Signal::Kill => libc::SIGKILL,
+        };
+        // The child leads its own process group (see `StdioPolicy::Background`),
+        // so this reaches everything it spawned as well.
+        // SAFETY: kill(2) has no memory-safety preconditions.
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), sig);
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn signal(&self, _signal: Signal) {}
+}
+
+#[derive(Clone, Copy)]
+enum Signal {
+    Terminate,
+    Kill,
+}
+
+/// Background processes owned by a single Codex session.
+pub struct BackgroundProcessManager {
+    processes: Mutex<HashMap<String, Arc<BackgroundProcess>>>,
+    next_id: AtomicU64,
+    tx_event: Sender<Event>,
+}
+
+impl BackgroundProcessManager {
+    pub fn new(tx_event: Sender<Event>) -> Self {
+        Self {
+            processes: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+            tx_event,
+        }
+    }
+
+    /// Spawns `command` with `bash -lc` and starts collecting its output.
+    /// `sub_id` is used as the event id for status updates about the
+    /// process.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn start(
+        self: &Arc<Self>,
+        command: String,
+        cwd: PathBuf,
+        env: HashMap<String, String>,
+        sandbox_type: SandboxType,
+        sandbox_policy: &SandboxPolicy,
+        codex_linux_sandbox_exe: &Option<PathBuf>,
+        sub_id: String,
+        overlay: Option<OverlayMount>,
+    ) -> io::Result<Arc<BackgroundProcess>> {
+        use crate::landlock::spawn_command_under_linux_sandbox_in_overlay;
+        use crate::seatbelt::spawn_command_under_seatbelt;
+        use crate::spawn::StdioPolicy;
+        use crate::spawn::spawn_child_async;
+
+        let running = self
+            .list()
+            .iter()
+            .filter(|process| process.is_running())
+            .count();
+        if running >= MAX_RUNNING {
+            return Err(io::Error::other(format!(
+                "too many background processes (max {MAX_RUNNING}); stop one first"
+            )));
+        }
+
+        let argv = vec!["bash".to_string(), "-lc".to_string(), command.clone()];
+        let stdio_policy = StdioPolicy::Background;
+        let mut child = match sandbox_type {
+            SandboxType::None => {
+                let (program, args) = argv
+                    .split_first()
+                    .ok_or_else(|| io::Error::other("empty command"))?;
+                spawn_child_async(
+                    PathBuf::from(program),
+                    args.to_vec(),
+                    None,
+                    cwd.clone(),
+                    sandbox_policy,
+                    stdio_policy,
+                    env,
+                    overlay.as_ref(),
+                )
+                .await?
+            }
+            SandboxType::MacosSeatbelt => {
+                spawn_command_under_seatbelt(argv, sandbox_policy, cwd.clone(), stdio_policy, env)
+                    .await?
+            }
+            SandboxType::LinuxSeccomp => {
+                let exe = codex_linux_sandbox_exe.as_ref().ok_or_else(|| {
+                    io::Error::other("codex-linux-sandbox executable not provided")
+                })?;
+                spawn_command_under_linux_sandbox_in_overlay(
+                    exe,
+                    argv,
+                    sandbox_policy,
+                    cwd.clone(),
+                    stdio_policy,
+                    env,
+                    overlay.as_ref(),
+                    None,
+                )
+                .await?
+            }
+        };
+
+        let id = format!("bg-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let process = Arc::new(BackgroundProcess {
+            id: id.clone(),
+            command,
+            cwd,
+            pid: child.id(),
+            started: Instant::now(),
+            log: Mutex::new(ProcessLog::default()),
+            state: Mutex::new(ProcessState::Running),
+            notify: Notify::new(),
+            overlay: Mutex::new(overlay),
+        });
+
+        let stdout = child
+            .stdout
+            .take()
+            .map(|out| tokio::spawn(read_lines(out, "stdout", process.clone())));
+        let stderr = child
+            .stderr
+            .take()
+            .map(|err| tokio::spawn(read_lines(err, "stderr", process.clone())));
+
+        {
+            let mut processes = self.processes.lock().unwrap_or_else(|e| e.into_inner());
+            evict_exited(&mut processes, MAX_EXITED);
+            processes.insert(id, process.clone());
+        }
+
+        let manager = Arc::downgrade(self);
+        let waiter = process.clone();
+        tokio::spawn(async move {
+            let status = child.wait().await;
+            // Drain the pipes before announcing the exit so a final `tail`
+            // sees every line.
+            for reader in [stdout, stderr].into_iter().flatten() {
+                let _ = reader.await;
+            }
+            {
+                let mut state = waiter.state.lock().unwrap_or_else(|e| e.into_inner());
+                if *state == ProcessState::Running {
+                    *state = match status.ok().and_then(|s| s.code()) {
+                        Some(code) => ProcessState::Exited(code),
+                        None => ProcessState::Stopped,
+                    };
+                }
+            }
+            waiter.notify.notify_waiters();
+            if let Some(manager) = manager.upgrade() {
+                manager.send_update(sub_id).await;
+            }
+        });
+
+        Ok(process)
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<BackgroundProcess>> {
+        self.processes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(id)
+            .cloned()
+    }
+
+    pub fn list(&self) -> Vec<Arc<BackgroundProcess>> {
+        let mut processes: Vec<_> = self
+            .processes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        processes.sort_by_key(|p| p.started);
+        processes
+    }
+
+    /// Stops the process (if still running) and forgets it. Returns `None` if
+    /// no process with this id exists.
+    pub async fn stop(&self, id: &str) -> Option<Arc<BackgroundProcess>> {
+        let process = self.get(id)?;
+        process.stop().await;
+        self.processes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+        Some(process)
+    }
+
+    /// Takes the exec snapshot overlays of processes that are no longer
+    /// running, so the session can record what they changed.
+    pub fn take_finished_overlays(&self) -> Vec<(Arc<BackgroundProcess>, OverlayMount)> {
+        self.list()
+            .into_iter()
+            .filter(|process| !process.is_running())
+            .filter_map(|process| {
+                let overlay = process.take_overlay()?;
+                Some((process, overlay))
+            })
+            .collect()
+    }
+
+    /// Emits a [`BackgroundProcessUpdateEvent`] with the current process list.
+    pub async fn send_update(&self, sub_id: String) {
+        let processes = self.list().iter().map(|p| p.info()).collect();
+        let event = Event {
+            id: sub_id,
+            msg: EventMsg::BackgroundProcessUpdate(BackgroundProcessUpdateEvent { processes }),
+        };
+        let _ = self.tx_event.send(event).await;
+    }
+}
+
+impl Drop for BackgroundProcessManager {
+    fn drop(&mut self) {
+        let processes = self.processes.get_mut().unwrap_or_else(|e| e.into_inner());
+        for process in processes.values() {
+            if process.is_running() {
+                process.signal(Signal::Kill);
+            }
+        }
+    }
+}
+
+/// Drops the oldest exited processes until at most `keep` remain.
+fn evict_exited(processes: &mut HashMap<String, Arc<BackgroundProcess>>, keep: usize) {
+    let mut exited: Vec<_> = processes
+        .values()
+        .filter(|p| !p.is_running())
+        .map(|p| (p.started, p.id.clone()))
+        .collect();
+    if exited.len() <= keep {
+        return;
+    }
+    exited.sort();
+    for (_, id) in exited.drain(..exited.len() - keep) {
+        processes.remove(&id);
+    }
+}
+
+async fn read_lines<R>(reader: R, stream: &'static str, process: Arc<BackgroundProcess>)
+where
+    R: AsyncRead + Unpin,
+{
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let line = String::from_utf8_lossy(&buf);
+        process
+            .log
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(stream, &line);
+        process.notify.notify_waiters();
+    }
+}
+
+/// Joins lines, keeping the most recent ones when over the byte cap.
+fn cap_output<'a>(lines: impl DoubleEndedIterator<Item = &'a String>) -> String {
+    let mut kept = Vec::new();
+    let mut bytes = 0;
+    for line in lines.rev() {
+        if bytes + line.len() + 1 > MAX_STREAM_OUTPUT {
+            break;
+        }
+        bytes += line.len() + 1;
+        kept.push(line.as_str());
+    }
+    kept.reverse();
+    kept.join("\n")
+}
+
+/// Formats a process and (optionally) some of its output as the JSON payload
+/// returned to the model.
+pub fn format_background_process_output(
+    process: &BackgroundProcess,
+    output: Option<String>,
+    extra: Vec<(&str, serde_json::Value)>,
+) -> String {
+    let state = process.state();
+    let mut payload = serde_json::json!({
+        "process_id": process.id,
+        "command": process.command,
+        "status": state.as_str(),
+        "uptime_seconds": process.started.elapsed().as_secs(),
+    });
+    if let Some(pid) = process.pid {
+        payload["pid"] = pid.into();
+    }
+    if let ProcessState::Exited(code) = state {
+        payload["exit_code"] = code.into();
+    }
+    for (key, value) in extra {
+        payload[key] = value;
+    }
+    if let Some(output) = output {
+        payload["output"] = output.into();
+    }
+    payload.to_string()
+}
+
+pub(crate) fn create_background_process_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "action".to_string(),
+        JsonSchema::String {
+            description: Some("One of 'start', 'tail', 'wait', 'stop' or 'list'.".to_string()),
+        },
+    );
+    properties.insert(
+        "process_id".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Process id returned by 'start'. Required for 'tail', 'wait' and 'stop'."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "command".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "For 'start': the shell command line to run, e.g. `npm run dev`.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "workdir".to_string(),
+        JsonSchema::String {
+            description: Some("For 'start': the working directory.".to_string()),
+        },
+    );
+    properties.insert(
+        "lines".to_string(),
+        JsonSchema::Number {
+            description: Some(format!(
+                "For 'tail': number of most recent log lines to return (default {DEFAULT_TAIL_LINES})."
+            )),
+        },
+    );
+    properties.insert(
+        "port".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "For 'wait': wait until this TCP port accepts connections.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "host".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "For 'wait' with port: host to connect to (default 127.0.0.1).".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "pattern".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "For 'wait': wait until a log line matches this regular expression. Output printed before this call counts too."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "timeout".to_string(),
+        JsonSchema::Number {
+            description: Some(format!(
+                "For 'wait': maximum time to wait in milliseconds (default {DEFAULT_WAIT_TIMEOUT_MS})."
+            )),
+        },
+    );
+    properties.insert(
+        "with_escalated_permissions".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "For 'start': run without sandbox restrictions. Requires user approval."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "justification".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "For 'start' with with_escalated_permissions: 1-sentence explanation of why the process needs it."
+                    .to_string(),
+            ),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "background_process".to_string(),
+        description: "Runs long-lived commands such as dev servers or file watchers without blocking the turn. 'start' launches a command and returns its process_id immediately, 'tail' returns its most recent output, 'wait' blocks until it listens on a port or prints a line matching a pattern, 'stop' terminates it and its children, and 'list' shows all background processes. Processes are stopped when the session ends.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["action".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_log_drops_oldest_lines() {
+        let mut log = ProcessLog::default();
+        for i in 0..MAX_LOG_LINES + 5 {
+            log.push("stdout", &format!("{i}\n"));
+        }
+        assert_eq!(log.lines.len(), MAX_LOG_LINES);
+        assert_eq!(log.first_index(), 5);
+        assert_eq!(log.lines.front().map(String::as_str), Some("5"));
+    }
+
+    #[test]
+    fn process_log_marks_stderr() {
+        let mut log = ProcessLog::default();
+        log.push("stderr", "boom\r\n");
+        assert_eq!(log.lines.back().map(String::as_str), Some("[stderr] boom"));
+    }
+
+    fn process(id: &str, state: ProcessState) -> Arc<BackgroundProcess> {
+        Arc::new(BackgroundProcess {
+            id: id.to_string(),
+            command: "true".to_string(),
+            cwd: PathBuf::from("/"),
+            pid: None,
+            started: Instant::now(),
+            log: Mutex::new(ProcessLog::default()),
+            state: Mutex::new(state),
+            notify: Notify::new(),
+            overlay: Mutex::new(None),
+        })
+    }
+
+    #[test]
+    fn evict_exited_keeps_running_and_newest_exited() {
+        let mut processes = HashMap::new();
+        for (id, state) in [
+            ("bg-1", ProcessState::Exited(0)),
+            ("bg-2", ProcessState::Running),
+            ("bg-3", ProcessState::Stopped),
+            ("bg-4", ProcessState::Exited(1)),
+        ] {
+            processes.insert(id.to_string(), process(id, state));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        evict_exited(&mut processes, 1);
+        let mut ids: Vec<_> = processes.keys().cloned().collect();
+        ids.sort();
+        assert_eq!(ids, vec!["bg-2".to_string(), "bg-4".to_string()]);
+    }
+
+    #[test]
+    fn cap_output_keeps_most_recent_lines() {
+        let lines: Vec<String> = (0..MAX_STREAM_OUTPUT).map(|i| i.to_string()).collect();
+        let out = cap_output(lines.iter());
+        assert!(out.len() <= MAX_STREAM_OUTPUT);
+        assert!(out.ends_with(&(MAX_STREAM_OUTPUT - 1).to_string()));
+    }
+}
@@ -142,12 +142,15 @@ pub(crate) async fn stream_chat_completions(
     }
 
     let tools_json = create_tools_json_for_chat_completions_api(&prompt.tools)?;
-    let payload = json!({
+    let mut payload = json!({
         "model": model_family.slug,
         "messages": messages,
         "stream": true,
         "tools": tools_json,
     });
+    if let Some(max_tokens) = prompt.max_output_tokens {
+        payload["max_tokens"] = json!(max_tokens);
+    }
 
     let endpoint = provider.get_full_url(&None);
     debug!(
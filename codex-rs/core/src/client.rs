@@ -232,6 +232,7 @@ impl ModelClient {
             stream: true,
             include,
             prompt_cache_key: Some(self.session_id.to_string()),
+            max_output_tokens: prompt.max_output_tokens,
         };
 
         let mut attempt = 0;
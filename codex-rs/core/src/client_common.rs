@@ -58,6 +58,9 @@ pub struct Prompt {
 
     /// Optional override for the built-in BASE_INSTRUCTIONS.
     pub base_instructions_override: Option<String>,
+
+    /// Cap on the number of output tokens; `None` leaves it to the provider.
+    pub max_output_tokens: Option<u32>,
 }
 
 impl Prompt {
@@ -316,6 +319,8 @@ pub(crate) struct ResponsesApiRequest<'a> {
     pub(crate) include: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) prompt_cache_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max_output_tokens: Option<u32>,
 }
 
 pub(crate) fn create_reasoning_param_for_request(
@@ -4,10 +4,12 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::Weak;
 use std::sync::atomic::AtomicU64;
 use std::time::Duration;
 
@@ -22,8 +24,25 @@ use codex_protocol::protocol::TurnAbortReason;
 use codex_protocol::protocol::TurnAbortedEvent;
 use futures::prelude::*;
 use mcp_types::CallToolResult;
+use mcp_types::CreateMessageRequest;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
+use mcp_types::CreateMessageResultContent;
+use mcp_types::ElicitRequest;
+use mcp_types::ElicitRequestParams;
+use mcp_types::ElicitResult;
+use mcp_types::JSONRPCRequest;
+use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ProgressNotificationParams;
+use mcp_types::RequestId;
+use mcp_types::Role;
+use mcp_types::SamplingMessage;
+use mcp_types::SamplingMessageContent;
+use mcp_types::TextContent;
+use mcp_types::ToolOutputSchema;
 use serde::Serialize;
 use serde_json;
+use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::task::AbortHandle;
 use tracing::debug;
@@ -102,10 +121,14 @@ reasoning: {:?}"#,
                 .await
                 .unwrap_or_else(|| "unknown".to_string());
 
+            // An open JavaScript dialog blocks the page, so skip anything
+            // that would evaluate in it until the dialog is answered.
+            let pending_dialog = browser_manager.pending_dialog().await;
+
             // Try to get a tab title if available
             let title = match browser_manager.get_or_create_page().await {
-                Ok(page) => page.get_title().await,
-                Err(_) => None,
+                Ok(page) if pending_dialog.is_none() => page.get_title().await,
+                _ => None,
             };
 
             // Get browser type description
@@ -113,7 +136,21 @@ reasoning: {:?}"#,
 
             // Get viewport dimensions
             let (viewport_width, viewport_height) = browser_manager.get_viewport_size().await;
-            let viewport_info = format!(" | Viewport: {}x{}", viewport_width, viewport_height);
+            let mut viewport_info = format!(" | Viewport: {}x{}", viewport_width, viewport_height);
+
+            // Mention other tabs so the model knows popups opened
+            if let Some(tab) = browser_manager.active_tab().await.filter(|t| t.count > 1) {
+                viewport_info.push_str(&format!(
+                    " | Tab {} of {} (use browser_tabs to switch)",
+                    tab.index, tab.count
+                ));
+            }
+            if let Some(dialog) = &pending_dialog {
+                viewport_info.push_str(&format!(
+                    " | JavaScript dialog open: {} (use browser_dialog to accept or dismiss)",
+                    dialog
+                ));
+            }
 
             // Get cursor position
             let cursor_info = match browser_manager.get_cursor_position().await {
@@ -125,7 +162,12 @@ reasoning: {:?}"#,
             };
 
             // Try to capture screenshot and compare with last one
-            let screenshot_status = match capture_browser_screenshot(sess).await {
+            let capture = if pending_dialog.is_some() {
+                Err("a JavaScript dialog is open".to_string())
+            } else {
+                capture_browser_screenshot(sess).await
+            };
+            let screenshot_status = match capture {
                 Ok((screenshot_path, _url)) => {
                     // Check if screenshot has changed using image hashing
                     let mut last_screenshot_info = sess.last_screenshot_info.lock().unwrap();
@@ -248,6 +290,8 @@ reasoning: {:?}"#,
         });
     }
 
+    jar.items.extend(consume_pending_screenshots(sess));
+
     jar.into_items()
 }
 use crate::agent_tool::AGENT_MANAGER;
@@ -269,6 +313,9 @@ use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::environment_context::EnvironmentContext;
 use crate::config::Config;
+use crate::config_types::McpElicitationPolicy;
+use crate::config_types::McpRequestsConfig;
+use crate::config_types::McpSamplingPolicy;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::conversation_history::ConversationHistory;
 use crate::error::CodexErr;
@@ -283,6 +330,9 @@ use crate::exec::StreamOutput;
 use crate::exec::process_exec_tool_call;
 use crate::exec_env::create_env;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::mcp_connection_manager::McpServerRequest;
+use crate::mcp_connection_manager::roots_for;
+use crate::mcp_tool_call::call_tool_result_to_model_text;
 use crate::mcp_tool_call::handle_mcp_tool_call;
 use crate::models::ContentItem;
 use crate::models::FunctionCallOutputPayload;
@@ -293,6 +343,7 @@ use crate::models::ResponseInputItem;
 use crate::models::ResponseItem;
 use crate::models::ShellToolCallParams;
 use crate::openai_tools::ToolsConfig;
+use crate::openai_tools::create_mcp_resource_tools;
 use crate::openai_tools::get_openai_tools;
 use crate::parse_command::parse_command;
 use crate::plan_tool::handle_update_plan;
@@ -311,6 +362,7 @@ use crate::protocol::ApplyPatchApprovalRequestEvent;
 use crate::protocol::AskForApproval;
 use crate::protocol::BackgroundEventEvent;
 use crate::protocol::BrowserScreenshotUpdateEvent;
+use crate::protocol::BrowserTabSummary;
 use crate::protocol::ErrorEvent;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
@@ -324,6 +376,13 @@ use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
 use crate::protocol::ReviewDecision;
 use crate::protocol::SandboxPolicy;
+use crate::sandbox_denial::SandboxDenial;
+use crate::sandbox_denial::suggest_writable_root;
+use crate::protocol::ElicitationAction;
+use crate::protocol::McpElicitationRequestEvent;
+use crate::protocol::McpListServersResponseEvent;
+use crate::protocol::McpPromptsEvent;
+use crate::protocol::McpSamplingRequestEvent;
 use crate::protocol::SessionConfiguredEvent;
 use crate::protocol::Submission;
 use crate::protocol::TaskCompleteEvent;
@@ -332,7 +391,27 @@ use crate::rollout::RolloutRecorder;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_command_safety;
 use crate::safety::assess_safety_for_untrusted_command;
+use crate::safety::get_platform_sandbox;
 use crate::shell;
+use crate::background_process::BackgroundProcess;
+use crate::background_process::BackgroundProcessManager;
+use crate::background_process::BackgroundProcessToolCallParams;
+use crate::background_process::DEFAULT_TAIL_LINES;
+use crate::background_process::DEFAULT_WAIT_TIMEOUT_MS;
+use crate::background_process::format_background_process_output;
+use crate::exec_snapshot;
+use crate::exec_snapshot::ExecSnapshot;
+use crate::exec_snapshot::Layer;
+use crate::exec_snapshot::MAX_PENDING_LAYERS;
+use crate::exec_snapshot::OverlayMount;
+use crate::exec_snapshot::SnapshotChange;
+use crate::protocol::ExecSnapshotEvent;
+use crate::protocol::ExecSnapshotStatus;
+use crate::shell_session::DEFAULT_EXEC_TIMEOUT_MS;
+use crate::shell_session::DEFAULT_READ_TIMEOUT_MS;
+use crate::shell_session::ShellSessionManager;
+use crate::shell_session::ShellSessionToolCallParams;
+use crate::shell_session::format_shell_session_output;
 use crate::turn_diff_tracker::TurnDiffTracker;
 use crate::user_notification::UserNotification;
 use crate::util::backoff;
@@ -382,10 +461,16 @@ impl Codex {
             resume_path: resume_path.clone(),
         };
 
+        configure_browser_environment(&config.browser).await;
+
         let config = Arc::new(config);
 
-        // Generate a unique ID for the lifetime of this Codex session.
-        let session_id = Uuid::new_v4();
+        // Generate a unique ID for the lifetime of this Codex session. A
+        // resumed session keeps the id recorded in its rollout.
+        let session_id = resume_path
+            .as_deref()
+            .and_then(|path| crate::rollout::read_rollout_session_id(path).ok())
+            .unwrap_or_else(Uuid::new_v4);
 
         // This task will run until Op::Shutdown is received.
         tokio::spawn(submission_loop(session_id, config, auth, rx_sub, tx_event));
@@ -453,6 +538,17 @@ struct State {
     /// model for a given batch when using `agent_wait` without `return_all`.
     /// This enables sequential waiting behavior across multiple calls.
     seen_completed_agents_by_batch: HashMap<String, HashSet<String>>,
+    /// File changes made by shell commands that the user has not yet
+    /// committed or rolled back, oldest first (only populated when
+    /// `exec_snapshots` is enabled).
+    exec_snapshots: Vec<ExecSnapshot>,
+    next_exec_snapshot_id: u64,
+    /// Writable roots the user granted after a sandbox denial, on top of the
+    /// configured sandbox policy.
+    granted_writable_roots: Vec<PathBuf>,
+    /// Sampling requests from MCP servers awaiting the user's decision, by
+    /// server name and request id.
+    pending_mcp_sampling: HashMap<(String, RequestId), oneshot::Sender<ReviewDecision>>,
 }
 
 /// Context for an initialized model agent
@@ -478,6 +574,9 @@ pub(crate) struct Session {
     /// Manager for external MCP servers/tools.
     mcp_connection_manager: McpConnectionManager,
 
+    /// How requests sent by MCP servers are answered.
+    mcp_requests: McpRequestsConfig,
+
     /// Configuration for available agent models
     agents: Vec<crate::config_types::AgentConfig>,
 
@@ -492,13 +591,22 @@ pub(crate) struct Session {
     codex_linux_sandbox_exe: Option<PathBuf>,
     user_shell: shell::Shell,
     show_raw_agent_reasoning: bool,
-    /// Pending browser screenshots to include in the next model request
-    #[allow(dead_code)]
-    pending_browser_screenshots: Mutex<Vec<PathBuf>>,
+    /// Browser images (with a label) to include in the next model request
+    pending_browser_screenshots: Mutex<Vec<(PathBuf, String)>>,
+    /// Messages carrying images returned by MCP tools, recorded in history
+    /// after the outputs of the turn that produced them
+    pending_mcp_tool_images: Mutex<Vec<ResponseItem>>,
     /// Track the last system status to detect changes
     last_system_status: Mutex<Option<String>>,
     /// Track the last screenshot path and hash to detect changes
     last_screenshot_info: Mutex<Option<(PathBuf, Vec<u8>, Vec<u8>)>>, // (path, phash, dhash)
+    /// Persistent shells opened via the `shell_session` tool.
+    shell_sessions: ShellSessionManager,
+    /// Long-running commands started via the `background_process` tool.
+    background_processes: Arc<BackgroundProcessManager>,
+    /// Where exec snapshot layers are kept; `None` when exec snapshots are
+    /// disabled or not supported on this system.
+    exec_snapshot_dir: Option<PathBuf>,
 }
 
 impl Session {
@@ -518,6 +626,28 @@ impl Session {
         &self.sandbox_policy
     }
 
+    /// The configured sandbox policy plus any writable roots the user granted
+    /// after a sandbox denial. Commands should run under this policy.
+    fn effective_sandbox_policy(&self) -> SandboxPolicy {
+        let state = self.state.lock().unwrap();
+        self.sandbox_policy
+            .with_additional_writable_roots(&state.granted_writable_roots)
+    }
+
+    /// Also tells MCP servers about the new root.
+    fn grant_writable_root(&self, root: PathBuf) {
+        let policy = {
+            let mut state = self.state.lock().unwrap();
+            if state.granted_writable_roots.contains(&root) {
+                return;
+            }
+            state.granted_writable_roots.push(root);
+            self.sandbox_policy
+                .with_additional_writable_roots(&state.granted_writable_roots)
+        };
+        self.mcp_connection_manager.set_roots(roots_for(&self.cwd, &policy));
+    }
+
     fn resolve_path(&self, path: Option<String>) -> PathBuf {
         path.as_ref()
             .map(PathBuf::from)
@@ -558,6 +688,7 @@ impl Session {
         command: Vec<String>,
         cwd: PathBuf,
         reason: Option<String>,
+        grant_root: Option<PathBuf>,
     ) -> oneshot::Receiver<ReviewDecision> {
         let (tx_approve, rx_approve) = oneshot::channel();
         let event = Event {
@@ -567,6 +698,7 @@ impl Session {
                 command,
                 cwd,
                 reason,
+                grant_root,
             }),
         };
         let _ = self.tx_event.send(event).await;
@@ -820,6 +952,7 @@ impl Session {
             stderr,
             duration,
             exit_code,
+            ..
         } = output;
         // Because stdout and stderr could each be up to 100 KiB, we send
         // truncated versions.
@@ -893,12 +1026,14 @@ impl Session {
         let output_stderr;
         let borrowed: &ExecToolCallOutput = match &result {
             Ok(output) => output,
+            Err(CodexErr::Sandbox(SandboxErr::Denied(output))) => output,
             Err(e) => {
                 output_stderr = ExecToolCallOutput {
                     exit_code: -1,
                     stdout: StreamOutput::new(String::new()),
                     stderr: StreamOutput::new(get_error_message_ui(e)),
                     duration: Duration::default(),
+                    sandbox_denials: Vec::new(),
                 };
                 &output_stderr
             }
@@ -915,6 +1050,211 @@ impl Session {
         result
     }
 
+    /// Prepares the overlay a shell command runs on when exec snapshots are
+    /// enabled. Commands whose working directory is outside the session's run
+    /// directly on the real tree, as do all commands if a layer cannot be
+    /// created. `Err` carries the message for the model when the command has
+    /// to be refused because too many snapshots are pending.
+    async fn begin_exec_snapshot(
+        &self,
+        sub_id: &str,
+        cwd: &Path,
+    ) -> Result<Option<OverlayMount>, String> {
+        let Some(dir) = &self.exec_snapshot_dir else {
+            return Ok(None);
+        };
+        // Background processes that exited since the last command have to be
+        // recorded first so this command sees their changes.
+        self.collect_background_exec_snapshots(sub_id).await;
+        if !cwd.starts_with(&self.cwd) {
+            return Ok(None);
+        }
+        let lower: Vec<Arc<Layer>> = {
+            let state = self.state.lock().unwrap();
+            state
+                .exec_snapshots
+                .iter()
+                .rev()
+                .map(|snapshot| snapshot.layer.clone())
+                .collect()
+        };
+        if lower.len() >= MAX_PENDING_LAYERS {
+            return Err(format!(
+                "{MAX_PENDING_LAYERS} exec snapshots are pending; ask the user to commit or roll \
+                 them back before running more commands"
+            ));
+        }
+        match Layer::new(dir) {
+            Ok(layer) => Ok(Some(OverlayMount::new(self.cwd.clone(), lower, layer))),
+            Err(e) => {
+                warn!("failed to create exec snapshot layer: {e}");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Collects what the command that ran on `overlay` changed and, if it
+    /// changed anything, records a pending snapshot and emits an
+    /// `ExecSnapshot` event.
+    async fn finish_exec_snapshot(
+        &self,
+        sub_id: &str,
+        call_id: &str,
+        command: Vec<String>,
+        overlay: OverlayMount,
+    ) {
+        let call_id = call_id.to_string();
+        let result = tokio::task::spawn_blocking(move || {
+            ExecSnapshot::from_mount(String::new(), call_id, command, &overlay)
+        })
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+        let mut snapshot = match result {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("failed to read exec snapshot layer: {e}");
+                return;
+            }
+        };
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.next_exec_snapshot_id += 1;
+            snapshot.id = format!("snap-{}", state.next_exec_snapshot_id);
+            state.exec_snapshots.push(snapshot.clone());
+        }
+        self.send_exec_snapshot_event(sub_id, &snapshot, ExecSnapshotStatus::Pending)
+            .await;
+    }
+
+    /// Records snapshots for background processes that are no longer running.
+    async fn collect_background_exec_snapshots(&self, sub_id: &str) {
+        for (process, overlay) in self.background_processes.take_finished_overlays() {
+            self.finish_exec_snapshot(sub_id, &process.id, vec![process.command.clone()], overlay)
+                .await;
+        }
+    }
+
+    /// Commits or rolls back a pending exec snapshot (the most recent one when
+    /// `snapshot_id` is `None`). Layers stack in order, so a commit also
+    /// commits every older pending snapshot and a rollback also discards every
+    /// newer one. An `ExecSnapshot` event reports each resolved snapshot.
+    async fn resolve_exec_snapshot(&self, sub_id: &str, snapshot_id: Option<String>, rollback: bool) {
+        self.collect_background_exec_snapshots(sub_id).await;
+        let batch = {
+            let mut state = self.state.lock().unwrap();
+            let index = match &snapshot_id {
+                Some(id) => state.exec_snapshots.iter().position(|s| &s.id == id),
+                None => state.exec_snapshots.len().checked_sub(1),
+            };
+            index.map(|i| {
+                if rollback {
+                    state.exec_snapshots.split_off(i)
+                } else {
+                    state.exec_snapshots.drain(..=i).collect()
+                }
+            })
+        };
+        let Some(batch) = batch else {
+            let message = match snapshot_id {
+                Some(id) => format!("no pending exec snapshot with id {id}"),
+                None => "no pending exec snapshots".to_string(),
+            };
+            self.send_error(sub_id, message).await;
+            return;
+        };
+
+        if rollback {
+            // Dropping the layers removes them from disk.
+            for snapshot in batch.iter().rev() {
+                self.send_exec_snapshot_event(sub_id, snapshot, ExecSnapshotStatus::RolledBack)
+                    .await;
+            }
+            return;
+        }
+
+        let mut remaining = VecDeque::from(batch);
+        while let Some(snapshot) = remaining.pop_front() {
+            let to_commit = snapshot.clone();
+            let result = tokio::task::spawn_blocking(move || to_commit.commit())
+                .await
+                .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+            if let Err(e) = result {
+                let message = format!("failed to commit {}: {e}", snapshot.id);
+                // Keep the snapshot and everything newer so the user can retry
+                // after resolving the conflict.
+                remaining.push_front(snapshot);
+                {
+                    let mut state = self.state.lock().unwrap();
+                    let newer = std::mem::take(&mut state.exec_snapshots);
+                    state.exec_snapshots = remaining.into_iter().chain(newer).collect();
+                }
+                self.send_error(sub_id, message).await;
+                return;
+            }
+            for pending in remaining.iter_mut() {
+                pending.refresh_stamps(&snapshot);
+            }
+            for pending in self.state.lock().unwrap().exec_snapshots.iter_mut() {
+                pending.refresh_stamps(&snapshot);
+            }
+            self.send_exec_snapshot_event(sub_id, &snapshot, ExecSnapshotStatus::Committed)
+                .await;
+        }
+    }
+
+    /// Closes a shell session and records what it changed while exec
+    /// snapshots were enabled. Returns `false` if no session has this id.
+    async fn close_shell_session(&self, sub_id: &str, id: &str) -> bool {
+        let Some(session) = self.shell_sessions.close(id).await else {
+            return false;
+        };
+        if let Some(overlay) = session.take_overlay() {
+            let command = vec!["shell_session".to_string(), id.to_string()];
+            self.finish_exec_snapshot(sub_id, id, command, overlay).await;
+        }
+        true
+    }
+
+    async fn send_exec_snapshot_event(
+        &self,
+        sub_id: &str,
+        snapshot: &ExecSnapshot,
+        status: ExecSnapshotStatus,
+    ) {
+        let paths = |kind: SnapshotChange| {
+            snapshot
+                .changes
+                .iter()
+                .filter(|(change, _)| *change == kind)
+                .map(|(_, path)| path.clone())
+                .collect()
+        };
+        let event = Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::ExecSnapshot(ExecSnapshotEvent {
+                snapshot_id: snapshot.id.clone(),
+                call_id: snapshot.call_id.clone(),
+                command: snapshot.command.clone(),
+                status,
+                added: paths(SnapshotChange::Added),
+                modified: paths(SnapshotChange::Modified),
+                deleted: paths(SnapshotChange::Deleted),
+                unified_diff: snapshot.unified_diff.clone(),
+            }),
+        };
+        let _ = self.tx_event.send(event).await;
+    }
+
+    async fn send_error(&self, sub_id: &str, message: String) {
+        let event = Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::Error(ErrorEvent { message }),
+        };
+        let _ = self.tx_event.send(event).await;
+    }
+
     /// Helper that emits a BackgroundEvent with the given message. This keeps
     /// the call‑sites terse so adding more diagnostics does not clutter the
     /// core agent logic.
@@ -1073,12 +1413,36 @@ impl Session {
         tool: &str,
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
+        progress: mpsc::UnboundedSender<ProgressNotificationParams>,
     ) -> anyhow::Result<CallToolResult> {
         self.mcp_connection_manager
-            .call_tool(server, tool, arguments, timeout)
+            .call_tool(server, tool, arguments, timeout, progress)
             .await
     }
 
+    pub(crate) fn mcp_tool_output_schema(
+        &self,
+        server: &str,
+        tool: &str,
+    ) -> Option<ToolOutputSchema> {
+        self.mcp_connection_manager.tool_output_schema(server, tool)
+    }
+
+    /// True if images returned by tools can be passed on to the model.
+    pub(crate) fn supports_image_input(&self) -> bool {
+        self.client.get_model_family().supports_image_input
+    }
+
+    /// Queue a message carrying images an MCP tool returned. Unlike browser
+    /// screenshots it becomes part of the conversation history.
+    pub(crate) fn queue_mcp_tool_images(&self, message: ResponseItem) {
+        self.pending_mcp_tool_images.lock().unwrap().push(message);
+    }
+
+    fn take_mcp_tool_images(&self) -> Vec<ResponseItem> {
+        std::mem::take(&mut *self.pending_mcp_tool_images.lock().unwrap())
+    }
+
     fn abort(&self) {
         info!("Aborting existing session");
         let mut state = self.state.lock().unwrap();
@@ -1346,20 +1710,25 @@ async fn submission_loop(
                 let writable_roots = get_writable_roots(&cwd);
 
                 // Error messages to dispatch after SessionConfigured is sent.
-                let mut mcp_connection_errors = Vec::<Event>::new();
-                let (mcp_connection_manager, failed_clients) =
-                    match McpConnectionManager::new(config.mcp_servers.clone()).await {
-                        Ok((mgr, failures)) => (mgr, failures),
-                        Err(e) => {
-                            let message = format!("Failed to create MCP connection manager: {e:#}");
-                            error!("{message}");
-                            mcp_connection_errors.push(Event {
-                                id: sub.id.clone(),
-                                msg: EventMsg::Error(ErrorEvent { message }),
-                            });
-                            (McpConnectionManager::default(), Default::default())
-                        }
-                    };
+                let mut startup_errors = Vec::<Event>::new();
+                let (mcp_connection_manager, failed_clients) = match McpConnectionManager::new(
+                    config.mcp_servers.clone(),
+                    &config.codex_home,
+                    roots_for(&cwd, &sandbox_policy),
+                )
+                .await
+                {
+                    Ok((mgr, failures)) => (mgr, failures),
+                    Err(e) => {
+                        let message = format!("Failed to create MCP connection manager: {e:#}");
+                        error!("{message}");
+                        startup_errors.push(Event {
+                            id: sub.id.clone(),
+                            msg: EventMsg::Error(ErrorEvent { message }),
+                        });
+                        (McpConnectionManager::default(), Default::default())
+                    }
+                };
 
                 // Surface individual client start-up failures to the user.
                 if !failed_clients.is_empty() {
@@ -1367,12 +1736,35 @@ async fn submission_loop(
                         let message =
                             format!("MCP client for `{server_name}` failed to start: {err:#}");
                         error!("{message}");
-                        mcp_connection_errors.push(Event {
+                        startup_errors.push(Event {
                             id: sub.id.clone(),
                             msg: EventMsg::Error(ErrorEvent { message }),
                         });
                     }
                 }
+                let exec_snapshot_dir = if config.exec_snapshots {
+                    let dir = config.codex_home.join("exec-snapshots");
+                    let probe_dir = dir.clone();
+                    let supported = tokio::task::spawn_blocking(move || {
+                        exec_snapshot::overlay_supported(&probe_dir)
+                    })
+                    .await
+                    .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+                    match supported {
+                        Ok(()) => Some(dir),
+                        Err(e) => {
+                            let message = format!("Exec snapshots are disabled: {e}");
+                            error!("{message}");
+                            startup_errors.push(Event {
+                                id: sub.id.clone(),
+                                msg: EventMsg::Error(ErrorEvent { message }),
+                            });
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
                 let default_shell = shell::default_user_shell().await;
                 sess = Some(Arc::new(Session {
                     client,
@@ -1391,6 +1783,7 @@ async fn submission_loop(
                     cwd,
                     writable_roots,
                     mcp_connection_manager,
+                    mcp_requests: config.mcp_requests,
                     agents: config.agents.clone(),
                     notify,
                     state: Mutex::new(state),
@@ -1400,8 +1793,14 @@ async fn submission_loop(
                     user_shell: default_shell,
                     show_raw_agent_reasoning: config.show_raw_agent_reasoning,
                     pending_browser_screenshots: Mutex::new(Vec::new()),
+                    pending_mcp_tool_images: Mutex::new(Vec::new()),
                     last_system_status: Mutex::new(None),
                     last_screenshot_info: Mutex::new(None),
+                    shell_sessions: ShellSessionManager::default(),
+                    background_processes: Arc::new(BackgroundProcessManager::new(
+                        tx_event.clone(),
+                    )),
+                    exec_snapshot_dir,
                 }));
 
                 // Patch restored state into the newly created session.
@@ -1426,12 +1825,44 @@ async fn submission_loop(
                         history_entry_count,
                     }),
                 })
-                .chain(mcp_connection_errors.into_iter());
+                .chain(startup_errors.into_iter());
                 for event in events {
                     if let Err(e) = tx_event.send(event).await {
                         error!("failed to send event: {e:?}");
                     }
                 }
+
+                // Keep the front-end's list of MCP prompts current.
+                if let Some(sess_arc) = &sess {
+                    let mut prompts = sess_arc.mcp_connection_manager.subscribe_prompts();
+                    let tx_event = tx_event.clone();
+                    let sub_id = sub.id.clone();
+                    tokio::spawn(async move {
+                        loop {
+                            let current = prompts.borrow_and_update().clone();
+                            if !current.is_empty() {
+                                let event = Event {
+                                    id: sub_id.clone(),
+                                    msg: EventMsg::McpPrompts(McpPromptsEvent { prompts: current }),
+                                };
+                                if tx_event.send(event).await.is_err() {
+                                    break;
+                                }
+                            }
+                            if prompts.changed().await.is_err() {
+                                break;
+                            }
+                        }
+                    });
+
+                    if let Some(requests) = sess_arc.mcp_connection_manager.take_server_requests() {
+                        tokio::spawn(handle_mcp_server_requests(
+                            Arc::downgrade(sess_arc),
+                            sub.id.clone(),
+                            requests,
+                        ));
+                    }
+                }
                 
                 // Initialize agent manager after SessionConfigured is sent
                 if !agent_manager_initialized {
@@ -1500,6 +1931,70 @@ async fn submission_loop(
                     other => sess.notify_approval(&id, other),
                 }
             }
+            Op::McpElicitationResponse {
+                server,
+                request_id,
+                action,
+                content,
+            } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => sess,
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                let content = if action == ElicitationAction::Accept {
+                    content
+                } else {
+                    None
+                };
+                if let Err(e) = sess
+                    .mcp_connection_manager
+                    .send_response(&server, request_id, elicit_result(action, content))
+                    .await
+                {
+                    warn!("failed to answer elicitation request from MCP server `{server}`: {e:#}");
+                }
+            }
+            Op::McpSamplingApproval {
+                server,
+                request_id,
+                decision,
+            } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => sess,
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                let pending = sess
+                    .state
+                    .lock()
+                    .unwrap()
+                    .pending_mcp_sampling
+                    .remove(&(server, request_id));
+                if let Some(tx) = pending {
+                    tx.send(decision).ok();
+                }
+            }
+            Op::ListMcpServers => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => sess,
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                let event = Event {
+                    id: sub.id,
+                    msg: EventMsg::McpListServersResponse(McpListServersResponseEvent {
+                        servers: sess.mcp_connection_manager.list_servers(),
+                    }),
+                };
+                sess.send_event(event).await;
+            }
             Op::AddToHistory { text } => {
                 // TODO: What should we do if we got AddToHistory before ConfigureSession?
                 // currently, if ConfigureSession has resume path, this history will be ignored
@@ -1568,6 +2063,67 @@ async fn submission_loop(
                     sess.set_agent(agent);
                 }
             }
+            Op::McpPrompt {
+                server,
+                name,
+                arguments,
+            } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => Arc::clone(sess),
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                // Expanding the prompt is a server round trip; keep the
+                // submission loop responsive meanwhile.
+                tokio::spawn(async move {
+                    let items = match sess
+                        .mcp_connection_manager
+                        .get_prompt(&server, &name, arguments)
+                        .await
+                    {
+                        Ok(result) => prompt_to_input_items(result),
+                        Err(e) => {
+                            sess.send_event(Event {
+                                id: sub.id,
+                                msg: EventMsg::Error(ErrorEvent {
+                                    message: format!("{e:#}"),
+                                }),
+                            })
+                            .await;
+                            return;
+                        }
+                    };
+                    if items.is_empty() {
+                        return;
+                    }
+                    if let Err(items) = sess.inject_input(items) {
+                        let agent = AgentAgent::spawn(Arc::clone(&sess), sub.id, items);
+                        sess.set_agent(agent);
+                    }
+                });
+            }
+            Op::CommitExecSnapshot { snapshot_id } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => sess,
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                sess.resolve_exec_snapshot(&sub.id, snapshot_id, false).await;
+            }
+            Op::RollbackExecSnapshot { snapshot_id } => {
+                let sess = match sess.as_ref() {
+                    Some(sess) => sess,
+                    None => {
+                        send_no_session_event(sub.id).await;
+                        continue;
+                    }
+                };
+                sess.resolve_exec_snapshot(&sub.id, snapshot_id, true).await;
+            }
             Op::Shutdown => {
                 info!("Shutting down Codex instance");
 
@@ -1742,12 +2298,8 @@ async fn run_agent(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
                         ) => {
                             items_to_record_in_conversation_history.push(item);
                             let (content, success): (String, Option<bool>) = match result {
-                                Ok(CallToolResult {
-                                    content,
-                                    is_error,
-                                    structured_content: _,
-                                }) => match serde_json::to_string(content) {
-                                    Ok(content) => (content, *is_error),
+                                Ok(result) => match call_tool_result_to_model_text(result) {
+                                    Ok(content) => (content, result.is_error),
                                     Err(e) => {
                                         warn!("Failed to serialize MCP tool call output: {e}");
                                         (e.to_string(), Some(true))
@@ -1786,6 +2338,9 @@ async fn run_agent(sess: Arc<Session>, sub_id: String, input: Vec<InputItem>) {
                         responses.push(response);
                     }
                 }
+                // Images from MCP tools follow all of the turn's tool outputs
+                // so they never separate a call from its output.
+                items_to_record_in_conversation_history.extend(sess.take_mcp_tool_images());
 
                 // Only attempt to take the lock if there is something to record.
                 if !items_to_record_in_conversation_history.is_empty() {
@@ -1959,11 +2514,14 @@ async fn run_turn(
     // Check if browser is enabled
     let browser_enabled = codex_browser::global::get_browser_manager().await.is_some();
     
-    let tools = get_openai_tools(
+    let mut tools = get_openai_tools(
         &sess.tools_config,
         Some(sess.mcp_connection_manager.list_all_tools()),
         browser_enabled,
     );
+    if sess.mcp_connection_manager.has_resources() {
+        tools.extend(create_mcp_resource_tools());
+    }
 
     let mut retries = 0;
     let mut injection_notice_sent = false;
@@ -1999,6 +2557,7 @@ async fn run_turn(
                 Some(sess.user_shell.clone()),
             )),
             status_items, // Include status items with this request
+            max_output_tokens: None,
         };
 
         // If we injected memory/code hints, emit a lightweight background notice once
@@ -2873,7 +3432,7 @@ mod memory_injection_tests {
         let (tx_event, _) = async_channel::unbounded::<Event>();
         let sess = super::Session {
             client,
-            tx_event,
+            tx_event: tx_event.clone(),
             cwd: tmp.path().to_path_buf(),
             base_instructions: None,
             user_instructions: None,
@@ -2884,6 +3443,7 @@ mod memory_injection_tests {
             disable_response_storage: cfg.disable_response_storage,
             tools_config: ToolsConfig::new(&cfg.model_family, cfg.approval_policy, cfg.sandbox_policy.clone(), cfg.include_plan_tool),
             mcp_connection_manager: super::McpConnectionManager::default(),
+            mcp_requests: Default::default(),
             agents: cfg.agents.clone(),
             notify: cfg.notify.clone(),
             state: std::sync::Mutex::new(super::State::default()),
@@ -2892,8 +3452,14 @@ mod memory_injection_tests {
             user_shell: shell::Shell::Unknown,
             show_raw_agent_reasoning: false,
             pending_browser_screenshots: std::sync::Mutex::new(Vec::new()),
+            pending_mcp_tool_images: std::sync::Mutex::new(Vec::new()),
             last_system_status: std::sync::Mutex::new(None),
             last_screenshot_info: std::sync::Mutex::new(None),
+            shell_sessions: crate::shell_session::ShellSessionManager::default(),
+            background_processes: Arc::new(
+                crate::background_process::BackgroundProcessManager::new(tx_event),
+            ),
+            exec_snapshot_dir: None,
         };
 
         // Input of ~100 tokens → window 10k, reserve 1k, safety 2k, cap 10% (1k)
@@ -2930,7 +3496,7 @@ mod memory_injection_tests {
         let (tx_event2, _) = async_channel::unbounded::<Event>();
         let sess2 = super::Session {
             client: client2,
-            tx_event: tx_event2,
+            tx_event: tx_event2.clone(),
             cwd: tmp.path().to_path_buf(),
             base_instructions: None,
             user_instructions: None,
@@ -2941,6 +3507,7 @@ mod memory_injection_tests {
             disable_response_storage: cfg2.disable_response_storage,
             tools_config: ToolsConfig::new(&cfg2.model_family, cfg2.approval_policy, cfg2.sandbox_policy.clone(), cfg2.include_plan_tool),
             mcp_connection_manager: super::McpConnectionManager::default(),
+            mcp_requests: Default::default(),
             agents: cfg2.agents.clone(),
             notify: cfg2.notify.clone(),
             state: std::sync::Mutex::new(super::State::default()),
@@ -2949,8 +3516,14 @@ mod memory_injection_tests {
             user_shell: shell::Shell::Unknown,
             show_raw_agent_reasoning: false,
             pending_browser_screenshots: std::sync::Mutex::new(Vec::new()),
+            pending_mcp_tool_images: std::sync::Mutex::new(Vec::new()),
             last_system_status: std::sync::Mutex::new(None),
             last_screenshot_info: std::sync::Mutex::new(None),
+            shell_sessions: crate::shell_session::ShellSessionManager::default(),
+            background_processes: Arc::new(
+                crate::background_process::BackgroundProcessManager::new(tx_event2),
+            ),
+            exec_snapshot_dir: None,
         };
         let budget2 = compute_injection_char_budget(&sess2, &turn_input);
         assert_eq!(budget2, 0);
@@ -3441,6 +4014,7 @@ async fn run_compact_agent(
             tools: Vec::new(),
             base_instructions_override: Some(compact_instructions.clone()),
             status_items, // Include status items with this request
+            max_output_tokens: None,
         };
 
         let attempt_result = drain_to_completed(&sess, &sub_id, &prompt).await;
@@ -3680,6 +4254,10 @@ async fn handle_function_call(
             handle_container_exec_with_params(params, sess, turn_diff_tracker, sub_id, call_id)
                 .await
         }
+        "shell_session" => {
+            handle_shell_session(sess, turn_diff_tracker, arguments, sub_id, call_id).await
+        }
+        "background_process" => handle_background_process(sess, arguments, sub_id, call_id).await,
         "update_plan" => handle_update_plan(sess, arguments, sub_id, call_id).await,
         // agent_* tools
         "agent_run" => handle_run_agent(sess, arguments, sub_id, call_id).await,
@@ -3701,12 +4279,25 @@ async fn handle_function_call(
         "browser_history" => handle_browser_history(sess, arguments, sub_id, call_id).await,
         "browser_console" => handle_browser_console(sess, arguments, sub_id, call_id).await,
         "browser_inspect" => handle_browser_inspect(sess, arguments, sub_id, call_id).await,
+        "browser_snapshot" => handle_browser_snapshot(sess, arguments, sub_id, call_id).await,
+        "browser_screenshot" => handle_browser_screenshot(sess, arguments, sub_id, call_id).await,
+        "browser_network" => handle_browser_network(sess, arguments, sub_id, call_id).await,
+        "browser_tabs" => handle_browser_tabs(sess, arguments, sub_id, call_id).await,
+        "browser_script" => handle_browser_script(sess, arguments, sub_id, call_id).await,
+        "browser_perf" => handle_browser_perf(sess, arguments, sub_id, call_id).await,
+        "browser_state" => handle_browser_state(sess, arguments, sub_id, call_id).await,
+        "browser_responsive" => handle_browser_responsive(sess, arguments, sub_id, call_id).await,
+        "browser_dialog" => handle_browser_dialog(sess, arguments, sub_id, call_id).await,
+        "browser_upload" => handle_browser_upload(sess, arguments, sub_id, call_id).await,
+        "browser_downloads" => handle_browser_downloads(sess, arguments, sub_id, call_id).await,
         "browser_cdp" => handle_browser_cdp(sess, arguments, sub_id, call_id).await,
         "browser_cleanup" => handle_browser_cleanup(sess, sub_id, call_id).await,
+        "mcp_list_resources" => handle_mcp_list_resources(sess, arguments, sub_id, call_id).await,
+        "mcp_read_resource" => handle_mcp_read_resource(sess, arguments, sub_id, call_id).await,
         _ => {
             match sess.mcp_connection_manager.parse_tool_name(&name) {
                 Some((server, tool_name)) => {
-                    // TODO(mbolin): Determine appropriate timeout for tool call.
+                    // The server's configured `tool_timeout_ms` applies.
                     let timeout = None;
                     handle_mcp_tool_call(
                         sess, &sub_id, call_id, server, tool_name, arguments, timeout,
@@ -3728,35 +4319,448 @@ async fn handle_function_call(
     }
 }
 
-async fn handle_browser_cleanup(
+async fn handle_mcp_list_resources(
     sess: &Session,
+    arguments: String,
     sub_id: String,
     call_id: String,
 ) -> ResponseInputItem {
-    let sess_clone = sess;
+    let params = serde_json::from_str::<serde_json::Value>(&arguments).ok();
     let call_id_clone = call_id.clone();
+
     execute_custom_tool(
         sess,
         &sub_id,
         call_id,
-        "browser_cleanup".to_string(),
-        Some(serde_json::json!({})),
+        "mcp_list_resources".to_string(),
+        params.clone(),
         || async move {
-            if let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await {
-                match browser_manager.cleanup().await {
-                    Ok(_) => ResponseInputItem::FunctionCallOutput {
-                        call_id: call_id_clone,
-                        output: FunctionCallOutputPayload { content: "Browser cleanup completed".to_string(), success: Some(true) },
-                    },
-                    Err(e) => ResponseInputItem::FunctionCallOutput {
-                        call_id: call_id_clone,
-                        output: FunctionCallOutputPayload { content: format!("Cleanup failed: {}", e), success: Some(false) },
-                    },
-                }
-            } else {
-                ResponseInputItem::FunctionCallOutput {
-                    call_id: call_id_clone,
-                    output: FunctionCallOutputPayload { content: "Browser is not initialized. Use browser_open to start the browser.".to_string(), success: Some(false) },
+            let server = params
+                .as_ref()
+                .and_then(|p| p.get("server"))
+                .and_then(|v| v.as_str());
+            let (content, success) = match sess.mcp_connection_manager.list_resources(server) {
+                Ok(resources) => {
+                    let mut servers: Vec<_> = resources.into_iter().collect();
+                    servers.sort_by(|a, b| a.0.cmp(&b.0));
+                    let listed: Vec<serde_json::Value> = servers
+                        .into_iter()
+                        .flat_map(|(server, resources)| {
+                            resources.into_iter().map(move |resource| {
+                                let mut entry = serde_json::to_value(&resource)
+                                    .unwrap_or_else(|_| serde_json::json!({}));
+                                entry["server"] = serde_json::Value::String(server.clone());
+                                entry
+                            })
+                        })
+                        .collect();
+                    if listed.is_empty() {
+                        ("No MCP resources available.".to_string(), true)
+                    } else {
+                        (serde_json::Value::Array(listed).to_string(), true)
+                    }
+                }
+                Err(e) => (format!("{e:#}"), false),
+            };
+            ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone,
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(success),
+                },
+            }
+        },
+    )
+    .await
+}
+
+async fn handle_mcp_read_resource(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let params = serde_json::from_str::<serde_json::Value>(&arguments).ok();
+    let call_id_clone = call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "mcp_read_resource".to_string(),
+        params.clone(),
+        || async move {
+            let reply = |content: String, success: bool| ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone.clone(),
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(success),
+                },
+            };
+            let args = params.unwrap_or_else(|| serde_json::json!({}));
+            let str_arg = |key: &str| args.get(key).and_then(|v| v.as_str());
+            let (Some(server), Some(uri)) = (str_arg("server"), str_arg("uri")) else {
+                return reply("Provide both 'server' and 'uri'.".to_string(), false);
+            };
+            match sess.mcp_connection_manager.read_resource(server, uri).await {
+                Ok(result) => reply(format_resource_contents(result), true),
+                Err(e) => reply(format!("{e:#}"), false),
+            }
+        },
+    )
+    .await
+}
+
+/// Text of every content item in a `resources/read` result. Binary contents
+/// are described rather than inlined.
+fn format_resource_contents(result: mcp_types::ReadResourceResult) -> String {
+    use mcp_types::ReadResourceResultContents;
+    const MAX_RESOURCE_CHARS: usize = 64 * 1024;
+
+    let multiple = result.contents.len() > 1;
+    let parts: Vec<String> = result
+        .contents
+        .into_iter()
+        .map(|contents| match contents {
+            ReadResourceResultContents::TextResourceContents(text) if multiple => {
+                format!("--- {} ---\n{}", text.uri, text.text)
+            }
+            ReadResourceResultContents::TextResourceContents(text) => text.text,
+            ReadResourceResultContents::BlobResourceContents(blob) => format!(
+                "[binary resource {} ({}), {} bytes base64-encoded]",
+                blob.uri,
+                blob.mime_type.as_deref().unwrap_or("unknown type"),
+                blob.blob.len()
+            ),
+        })
+        .collect();
+    let mut content = parts.join("\n");
+    if content.len() > MAX_RESOURCE_CHARS {
+        let mut end = MAX_RESOURCE_CHARS;
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        content.truncate(end);
+        content.push_str("\n[truncated]");
+    }
+    if content.is_empty() {
+        content = "Resource is empty.".to_string();
+    }
+    content
+}
+
+/// Turn the messages of an expanded MCP prompt into user input.
+fn prompt_to_input_items(result: mcp_types::GetPromptResult) -> Vec<InputItem> {
+    use mcp_types::ContentBlock;
+    use mcp_types::EmbeddedResourceResource;
+
+    result
+        .messages
+        .into_iter()
+        .filter_map(|message| match message.content {
+            ContentBlock::TextContent(text) => Some(InputItem::Text { text: text.text }),
+            ContentBlock::ImageContent(image) => Some(InputItem::Image {
+                image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+            }),
+            ContentBlock::EmbeddedResource(embedded) => match embedded.resource {
+                EmbeddedResourceResource::TextResourceContents(text) => {
+                    Some(InputItem::Text { text: text.text })
+                }
+                EmbeddedResourceResource::BlobResourceContents(blob) => Some(InputItem::Text {
+                    text: format!("[binary resource {}]", blob.uri),
+                }),
+            },
+            ContentBlock::ResourceLink(link) => Some(InputItem::Text {
+                text: format!("[resource {}]", link.uri),
+            }),
+            ContentBlock::AudioContent(_) => None,
+        })
+        .collect()
+}
+
+/// JSON-RPC error codes used when answering MCP server requests.
+const MCP_USER_REJECTED_ERROR_CODE: i64 = -1;
+const MCP_METHOD_NOT_FOUND_ERROR_CODE: i64 = -32601;
+const MCP_INVALID_PARAMS_ERROR_CODE: i64 = -32602;
+const MCP_INTERNAL_ERROR_CODE: i64 = -32603;
+
+/// Instructions for sampling requests that bring no system prompt.
+const MCP_SAMPLING_INSTRUCTIONS: &str =
+    "You are answering a request from a tool. Reply to the last message directly.";
+
+/// Answer the requests MCP servers send for as long as the session lives.
+async fn handle_mcp_server_requests(
+    sess: Weak<Session>,
+    sub_id: String,
+    mut requests: mpsc::UnboundedReceiver<McpServerRequest>,
+) {
+    while let Some((server, request)) = requests.recv().await {
+        // Sampling waits on the user and the model; keep serving meanwhile.
+        tokio::spawn(handle_mcp_server_request(
+            sess.clone(),
+            sub_id.clone(),
+            server,
+            request,
+        ));
+    }
+}
+
+async fn handle_mcp_server_request(
+    sess: Weak<Session>,
+    sub_id: String,
+    server: String,
+    request: JSONRPCRequest,
+) {
+    let JSONRPCRequest {
+        id, method, params, ..
+    } = request;
+    let params = params.unwrap_or(Value::Null);
+    let reply = match method.as_str() {
+        ElicitRequest::METHOD => match serde_json::from_value::<ElicitRequestParams>(params) {
+            Ok(params) => {
+                let Some(session) = sess.upgrade() else {
+                    return;
+                };
+                match request_mcp_elicitation(&session, &sub_id, &server, id.clone(), params).await
+                {
+                    Some(result) => Ok(result),
+                    // Answered later through `Op::McpElicitationResponse`.
+                    None => return,
+                }
+            }
+            Err(e) => Err((
+                MCP_INVALID_PARAMS_ERROR_CODE,
+                format!("invalid {method} params: {e}"),
+            )),
+        },
+        CreateMessageRequest::METHOD => {
+            match serde_json::from_value::<CreateMessageRequestParams>(params) {
+                Ok(params) => run_mcp_sampling(&sess, &sub_id, &server, id.clone(), params).await,
+                Err(e) => Err((
+                    MCP_INVALID_PARAMS_ERROR_CODE,
+                    format!("invalid {method} params: {e}"),
+                )),
+            }
+        }
+        _ => Err((
+            MCP_METHOD_NOT_FOUND_ERROR_CODE,
+            format!("client does not handle `{method}` requests"),
+        )),
+    };
+
+    let Some(session) = sess.upgrade() else {
+        return;
+    };
+    let manager = &session.mcp_connection_manager;
+    let sent = match reply {
+        Ok(result) => manager.send_response(&server, id, result).await,
+        Err((code, message)) => manager.send_error_response(&server, id, code, message).await,
+    };
+    if let Err(e) = sent {
+        warn!("failed to answer {method} request from MCP server `{server}`: {e:#}");
+    }
+}
+
+/// Ask the user for the input `server` requests, unless the configured policy
+/// answers for them. Returns `None` when the front-end will answer.
+async fn request_mcp_elicitation(
+    sess: &Session,
+    sub_id: &str,
+    server: &str,
+    request_id: RequestId,
+    params: ElicitRequestParams,
+) -> Option<Value> {
+    let action = match sess.mcp_requests.elicitation {
+        McpElicitationPolicy::Ask => {
+            let event = Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::McpElicitationRequest(McpElicitationRequestEvent {
+                    server: server.to_string(),
+                    request_id,
+                    message: params.message,
+                    requested_schema: params.requested_schema,
+                }),
+            };
+            sess.send_event(event).await;
+            return None;
+        }
+        McpElicitationPolicy::Decline => ElicitationAction::Decline,
+        McpElicitationPolicy::Cancel => ElicitationAction::Cancel,
+    };
+    Some(elicit_result(action, None))
+}
+
+fn elicit_result(action: ElicitationAction, content: Option<Value>) -> Value {
+    ElicitResult {
+        action: action.as_str().to_string(),
+        content,
+    }
+    .into()
+}
+
+/// Complete the conversation `server` sent with the session's model, once the
+/// user (or the configured policy) approves. The output is capped at
+/// `sampling_max_tokens`.
+async fn run_mcp_sampling(
+    sess: &Weak<Session>,
+    sub_id: &str,
+    server: &str,
+    request_id: RequestId,
+    params: CreateMessageRequestParams,
+) -> Result<Value, (i64, String)> {
+    let session_ended = || (MCP_INTERNAL_ERROR_CODE, "session ended".to_string());
+    let session = sess.upgrade().ok_or_else(session_ended)?;
+    let max_tokens = u32::try_from(params.max_tokens.max(1))
+        .unwrap_or(u32::MAX)
+        .min(session.mcp_requests.sampling_max_tokens);
+
+    let approved = match session.mcp_requests.sampling {
+        McpSamplingPolicy::Approve => true,
+        McpSamplingPolicy::Deny => false,
+        McpSamplingPolicy::Ask => {
+            let (tx, rx) = oneshot::channel();
+            {
+                let mut state = session.state.lock().unwrap();
+                state
+                    .pending_mcp_sampling
+                    .insert((server.to_string(), request_id.clone()), tx);
+            }
+            let event = Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::McpSamplingRequest(McpSamplingRequestEvent {
+                    server: server.to_string(),
+                    request_id,
+                    messages: params.messages.clone(),
+                    system_prompt: params.system_prompt.clone(),
+                    max_tokens,
+                }),
+            };
+            session.send_event(event).await;
+            // Do not keep the session alive while the user decides.
+            drop(session);
+            matches!(
+                rx.await,
+                Ok(ReviewDecision::Approved | ReviewDecision::ApprovedForSession)
+            )
+        }
+    };
+    if !approved {
+        return Err((
+            MCP_USER_REJECTED_ERROR_CODE,
+            "User rejected sampling request".to_string(),
+        ));
+    }
+
+    let session = sess.upgrade().ok_or_else(session_ended)?;
+    sample_from_model(&session, params, max_tokens)
+        .await
+        .map(Value::from)
+        .map_err(|e| (MCP_INTERNAL_ERROR_CODE, format!("sampling failed: {e}")))
+}
+
+async fn sample_from_model(
+    sess: &Session,
+    params: CreateMessageRequestParams,
+    max_tokens: u32,
+) -> CodexResult<CreateMessageResult> {
+    let prompt = Prompt {
+        input: params
+            .messages
+            .into_iter()
+            .filter_map(sampling_message_to_response_item)
+            .collect(),
+        store: !sess.disable_response_storage,
+        base_instructions_override: Some(
+            params
+                .system_prompt
+                .unwrap_or_else(|| MCP_SAMPLING_INSTRUCTIONS.to_string()),
+        ),
+        max_output_tokens: Some(max_tokens),
+        ..Default::default()
+    };
+
+    let mut stream = sess.client.clone().stream(&prompt).await?;
+    let mut text = String::new();
+    while let Some(event) = stream.next().await {
+        match event? {
+            ResponseEvent::OutputItemDone(ResponseItem::Message { content, .. }) => {
+                for item in content {
+                    if let ContentItem::OutputText { text: delta } = item {
+                        text.push_str(&delta);
+                    }
+                }
+            }
+            ResponseEvent::Completed { .. } => break,
+            _ => {}
+        }
+    }
+
+    Ok(CreateMessageResult {
+        content: CreateMessageResultContent::TextContent(TextContent {
+            annotations: None,
+            text,
+            r#type: "text".to_string(),
+        }),
+        model: sess.client.get_model(),
+        role: Role::Assistant,
+        stop_reason: Some("endTurn".to_string()),
+    })
+}
+
+/// A sampling message as model input. Audio is not supported and dropped.
+fn sampling_message_to_response_item(message: SamplingMessage) -> Option<ResponseItem> {
+    let (role, content) = match (message.role, message.content) {
+        (Role::User, SamplingMessageContent::TextContent(text)) => {
+            ("user", ContentItem::InputText { text: text.text })
+        }
+        (Role::Assistant, SamplingMessageContent::TextContent(text)) => {
+            ("assistant", ContentItem::OutputText { text: text.text })
+        }
+        (Role::User, SamplingMessageContent::ImageContent(image)) => (
+            "user",
+            ContentItem::InputImage {
+                image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+                detail: None,
+            },
+        ),
+        _ => return None,
+    };
+    Some(ResponseItem::Message {
+        id: None,
+        role: role.to_string(),
+        content: vec![content],
+    })
+}
+
+async fn handle_browser_cleanup(
+    sess: &Session,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let sess_clone = sess;
+    let call_id_clone = call_id.clone();
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "browser_cleanup".to_string(),
+        Some(serde_json::json!({})),
+        || async move {
+            if let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await {
+                match browser_manager.cleanup().await {
+                    Ok(_) => ResponseInputItem::FunctionCallOutput {
+                        call_id: call_id_clone,
+                        output: FunctionCallOutputPayload { content: "Browser cleanup completed".to_string(), success: Some(true) },
+                    },
+                    Err(e) => ResponseInputItem::FunctionCallOutput {
+                        call_id: call_id_clone,
+                        output: FunctionCallOutputPayload { content: format!("Cleanup failed: {}", e), success: Some(false) },
+                    },
+                }
+            } else {
+                ResponseInputItem::FunctionCallOutput {
+                    call_id: call_id_clone,
+                    output: FunctionCallOutputPayload { content: "Browser is not initialized. Use browser_open to start the browser.".to_string(), success: Some(false) },
                 }
             }
         }
@@ -3771,6 +4775,7 @@ fn to_exec_params(params: ShellToolCallParams, sess: &Session) -> ExecParams {
         env: create_env(&sess.shell_environment_policy),
         with_escalated_permissions: params.with_escalated_permissions,
         justification: params.justification,
+        overlay: None,
     }
 }
 
@@ -4631,6 +5636,7 @@ async fn handle_container_exec_with_params(
         MaybeApplyPatchVerified::NotApplyPatch => None,
     };
 
+    let sandbox_policy = sess.effective_sandbox_policy();
     let (params, safety, command_for_display) = match &apply_patch_exec {
         Some(ApplyPatchExec {
             action: ApplyPatchAction { patch, cwd, .. },
@@ -4660,6 +5666,7 @@ async fn handle_container_exec_with_params(
                 env: HashMap::new(),
                 with_escalated_permissions: params.with_escalated_permissions,
                 justification: params.justification.clone(),
+                overlay: None,
             };
             let safety = if *user_explicitly_approved_this_action {
                 SafetyCheck::AutoApprove {
@@ -4668,7 +5675,7 @@ async fn handle_container_exec_with_params(
             } else {
                 assess_safety_for_untrusted_command(
                     sess.approval_policy,
-                    &sess.sandbox_policy,
+                    &sandbox_policy,
                     params.with_escalated_permissions.unwrap_or(false),
                 )
             };
@@ -4684,7 +5691,7 @@ async fn handle_container_exec_with_params(
                 assess_command_safety(
                     &params.command,
                     sess.approval_policy,
-                    &sess.sandbox_policy,
+                    &sandbox_policy,
                     &state.approved_commands,
                     params.with_escalated_permissions.unwrap_or(false),
                 )
@@ -4704,10 +5711,11 @@ async fn handle_container_exec_with_params(
                     params.command.clone(),
                     params.cwd.clone(),
                     params.justification.clone(),
+                    None,
                 )
                 .await;
             match rx_approve.await.unwrap_or_default() {
-                ReviewDecision::Approved => (),
+                ReviewDecision::Approved | ReviewDecision::ApprovedWithoutSandbox => (),
                 ReviewDecision::ApprovedForSession => {
                     sess.add_approved_command(params.command.clone());
                 }
@@ -4754,7 +5762,23 @@ async fn handle_container_exec_with_params(
         ),
     };
 
-    let params = maybe_run_with_user_profile(params, sess);
+    let mut params = maybe_run_with_user_profile(params, sess);
+    if exec_command_context.apply_patch.is_none() {
+        match sess.begin_exec_snapshot(&sub_id, &params.cwd).await {
+            Ok(overlay) => params.overlay = overlay,
+            Err(message) => {
+                return ResponseInputItem::FunctionCallOutput {
+                    call_id,
+                    output: FunctionCallOutputPayload {
+                        content: message,
+                        success: Some(false),
+                    },
+                };
+            }
+        }
+    }
+    // The approved retry without the sandbox runs on the same overlay.
+    let overlay = params.overlay.clone();
     let output_result = sess
         .run_exec_with_events(
             turn_diff_tracker,
@@ -4762,7 +5786,7 @@ async fn handle_container_exec_with_params(
             ExecInvokeArgs {
                 params: params.clone(),
                 sandbox_type,
-                sandbox_policy: &sess.sandbox_policy,
+                sandbox_policy: &sandbox_policy,
                 codex_linux_sandbox_exe: &sess.codex_linux_sandbox_exe,
                 stdout_stream: Some(StdoutStream {
                     sub_id: sub_id.clone(),
@@ -4773,7 +5797,7 @@ async fn handle_container_exec_with_params(
         )
         .await;
 
-    match output_result {
+    let response = match output_result {
         Ok(output) => {
             let ExecToolCallOutput { exit_code, .. } = &output;
 
@@ -4805,7 +5829,13 @@ async fn handle_container_exec_with_params(
                 success: None,
             },
         },
+    };
+
+    if let Some(overlay) = overlay {
+        sess.finish_exec_snapshot(&sub_id, &call_id, command_for_display, overlay)
+            .await;
     }
+    response
 }
 
 async fn handle_sandbox_error(
@@ -4820,6 +5850,17 @@ async fn handle_sandbox_error(
     let sub_id = exec_command_context.sub_id.clone();
     let cwd = exec_command_context.cwd.clone();
 
+    let denials = match &error {
+        SandboxErr::Denied(output) => output.sandbox_denials.clone(),
+        _ => Vec::new(),
+    };
+    // Only a workspace-write sandbox can be widened by a single root; for
+    // anything else the user can still choose to run without the sandbox.
+    let grant_root = match &sess.sandbox_policy {
+        SandboxPolicy::WorkspaceWrite { .. } => suggest_writable_root(&denials),
+        SandboxPolicy::ReadOnly | SandboxPolicy::DangerFullAccess => None,
+    };
+
     // Early out if either the user never wants to be asked for approval, or
     // we're letting the model manage escalation requests. Otherwise, continue
     match sess.approval_policy {
@@ -4828,7 +5869,8 @@ async fn handle_sandbox_error(
                 call_id,
                 output: FunctionCallOutputPayload {
                     content: format!(
-                        "failed in sandbox {sandbox_type:?} with execution error: {error}"
+                        "failed in sandbox {sandbox_type:?} with execution error: {error}{}",
+                        format_sandbox_denials(&denials, grant_root.as_deref())
                     ),
                     success: Some(false),
                 },
@@ -4858,42 +5900,81 @@ async fn handle_sandbox_error(
     // include additional metadata on the command to indicate whether non-zero
     // exit codes merit a retry.
 
-    // For now, we categorically ask the user to retry without sandbox and
-    // emit the raw error as a background event.
+    // When the denials point at a single directory, offer to make just that
+    // directory writable. Otherwise, we ask the user to retry without sandbox.
+    // Either way, emit the raw error as a background event.
     sess.notify_background_event(&sub_id, format!("Execution failed: {error}"))
         .await;
 
+    let reason = match (&grant_root, denials.first()) {
+        (Some(root), Some(first)) => format!(
+            "sandbox blocked {first}{}; allow writes under {} and retry?",
+            match denials.len() {
+                1 => String::new(),
+                n => format!(" and {} more", n - 1),
+            },
+            root.display()
+        ),
+        _ => "command failed; retry without sandbox?".to_string(),
+    };
     let rx_approve = sess
         .request_command_approval(
             sub_id.clone(),
             call_id.clone(),
             params.command.clone(),
             cwd.clone(),
-            Some("command failed; retry without sandbox?".to_string()),
+            Some(reason),
+            grant_root.clone(),
         )
         .await;
 
-    match rx_approve.await.unwrap_or_default() {
-        ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
-            // Persist this command as pre‑approved for the
-            // remainder of the session so future
-            // executions skip the sandbox directly.
-            // TODO(ragona): Isn't this a bug? It always saves the command in an | fork?
-            sess.add_approved_command(params.command.clone());
-            // Inform UI we are retrying without sandbox.
-            sess.notify_background_event(&sub_id, "retrying command without sandbox")
-                .await;
+    let decision = rx_approve.await.unwrap_or_default();
+    match decision {
+        ReviewDecision::Approved
+        | ReviewDecision::ApprovedForSession
+        | ReviewDecision::ApprovedWithoutSandbox => {
+            // The user may turn down the suggested root and still retry
+            // without the sandbox.
+            let grant_root =
+                grant_root.filter(|_| decision != ReviewDecision::ApprovedWithoutSandbox);
+            let (retry_sandbox_type, retry_policy) = match grant_root {
+                Some(root) => {
+                    if decision == ReviewDecision::ApprovedForSession {
+                        sess.grant_writable_root(root.clone());
+                    }
+                    sess.notify_background_event(
+                        &sub_id,
+                        format!("retrying command with write access to {}", root.display()),
+                    )
+                    .await;
+                    let policy = sess
+                        .effective_sandbox_policy()
+                        .with_additional_writable_roots(&[root]);
+                    (sandbox_type, policy)
+                }
+                None => {
+                    // Persist this command as pre‑approved for the
+                    // remainder of the session so future
+                    // executions skip the sandbox directly.
+                    // TODO(ragona): Isn't this a bug? It always saves the command in an | fork?
+                    sess.add_approved_command(params.command.clone());
+                    // Inform UI we are retrying without sandbox.
+                    sess.notify_background_event(&sub_id, "retrying command without sandbox")
+                        .await;
+                    // This is an escalated retry; the policy will not be
+                    // examined and the sandbox has been set to `None`.
+                    (SandboxType::None, sess.effective_sandbox_policy())
+                }
+            };
 
-            // This is an escalated retry; the policy will not be
-            // examined and the sandbox has been set to `None`.
             let retry_output_result = sess
                 .run_exec_with_events(
                     turn_diff_tracker,
                     exec_command_context.clone(),
                     ExecInvokeArgs {
                         params,
-                        sandbox_type: SandboxType::None,
-                        sandbox_policy: &sess.sandbox_policy,
+                        sandbox_type: retry_sandbox_type,
+                        sandbox_policy: &retry_policy,
                         codex_linux_sandbox_exe: &sess.codex_linux_sandbox_exe,
                         stdout_stream: Some(StdoutStream {
                             sub_id: sub_id.clone(),
@@ -4941,24 +6022,606 @@ async fn handle_sandbox_error(
     }
 }
 
-/// Exec output is a pre-serialized JSON payload
-fn format_exec_output(exec_output: ExecToolCallOutput) -> String {
-    let ExecToolCallOutput {
-        exit_code,
-        stdout,
-        stderr,
-        duration,
-    } = exec_output;
+async fn handle_shell_session(
+    sess: &Session,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let params = match serde_json::from_str::<ShellSessionToolCallParams>(&arguments) {
+        Ok(params) => params,
+        Err(e) => {
+            return ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: FunctionCallOutputPayload {
+                    content: format!("failed to parse function arguments: {e}"),
+                    success: None,
+                },
+            };
+        }
+    };
 
-    #[derive(Serialize)]
-    struct ExecMetadata {
-        exit_code: i32,
-        duration_seconds: f32,
+    match params.action.as_str() {
+        "exec" => {
+            handle_shell_session_exec(sess, turn_diff_tracker, params, sub_id, call_id).await
+        }
+        "open" | "read" | "close" => {
+            let parameters = serde_json::from_str(&arguments).ok();
+            let sub_id_clone = sub_id.clone();
+            let call_id_clone = call_id.clone();
+            execute_custom_tool(
+                sess,
+                &sub_id,
+                call_id,
+                "shell_session".to_string(),
+                parameters,
+                || async move {
+                    let result = match params.action.as_str() {
+                        "open" => shell_session_open(sess, params, sub_id_clone, &call_id_clone).await,
+                        "read" => shell_session_read(sess, params, &sub_id_clone).await,
+                        _ => shell_session_close(sess, params, &sub_id_clone).await,
+                    };
+                    let (content, success) = match result {
+                        Ok(content) => (content, true),
+                        Err(content) => (content, false),
+                    };
+                    ResponseInputItem::FunctionCallOutput {
+                        call_id: call_id_clone,
+                        output: FunctionCallOutputPayload {
+                            content,
+                            success: Some(success),
+                        },
+                    }
+                },
+            )
+            .await
+        }
+        other => ResponseInputItem::FunctionCallOutput {
+            call_id,
+            output: FunctionCallOutputPayload {
+                content: format!(
+                    "unknown shell_session action '{other}'; expected 'open', 'exec', 'read' or 'close'"
+                ),
+                success: None,
+            },
+        },
     }
+}
 
-    #[derive(Serialize)]
-    struct ExecOutput<'a> {
-        output: &'a str,
+async fn shell_session_open(
+    sess: &Session,
+    params: ShellSessionToolCallParams,
+    sub_id: String,
+    call_id: &str,
+) -> Result<String, String> {
+    let cwd = sess.resolve_path(params.workdir.clone());
+    let sandbox_policy = sess.effective_sandbox_policy();
+
+    // The whole shell runs under one sandbox, so decide it up front the same
+    // way an untrusted `shell` command would be treated.
+    let escalate = params.with_escalated_permissions.unwrap_or(false);
+    let sandbox_type = match assess_safety_for_untrusted_command(
+        sess.approval_policy,
+        &sandbox_policy,
+        escalate,
+    ) {
+        SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
+        SafetyCheck::AskUser => {
+            let reason = params.justification.clone().unwrap_or_else(|| {
+                if escalate {
+                    "open a persistent shell session without sandbox restrictions".to_string()
+                } else {
+                    "open a persistent shell session".to_string()
+                }
+            });
+            let rx_approve = sess
+                .request_command_approval(
+                    sub_id.clone(),
+                    call_id.to_string(),
+                    vec!["shell_session".to_string(), "open".to_string()],
+                    cwd.clone(),
+                    Some(reason),
+                    None,
+                )
+                .await;
+            // Approving the session only lifts the sandbox when escalation was
+            // asked for (or the policy has none); otherwise the shell stays
+            // sandboxed wherever the platform supports it.
+            match rx_approve.await.unwrap_or_default() {
+                ReviewDecision::Approved
+                | ReviewDecision::ApprovedForSession
+                | ReviewDecision::ApprovedWithoutSandbox => {
+                    if escalate || matches!(sandbox_policy, SandboxPolicy::DangerFullAccess) {
+                        SandboxType::None
+                    } else {
+                        get_platform_sandbox().unwrap_or(SandboxType::None)
+                    }
+                }
+                ReviewDecision::Denied | ReviewDecision::Abort => {
+                    return Err("shell session rejected by user".to_string());
+                }
+            }
+        }
+        SafetyCheck::Reject { reason } => {
+            return Err(format!("shell session rejected: {reason}"));
+        }
+    };
+
+    let overlay = sess.begin_exec_snapshot(&sub_id, &cwd).await?;
+    let session = sess
+        .shell_sessions
+        .open(
+            cwd,
+            create_env(&sess.shell_environment_policy),
+            sandbox_type,
+            &sandbox_policy,
+            &sess.codex_linux_sandbox_exe,
+            overlay,
+        )
+        .await
+        .map_err(|e| format!("failed to open shell session: {e}"))?;
+
+    Ok(serde_json::json!({
+        "session_id": session.id,
+        "cwd": session.cwd,
+        "sandboxed": session.sandbox_type != SandboxType::None,
+    })
+    .to_string())
+}
+
+async fn shell_session_read(
+    sess: &Session,
+    params: ShellSessionToolCallParams,
+    sub_id: &str,
+) -> Result<String, String> {
+    let id = params
+        .session_id
+        .ok_or_else(|| "session_id is required for action=read".to_string())?;
+    let session = sess
+        .shell_sessions
+        .get(&id)
+        .await
+        .ok_or_else(|| format!("unknown shell session: {id}"))?;
+    let timeout = Duration::from_millis(params.timeout_ms.unwrap_or(DEFAULT_READ_TIMEOUT_MS));
+    let output = session
+        .read(timeout, None)
+        .await
+        .map_err(|e| format!("failed to read from shell session {id}: {e}"))?;
+    if output.shell_exited {
+        sess.close_shell_session(sub_id, &id).await;
+    }
+    Ok(format_shell_session_output(&id, &output))
+}
+
+async fn shell_session_close(
+    sess: &Session,
+    params: ShellSessionToolCallParams,
+    sub_id: &str,
+) -> Result<String, String> {
+    let id = params
+        .session_id
+        .ok_or_else(|| "session_id is required for action=close".to_string())?;
+    if sess.close_shell_session(sub_id, &id).await {
+        Ok(format!("closed shell session {id}"))
+    } else {
+        Err(format!("unknown shell session: {id}"))
+    }
+}
+
+async fn handle_shell_session_exec(
+    sess: &Session,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    params: ShellSessionToolCallParams,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let failure = |call_id: String, content: String| ResponseInputItem::FunctionCallOutput {
+        call_id,
+        output: FunctionCallOutputPayload {
+            content,
+            success: None,
+        },
+    };
+
+    let Some(id) = params.session_id.clone() else {
+        return failure(call_id, "session_id is required for action=exec".to_string());
+    };
+    let Some(command) = params.command.clone().filter(|c| !c.trim().is_empty()) else {
+        return failure(call_id, "command is required for action=exec".to_string());
+    };
+    let Some(session) = sess.shell_sessions.get(&id).await else {
+        return failure(call_id, format!("unknown shell session: {id}"));
+    };
+
+    // Apply the same approval checks as the `shell` tool. The command itself
+    // always runs inside whatever sandbox the session was opened with.
+    let command_for_display = vec!["bash".to_string(), "-lc".to_string(), command.clone()];
+    let sandbox_policy = sess.effective_sandbox_policy();
+    let safety = {
+        let state = sess.state.lock().unwrap();
+        assess_command_safety(
+            &command_for_display,
+            sess.approval_policy,
+            &sandbox_policy,
+            &state.approved_commands,
+            false,
+        )
+    };
+    // A command that would normally be sandboxed runs without one in an
+    // unsandboxed session, so treat it like an escalated `shell` call rather
+    // than letting the approval for `open` cover it.
+    let safety = match safety {
+        SafetyCheck::AutoApprove { sandbox_type }
+            if sandbox_type != SandboxType::None && session.sandbox_type == SandboxType::None =>
+        {
+            if sess.approval_policy == AskForApproval::Never {
+                SafetyCheck::Reject {
+                    reason: "shell session is not sandboxed".to_string(),
+                }
+            } else {
+                SafetyCheck::AskUser
+            }
+        }
+        other => other,
+    };
+    match safety {
+        SafetyCheck::AutoApprove { .. } => {}
+        SafetyCheck::AskUser => {
+            let rx_approve = sess
+                .request_command_approval(
+                    sub_id.clone(),
+                    call_id.clone(),
+                    command_for_display.clone(),
+                    session.cwd.clone(),
+                    None,
+                    None,
+                )
+                .await;
+            match rx_approve.await.unwrap_or_default() {
+                ReviewDecision::Approved | ReviewDecision::ApprovedWithoutSandbox => (),
+                ReviewDecision::ApprovedForSession => {
+                    sess.add_approved_command(command_for_display.clone());
+                }
+                ReviewDecision::Denied | ReviewDecision::Abort => {
+                    return failure(call_id, "exec command rejected by user".to_string());
+                }
+            }
+        }
+        SafetyCheck::Reject { reason } => {
+            return failure(call_id, format!("exec command rejected: {reason}"));
+        }
+    }
+
+    sess.on_exec_command_begin(
+        turn_diff_tracker,
+        ExecCommandContext {
+            sub_id: sub_id.clone(),
+            call_id: call_id.clone(),
+            command_for_display,
+            cwd: session.cwd.clone(),
+            apply_patch: None,
+        },
+    )
+    .await;
+
+    let timeout = Duration::from_millis(params.timeout_ms.unwrap_or(DEFAULT_EXEC_TIMEOUT_MS));
+    let stdout_stream = StdoutStream {
+        sub_id: sub_id.clone(),
+        call_id: call_id.clone(),
+        tx_event: sess.tx_event.clone(),
+    };
+    let (end_output, response) = match session.exec(&command, timeout, Some(stdout_stream)).await {
+        Ok(output) => {
+            if output.shell_exited {
+                sess.close_shell_session(&sub_id, &id).await;
+            }
+            let stderr = match output.exit_code {
+                Some(_) => String::new(),
+                None if output.shell_exited => format!("shell session {id} exited"),
+                None => format!("still running in shell session {id}; use action=read to collect the rest"),
+            };
+            let end_output = ExecToolCallOutput {
+                exit_code: output.exit_code.unwrap_or(-1),
+                stdout: StreamOutput::new(output.output.clone()),
+                stderr: StreamOutput::new(stderr),
+                duration: output.duration,
+                sandbox_denials: Vec::new(),
+            };
+            let response = ResponseInputItem::FunctionCallOutput {
+                call_id: call_id.clone(),
+                output: FunctionCallOutputPayload {
+                    content: format_shell_session_output(&id, &output),
+                    success: Some(output.exit_code == Some(0)),
+                },
+            };
+            (end_output, response)
+        }
+        Err(e) => {
+            let end_output = ExecToolCallOutput {
+                exit_code: -1,
+                stdout: StreamOutput::new(String::new()),
+                stderr: StreamOutput::new(e.to_string()),
+                duration: Duration::default(),
+                sandbox_denials: Vec::new(),
+            };
+            (end_output, failure(call_id.clone(), format!("execution error: {e}")))
+        }
+    };
+
+    sess.on_exec_command_end(turn_diff_tracker, &sub_id, &call_id, &end_output, false)
+        .await;
+    response
+}
+
+async fn handle_background_process(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let params = match serde_json::from_str::<BackgroundProcessToolCallParams>(&arguments) {
+        Ok(params) => params,
+        Err(e) => {
+            return ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: FunctionCallOutputPayload {
+                    content: format!("failed to parse function arguments: {e}"),
+                    success: None,
+                },
+            };
+        }
+    };
+
+    let parameters = serde_json::from_str(&arguments).ok();
+    let sub_id_clone = sub_id.clone();
+    let call_id_clone = call_id.clone();
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "background_process".to_string(),
+        parameters,
+        || async move {
+            let result = match params.action.as_str() {
+                "start" => background_process_start(sess, params, sub_id_clone.clone(), &call_id_clone).await,
+                "tail" => background_process_tail(sess, params),
+                "wait" => background_process_wait(sess, params).await,
+                "stop" => background_process_stop(sess, params, sub_id_clone.clone()).await,
+                "list" => Ok(serde_json::json!({
+                    "processes": sess
+                        .background_processes
+                        .list()
+                        .iter()
+                        .map(|p| p.info())
+                        .collect::<Vec<_>>(),
+                })
+                .to_string()),
+                other => Err(format!(
+                    "unknown background_process action '{other}'; expected 'start', 'tail', 'wait', 'stop' or 'list'"
+                )),
+            };
+            sess.collect_background_exec_snapshots(&sub_id_clone).await;
+            let (content, success) = match result {
+                Ok(content) => (content, true),
+                Err(content) => (content, false),
+            };
+            ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone,
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(success),
+                },
+            }
+        },
+    )
+    .await
+}
+
+fn background_process_by_id(
+    sess: &Session,
+    params: &BackgroundProcessToolCallParams,
+) -> Result<Arc<BackgroundProcess>, String> {
+    let id = params
+        .process_id
+        .as_deref()
+        .ok_or_else(|| format!("process_id is required for action={}", params.action))?;
+    sess.background_processes
+        .get(id)
+        .ok_or_else(|| format!("unknown background process: {id}"))
+}
+
+async fn background_process_start(
+    sess: &Session,
+    params: BackgroundProcessToolCallParams,
+    sub_id: String,
+    call_id: &str,
+) -> Result<String, String> {
+    let command = params
+        .command
+        .clone()
+        .filter(|c| !c.trim().is_empty())
+        .ok_or_else(|| "command is required for action=start".to_string())?;
+    let cwd = sess.resolve_path(params.workdir.clone());
+    let sandbox_policy = sess.effective_sandbox_policy();
+
+    // Same approval rules as a `shell` call running the command directly.
+    let command_for_display = vec!["bash".to_string(), "-lc".to_string(), command.clone()];
+    let safety = {
+        let state = sess.state.lock().unwrap();
+        assess_command_safety(
+            &command_for_display,
+            sess.approval_policy,
+            &sandbox_policy,
+            &state.approved_commands,
+            params.with_escalated_permissions.unwrap_or(false),
+        )
+    };
+    let sandbox_type = match safety {
+        SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
+        SafetyCheck::AskUser => {
+            let rx_approve = sess
+                .request_command_approval(
+                    sub_id.clone(),
+                    call_id.to_string(),
+                    command_for_display.clone(),
+                    cwd.clone(),
+                    params.justification.clone(),
+                    None,
+                )
+                .await;
+            match rx_approve.await.unwrap_or_default() {
+                ReviewDecision::Approved | ReviewDecision::ApprovedWithoutSandbox => {
+                    SandboxType::None
+                }
+                ReviewDecision::ApprovedForSession => {
+                    sess.add_approved_command(command_for_display);
+                    SandboxType::None
+                }
+                ReviewDecision::Denied | ReviewDecision::Abort => {
+                    return Err("background process rejected by user".to_string());
+                }
+            }
+        }
+        SafetyCheck::Reject { reason } => {
+            return Err(format!("background process rejected: {reason}"));
+        }
+    };
+
+    let overlay = sess.begin_exec_snapshot(&sub_id, &cwd).await?;
+    let process = sess
+        .background_processes
+        .start(
+            command,
+            cwd,
+            create_env(&sess.shell_environment_policy),
+            sandbox_type,
+            &sandbox_policy,
+            &sess.codex_linux_sandbox_exe,
+            sub_id.clone(),
+            overlay,
+        )
+        .await
+        .map_err(|e| format!("failed to start background process: {e}"))?;
+    sess.background_processes.send_update(sub_id).await;
+
+    Ok(format_background_process_output(
+        &process,
+        None,
+        vec![("sandboxed", (sandbox_type != SandboxType::None).into())],
+    ))
+}
+
+fn background_process_tail(
+    sess: &Session,
+    params: BackgroundProcessToolCallParams,
+) -> Result<String, String> {
+    let process = background_process_by_id(sess, &params)?;
+    let output = process.tail(params.lines.unwrap_or(DEFAULT_TAIL_LINES));
+    Ok(format_background_process_output(&process, Some(output), Vec::new()))
+}
+
+async fn background_process_wait(
+    sess: &Session,
+    params: BackgroundProcessToolCallParams,
+) -> Result<String, String> {
+    let process = background_process_by_id(sess, &params)?;
+    let timeout = Duration::from_millis(params.timeout_ms.unwrap_or(DEFAULT_WAIT_TIMEOUT_MS));
+
+    let mut extra = Vec::new();
+    let ready = match (params.port, params.pattern.as_deref()) {
+        (Some(port), None) => {
+            let host = params.host.as_deref().unwrap_or("127.0.0.1");
+            process.wait_for_port(host, port, timeout).await
+        }
+        (None, Some(pattern)) => {
+            let regex = regex_lite::Regex::new(pattern)
+                .map_err(|e| format!("invalid pattern {pattern:?}: {e}"))?;
+            match process.wait_for_pattern(&regex, 0, timeout).await {
+                Some(line) => {
+                    extra.push(("matched_line", line.into()));
+                    true
+                }
+                None => false,
+            }
+        }
+        _ => return Err("action=wait needs exactly one of port or pattern".to_string()),
+    };
+    extra.push(("ready", ready.into()));
+    if !ready && process.is_running() {
+        extra.push(("timed_out", true.into()));
+    }
+
+    // Include recent output so a failed wait can be diagnosed without a
+    // separate `tail` call.
+    let output = (!ready).then(|| process.tail(DEFAULT_TAIL_LINES));
+    Ok(format_background_process_output(&process, output, extra))
+}
+
+async fn background_process_stop(
+    sess: &Session,
+    params: BackgroundProcessToolCallParams,
+    sub_id: String,
+) -> Result<String, String> {
+    let id = params
+        .process_id
+        .as_deref()
+        .ok_or_else(|| "process_id is required for action=stop".to_string())?;
+    let process = sess
+        .background_processes
+        .stop(id)
+        .await
+        .ok_or_else(|| format!("unknown background process: {id}"))?;
+    if let Some(overlay) = process.take_overlay() {
+        let command = vec![process.command.clone()];
+        sess.finish_exec_snapshot(&sub_id, &process.id, command, overlay)
+            .await;
+    }
+    sess.background_processes.send_update(sub_id).await;
+    let output = process.tail(DEFAULT_TAIL_LINES);
+    Ok(format_background_process_output(&process, Some(output), Vec::new()))
+}
+
+/// Summarizes sandbox denials for the model so it can ask for the right
+/// escalation instead of retrying blindly.
+fn format_sandbox_denials(denials: &[SandboxDenial], grant_root: Option<&Path>) -> String {
+    if denials.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from("\n\nThe sandbox blocked:");
+    for denial in denials {
+        out.push_str(&format!("\n- {denial}"));
+    }
+    if let Some(root) = grant_root {
+        out.push_str(&format!(
+            "\nMaking {} writable would allow this command.",
+            root.display()
+        ));
+    }
+    out
+}
+
+/// Exec output is a pre-serialized JSON payload
+fn format_exec_output(exec_output: ExecToolCallOutput) -> String {
+    let ExecToolCallOutput {
+        exit_code,
+        stdout,
+        stderr,
+        duration,
+        sandbox_denials,
+    } = exec_output;
+
+    #[derive(Serialize)]
+    struct ExecMetadata {
+        exit_code: i32,
+        duration_seconds: f32,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        sandbox_denials: Vec<SandboxDenial>,
+    }
+
+    #[derive(Serialize)]
+    struct ExecOutput<'a> {
+        output: &'a str,
         metadata: ExecMetadata,
     }
 
@@ -4980,6 +6643,7 @@ fn format_exec_output(exec_output: ExecToolCallOutput) -> String {
         metadata: ExecMetadata {
             exit_code,
             duration_seconds,
+            sandbox_denials,
         },
     };
 
@@ -5153,67 +6817,70 @@ fn add_pending_screenshot(sess: &Session, screenshot_path: PathBuf, url: String)
     // Do not queue screenshots for next turn anymore; we inject fresh per-turn.
     tracing::info!("Captured screenshot; updating UI and using per-turn injection");
 
-    // Also send an immediate event to update the TUI display
-    let event = Event {
-        id: "browser_screenshot".to_string(),
-        msg: EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
-            screenshot_path,
-            url,
-        }),
-    };
-
-    // Send event asynchronously to avoid blocking
+    // Send event asynchronously to avoid blocking; the active tab lets the
+    // TUI show which tab the screenshot belongs to.
     let tx_event = sess.tx_event.clone();
     tokio::spawn(async move {
+        let tab = match codex_browser::global::get_browser_manager().await {
+            Some(manager) => manager.active_tab().await.map(|t| BrowserTabSummary {
+                id: t.id,
+                index: t.index,
+                count: t.count,
+            }),
+            None => None,
+        };
+        let event = Event {
+            id: "browser_screenshot".to_string(),
+            msg: EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
+                screenshot_path,
+                url,
+                tab,
+            }),
+        };
         if let Err(e) = tx_event.send(event).await {
             tracing::error!("Failed to send browser screenshot update event: {}", e);
         }
     });
 }
 
-/// Consume pending screenshots and return them as ResponseInputItems
-#[allow(dead_code)]
-fn consume_pending_screenshots(sess: &Session) -> Vec<ResponseInputItem> {
+/// Consume images queued by browser tools and return them as ephemeral
+/// status items for the next request.
+fn consume_pending_screenshots(sess: &Session) -> Vec<ResponseItem> {
     let mut pending = sess.pending_browser_screenshots.lock().unwrap();
     let screenshots = pending.drain(..).collect::<Vec<_>>();
 
     screenshots
         .into_iter()
-        .map(|path| {
-            let metadata = format!(
-                "[EPHEMERAL:browser_screenshot] Browser screenshot at {}",
-                chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
-            );
+        .map(|(path, label)| {
+            let metadata = format!("[EPHEMERAL:browser_screenshot] {}", label);
 
             // Read the screenshot file and create an ephemeral image
-            match std::fs::read(&path) {
+            let content = match std::fs::read(&path) {
                 Ok(bytes) => {
                     let mime = mime_guess::from_path(&path)
                         .first()
                         .map(|m| m.to_string())
                         .unwrap_or_else(|| "image/png".to_string());
                     let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
-
-                    ResponseInputItem::Message {
-                        role: "user".to_string(),
-                        content: vec![
-                            ContentItem::InputText { text: metadata },
-                            ContentItem::InputImage {
-                                image_url: format!("data:{mime};base64,{encoded}"),
-                                detail: Some("high".to_string()),
-                            },
-                        ],
-                    }
+                    vec![
+                        ContentItem::InputText { text: metadata },
+                        ContentItem::InputImage {
+                            image_url: format!("data:{mime};base64,{encoded}"),
+                            detail: Some("high".to_string()),
+                        },
+                    ]
                 }
                 Err(e) => {
                     tracing::error!("Failed to read screenshot {}: {}", path.display(), e);
-                    ResponseInputItem::Message {
-                        role: "user".to_string(),
-                        content: vec![ContentItem::InputText {
-                            text: format!("Failed to load browser screenshot: {}", e),
-                        }],
-                    }
+                    vec![ContentItem::InputText {
+                        text: format!("Failed to load browser screenshot: {}", e),
+                    }]
                 }
+            };
+            ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content,
             }
         })
         .collect()
@@ -5277,13 +6944,143 @@ where
     result
 }
 
-async fn handle_browser_open(
-    sess: &Session,
-    arguments: String,
-    sub_id: String,
-    call_id: String,
-) -> ResponseInputItem {
-    // Parse arguments as JSON for the event
+/// Hands the locale, timezone, geolocation and color scheme set in
+/// `[browser]` to the browser. Unset values keep the host-derived defaults.
+async fn configure_browser_environment(configured: &crate::config_types::BrowserConfig) {
+    if configured.locale.is_none()
+        && configured.timezone.is_none()
+        && configured.accept_language.is_none()
+        && configured.geolocation.is_none()
+        && configured.color_scheme.is_none()
+    {
+        return;
+    }
+    let configured = configured.clone();
+    codex_browser::global::configure_environment(move |config| {
+        if let Some(locale) = &configured.locale {
+            config.locale = Some(locale.clone());
+            config.accept_language = Some(codex_browser::environment::accept_language_for(locale));
+        }
+        if let Some(timezone) = &configured.timezone {
+            config.timezone = Some(timezone.clone());
+        }
+        if let Some(accept_language) = &configured.accept_language {
+            config.accept_language = Some(accept_language.clone());
+        }
+        if let Some(g) = configured.geolocation {
+            config.geolocation = codex_browser::Geolocation::new(g.latitude, g.longitude, g.accuracy);
+        }
+        if let Some(scheme) = configured.color_scheme {
+            config.color_scheme = Some(match scheme {
+                crate::config_types::BrowserColorScheme::Light => codex_browser::ColorScheme::Light,
+                crate::config_types::BrowserColorScheme::Dark => codex_browser::ColorScheme::Dark,
+            });
+        }
+    })
+    .await;
+}
+
+/// Locale, timezone, geolocation and color-scheme overrides passed to
+/// browser_open. `None` leaves a setting as it is.
+#[derive(Default)]
+struct BrowserEnvironmentArgs {
+    locale: Option<String>,
+    timezone: Option<String>,
+    geolocation: Option<Option<codex_browser::Geolocation>>,
+    color_scheme: Option<Option<codex_browser::ColorScheme>>,
+}
+
+impl BrowserEnvironmentArgs {
+    fn parse(args: &Value) -> Result<Self, String> {
+        let text = |key: &str| {
+            args.get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        let color_scheme = match text("color_scheme") {
+            None => None,
+            Some(s) if s.eq_ignore_ascii_case("auto") => Some(None),
+            Some(s) => match codex_browser::ColorScheme::parse(&s) {
+                Some(scheme) => Some(Some(scheme)),
+                None => return Err(format!("Unknown color_scheme '{}'; use light, dark or auto", s)),
+            },
+        };
+        let geolocation = match args.get("geolocation") {
+            None | Some(Value::Null) => None,
+            Some(g) => {
+                let number = |key: &str| g.get(key).and_then(|v| v.as_f64());
+                match (number("latitude"), number("longitude")) {
+                    (None, None) => Some(None),
+                    (Some(lat), Some(lng)) => match codex_browser::Geolocation::new(lat, lng, number("accuracy")) {
+                        Some(position) => Some(Some(position)),
+                        None => return Err(format!("Invalid geolocation {}, {}", lat, lng)),
+                    },
+                    _ => return Err("geolocation needs both latitude and longitude".to_string()),
+                }
+            }
+        };
+        Ok(Self {
+            locale: text("locale"),
+            timezone: text("timezone"),
+            geolocation,
+            color_scheme,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.locale.is_none()
+            && self.timezone.is_none()
+            && self.geolocation.is_none()
+            && self.color_scheme.is_none()
+    }
+
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(locale) = &self.locale {
+            parts.push(format!("locale {}", locale));
+        }
+        if let Some(timezone) = &self.timezone {
+            parts.push(format!("timezone {}", timezone));
+        }
+        match &self.geolocation {
+            Some(Some(position)) => parts.push(format!("geolocation {}", position)),
+            Some(None) => parts.push("no geolocation".to_string()),
+            None => {}
+        }
+        match &self.color_scheme {
+            Some(Some(scheme)) => parts.push(format!("{} color scheme", scheme)),
+            Some(None) => parts.push("browser color scheme".to_string()),
+            None => {}
+        }
+        parts.join(", ")
+    }
+
+    fn apply(self, config: &mut codex_browser::BrowserConfig) {
+        if let Some(locale) = self.locale {
+            config.accept_language = Some(codex_browser::environment::accept_language_for(&locale));
+            config.locale = Some(locale);
+        }
+        if let Some(timezone) = self.timezone {
+            config.timezone = Some(timezone);
+        }
+        if let Some(geolocation) = self.geolocation {
+            config.geolocation = geolocation;
+        }
+        if let Some(color_scheme) = self.color_scheme {
+            config.color_scheme = color_scheme;
+        }
+    }
+}
+
+async fn handle_browser_open(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    // Parse arguments as JSON for the event
     let params = serde_json::from_str(&arguments).ok();
 
     let sess_clone = sess;
@@ -5306,6 +7103,23 @@ async fn handle_browser_open(
                         .get("url")
                         .and_then(|v| v.as_str())
                         .unwrap_or("about:blank");
+                    let environment = match BrowserEnvironmentArgs::parse(&json) {
+                        Ok(environment) => environment,
+                        Err(e) => {
+                            return ResponseInputItem::FunctionCallOutput {
+                                call_id: call_id_clone.clone(),
+                                output: FunctionCallOutputPayload {
+                                    content: e,
+                                    success: Some(false),
+                                },
+                            };
+                        }
+                    };
+                    let environment_note = if environment.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" ({})", environment.describe())
+                    };
 
                     // Use the global browser manager (create if needed)
                     let browser_manager = {
@@ -5324,6 +7138,20 @@ async fn handle_browser_open(
                     };
 
                     if let Some(browser_manager) = browser_manager {
+                        if !environment.is_empty() {
+                            if let Err(e) = browser_manager
+                                .update_environment(|config| environment.apply(config))
+                                .await
+                            {
+                                return ResponseInputItem::FunctionCallOutput {
+                                    call_id: call_id_clone.clone(),
+                                    output: FunctionCallOutputPayload {
+                                        content: format!("Failed to apply browser settings: {}", e),
+                                        success: Some(false),
+                                    },
+                                };
+                            }
+                        }
                         // Clear any lingering node highlight from previous commands
                         let _ = browser_manager
                             .execute_cdp("Overlay.hideHighlight", serde_json::json!({}))
@@ -5338,10 +7166,15 @@ async fn handle_browser_open(
                                     url,
                                     step_start.elapsed()
                                 );
+                                browser_manager
+                                    .record_step(codex_browser::script::ScriptStep::Navigate {
+                                        url: url.to_string(),
+                                    })
+                                    .await;
                                 ResponseInputItem::FunctionCallOutput {
                                     call_id: call_id_clone.clone(),
                                     output: FunctionCallOutputPayload {
-                                        content: format!("Browser opened to: {}", url),
+                                        content: format!("Browser opened to: {}{}", url, environment_note),
                                         success: Some(true),
                                     },
                                 }
@@ -5388,6 +7221,44 @@ async fn get_browser_manager_for_session(
     codex_browser::global::get_browser_manager().await
 }
 
+/// Reads the optional `ref` (from browser_snapshot) or CSS `selector` that
+/// browser_click, browser_type and browser_inspect accept in place of
+/// coordinates. A ref wins when both are given.
+fn browser_element_target(params: Option<&Value>) -> Option<codex_browser::ElementTarget> {
+    let field = |key: &str| {
+        params?
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    field("ref")
+        .map(codex_browser::ElementTarget::Ref)
+        .or_else(|| field("selector").map(codex_browser::ElementTarget::Selector))
+}
+
+/// Selector recorded in a browser_script for an element the model targeted
+/// by ref or selector. Refs are replaced by a selector that survives reloads.
+async fn recorded_selector(
+    browser_manager: &codex_browser::BrowserManager,
+    target: &codex_browser::ElementTarget,
+    backend_node_id: i64,
+) -> Option<String> {
+    match target {
+        codex_browser::ElementTarget::Selector(selector) => Some(selector.clone()),
+        codex_browser::ElementTarget::Ref(_) => {
+            match browser_manager.selector_for(backend_node_id).await {
+                Ok(selector) => Some(selector),
+                Err(e) => {
+                    tracing::warn!("Recording click by position: {}", e);
+                    None
+                }
+            }
+        }
+    }
+}
+
 async fn handle_browser_close(
     sess: &Session,
     sub_id: String,
@@ -5539,8 +7410,29 @@ async fn handle_browser_click(
                     }
                 }
 
-                // If x or y provided, resolve missing coord from current position, then move
-                if target_x.is_some() || target_y.is_some() {
+                // An element ref/selector takes precedence over coordinates: scroll the
+                // element into view and move to the center of its box.
+                let element = browser_element_target(params.as_ref());
+                let mut element_node = None;
+                if let Some(target) = element.as_ref() {
+                    let moved = match browser_manager.resolve_element(target).await {
+                        Ok(el) => {
+                            element_node = Some(el.backend_node_id);
+                            browser_manager.move_mouse(el.x, el.y).await
+                        }
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = moved {
+                        return ResponseInputItem::FunctionCallOutput {
+                            call_id: call_id_clone.clone(),
+                            output: FunctionCallOutputPayload {
+                                content: format!("Failed to locate {}: {}", target, e),
+                                success: Some(false),
+                            },
+                        };
+                    }
+                } else if target_x.is_some() || target_y.is_some() {
+                    // If x or y provided, resolve missing coord from current position, then move
                     // get current cursor for missing values
                     match browser_manager.get_cursor_position().await {
                         Ok((cx, cy)) => {
@@ -5586,10 +7478,34 @@ async fn handle_browser_click(
 
                 match action_result {
                     Ok((x, y, label)) => {
+                        if click_type == "click" && browser_manager.is_recording().await {
+                            let selector = match (element.as_ref(), element_node) {
+                                (Some(target), Some(node)) => {
+                                    recorded_selector(&browser_manager, target, node).await
+                                }
+                                _ => None,
+                            };
+                            let step = match selector {
+                                Some(selector) => codex_browser::script::ScriptStep::Click {
+                                    selector: Some(selector),
+                                    x: None,
+                                    y: None,
+                                },
+                                None => codex_browser::script::ScriptStep::Click {
+                                    selector: None,
+                                    x: Some(x),
+                                    y: Some(y),
+                                },
+                            };
+                            browser_manager.record_step(step).await;
+                        }
+                        let on = element
+                            .map(|target| format!(" on {}", target))
+                            .unwrap_or_default();
                         ResponseInputItem::FunctionCallOutput {
                             call_id: call_id_clone.clone(),
                             output: FunctionCallOutputPayload {
-                                content: format!("{} at ({}, {})", label, x, y),
+                                content: format!("{} at ({}, {}){}", label, x, y, on),
                                 success: Some(true),
                             },
                         }
@@ -5735,8 +7651,50 @@ async fn handle_browser_type(
                     Ok(json) => {
                         let text = json.get("text").and_then(|v| v.as_str()).unwrap_or("");
 
+                        // With a ref/selector, click the element first so it has focus.
+                        let mut recorded_target = None;
+                        if let Some(target) = browser_element_target(Some(&json)) {
+                            let focused = match browser_manager.resolve_element(&target).await {
+                                Ok(el) => {
+                                    if browser_manager.is_recording().await {
+                                        recorded_target = recorded_selector(
+                                            &browser_manager,
+                                            &target,
+                                            el.backend_node_id,
+                                        )
+                                        .await;
+                                    }
+                                    browser_manager.click(el.x, el.y).await
+                                }
+                                Err(e) => Err(e),
+                            };
+                            if let Err(e) = focused {
+                                return ResponseInputItem::FunctionCallOutput {
+                                    call_id: call_id_clone.clone(),
+                                    output: FunctionCallOutputPayload {
+                                        content: format!("Failed to focus {}: {}", target, e),
+                                        success: Some(false),
+                                    },
+                                };
+                            }
+                        }
+
                         match browser_manager.type_text(text).await {
                             Ok(_) => {
+                                if browser_manager.is_recording().await {
+                                    let into_password =
+                                        codex_browser::script::focused_field_is_password(
+                                            &browser_manager,
+                                        )
+                                        .await;
+                                    browser_manager
+                                        .record_step(codex_browser::script::ScriptStep::typed(
+                                            recorded_target,
+                                            text,
+                                            into_password,
+                                        ))
+                                        .await;
+                                }
                                 ResponseInputItem::FunctionCallOutput {
                                     call_id: call_id_clone.clone(),
                                     output: FunctionCallOutputPayload {
@@ -5808,6 +7766,11 @@ async fn handle_browser_key(
 
                         match browser_manager.press_key(key).await {
                             Ok(_) => {
+                                browser_manager
+                                    .record_step(codex_browser::script::ScriptStep::Key {
+                                        key: key.to_string(),
+                                    })
+                                    .await;
                                 ResponseInputItem::FunctionCallOutput {
                                     call_id: call_id_clone.clone(),
                                     output: FunctionCallOutputPayload {
@@ -6001,6 +7964,9 @@ async fn handle_browser_scroll(
 
                         match browser_manager.scroll_by(dx, dy).await {
                     Ok(_) => {
+                        browser_manager
+                            .record_step(codex_browser::script::ScriptStep::Scroll { dx, dy })
+                            .await;
                         ResponseInputItem::FunctionCallOutput {
                             call_id: call_id_clone.clone(),
                             output: FunctionCallOutputPayload {
@@ -6222,45 +8188,1322 @@ async fn handle_browser_cdp(
     .await
 }
 
-async fn handle_browser_inspect(
+async fn handle_browser_snapshot(
     sess: &Session,
     arguments: String,
     sub_id: String,
     call_id: String,
 ) -> ResponseInputItem {
-    use serde_json::json;
-    let params = serde_json::from_str(&arguments).ok();
+    let params = serde_json::from_str::<serde_json::Value>(&arguments).ok();
     let sess_clone = sess;
-    let arguments_clone = arguments.clone();
     let call_id_clone = call_id.clone();
 
     execute_custom_tool(
         sess,
         &sub_id,
         call_id,
-        "browser_inspect".to_string(),
-        params,
+        "browser_snapshot".to_string(),
+        params.clone(),
         || async move {
-            let browser_manager = get_browser_manager_for_session(sess_clone).await;
-            if let Some(browser_manager) = browser_manager {
-                let args: Result<Value, _> = serde_json::from_str(&arguments_clone);
-                match args {
-                    Ok(json) => {
-                        // Determine target element: by id, by coords, or by cursor
-                        let id_attr = json.get("id").and_then(|v| v.as_str()).map(|s| s.to_string());
-                        let mut x = json.get("x").and_then(|v| v.as_f64());
-                        let mut y = json.get("y").and_then(|v| v.as_f64());
+            let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await else {
+                return ResponseInputItem::FunctionCallOutput {
+                    call_id: call_id_clone,
+                    output: FunctionCallOutputPayload {
+                        content: "Browser is not initialized. Use browser_open to start the browser."
+                            .to_string(),
+                        success: Some(false),
+                    },
+                };
+            };
+            let _ = browser_manager
+                .execute_cdp("Overlay.hideHighlight", serde_json::json!({}))
+                .await;
+            let max_nodes = params
+                .as_ref()
+                .and_then(|v| v.get("max_nodes"))
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize);
+
+            match browser_manager.snapshot(max_nodes).await {
+                Ok(snapshot) => {
+                    let url = browser_manager.get_current_url().await.unwrap_or_default();
+                    let mut content = format!("Page: {}\n", url);
+                    content.push_str(&snapshot.text);
+                    if snapshot.truncated {
+                        content.push_str(
+                            "… (truncated; raise max_nodes or act on the refs above)\n",
+                        );
+                    }
+                    ResponseInputItem::FunctionCallOutput {
+                        call_id: call_id_clone,
+                        output: FunctionCallOutputPayload {
+                            content,
+                            success: Some(true),
+                        },
+                    }
+                }
+                Err(e) => ResponseInputItem::FunctionCallOutput {
+                    call_id: call_id_clone,
+                    output: FunctionCallOutputPayload {
+                        content: format!("Failed to capture accessibility snapshot: {}", e),
+                        success: Some(false),
+                    },
+                },
+            }
+        },
+    )
+    .await
+}
 
-                        if (x.is_none() || y.is_none()) && id_attr.is_none() {
-                            // No coords provided; use current cursor
-                            if let Ok((cx, cy)) = browser_manager.get_cursor_position().await {
-                                x = Some(cx);
-                                y = Some(cy);
-                            }
+async fn handle_browser_screenshot(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    use crate::visual_baseline::BaselineKey;
+    use crate::visual_baseline::BaselineStore;
+
+    let params = serde_json::from_str::<serde_json::Value>(&arguments).ok();
+    let sess_clone = sess;
+    let call_id_clone = call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "browser_screenshot".to_string(),
+        params.clone(),
+        || async move {
+            let reply = |content: String, success: bool| ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone.clone(),
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(success),
+                },
+            };
+            let args = params.unwrap_or_else(|| serde_json::json!({}));
+            let action = args
+                .get("action")
+                .and_then(|v| v.as_str())
+                .unwrap_or("compare");
+            let name = args
+                .get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("default")
+                .to_string();
+            let store = BaselineStore::for_workspace(&sess_clone.cwd);
+
+            if action == "list" {
+                return match store.list(None) {
+                    Ok(entries) if entries.is_empty() => reply(
+                        format!("No baselines saved in {}.", store.dir().display()),
+                        true,
+                    ),
+                    Ok(entries) => {
+                        let mut out = format!("{} baseline(s):\n", entries.len());
+                        for e in &entries {
+                            out.push_str(&format!(
+                                "- {} {} at {}x{}{}\n",
+                                e.key.name,
+                                e.key.url,
+                                e.key.width,
+                                e.key.height,
+                                if e.pending { " (failed comparison pending)" } else { "" }
+                            ));
                         }
+                        reply(out, true)
+                    }
+                    Err(e) => reply(format!("Failed to list baselines: {}", e), false),
+                };
+            }
 
-                        // Resolve nodeId
-                        let node_id_value = if let Some(id_attr) = id_attr.clone() {
+            let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await else {
+                return reply(
+                    "Browser is not initialized. Use browser_open to start the browser.".to_string(),
+                    false,
+                );
+            };
+            let (screenshot, url) = match capture_browser_screenshot(sess_clone).await {
+                Ok(shot) => shot,
+                Err(e) => return reply(e, false),
+            };
+            add_pending_screenshot(sess_clone, screenshot.clone(), url.clone());
+            let (width, height) = browser_manager.get_viewport_size().await;
+            let key = BaselineKey {
+                name,
+                url,
+                width,
+                height,
+            };
+
+            match action {
+                "baseline" => match store.save(&key, &screenshot) {
+                    Ok(path) => reply(
+                        format!(
+                            "Saved baseline '{}' for {} at {}x{}: {}",
+                            key.name,
+                            key.url,
+                            width,
+                            height,
+                            path.display()
+                        ),
+                        true,
+                    ),
+                    Err(e) => reply(format!("Failed to save baseline: {}", e), false),
+                },
+                "compare" => {
+                    let min_similarity = args
+                        .get("min_similarity")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(crate::visual_baseline::DEFAULT_MIN_SIMILARITY);
+                    let result = match store.compare(&key, &screenshot, min_similarity) {
+                        Ok(Some(result)) => result,
+                        Ok(None) => {
+                            return reply(
+                                format!(
+                                    "No baseline '{}' for {} at {}x{}. Save one with action 'baseline' first.",
+                                    key.name, key.url, width, height
+                                ),
+                                false,
+                            );
+                        }
+                        Err(e) => return reply(format!("Failed to compare screenshot: {}", e), false),
+                    };
+
+                    let stats = &result.stats;
+                    let mut out = format!(
+                        "{}: similarity {:.2}% ({} of {} pixels changed, minimum {:.2}%) against baseline '{}' for {} at {}x{}.\n",
+                        if result.passed { "PASSED" } else { "FAILED" },
+                        stats.similarity * 100.0,
+                        stats.changed_pixels,
+                        stats.total_pixels,
+                        min_similarity * 100.0,
+                        key.name,
+                        key.url,
+                        width,
+                        height
+                    );
+                    if let Some(((bw, bh), (aw, ah))) = result.size_changed {
+                        out.push_str(&format!(
+                            "Screenshot size changed from {}x{} to {}x{}.\n",
+                            bw, bh, aw, ah
+                        ));
+                    }
+                    out.push_str(&format!(
+                        "Perceptual hash: {}.\n",
+                        if result.perceptual_match { "similar" } else { "different" }
+                    ));
+                    if !stats.regions.is_empty() {
+                        out.push_str("Changed regions (x, y, width, height):\n");
+                        for r in &stats.regions {
+                            out.push_str(&format!("  {}, {}, {}, {}\n", r.x, r.y, r.width, r.height));
+                        }
+                        if stats.omitted_regions > 0 {
+                            out.push_str(&format!("  ... and {} smaller regions\n", stats.omitted_regions));
+                        }
+                    }
+                    if let Some(diff) = &result.diff_path {
+                        out.push_str(&format!(
+                            "Diff image (changes in red): {}\n",
+                            diff.display()
+                        ));
+                    }
+                    if !result.passed {
+                        out.push_str(&format!(
+                            "If the change is intended, save a new baseline with action 'baseline' or run `code baselines accept {}`.\n",
+                            key.name
+                        ));
+                    }
+                    reply(out, true)
+                }
+                other => reply(format!("Unknown browser_screenshot action: {}", other), false),
+            }
+        },
+    )
+    .await
+}
+
+async fn handle_browser_network(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let params = serde_json::from_str::<serde_json::Value>(&arguments).ok();
+    let sess_clone = sess;
+    let call_id_clone = call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "browser_network".to_string(),
+        params.clone(),
+        || async move {
+            let reply = |content: String, success: bool| ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone.clone(),
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(success),
+                },
+            };
+            let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await else {
+                return reply(
+                    "Browser is not initialized. Use browser_open to start the browser.".to_string(),
+                    false,
+                );
+            };
+            let args = params.unwrap_or_else(|| serde_json::json!({}));
+            let str_arg = |key: &str| args.get(key).and_then(|v| v.as_str()).map(str::to_string);
+            let action = str_arg("action").unwrap_or_else(|| "list".to_string());
+            let filter: codex_browser::NetworkFilter =
+                serde_json::from_value(args.clone()).unwrap_or_default();
+
+            match action.as_str() {
+                "list" => match browser_manager.network_entries(&filter).await {
+                    Ok(entries) if entries.is_empty() => {
+                        reply("No matching requests recorded.".to_string(), true)
+                    }
+                    Ok(entries) => {
+                        let mut out = format!("{} request(s):\n", entries.len());
+                        for entry in &entries {
+                            out.push_str(&entry.summary());
+                            out.push('\n');
+                        }
+                        reply(out, true)
+                    }
+                    Err(e) => reply(format!("Failed to read network log: {}", e), false),
+                },
+                "show" => {
+                    let Some(id) = str_arg("id") else {
+                        return reply("browser_network show requires an id".to_string(), false);
+                    };
+                    let entry = match browser_manager.network_entry(&id).await {
+                        Ok(Some(entry)) => entry,
+                        Ok(None) => return reply(format!("No request with id {}", id), false),
+                        Err(e) => return reply(format!("Failed to read network log: {}", e), false),
+                    };
+                    let mut out = format!("{}\n", entry.summary());
+                    if let Some(rt) = &entry.resource_type {
+                        out.push_str(&format!("Type: {}\n", rt));
+                    }
+                    out.push_str("\nRequest headers:\n");
+                    for (k, v) in &entry.request_headers {
+                        out.push_str(&format!("  {}: {}\n", k, v));
+                    }
+                    if let Some(post) = &entry.post_data {
+                        out.push_str(&format!("\nRequest body:\n{}\n", truncate_body(post)));
+                    }
+                    if !entry.response_headers.is_empty() {
+                        out.push_str("\nResponse headers:\n");
+                        for (k, v) in &entry.response_headers {
+                            out.push_str(&format!("  {}: {}\n", k, v));
+                        }
+                    }
+                    if entry.status.is_some() {
+                        match browser_manager.network_response_body(&id).await {
+                            Ok(body) if body.base64_encoded => out.push_str(&format!(
+                                "\nResponse body: <{} bytes of binary data>\n",
+                                body.body.len() * 3 / 4
+                            )),
+                            Ok(body) => out.push_str(&format!(
+                                "\nResponse body:\n{}\n",
+                                truncate_body(&body.body)
+                            )),
+                            Err(e) => out.push_str(&format!("\nResponse body unavailable: {}\n", e)),
+                        }
+                    }
+                    reply(out, true)
+                }
+                "har" => {
+                    let include_bodies = args.get("bodies").and_then(|v| v.as_bool()).unwrap_or(true);
+                    let path = match str_arg("path") {
+                        Some(p) => sess_clone.resolve_path(Some(p)),
+                        None => std::env::temp_dir().join(format!(
+                            "codex-browser-{}.har",
+                            chrono::Utc::now().format("%Y%m%d-%H%M%S")
+                        )),
+                    };
+                    let har = match browser_manager.export_har(&filter, include_bodies).await {
+                        Ok(har) => har,
+                        Err(e) => return reply(format!("Failed to export HAR: {}", e), false),
+                    };
+                    let count = har["log"]["entries"].as_array().map_or(0, |e| e.len());
+                    let written = serde_json::to_vec_pretty(&har)
+                        .map_err(std::io::Error::other)
+                        .and_then(|bytes| std::fs::write(&path, bytes));
+                    match written {
+                        Ok(()) => reply(
+                            format!("Wrote {} request(s) to {}", count, path.display()),
+                            true,
+                        ),
+                        Err(e) => reply(format!("Failed to write {}: {}", path.display(), e), false),
+                    }
+                }
+                "clear" => match browser_manager.clear_network_log().await {
+                    Ok(()) => reply("Network log cleared.".to_string(), true),
+                    Err(e) => reply(format!("Failed to clear network log: {}", e), false),
+                },
+                "mock" | "block" => {
+                    // `action` doubles as the rule's tag, so the arguments deserialize as-is.
+                    let rule: codex_browser::RouteRule = match serde_json::from_value(args.clone()) {
+                        Ok(rule) => rule,
+                        Err(e) => return reply(format!("Invalid {} rule: {}", action, e), false),
+                    };
+                    let pattern = rule.pattern.clone();
+                    match browser_manager.add_route(rule).await {
+                        Ok(()) => reply(
+                            format!(
+                                "{} requests matching '{}'. Reload the page to apply to requests already made.",
+                                if action == "mock" { "Mocking" } else { "Blocking" },
+                                pattern
+                            ),
+                            true,
+                        ),
+                        Err(e) => reply(format!("Failed to install rule: {}", e), false),
+                    }
+                }
+                "unroute" => {
+                    let pattern = str_arg("pattern");
+                    match browser_manager.remove_routes(pattern.as_deref()).await {
+                        Ok(n) => reply(format!("Removed {} rule(s).", n), true),
+                        Err(e) => reply(format!("Failed to remove rules: {}", e), false),
+                    }
+                }
+                "routes" => match browser_manager.routes().await {
+                    Ok(rules) if rules.is_empty() => reply("No routing rules installed.".to_string(), true),
+                    Ok(rules) => {
+                        let mut out = String::from("Routing rules (first match wins):\n");
+                        for rule in &rules {
+                            out.push_str(&format!(
+                                "  {}\n",
+                                serde_json::to_string(rule).unwrap_or_default()
+                            ));
+                        }
+                        reply(out, true)
+                    }
+                    Err(e) => reply(format!("Failed to read routing rules: {}", e), false),
+                },
+                other => reply(
+                    format!(
+                        "Unknown browser_network action '{}'. Use list, show, har, clear, mock, block, unroute or routes.",
+                        other
+                    ),
+                    false,
+                ),
+            }
+        },
+    )
+    .await
+}
+
+/// Caps request/response bodies echoed back to the model.
+fn truncate_body(body: &str) -> String {
+    const MAX_BODY_CHARS: usize = 4000;
+    if body.chars().count() <= MAX_BODY_CHARS {
+        return body.to_string();
+    }
+    let head: String = body.chars().take(MAX_BODY_CHARS).collect();
+    format!("{}… [truncated, {} chars total]", head, body.chars().count())
+}
+
+async fn handle_browser_tabs(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let params = serde_json::from_str::<serde_json::Value>(&arguments).ok();
+    let sess_clone = sess;
+    let call_id_clone = call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "browser_tabs".to_string(),
+        params.clone(),
+        || async move {
+            let reply = |content: String, success: bool| ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone.clone(),
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(success),
+                },
+            };
+            let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await else {
+                return reply(
+                    "Browser is not initialized. Use browser_open to start the browser.".to_string(),
+                    false,
+                );
+            };
+            let str_arg = |key: &str| {
+                params
+                    .as_ref()
+                    .and_then(|p| p.get(key))
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+            };
+            let action = str_arg("action").unwrap_or_else(|| "list".to_string());
+            let describe = |tab: &codex_browser::TabInfo| {
+                let title = tab.title.as_deref().unwrap_or("");
+                let mut line = format!(
+                    "{} {} {} [id={}]",
+                    if tab.active { "*" } else { "-" },
+                    tab.url,
+                    if title.is_empty() { String::new() } else { format!("— {}", title) },
+                    tab.id
+                );
+                if let Some(opener) = &tab.opener_id {
+                    line.push_str(&format!(" (opened by {})", opener));
+                }
+                line
+            };
+
+            match action.as_str() {
+                "list" => match browser_manager.list_tabs().await {
+                    Ok(tabs) => {
+                        let mut out = format!("{} tab(s), * marks the active one:\n", tabs.len());
+                        for tab in &tabs {
+                            out.push_str(&describe(tab));
+                            out.push('\n');
+                        }
+                        reply(out, true)
+                    }
+                    Err(e) => reply(format!("Failed to list tabs: {}", e), false),
+                },
+                "switch" => {
+                    let Some(id) = str_arg("id") else {
+                        return reply("browser_tabs switch requires an id".to_string(), false);
+                    };
+                    match browser_manager.switch_tab(&id).await {
+                        Ok(tab) => reply(format!("Switched to {}", describe(&tab)), true),
+                        Err(e) => reply(format!("Failed to switch tab: {}", e), false),
+                    }
+                }
+                "open" => match browser_manager.open_tab(str_arg("url").as_deref()).await {
+                    Ok(tab) => reply(format!("Opened {}", describe(&tab)), true),
+                    Err(e) => reply(format!("Failed to open tab: {}", e), false),
+                },
+                "close" => match browser_manager.close_tab(str_arg("id").as_deref()).await {
+                    Ok(Some(tab)) => reply(format!("Closed tab. Active: {}", describe(&tab)), true),
+                    Ok(None) => reply("Closed the last tab.".to_string(), true),
+                    Err(e) => reply(format!("Failed to close tab: {}", e), false),
+                },
+                other => reply(format!("Unknown browser_tabs action: {}", other), false),
+            }
+        },
+    )
+    .await
+}
+
+async fn handle_browser_script(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    use codex_browser::script::ScriptStep;
+    use codex_browser::script::ScriptStore;
+
+    let params = serde_json::from_str::<serde_json::Value>(&arguments).ok();
+    let sess_clone = sess;
+    let call_id_clone = call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "browser_script".to_string(),
+        params.clone(),
+        || async move {
+            let reply = |content: String, success: bool| ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone.clone(),
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(success),
+                },
+            };
+            let args = params.unwrap_or_else(|| serde_json::json!({}));
+            let str_arg = |key: &str| {
+                args.get(key)
+                    .and_then(|v| v.as_str())
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string)
+            };
+            let action = str_arg("action").unwrap_or_default();
+            let store = ScriptStore::for_workspace(&sess_clone.cwd);
+
+            match action.as_str() {
+                "list" => match store.list() {
+                    Ok(names) if names.is_empty() => reply(
+                        format!("No scripts saved in {}.", store.dir().display()),
+                        true,
+                    ),
+                    Ok(names) => reply(format!("Saved scripts:\n- {}", names.join("\n- ")), true),
+                    Err(e) => reply(format!("Failed to list scripts: {}", e), false),
+                },
+                "show" => {
+                    let Some(name) = str_arg("name") else {
+                        return reply("browser_script show requires a name".to_string(), false);
+                    };
+                    match store.load(&name) {
+                        Ok(script) => reply(
+                            serde_json::to_string_pretty(&script).unwrap_or_default(),
+                            true,
+                        ),
+                        Err(e) => reply(e.to_string(), false),
+                    }
+                }
+                "replay" => {
+                    let Some(name) = str_arg("name") else {
+                        return reply("browser_script replay requires a name".to_string(), false);
+                    };
+                    let script = match store.load(&name) {
+                        Ok(script) => script,
+                        Err(e) => return reply(e.to_string(), false),
+                    };
+                    match codex_browser::script::replay_headless(
+                        &script,
+                        codex_browser::script::DEFAULT_STEP_TIMEOUT,
+                    )
+                    .await
+                    {
+                        Ok(report) => reply(report.summary(), true),
+                        Err(e) => reply(format!("Failed to start headless browser: {}", e), false),
+                    }
+                }
+                "start" | "stop" | "assert" | "wait" => {
+                    let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await
+                    else {
+                        return reply(
+                            "Browser is not initialized. Use browser_open to start the browser."
+                                .to_string(),
+                            false,
+                        );
+                    };
+                    match action.as_str() {
+                        "start" => {
+                            let name = str_arg("name").unwrap_or_else(|| "default".to_string());
+                            browser_manager.start_recording(&name).await;
+                            reply(
+                                format!(
+                                    "Recording '{}'. Browser actions are recorded until browser_script stop; add checks with assert.",
+                                    name
+                                ),
+                                true,
+                            )
+                        }
+                        "stop" => {
+                            let Some(script) = browser_manager.stop_recording().await else {
+                                return reply("No recording in progress.".to_string(), false);
+                            };
+                            match store.save(&script) {
+                                Ok(path) => {
+                                    let mut out = format!(
+                                        "Saved {} step(s) to {}:\n",
+                                        script.steps.len(),
+                                        path.display()
+                                    );
+                                    for (i, step) in script.steps.iter().enumerate() {
+                                        out.push_str(&format!("  {}. {}\n", i + 1, step));
+                                    }
+                                    reply(out, true)
+                                }
+                                Err(e) => reply(format!("Failed to save script: {}", e), false),
+                            }
+                        }
+                        _ => {
+                            let absent = args.get("absent").and_then(|v| v.as_bool()).unwrap_or(false);
+                            let step = match (action.as_str(), str_arg("selector"), str_arg("text")) {
+                                ("assert", Some(selector), _) => {
+                                    ScriptStep::AssertSelector { selector, absent }
+                                }
+                                ("assert", None, Some(text)) => ScriptStep::AssertText { text, absent },
+                                ("assert", None, None) => {
+                                    return reply(
+                                        "browser_script assert requires text or selector".to_string(),
+                                        false,
+                                    );
+                                }
+                                (_, selector, text) => ScriptStep::Wait {
+                                    selector,
+                                    text,
+                                    ms: args.get("ms").and_then(|v| v.as_u64()),
+                                },
+                            };
+                            // Check the step against the live page so only
+                            // assertions that currently hold are recorded.
+                            if let Err(e) = codex_browser::script::run_step(
+                                &browser_manager,
+                                &step,
+                                codex_browser::script::DEFAULT_STEP_TIMEOUT,
+                            )
+                            .await
+                            {
+                                return reply(format!("{} failed: {}", step, e), false);
+                            }
+                            let recording = browser_manager.is_recording().await;
+                            let note = if recording { "recorded" } else { "not recording" };
+                            browser_manager.record_step(step.clone()).await;
+                            reply(format!("{} holds ({})", step, note), true)
+                        }
+                    }
+                }
+                other => reply(format!("Unknown browser_script action: {}", other), false),
+            }
+        },
+    )
+    .await
+}
+
+async fn handle_browser_dialog(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let params = serde_json::from_str::<serde_json::Value>(&arguments).ok();
+    let sess_clone = sess;
+    let call_id_clone = call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "browser_dialog".to_string(),
+        params.clone(),
+        || async move {
+            let reply = |content: String, success: bool| ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone.clone(),
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(success),
+                },
+            };
+            let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await else {
+                return reply(
+                    "Browser is not initialized. Use browser_open to start the browser.".to_string(),
+                    false,
+                );
+            };
+            let args = params.unwrap_or_else(|| serde_json::json!({}));
+            let str_arg = |key: &str| args.get(key).and_then(|v| v.as_str()).map(str::to_string);
+            let action = str_arg("action").unwrap_or_else(|| "list".to_string());
+
+            match action.as_str() {
+                "accept" | "dismiss" => {
+                    let accept = action == "accept";
+                    let text = if accept { str_arg("text") } else { None };
+                    match browser_manager.handle_dialog(accept, text).await {
+                        Ok(dialog) => reply(format!("{}", dialog), true),
+                        Err(e) => reply(format!("Failed to {} dialog: {}", action, e), false),
+                    }
+                }
+                "list" => match browser_manager.dialogs().await {
+                    Ok(dialogs) if dialogs.is_empty() => reply("No JavaScript dialogs have opened in this tab.".to_string(), true),
+                    Ok(dialogs) => {
+                        let lines: Vec<String> = dialogs.iter().map(|d| format!("- {}", d)).collect();
+                        reply(format!("Dialogs in this tab (oldest first):\n{}", lines.join("\n")), true)
+                    }
+                    Err(e) => reply(format!("Failed to list dialogs: {}", e), false),
+                },
+                "policy" => {
+                    let Some(value) = str_arg("policy") else {
+                        let current = browser_manager.config.read().await.dialog_policy;
+                        return reply(format!("Dialog policy: {}", current), true);
+                    };
+                    match codex_browser::DialogPolicy::parse(&value) {
+                        Some(policy) => {
+                            browser_manager.set_dialog_policy(policy).await;
+                            reply(format!("Dialog policy set to {}", policy), true)
+                        }
+                        None => reply(
+                            format!("Unknown dialog policy '{}'; use accept, dismiss or manual", value),
+                            false,
+                        ),
+                    }
+                }
+                other => reply(
+                    format!("Unknown browser_dialog action '{}'; use accept, dismiss, list or policy", other),
+                    false,
+                ),
+            }
+        },
+    )
+    .await
+}
+
+async fn handle_browser_upload(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let params = serde_json::from_str::<serde_json::Value>(&arguments).ok();
+    let sess_clone = sess;
+    let call_id_clone = call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "browser_upload".to_string(),
+        params.clone(),
+        || async move {
+            let reply = |content: String, success: bool| ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone.clone(),
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(success),
+                },
+            };
+            let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await else {
+                return reply(
+                    "Browser is not initialized. Use browser_open to start the browser.".to_string(),
+                    false,
+                );
+            };
+            let target = browser_element_target(params.as_ref())
+                .unwrap_or_else(|| codex_browser::ElementTarget::Selector("input[type=file]".to_string()));
+            let requested: Vec<String> = params
+                .as_ref()
+                .and_then(|p| p.get("paths"))
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect();
+            if requested.is_empty() {
+                return reply("Provide at least one file in paths.".to_string(), false);
+            }
+
+            // Only files inside the workspace may be handed to a page.
+            let workspace = match sess_clone.cwd.canonicalize() {
+                Ok(dir) => dir,
+                Err(e) => return reply(format!("Failed to resolve the workspace: {}", e), false),
+            };
+            let mut files = Vec::with_capacity(requested.len());
+            for path in &requested {
+                let file = match sess_clone.resolve_path(Some(path.clone())).canonicalize() {
+                    Ok(file) => file,
+                    Err(e) => return reply(format!("Cannot upload {}: {}", path, e), false),
+                };
+                if !file.starts_with(&workspace) {
+                    return reply(
+                        format!("Cannot upload {}: only files inside {} can be uploaded", path, workspace.display()),
+                        false,
+                    );
+                }
+                if !file.is_file() {
+                    return reply(format!("Cannot upload {}: not a file", path), false);
+                }
+                files.push(file);
+            }
+
+            match browser_manager.upload_files(&target, &files).await {
+                Ok(()) => reply(format!("Set {} file(s) on {}: {}", files.len(), target, requested.join(", ")), true),
+                Err(e) => reply(format!("Failed to upload to {}: {}", target, e), false),
+            }
+        },
+    )
+    .await
+}
+
+async fn handle_browser_downloads(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let params = serde_json::from_str::<serde_json::Value>(&arguments).ok();
+    let sess_clone = sess;
+    let call_id_clone = call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "browser_downloads".to_string(),
+        params.clone(),
+        || async move {
+            const MAX_READ_BYTES: usize = 64 * 1024;
+            let reply = |content: String, success: bool| ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone.clone(),
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(success),
+                },
+            };
+            let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await else {
+                return reply(
+                    "Browser is not initialized. Use browser_open to start the browser.".to_string(),
+                    false,
+                );
+            };
+            let args = params.unwrap_or_else(|| serde_json::json!({}));
+            let str_arg = |key: &str| args.get(key).and_then(|v| v.as_str()).map(str::to_string);
+            let (dir, downloads) = browser_manager.downloads().await;
+
+            match str_arg("action").unwrap_or_else(|| "list".to_string()).as_str() {
+                "list" => {
+                    if downloads.is_empty() {
+                        return reply(format!("No downloads yet. Files will be saved to {}", dir.display()), true);
+                    }
+                    let lines: Vec<String> = downloads.iter().map(|d| format!("- {}", d)).collect();
+                    reply(format!("Downloads in {}:\n{}", dir.display(), lines.join("\n")), true)
+                }
+                "read" => {
+                    let Some(name) = str_arg("name") else {
+                        return reply("Provide the name of a completed download.".to_string(), false);
+                    };
+                    let found = downloads.iter().rev().find(|d| {
+                        d.state == codex_browser::download::DownloadState::Completed
+                            && (d.path.file_name().is_some_and(|f| f.to_string_lossy() == name)
+                                || d.suggested_filename == name)
+                    });
+                    let Some(download) = found else {
+                        return reply(format!("No completed download named '{}'", name), false);
+                    };
+                    let bytes = match std::fs::read(&download.path) {
+                        Ok(bytes) => bytes,
+                        Err(e) => return reply(format!("Failed to read {}: {}", download.path.display(), e), false),
+                    };
+                    let shown = &bytes[..bytes.len().min(MAX_READ_BYTES)];
+                    match std::str::from_utf8(shown) {
+                        Ok(text) if !text.contains('\0') => {
+                            let mut out = format!("{} ({} bytes):\n{}", download.path.display(), bytes.len(), text);
+                            if bytes.len() > MAX_READ_BYTES {
+                                out.push_str(&format!("\n[truncated after {} bytes]", MAX_READ_BYTES));
+                            }
+                            reply(out, true)
+                        }
+                        _ => reply(
+                            format!(
+                                "{} is binary ({} bytes); inspect it with shell tools.",
+                                download.path.display(),
+                                bytes.len()
+                            ),
+                            true,
+                        ),
+                    }
+                }
+                other => reply(format!("Unknown browser_downloads action '{}'; use list or read", other), false),
+            }
+        },
+    )
+    .await
+}
+
+async fn handle_browser_responsive(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let params = serde_json::from_str::<serde_json::Value>(&arguments).ok();
+    let sess_clone = sess;
+    let call_id_clone = call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "browser_responsive".to_string(),
+        params.clone(),
+        || async move {
+            let reply = |content: String, success: bool| ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone.clone(),
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(success),
+                },
+            };
+            let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await else {
+                return reply(
+                    "Browser is not initialized. Use browser_open to start the browser.".to_string(),
+                    false,
+                );
+            };
+            let args = params.unwrap_or_else(|| serde_json::json!({}));
+            let config = browser_manager.config.read().await.clone();
+            let mut names: Vec<String> = args
+                .get("devices")
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect();
+            if names.is_empty() {
+                names = config.responsive_devices.clone();
+            }
+            let devices = match codex_browser::responsive::resolve_devices(&names, &config.device_presets) {
+                Ok(devices) => devices,
+                Err(e) => return reply(e.to_string(), false),
+            };
+            let reload = args.get("reload").and_then(|v| v.as_bool()).unwrap_or(false);
+            let settle = args
+                .get("settle_ms")
+                .and_then(|v| v.as_u64())
+                .map(std::time::Duration::from_millis)
+                .unwrap_or(codex_browser::responsive::DEFAULT_SETTLE);
+            let separate = args.get("layout").and_then(|v| v.as_str()) == Some("images");
+
+            let shots = match browser_manager.responsive_sweep(&devices, reload, settle).await {
+                Ok(shots) => shots,
+                Err(e) => return reply(format!("Responsive sweep failed: {}", e), false),
+            };
+
+            let stamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+            let dir = std::env::temp_dir();
+            let mut out = format!("Captured {} device(s):", shots.len());
+            let mut queued = Vec::new();
+            for shot in &shots {
+                let path = dir.join(format!("codex-responsive-{}-{}.png", stamp, shot.device.name));
+                if let Err(e) = std::fs::write(&path, &shot.png) {
+                    return reply(format!("Failed to save {}: {}", path.display(), e), false);
+                }
+                out.push_str(&format!("\n  {} -> {}", shot.device.label(), path.display()));
+                queued.push((path, format!("Responsive capture: {}", shot.device.label())));
+            }
+            if !separate {
+                let grid = match codex_browser::responsive::compose_grid(&shots) {
+                    Ok(grid) => grid,
+                    Err(e) => return reply(format!("Failed to compose grid: {}", e), false),
+                };
+                let path = dir.join(format!("codex-responsive-{}-grid.png", stamp));
+                if let Err(e) = grid.save(&path) {
+                    return reply(format!("Failed to save {}: {}", path.display(), e), false);
+                }
+                let labels: Vec<String> = shots.iter().map(|s| s.device.label()).collect();
+                out.push_str(&format!("\nGrid (left to right) -> {}", path.display()));
+                queued = vec![(path, format!("Responsive grid, left to right: {}", labels.join(" | ")))];
+            }
+            out.push_str(if separate {
+                "\nThe images are attached to your next input."
+            } else {
+                "\nThe grid is attached to your next input."
+            });
+            sess_clone.pending_browser_screenshots.lock().unwrap().extend(queued);
+            reply(out, true)
+        },
+    )
+    .await
+}
+
+async fn handle_browser_state(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let params = serde_json::from_str::<serde_json::Value>(&arguments).ok();
+    let sess_clone = sess;
+    let call_id_clone = call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "browser_state".to_string(),
+        params.clone(),
+        || async move {
+            let reply = |content: String, success: bool| ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone.clone(),
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(success),
+                },
+            };
+            let args = params.unwrap_or_else(|| serde_json::json!({}));
+            let str_arg = |key: &str| args.get(key).and_then(|v| v.as_str()).map(str::to_string);
+            let action = str_arg("action").unwrap_or_default();
+            let name = str_arg("name").unwrap_or_else(|| "default".to_string());
+            // States live in the Codex home so credentials never land in the
+            // workspace; replies only describe them, never their values.
+            let store = codex_browser::storage_state::StateStore::new(sess_clone.client.get_codex_home());
+
+            match action.as_str() {
+                "list" => match store.list() {
+                    Ok(names) if names.is_empty() => reply("No saved browser states.".to_string(), true),
+                    Ok(names) => reply(format!("Saved browser states:\n{}", names.join("\n")), true),
+                    Err(e) => reply(format!("Failed to list browser states: {}", e), false),
+                },
+                "delete" => match store.delete(&name) {
+                    Ok(true) => reply(format!("Deleted browser state '{}'", name), true),
+                    Ok(false) => reply(format!("No browser state named '{}'", name), false),
+                    Err(e) => reply(format!("Failed to delete browser state: {}", e), false),
+                },
+                "save" | "load" => {
+                    let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await else {
+                        return reply(
+                            "Browser is not initialized. Use browser_open to start the browser.".to_string(),
+                            false,
+                        );
+                    };
+                    if action == "save" {
+                        let origins: Vec<String> = args
+                            .get("origins")
+                            .and_then(|v| v.as_array())
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|v| v.as_str().map(str::to_string))
+                            .collect();
+                        let state = match browser_manager.save_storage_state(&origins).await {
+                            Ok(state) => state,
+                            Err(e) => return reply(format!("Failed to capture browser state: {}", e), false),
+                        };
+                        match store.save(&name, &state) {
+                            Ok(_) => reply(format!("Saved browser state '{}': {}", name, state.summary()), true),
+                            Err(e) => reply(format!("Failed to save browser state: {}", e), false),
+                        }
+                    } else {
+                        let state = match store.load(&name) {
+                            Ok(state) => state,
+                            Err(e) => return reply(format!("Failed to load browser state '{}': {}", name, e), false),
+                        };
+                        match browser_manager.restore_storage_state(&state).await {
+                            Ok(()) => reply(
+                                format!(
+                                    "Restored browser state '{}': {}\nNavigate to the site to use it.",
+                                    name,
+                                    state.summary()
+                                ),
+                                true,
+                            ),
+                            Err(e) => reply(format!("Failed to restore browser state: {}", e), false),
+                        }
+                    }
+                }
+                other => reply(
+                    format!("Unknown browser_state action '{}'; use save, load, list or delete", other),
+                    false,
+                ),
+            }
+        },
+    )
+    .await
+}
+
+async fn handle_browser_perf(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    let params = serde_json::from_str::<serde_json::Value>(&arguments).ok();
+    let sess_clone = sess;
+    let call_id_clone = call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "browser_perf".to_string(),
+        params.clone(),
+        || async move {
+            let reply = |content: String, success: bool| ResponseInputItem::FunctionCallOutput {
+                call_id: call_id_clone.clone(),
+                output: FunctionCallOutputPayload {
+                    content,
+                    success: Some(success),
+                },
+            };
+            let Some(browser_manager) = get_browser_manager_for_session(sess_clone).await else {
+                return reply(
+                    "Browser is not initialized. Use browser_open to start the browser.".to_string(),
+                    false,
+                );
+            };
+            let args = params.unwrap_or_else(|| serde_json::json!({}));
+            let str_arg = |key: &str| args.get(key).and_then(|v| v.as_str()).map(str::to_string);
+            let action = str_arg("action").unwrap_or_else(|| "measure".to_string());
+            let trace_path = || match str_arg("path") {
+                Some(p) => sess_clone.resolve_path(Some(p)),
+                None => std::env::temp_dir().join(format!(
+                    "codex-trace-{}.json",
+                    chrono::Utc::now().format("%Y%m%d-%H%M%S")
+                )),
+            };
+
+            // Throttling persists on the page until it is changed again.
+            let mut out = String::new();
+            let network = match str_arg("network") {
+                Some(name) => match codex_browser::perf::NetworkConditions::preset(&name) {
+                    Some(conditions) => {
+                        out.push_str(&format!("Network throttling: {}\n", name));
+                        Some(conditions)
+                    }
+                    None => {
+                        return reply(
+                            format!("Unknown network preset '{}'; use slow-3g, fast-3g, 4g, offline or none", name),
+                            false,
+                        );
+                    }
+                },
+                None => None,
+            };
+            let cpu_slowdown = args.get("cpu_slowdown").and_then(|v| v.as_f64());
+            if let Some(rate) = cpu_slowdown {
+                out.push_str(&format!("CPU throttling: {}x\n", rate.max(1.0)));
+            }
+            if network.is_some() || cpu_slowdown.is_some() {
+                if let Err(e) = browser_manager.set_throttling(network, cpu_slowdown).await {
+                    return reply(format!("Failed to apply throttling: {}", e), false);
+                }
+            }
+            if let Err(e) = browser_manager.install_vitals_observer().await {
+                return reply(format!("Failed to install Web Vitals observers: {}", e), false);
+            }
+
+            match action.as_str() {
+                "measure" => match browser_manager.perf_report().await {
+                    Ok(report) => {
+                        out.push_str(&report.render());
+                        reply(out, true)
+                    }
+                    Err(e) => reply(format!("Failed to measure performance: {}", e), false),
+                },
+                "navigate" => {
+                    let trace = args.get("trace").and_then(|v| v.as_bool()).unwrap_or(true);
+                    let settle_ms = args.get("settle_ms").and_then(|v| v.as_u64()).unwrap_or(1500);
+                    let url = match str_arg("url") {
+                        Some(url) => url,
+                        None => match browser_manager.get_current_url().await {
+                            Some(url) => url,
+                            None => return reply("No page loaded; pass a url.".to_string(), false),
+                        },
+                    };
+                    if trace {
+                        if let Err(e) = browser_manager.start_trace().await {
+                            return reply(format!("Failed to start trace: {}", e), false);
+                        }
+                    }
+                    let loaded = browser_manager.goto(&url).await;
+                    tokio::time::sleep(std::time::Duration::from_millis(settle_ms)).await;
+                    let summary = if trace {
+                        let path = trace_path();
+                        match browser_manager.stop_trace(&path).await {
+                            Ok(summary) => {
+                                out.push_str(&format!("Trace saved to {}\n", path.display()));
+                                Some(summary)
+                            }
+                            Err(e) => {
+                                out.push_str(&format!("Trace failed: {}\n", e));
+                                None
+                            }
+                        }
+                    } else {
+                        None
+                    };
+                    if let Err(e) = loaded {
+                        return reply(format!("{}Failed to load {}: {}", out, url, e), false);
+                    }
+                    match browser_manager.perf_report().await {
+                        Ok(mut report) => {
+                            report.trace = summary;
+                            out.push_str(&report.render());
+                            reply(out, true)
+                        }
+                        Err(e) => reply(format!("{}Failed to measure performance: {}", out, e), false),
+                    }
+                }
+                "start_trace" => match browser_manager.start_trace().await {
+                    Ok(()) => {
+                        out.push_str("Tracing. Interact with the page, then call browser_perf with action 'stop_trace'.");
+                        reply(out, true)
+                    }
+                    Err(e) => reply(format!("Failed to start trace: {}", e), false),
+                },
+                "stop_trace" => {
+                    let path = trace_path();
+                    let summary = match browser_manager.stop_trace(&path).await {
+                        Ok(summary) => summary,
+                        Err(e) => return reply(format!("Failed to stop trace: {}", e), false),
+                    };
+                    out.push_str(&format!("Trace saved to {}\n", path.display()));
+                    match browser_manager.perf_report().await {
+                        Ok(mut report) => {
+                            report.trace = Some(summary);
+                            out.push_str(&report.render());
+                            reply(out, true)
+                        }
+                        Err(e) => reply(format!("{}Failed to measure performance: {}", out, e), false),
+                    }
+                }
+                other => reply(format!("Unknown browser_perf action: {}", other), false),
+            }
+        },
+    )
+    .await
+}
+
+async fn handle_browser_inspect(
+    sess: &Session,
+    arguments: String,
+    sub_id: String,
+    call_id: String,
+) -> ResponseInputItem {
+    use serde_json::json;
+    let params = serde_json::from_str(&arguments).ok();
+    let sess_clone = sess;
+    let arguments_clone = arguments.clone();
+    let call_id_clone = call_id.clone();
+
+    execute_custom_tool(
+        sess,
+        &sub_id,
+        call_id,
+        "browser_inspect".to_string(),
+        params,
+        || async move {
+            let browser_manager = get_browser_manager_for_session(sess_clone).await;
+            if let Some(browser_manager) = browser_manager {
+                let args: Result<Value, _> = serde_json::from_str(&arguments_clone);
+                match args {
+                    Ok(json) => {
+                        // Determine target element: by ref/selector, id, coords, or cursor
+                        let element = browser_element_target(Some(&json));
+                        let id_attr = json.get("id").and_then(|v| v.as_str()).map(|s| s.to_string());
+                        let mut x = json.get("x").and_then(|v| v.as_f64());
+                        let mut y = json.get("y").and_then(|v| v.as_f64());
+
+                        if (x.is_none() || y.is_none()) && id_attr.is_none() && element.is_none() {
+                            // No coords provided; use current cursor
+                            if let Ok((cx, cy)) = browser_manager.get_cursor_position().await {
+                                x = Some(cx);
+                                y = Some(cy);
+                            }
+                        }
+
+                        // Resolve nodeId
+                        let node_id_value = if let Some(target) = element.as_ref() {
+                            let backend = match browser_manager.resolve_element(target).await {
+                                Ok(el) => el.backend_node_id,
+                                Err(e) => {
+                                    return ResponseInputItem::FunctionCallOutput {
+                                        call_id: call_id_clone,
+                                        output: FunctionCallOutputPayload {
+                                            content: format!("Failed to locate {}: {}", target, e),
+                                            success: Some(false),
+                                        },
+                                    };
+                                }
+                            };
+                            // Backend ids must be pushed to the frontend, which requires the document.
+                            let _ = browser_manager.execute_cdp("DOM.getDocument", json!({})).await;
+                            browser_manager
+                                .execute_cdp(
+                                    "DOM.pushNodesByBackendIdsToFrontend",
+                                    json!({ "backendNodeIds": [backend] }),
+                                )
+                                .await
+                                .ok()
+                                .and_then(|pv| pv.get("nodeIds").and_then(|arr| arr.as_array().cloned()))
+                                .and_then(|arr| arr.first().cloned())
+                        } else if let Some(id_attr) = id_attr.clone() {
                             // Use DOM.getDocument -> DOM.querySelector with selector `#id`
                             let doc = browser_manager
                                 .execute_cdp("DOM.getDocument", json!({}))
@@ -6376,7 +9619,9 @@ async fn handle_browser_inspect(
 
                         // Format output
                         let mut out = String::new();
-                        if let (Some(ix), Some(iy)) = (x, y) {
+                        if let Some(target) = element {
+                            out.push_str(&format!("Target: {}\n", target));
+                        } else if let (Some(ix), Some(iy)) = (x, y) {
                             out.push_str(&format!("Target: coordinates ({}, {})\n", ix, iy));
                         }
                         if let Some(id_attr) = id_attr {
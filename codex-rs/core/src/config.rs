@@ -3,6 +3,7 @@ use crate::config_types::AgentConfig;
 use crate::config_types::BrowserConfig;
 use crate::config_types::History;
 use crate::config_types::ThemeName;
+use crate::config_types::McpRequestsConfig;
 use crate::config_types::McpServerConfig;
 use crate::config_types::SandboxWorkspaceWrite;
 use crate::config_types::ShellEnvironmentPolicy;
@@ -69,6 +70,11 @@ pub struct Config {
 
     pub shell_environment_policy: ShellEnvironmentPolicy,
 
+    /// When `true`, shell commands write to an overlay on top of the working
+    /// directory so the files they changed can be shown and then committed or
+    /// rolled back.
+    pub exec_snapshots: bool,
+
     /// When `true`, `AgentReasoning` events emitted by the backend will be
     /// suppressed from the frontend output. This can reduce visual noise when
     /// users are only interested in the final agent responses.
@@ -119,6 +125,9 @@ pub struct Config {
     /// Definition for MCP servers that Codex can reach out to for tool calls.
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// How requests sent by MCP servers (elicitation, sampling) are answered.
+    pub mcp_requests: McpRequestsConfig,
+
     /// Configuration for available agent models
     pub agents: Vec<AgentConfig>,
 
@@ -142,6 +151,9 @@ pub struct Config {
     /// Collection of settings that are specific to the TUI.
     pub tui: Tui,
 
+    /// Browser configuration for integrated screenshot capabilities.
+    pub browser: BrowserConfig,
+
     /// Semantic compression (memory) configuration.
     pub memory: MemoryConfig,
 
@@ -497,6 +509,10 @@ pub struct ConfigToml {
     #[serde(default)]
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// How requests sent by MCP servers (elicitation, sampling) are answered.
+    #[serde(default)]
+    pub mcp_requests: Option<McpRequestsConfig>,
+
     /// Configuration for available agent models
     #[serde(default)]
     pub agents: Vec<AgentConfig>,
@@ -540,6 +556,11 @@ pub struct ConfigToml {
     /// Defaults to `false`.
     pub show_raw_agent_reasoning: Option<bool>,
 
+    /// When set to `true`, run shell commands on an overlay of the working
+    /// directory so their file changes can be committed or rolled back.
+    /// Defaults to `false`.
+    pub exec_snapshots: Option<bool>,
+
     pub model_reasoning_effort: Option<ReasoningEffort>,
     pub model_reasoning_summary: Option<ReasoningSummary>,
     pub model_text_verbosity: Option<TextVerbosity>,
@@ -744,6 +765,7 @@ impl Config {
                 supports_reasoning_summaries,
                 uses_local_shell_tool: false,
                 uses_apply_patch_tool: false,
+                supports_image_input: false,
             }
         });
 
@@ -800,6 +822,7 @@ impl Config {
             user_instructions,
             base_instructions,
             mcp_servers: cfg.mcp_servers,
+            mcp_requests: cfg.mcp_requests.unwrap_or_default(),
             agents: cfg.agents,
             model_providers,
             project_doc_max_bytes: cfg.project_doc_max_bytes.unwrap_or(PROJECT_DOC_MAX_BYTES),
@@ -807,6 +830,7 @@ impl Config {
             history,
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
             tui: cfg.tui.unwrap_or_default(),
+            browser: cfg.browser.unwrap_or_default(),
             memory,
             codex_linux_sandbox_exe,
 
@@ -815,6 +839,7 @@ impl Config {
                 .show_raw_agent_reasoning
                 .or(show_raw_agent_reasoning)
                 .unwrap_or(false),
+            exec_snapshots: cfg.exec_snapshots.unwrap_or(false),
             model_reasoning_effort: config_profile
                 .model_reasoning_effort
                 .or(cfg.model_reasoning_effort)
@@ -972,6 +997,10 @@ pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
 mod tests {
     #![allow(clippy::expect_used, clippy::unwrap_used)]
     use crate::config_types::HistoryPersistence;
+    use crate::config_types::McpElicitationPolicy;
+    use crate::config_types::McpSamplingPolicy;
+    use crate::config_types::McpServerTransportConfig;
+    use crate::config_types::McpStartupMode;
 
     use super::*;
     use pretty_assertions::assert_eq;
@@ -1065,6 +1094,98 @@ exclude_slash_tmp = true
         );
     }
 
+    #[test]
+    fn test_mcp_server_transport_parsing() {
+        let mcp_servers = r#"
+[mcp_servers.local]
+command = "npx"
+args = ["-y", "mcp-server"]
+
+[mcp_servers.remote]
+url = "https://mcp.example.com/mcp"
+env_http_headers = { "Authorization" = "DOCS_MCP_TOKEN" }
+"#;
+        let cfg = toml::from_str::<ConfigToml>(mcp_servers)
+            .expect("TOML deserialization should succeed");
+
+        assert_eq!(
+            McpServerTransportConfig::Stdio {
+                command: "npx".to_string(),
+                args: vec!["-y".to_string(), "mcp-server".to_string()],
+                env: None,
+            },
+            cfg.mcp_servers["local"].transport
+        );
+        assert_eq!(
+            McpServerTransportConfig::StreamableHttp {
+                url: "https://mcp.example.com/mcp".to_string(),
+                headers: None,
+                env_http_headers: Some(HashMap::from([(
+                    "Authorization".to_string(),
+                    "DOCS_MCP_TOKEN".to_string()
+                )])),
+            },
+            cfg.mcp_servers["remote"].transport
+        );
+    }
+
+    #[test]
+    fn test_mcp_server_options_parsing() {
+        let mcp_servers = r#"
+[mcp_servers.local]
+command = "npx"
+startup = "lazy"
+startup_timeout_ms = 30000
+tool_timeout_ms = 60000
+enabled_tools = ["search_*"]
+disabled_tools = ["search_admin"]
+
+[mcp_servers.plain]
+command = "npx"
+"#;
+        let cfg = toml::from_str::<ConfigToml>(mcp_servers)
+            .expect("TOML deserialization should succeed");
+
+        assert_eq!(
+            McpServerConfig {
+                transport: McpServerTransportConfig::Stdio {
+                    command: "npx".to_string(),
+                    args: Vec::new(),
+                    env: None,
+                },
+                startup: McpStartupMode::Lazy,
+                startup_timeout_ms: Some(30_000),
+                tool_timeout_ms: Some(60_000),
+                enabled_tools: Some(vec!["search_*".to_string()]),
+                disabled_tools: vec!["search_admin".to_string()],
+            },
+            cfg.mcp_servers["local"]
+        );
+        assert_eq!(McpStartupMode::Eager, cfg.mcp_servers["plain"].startup);
+        assert_eq!(None, cfg.mcp_servers["plain"].enabled_tools);
+    }
+
+    #[test]
+    fn test_mcp_requests_parsing() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[mcp_requests]
+sampling = "approve"
+sampling_max_tokens = 512
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        assert_eq!(
+            Some(McpRequestsConfig {
+                elicitation: McpElicitationPolicy::Ask,
+                sampling: McpSamplingPolicy::Approve,
+                sampling_max_tokens: 512,
+            }),
+            cfg.mcp_requests
+        );
+    }
+
     struct PrecedenceTestFixture {
         cwd: TempDir,
         codex_home: TempDir,
@@ -1213,15 +1334,18 @@ disable_response_storage = true
                 notify: None,
                 cwd: fixture.cwd(),
                 mcp_servers: HashMap::new(),
+            mcp_requests: McpRequestsConfig::default(),
                 model_providers: fixture.model_provider_map.clone(),
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
                 codex_home: fixture.codex_home(),
                 history: History::default(),
                 file_opener: UriBasedFileOpener::VsCode,
                 tui: Tui::default(),
+                browser: BrowserConfig::default(),
                 codex_linux_sandbox_exe: None,
                 hide_agent_reasoning: false,
                 show_raw_agent_reasoning: false,
+                exec_snapshots: false,
                 model_reasoning_effort: ReasoningEffort::High,
                 model_reasoning_summary: ReasoningSummary::Detailed,
                 model_text_verbosity: TextVerbosity::default(),
@@ -1267,15 +1391,18 @@ disable_response_storage = true
             notify: None,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            mcp_requests: McpRequestsConfig::default(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             codex_home: fixture.codex_home(),
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
+            browser: BrowserConfig::default(),
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
             show_raw_agent_reasoning: false,
+            exec_snapshots: false,
             model_reasoning_effort: ReasoningEffort::default(),
             model_reasoning_summary: ReasoningSummary::default(),
             model_text_verbosity: TextVerbosity::default(),
@@ -1336,15 +1463,18 @@ disable_response_storage = true
             notify: None,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            mcp_requests: McpRequestsConfig::default(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             codex_home: fixture.codex_home(),
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
+            browser: BrowserConfig::default(),
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
             show_raw_agent_reasoning: false,
+            exec_snapshots: false,
             model_reasoning_effort: ReasoningEffort::default(),
             model_reasoning_summary: ReasoningSummary::default(),
             model_text_verbosity: TextVerbosity::default(),
@@ -48,13 +48,127 @@ fn default_true() -> bool {
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct McpServerConfig {
-    pub command: String,
+    #[serde(flatten)]
+    pub transport: McpServerTransportConfig,
 
+    /// When the server is started.
     #[serde(default)]
-    pub args: Vec<String>,
+    pub startup: McpStartupMode,
 
+    /// Time allowed for the server to answer `initialize` and `tools/list`.
+    /// Defaults to 10 seconds.
     #[serde(default)]
-    pub env: Option<HashMap<String, String>>,
+    pub startup_timeout_ms: Option<u64>,
+
+    /// Time a tool call may take before it fails. Unbounded by default.
+    #[serde(default)]
+    pub tool_timeout_ms: Option<u64>,
+
+    /// Globs naming the tools exposed to the model; all tools when unset.
+    #[serde(default)]
+    pub enabled_tools: Option<Vec<String>>,
+
+    /// Globs naming tools that are never exposed, even if enabled.
+    #[serde(default)]
+    pub disabled_tools: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum McpStartupMode {
+    /// Start the server with the session.
+    #[default]
+    Eager,
+
+    /// Start the server the first time one of its tools, resources or
+    /// prompts is used. The tools are remembered from the last time the
+    /// server ran; until they are (the first session after the server is
+    /// added or its command or URL changes), it is started with the session
+    /// like an eager one.
+    Lazy,
+}
+
+/// How to reach an MCP server: a `command` to spawn and talk to over stdio,
+/// or a `url` speaking the Streamable HTTP transport (falling back to the
+/// legacy HTTP+SSE transport for older servers).
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum McpServerTransportConfig {
+    Stdio {
+        command: String,
+
+        #[serde(default)]
+        args: Vec<String>,
+
+        #[serde(default)]
+        env: Option<HashMap<String, String>>,
+    },
+    StreamableHttp {
+        url: String,
+
+        /// Headers sent with every request, e.g. `Authorization`.
+        #[serde(default)]
+        headers: Option<HashMap<String, String>>,
+
+        /// Headers whose values are read from environment variables when
+        /// connecting, keyed by header name, so secrets stay out of the
+        /// config file.
+        #[serde(default)]
+        env_http_headers: Option<HashMap<String, String>>,
+    },
+}
+
+/// How Codex answers requests that MCP servers send it.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct McpRequestsConfig {
+    /// Requests for input from the user (`elicitation/create`).
+    #[serde(default)]
+    pub elicitation: McpElicitationPolicy,
+
+    /// Requests to sample from the model (`sampling/createMessage`).
+    #[serde(default)]
+    pub sampling: McpSamplingPolicy,
+
+    /// Upper bound on the output tokens of a sampling request, whatever the
+    /// server asks for.
+    #[serde(default = "default_sampling_max_tokens")]
+    pub sampling_max_tokens: u32,
+}
+
+impl Default for McpRequestsConfig {
+    fn default() -> Self {
+        Self {
+            elicitation: McpElicitationPolicy::default(),
+            sampling: McpSamplingPolicy::default(),
+            sampling_max_tokens: default_sampling_max_tokens(),
+        }
+    }
+}
+
+fn default_sampling_max_tokens() -> u32 {
+    4096
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum McpElicitationPolicy {
+    /// Show the form to the user. Front-ends that cannot (`codex exec`)
+    /// decline.
+    #[default]
+    Ask,
+    Decline,
+    Cancel,
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum McpSamplingPolicy {
+    /// Ask the user to approve each request. Front-ends that cannot
+    /// (`codex exec`) deny.
+    #[default]
+    Ask,
+    Approve,
+    Deny,
 }
 
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
@@ -226,6 +340,44 @@ pub struct BrowserConfig {
 
     #[serde(default)]
     pub format: Option<BrowserImageFormat>,
+
+    /// BCP 47 locale pages report, e.g. `de-DE`. Defaults to the host's `LANG`.
+    #[serde(default)]
+    pub locale: Option<String>,
+
+    /// IANA timezone pages report, e.g. `America/New_York`. Defaults to the
+    /// host's `TZ`, or the zone `/etc/localtime` points to.
+    #[serde(default)]
+    pub timezone: Option<String>,
+
+    /// `Accept-Language` header; derived from `locale` when unset.
+    #[serde(default)]
+    pub accept_language: Option<String>,
+
+    /// Position reported by `navigator.geolocation`.
+    #[serde(default)]
+    pub geolocation: Option<BrowserGeolocation>,
+
+    /// Emulated `prefers-color-scheme`.
+    #[serde(default)]
+    pub color_scheme: Option<BrowserColorScheme>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BrowserGeolocation {
+    pub latitude: f64,
+    pub longitude: f64,
+
+    /// Accuracy in meters.
+    #[serde(default)]
+    pub accuracy: Option<f64>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BrowserColorScheme {
+    Light,
+    Dark,
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
@@ -101,4 +101,12 @@ impl ConversationManager {
             .cloned()
             .ok_or_else(|| CodexErr::ConversationNotFound(conversation_id))
     }
+
+    /// Stop tracking a conversation, returning it if it was known.
+    pub async fn remove_conversation(
+        &self,
+        conversation_id: Uuid,
+    ) -> Option<Arc<CodexConversation>> {
+        self.conversations.write().await.remove(&conversation_id)
+    }
 }
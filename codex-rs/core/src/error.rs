@@ -1,3 +1,4 @@
+use crate::exec::ExecToolCallOutput;
 use reqwest::StatusCode;
 use serde_json;
 use std::io;
@@ -11,8 +12,13 @@ pub type Result<T> = std::result::Result<T, CodexErr>;
 #[derive(Error, Debug)]
 pub enum SandboxErr {
     /// Error from sandbox execution
-    #[error("sandbox denied exec error, exit code: {0}, stdout: {1}, stderr: {2}")]
-    Denied(i32, String, String),
+    #[error(
+        "sandbox denied exec error, exit code: {}, stdout: {}, stderr: {}",
+        .0.exit_code,
+        .0.stdout.text,
+        .0.stderr.text
+    )]
+    Denied(Box<ExecToolCallOutput>),
 
     /// Error from linux seccomp filter setup
     #[cfg(target_os = "linux")]
@@ -183,7 +189,7 @@ impl CodexErr {
 
 pub fn get_error_message_ui(e: &CodexErr) -> String {
     match e {
-        CodexErr::Sandbox(SandboxErr::Denied(_, _, stderr)) => stderr.to_string(),
+        CodexErr::Sandbox(SandboxErr::Denied(output)) => output.stderr.text.clone(),
         _ => e.to_string(),
     }
 }
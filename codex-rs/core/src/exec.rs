@@ -17,12 +17,16 @@ use tokio::process::Child;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
-use crate::landlock::spawn_command_under_linux_sandbox;
+use crate::exec_snapshot::OverlayMount;
+use crate::landlock::spawn_command_under_linux_sandbox_in_overlay;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandOutputDeltaEvent;
 use crate::protocol::ExecOutputStream;
 use crate::protocol::SandboxPolicy;
+use crate::sandbox_denial::SandboxDenial;
+use crate::sandbox_denial::detect_sandbox_denials;
+use crate::sandbox_denial::read_denial_log;
 use crate::seatbelt::spawn_command_under_seatbelt;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
@@ -31,8 +35,10 @@ use serde_bytes::ByteBuf;
 // Maximum we send for each stream, which is either:
 // - 10KiB OR
 // - 256 lines
-const MAX_STREAM_OUTPUT: usize = 10 * 1024;
-const MAX_STREAM_OUTPUT_LINES: usize = 256;
+// Shell sessions and background processes return output to the model under
+// the same limits.
+pub(crate) const MAX_STREAM_OUTPUT: usize = 10 * 1024;
+pub(crate) const MAX_STREAM_OUTPUT_LINES: usize = 256;
 
 const DEFAULT_TIMEOUT_MS: u64 = 120_000;
 
@@ -49,6 +55,9 @@ pub struct ExecParams {
     pub env: HashMap<String, String>,
     pub with_escalated_permissions: Option<bool>,
     pub justification: Option<String>,
+    /// Run the command on top of this exec snapshot overlay so that its
+    /// writes can be reviewed before they reach the real tree.
+    pub overlay: Option<OverlayMount>,
 }
 
 impl ExecParams {
@@ -83,6 +92,9 @@ pub async fn process_exec_tool_call(
     stdout_stream: Option<StdoutStream>,
 ) -> Result<ExecToolCallOutput> {
     let start = Instant::now();
+    let sandbox_cwd = params.cwd.clone();
+    // Where `codex-linux-sandbox` records what the sandbox blocked.
+    let mut denial_log: Option<tempfile::NamedTempFile> = None;
 
     let raw_output_result: std::result::Result<RawExecToolCallOutput, CodexErr> = match sandbox_type
     {
@@ -105,19 +117,29 @@ pub async fn process_exec_tool_call(
         SandboxType::LinuxSeccomp => {
             let timeout = params.timeout_duration();
             let ExecParams {
-                command, cwd, env, ..
+                command,
+                cwd,
+                env,
+                overlay,
+                ..
             } = params;
 
             let codex_linux_sandbox_exe = codex_linux_sandbox_exe
                 .as_ref()
                 .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
-            let child = spawn_command_under_linux_sandbox(
+            denial_log = tempfile::Builder::new()
+                .prefix("codex-denials-")
+                .tempfile()
+                .ok();
+            let child = spawn_command_under_linux_sandbox_in_overlay(
                 codex_linux_sandbox_exe,
                 command,
                 sandbox_policy,
                 cwd,
                 StdioPolicy::RedirectForShellTool,
                 env,
+                overlay.as_ref(),
+                denial_log.as_ref().map(|log| log.path()),
             )
             .await?;
 
@@ -140,21 +162,31 @@ pub async fn process_exec_tool_call(
             }
 
             let exit_code = raw_output.exit_status.code().unwrap_or(-1);
+            let sandbox_denials = if exit_code == 0 {
+                Vec::new()
+            } else if let Some(log) = &denial_log {
+                // The helper unlinks the log once it has opened it, so read
+                // it back through our own handle.
+                read_denial_log(log.as_file())
+            } else if sandbox_type == SandboxType::MacosSeatbelt {
+                detect_sandbox_denials(&stderr.text, sandbox_policy, &sandbox_cwd)
+            } else {
+                Vec::new()
+            };
 
-            if exit_code != 0 && is_likely_sandbox_denied(sandbox_type, exit_code) {
-                return Err(CodexErr::Sandbox(SandboxErr::Denied(
-                    exit_code,
-                    stdout.text,
-                    stderr.text,
-                )));
-            }
-
-            Ok(ExecToolCallOutput {
+            let output = ExecToolCallOutput {
                 exit_code,
                 stdout,
                 stderr,
                 duration,
-            })
+                sandbox_denials,
+            };
+
+            if exit_code != 0 && is_likely_sandbox_denied(sandbox_type, exit_code) {
+                return Err(CodexErr::Sandbox(SandboxErr::Denied(Box::new(output))));
+            }
+
+            Ok(output)
         }
         Err(err) => {
             tracing::error!("exec error: {err}");
@@ -219,6 +251,8 @@ pub struct ExecToolCallOutput {
     pub stdout: StreamOutput<String>,
     pub stderr: StreamOutput<String>,
     pub duration: Duration,
+    /// Operations the sandbox blocked, when the command failed.
+    pub sandbox_denials: Vec<SandboxDenial>,
 }
 
 async fn exec(
@@ -228,7 +262,11 @@ async fn exec(
 ) -> Result<RawExecToolCallOutput> {
     let timeout = params.timeout_duration();
     let ExecParams {
-        command, cwd, env, ..
+        command,
+        cwd,
+        env,
+        overlay,
+        ..
     } = params;
 
     let (program, args) = command.split_first().ok_or_else(|| {
@@ -246,6 +284,7 @@ async fn exec(
         sandbox_policy,
         StdioPolicy::RedirectForShellTool,
         env,
+        overlay.as_ref(),
     )
     .await?;
     consume_truncated_output(child, timeout, stdout_stream).await
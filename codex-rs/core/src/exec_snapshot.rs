@@ -0,0 +1,679 @@
+//! Filesystem snapshots around shell commands.
+//!
+//! `TurnDiffTracker` only sees edits made through `apply_patch`; anything a
+//! shell command writes is invisible to it and cannot be undone. When
+//! `exec_snapshots` is enabled, shell commands run in their own user and
+//! mount namespace in which the session's working directory is covered by an
+//! overlayfs mount: the real tree is the lower layer and every write lands in
+//! a private upper layer. Once the command exits, that upper layer is exactly
+//! what it changed. It can be shown to the user and then either committed
+//! (copied into the real tree) or rolled back (thrown away).
+//!
+//! Layers that are neither committed nor rolled back stay pending, and later
+//! commands run with the pending layers stacked below their own so they see
+//! the earlier changes as if they were on disk. Because of that ordering,
+//! committing a layer also commits every older pending layer and rolling one
+//! back also discards every newer one.
+//!
+//! Mounting an overlay without privileges needs Linux 5.11 or newer with
+//! unprivileged user namespaces; [`overlay_supported`] checks for that.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use tempfile::TempDir;
+
+/// Cap on the unified diff included in an [`ExecSnapshotEvent`].
+///
+/// [`ExecSnapshotEvent`]: crate::protocol::ExecSnapshotEvent
+const MAX_DIFF_BYTES: usize = 64 * 1024;
+
+/// All layers of an overlay are passed in one mount option string, which
+/// has to fit in a page, so only this many pending layers are stacked.
+pub const MAX_PENDING_LAYERS: usize = 32;
+
+const DEV_NULL: &str = "/dev/null";
+
+/// One overlay layer on disk: `upper/` receives the writes of a command and
+/// `work/` is scratch space overlayfs needs on the same filesystem. The
+/// directory is removed when the last reference is dropped.
+#[derive(Debug)]
+pub struct Layer {
+    dir: TempDir,
+}
+
+impl Layer {
+    pub fn new(parent: &Path) -> io::Result<Self> {
+        fs::create_dir_all(parent)?;
+        let dir = tempfile::Builder::new()
+            .prefix("layer-")
+            .tempdir_in(parent)?;
+        fs::create_dir(dir.path().join("upper"))?;
+        fs::create_dir(dir.path().join("work"))?;
+        Ok(Self { dir })
+    }
+
+    pub fn upper(&self) -> PathBuf {
+        self.dir.path().join("upper")
+    }
+
+    fn work(&self) -> PathBuf {
+        self.dir.path().join("work")
+    }
+}
+
+impl Drop for Layer {
+    fn drop(&mut self) {
+        // Overlayfs leaves `work/work` without any permissions, which would
+        // keep the directory from being removed.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ =
+                fs::set_permissions(self.work().join("work"), fs::Permissions::from_mode(0o700));
+        }
+    }
+}
+
+/// The overlay a single command runs on.
+#[derive(Debug, Clone)]
+pub struct OverlayMount {
+    /// Directory covered by the overlay.
+    root: PathBuf,
+    /// Pending layers below this command's own, newest first.
+    lower: Vec<Arc<Layer>>,
+    layer: Arc<Layer>,
+}
+
+impl OverlayMount {
+    pub fn new(root: PathBuf, lower: Vec<Arc<Layer>>, layer: Layer) -> Self {
+        Self {
+            root,
+            lower,
+            layer: Arc::new(layer),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    #[cfg(target_os = "linux")]
+    fn mount_options(&self) -> io::Result<String> {
+        let mut lowerdir = self
+            .lower
+            .iter()
+            .map(|layer| escape_option(&layer.upper()))
+            .collect::<io::Result<Vec<_>>>()?;
+        lowerdir.push(escape_option(&self.root)?);
+        Ok(format!(
+            "lowerdir={},upperdir={},workdir={},userxattr",
+            lowerdir.join(":"),
+            escape_option(&self.layer.upper())?,
+            escape_option(&self.layer.work())?,
+        ))
+    }
+
+    /// Returns a `pre_exec` hook that moves the child into a new user and
+    /// mount namespace, mounts the overlay over `root` and changes into `cwd`
+    /// again so that relative paths resolve inside the overlay.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn pre_exec_hook(
+        &self,
+        cwd: &Path,
+    ) -> io::Result<impl FnMut() -> io::Result<()> + Send + Sync + 'static> {
+        let root = c_path(&self.root)?;
+        let cwd = c_path(cwd)?;
+        let options = std::ffi::CString::new(self.mount_options()?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        // SAFETY: getuid(2) and getgid(2) always succeed.
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        let uid_map = format!("{uid} {uid} 1");
+        let gid_map = format!("{gid} {gid} 1");
+
+        // Runs in the forked child, so it must not allocate.
+        Ok(move || {
+            // SAFETY: plain syscalls on NUL-terminated strings owned by the
+            // closure.
+            unsafe {
+                if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNS) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                write_proc_file(c"/proc/self/setgroups", b"deny")?;
+                write_proc_file(c"/proc/self/uid_map", uid_map.as_bytes())?;
+                write_proc_file(c"/proc/self/gid_map", gid_map.as_bytes())?;
+                if libc::mount(
+                    std::ptr::null(),
+                    c"/".as_ptr(),
+                    std::ptr::null(),
+                    libc::MS_REC | libc::MS_PRIVATE,
+                    std::ptr::null(),
+                ) == -1
+                {
+                    return Err(io::Error::last_os_error());
+                }
+                if libc::mount(
+                    c"overlay".as_ptr(),
+                    root.as_ptr(),
+                    c"overlay".as_ptr(),
+                    0,
+                    options.as_ptr().cast(),
+                ) == -1
+                {
+                    return Err(io::Error::last_os_error());
+                }
+                if libc::chdir(cwd.as_ptr()) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Checks that an overlay can be mounted in an unprivileged user namespace
+/// by running a command that writes a file inside a throwaway overlay under
+/// `scratch`.
+#[cfg(target_os = "linux")]
+pub fn overlay_supported(scratch: &Path) -> io::Result<()> {
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+    use std::process::Stdio;
+
+    fs::create_dir_all(scratch)?;
+    let root = tempfile::Builder::new()
+        .prefix("probe-")
+        .tempdir_in(scratch)?;
+    let mount = OverlayMount::new(root.path().to_path_buf(), Vec::new(), Layer::new(scratch)?);
+    let mut cmd = Command::new("/bin/sh");
+    cmd.args(["-c", ": > probe"])
+        .current_dir(root.path())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // SAFETY: the hook only performs async-signal-safe syscalls.
+    unsafe {
+        cmd.pre_exec(mount.pre_exec_hook(root.path())?);
+    }
+    let status = cmd.status().map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("cannot mount an overlay in a user namespace: {e}"),
+        )
+    })?;
+    if !status.success()
+        || root.path().join("probe").exists()
+        || !mount.layer.upper().join("probe").exists()
+    {
+        return Err(io::Error::other(
+            "writes inside the overlay did not reach the upper layer",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn overlay_supported(_scratch: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "exec snapshots rely on overlayfs and are only available on Linux",
+    ))
+}
+
+/// How a command changed one path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotChange {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/// Size and modification time of a path in the real tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    fs::symlink_metadata(path).ok().map(|meta| Stamp {
+        len: meta.len(),
+        modified: meta.modified().ok(),
+    })
+}
+
+/// Changes made by a single command, waiting to be committed or rolled back.
+#[derive(Debug, Clone)]
+pub struct ExecSnapshot {
+    pub id: String,
+    pub call_id: String,
+    pub command: Vec<String>,
+    /// Directory the overlay covered.
+    pub root: PathBuf,
+    pub layer: Arc<Layer>,
+    /// Paths relative to `root`.
+    pub changes: Vec<(SnapshotChange, PathBuf)>,
+    /// Unified diff against the tree the command saw, truncated to a
+    /// displayable size.
+    pub unified_diff: String,
+    /// State of each changed path in the real tree when the command finished,
+    /// used to notice edits made outside the overlay before committing.
+    stamps: Vec<Option<Stamp>>,
+}
+
+impl ExecSnapshot {
+    /// Collects what the command that ran on `mount` changed. Returns
+    /// `Ok(None)` when it changed nothing.
+    pub fn from_mount(
+        id: String,
+        call_id: String,
+        command: Vec<String>,
+        mount: &OverlayMount,
+    ) -> io::Result<Option<Self>> {
+        let lower = LowerView {
+            root: &mount.root,
+            layers: mount.lower.iter().map(|layer| layer.upper()).collect(),
+        };
+        let upper = mount.layer.upper();
+        let mut changes = Vec::new();
+        scan_upper(&upper, Path::new(""), &lower, false, &mut changes)?;
+        if changes.is_empty() {
+            return Ok(None);
+        }
+        let unified_diff = unified_diff(&upper, &lower, &changes);
+        let stamps = changes
+            .iter()
+            .map(|(_, path)| stamp(&mount.root.join(path)))
+            .collect();
+        Ok(Some(Self {
+            id,
+            call_id,
+            command,
+            root: mount.root.clone(),
+            layer: mount.layer.clone(),
+            changes,
+            unified_diff,
+            stamps,
+        }))
+    }
+
+    /// Copies the layer into the real tree. Refuses (and changes nothing) if
+    /// any of the changed paths was modified outside the overlay since the
+    /// command finished; the conflicting paths are listed in the error.
+    pub fn commit(&self) -> io::Result<()> {
+        let conflicts: Vec<String> = self
+            .changes
+            .iter()
+            .zip(&self.stamps)
+            .filter(|((_, path), before)| stamp(&self.root.join(path)) != **before)
+            .map(|((_, path), _)| path.display().to_string())
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(io::Error::other(format!(
+                "files changed outside the snapshot since the command finished: {}",
+                conflicts.join(", ")
+            )));
+        }
+        apply_layer(&self.layer.upper(), &self.root, Path::new(""))
+    }
+
+    /// Records the new state of paths that `committed`, an older layer, just
+    /// wrote to the real tree so they do not count as conflicts later.
+    pub fn refresh_stamps(&mut self, committed: &ExecSnapshot) {
+        for ((_, path), before) in self.changes.iter().zip(self.stamps.iter_mut()) {
+            if committed.changes.iter().any(|(_, p)| p == path) {
+                *before = stamp(&self.root.join(path));
+            }
+        }
+    }
+}
+
+/// The tree a command saw before it ran: pending layers, newest first, on
+/// top of the real tree.
+struct LowerView<'a> {
+    root: &'a Path,
+    layers: Vec<PathBuf>,
+}
+
+impl LowerView<'_> {
+    /// Where the entry visible at `rel` lives, if there is one.
+    fn resolve(&self, rel: &Path) -> Option<PathBuf> {
+        let components: Vec<_> = rel.components().collect();
+        for layer in &self.layers {
+            let mut path = layer.clone();
+            let mut hides_lower = false;
+            for (i, component) in components.iter().enumerate() {
+                path.push(component);
+                let Ok(meta) = fs::symlink_metadata(&path) else {
+                    break;
+                };
+                if is_whiteout(&meta) {
+                    return None;
+                }
+                if i + 1 == components.len() {
+                    return Some(path);
+                }
+                if !meta.is_dir() {
+                    return None;
+                }
+                hides_lower |= is_opaque(&path);
+            }
+            if hides_lower {
+                return None;
+            }
+        }
+        let path = self.root.join(rel);
+        fs::symlink_metadata(&path).is_ok().then_some(path)
+    }
+}
+
+/// Walks the upper layer below `rel`. `replaced` is set inside directories
+/// that were deleted and created again, whose contents are all new.
+fn scan_upper(
+    upper: &Path,
+    rel: &Path,
+    lower: &LowerView<'_>,
+    replaced: bool,
+    changes: &mut Vec<(SnapshotChange, PathBuf)>,
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(upper.join(rel))?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = rel.join(entry.file_name());
+        // Does not follow symlinks.
+        let meta = entry.metadata()?;
+        if is_whiteout(&meta) {
+            changes.push((SnapshotChange::Deleted, path));
+        } else if meta.is_dir() {
+            let opaque = !replaced && is_opaque(&entry.path());
+            if opaque && lower.resolve(&path).is_some() {
+                changes.push((SnapshotChange::Deleted, path.clone()));
+            }
+            scan_upper(upper, &path, lower, replaced || opaque, changes)?;
+        } else {
+            match lower.resolve(&path).filter(|_| !replaced) {
+                // Opening a file for writing copies it up even if nothing
+                // is written.
+                Some(before) if same_entry(&before, &entry.path()) => {}
+                Some(_) => changes.push((SnapshotChange::Modified, path)),
+                None => changes.push((SnapshotChange::Added, path)),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn same_entry(before: &Path, after: &Path) -> bool {
+    let (Ok(old), Ok(new)) = (fs::symlink_metadata(before), fs::symlink_metadata(after)) else {
+        return false;
+    };
+    if old.file_type() != new.file_type()
+        || old.permissions() != new.permissions()
+        || old.len() != new.len()
+    {
+        return false;
+    }
+    if old.file_type().is_symlink() {
+        return fs::read_link(before).ok() == fs::read_link(after).ok();
+    }
+    matches!((fs::read(before), fs::read(after)), (Ok(old), Ok(new)) if old == new)
+}
+
+fn unified_diff(
+    upper: &Path,
+    lower: &LowerView<'_>,
+    changes: &[(SnapshotChange, PathBuf)],
+) -> String {
+    let mut diff = String::new();
+    for (change, path) in changes {
+        if diff.len() > MAX_DIFF_BYTES {
+            break;
+        }
+        let display = path.display();
+        let before = match change {
+            SnapshotChange::Added => None,
+            _ => lower.resolve(path),
+        };
+        let after = match change {
+            SnapshotChange::Deleted => None,
+            _ => Some(upper.join(path)),
+        };
+        let old_header = match &before {
+            Some(_) => format!("a/{display}"),
+            None => DEV_NULL.to_string(),
+        };
+        let new_header = match &after {
+            Some(_) => format!("b/{display}"),
+            None => DEV_NULL.to_string(),
+        };
+
+        diff.push_str(&format!("diff --git a/{display} b/{display}\n"));
+        if before.as_deref().is_some_and(Path::is_dir) {
+            diff.push_str(&format!("--- {old_header}/\n+++ {new_header}\n"));
+            diff.push_str("Directory removed\n");
+            continue;
+        }
+        match (text_of(before.as_deref()), text_of(after.as_deref())) {
+            (Some(old), Some(new)) => {
+                let unified = similar::TextDiff::from_lines(&old, &new)
+                    .unified_diff()
+                    .context_radius(3)
+                    .header(&old_header, &new_header)
+                    .to_string();
+                diff.push_str(&unified);
+            }
+            _ => {
+                diff.push_str(&format!("--- {old_header}\n+++ {new_header}\n"));
+                diff.push_str("Binary files differ\n");
+            }
+        }
+    }
+    truncate_diff(diff)
+}
+
+/// Text of a file for diffing; an absent file is empty. `None` for
+/// anything that is not valid UTF-8 text.
+fn text_of(path: Option<&Path>) -> Option<String> {
+    let Some(path) = path else {
+        return Some(String::new());
+    };
+    let meta = fs::symlink_metadata(path).ok()?;
+    if meta.file_type().is_symlink() {
+        return fs::read_link(path)
+            .ok()
+            .map(|target| format!("{}\n", target.display()));
+    }
+    if !meta.is_file() {
+        return None;
+    }
+    String::from_utf8(fs::read(path).ok()?).ok()
+}
+
+/// Replays the upper layer below `rel` onto `root`.
+fn apply_layer(upper: &Path, root: &Path, rel: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(upper.join(rel))? {
+        let entry = entry?;
+        let rel = rel.join(entry.file_name());
+        let src = entry.path();
+        let dst = root.join(&rel);
+        let meta = entry.metadata()?;
+        if is_whiteout(&meta) {
+            remove_path(&dst)?;
+        } else if meta.is_dir() {
+            match fs::symlink_metadata(&dst) {
+                Ok(existing) if existing.is_dir() => {
+                    if is_opaque(&src) {
+                        fs::remove_dir_all(&dst)?;
+                        fs::create_dir(&dst)?;
+                    }
+                }
+                Ok(_) => {
+                    fs::remove_file(&dst)?;
+                    fs::create_dir(&dst)?;
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => fs::create_dir(&dst)?,
+                Err(e) => return Err(e),
+            }
+            apply_layer(upper, root, &rel)?;
+            // Only now, in case the directory is read-only.
+            fs::set_permissions(&dst, meta.permissions())?;
+        } else if meta.file_type().is_symlink() {
+            remove_path(&dst)?;
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(fs::read_link(&src)?, &dst)?;
+        } else if meta.is_file() {
+            // Overwrite regular files in place so hard links survive.
+            if !fs::symlink_metadata(&dst).is_ok_and(|existing| existing.is_file()) {
+                remove_path(&dst)?;
+            }
+            fs::copy(&src, &dst)?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Overlayfs records a deleted entry as a 0/0 character device.
+#[cfg(unix)]
+fn is_whiteout(meta: &fs::Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::fs::MetadataExt;
+    meta.file_type().is_char_device() && meta.rdev() == 0
+}
+
+#[cfg(not(unix))]
+fn is_whiteout(_meta: &fs::Metadata) -> bool {
+    false
+}
+
+/// A directory that was deleted and created again is marked opaque, which
+/// hides everything below it in lower layers.
+#[cfg(target_os = "linux")]
+fn is_opaque(dir: &Path) -> bool {
+    let Ok(path) = c_path(dir) else {
+        return false;
+    };
+    let mut value = [0u8; 1];
+    // SAFETY: both strings are NUL-terminated and `value` is writable for
+    // `value.len()` bytes.
+    let len = unsafe {
+        libc::lgetxattr(
+            path.as_ptr(),
+            c"user.overlay.opaque".as_ptr(),
+            value.as_mut_ptr().cast(),
+            value.len(),
+        )
+    };
+    len == 1 && value[0] == b'y'
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_opaque(_dir: &Path) -> bool {
+    false
+}
+
+#[cfg(target_os = "linux")]
+fn c_path(path: &Path) -> io::Result<std::ffi::CString> {
+    use std::os::unix::ffi::OsStrExt;
+    std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Writes `contents` to a file under `/proc` without allocating.
+#[cfg(target_os = "linux")]
+fn write_proc_file(path: &std::ffi::CStr, contents: &[u8]) -> io::Result<()> {
+    // SAFETY: `path` is NUL-terminated and `contents` is a valid buffer.
+    unsafe {
+        let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+        if fd == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        let written = libc::write(fd, contents.as_ptr().cast(), contents.len());
+        let err = io::Error::last_os_error();
+        libc::close(fd);
+        if written != contents.len() as isize {
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+/// Escapes the separators overlayfs recognizes in its mount options.
+#[cfg(target_os = "linux")]
+fn escape_option(path: &Path) -> io::Result<String> {
+    let path = path.to_str().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("overlay paths must be valid UTF-8: {}", path.display()),
+        )
+    })?;
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if matches!(c, '\\' | ':' | ',') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    Ok(escaped)
+}
+
+fn truncate_diff(mut diff: String) -> String {
+    if diff.len() <= MAX_DIFF_BYTES {
+        return diff;
+    }
+    let mut cut = MAX_DIFF_BYTES;
+    while !diff.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    diff.truncate(cut);
+    diff.push_str("\n[diff truncated]\n");
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn escapes_overlay_option_separators() {
+        assert_eq!(
+            escape_option(Path::new("/tmp/a:b,c\\d")).unwrap(),
+            "/tmp/a\\:b\\,c\\\\d"
+        );
+    }
+
+    #[test]
+    fn lower_view_prefers_newer_layers() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().join("root");
+        let newer = tmp.path().join("newer");
+        let older = tmp.path().join("older");
+        for dir in [&root, &newer, &older] {
+            fs::create_dir_all(dir.join("dir")).unwrap();
+        }
+        fs::write(root.join("dir/a"), "root").unwrap();
+        fs::write(older.join("dir/a"), "older").unwrap();
+        fs::write(root.join("b"), "root").unwrap();
+
+        let lower = LowerView {
+            root: &root,
+            layers: vec![newer.clone(), older.clone()],
+        };
+        assert_eq!(lower.resolve(Path::new("dir/a")), Some(older.join("dir/a")));
+        assert_eq!(lower.resolve(Path::new("b")), Some(root.join("b")));
+        assert_eq!(lower.resolve(Path::new("missing")), None);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::exec_snapshot::OverlayMount;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
@@ -24,7 +25,37 @@ pub async fn spawn_command_under_linux_sandbox<P>(
 where
     P: AsRef<Path>,
 {
-    let args = create_linux_sandbox_command_args(command, sandbox_policy, &cwd);
+    spawn_command_under_linux_sandbox_in_overlay(
+        codex_linux_sandbox_exe,
+        command,
+        sandbox_policy,
+        cwd,
+        stdio_policy,
+        env,
+        None,
+        None,
+    )
+    .await
+}
+
+/// Like [`spawn_command_under_linux_sandbox`], but starts the helper on top
+/// of an exec snapshot overlay when one is given and has it log what the
+/// sandbox blocked to `denial_log`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn spawn_command_under_linux_sandbox_in_overlay<P>(
+    codex_linux_sandbox_exe: P,
+    command: Vec<String>,
+    sandbox_policy: &SandboxPolicy,
+    cwd: PathBuf,
+    stdio_policy: StdioPolicy,
+    env: HashMap<String, String>,
+    overlay: Option<&OverlayMount>,
+    denial_log: Option<&Path>,
+) -> std::io::Result<Child>
+where
+    P: AsRef<Path>,
+{
+    let args = create_linux_sandbox_command_args(command, sandbox_policy, &cwd, denial_log);
     let arg0 = Some("codex-linux-sandbox");
     spawn_child_async(
         codex_linux_sandbox_exe.as_ref().to_path_buf(),
@@ -34,6 +65,7 @@ where
         sandbox_policy,
         stdio_policy,
         env,
+        overlay,
     )
     .await
 }
@@ -43,6 +75,7 @@ fn create_linux_sandbox_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    denial_log: Option<&Path>,
 ) -> Vec<String> {
     #[expect(clippy::expect_used)]
     let sandbox_policy_cwd = cwd.to_str().expect("cwd must be valid UTF-8").to_string();
@@ -51,13 +84,18 @@ fn create_linux_sandbox_command_args(
     let sandbox_policy_json =
         serde_json::to_string(sandbox_policy).expect("Failed to serialize SandboxPolicy to JSON");
 
-    let mut linux_cmd: Vec<String> = vec![
+    let mut linux_cmd: Vec<String> = Vec::new();
+    if let Some(denial_log) = denial_log {
+        linux_cmd.push("--denial-log".to_string());
+        linux_cmd.push(denial_log.to_string_lossy().into_owned());
+    }
+    linux_cmd.extend([
         sandbox_policy_cwd,
         sandbox_policy_json,
         // Separator so that command arguments starting with `-` are not parsed as
         // options of the helper itself.
         "--".to_string(),
-    ];
+    ]);
 
     // Append the original tool command.
     linux_cmd.extend(command);
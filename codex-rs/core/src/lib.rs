@@ -7,6 +7,7 @@
 
 mod agent_tool;
 mod apply_patch;
+pub mod background_process;
 mod bash;
 mod chat_completions;
 mod client;
@@ -26,6 +27,7 @@ mod environment_context;
 pub mod error;
 pub mod exec;
 pub mod exec_env;
+pub mod exec_snapshot;
 mod flags;
 pub mod git_info;
 mod image_comparison;
@@ -51,17 +53,32 @@ mod openai_tools;
 pub mod plan_tool;
 pub mod project_doc;
 mod rollout;
+pub use rollout::ConversationSummary;
+pub use rollout::ConversationsPage;
+pub use rollout::archive_rollout;
+pub use rollout::find_rollout;
+pub use rollout::fork_rollout;
+pub use rollout::list_conversations;
+pub use rollout::read_rollout_session_id;
+pub use rollout::recorded_rollouts;
 pub(crate) mod safety;
+pub mod sandbox_denial;
 pub mod seatbelt;
 pub mod shell;
+pub mod shell_session;
 pub mod spawn;
 pub mod terminal;
 pub mod turn_diff_tracker;
 pub mod user_agent;
 mod user_notification;
 pub mod util;
+pub mod visual_baseline;
 pub use apply_patch::CODEX_APPLY_PATCH_ARG1;
+pub use safety::SafetyCheck;
+pub use safety::assess_command_safety;
+pub use safety::assess_patch_safety;
 pub use safety::get_platform_sandbox;
+pub use safety::is_write_patch_constrained_to_writable_paths;
 // Use our local protocol definitions to preserve custom events and input items.
 pub mod protocol;
 // Optionally expose upstream protocol config enums for callers that need them.
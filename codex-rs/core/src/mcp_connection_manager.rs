@@ -4,29 +4,83 @@
 //! configured server (keyed by the *server name*). It offers convenience
 //! helpers to query the available tools across *all* servers and returns them
 //! in a single aggregated map using the fully-qualified tool name
-//! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key.
+//! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key. A server's
+//! `enabled_tools`/`disabled_tools` globs decide which of its tools are in
+//! the map.
+//!
+//! Resources and prompts are cached per server. All three lists are refreshed
+//! mid-session when a server sends the matching `list_changed` notification.
+//!
+//! Servers configured with `startup = "lazy"` are started on first use, their
+//! tools being remembered from the last time they ran. A server whose
+//! connection ends is started again after a delay that grows while it keeps
+//! exiting.
+//!
+//! Requests the servers send (elicitation, sampling) are collected into one
+//! stream, see [`McpConnectionManager::take_server_requests`]. `roots/list`
+//! is answered here from the roots set with
+//! [`McpConnectionManager::set_roots`].
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
 use codex_mcp_client::McpClient;
 use mcp_types::ClientCapabilities;
+use mcp_types::ClientCapabilitiesRoots;
 use mcp_types::Implementation;
+use mcp_types::JSONRPCRequest;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ListRootsRequest;
+use mcp_types::ListRootsResult;
+use mcp_types::ModelContextProtocolNotification;
+use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ProgressNotificationParams;
+use mcp_types::Prompt;
+use mcp_types::PromptListChangedNotification;
+use mcp_types::ReadResourceResult;
+use mcp_types::RequestId;
+use mcp_types::Resource;
+use mcp_types::ResourceListChangedNotification;
+use mcp_types::Root;
+use mcp_types::RootsListChangedNotification;
 use mcp_types::Tool;
+use mcp_types::ToolListChangedNotification;
+use mcp_types::ToolOutputSchema;
 
+use serde::Deserialize;
+use serde::Serialize;
 use serde_json::json;
 use sha1::Digest;
 use sha1::Sha1;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+use tokio::sync::watch;
 use tokio::task::JoinSet;
 use tracing::info;
 use tracing::warn;
+use wildmatch::WildMatchPattern;
 
 use crate::config_types::McpServerConfig;
+use crate::config_types::McpServerTransportConfig;
+use crate::config_types::McpStartupMode;
+use crate::protocol::McpServerStatus;
+use crate::protocol::McpServerSummary;
+use crate::protocol::SandboxPolicy;
 
 /// Delimiter used to separate the server name from the tool name in a fully
 /// qualified tool name.
@@ -36,13 +90,44 @@ use crate::config_types::McpServerConfig;
 const MCP_TOOL_NAME_DELIMITER: &str = "__";
 const MAX_TOOL_NAME_LENGTH: usize = 64;
 
-/// Timeout for the `tools/list` request.
-const LIST_TOOLS_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time allowed for the `initialize` and `tools/list` requests unless the
+/// server sets `startup_timeout_ms`.
+const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Timeout for each page of a `resources/list` or `prompts/list` request.
+const LIST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Timeout for the `resources/read` request.
+const READ_RESOURCE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Delay before restarting a server whose connection ended; doubled after
+/// each consecutive restart up to [`MAX_RESTART_DELAY`].
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+
+/// Consecutive restarts after which a server is given up on.
+const MAX_RESTART_ATTEMPTS: u32 = 5;
+
+/// A server that ran at least this long before exiting is restarted as if it
+/// had never crashed.
+const HEALTHY_UPTIME: Duration = Duration::from_secs(60);
+
+/// Directory under `CODEX_HOME` holding the tools each lazy server listed the
+/// last time it ran.
+const TOOL_CACHE_DIR: &str = "mcp_tools";
+
+/// Server name -> prompts offered by that server.
+pub(crate) type McpPrompts = HashMap<String, Vec<Prompt>>;
+
+/// A request sent by the named server.
+pub(crate) type McpServerRequest = (String, JSONRPCRequest);
 
 /// Map that holds a startup error for every MCP server that could **not** be
 /// spawned successfully.
 pub type ClientStartErrors = HashMap<String, anyhow::Error>;
 
+type ToolPattern = WildMatchPattern<'*', '?'>;
+
 fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
     let mut used_names = HashSet::new();
     let mut qualified_tools = HashMap::new();
@@ -75,42 +160,203 @@ fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
     qualified_tools
 }
 
+#[derive(Clone)]
 struct ToolInfo {
     server_name: String,
     tool_name: String,
     tool: Tool,
 }
 
+/// A configured server and, while it runs, its client.
+struct McpServer {
+    name: String,
+    config: McpServerConfig,
+    enabled_tools: Option<Vec<ToolPattern>>,
+    disabled_tools: Vec<ToolPattern>,
+    client: RwLock<Option<Arc<McpClient>>>,
+    status: RwLock<McpServerStatus>,
+
+    /// Held while the server is started, so callers racing to use a lazy
+    /// server start it only once.
+    start_lock: tokio::sync::Mutex<()>,
+    started_at: std::sync::Mutex<Option<Instant>>,
+
+    /// Restarts since the server last ran for [`HEALTHY_UPTIME`].
+    restarts: AtomicU32,
+
+    /// Tools the server lists, before `enabled_tools`/`disabled_tools`.
+    listed_tools: AtomicUsize,
+}
+
+impl McpServer {
+    fn new(name: String, config: McpServerConfig) -> Self {
+        let enabled_tools = config
+            .enabled_tools
+            .as_ref()
+            .map(|globs| globs.iter().map(|glob| ToolPattern::new(glob)).collect());
+        let disabled_tools = config
+            .disabled_tools
+            .iter()
+            .map(|glob| ToolPattern::new(glob))
+            .collect();
+        let status = match config.startup {
+            McpStartupMode::Eager => McpServerStatus::Starting,
+            McpStartupMode::Lazy => McpServerStatus::NotStarted,
+        };
+        Self {
+            name,
+            config,
+            enabled_tools,
+            disabled_tools,
+            client: RwLock::new(None),
+            status: RwLock::new(status),
+            start_lock: tokio::sync::Mutex::new(()),
+            started_at: std::sync::Mutex::new(None),
+            restarts: AtomicU32::new(0),
+            listed_tools: AtomicUsize::new(0),
+        }
+    }
+
+    fn client(&self) -> Option<Arc<McpClient>> {
+        self.client.read().ok()?.clone()
+    }
+
+    fn set_client(&self, client: Option<Arc<McpClient>>) {
+        if let Ok(mut current) = self.client.write() {
+            *current = client;
+        }
+    }
+
+    fn status(&self) -> McpServerStatus {
+        match self.status.read() {
+            Ok(status) => status.clone(),
+            Err(_) => McpServerStatus::Failed {
+                error: "status lock is poisoned".to_string(),
+            },
+        }
+    }
+
+    fn set_status(&self, status: McpServerStatus) {
+        if let Ok(mut current) = self.status.write() {
+            *current = status;
+        }
+    }
+
+    fn is_lazy(&self) -> bool {
+        self.config.startup == McpStartupMode::Lazy
+    }
+
+    fn startup_timeout(&self) -> Duration {
+        self.config
+            .startup_timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_STARTUP_TIMEOUT)
+    }
+
+    fn tool_timeout(&self) -> Option<Duration> {
+        self.config.tool_timeout_ms.map(Duration::from_millis)
+    }
+
+    /// Whether `tool` passes the server's `enabled_tools`/`disabled_tools`.
+    fn exposes_tool(&self, tool: &str) -> bool {
+        let enabled = match &self.enabled_tools {
+            Some(patterns) => patterns.iter().any(|pattern| pattern.matches(tool)),
+            None => true,
+        };
+        enabled
+            && !self
+                .disabled_tools
+                .iter()
+                .any(|pattern| pattern.matches(tool))
+    }
+}
+
+/// What the servers offer, shared with the tasks that keep it current.
+struct McpCaches {
+    /// Fully qualified tool name -> tool instance.
+    tools: RwLock<HashMap<String, ToolInfo>>,
+
+    /// Server name -> resources offered by that server. Only servers that
+    /// advertise the `resources` capability have an entry.
+    resources: RwLock<HashMap<String, Vec<Resource>>>,
+
+    /// Prompts offered by servers that advertise the `prompts` capability.
+    prompts: watch::Sender<McpPrompts>,
+
+    /// Where the requests of every server are forwarded.
+    requests: mpsc::UnboundedSender<McpServerRequest>,
+
+    /// Where lazy servers' tools are remembered; `None` to not remember them.
+    tool_cache_dir: Option<PathBuf>,
+
+    /// The directories the session works in, answered to `roots/list`.
+    roots: RwLock<Vec<Root>>,
+}
+
 /// A thin wrapper around a set of running [`McpClient`] instances.
-#[derive(Default)]
 pub(crate) struct McpConnectionManager {
-    /// Server-name -> client instance.
+    /// Server-name -> server.
     ///
     /// The server name originates from the keys of the `mcp_servers` map in
     /// the user configuration.
-    clients: HashMap<String, std::sync::Arc<McpClient>>,
+    servers: HashMap<String, Arc<McpServer>>,
 
-    /// Fully qualified tool name -> tool instance.
-    tools: HashMap<String, ToolInfo>,
+    caches: Arc<McpCaches>,
+
+    /// Requests from every server, until taken by the session.
+    server_requests: std::sync::Mutex<Option<mpsc::UnboundedReceiver<McpServerRequest>>>,
+}
+
+impl Default for McpConnectionManager {
+    fn default() -> Self {
+        Self::empty(None, Vec::new())
+    }
 }
 
 impl McpConnectionManager {
+    fn empty(tool_cache_dir: Option<PathBuf>, roots: Vec<Root>) -> Self {
+        let (requests, server_requests) = mpsc::unbounded_channel();
+        Self {
+            servers: HashMap::new(),
+            caches: Arc::new(McpCaches {
+                tools: RwLock::new(HashMap::new()),
+                resources: RwLock::new(HashMap::new()),
+                prompts: watch::Sender::new(McpPrompts::new()),
+                requests,
+                tool_cache_dir,
+                roots: RwLock::new(roots),
+            }),
+            server_requests: std::sync::Mutex::new(Some(server_requests)),
+        }
+    }
+
     /// Spawn a [`McpClient`] for each configured server.
     ///
     /// * `mcp_servers` – Map loaded from the user configuration where *keys*
     ///   are human-readable server identifiers and *values* are the spawn
     ///   instructions.
+    /// * `codex_home` – Where the tools of lazy servers are remembered
+    ///   between sessions.
+    /// * `roots` – The directories offered to servers that ask for
+    ///   `roots/list`, see [`roots_for`].
     ///
-    /// Servers that fail to start are reported in `ClientStartErrors`: the
-    /// user should be informed about these errors.
+    /// Lazy servers whose tools are remembered are not started here. A lazy
+    /// server without remembered tools, i.e. in the first session after it
+    /// was added or its command or URL changed, is started like an eager one
+    /// to learn them. Servers that fail to start are reported in
+    /// `ClientStartErrors`: the user should be informed about these errors.
     pub async fn new(
         mcp_servers: HashMap<String, McpServerConfig>,
+        codex_home: &Path,
+        roots: Vec<Root>,
     ) -> Result<(Self, ClientStartErrors)> {
         // Early exit if no servers are configured.
         if mcp_servers.is_empty() {
-            return Ok((Self::default(), ClientStartErrors::default()));
+            return Ok((Self::empty(None, roots), ClientStartErrors::default()));
         }
 
+        let mut manager = Self::empty(Some(codex_home.join(TOOL_CACHE_DIR)), roots);
+
         // Launch all configured servers concurrently.
         let mut join_set = JoinSet::new();
         let mut errors = ClientStartErrors::new();
@@ -126,151 +372,749 @@ impl McpConnectionManager {
                 continue;
             }
 
+            let server = Arc::new(McpServer::new(server_name.clone(), cfg));
+            manager.servers.insert(server_name, Arc::clone(&server));
+
+            let cached_tools = if server.is_lazy() {
+                read_cached_tools(&manager.caches, &server).await
+            } else {
+                None
+            };
+            if let Some(tools) = cached_tools {
+                set_server_tools(&manager.caches, &server, tools);
+                continue;
+            }
+
+            let caches = Arc::clone(&manager.caches);
             join_set.spawn(async move {
-                let McpServerConfig { command, args, env } = cfg;
-                let client_res = McpClient::new_stdio_client(
-                    command.into(),
-                    args.into_iter().map(OsString::from).collect(),
-                    env,
-                )
-                .await;
-                match client_res {
-                    Ok(client) => {
-                        // Initialize the client.
-                        let params = mcp_types::InitializeRequestParams {
-                            capabilities: ClientCapabilities {
-                                experimental: None,
-                                roots: None,
-                                sampling: None,
-                                // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
-                                // indicates this should be an empty object.
-                                elicitation: Some(json!({})),
-                            },
-                            client_info: Implementation {
-                                name: "code-mcp-client".to_owned(),
-                                version: env!("CARGO_PKG_VERSION").to_owned(),
-                                title: Some("Codex".into()),
-                            },
-                            protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
-                        };
-                        let initialize_notification_params = None;
-                        let timeout = Some(Duration::from_secs(10));
-                        match client
-                            .initialize(params, initialize_notification_params, timeout)
-                            .await
-                        {
-                            Ok(_response) => (server_name, Ok(client)),
-                            Err(e) => (server_name, Err(e)),
-                        }
-                    }
-                    Err(e) => (server_name, Err(e.into())),
-                }
+                let result = start_server(&caches, &server).await;
+                (server.name.clone(), result)
             });
         }
 
-        let mut clients: HashMap<String, std::sync::Arc<McpClient>> =
-            HashMap::with_capacity(join_set.len());
-
         while let Some(res) = join_set.join_next().await {
-            let (server_name, client_res) = res?; // JoinError propagation
-
-            match client_res {
-                Ok(client) => {
-                    clients.insert(server_name, std::sync::Arc::new(client));
-                }
-                Err(e) => {
-                    errors.insert(server_name, e);
-                }
+            let (server_name, result) = res?; // JoinError propagation
+            if let Err(e) = result {
+                errors.insert(server_name, e);
             }
         }
 
-        let all_tools = list_all_tools(&clients).await?;
+        info!(
+            "aggregated {} tools from {} servers",
+            manager.list_all_tools().len(),
+            manager.servers.len()
+        );
 
-        let tools = qualify_tools(all_tools);
+        Ok((manager, errors))
+    }
 
-        Ok((Self { clients, tools }, errors))
+    /// The client of `server_name`, starting the server if it is lazy and
+    /// has not been needed before.
+    async fn client(&self, server_name: &str) -> Result<Arc<McpClient>> {
+        let server = self
+            .servers
+            .get(server_name)
+            .ok_or_else(|| anyhow!("unknown MCP server '{server_name}'"))?;
+        if let Some(client) = server.client() {
+            return Ok(client);
+        }
+        match server.status() {
+            McpServerStatus::NotStarted => start_server(&self.caches, server).await,
+            McpServerStatus::Starting | McpServerStatus::Running => {
+                // Someone else is starting the server; wait for them.
+                drop(server.start_lock.lock().await);
+                server
+                    .client()
+                    .ok_or_else(|| anyhow!("MCP server '{server_name}' failed to start"))
+            }
+            McpServerStatus::Restarting { .. } => Err(anyhow!(
+                "MCP server '{server_name}' exited and is being restarted"
+            )),
+            McpServerStatus::Failed { error } => Err(anyhow!(
+                "MCP server '{server_name}' is unavailable: {error}"
+            )),
+        }
+    }
+
+    /// The client of `server_name` if it is running; never starts it.
+    fn running_client(&self, server_name: &str) -> Result<Arc<McpClient>> {
+        self.servers
+            .get(server_name)
+            .ok_or_else(|| anyhow!("unknown MCP server '{server_name}'"))?
+            .client()
+            .ok_or_else(|| anyhow!("MCP server '{server_name}' is not running"))
+    }
+
+    /// The `outputSchema` of `server`'s `tool`, if it declares one.
+    pub fn tool_output_schema(&self, server: &str, tool: &str) -> Option<ToolOutputSchema> {
+        let tools = self.caches.tools.read().ok()?;
+        tools
+            .values()
+            .find(|info| info.server_name == server && info.tool_name == tool)
+            .and_then(|info| info.tool.output_schema.clone())
     }
 
     /// Returns a single map that contains **all** tools. Each key is the
     /// fully-qualified name for the tool.
     pub fn list_all_tools(&self) -> HashMap<String, Tool> {
-        self.tools
+        let Ok(tools) = self.caches.tools.read() else {
+            return HashMap::new();
+        };
+        tools
             .iter()
             .map(|(name, tool)| (name.clone(), tool.tool.clone()))
             .collect()
     }
 
-    /// Invoke the tool indicated by the (server, tool) pair.
+    /// Health and tool counts of every configured server, sorted by name.
+    pub fn list_servers(&self) -> Vec<McpServerSummary> {
+        let mut tool_counts = HashMap::<&str, usize>::new();
+        let tools = self.caches.tools.read().ok();
+        for tool in tools.iter().flat_map(|tools| tools.values()) {
+            *tool_counts.entry(tool.server_name.as_str()).or_default() += 1;
+        }
+        let resources = self.caches.resources.read().ok();
+        let prompts = self.caches.prompts.borrow();
+
+        let mut servers: Vec<McpServerSummary> = self
+            .servers
+            .values()
+            .map(|server| McpServerSummary {
+                name: server.name.clone(),
+                lazy: server.is_lazy(),
+                status: server.status(),
+                tools: tool_counts.get(server.name.as_str()).copied().unwrap_or(0),
+                listed_tools: server.listed_tools.load(Ordering::Relaxed),
+                resources: resources
+                    .as_ref()
+                    .and_then(|resources| resources.get(&server.name))
+                    .map_or(0, Vec::len),
+                prompts: prompts.get(&server.name).map_or(0, Vec::len),
+            })
+            .collect();
+        servers.sort_by(|a, b| a.name.cmp(&b.name));
+        servers
+    }
+
+    /// Whether any server offers resources, so the resource tools are worth
+    /// exposing.
+    pub fn has_resources(&self) -> bool {
+        self.caches
+            .resources
+            .read()
+            .map(|resources| !resources.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// Cached resources, by server name, optionally limited to `server`.
+    pub fn list_resources(&self, server: Option<&str>) -> Result<HashMap<String, Vec<Resource>>> {
+        let resources = self
+            .caches
+            .resources
+            .read()
+            .map_err(|_| anyhow!("MCP resource cache is poisoned"))?;
+        match server {
+            Some(server) => {
+                let listed = resources
+                    .get(server)
+                    .ok_or_else(|| anyhow!("MCP server '{server}' offers no resources"))?;
+                Ok(HashMap::from([(server.to_string(), listed.clone())]))
+            }
+            None => Ok(resources.clone()),
+        }
+    }
+
+    /// Read the resource at `uri` from `server`.
+    pub async fn read_resource(&self, server: &str, uri: &str) -> Result<ReadResourceResult> {
+        let client = self.client(server).await?;
+
+        client
+            .read_resource(uri.to_string(), Some(READ_RESOURCE_TIMEOUT))
+            .await
+            .with_context(|| format!("resources/read failed for `{server}` ({uri})"))
+    }
+
+    /// Receives the prompts offered by every server, now and whenever a
+    /// server's list changes.
+    pub fn subscribe_prompts(&self) -> watch::Receiver<McpPrompts> {
+        self.caches.prompts.subscribe()
+    }
+
+    /// Expand `name` from `server` with `arguments`.
+    pub async fn get_prompt(
+        &self,
+        server: &str,
+        name: &str,
+        arguments: HashMap<String, String>,
+    ) -> Result<mcp_types::GetPromptResult> {
+        let client = self.client(server).await?;
+
+        let arguments = (!arguments.is_empty()).then(|| json!(arguments));
+        client
+            .get_prompt(name.to_string(), arguments, Some(LIST_TIMEOUT))
+            .await
+            .with_context(|| format!("prompts/get failed for `{server}/{name}`"))
+    }
+
+    /// Takes the stream of requests sent by the servers; `None` once taken.
+    /// Every request must be answered with
+    /// [`send_response`](Self::send_response) or
+    /// [`send_error_response`](Self::send_error_response).
+    pub fn take_server_requests(&self) -> Option<mpsc::UnboundedReceiver<McpServerRequest>> {
+        self.server_requests.lock().ok()?.take()
+    }
+
+    /// Replace the roots offered to servers. Running servers are sent
+    /// `notifications/roots/list_changed` when the roots differ from before.
+    pub fn set_roots(&self, roots: Vec<Root>) {
+        {
+            let Ok(mut current) = self.caches.roots.write() else {
+                return;
+            };
+            if *current == roots {
+                return;
+            }
+            *current = roots;
+        }
+        for server in self.servers.values() {
+            let Some(client) = server.client() else {
+                continue;
+            };
+            let server_name = server.name.clone();
+            tokio::spawn(async move {
+                if let Err(e) = client
+                    .send_notification::<RootsListChangedNotification>(None)
+                    .await
+                {
+                    warn!("failed to tell MCP server '{server_name}' that roots changed: {e:#}");
+                }
+            });
+        }
+    }
+
+    /// Answer request `id` from `server` with `result`.
+    pub async fn send_response(
+        &self,
+        server: &str,
+        id: RequestId,
+        result: serde_json::Value,
+    ) -> Result<()> {
+        let client = self.running_client(server)?;
+        client.send_response(id, result).await
+    }
+
+    /// Reject request `id` from `server` with a JSON-RPC error.
+    pub async fn send_error_response(
+        &self,
+        server: &str,
+        id: RequestId,
+        code: i64,
+        message: String,
+    ) -> Result<()> {
+        let client = self.running_client(server)?;
+        client.send_error_response(id, code, message).await
+    }
+
+    /// Invoke the tool indicated by the (server, tool) pair. Without a
+    /// `timeout`, the server's `tool_timeout_ms` applies. The server's
+    /// progress reports for the call are forwarded to `progress`.
     pub async fn call_tool(
         &self,
         server: &str,
         tool: &str,
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
+        progress: mpsc::UnboundedSender<ProgressNotificationParams>,
     ) -> Result<mcp_types::CallToolResult> {
-        let client = self
-            .clients
-            .get(server)
-            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?
-            .clone();
+        let timeout = timeout.or_else(|| {
+            self.servers
+                .get(server)
+                .and_then(|server| server.tool_timeout())
+        });
+        let client = self.client(server).await?;
 
         client
-            .call_tool(tool.to_string(), arguments, timeout)
+            .call_tool_with_progress(tool.to_string(), arguments, timeout, progress)
             .await
             .with_context(|| format!("tool call failed for `{server}/{tool}`"))
     }
 
     pub fn parse_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
-        self.tools
+        self.caches
+            .tools
+            .read()
+            .ok()?
             .get(tool_name)
             .map(|tool| (tool.server_name.clone(), tool.tool_name.clone()))
     }
 }
 
-/// Query every server for its available tools and return a single map that
-/// contains **all** tools. Each key is the fully-qualified name for the tool.
-async fn list_all_tools(
-    clients: &HashMap<String, std::sync::Arc<McpClient>>,
-) -> Result<Vec<ToolInfo>> {
-    let mut join_set = JoinSet::new();
-
-    // Spawn one task per server so we can query them concurrently. This
-    // keeps the overall latency roughly at the slowest server instead of
-    // the cumulative latency.
-    for (server_name, client) in clients {
-        let server_name_cloned = server_name.clone();
-        let client_clone = client.clone();
-        join_set.spawn(async move {
-            let res = client_clone
-                .list_tools(None, Some(LIST_TOOLS_TIMEOUT))
-                .await;
-            (server_name_cloned, res)
+/// Start `server`, list what it offers and watch it until it exits.
+/// Concurrent callers share one start.
+async fn start_server(caches: &Arc<McpCaches>, server: &Arc<McpServer>) -> Result<Arc<McpClient>> {
+    let _starting = server.start_lock.lock().await;
+    if let Some(client) = server.client() {
+        return Ok(client);
+    }
+
+    server.set_status(McpServerStatus::Starting);
+    match connect(caches, server).await {
+        Ok(client) => {
+            if let Ok(mut started_at) = server.started_at.lock() {
+                *started_at = Some(Instant::now());
+            }
+            server.set_client(Some(Arc::clone(&client)));
+            server.set_status(McpServerStatus::Running);
+            watch_list_changes(caches, server, &client);
+            restart_on_exit(caches, server, &client);
+            Ok(client)
+        }
+        Err(e) => {
+            server.set_status(McpServerStatus::Failed {
+                error: format!("{e:#}"),
+            });
+            Err(e)
+        }
+    }
+}
+
+/// Spawn or connect to `server`, initialize the session and list the
+/// server's tools, resources and prompts into `caches`.
+async fn connect(caches: &Arc<McpCaches>, server: &McpServer) -> Result<Arc<McpClient>> {
+    let client = match server.config.transport.clone() {
+        McpServerTransportConfig::Stdio { command, args, env } => McpClient::new_stdio_client(
+            command.into(),
+            args.into_iter().map(OsString::from).collect(),
+            env,
+        )
+        .await
+        .map_err(anyhow::Error::from)?,
+        McpServerTransportConfig::StreamableHttp {
+            url,
+            headers,
+            env_http_headers,
+        } => {
+            let headers = http_headers(&server.name, headers, env_http_headers);
+            McpClient::new_streamable_http_client(&url, headers).await?
+        }
+    };
+
+    // Take over server requests before the server can send any.
+    let requests = client.handle_server_requests();
+    // Initialize the client.
+    let params = mcp_types::InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: Some(ClientCapabilitiesRoots {
+                list_changed: Some(true),
+            }),
+            sampling: Some(json!({})),
+            // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
+            // indicates this should be an empty object.
+            elicitation: Some(json!({})),
+        },
+        client_info: Implementation {
+            name: "code-mcp-client".to_owned(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            title: Some("Codex".into()),
+        },
+        protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
+    };
+    let initialize_notification_params = None;
+    let timeout = Some(server.startup_timeout());
+    let response = client
+        .initialize(params, initialize_notification_params, timeout)
+        .await?;
+    let client = Arc::new(client);
+    forward_server_requests(server.name.clone(), requests, caches, &client);
+
+    let listed = client
+        .list_tools(None, timeout)
+        .await
+        .context("tools/list failed")?
+        .tools;
+    remember_tools(caches, server, &listed).await;
+    set_server_tools(caches, server, listed);
+
+    if response.capabilities.resources.is_some() {
+        let listed = list_resources(&server.name, &client).await;
+        if let Ok(mut resources) = caches.resources.write() {
+            resources.insert(server.name.clone(), listed);
+        }
+    }
+    if response.capabilities.prompts.is_some() {
+        let listed = list_prompts(&server.name, &client).await;
+        caches.prompts.send_modify(|prompts| {
+            prompts.insert(server.name.clone(), listed);
         });
     }
 
-    let mut aggregated: Vec<ToolInfo> = Vec::with_capacity(join_set.len());
+    Ok(client)
+}
+
+/// Spawn a task that refreshes the cached tools, resources or prompts of
+/// `server` when it reports that the list changed. The task ends once the
+/// connection is gone.
+fn watch_list_changes(caches: &Arc<McpCaches>, server: &Arc<McpServer>, client: &Arc<McpClient>) {
+    let mut notifications = client.subscribe_notifications();
+    let caches = Arc::clone(caches);
+    let server = Arc::downgrade(server);
+    let client = Arc::downgrade(client);
+    tokio::spawn(async move {
+        loop {
+            let method = match notifications.recv().await {
+                Ok(notification) => notification.method,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let (Some(server), Some(client)) = (server.upgrade(), client.upgrade()) else {
+                break;
+            };
+            match method.as_str() {
+                ToolListChangedNotification::METHOD => {
+                    refresh_tools(&caches, &server, &client).await;
+                }
+                ResourceListChangedNotification::METHOD => {
+                    let listed = list_resources(&server.name, &client).await;
+                    if let Ok(mut resources) = caches.resources.write() {
+                        resources.insert(server.name.clone(), listed);
+                    }
+                }
+                PromptListChangedNotification::METHOD => {
+                    let listed = list_prompts(&server.name, &client).await;
+                    caches.prompts.send_modify(|prompts| {
+                        prompts.insert(server.name.clone(), listed);
+                    });
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+/// Spawn a task that starts `server` again once its connection ends, waiting
+/// longer after each consecutive restart and giving up after
+/// [`MAX_RESTART_ATTEMPTS`]. Dropping the manager ends the task.
+fn restart_on_exit(caches: &Arc<McpCaches>, server: &Arc<McpServer>, client: &McpClient) {
+    let closed = client.closed();
+    let caches = Arc::downgrade(caches);
+    let server = Arc::downgrade(server);
+    tokio::spawn(async move {
+        closed.await;
+        {
+            let Some(server) = server.upgrade() else {
+                return;
+            };
+            warn!("MCP server '{}' exited", server.name);
+            server.set_client(None);
+            let uptime = server
+                .started_at
+                .lock()
+                .ok()
+                .and_then(|started_at| *started_at)
+                .map(|started_at| started_at.elapsed());
+            if uptime.is_some_and(|uptime| uptime >= HEALTHY_UPTIME) {
+                server.restarts.store(0, Ordering::Relaxed);
+            }
+        }
 
-    while let Some(join_res) = join_set.join_next().await {
-        let (server_name, list_result) = join_res?;
-        let list_result = list_result?;
+        let mut last_error = None;
+        loop {
+            let attempt = {
+                let Some(server) = server.upgrade() else {
+                    return;
+                };
+                let attempt = server.restarts.fetch_add(1, Ordering::Relaxed) + 1;
+                if attempt > MAX_RESTART_ATTEMPTS {
+                    let error = match last_error {
+                        Some(e) => format!("gave up restarting: {e}"),
+                        None => format!("exited {MAX_RESTART_ATTEMPTS} times; gave up restarting"),
+                    };
+                    server.set_status(McpServerStatus::Failed { error });
+                    return;
+                }
+                server.set_status(McpServerStatus::Restarting { attempt });
+                attempt
+            };
 
-        for tool in list_result.tools {
-            let tool_info = ToolInfo {
-                server_name: server_name.clone(),
-                tool_name: tool.name.clone(),
-                tool,
+            tokio::time::sleep(restart_delay(attempt)).await;
+
+            let (Some(caches), Some(server)) = (caches.upgrade(), server.upgrade()) else {
+                return;
             };
-            aggregated.push(tool_info);
+            match start_server(&caches, &server).await {
+                Ok(_) => {
+                    info!("restarted MCP server '{}'", server.name);
+                    return;
+                }
+                Err(e) => {
+                    warn!("failed to restart MCP server '{}': {e:#}", server.name);
+                    last_error = Some(format!("{e:#}"));
+                }
+            }
+        }
+    });
+}
+
+/// Delay before the `attempt`th consecutive restart, counting from 1.
+fn restart_delay(attempt: u32) -> Duration {
+    let factor = 1u32 << attempt.saturating_sub(1).min(16);
+    RESTART_DELAY.saturating_mul(factor).min(MAX_RESTART_DELAY)
+}
+
+/// Answer `roots/list` from `caches`; tag the other requests sent by
+/// `server_name` and pass them on to the session.
+fn forward_server_requests(
+    server_name: String,
+    mut requests: mpsc::UnboundedReceiver<JSONRPCRequest>,
+    caches: &Arc<McpCaches>,
+    client: &Arc<McpClient>,
+) {
+    let caches = Arc::clone(caches);
+    let client = Arc::downgrade(client);
+    tokio::spawn(async move {
+        while let Some(request) = requests.recv().await {
+            if request.method != ListRootsRequest::METHOD {
+                if caches
+                    .requests
+                    .send((server_name.clone(), request))
+                    .is_err()
+                {
+                    break;
+                }
+                continue;
+            }
+            let Some(client) = client.upgrade() else {
+                break;
+            };
+            let roots = caches
+                .roots
+                .read()
+                .map(|roots| roots.clone())
+                .unwrap_or_default();
+            if let Err(e) = client
+                .send_response(request.id, ListRootsResult { roots }.into())
+                .await
+            {
+                warn!("failed to answer roots/list from MCP server '{server_name}': {e:#}");
+            }
+        }
+    });
+}
+
+/// The roots offered to MCP servers: `cwd` followed by every other directory
+/// `sandbox_policy` lets commands write to.
+pub(crate) fn roots_for(cwd: &Path, sandbox_policy: &SandboxPolicy) -> Vec<Root> {
+    let mut paths = vec![cwd.to_path_buf()];
+    for writable_root in sandbox_policy.get_writable_roots_with_cwd(cwd) {
+        if !paths.contains(&writable_root.root) {
+            paths.push(writable_root.root);
         }
     }
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let uri = reqwest::Url::from_file_path(&path).ok()?;
+            Some(Root {
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned()),
+                uri: uri.to_string(),
+            })
+        })
+        .collect()
+}
 
-    info!(
-        "aggregated {} tools from {} servers",
-        aggregated.len(),
-        clients.len()
+/// Swap the tools `server` lists into the aggregated map, keeping every other
+/// server's tools and dropping those the server's globs hide.
+fn set_server_tools(caches: &McpCaches, server: &McpServer, listed: Vec<Tool>) {
+    server.listed_tools.store(listed.len(), Ordering::Relaxed);
+    let Ok(mut tools) = caches.tools.write() else {
+        return;
+    };
+    let mut all_tools: Vec<ToolInfo> = tools
+        .values()
+        .filter(|tool| tool.server_name != server.name)
+        .cloned()
+        .collect();
+    all_tools.extend(
+        listed
+            .into_iter()
+            .filter(|tool| server.exposes_tool(&tool.name))
+            .map(|tool| ToolInfo {
+                server_name: server.name.clone(),
+                tool_name: tool.name.clone(),
+                tool,
+            }),
     );
+    *tools = qualify_tools(all_tools);
+}
 
-    Ok(aggregated)
+/// Re-list the tools of `server` after it reported a change.
+async fn refresh_tools(caches: &McpCaches, server: &McpServer, client: &McpClient) {
+    let listed = match client
+        .list_tools(None, Some(server.startup_timeout()))
+        .await
+    {
+        Ok(listed) => listed.tools,
+        Err(e) => {
+            warn!(
+                "failed to refresh tools for MCP server '{}': {e:#}",
+                server.name
+            );
+            return;
+        }
+    };
+    remember_tools(caches, server, &listed).await;
+    set_server_tools(caches, server, listed);
+    info!("refreshed tools for MCP server '{}'", server.name);
+}
+
+fn tool_cache_path(caches: &McpCaches, server_name: &str) -> Option<PathBuf> {
+    caches
+        .tool_cache_dir
+        .as_ref()
+        .map(|dir| dir.join(format!("{server_name}.json")))
+}
+
+/// What a lazy server listed the last time it ran.
+#[derive(Serialize, Deserialize)]
+struct ToolCache {
+    /// [`transport_fingerprint`] of the server the tools were listed by.
+    transport: String,
+    tools: Vec<Tool>,
+}
+
+/// Identifies the command or URL a server is reached with, so tools
+/// remembered for one server are not offered once the entry points
+/// somewhere else.
+fn transport_fingerprint(transport: &McpServerTransportConfig) -> String {
+    fn sorted(map: &Option<HashMap<String, String>>) -> Option<BTreeMap<&String, &String>> {
+        map.as_ref().map(|map| map.iter().collect())
+    }
+    let canonical = match transport {
+        McpServerTransportConfig::Stdio { command, args, env } => json!({
+            "command": command,
+            "args": args,
+            "env": sorted(env),
+        }),
+        McpServerTransportConfig::StreamableHttp {
+            url,
+            headers,
+            env_http_headers,
+        } => json!({
+            "url": url,
+            "headers": sorted(headers),
+            "env_http_headers": sorted(env_http_headers),
+        }),
+    };
+    let mut hasher = Sha1::new();
+    hasher.update(canonical.to_string().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// The tools `server` listed the last time it ran with its current
+/// transport config, if remembered.
+async fn read_cached_tools(caches: &McpCaches, server: &McpServer) -> Option<Vec<Tool>> {
+    let path = tool_cache_path(caches, &server.name)?;
+    let json = tokio::fs::read(&path).await.ok()?;
+    match serde_json::from_slice::<ToolCache>(&json) {
+        Ok(cache) if cache.transport == transport_fingerprint(&server.config.transport) => {
+            Some(cache.tools)
+        }
+        Ok(_) => {
+            info!(
+                "ignoring tool cache of MCP server '{}' written for another config",
+                server.name
+            );
+            None
+        }
+        Err(e) => {
+            warn!("ignoring unreadable tool cache {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Remember the tools a lazy server lists, so the next session can offer
+/// them without starting the server.
+async fn remember_tools(caches: &McpCaches, server: &McpServer, tools: &[Tool]) {
+    if !server.is_lazy() {
+        return;
+    }
+    let Some(path) = tool_cache_path(caches, &server.name) else {
+        return;
+    };
+    let result = async {
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let cache = ToolCache {
+            transport: transport_fingerprint(&server.config.transport),
+            tools: tools.to_vec(),
+        };
+        tokio::fs::write(&path, serde_json::to_vec(&cache)?).await?;
+        anyhow::Ok(())
+    }
+    .await;
+    if let Err(e) = result {
+        warn!(
+            "failed to remember the tools of MCP server '{}': {e:#}",
+            server.name
+        );
+    }
+}
+
+/// All resources offered by `server_name`, following pagination. Failures
+/// are logged and yield whatever was listed so far.
+async fn list_resources(server_name: &str, client: &McpClient) -> Vec<Resource> {
+    let mut resources = Vec::new();
+    let mut cursor = None;
+    loop {
+        let params = cursor.map(|cursor| ListResourcesRequestParams {
+            cursor: Some(cursor),
+        });
+        match client.list_resources(params, Some(LIST_TIMEOUT)).await {
+            Ok(page) => {
+                resources.extend(page.resources);
+                cursor = page.next_cursor;
+            }
+            Err(e) => {
+                warn!("failed to list resources for MCP server '{server_name}': {e:#}");
+                break;
+            }
+        }
+        if cursor.is_none() {
+            break;
+        }
+    }
+    resources
+}
+
+/// All prompts offered by `server_name`, following pagination. Failures are
+/// logged and yield whatever was listed so far.
+async fn list_prompts(server_name: &str, client: &McpClient) -> Vec<Prompt> {
+    let mut prompts = Vec::new();
+    let mut cursor = None;
+    loop {
+        let params = cursor.map(|cursor| ListPromptsRequestParams {
+            cursor: Some(cursor),
+        });
+        match client.list_prompts(params, Some(LIST_TIMEOUT)).await {
+            Ok(page) => {
+                prompts.extend(page.prompts);
+                cursor = page.next_cursor;
+            }
+            Err(e) => {
+                warn!("failed to list prompts for MCP server '{server_name}': {e:#}");
+                break;
+            }
+        }
+        if cursor.is_none() {
+            break;
+        }
+    }
+    prompts
 }
 
 fn is_valid_mcp_server_name(server_name: &str) -> bool {
@@ -280,10 +1124,37 @@ fn is_valid_mcp_server_name(server_name: &str) -> bool {
             .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
 }
 
+/// Merges a server's static `headers` with `env_http_headers`, whose values
+/// name environment variables read now. Unset variables are skipped with a
+/// warning rather than sending an empty header.
+fn http_headers(
+    server_name: &str,
+    headers: Option<HashMap<String, String>>,
+    env_http_headers: Option<HashMap<String, String>>,
+) -> HashMap<String, String> {
+    let mut merged = headers.unwrap_or_default();
+    for (header, var) in env_http_headers.unwrap_or_default() {
+        match std::env::var(&var) {
+            Ok(value) if !value.is_empty() => {
+                merged.insert(header, value);
+            }
+            _ => warn!("MCP server '{server_name}': {var} is not set; not sending {header}"),
+        }
+    }
+    merged
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use mcp_types::ToolInputSchema;
+    use serde_json::Value;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::Request;
+    use wiremock::Respond;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::any;
 
     fn create_test_tool(server_name: &str, tool_name: &str) -> ToolInfo {
         ToolInfo {
@@ -366,4 +1237,194 @@ mod tests {
             "my_server__yet_another_e1c3987bd9c50b826cbe1687966f79f0c602d19ca"
         );
     }
+
+    #[test]
+    fn test_exposes_tool_applies_enabled_and_disabled_globs() {
+        let server = McpServer::new(
+            "search".to_string(),
+            McpServerConfig {
+                transport: McpServerTransportConfig::Stdio {
+                    command: "search-mcp".to_string(),
+                    args: Vec::new(),
+                    env: None,
+                },
+                startup: McpStartupMode::Lazy,
+                startup_timeout_ms: None,
+                tool_timeout_ms: None,
+                enabled_tools: Some(vec!["search_*".to_string(), "fetch".to_string()]),
+                disabled_tools: vec!["search_admin*".to_string()],
+            },
+        );
+
+        assert!(server.exposes_tool("search_web"));
+        assert!(server.exposes_tool("fetch"));
+        assert!(!server.exposes_tool("fetch_all"));
+        assert!(!server.exposes_tool("search_admin_users"));
+        assert_eq!(McpServerStatus::NotStarted, server.status());
+    }
+
+    #[test]
+    fn test_restart_delay_doubles_up_to_the_cap() {
+        assert_eq!(Duration::from_secs(1), restart_delay(1));
+        assert_eq!(Duration::from_secs(2), restart_delay(2));
+        assert_eq!(Duration::from_secs(16), restart_delay(5));
+        assert_eq!(MAX_RESTART_DELAY, restart_delay(6));
+        assert_eq!(MAX_RESTART_DELAY, restart_delay(40));
+    }
+
+    #[test]
+    fn test_http_headers_read_env_and_skip_unset_vars() {
+        let headers = http_headers(
+            "docs",
+            Some(HashMap::from([("X-Team".to_string(), "core".to_string())])),
+            Some(HashMap::from([
+                ("X-Home".to_string(), "HOME".to_string()),
+                (
+                    "Authorization".to_string(),
+                    "CODEX_TEST_UNSET_MCP_TOKEN".to_string(),
+                ),
+            ])),
+        );
+
+        assert_eq!(headers.get("X-Team").map(String::as_str), Some("core"));
+        assert_eq!(headers.get("X-Home"), std::env::var("HOME").ok().as_ref());
+        assert!(!headers.contains_key("Authorization"));
+    }
+
+    #[test]
+    fn test_roots_for_lists_cwd_then_other_writable_roots() {
+        let cwd = Path::new("/work/app");
+        let workspace_write = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![PathBuf::from("/srv/data"), cwd.to_path_buf()],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+        };
+        assert_eq!(
+            roots_for(cwd, &workspace_write),
+            vec![
+                Root {
+                    name: Some("app".to_string()),
+                    uri: "file:///work/app".to_string(),
+                },
+                Root {
+                    name: Some("data".to_string()),
+                    uri: "file:///srv/data".to_string(),
+                },
+            ]
+        );
+
+        let read_only: Vec<String> = roots_for(cwd, &SandboxPolicy::ReadOnly)
+            .into_iter()
+            .map(|root| root.uri)
+            .collect();
+        assert_eq!(read_only, vec!["file:///work/app".to_string()]);
+    }
+
+    /// Speaks Streamable HTTP and offers a single `search` tool.
+    struct FakeMcpServer;
+
+    impl Respond for FakeMcpServer {
+        fn respond(&self, request: &Request) -> ResponseTemplate {
+            // GET requests for a server stream carry no body.
+            let Ok(message) = request.body_json::<Value>() else {
+                return ResponseTemplate::new(405);
+            };
+            let result = match message["method"].as_str() {
+                Some("initialize") => json!({
+                    "protocolVersion": mcp_types::MCP_SCHEMA_VERSION,
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "docs", "version": "1.0.0" },
+                }),
+                Some("tools/list") => json!({
+                    "tools": [{ "name": "search", "inputSchema": { "type": "object" } }],
+                }),
+                Some("tools/call") => json!({
+                    "content": [{ "type": "text", "text": "found 3 pages" }],
+                }),
+                _ => return ResponseTemplate::new(202),
+            };
+            ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": message["id"],
+                "result": result,
+            }))
+        }
+    }
+
+    async fn initializes(server: &MockServer) -> usize {
+        server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter(|request| {
+                request
+                    .body_json::<Value>()
+                    .is_ok_and(|message| message["method"] == "initialize")
+            })
+            .count()
+    }
+
+    fn lazy_server(url: String) -> HashMap<String, McpServerConfig> {
+        let config = McpServerConfig {
+            transport: McpServerTransportConfig::StreamableHttp {
+                url,
+                headers: None,
+                env_http_headers: None,
+            },
+            startup: McpStartupMode::Lazy,
+            startup_timeout_ms: None,
+            tool_timeout_ms: None,
+            enabled_tools: None,
+            disabled_tools: Vec::new(),
+        };
+        HashMap::from([("docs".to_string(), config)])
+    }
+
+    #[tokio::test]
+    async fn test_lazy_server_starts_on_first_use_once_its_tools_are_remembered() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(FakeMcpServer)
+            .mount(&server)
+            .await;
+        let codex_home = tempfile::tempdir().unwrap();
+        let url = format!("{}/mcp", server.uri());
+
+        // Nothing is remembered yet, so the server starts with the session.
+        let (manager, errors) =
+            McpConnectionManager::new(lazy_server(url.clone()), codex_home.path(), Vec::new())
+                .await
+                .unwrap();
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(initializes(&server).await, 1);
+        drop(manager);
+
+        // The next session offers the remembered tools and starts the server
+        // when one of them is called.
+        let (manager, _) =
+            McpConnectionManager::new(lazy_server(url.clone()), codex_home.path(), Vec::new())
+                .await
+                .unwrap();
+        assert!(manager.list_all_tools().contains_key("docs__search"));
+        assert_eq!(initializes(&server).await, 1);
+        let (progress, _progress_rx) = mpsc::unbounded_channel();
+        manager
+            .call_tool("docs", "search", None, None, progress)
+            .await
+            .unwrap();
+        assert_eq!(initializes(&server).await, 2);
+        drop(manager);
+
+        // Tools remembered for another URL are not offered.
+        let (_manager, _) = McpConnectionManager::new(
+            lazy_server(format!("{url}?v=2")),
+            codex_home.path(),
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(initializes(&server).await, 3);
+    }
 }
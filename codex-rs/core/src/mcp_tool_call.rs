@@ -1,19 +1,34 @@
 use std::time::Duration;
 use std::time::Instant;
 
+use base64::Engine;
+use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use mcp_types::EmbeddedResource;
+use mcp_types::EmbeddedResourceResource;
+use mcp_types::ToolOutputSchema;
+use serde_json::Value;
+use serde_json::json;
+use tokio::sync::mpsc;
 use tracing::error;
+use tracing::warn;
 
 use crate::codex::Session;
+use crate::models::ContentItem;
 use crate::models::FunctionCallOutputPayload;
 use crate::models::ResponseInputItem;
+use crate::models::ResponseItem;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::McpInvocation;
 use crate::protocol::McpToolCallBeginEvent;
 use crate::protocol::McpToolCallEndEvent;
+use crate::protocol::McpToolCallProgressEvent;
 
 /// Handles the specified tool call dispatches the appropriate
-/// `McpToolCallBegin` and `McpToolCallEnd` events to the `Session`.
+/// `McpToolCallBegin`, `McpToolCallProgress` and `McpToolCallEnd` events to
+/// the `Session`. Dropping the returned future (e.g. because the turn was
+/// interrupted) cancels the call on the server.
 pub(crate) async fn handle_mcp_tool_call(
     sess: &Session,
     sub_id: &str,
@@ -56,11 +71,43 @@ pub(crate) async fn handle_mcp_tool_call(
     notify_mcp_tool_call_event(sess, sub_id, tool_call_begin_event).await;
 
     let start = Instant::now();
-    // Perform the tool call.
-    let result = sess
-        .call_tool(&server, &tool_name, arguments_value.clone(), timeout)
-        .await
-        .map_err(|e| format!("tool call error: {e}"));
+    // Perform the tool call, relaying progress while it runs.
+    let (progress_tx, mut progress_rx) = mpsc::unbounded_channel();
+    let call = sess.call_tool(
+        &server,
+        &tool_name,
+        arguments_value.clone(),
+        timeout,
+        progress_tx,
+    );
+    tokio::pin!(call);
+    let result = loop {
+        tokio::select! {
+            result = &mut call => break result,
+            Some(progress) = progress_rx.recv() => {
+                let progress_event = EventMsg::McpToolCallProgress(McpToolCallProgressEvent {
+                    call_id: call_id.clone(),
+                    progress: progress.progress,
+                    total: progress.total,
+                    message: progress.message,
+                });
+                notify_mcp_tool_call_event(sess, sub_id, progress_event).await;
+            }
+        }
+    }
+    .map_err(|e| format!("tool call error: {e}"));
+    let result = match (result, sess.mcp_tool_output_schema(&server, &tool_name)) {
+        (Ok(result), Some(schema)) => check_structured_content(&schema, result).map_err(|e| {
+            format!("`{server}/{tool_name}` returned invalid structured content: {e}")
+        }),
+        (result, _) => result,
+    };
+    if let Ok(result) = &result
+        && sess.supports_image_input()
+        && let Some(message) = tool_result_images_message(&server, &tool_name, result)
+    {
+        sess.queue_mcp_tool_images(message);
+    }
     let tool_call_end_event = EventMsg::McpToolCallEnd(McpToolCallEndEvent {
         call_id: call_id.clone(),
         invocation,
@@ -80,3 +127,359 @@ async fn notify_mcp_tool_call_event(sess: &Session, sub_id: &str, event: EventMs
     })
     .await;
 }
+
+/// The text a tool result is given to the model as. The base64 payloads of
+/// images, audio and blobs are left out; images reach models that accept
+/// them as image inputs instead.
+pub(crate) fn call_tool_result_to_model_text(
+    result: &CallToolResult,
+) -> serde_json::Result<String> {
+    let content = result
+        .content
+        .iter()
+        .map(content_block_for_model)
+        .collect::<serde_json::Result<Vec<Value>>>()?;
+    match &result.structured_content {
+        Some(structured) => serde_json::to_string(&json!({
+            "content": content,
+            "structuredContent": structured,
+        })),
+        None => serde_json::to_string(&content),
+    }
+}
+
+fn content_block_for_model(block: &ContentBlock) -> serde_json::Result<Value> {
+    match block {
+        ContentBlock::ImageContent(image) => Ok(json!({
+            "type": image.r#type,
+            "mimeType": image.mime_type,
+        })),
+        ContentBlock::AudioContent(audio) => Ok(json!({
+            "type": audio.r#type,
+            "mimeType": audio.mime_type,
+        })),
+        ContentBlock::EmbeddedResource(EmbeddedResource {
+            resource: EmbeddedResourceResource::BlobResourceContents(blob),
+            r#type,
+            ..
+        }) => Ok(json!({
+            "type": r#type,
+            "resource": { "uri": blob.uri, "mimeType": blob.mime_type },
+        })),
+        other => serde_json::to_value(other),
+    }
+}
+
+/// Marks the message that carries the images a tool returned. These are not
+/// `[EPHEMERAL:...]` browser screenshots: they stay in the conversation.
+const MCP_TOOL_IMAGE_LABEL: &str = "[mcp_tool_image]";
+
+/// A user message carrying the images in `result` as image inputs, or `None`
+/// if there are none. Payloads that are not valid base64 are skipped.
+fn tool_result_images_message(
+    server: &str,
+    tool: &str,
+    result: &CallToolResult,
+) -> Option<ResponseItem> {
+    let images: Vec<ContentItem> = result
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::ImageContent(image) => Some((image.mime_type.as_str(), &image.data)),
+            ContentBlock::EmbeddedResource(EmbeddedResource {
+                resource: EmbeddedResourceResource::BlobResourceContents(blob),
+                ..
+            }) => blob
+                .mime_type
+                .as_deref()
+                .filter(|mime| mime.starts_with("image/"))
+                .map(|mime| (mime, &blob.blob)),
+            _ => None,
+        })
+        .filter_map(|(mime, data)| {
+            if let Err(e) = base64::engine::general_purpose::STANDARD.decode(data) {
+                warn!("skipping undecodable image from `{server}/{tool}`: {e}");
+                return None;
+            }
+            Some(ContentItem::InputImage {
+                image_url: format!("data:{mime};base64,{data}"),
+                detail: None,
+            })
+        })
+        .collect();
+    if images.is_empty() {
+        return None;
+    }
+
+    let mut content = vec![ContentItem::InputText {
+        text: format!("{MCP_TOOL_IMAGE_LABEL} Image returned by MCP tool {server}/{tool}"),
+    }];
+    content.extend(images);
+    Some(ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content,
+    })
+}
+
+/// Check `structuredContent` against the tool's `outputSchema`, as MCP asks
+/// clients to. Results the server marked as errors are not checked.
+///
+/// [`ToolOutputSchema`] only keeps `type`, `properties` and `required` at the
+/// top level, so other top-level keywords (e.g. `additionalProperties`) the
+/// server sent are not enforced. Schemas under `properties` are kept whole.
+fn check_structured_content(
+    schema: &ToolOutputSchema,
+    result: CallToolResult,
+) -> Result<CallToolResult, String> {
+    if result.is_error == Some(true) {
+        return Ok(result);
+    }
+    let Some(structured) = &result.structured_content else {
+        return Err("the tool declares an output schema but returned none".to_string());
+    };
+    let schema = serde_json::to_value(schema).map_err(|e| e.to_string())?;
+    match schema_violation(&schema, structured, "$") {
+        Some(violation) => Err(violation),
+        None => Ok(result),
+    }
+}
+
+/// The first way `value` fails to match `schema`, if any. Only `type`,
+/// `enum`, `const`, `properties`, `required`, `additionalProperties` and
+/// `items` are checked; any other keyword is ignored.
+fn schema_violation(schema: &Value, value: &Value, path: &str) -> Option<String> {
+    let schema = match schema {
+        Value::Bool(false) => return Some(format!("{path}: no value is allowed here")),
+        Value::Object(schema) => schema,
+        _ => return None,
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(ty) => vec![ty.as_str()],
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|ty| has_type(value, ty)) {
+            return Some(format!("{path}: expected {}", types.join(" or ")));
+        }
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum")
+        && !allowed.contains(value)
+    {
+        return Some(format!("{path}: {value} is not one of the allowed values"));
+    }
+    if let Some(constant) = schema.get("const")
+        && constant != value
+    {
+        return Some(format!("{path}: expected {constant}"));
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(key) {
+                        return Some(format!("{path}: missing required property `{key}`"));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, item) in object {
+                let item_path = format!("{path}.{key}");
+                let violation = match properties.and_then(|properties| properties.get(key)) {
+                    Some(item_schema) => schema_violation(item_schema, item, &item_path),
+                    None => schema
+                        .get("additionalProperties")
+                        .and_then(|extra| schema_violation(extra, item, &item_path)),
+                };
+                if violation.is_some() {
+                    return violation;
+                }
+            }
+            None
+        }
+        Value::Array(items) => {
+            let item_schema = schema.get("items")?;
+            items.iter().enumerate().find_map(|(index, item)| {
+                schema_violation(item_schema, item, &format!("{path}[{index}]"))
+            })
+        }
+        _ => None,
+    }
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        // Unknown types are not ours to reject.
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::ImageContent;
+    use mcp_types::TextContent;
+    use pretty_assertions::assert_eq;
+
+    fn output_schema() -> ToolOutputSchema {
+        ToolOutputSchema {
+            properties: Some(json!({
+                "count": { "type": "integer" },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "status": { "enum": ["ok", "partial"] },
+            })),
+            required: Some(vec!["count".to_string()]),
+            r#type: "object".to_string(),
+        }
+    }
+
+    fn structured(value: Value) -> CallToolResult {
+        CallToolResult {
+            content: Vec::new(),
+            is_error: None,
+            structured_content: Some(value),
+        }
+    }
+
+    #[test]
+    fn structured_content_matching_the_schema_is_accepted() {
+        let result = structured(json!({ "count": 3, "tags": ["a"], "status": "ok", "extra": 1 }));
+        assert_eq!(
+            check_structured_content(&output_schema(), result.clone()),
+            Ok(result)
+        );
+    }
+
+    #[test]
+    fn structured_content_violations_are_reported() {
+        let cases = [
+            (
+                json!({ "tags": [] }),
+                "$: missing required property `count`",
+            ),
+            (json!({ "count": 1.5 }), "$.count: expected integer"),
+            (
+                json!({ "count": 1, "tags": ["a", 2] }),
+                "$.tags[1]: expected string",
+            ),
+            (
+                json!({ "count": 1, "status": "done" }),
+                "$.status: \"done\" is not one of the allowed values",
+            ),
+            (json!([1]), "$: expected object"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(
+                check_structured_content(&output_schema(), structured(value)),
+                Err(expected.to_string())
+            );
+        }
+
+        let missing = CallToolResult {
+            content: Vec::new(),
+            is_error: None,
+            structured_content: None,
+        };
+        assert!(check_structured_content(&output_schema(), missing).is_err());
+
+        let failed = CallToolResult {
+            content: Vec::new(),
+            is_error: Some(true),
+            structured_content: None,
+        };
+        assert_eq!(
+            check_structured_content(&output_schema(), failed.clone()),
+            Ok(failed)
+        );
+    }
+
+    #[test]
+    fn model_text_leaves_out_image_data() {
+        let result = CallToolResult {
+            content: vec![
+                ContentBlock::TextContent(TextContent {
+                    annotations: None,
+                    text: "rendered".to_string(),
+                    r#type: "text".to_string(),
+                }),
+                ContentBlock::ImageContent(ImageContent {
+                    annotations: None,
+                    data: "iVBORw0KGgo=".to_string(),
+                    mime_type: "image/png".to_string(),
+                    r#type: "image".to_string(),
+                }),
+            ],
+            is_error: None,
+            structured_content: Some(json!({ "width": 640 })),
+        };
+
+        let text = call_tool_result_to_model_text(&result).expect("serialize result");
+        assert_eq!(
+            serde_json::from_str::<Value>(&text).expect("parse result"),
+            json!({
+                "content": [
+                    { "type": "text", "text": "rendered" },
+                    { "type": "image", "mimeType": "image/png" },
+                ],
+                "structuredContent": { "width": 640 },
+            })
+        );
+    }
+
+    #[test]
+    fn tool_images_become_a_labelled_message() {
+        let image = |data: &str| {
+            ContentBlock::ImageContent(ImageContent {
+                annotations: None,
+                data: data.to_string(),
+                mime_type: "image/png".to_string(),
+                r#type: "image".to_string(),
+            })
+        };
+        let result = CallToolResult {
+            content: vec![image("iVBORw0KGgo="), image("not base64!")],
+            is_error: None,
+            structured_content: None,
+        };
+
+        assert_eq!(
+            tool_result_images_message("figma", "render", &result),
+            Some(ResponseItem::Message {
+                id: None,
+                role: "user".to_string(),
+                content: vec![
+                    ContentItem::InputText {
+                        text: "[mcp_tool_image] Image returned by MCP tool figma/render"
+                            .to_string(),
+                    },
+                    ContentItem::InputImage {
+                        image_url: "data:image/png;base64,iVBORw0KGgo=".to_string(),
+                        detail: None,
+                    },
+                ],
+            })
+        );
+
+        let undecodable = CallToolResult {
+            content: vec![image("not base64!")],
+            is_error: None,
+            structured_content: None,
+        };
+        assert_eq!(
+            tool_result_images_message("figma", "render", &undecodable),
+            None
+        );
+    }
+}
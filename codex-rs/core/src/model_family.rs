@@ -27,6 +27,10 @@ pub struct ModelFamily {
     /// True if the model performs better when `apply_patch` is provided as
     /// a tool call instead of just a bash command.
     pub uses_apply_patch_tool: bool,
+
+    /// True if the model accepts images as input, so images returned by
+    /// tools can be passed on to it.
+    pub supports_image_input: bool,
 }
 
 macro_rules! model_family {
@@ -41,6 +45,7 @@ macro_rules! model_family {
             supports_reasoning_summaries: false,
             uses_local_shell_tool: false,
             uses_apply_patch_tool: false,
+            supports_image_input: true,
         };
         // apply overrides
         $(
@@ -61,6 +66,7 @@ macro_rules! simple_model_family {
             supports_reasoning_summaries: false,
             uses_local_shell_tool: false,
             uses_apply_patch_tool: false,
+            supports_image_input: true,
         })
     }};
 }
@@ -95,11 +101,15 @@ pub fn find_family_for_model(slug: &str) -> Option<ModelFamily> {
             needs_special_apply_patch_instructions: true,
         )
     } else if slug.starts_with("gpt-oss") {
-        model_family!(slug, "gpt-oss", uses_apply_patch_tool: true)
+        model_family!(
+            slug, "gpt-oss",
+            uses_apply_patch_tool: true,
+            supports_image_input: false,
+        )
     } else if slug.starts_with("gpt-4o") {
         simple_model_family!(slug, "gpt-4o")
     } else if slug.starts_with("gpt-3.5") {
-        simple_model_family!(slug, "gpt-3.5")
+        model_family!(slug, "gpt-3.5", supports_image_input: false)
     } else if slug.starts_with("gpt-5") {
         model_family!(
             slug, "gpt-5",
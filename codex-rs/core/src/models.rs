@@ -7,6 +7,7 @@ use serde::Deserializer;
 use serde::Serialize;
 use serde::ser::Serializer;
 
+use crate::mcp_tool_call::call_tool_result_to_model_text;
 use crate::protocol::InputItem;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -115,7 +116,7 @@ impl From<ResponseInputItem> for ResponseItem {
                     content: result.map_or_else(
                         |tool_call_err| format!("err: {tool_call_err:?}"),
                         |result| {
-                            serde_json::to_string(&result)
+                            call_tool_result_to_model_text(&result)
                                 .unwrap_or_else(|e| format!("JSON serialization error: {e}"))
                         },
                     ),
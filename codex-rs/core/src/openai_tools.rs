@@ -11,10 +11,12 @@ use crate::agent_tool::create_get_agent_result_tool;
 use crate::agent_tool::create_list_agents_tool;
 use crate::agent_tool::create_run_agent_tool;
 use crate::agent_tool::create_wait_for_agent_tool;
+use crate::background_process::create_background_process_tool;
 use crate::model_family::ModelFamily;
 use crate::plan_tool::PLAN_TOOL;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
+use crate::shell_session::create_shell_session_tool;
 
 #[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct ResponsesApiTool {
@@ -463,6 +465,15 @@ pub(crate) fn get_openai_tools(
             tools.push(OpenAiTool::LocalShell {});
         }
     }
+    // Shell sessions run on a pty, which is only implemented for unix.
+    if cfg!(unix) {
+        tools.push(create_shell_session_tool());
+    }
+    // Background processes rely on their own process group to be stopped
+    // together with their children.
+    if cfg!(unix) {
+        tools.push(create_background_process_tool());
+    }
 
     if config.plan_tool {
         tools.push(PLAN_TOOL.clone());
@@ -481,6 +492,17 @@ pub(crate) fn get_openai_tools(
         tools.push(create_browser_scroll_tool());
         tools.push(create_browser_history_tool());
         tools.push(create_browser_inspect_tool());
+        tools.push(create_browser_snapshot_tool());
+        tools.push(create_browser_screenshot_tool());
+        tools.push(create_browser_network_tool());
+        tools.push(create_browser_tabs_tool());
+        tools.push(create_browser_script_tool());
+        tools.push(create_browser_perf_tool());
+        tools.push(create_browser_state_tool());
+        tools.push(create_browser_responsive_tool());
+        tools.push(create_browser_dialog_tool());
+        tools.push(create_browser_upload_tool());
+        tools.push(create_browser_downloads_tool());
         tools.push(create_browser_console_tool());
         tools.push(create_browser_cleanup_tool());
         tools.push(create_browser_cdp_tool());
@@ -529,6 +551,11 @@ mod tests {
                 OpenAiTool::LocalShell {} => "local_shell",
             })
             .collect::<Vec<_>>();
+        let expected_names = expected_names
+            .iter()
+            .copied()
+            .filter(|name| cfg!(unix) || !matches!(*name, "shell_session" | "background_process"))
+            .collect::<Vec<_>>();
 
         assert_eq!(
             tool_names.len(),
@@ -555,7 +582,7 @@ mod tests {
         );
         let tools = get_openai_tools(&config, Some(HashMap::new()), false);
 
-        assert_eq_tool_names(&tools, &["local_shell", "update_plan", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list"]);
+        assert_eq_tool_names(&tools, &["local_shell", "shell_session", "background_process", "update_plan", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list"]);
     }
 
     #[test]
@@ -569,7 +596,7 @@ mod tests {
         );
         let tools = get_openai_tools(&config, Some(HashMap::new()), false);
 
-        assert_eq_tool_names(&tools, &["shell", "update_plan", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list"]);
+        assert_eq_tool_names(&tools, &["shell", "shell_session", "background_process", "update_plan", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list"]);
     }
 
     #[test]
@@ -620,7 +647,7 @@ mod tests {
             false,
         );
 
-        assert_eq_tool_names(&tools, &["shell", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list", "test_server/do_something_cool"]);
+        assert_eq_tool_names(&tools, &["shell", "shell_session", "background_process", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list", "test_server/do_something_cool"]);
 
         let last = tools.last().expect("should include mcp tool");
         assert_eq!(
@@ -701,7 +728,7 @@ mod tests {
             false,
         );
 
-        assert_eq_tool_names(&tools, &["shell", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list", "dash/search"]);
+        assert_eq_tool_names(&tools, &["shell", "shell_session", "background_process", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list", "dash/search"]);
 
         let last = tools.last().expect("should include mcp tool");
         assert_eq!(
@@ -756,7 +783,7 @@ mod tests {
             false,
         );
 
-        assert_eq_tool_names(&tools, &["shell", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list", "dash/paginate"]);
+        assert_eq_tool_names(&tools, &["shell", "shell_session", "background_process", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list", "dash/paginate"]);
         let last = tools.last().expect("should include mcp tool");
         assert_eq!(
             *last,
@@ -808,7 +835,7 @@ mod tests {
             false,
         );
 
-        assert_eq_tool_names(&tools, &["shell", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list", "dash/tags"]);
+        assert_eq_tool_names(&tools, &["shell", "shell_session", "background_process", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list", "dash/tags"]);
         let last = tools.last().expect("should include mcp tool");
         assert_eq!(
             *last,
@@ -863,7 +890,7 @@ mod tests {
             false,
         );
 
-        assert_eq_tool_names(&tools, &["shell", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list", "dash/value"]);
+        assert_eq_tool_names(&tools, &["shell", "shell_session", "background_process", "browser_open", "browser_status", "agent_run", "agent_check", "agent_result", "agent_cancel", "agent_wait", "agent_list", "dash/value"]);
         let last = tools.last().expect("should include mcp tool");
         assert_eq!(
             *last,
@@ -892,10 +919,45 @@ fn create_browser_open_tool() -> OpenAiTool {
             description: Some("The URL to navigate to (e.g., https://example.com)".to_string()),
         },
     );
+    properties.insert(
+        "locale".to_string(),
+        JsonSchema::String {
+            description: Some("Optional BCP 47 locale pages should use from now on, e.g. 'de-DE'. Also sets Accept-Language.".to_string()),
+        },
+    );
+    properties.insert(
+        "timezone".to_string(),
+        JsonSchema::String {
+            description: Some("Optional IANA timezone pages should use from now on, e.g. 'America/New_York'.".to_string()),
+        },
+    );
+    properties.insert(
+        "color_scheme".to_string(),
+        JsonSchema::String {
+            description: Some("Optional prefers-color-scheme to emulate: 'light', 'dark', or 'auto' to stop emulating.".to_string()),
+        },
+    );
+    let mut geolocation = BTreeMap::new();
+    geolocation.insert("latitude".to_string(), JsonSchema::Number { description: None });
+    geolocation.insert("longitude".to_string(), JsonSchema::Number { description: None });
+    geolocation.insert(
+        "accuracy".to_string(),
+        JsonSchema::Number {
+            description: Some("Meters (default 50).".to_string()),
+        },
+    );
+    properties.insert(
+        "geolocation".to_string(),
+        JsonSchema::Object {
+            properties: geolocation,
+            required: Some(vec![]),
+            additional_properties: Some(false),
+        },
+    );
 
     OpenAiTool::Function(ResponsesApiTool {
         name: "browser_open".to_string(),
-        description: "Opens a browser window and navigates to the specified URL. Screenshots will be automatically attached to subsequent messages.".to_string(),
+        description: "Opens a browser window and navigates to the specified URL. Screenshots will be automatically attached to subsequent messages. Optionally changes the locale, timezone, geolocation (an empty object stops emulating it) or color scheme pages see; changes persist until changed again.".to_string(),
         strict: false,
         parameters: JsonSchema::Object {
             properties,
@@ -956,9 +1018,22 @@ fn create_browser_click_tool() -> OpenAiTool {
         },
     );
 
+    properties.insert(
+        "ref".to_string(),
+        JsonSchema::String {
+            description: Some("Optional element ref from browser_snapshot (e.g. 'e42'). The element is scrolled into view and clicked at the center of its box.".to_string()),
+        },
+    );
+    properties.insert(
+        "selector".to_string(),
+        JsonSchema::String {
+            description: Some("Optional CSS selector; the first matching element is used like a ref. Ignored when ref is provided.".to_string()),
+        },
+    );
+
     OpenAiTool::Function(ResponsesApiTool {
         name: "browser_click".to_string(),
-        description: "Performs a mouse action. By default acts at the current cursor; if x,y are provided, moves there (briefly waits for animation) then clicks.".to_string(),
+        description: "Performs a mouse action. By default acts at the current cursor; if ref or selector is provided, scrolls that element into view and acts on its center; if x,y are provided, moves there (briefly waits for animation) then clicks. Prefer refs from browser_snapshot over coordinates.".to_string(),
         strict: false,
         parameters: JsonSchema::Object {
             properties,
@@ -1026,9 +1101,22 @@ fn create_browser_type_tool() -> OpenAiTool {
         },
     );
 
+    properties.insert(
+        "ref".to_string(),
+        JsonSchema::String {
+            description: Some("Optional element ref from browser_snapshot (e.g. 'e42'). The element is scrolled into view and clicked at its center to focus it before typing.".to_string()),
+        },
+    );
+    properties.insert(
+        "selector".to_string(),
+        JsonSchema::String {
+            description: Some("Optional CSS selector; the first matching element is used like a ref. Ignored when ref is provided.".to_string()),
+        },
+    );
+
     OpenAiTool::Function(ResponsesApiTool {
         name: "browser_type".to_string(),
-        description: "Types text into the currently focused element in the browser.".to_string(),
+        description: "Types text into the currently focused element in the browser, or into the element given by ref or selector after clicking it.".to_string(),
         strict: false,
         parameters: JsonSchema::Object {
             properties,
@@ -1151,9 +1239,526 @@ fn create_browser_inspect_tool() -> OpenAiTool {
         },
     );
 
+    properties.insert(
+        "ref".to_string(),
+        JsonSchema::String {
+            description: Some("Optional element ref from browser_snapshot (e.g. 'e42'). The element is scrolled into view and inspected.".to_string()),
+        },
+    );
+    properties.insert(
+        "selector".to_string(),
+        JsonSchema::String {
+            description: Some("Optional CSS selector; the first matching element is used like a ref. Ignored when ref is provided.".to_string()),
+        },
+    );
+
     OpenAiTool::Function(ResponsesApiTool {
         name: "browser_inspect".to_string(),
-        description: "Inspects a DOM element by coordinates or id, returns attributes, outerHTML, box model, and matched styles.".to_string(),
+        description: "Inspects a DOM element by ref, selector, coordinates or id, returns attributes, outerHTML, box model, and matched styles.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec![]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+fn create_browser_snapshot_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "max_nodes".to_string(),
+        JsonSchema::Number {
+            description: Some("Optional maximum number of nodes to return (default: 600)".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "browser_snapshot".to_string(),
+        description: "Returns a compact accessibility tree of the current page (roles, names, values and states). Each element carries a ref like [ref=e42] that browser_click, browser_type and browser_inspect accept; refs stay valid until the page navigates.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec![]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+fn create_browser_screenshot_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "action".to_string(),
+        JsonSchema::String {
+            description: Some("One of: 'compare' (default) the current screenshot with its baseline, 'baseline' to save the current screenshot as the baseline, 'list' saved baselines.".to_string()),
+        },
+    );
+    properties.insert(
+        "name".to_string(),
+        JsonSchema::String {
+            description: Some("Baseline name, e.g. 'before-css-refactor' (default: 'default'). Baselines are kept per name, URL and viewport.".to_string()),
+        },
+    );
+    properties.insert(
+        "min_similarity".to_string(),
+        JsonSchema::Number {
+            description: Some("For compare: fraction of matching pixels required to pass, 0.0-1.0 (default: 0.999).".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "browser_screenshot".to_string(),
+        description: "Visual regression checks: saves the current page's screenshot as a named baseline under .code/visual-baselines/ and compares later screenshots against it, reporting a similarity score, the changed regions and a diff image with the changes highlighted.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec![]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+fn create_browser_network_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "action".to_string(),
+        JsonSchema::String {
+            description: Some("One of: 'list' (default) recorded requests, 'show' one request with headers and body, 'har' export to a file, 'clear' the log, 'mock' or 'block' requests matching pattern, 'unroute' to remove a rule (all rules without pattern), 'routes' to list rules.".to_string()),
+        },
+    );
+    properties.insert(
+        "url".to_string(),
+        JsonSchema::String {
+            description: Some("Filter for list/har: URL substring, or a glob when it contains '*'.".to_string()),
+        },
+    );
+    properties.insert(
+        "method".to_string(),
+        JsonSchema::String {
+            description: Some("Filter for list/har: HTTP method.".to_string()),
+        },
+    );
+    properties.insert(
+        "resource_type".to_string(),
+        JsonSchema::String {
+            description: Some("Filter for list/har: resource type such as 'XHR', 'Fetch', 'Document', 'Script'.".to_string()),
+        },
+    );
+    properties.insert(
+        "failed_only".to_string(),
+        JsonSchema::Boolean {
+            description: Some("Filter for list/har: only failed requests and 4xx/5xx responses.".to_string()),
+        },
+    );
+    properties.insert(
+        "limit".to_string(),
+        JsonSchema::Number {
+            description: Some("Filter for list/har: keep only the most recent N matches.".to_string()),
+        },
+    );
+    properties.insert(
+        "id".to_string(),
+        JsonSchema::String {
+            description: Some("For show: the request id printed by list as [id=...].".to_string()),
+        },
+    );
+    properties.insert(
+        "path".to_string(),
+        JsonSchema::String {
+            description: Some("For har: output file, relative to the working directory (default: a temp file).".to_string()),
+        },
+    );
+    properties.insert(
+        "bodies".to_string(),
+        JsonSchema::Boolean {
+            description: Some("For har: embed response bodies that are still available (default: true).".to_string()),
+        },
+    );
+    properties.insert(
+        "pattern".to_string(),
+        JsonSchema::String {
+            description: Some("For mock/block/unroute: URL substring, or a glob when it contains '*'.".to_string()),
+        },
+    );
+    properties.insert(
+        "status".to_string(),
+        JsonSchema::Number {
+            description: Some("For mock: HTTP status to respond with (default: 200).".to_string()),
+        },
+    );
+    properties.insert(
+        "body".to_string(),
+        JsonSchema::String {
+            description: Some("For mock: response body.".to_string()),
+        },
+    );
+    properties.insert(
+        "content_type".to_string(),
+        JsonSchema::String {
+            description: Some("For mock: Content-Type header (default: guessed from the body).".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "browser_network".to_string(),
+        description: "Inspects network traffic recorded for the current page (status, timing, headers, bodies), exports it as HAR, and mocks or blocks requests by URL pattern.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec![]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+fn create_browser_tabs_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "action".to_string(),
+        JsonSchema::String {
+            description: Some("One of: 'list' (default) open tabs, 'switch' to a tab, 'open' a new tab, 'close' a tab (the active one without id).".to_string()),
+        },
+    );
+    properties.insert(
+        "id".to_string(),
+        JsonSchema::String {
+            description: Some("For switch/close: the tab id printed by list as [id=...].".to_string()),
+        },
+    );
+    properties.insert(
+        "url".to_string(),
+        JsonSchema::String {
+            description: Some("For open: URL to load in the new tab (default: about:blank).".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "browser_tabs".to_string(),
+        description: "Lists, switches, opens and closes browser tabs. Popups and target=_blank links open new tabs that become active automatically; other browser_* tools act on the active tab.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec![]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+fn create_browser_script_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "action".to_string(),
+        JsonSchema::String {
+            description: Some("One of: 'start' recording browser actions, 'stop' and save the recording, 'assert' text or a selector (checked now and added to the recording), 'wait' for a selector, text or ms (added to the recording), 'replay' a saved script in a fresh headless browser, 'list' saved scripts, 'show' a script.".to_string()),
+        },
+    );
+    properties.insert(
+        "name".to_string(),
+        JsonSchema::String {
+            description: Some("For start/replay/show: script name; scripts are saved as .code/browser-scripts/<name>.json.".to_string()),
+        },
+    );
+    properties.insert(
+        "text".to_string(),
+        JsonSchema::String {
+            description: Some("For assert/wait: text that must be visible on the page.".to_string()),
+        },
+    );
+    properties.insert(
+        "selector".to_string(),
+        JsonSchema::String {
+            description: Some("For assert/wait: CSS selector that must match an element.".to_string()),
+        },
+    );
+    properties.insert(
+        "absent".to_string(),
+        JsonSchema::Boolean {
+            description: Some("For assert: require the text or selector to be absent instead.".to_string()),
+        },
+    );
+    properties.insert(
+        "ms".to_string(),
+        JsonSchema::Number {
+            description: Some("For wait: fixed delay in milliseconds.".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "browser_script".to_string(),
+        description: "Records browser_open/click/type/key/scroll calls as a replayable script with assertions, and replays saved scripts headlessly so a verified UI flow becomes a regression check.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["action".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+fn create_browser_dialog_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "action".to_string(),
+        JsonSchema::String {
+            description: Some("One of: 'accept' or 'dismiss' the open dialog, 'list' recent dialogs (default), or 'policy' to show or set how dialogs are answered as they open.".to_string()),
+        },
+    );
+    properties.insert(
+        "text".to_string(),
+        JsonSchema::String {
+            description: Some("For accept: text to enter into a prompt() dialog.".to_string()),
+        },
+    );
+    properties.insert(
+        "policy".to_string(),
+        JsonSchema::String {
+            description: Some("For policy: 'accept' (default), 'dismiss', or 'manual' to leave dialogs open until accepted or dismissed here.".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "browser_dialog".to_string(),
+        description: "Handles JavaScript alert/confirm/prompt/beforeunload dialogs. An open dialog blocks the page until it is accepted or dismissed.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec![]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+fn create_browser_upload_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "paths".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some("Files to upload, inside the working directory (relative paths are resolved against it).".to_string()),
+        },
+    );
+    properties.insert(
+        "ref".to_string(),
+        JsonSchema::String {
+            description: Some("Optional ref of the <input type=file> from browser_snapshot.".to_string()),
+        },
+    );
+    properties.insert(
+        "selector".to_string(),
+        JsonSchema::String {
+            description: Some("Optional CSS selector of the file input (default: 'input[type=file]'). Hidden inputs work.".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "browser_upload".to_string(),
+        description: "Sets the files of a file input without opening the file chooser. Only files inside the working directory can be uploaded.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["paths".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+fn create_browser_downloads_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "action".to_string(),
+        JsonSchema::String {
+            description: Some("'list' downloads and where they were saved (default), or 'read' a completed text download.".to_string()),
+        },
+    );
+    properties.insert(
+        "name".to_string(),
+        JsonSchema::String {
+            description: Some("For read: file name of the download.".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "browser_downloads".to_string(),
+        description: "Lists files the browser downloaded this session, saved to a session download directory, and reads text downloads.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec![]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+/// Tools for browsing the resources offered by MCP servers. Only exposed
+/// when at least one server offers resources.
+pub(crate) fn create_mcp_resource_tools() -> Vec<OpenAiTool> {
+    let mut list_properties = BTreeMap::new();
+    list_properties.insert(
+        "server".to_string(),
+        JsonSchema::String {
+            description: Some("Only list resources of this MCP server (default: all servers).".to_string()),
+        },
+    );
+    let list = OpenAiTool::Function(ResponsesApiTool {
+        name: "mcp_list_resources".to_string(),
+        description: "Lists the resources (files, documents, records) MCP servers make available, with their server, uri, name and mime type.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties: list_properties,
+            required: Some(vec![]),
+            additional_properties: Some(false),
+        },
+    });
+
+    let mut read_properties = BTreeMap::new();
+    read_properties.insert(
+        "server".to_string(),
+        JsonSchema::String {
+            description: Some("MCP server that offers the resource.".to_string()),
+        },
+    );
+    read_properties.insert(
+        "uri".to_string(),
+        JsonSchema::String {
+            description: Some("URI of the resource, as listed by mcp_list_resources.".to_string()),
+        },
+    );
+    let read = OpenAiTool::Function(ResponsesApiTool {
+        name: "mcp_read_resource".to_string(),
+        description: "Reads the contents of an MCP server resource.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties: read_properties,
+            required: Some(vec!["server".to_string(), "uri".to_string()]),
+            additional_properties: Some(false),
+        },
+    });
+
+    vec![list, read]
+}
+
+fn create_browser_responsive_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "devices".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some("Device presets to capture: 'mobile', 'tablet', 'desktop', 'iphone-se', 'pixel-7', 'ipad-pro', 'laptop', 'desktop-hd', configured presets, or 'WIDTHxHEIGHT' (default: mobile, tablet, desktop).".to_string()),
+        },
+    );
+    properties.insert(
+        "layout".to_string(),
+        JsonSchema::String {
+            description: Some("'grid' (default) for one labeled image with all devices side by side, or 'images' for one image per device.".to_string()),
+        },
+    );
+    properties.insert(
+        "reload".to_string(),
+        JsonSchema::Boolean {
+            description: Some("Reload the page for each device so user-agent and touch detection at load time apply (default: false).".to_string()),
+        },
+    );
+    properties.insert(
+        "settle_ms".to_string(),
+        JsonSchema::Number {
+            description: Some("Time to wait after switching devices before capturing (default: 400).".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "browser_responsive".to_string(),
+        description: "Captures the current page across device presets (viewport, pixel ratio, touch and user agent) to spot layout bugs at breakpoints in one call. The browser's own settings are restored afterwards.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec![]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+fn create_browser_state_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "action".to_string(),
+        JsonSchema::String {
+            description: Some("One of: 'save' the browser's login state, 'load' a saved state into the browser, 'list' saved states, 'delete' a saved state.".to_string()),
+        },
+    );
+    properties.insert(
+        "name".to_string(),
+        JsonSchema::String {
+            description: Some("State name, e.g. 'admin' (default: 'default').".to_string()),
+        },
+    );
+    properties.insert(
+        "origins".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some("For save: extra origins (e.g. 'http://localhost:3000') to capture besides the current page's origin.".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "browser_state".to_string(),
+        description: "Saves and restores browser login state (cookies, localStorage and sessionStorage) as named states shared across sessions, so a flow behind a login only needs to sign in once. Load a state before navigating to the site. Stored values are never shown.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["action".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+fn create_browser_perf_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "action".to_string(),
+        JsonSchema::String {
+            description: Some("One of: 'measure' (default) the current page's metrics and Web Vitals, 'navigate' to load url (or reload) while tracing, 'start_trace' before interacting with other browser tools, 'stop_trace' to finish that trace.".to_string()),
+        },
+    );
+    properties.insert(
+        "url".to_string(),
+        JsonSchema::String {
+            description: Some("For navigate: URL to load (default: reload the current page).".to_string()),
+        },
+    );
+    properties.insert(
+        "network".to_string(),
+        JsonSchema::String {
+            description: Some("Network throttling applied before measuring: 'slow-3g', 'fast-3g', '4g', 'offline' or 'none'. Stays in effect until changed.".to_string()),
+        },
+    );
+    properties.insert(
+        "cpu_slowdown".to_string(),
+        JsonSchema::Number {
+            description: Some("CPU throttling factor, e.g. 4 for a mid-range phone; 1 disables it. Stays in effect until changed.".to_string()),
+        },
+    );
+    properties.insert(
+        "trace".to_string(),
+        JsonSchema::Boolean {
+            description: Some("For navigate: record a trace of the load (default: true).".to_string()),
+        },
+    );
+    properties.insert(
+        "settle_ms".to_string(),
+        JsonSchema::Number {
+            description: Some("For navigate: time to wait after load before measuring, so LCP and layout shifts settle (default: 1500).".to_string()),
+        },
+    );
+    properties.insert(
+        "path".to_string(),
+        JsonSchema::String {
+            description: Some("Trace output file, relative to the working directory (default: a temp file).".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "browser_perf".to_string(),
+        description: "Measures page performance: Core Web Vitals (LCP, CLS, INP, FCP, TTFB), CDP performance metrics and a summarized trace of main-thread work saved as a Chrome trace file, with optional network and CPU throttling.".to_string(),
         strict: false,
         parameters: JsonSchema::Object {
             properties,
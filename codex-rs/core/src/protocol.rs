@@ -11,6 +11,10 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use mcp_types::CallToolResult;
+use mcp_types::ElicitRequestParamsRequestedSchema;
+use mcp_types::Prompt;
+use mcp_types::RequestId;
+use mcp_types::SamplingMessage;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_bytes::ByteBuf;
@@ -129,6 +133,55 @@ pub enum Op {
     /// The agent will use its existing context (either conversation history or previous response id)
     /// to generate a summary which will be returned as an AgentMessage event.
     Compact,
+
+    /// Write the file changes recorded by an exec snapshot (see
+    /// `exec_snapshots` in the config) to the real tree, together with every
+    /// older pending snapshot. `None` selects the most recent one.
+    CommitExecSnapshot { snapshot_id: Option<String> },
+
+    /// Discard the file changes recorded by an exec snapshot, together with
+    /// every newer pending snapshot. `None` selects the most recent one.
+    RollbackExecSnapshot { snapshot_id: Option<String> },
+
+    /// Expand a prompt offered by an MCP server (see `EventMsg::McpPrompts`)
+    /// and submit the resulting messages as user input.
+    McpPrompt {
+        /// Name of the MCP server as defined in the config.
+        server: String,
+        /// Name of the prompt as given by the MCP server.
+        name: String,
+        /// Values for the prompt's arguments, by argument name.
+        #[serde(default)]
+        arguments: HashMap<String, String>,
+    },
+
+    /// Answer an MCP server's request for input from the user (see
+    /// `EventMsg::McpElicitationRequest`).
+    McpElicitationResponse {
+        /// Name of the MCP server that sent the request.
+        server: String,
+        /// Id of the request being answered.
+        request_id: RequestId,
+        action: ElicitationAction,
+        /// Values for the requested fields; only sent with `Accept`.
+        #[serde(default)]
+        content: Option<serde_json::Value>,
+    },
+
+    /// Approve or deny an MCP server's request to sample from the model (see
+    /// `EventMsg::McpSamplingRequest`).
+    McpSamplingApproval {
+        /// Name of the MCP server that sent the request.
+        server: String,
+        /// Id of the request being answered.
+        request_id: RequestId,
+        decision: ReviewDecision,
+    },
+
+    /// Report the configured MCP servers and their health; answered with
+    /// `EventMsg::McpListServersResponse`.
+    ListMcpServers,
+
     /// Request to shut down codex instance.
     Shutdown,
 }
@@ -251,6 +304,21 @@ impl SandboxPolicy {
         }
     }
 
+    /// Returns a copy of this policy that can also write under `roots`. Only
+    /// `WorkspaceWrite` has a notion of writable roots; other policies are
+    /// returned unchanged.
+    pub fn with_additional_writable_roots(&self, roots: &[PathBuf]) -> Self {
+        let mut policy = self.clone();
+        if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = &mut policy {
+            for root in roots {
+                if !writable_roots.contains(root) {
+                    writable_roots.push(root.clone());
+                }
+            }
+        }
+        policy
+    }
+
     /// Always returns `true`; restricting read access is not supported.
     pub fn has_full_disk_read_access(&self) -> bool {
         true
@@ -421,8 +489,26 @@ pub enum EventMsg {
 
     McpToolCallBegin(McpToolCallBeginEvent),
 
+    /// Progress reported by the MCP server for a running tool call.
+    McpToolCallProgress(McpToolCallProgressEvent),
+
     McpToolCallEnd(McpToolCallEndEvent),
 
+    /// The prompts offered by the configured MCP servers. Sent after
+    /// `SessionConfigured` and again whenever a server's list changes.
+    McpPrompts(McpPromptsEvent),
+
+    /// An MCP server asks the user to fill in a form; answer with
+    /// `Op::McpElicitationResponse`.
+    McpElicitationRequest(McpElicitationRequestEvent),
+
+    /// An MCP server asks to sample from the model; answer with
+    /// `Op::McpSamplingApproval`.
+    McpSamplingRequest(McpSamplingRequestEvent),
+
+    /// Reply to `Op::ListMcpServers`.
+    McpListServersResponse(McpListServersResponseEvent),
+
     /// Custom tool call events for non-MCP tools (browser, agent, etc)
     CustomToolCallBegin(CustomToolCallBeginEvent),
     CustomToolCallEnd(CustomToolCallEndEvent),
@@ -461,6 +547,15 @@ pub enum EventMsg {
     /// Agent status has been updated
     AgentStatusUpdate(AgentStatusUpdateEvent),
 
+    /// A background process was started, exited or stopped
+    BackgroundProcessUpdate(BackgroundProcessUpdateEvent),
+
+    /// A shell command changed files while exec snapshots were enabled, or
+    /// such changes were committed or rolled back. Pending changes can be
+    /// kept or undone with `Op::CommitExecSnapshot` /
+    /// `Op::RollbackExecSnapshot`.
+    ExecSnapshot(ExecSnapshotEvent),
+
     /// Notification that the agent is shutting down.
     ShutdownComplete,
 }
@@ -624,6 +719,18 @@ pub struct McpToolCallBeginEvent {
     pub invocation: McpInvocation,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpToolCallProgressEvent {
+    /// Identifier of the McpToolCallBegin this progress belongs to.
+    pub call_id: String,
+    /// Progress so far; increases with every report.
+    pub progress: f64,
+    /// Total amount of work, if the server knows it.
+    pub total: Option<f64>,
+    /// Human-readable description of the current step.
+    pub message: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct McpToolCallEndEvent {
     /// Identifier for the corresponding McpToolCallBegin that finished.
@@ -643,6 +750,93 @@ impl McpToolCallEndEvent {
     }
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpPromptsEvent {
+    /// Server name -> prompts offered by that server.
+    pub prompts: HashMap<String, Vec<Prompt>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpElicitationRequestEvent {
+    /// Name of the MCP server that sent the request.
+    pub server: String,
+    pub request_id: RequestId,
+    /// What the server is asking for.
+    pub message: String,
+    /// Flat JSON schema of the fields to fill in.
+    pub requested_schema: ElicitRequestParamsRequestedSchema,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpSamplingRequestEvent {
+    /// Name of the MCP server that sent the request.
+    pub server: String,
+    pub request_id: RequestId,
+    /// Conversation the server wants the model to continue.
+    pub messages: Vec<SamplingMessage>,
+    pub system_prompt: Option<String>,
+    /// Output token limit for the completion, after applying the configured
+    /// cap.
+    pub max_tokens: u32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpListServersResponseEvent {
+    /// Every configured server, sorted by name.
+    pub servers: Vec<McpServerSummary>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct McpServerSummary {
+    pub name: String,
+    /// Whether the server is only started on first use.
+    pub lazy: bool,
+    pub status: McpServerStatus,
+    /// Tools exposed to the model.
+    pub tools: usize,
+    /// Tools the server lists, including those hidden by
+    /// `enabled_tools`/`disabled_tools`.
+    pub listed_tools: usize,
+    pub resources: usize,
+    pub prompts: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum McpServerStatus {
+    /// A lazy server that has not been needed yet.
+    NotStarted,
+    Starting,
+    Running,
+    /// The server exited and is started again after a delay.
+    Restarting { attempt: u32 },
+    /// The server failed to start, or crashed too often.
+    Failed { error: String },
+}
+
+/// How the user answered an MCP elicitation request.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ElicitationAction {
+    /// Submit the filled-in form.
+    Accept,
+    /// Explicitly refuse to provide the information.
+    Decline,
+    /// Dismiss the form without choosing.
+    Cancel,
+}
+
+impl ElicitationAction {
+    /// Value of the `action` field in the MCP `ElicitResult`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ElicitationAction::Accept => "accept",
+            ElicitationAction::Decline => "decline",
+            ElicitationAction::Cancel => "cancel",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CustomToolCallBeginEvent {
     /// Identifier so this can be paired with the CustomToolCallEnd event.
@@ -721,6 +915,12 @@ pub struct ExecApprovalRequestEvent {
     /// Optional human-readable reason for the approval (e.g. retry without sandbox).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
+    /// When set, the command was blocked by the sandbox and approving it
+    /// retries under the sandbox with this root made writable instead of
+    /// running it unsandboxed. Approving for the session keeps the root
+    /// writable for the remainder of the session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grant_root: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -798,6 +998,20 @@ pub struct BrowserScreenshotUpdateEvent {
     pub screenshot_path: PathBuf,
     /// Current URL of the browser
     pub url: String,
+    /// Active tab, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab: Option<BrowserTabSummary>,
+}
+
+/// Position of the active browser tab among the open tabs.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BrowserTabSummary {
+    /// CDP target id of the tab
+    pub id: String,
+    /// 1-based position of the tab in opening order
+    pub index: usize,
+    /// Number of open tabs
+    pub count: usize,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -822,6 +1036,57 @@ pub struct AgentInfo {
     pub model: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BackgroundProcessUpdateEvent {
+    /// All background processes tracked by the session
+    pub processes: Vec<BackgroundProcessInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BackgroundProcessInfo {
+    /// Identifier used by the `background_process` tool
+    pub id: String,
+    /// Command line as given by the model
+    pub command: String,
+    /// OS process id (also the process group id)
+    pub pid: Option<u32>,
+    /// One of "running", "exited" or "stopped"
+    pub status: String,
+    /// Exit code once the process has exited on its own
+    pub exit_code: Option<i32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ExecSnapshotEvent {
+    /// Identifier to pass to `Op::CommitExecSnapshot` / `Op::RollbackExecSnapshot`
+    pub snapshot_id: String,
+    /// Identifier of the exec call that made the changes
+    pub call_id: String,
+    /// The command that was run
+    pub command: Vec<String>,
+    /// Whether the changes are still pending or were committed or rolled back
+    pub status: ExecSnapshotStatus,
+    /// Paths (relative to the session's working directory) created by the command
+    pub added: Vec<PathBuf>,
+    /// Paths (relative to the session's working directory) modified by the command
+    pub modified: Vec<PathBuf>,
+    /// Paths (relative to the session's working directory) deleted by the command
+    pub deleted: Vec<PathBuf>,
+    /// Unified diff of the changes (may be truncated)
+    pub unified_diff: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecSnapshotStatus {
+    /// The changes only exist in the snapshot so far.
+    Pending,
+    /// The changes were written to the real tree.
+    Committed,
+    /// The changes were discarded.
+    RolledBack,
+}
+
 /// User's decision in response to an ExecApprovalRequest.
 #[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -834,6 +1099,11 @@ pub enum ReviewDecision {
     /// remainder of the session.
     ApprovedForSession,
 
+    /// User has approved retrying a command the sandbox blocked with the
+    /// sandbox disabled, instead of granting the writable root suggested for
+    /// it.
+    ApprovedWithoutSandbox,
+
     /// User has denied this command and the agent should not execute it, but
     /// it should continue the session and try something else.
     #[default]
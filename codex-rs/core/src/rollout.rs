@@ -2,8 +2,13 @@
 
 use std::fs::File;
 use std::fs::{self};
+use std::io::BufRead;
+use std::io::BufReader;
 use std::io::Error as IoError;
+use std::io::ErrorKind;
+use std::io::Write;
 use std::path::Path;
+use std::path::PathBuf;
 
 use serde::Deserialize;
 use serde::Serialize;
@@ -20,11 +25,14 @@ use tracing::warn;
 use uuid::Uuid;
 
 use crate::config::Config;
+use crate::environment_context::ENVIRONMENT_CONTEXT_START;
 use crate::git_info::GitInfo;
 use crate::git_info::collect_git_info;
+use crate::models::ContentItem;
 use crate::models::ResponseItem;
 
 const SESSIONS_SUBDIR: &str = "sessions";
+const ARCHIVED_SESSIONS_SUBDIR: &str = "archived_sessions";
 
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct SessionMeta {
@@ -33,11 +41,11 @@ pub struct SessionMeta {
     pub instructions: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 struct SessionMetaWithGit {
     #[serde(flatten)]
     meta: SessionMeta,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     git: Option<GitInfo>,
 }
 
@@ -87,14 +95,9 @@ impl RolloutRecorder {
             file,
             session_id,
             timestamp,
-        } = create_log_file(config, uuid)?;
-
-        let timestamp_format: &[FormatItem] = format_description!(
-            "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
-        );
-        let timestamp = timestamp
-            .format(timestamp_format)
-            .map_err(|e| IoError::other(format!("failed to format timestamp: {e}")))?;
+            ..
+        } = create_log_file(&config.codex_home, uuid)?;
+        let timestamp = format_meta_timestamp(timestamp)?;
 
         // Clone the cwd for the spawned task to collect git info asynchronously
         let cwd = config.cwd.clone();
@@ -246,6 +249,9 @@ struct LogFileInfo {
     /// Opened file handle to the rollout file.
     file: File,
 
+    /// Path of the rollout file.
+    path: PathBuf,
+
     /// Session ID (also embedded in filename).
     session_id: Uuid,
 
@@ -253,11 +259,11 @@ struct LogFileInfo {
     timestamp: OffsetDateTime,
 }
 
-fn create_log_file(config: &Config, session_id: Uuid) -> std::io::Result<LogFileInfo> {
+fn create_log_file(codex_home: &Path, session_id: Uuid) -> std::io::Result<LogFileInfo> {
     // Resolve ~/.codex/sessions/YYYY/MM/DD and create it if missing.
     let timestamp = OffsetDateTime::now_local()
         .map_err(|e| IoError::other(format!("failed to get local time: {e}")))?;
-    let mut dir = config.codex_home.clone();
+    let mut dir = codex_home.to_path_buf();
     dir.push(SESSIONS_SUBDIR);
     dir.push(timestamp.year().to_string());
     dir.push(format!("{:02}", u8::from(timestamp.month())));
@@ -282,11 +288,20 @@ fn create_log_file(config: &Config, session_id: Uuid) -> std::io::Result<LogFile
 
     Ok(LogFileInfo {
         file,
+        path,
         session_id,
         timestamp,
     })
 }
 
+fn format_meta_timestamp(timestamp: OffsetDateTime) -> std::io::Result<String> {
+    let timestamp_format: &[FormatItem] =
+        format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z");
+    timestamp
+        .format(timestamp_format)
+        .map_err(|e| IoError::other(format!("failed to format timestamp: {e}")))
+}
+
 async fn rollout_writer(
     file: tokio::fs::File,
     mut rx: mpsc::Receiver<RolloutCmd>,
@@ -360,3 +375,376 @@ impl JsonlWriter {
         Ok(())
     }
 }
+
+/// Summary of a recorded conversation, read from the head of its rollout.
+#[derive(Debug, Clone)]
+pub struct ConversationSummary {
+    pub path: PathBuf,
+    pub id: Uuid,
+    pub timestamp: String,
+    pub git: Option<GitInfo>,
+    /// Text of the first user turn, if the conversation got that far.
+    pub preview: Option<String>,
+}
+
+/// One page of [`list_conversations`].
+#[derive(Debug, Clone)]
+pub struct ConversationsPage {
+    pub items: Vec<ConversationSummary>,
+    /// Pass back to [`list_conversations`] for the next page; `None` once
+    /// every rollout has been returned.
+    pub next_cursor: Option<String>,
+}
+
+/// All rollouts under `codex_home/sessions/YYYY/MM/DD`, newest first.
+pub fn recorded_rollouts(codex_home: &Path) -> Vec<(Uuid, PathBuf)> {
+    let mut rollouts = Vec::new();
+    let sessions_dir = codex_home.join(SESSIONS_SUBDIR);
+    for year in sorted_entries(&sessions_dir) {
+        for month in sorted_entries(&year) {
+            for day in sorted_entries(&month) {
+                for file in sorted_entries(&day) {
+                    if let Some(session_id) = rollout_file_session_id(&file) {
+                        rollouts.push((session_id, file));
+                    }
+                }
+            }
+        }
+    }
+    rollouts
+}
+
+/// Path of the rollout recorded for `session_id`, if any.
+pub fn find_rollout(codex_home: &Path, session_id: &Uuid) -> Option<PathBuf> {
+    recorded_rollouts(codex_home)
+        .into_iter()
+        .find(|(id, _)| id == session_id)
+        .map(|(_, path)| path)
+}
+
+/// List recorded conversations newest first, `page_size` at a time. The
+/// cursor is the rollout path relative to the sessions directory, so pages
+/// stay stable while new sessions are being recorded.
+pub fn list_conversations(
+    codex_home: &Path,
+    page_size: usize,
+    cursor: Option<&str>,
+) -> std::io::Result<ConversationsPage> {
+    let sessions_dir = codex_home.join(SESSIONS_SUBDIR);
+    let mut items = Vec::new();
+    let mut next_cursor = None;
+    let mut last_key = None;
+    for (_, path) in recorded_rollouts(codex_home) {
+        let key = rollout_cursor(&sessions_dir, &path);
+        if cursor.is_some_and(|cursor| key.as_str() >= cursor) {
+            continue;
+        }
+        if items.len() == page_size {
+            next_cursor = last_key;
+            break;
+        }
+        match read_conversation_summary(&path) {
+            Ok(summary) => {
+                items.push(summary);
+                last_key = Some(key);
+            }
+            Err(e) => warn!("skipping unreadable rollout {path:?}: {e}"),
+        }
+    }
+    Ok(ConversationsPage { items, next_cursor })
+}
+
+/// Read the session id from the metadata line of a rollout.
+pub fn read_rollout_session_id(path: &Path) -> std::io::Result<Uuid> {
+    let (meta, _) = read_rollout_head(path)?;
+    Ok(meta.meta.id)
+}
+
+/// Copy the first `turn_index` turns of a rollout into a new rollout with a
+/// fresh session id, returning that id and the new path. Turns start at each
+/// message the user typed; status updates injected by Codex do not count.
+pub fn fork_rollout(
+    codex_home: &Path,
+    path: &Path,
+    turn_index: usize,
+) -> std::io::Result<(Uuid, PathBuf)> {
+    let text = fs::read_to_string(path)?;
+    let mut lines = text.lines();
+    let meta_line = lines
+        .next()
+        .ok_or_else(|| IoError::other("empty session file"))?;
+    let mut meta: SessionMetaWithGit = serde_json::from_str(meta_line)
+        .map_err(|e| IoError::other(format!("failed to parse session meta: {e}")))?;
+
+    let mut kept = Vec::new();
+    let mut turns = 0;
+    for line in lines {
+        if parse_user_turn(line).is_some() {
+            if turns == turn_index {
+                break;
+            }
+            turns += 1;
+        }
+        kept.push(line);
+    }
+    if turns < turn_index {
+        return Err(IoError::new(
+            ErrorKind::InvalidInput,
+            format!("turn index {turn_index} is past the end of the conversation ({turns} turns)"),
+        ));
+    }
+
+    let LogFileInfo {
+        mut file,
+        path: new_path,
+        session_id,
+        timestamp,
+    } = create_log_file(codex_home, Uuid::new_v4())?;
+    meta.meta.id = session_id;
+    meta.meta.timestamp = format_meta_timestamp(timestamp)?;
+
+    let mut out = serde_json::to_string(&meta)?;
+    out.push('\n');
+    for line in kept {
+        out.push_str(line);
+        out.push('\n');
+    }
+    file.write_all(out.as_bytes())?;
+    Ok((session_id, new_path))
+}
+
+/// Move a rollout out of the sessions directory into
+/// `codex_home/archived_sessions`, returning its new path.
+pub fn archive_rollout(codex_home: &Path, path: &Path) -> std::io::Result<PathBuf> {
+    let sessions_dir = codex_home.join(SESSIONS_SUBDIR).canonicalize()?;
+    let path = path.canonicalize()?;
+    let file_name = match path.file_name() {
+        Some(name)
+            if path.starts_with(&sessions_dir) && rollout_file_session_id(&path).is_some() =>
+        {
+            name.to_owned()
+        }
+        _ => {
+            return Err(IoError::new(
+                ErrorKind::InvalidInput,
+                format!("{} is not a recorded session", path.display()),
+            ));
+        }
+    };
+    let archive_dir = codex_home.join(ARCHIVED_SESSIONS_SUBDIR);
+    fs::create_dir_all(&archive_dir)?;
+    let archived = archive_dir.join(file_name);
+    fs::rename(&path, &archived)?;
+    Ok(archived)
+}
+
+fn read_conversation_summary(path: &Path) -> std::io::Result<ConversationSummary> {
+    let (SessionMetaWithGit { meta, git }, lines) = read_rollout_head(path)?;
+    let mut preview = None;
+    for line in lines {
+        if let Some(text) = parse_user_turn(&line?) {
+            preview = Some(text);
+            break;
+        }
+    }
+    Ok(ConversationSummary {
+        path: path.to_path_buf(),
+        id: meta.id,
+        timestamp: meta.timestamp,
+        git,
+        preview,
+    })
+}
+
+fn read_rollout_head(
+    path: &Path,
+) -> std::io::Result<(SessionMetaWithGit, std::io::Lines<BufReader<File>>)> {
+    let mut lines = BufReader::new(File::open(path)?).lines();
+    let meta_line = lines
+        .next()
+        .ok_or_else(|| IoError::other("empty session file"))??;
+    let meta = serde_json::from_str(&meta_line)
+        .map_err(|e| IoError::other(format!("failed to parse session meta: {e}")))?;
+    Ok((meta, lines))
+}
+
+/// The text of a rollout line that starts a user turn.
+fn parse_user_turn(line: &str) -> Option<String> {
+    let Ok(ResponseItem::Message { role, content, .. }) = serde_json::from_str(line) else {
+        return None;
+    };
+    if role != "user" {
+        return None;
+    }
+    let text = content
+        .iter()
+        .filter_map(|item| match item {
+            ContentItem::InputText { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let injected = text.starts_with(ENVIRONMENT_CONTEXT_START)
+        || text.starts_with("<user_instructions>")
+        || text.contains("== System Status ==");
+    (!text.is_empty() && !injected).then_some(text)
+}
+
+fn rollout_cursor(sessions_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(sessions_dir)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Directory entries sorted by name, descending. Rollout paths embed their
+/// start time, so this yields newest first at every level.
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort_by(|a, b| b.cmp(a));
+    paths
+}
+
+/// Extract the session id from `rollout-<timestamp>-<uuid>.jsonl`.
+fn rollout_file_session_id(path: &Path) -> Option<Uuid> {
+    let name = path.file_name()?.to_str()?;
+    let stem = name.strip_prefix("rollout-")?.strip_suffix(".jsonl")?;
+    let uuid_start = stem.len().checked_sub(36)?;
+    Uuid::parse_str(stem.get(uuid_start..)?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn user_message(text: &str) -> String {
+        json!({
+            "type": "message",
+            "role": "user",
+            "content": [{ "type": "input_text", "text": text }],
+        })
+        .to_string()
+    }
+
+    fn write_rollout(codex_home: &Path, day: &str, stamp: &str, id: &Uuid, items: &[String]) {
+        let dir = codex_home.join("sessions/2025/01").join(day);
+        fs::create_dir_all(&dir).expect("create sessions dir");
+        let mut text = json!({
+            "id": id,
+            "timestamp": format!("{stamp}Z"),
+            "instructions": null,
+            "git": { "branch": "main" },
+        })
+        .to_string();
+        for item in items {
+            text.push('\n');
+            text.push_str(item);
+        }
+        text.push('\n');
+        fs::write(dir.join(format!("rollout-{stamp}-{id}.jsonl")), text).expect("write rollout");
+    }
+
+    #[test]
+    fn lists_rollouts_newest_first_in_pages() {
+        let codex_home = tempfile::TempDir::new().expect("tempdir");
+        let older = Uuid::new_v4();
+        let newer = Uuid::new_v4();
+        write_rollout(
+            codex_home.path(),
+            "01",
+            "2025-01-01T10-00-00",
+            &older,
+            &[user_message("fix the build")],
+        );
+        write_rollout(codex_home.path(), "02", "2025-01-02T09-00-00", &newer, &[]);
+        fs::write(codex_home.path().join("sessions/2025/01/02/notes.txt"), "")
+            .expect("write unrelated file");
+
+        let ids: Vec<Uuid> = recorded_rollouts(codex_home.path())
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ids, vec![newer, older]);
+
+        let first = list_conversations(codex_home.path(), 1, None).expect("first page");
+        assert_eq!(
+            first.items.iter().map(|c| c.id).collect::<Vec<_>>(),
+            vec![newer]
+        );
+        assert_eq!(first.items[0].preview, None);
+        assert_eq!(
+            first.items[0]
+                .git
+                .as_ref()
+                .and_then(|git| git.branch.clone()),
+            Some("main".to_string())
+        );
+
+        let cursor = first.next_cursor.expect("next cursor");
+        let second = list_conversations(codex_home.path(), 1, Some(&cursor)).expect("second page");
+        assert_eq!(
+            second.items.iter().map(|c| c.id).collect::<Vec<_>>(),
+            vec![older]
+        );
+        assert_eq!(second.items[0].preview.as_deref(), Some("fix the build"));
+        assert_eq!(second.next_cursor, None);
+    }
+
+    #[test]
+    fn fork_keeps_turns_before_the_index() {
+        let codex_home = tempfile::TempDir::new().expect("tempdir");
+        let id = Uuid::new_v4();
+        let status = user_message("== System Status ==\ncwd: /tmp");
+        let items = [
+            user_message("first"),
+            status.clone(),
+            user_message("second"),
+            user_message("third"),
+        ];
+        write_rollout(codex_home.path(), "01", "2025-01-01T10-00-00", &id, &items);
+        let path = find_rollout(codex_home.path(), &id).expect("rollout");
+
+        let (fork_id, fork_path) = fork_rollout(codex_home.path(), &path, 2).expect("fork");
+        assert_ne!(fork_id, id);
+        assert_eq!(
+            read_rollout_session_id(&fork_path).expect("fork id"),
+            fork_id
+        );
+        let lines: Vec<String> = fs::read_to_string(&fork_path)
+            .expect("read fork")
+            .lines()
+            .skip(1)
+            .map(str::to_string)
+            .collect();
+        assert_eq!(
+            lines,
+            vec![user_message("first"), status, user_message("second")]
+        );
+
+        let err = fork_rollout(codex_home.path(), &path, 4).expect_err("past the end");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn archive_moves_rollout_out_of_sessions() {
+        let codex_home = tempfile::TempDir::new().expect("tempdir");
+        let id = Uuid::new_v4();
+        write_rollout(codex_home.path(), "01", "2025-01-01T10-00-00", &id, &[]);
+        let path = find_rollout(codex_home.path(), &id).expect("rollout");
+
+        let archived = archive_rollout(codex_home.path(), &path).expect("archive");
+        assert!(!path.exists());
+        assert!(archived.starts_with(codex_home.path().join(ARCHIVED_SESSIONS_SUBDIR)));
+        assert_eq!(find_rollout(codex_home.path(), &id), None);
+
+        let err = archive_rollout(codex_home.path(), &archived).expect_err("not a session");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -169,7 +169,9 @@ pub fn get_platform_sandbox() -> Option<SandboxType> {
     }
 }
 
-fn is_write_patch_constrained_to_writable_paths(
+/// Whether every path the patch adds, deletes, updates or moves to lies inside
+/// one of the policy's writable roots.
+pub fn is_write_patch_constrained_to_writable_paths(
     action: &ApplyPatchAction,
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
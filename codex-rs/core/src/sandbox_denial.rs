@@ -0,0 +1,436 @@
+//! Diagnosis of failures caused by the sandbox.
+//!
+//! On Linux, `codex-linux-sandbox --denial-log` watches the command's
+//! network and file syscalls itself and records the ones the policy blocks;
+//! [`read_denial_log`] parses that record. Seatbelt does not tell us what it
+//! blocked, so on macOS we fall back to scanning stderr for the messages
+//! `EACCES`/`EPERM` produce and keep only the ones the active policy
+//! explains. Those denials are marked as inferred and are never used to
+//! suggest a writable root.
+
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::SandboxPolicy;
+
+/// Upper bound on the number of denials reported for a single command.
+const MAX_DENIALS: usize = 16;
+
+/// What the sandbox prevented the command from doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxOperation {
+    /// Creating, modifying or removing a file outside the writable roots.
+    FileWrite,
+    /// Opening a network socket or connecting to a remote host.
+    Network,
+}
+
+/// A single operation blocked by the sandbox.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxDenial {
+    pub operation: SandboxOperation,
+    /// Absolute path the command tried to write, for file denials.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Syscall that was blocked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub syscall: Option<String>,
+    /// Whether the denial was guessed from the command's error output rather
+    /// than reported by the sandbox.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inferred: bool,
+}
+
+impl std::fmt::Display for SandboxDenial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.operation {
+            SandboxOperation::FileWrite => write!(f, "write")?,
+            SandboxOperation::Network => write!(f, "network access")?,
+        }
+        if let Some(path) = &self.path {
+            write!(f, " to {}", path.display())?;
+        }
+        if let Some(syscall) = &self.syscall {
+            write!(f, " ({syscall})")?;
+        }
+        if self.inferred {
+            write!(f, " [inferred from error output]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the denials `codex-linux-sandbox --denial-log` recorded, one JSON
+/// object per line.
+pub fn read_denial_log(log: impl Read) -> Vec<SandboxDenial> {
+    let mut denials: Vec<SandboxDenial> = Vec::new();
+    for line in BufReader::new(log).lines().map_while(std::io::Result::ok) {
+        let Ok(denial) = serde_json::from_str::<SandboxDenial>(&line) else {
+            continue;
+        };
+        if !denials.contains(&denial) {
+            denials.push(denial);
+        }
+        if denials.len() >= MAX_DENIALS {
+            break;
+        }
+    }
+    denials
+}
+
+/// Scans `stderr` of a command that ran under `sandbox_policy` in `cwd` for
+/// operations the sandbox is known to block. Used where the sandbox cannot
+/// report denials itself.
+pub fn detect_sandbox_denials(
+    stderr: &str,
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+) -> Vec<SandboxDenial> {
+    if sandbox_policy.has_full_disk_write_access() && sandbox_policy.has_full_network_access() {
+        return Vec::new();
+    }
+    let writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
+
+    let mut denials: Vec<SandboxDenial> = Vec::new();
+    for line in stderr.lines() {
+        let lower = line.to_ascii_lowercase();
+        if !DENIAL_MARKERS.iter().any(|m| lower.contains(m)) {
+            continue;
+        }
+
+        let network_syscall = (!sandbox_policy.has_full_network_access())
+            .then(|| network_syscall(&lower))
+            .flatten();
+        let denial = if let Some(syscall) = network_syscall {
+            Some(SandboxDenial {
+                operation: SandboxOperation::Network,
+                path: None,
+                syscall: Some(syscall.to_string()),
+                inferred: true,
+            })
+        } else if !sandbox_policy.has_full_disk_write_access() {
+            extract_path(line)
+                .map(|p| normalize(&cwd.join(p)))
+                .filter(|p| !writable_roots.iter().any(|root| root.is_path_writable(p)))
+                .map(|path| SandboxDenial {
+                    operation: SandboxOperation::FileWrite,
+                    path: Some(path),
+                    syscall: Some(write_syscall(&lower).to_string()),
+                    inferred: true,
+                })
+        } else {
+            None
+        };
+
+        if let Some(denial) = denial {
+            if !denials.contains(&denial) {
+                denials.push(denial);
+            }
+            if denials.len() >= MAX_DENIALS {
+                break;
+            }
+        }
+    }
+    denials
+}
+
+/// Suggests the narrowest single directory that, once writable, would have
+/// allowed every file write in `denials`. Returns `None` when there are no
+/// file denials, when any network access was denied (a writable root would
+/// not help), when any denial was only inferred from error output, or when
+/// the only common ancestor is the filesystem root.
+pub fn suggest_writable_root(denials: &[SandboxDenial]) -> Option<PathBuf> {
+    if denials.is_empty()
+        || denials
+            .iter()
+            .any(|d| d.operation == SandboxOperation::Network || d.inferred)
+    {
+        return None;
+    }
+
+    let mut common: Option<PathBuf> = None;
+    for path in denials.iter().filter_map(|d| d.path.as_deref()) {
+        // Grant the directory that contains the target; a path that does not
+        // exist yet (e.g. `mkdir -p a/b/c`) is granted from its nearest
+        // existing ancestor.
+        let mut dir = if path.is_dir() {
+            path.to_path_buf()
+        } else {
+            path.parent()?.to_path_buf()
+        };
+        while !dir.exists() {
+            dir = dir.parent()?.to_path_buf();
+        }
+        common = Some(match common {
+            None => dir,
+            Some(prev) => common_ancestor(&prev, &dir)?,
+        });
+    }
+
+    common.filter(|dir| dir.parent().is_some())
+}
+
+/// Lowercase substrings produced by `strerror(EACCES)`, `strerror(EPERM)` and
+/// `strerror(EROFS)` or by runtimes that print the errno name.
+const DENIAL_MARKERS: &[&str] = &[
+    "permission denied",
+    "operation not permitted",
+    "read-only file system",
+    "eacces",
+    "eperm",
+    "erofs",
+];
+
+/// Network syscalls the sandbox blocks, as they appear in messages like
+/// `ping: socket: Operation not permitted` or `connect(): Permission denied`.
+const NETWORK_SYSCALLS: &[&str] = &[
+    "socket", "connect", "bind", "listen", "accept", "sendto", "sendmsg",
+];
+
+/// Returns the network syscall `lower` reports as failing. Only the
+/// syscall-shaped `name:` and `name(` forms count, so messages that merely
+/// mention a network concept (`failed to connect to host`) are ignored.
+fn network_syscall(lower: &str) -> Option<&'static str> {
+    NETWORK_SYSCALLS.iter().copied().find(|name| {
+        lower.match_indices(name).any(|(start, _)| {
+            let before = lower[..start].chars().next_back();
+            let after = lower[start + name.len()..].chars().next();
+            !before.is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+                && matches!(after, Some(':' | '('))
+        })
+    })
+}
+
+fn write_syscall(lower: &str) -> &'static str {
+    if lower.contains("mkdir") || lower.contains("create directory") {
+        "mkdir"
+    } else if lower.contains("rmdir") || lower.contains("remove director") {
+        "rmdir"
+    } else if lower.contains("unlink") || lower.contains("remove") {
+        "unlink"
+    } else if lower.contains("rename") || lower.contains("move") {
+        "rename"
+    } else if lower.contains("symlink") || lower.contains("symbolic link") {
+        "symlink"
+    } else {
+        "openat"
+    }
+}
+
+/// Extracts the path a denial message refers to. Handles the common shapes:
+///
+/// - `touch: cannot touch '/etc/x': Permission denied` (quoted path)
+/// - `bash: /etc/x: Permission denied` (colon-separated field)
+/// - `PermissionError: [Errno 13] Permission denied: '/etc/x'`
+/// - `EACCES: permission denied, mkdir '/etc/x'`
+fn extract_path(line: &str) -> Option<PathBuf> {
+    for (open, close) in [('\'', '\''), ('‘', '’'), ('"', '"'), ('`', '\'')] {
+        let mut rest = line;
+        while let Some(start) = rest.find(open) {
+            let after = &rest[start + open.len_utf8()..];
+            let Some(end) = after.find(close) else {
+                break;
+            };
+            let candidate = &after[..end];
+            if looks_like_path(candidate) {
+                return Some(PathBuf::from(candidate));
+            }
+            rest = &after[end + close.len_utf8()..];
+        }
+    }
+
+    line.split(": ")
+        .map(str::trim)
+        .find(|field| looks_like_path(field) && !field.contains(' '))
+        .map(PathBuf::from)
+}
+
+fn looks_like_path(s: &str) -> bool {
+    !s.is_empty()
+        && !s.contains("://")
+        && (s.starts_with('/') || s.starts_with("./") || s.starts_with("../") || s.contains('/'))
+}
+
+/// Resolves `.` and `..` without touching the filesystem, so paths that do
+/// not exist yet can still be compared against writable roots.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for comp in path.components() {
+        match comp {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+fn common_ancestor(a: &Path, b: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for (x, y) in a.components().zip(b.components()) {
+        if x != y {
+            break;
+        }
+        out.push(x.as_os_str());
+    }
+    (!out.as_os_str().is_empty()).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace_policy() -> SandboxPolicy {
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+        }
+    }
+
+    #[test]
+    fn detects_writes_outside_writable_roots() {
+        let cwd = Path::new("/work/repo");
+        let stderr = "\
+touch: cannot touch '/etc/codex-test': Permission denied
+mkdir: cannot create directory ‘/opt/tool’: Permission denied
+bash: ../sibling/out.txt: Permission denied
+rm: cannot remove 'src/lib.rs': Permission denied
+PermissionError: [Errno 13] Permission denied: '/usr/lib/x.pyc'
+";
+        let denials = detect_sandbox_denials(stderr, &workspace_policy(), cwd);
+        assert_eq!(
+            denials,
+            vec![
+                SandboxDenial {
+                    operation: SandboxOperation::FileWrite,
+                    path: Some(PathBuf::from("/etc/codex-test")),
+                    syscall: Some("openat".to_string()),
+                    inferred: true,
+                },
+                SandboxDenial {
+                    operation: SandboxOperation::FileWrite,
+                    path: Some(PathBuf::from("/opt/tool")),
+                    syscall: Some("mkdir".to_string()),
+                    inferred: true,
+                },
+                SandboxDenial {
+                    operation: SandboxOperation::FileWrite,
+                    path: Some(PathBuf::from("/work/sibling/out.txt")),
+                    syscall: Some("openat".to_string()),
+                    inferred: true,
+                },
+                // `src/lib.rs` is inside the workspace, so the sandbox did
+                // not cause that failure.
+                SandboxDenial {
+                    operation: SandboxOperation::FileWrite,
+                    path: Some(PathBuf::from("/usr/lib/x.pyc")),
+                    syscall: Some("openat".to_string()),
+                    inferred: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_network_denials_only_without_network_access() {
+        let cwd = Path::new("/work");
+        let stderr = "ping: socket: Operation not permitted\n";
+        assert_eq!(
+            detect_sandbox_denials(stderr, &workspace_policy(), cwd),
+            vec![SandboxDenial {
+                operation: SandboxOperation::Network,
+                path: None,
+                syscall: Some("socket".to_string()),
+                inferred: true,
+            }]
+        );
+        // Messages that only mention networking are not syscall failures.
+        let unrelated = "\
+error: failed to connect to host: Permission denied
+warning: network socket permission denied by policy
+";
+        assert!(detect_sandbox_denials(unrelated, &workspace_policy(), cwd).is_empty());
+        let with_network = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: true,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+        };
+        assert!(detect_sandbox_denials(stderr, &with_network, cwd).is_empty());
+    }
+
+    #[expect(clippy::unwrap_used)]
+    #[test]
+    fn suggests_common_existing_directory() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path().join("cache");
+        std::fs::create_dir(&root).unwrap();
+        let write = |p: PathBuf| SandboxDenial {
+            operation: SandboxOperation::FileWrite,
+            path: Some(p),
+            syscall: None,
+            inferred: false,
+        };
+
+        let denials = vec![
+            write(root.join("a.txt")),
+            write(root.join("missing/deeper/b.txt")),
+        ];
+        assert_eq!(suggest_writable_root(&denials), Some(root.clone()));
+
+        let mut with_network = denials.clone();
+        with_network.push(SandboxDenial {
+            operation: SandboxOperation::Network,
+            path: None,
+            syscall: Some("connect".to_string()),
+            inferred: false,
+        });
+        assert_eq!(suggest_writable_root(&with_network), None);
+
+        // Error output alone never leads to a grant.
+        let mut inferred = denials.clone();
+        inferred[0].inferred = true;
+        assert_eq!(suggest_writable_root(&inferred), None);
+
+        let spread = vec![write(root.join("a.txt")), write(PathBuf::from("/x"))];
+        assert_eq!(suggest_writable_root(&spread), None);
+    }
+
+    #[test]
+    fn reads_denials_logged_by_the_sandbox() {
+        let log = r#"{"operation":"file_write","path":"/etc/x","syscall":"openat"}
+{"operation":"network","syscall":"connect"}
+{"operation":"file_write","path":"/etc/x","syscall":"openat"}
+not json
+"#;
+        assert_eq!(
+            read_denial_log(log.as_bytes()),
+            vec![
+                SandboxDenial {
+                    operation: SandboxOperation::FileWrite,
+                    path: Some(PathBuf::from("/etc/x")),
+                    syscall: Some("openat".to_string()),
+                    inferred: false,
+                },
+                SandboxDenial {
+                    operation: SandboxOperation::Network,
+                    path: None,
+                    syscall: Some("connect".to_string()),
+                    inferred: false,
+                },
+            ]
+        );
+    }
+}
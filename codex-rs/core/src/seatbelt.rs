@@ -34,6 +34,7 @@ pub async fn spawn_command_under_seatbelt(
         sandbox_policy,
         stdio_policy,
         env,
+        None,
     )
     .await
 }
@@ -317,6 +317,7 @@ mod tests {
                     )]),
                     with_escalated_permissions: None,
                     justification: None,
+                    overlay: None,
                 },
                 SandboxType::None,
                 &SandboxPolicy::DangerFullAccess,
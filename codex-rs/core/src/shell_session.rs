@@ -0,0 +1,842 @@
+//! Persistent, PTY-backed shell sessions for the `shell_session` tool.
+//!
+//! Every `shell` call spawns a fresh process, so state such as the working
+//! directory, exported variables or an activated virtualenv is lost between
+//! calls. A shell session keeps a single interactive shell alive for the
+//! lifetime of the Codex session and feeds it one command at a time. The
+//! shell is spawned under the same sandbox as regular exec calls, so every
+//! command run inside it inherits those restrictions.
+//!
+//! Commands are delimited by a marker line printed after each command with
+//! the command's exit status. Output produced while a command runs is
+//! forwarded as `ExecCommandOutputDelta` events.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_bytes::ByteBuf;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Child;
+use tokio::sync::Notify;
+
+use crate::exec::MAX_STREAM_OUTPUT;
+use crate::exec::MAX_STREAM_OUTPUT_LINES;
+use crate::exec::SandboxType;
+use crate::exec::StdoutStream;
+use crate::exec_snapshot::OverlayMount;
+use crate::openai_tools::JsonSchema;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::ResponsesApiTool;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::ExecCommandOutputDeltaEvent;
+use crate::protocol::ExecOutputStream;
+use crate::protocol::SandboxPolicy;
+
+pub const DEFAULT_EXEC_TIMEOUT_MS: u64 = 120_000;
+pub const DEFAULT_READ_TIMEOUT_MS: u64 = 1_000;
+
+/// Upper bound on concurrently open sessions per Codex session.
+const MAX_SESSIONS: usize = 8;
+
+/// Bytes retained for a command that has not been read yet.
+const MAX_BUFFERED_BYTES: usize = 1024 * 1024;
+
+const MARKER_PREFIX: &str = "__CODEX_SHELL_DONE";
+
+/// Time allowed for the shell to come up and process its init line.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShellSessionToolCallParams {
+    pub action: String,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub workdir: Option<String>,
+    #[serde(default, alias = "timeout")]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub with_escalated_permissions: Option<bool>,
+    #[serde(default)]
+    pub justification: Option<String>,
+}
+
+/// Result of running or polling a command in a session.
+#[derive(Debug, Clone)]
+pub struct ShellSessionOutput {
+    pub output: String,
+    pub truncated_after_lines: Option<u32>,
+    /// `None` while the command is still running.
+    pub exit_code: Option<i32>,
+    /// The shell itself has exited; the session is no longer usable.
+    pub shell_exited: bool,
+    pub duration: Duration,
+}
+
+struct PendingCommand {
+    marker: String,
+    started: Instant,
+}
+
+#[derive(Default)]
+struct OutputBuffer {
+    data: Vec<u8>,
+    /// Offset into `data` up to which output has been returned to the model.
+    consumed: usize,
+    closed: bool,
+    stream: Option<StdoutStream>,
+}
+
+pub struct ShellSession {
+    pub id: String,
+    pub cwd: PathBuf,
+    pub sandbox_type: SandboxType,
+    child: tokio::sync::Mutex<Child>,
+    writer: tokio::sync::Mutex<tokio::fs::File>,
+    buffer: Arc<Mutex<OutputBuffer>>,
+    notify: Arc<Notify>,
+    pending: Mutex<Option<PendingCommand>>,
+    /// Exec snapshot overlay the shell runs on, until the session has
+    /// recorded what it changed.
+    overlay: Mutex<Option<OverlayMount>>,
+}
+
+impl ShellSession {
+    #[cfg(unix)]
+    async fn spawn(
+        id: String,
+        cwd: PathBuf,
+        mut env: HashMap<String, String>,
+        sandbox_type: SandboxType,
+        sandbox_policy: &SandboxPolicy,
+        codex_linux_sandbox_exe: &Option<PathBuf>,
+        overlay: Option<OverlayMount>,
+    ) -> io::Result<Self> {
+        use crate::landlock::spawn_command_under_linux_sandbox_in_overlay;
+        use crate::seatbelt::spawn_command_under_seatbelt;
+        use crate::spawn::StdioPolicy;
+        use crate::spawn::spawn_child_async;
+        use std::os::fd::AsRawFd;
+
+        let (master, slave) = open_pty()?;
+
+        let command = if std::path::Path::new("/bin/bash").exists() {
+            vec![
+                "/bin/bash".to_string(),
+                "--noprofile".to_string(),
+                "--norc".to_string(),
+                "--noediting".to_string(),
+            ]
+        } else {
+            vec!["/bin/sh".to_string()]
+        };
+        env.insert("PS1".to_string(), String::new());
+        env.insert("PS2".to_string(), String::new());
+        env.insert("TERM".to_string(), "dumb".to_string());
+        env.insert("PAGER".to_string(), "cat".to_string());
+        env.insert("GIT_PAGER".to_string(), "cat".to_string());
+
+        let stdio_policy = StdioPolicy::Pty(slave.as_raw_fd());
+        let child = match sandbox_type {
+            SandboxType::None => {
+                let (program, args) = command
+                    .split_first()
+                    .ok_or_else(|| io::Error::other("empty shell command"))?;
+                spawn_child_async(
+                    PathBuf::from(program),
+                    args.to_vec(),
+                    None,
+                    cwd.clone(),
+                    sandbox_policy,
+                    stdio_policy,
+                    env,
+                    overlay.as_ref(),
+                )
+                .await?
+            }
+            SandboxType::MacosSeatbelt => {
+                spawn_command_under_seatbelt(
+                    command,
+                    sandbox_policy,
+                    cwd.clone(),
+                    stdio_policy,
+                    env,
+                )
+                .await?
+            }
+            SandboxType::LinuxSeccomp => {
+                let exe = codex_linux_sandbox_exe.as_ref().ok_or_else(|| {
+                    io::Error::other("codex-linux-sandbox executable not provided")
+                })?;
+                spawn_command_under_linux_sandbox_in_overlay(
+                    exe,
+                    command,
+                    sandbox_policy,
+                    cwd.clone(),
+                    stdio_policy,
+                    env,
+                    overlay.as_ref(),
+                    None,
+                )
+                .await?
+            }
+        };
+        // The child holds its own copies of the slave side; keeping ours open
+        // would prevent EOF on the master once the shell exits.
+        drop(slave);
+
+        let reader = tokio::fs::File::from_std(std::fs::File::from(master.try_clone()?));
+        let writer = tokio::fs::File::from_std(std::fs::File::from(master));
+
+        let buffer = Arc::new(Mutex::new(OutputBuffer::default()));
+        let notify = Arc::new(Notify::new());
+        tokio::spawn(read_pty(reader, buffer.clone(), notify.clone()));
+
+        let session = Self {
+            id,
+            cwd,
+            sandbox_type,
+            child: tokio::sync::Mutex::new(child),
+            writer: tokio::sync::Mutex::new(writer),
+            buffer,
+            notify,
+            pending: Mutex::new(None),
+            overlay: Mutex::new(overlay),
+        };
+
+        // Make sure the prompt is empty, the tty does not echo our input and
+        // `!` is not subject to history expansion, then wait for the shell to
+        // acknowledge before handing it out. Stdin is redirected inside
+        // `exec`, so point stty at the tty through stdout.
+        let init =
+            "stty -echo <&1 2>/dev/null; set +H 2>/dev/null; PS1=''; PS2=''; PROMPT_COMMAND=''";
+        let result = session.exec(init, STARTUP_TIMEOUT, None).await?;
+        if result.exit_code.is_none() || result.shell_exited {
+            return Err(io::Error::other(format!(
+                "shell did not start: {}",
+                result.output.trim()
+            )));
+        }
+        Ok(session)
+    }
+
+    #[cfg(not(unix))]
+    async fn spawn(
+        _id: String,
+        _cwd: PathBuf,
+        _env: HashMap<String, String>,
+        _sandbox_type: SandboxType,
+        _sandbox_policy: &SandboxPolicy,
+        _codex_linux_sandbox_exe: &Option<PathBuf>,
+        _overlay: Option<OverlayMount>,
+    ) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "shell sessions are only supported on Unix",
+        ))
+    }
+
+    /// Sends `command` to the shell and waits up to `timeout` for it to
+    /// finish. If it is still running when the timeout elapses, the output
+    /// collected so far is returned and the command keeps running; use
+    /// [`ShellSession::read`] to collect the rest.
+    pub async fn exec(
+        &self,
+        command: &str,
+        timeout: Duration,
+        stream: Option<StdoutStream>,
+    ) -> io::Result<ShellSessionOutput> {
+        let script = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            if pending.is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    format!(
+                        "shell session {} is still running a command; use action=read to wait for it or action=close to kill it",
+                        self.id
+                    ),
+                ));
+            }
+            let nonce = uuid::Uuid::new_v4().simple().to_string();
+            *pending = Some(PendingCommand {
+                marker: format!("{MARKER_PREFIX}_{nonce}:"),
+                started: Instant::now(),
+            });
+
+            let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+            buffer.data.clear();
+            buffer.consumed = 0;
+            buffer.stream = stream;
+
+            // The command runs inside a brace group in the current shell so
+            // `cd`, exports and sourced scripts persist. Stdin is redirected so
+            // commands cannot swallow the marker line. The marker is printed
+            // in pieces so a tty echo of the input never matches it.
+            format!(
+                "{{ {command}\n}} </dev/null\nprintf '\\n%s_%s:%s\\n' {MARKER_PREFIX} {nonce} \"$?\"\n"
+            )
+        };
+
+        let written = {
+            let mut writer = self.writer.lock().await;
+            match writer.write_all(script.as_bytes()).await {
+                Ok(()) => writer.flush().await,
+                Err(e) => Err(e),
+            }
+        };
+        if let Err(e) = written {
+            self.pending
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .take();
+            return Err(e);
+        }
+
+        self.wait(timeout).await
+    }
+
+    /// Returns output produced since the last call, waiting up to `timeout`
+    /// for the running command (if any) to finish.
+    pub async fn read(
+        &self,
+        timeout: Duration,
+        stream: Option<StdoutStream>,
+    ) -> io::Result<ShellSessionOutput> {
+        let running = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some();
+        if running {
+            self.buffer.lock().unwrap_or_else(|e| e.into_inner()).stream = stream;
+            self.wait(timeout).await
+        } else {
+            Ok(self.take_output(None, Duration::ZERO))
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some()
+    }
+
+    /// Takes the exec snapshot overlay the shell ran on, if any.
+    pub fn take_overlay(&self) -> Option<OverlayMount> {
+        self.overlay
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+    }
+
+    pub async fn kill(&self) {
+        let mut child = self.child.lock().await;
+        let _ = child.start_kill();
+        let _ = tokio::time::timeout(Duration::from_secs(2), child.wait()).await;
+    }
+
+    async fn wait(&self, timeout: Duration) -> io::Result<ShellSessionOutput> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let (marker, started) = {
+                let pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+                match pending.as_ref() {
+                    Some(p) => (p.marker.clone(), p.started),
+                    None => return Ok(self.take_output(None, Duration::ZERO)),
+                }
+            };
+
+            let (completed, closed) = {
+                let buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+                (find_marker(&buffer.data, &marker), buffer.closed)
+            };
+            if let Some(end) = completed {
+                self.pending
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .take();
+                return Ok(self.take_output(Some(end), started.elapsed()));
+            }
+            if closed {
+                self.pending
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .take();
+                let mut output = self.take_output(None, started.elapsed());
+                output.shell_exited = true;
+                return Ok(output);
+            }
+
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                // Output that arrives after this call returns is picked up by
+                // the next `read`, not streamed against this call id.
+                self.buffer.lock().unwrap_or_else(|e| e.into_inner()).stream = None;
+                return Ok(self.take_output(None, started.elapsed()));
+            }
+        }
+    }
+
+    /// Drains unread output up to `end` (the marker position), or everything
+    /// buffered when the command is still running.
+    fn take_output(&self, end: Option<(usize, i32)>, duration: Duration) -> ShellSessionOutput {
+        let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        let (stop, exit_code) = match end {
+            Some((pos, code)) => (pos, Some(code)),
+            None => (buffer.data.len(), None),
+        };
+        let start = buffer.consumed.min(stop);
+        let mut raw = buffer.data[start..stop].to_vec();
+        if exit_code.is_some() {
+            // Drop the newline printed in front of the marker.
+            if raw.ends_with(b"\r\n") {
+                raw.truncate(raw.len() - 2);
+            } else if raw.ends_with(b"\n") {
+                raw.truncate(raw.len() - 1);
+            }
+            buffer.data.clear();
+            buffer.consumed = 0;
+            buffer.stream = None;
+        } else {
+            buffer.consumed = stop;
+        }
+        let closed = buffer.closed;
+        drop(buffer);
+
+        let text = String::from_utf8_lossy(&raw).replace("\r\n", "\n");
+        let (output, truncated_after_lines) = truncate_output(&text);
+        ShellSessionOutput {
+            output,
+            truncated_after_lines,
+            exit_code,
+            shell_exited: closed && exit_code.is_none(),
+            duration,
+        }
+    }
+}
+
+/// Sessions owned by a single Codex session. Shells are spawned with
+/// `kill_on_drop`, so dropping the manager kills every shell it spawned.
+#[derive(Default)]
+pub struct ShellSessionManager {
+    sessions: tokio::sync::Mutex<HashMap<String, Arc<ShellSession>>>,
+    next_id: AtomicU64,
+}
+
+impl ShellSessionManager {
+    pub async fn open(
+        &self,
+        cwd: PathBuf,
+        env: HashMap<String, String>,
+        sandbox_type: SandboxType,
+        sandbox_policy: &SandboxPolicy,
+        codex_linux_sandbox_exe: &Option<PathBuf>,
+        overlay: Option<OverlayMount>,
+    ) -> io::Result<Arc<ShellSession>> {
+        if self.sessions.lock().await.len() >= MAX_SESSIONS {
+            return Err(io::Error::other(format!(
+                "too many open shell sessions (max {MAX_SESSIONS}); close one first"
+            )));
+        }
+        let id = format!("sh-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let session = Arc::new(
+            ShellSession::spawn(
+                id.clone(),
+                cwd,
+                env,
+                sandbox_type,
+                sandbox_policy,
+                codex_linux_sandbox_exe,
+                overlay,
+            )
+            .await?,
+        );
+        self.sessions.lock().await.insert(id, session.clone());
+        Ok(session)
+    }
+
+    pub async fn get(&self, id: &str) -> Option<Arc<ShellSession>> {
+        self.sessions.lock().await.get(id).cloned()
+    }
+
+    /// Kills the shell and forgets the session. Returns `None` if no session
+    /// with this id exists.
+    pub async fn close(&self, id: &str) -> Option<Arc<ShellSession>> {
+        let session = self.sessions.lock().await.remove(id)?;
+        session.kill().await;
+        Some(session)
+    }
+
+    pub async fn list(&self) -> Vec<Arc<ShellSession>> {
+        let mut sessions: Vec<_> = self.sessions.lock().await.values().cloned().collect();
+        sessions.sort_by(|a, b| a.id.cmp(&b.id));
+        sessions
+    }
+}
+
+/// Formats a session result as the JSON payload returned to the model.
+pub fn format_shell_session_output(session_id: &str, output: &ShellSessionOutput) -> String {
+    #[derive(Serialize)]
+    struct Metadata {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
+        running: bool,
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        shell_exited: bool,
+        duration_seconds: f32,
+    }
+
+    #[derive(Serialize)]
+    struct Payload<'a> {
+        session_id: &'a str,
+        output: &'a str,
+        metadata: Metadata,
+    }
+
+    let mut text = output.output.clone();
+    if let Some(lines) = output.truncated_after_lines {
+        text.push_str(&format!(
+            "\n\n[Output truncated after {lines} lines: too many lines or bytes.]",
+        ));
+    }
+
+    let payload = Payload {
+        session_id,
+        output: &text,
+        metadata: Metadata {
+            exit_code: output.exit_code,
+            running: output.exit_code.is_none() && !output.shell_exited,
+            shell_exited: output.shell_exited,
+            duration_seconds: ((output.duration.as_secs_f32()) * 10.0).round() / 10.0,
+        },
+    };
+
+    #[expect(clippy::expect_used)]
+    serde_json::to_string(&payload).expect("serialize shell session output")
+}
+
+pub(crate) fn create_shell_session_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "action".to_string(),
+        JsonSchema::String {
+            description: Some("One of 'open', 'exec', 'read' or 'close'.".to_string()),
+        },
+    );
+    properties.insert(
+        "session_id".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Session id returned by 'open'. Required for 'exec', 'read' and 'close'."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "command".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "For 'exec': a shell command line to run in the session. Stdin is not connected."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "workdir".to_string(),
+        JsonSchema::String {
+            description: Some("For 'open': the initial working directory.".to_string()),
+        },
+    );
+    properties.insert(
+        "timeout".to_string(),
+        JsonSchema::Number {
+            description: Some(format!(
+                "For 'exec' and 'read': how long to wait for the command to finish in milliseconds (defaults: exec {DEFAULT_EXEC_TIMEOUT_MS}, read {DEFAULT_READ_TIMEOUT_MS}). The command keeps running after a timeout."
+            )),
+        },
+    );
+    properties.insert(
+        "with_escalated_permissions".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "For 'open': request a session that runs without sandbox restrictions. Requires user approval."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "justification".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "For 'open' with with_escalated_permissions: 1-sentence explanation of why the session needs it."
+                    .to_string(),
+            ),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: "shell_session".to_string(),
+        description: "Runs commands in a persistent shell. Unlike `shell`, the working directory, exported variables, activated virtualenvs and sourced scripts carry over between 'exec' calls in the same session. 'open' starts a session and returns its session_id, 'exec' runs a command and returns its output and exit code, 'read' collects output from a command that outlived its timeout, and 'close' kills the session.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["action".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+#[cfg(unix)]
+fn open_pty() -> io::Result<(std::os::fd::OwnedFd, std::os::fd::OwnedFd)> {
+    use std::os::fd::FromRawFd;
+    use std::os::fd::OwnedFd;
+
+    let mut master: libc::c_int = -1;
+    let mut slave: libc::c_int = -1;
+    // Wide enough that typical command output is not wrapped by the tty.
+    let mut winsize = libc::winsize {
+        ws_row: 24,
+        ws_col: 200,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: all pointers are valid for the duration of the call.
+    let rc = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &raw mut winsize,
+        )
+    };
+    if rc != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: openpty returned two freshly opened descriptors that we own.
+    let (master, slave) = unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+
+    // Disable echo up front so the command text does not show up in the
+    // output; the init line repeats this in case the shell resets it.
+    // SAFETY: `termios` is plain data and the descriptor is valid.
+    unsafe {
+        use std::os::fd::AsRawFd;
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(slave.as_raw_fd(), &mut termios) == 0 {
+            termios.c_lflag &= !(libc::ECHO | libc::ECHONL);
+            libc::tcsetattr(slave.as_raw_fd(), libc::TCSANOW, &termios);
+        }
+    }
+
+    Ok((master, slave))
+}
+
+async fn read_pty(
+    mut reader: tokio::fs::File,
+    buffer: Arc<Mutex<OutputBuffer>>,
+    notify: Arc<Notify>,
+) {
+    let mut tmp = [0u8; 8192];
+    let mut filter = MarkerFilter::default();
+    loop {
+        // Once the shell and all of its children exit, reading the master
+        // side fails with EIO on Linux; treat that like EOF.
+        let n = match reader.read(&mut tmp).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+
+        let stream = {
+            let mut buffer = buffer.lock().unwrap_or_else(|e| e.into_inner());
+            buffer.data.extend_from_slice(&tmp[..n]);
+            if buffer.data.len() > MAX_BUFFERED_BYTES {
+                let excess = buffer.data.len() - MAX_BUFFERED_BYTES;
+                buffer.data.drain(..excess);
+                buffer.consumed = buffer.consumed.saturating_sub(excess);
+            }
+            buffer.stream.clone()
+        };
+        notify.notify_waiters();
+
+        if let Some(stream) = stream {
+            let chunk = filter.push(&tmp[..n]);
+            if !chunk.is_empty() {
+                let event = Event {
+                    id: stream.sub_id.clone(),
+                    msg: EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+                        call_id: stream.call_id.clone(),
+                        stream: ExecOutputStream::Stdout,
+                        chunk: ByteBuf::from(chunk),
+                    }),
+                };
+                let _ = stream.tx_event.send(event).await;
+            }
+        }
+    }
+
+    let stream = {
+        let mut buffer = buffer.lock().unwrap_or_else(|e| e.into_inner());
+        buffer.closed = true;
+        buffer.stream.clone()
+    };
+    notify.notify_waiters();
+
+    let chunk = filter.finish();
+    if let Some(stream) = stream
+        && !chunk.is_empty()
+    {
+        let event = Event {
+            id: stream.sub_id.clone(),
+            msg: EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+                call_id: stream.call_id.clone(),
+                stream: ExecOutputStream::Stdout,
+                chunk: ByteBuf::from(chunk),
+            }),
+        };
+        let _ = stream.tx_event.send(event).await;
+    }
+}
+
+/// Finds `marker` followed by an exit status and a newline. Returns the byte
+/// offset where the marker starts together with the parsed status.
+fn find_marker(data: &[u8], marker: &str) -> Option<(usize, i32)> {
+    let marker = marker.as_bytes();
+    let pos = data
+        .windows(marker.len())
+        .position(|window| window == marker)?;
+    let rest = &data[pos + marker.len()..];
+    let line_end = rest.iter().position(|&b| b == b'\n')?;
+    let code = std::str::from_utf8(&rest[..line_end])
+        .ok()?
+        .trim()
+        .parse::<i32>()
+        .ok()?;
+    Some((pos, code))
+}
+
+/// Strips marker lines from streamed output. A read can end in the middle
+/// of a marker line, so a trailing partial line that may still turn into a
+/// marker is held back until the rest of it arrives.
+#[derive(Default)]
+struct MarkerFilter {
+    pending: Vec<u8>,
+}
+
+impl MarkerFilter {
+    fn push(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.pending.extend_from_slice(chunk);
+        let line_start = self
+            .pending
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |pos| pos + 1);
+        let held = if could_be_marker(&self.pending[line_start..]) {
+            self.pending.len() - line_start
+        } else {
+            0
+        };
+        let ready: Vec<u8> = self.pending.drain(..self.pending.len() - held).collect();
+        strip_marker_lines(&ready)
+    }
+
+    /// Releases whatever is still held back once the stream has ended.
+    fn finish(&mut self) -> Vec<u8> {
+        strip_marker_lines(&std::mem::take(&mut self.pending))
+    }
+}
+
+/// Whether an unterminated line is a prefix of, or starts with, a marker.
+fn could_be_marker(line: &[u8]) -> bool {
+    let prefix = MARKER_PREFIX.as_bytes();
+    let len = line.len().min(prefix.len());
+    !line.is_empty() && line[..len] == prefix[..len]
+}
+
+/// Removes marker lines from a chunk before it is streamed to the UI.
+fn strip_marker_lines(chunk: &[u8]) -> Vec<u8> {
+    let prefix = MARKER_PREFIX.as_bytes();
+    if !chunk.windows(prefix.len()).any(|w| w == prefix) {
+        return chunk.to_vec();
+    }
+    chunk
+        .split_inclusive(|&b| b == b'\n')
+        .filter(|line| !line.starts_with(prefix))
+        .flatten()
+        .copied()
+        .collect()
+}
+
+fn truncate_output(text: &str) -> (String, Option<u32>) {
+    let mut out = String::new();
+    for (lines, line) in text.split_inclusive('\n').enumerate() {
+        if lines >= MAX_STREAM_OUTPUT_LINES || out.len() + line.len() > MAX_STREAM_OUTPUT {
+            return (out, Some(lines as u32));
+        }
+        out.push_str(line);
+    }
+    (out, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_marker_parses_exit_code() {
+        let data = b"hello\r\n__CODEX_SHELL_DONE_abc:3\r\n";
+        assert_eq!(find_marker(data, "__CODEX_SHELL_DONE_abc:"), Some((7, 3)));
+        // Incomplete marker line is not a match yet.
+        assert_eq!(
+            find_marker(b"__CODEX_SHELL_DONE_abc:1", "__CODEX_SHELL_DONE_abc:"),
+            None
+        );
+        // Echoed input does not contain the joined marker.
+        let echoed = b"printf '\\n%s_%s:%s\\n' __CODEX_SHELL_DONE abc \"$?\"\r\n";
+        assert_eq!(find_marker(echoed, "__CODEX_SHELL_DONE_abc:"), None);
+    }
+
+    #[test]
+    fn strip_marker_lines_hides_marker_from_stream() {
+        let chunk = b"out\n__CODEX_SHELL_DONE_abc:0\n";
+        assert_eq!(strip_marker_lines(chunk), b"out\n".to_vec());
+        assert_eq!(strip_marker_lines(b"plain\n"), b"plain\n".to_vec());
+    }
+
+    #[test]
+    fn marker_filter_hides_marker_split_across_reads() {
+        let mut filter = MarkerFilter::default();
+        assert_eq!(filter.push(b"out\n__CODEX_SH"), b"out\n".to_vec());
+        assert_eq!(filter.push(b"ELL_DONE_abc:"), Vec::<u8>::new());
+        assert_eq!(filter.push(b"0\nnext"), b"next".to_vec());
+        // Partial lines that cannot be a marker are streamed right away.
+        assert_eq!(filter.push(b"$ "), b"$ ".to_vec());
+        assert_eq!(filter.push(b"\n__CO"), b"\n".to_vec());
+        assert_eq!(filter.finish(), b"__CO".to_vec());
+    }
+
+    #[test]
+    fn truncate_output_caps_lines() {
+        let text: String = (0..300).map(|i| format!("{i}\n")).collect();
+        let (out, truncated) = truncate_output(&text);
+        assert_eq!(truncated, Some(MAX_STREAM_OUTPUT_LINES as u32));
+        assert_eq!(out.lines().count(), MAX_STREAM_OUTPUT_LINES);
+    }
+}
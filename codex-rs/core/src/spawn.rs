@@ -5,6 +5,7 @@ use tokio::process::Child;
 use tokio::process::Command;
 use tracing::trace;
 
+use crate::exec_snapshot::OverlayMount;
 use crate::protocol::SandboxPolicy;
 
 /// Experimental environment variable that will be set to some non-empty value
@@ -25,7 +26,16 @@ pub const CODEX_SANDBOX_ENV_VAR: &str = "CODEX_SANDBOX";
 #[derive(Debug, Clone, Copy)]
 pub enum StdioPolicy {
     RedirectForShellTool,
+    /// Like `RedirectForShellTool`, but the child is placed in its own
+    /// process group so the whole tree can be signalled at once. Used for
+    /// background processes that outlive the tool call that started them.
+    Background,
     Inherit,
+    /// Attach stdin, stdout and stderr to the slave side of a pseudo-terminal
+    /// and make it the controlling terminal of a new session. Used by
+    /// persistent shell sessions.
+    #[cfg(unix)]
+    Pty(std::os::fd::RawFd),
 }
 
 /// Spawns the appropriate child process for the ExecParams and SandboxPolicy,
@@ -35,6 +45,7 @@ pub enum StdioPolicy {
 /// For now, we take `SandboxPolicy` as a parameter to spawn_child() because
 /// we need to determine whether to set the
 /// `CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR` environment variable.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn spawn_child_async(
     program: PathBuf,
     args: Vec<String>,
@@ -43,15 +54,31 @@ pub(crate) async fn spawn_child_async(
     sandbox_policy: &SandboxPolicy,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
+    overlay: Option<&OverlayMount>,
 ) -> std::io::Result<Child> {
     trace!(
-        "spawn_child_async: {program:?} {args:?} {arg0:?} {cwd:?} {sandbox_policy:?} {stdio_policy:?} {env:?}"
+        "spawn_child_async: {program:?} {args:?} {arg0:?} {cwd:?} {sandbox_policy:?} {stdio_policy:?} {env:?} {overlay:?}"
     );
 
     let mut cmd = Command::new(&program);
     #[cfg(unix)]
     cmd.arg0(arg0.map_or_else(|| program.to_string_lossy().to_string(), String::from));
     cmd.args(args);
+
+    // Run the child on top of an exec snapshot overlay (see
+    // `crate::exec_snapshot`). Registered before anything else so that the
+    // sandbox helper already starts inside the new namespaces.
+    #[cfg(target_os = "linux")]
+    if let Some(overlay) = overlay {
+        let hook = overlay.pre_exec_hook(&cwd)?;
+        // SAFETY: the hook only performs async-signal-safe syscalls.
+        unsafe {
+            cmd.pre_exec(hook);
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = overlay;
+
     cmd.current_dir(cwd);
     cmd.env_clear();
     cmd.envs(env);
@@ -95,12 +122,44 @@ pub(crate) async fn spawn_child_async(
 
             cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
         }
+        StdioPolicy::Background => {
+            cmd.stdin(Stdio::null());
+            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+            #[cfg(unix)]
+            cmd.process_group(0);
+        }
         StdioPolicy::Inherit => {
             // Inherit stdin, stdout, and stderr from the parent process.
             cmd.stdin(Stdio::inherit())
                 .stdout(Stdio::inherit())
                 .stderr(Stdio::inherit());
         }
+        #[cfg(unix)]
+        StdioPolicy::Pty(slave_fd) => {
+            use std::os::fd::BorrowedFd;
+
+            // SAFETY: the caller keeps the slave side of the pty open until
+            // the child has been spawned.
+            let slave = unsafe { BorrowedFd::borrow_raw(slave_fd) };
+            cmd.stdin(Stdio::from(slave.try_clone_to_owned()?))
+                .stdout(Stdio::from(slave.try_clone_to_owned()?))
+                .stderr(Stdio::from(slave.try_clone_to_owned()?));
+
+            unsafe {
+                cmd.pre_exec(|| {
+                    // Start a new session so the pty becomes the controlling
+                    // terminal of the shell (needed for job control and for
+                    // SIGHUP delivery when the master side is closed).
+                    if libc::setsid() == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    if libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
     }
 
     cmd.kill_on_drop(true).spawn()
@@ -0,0 +1,554 @@
+//! Visual regression baselines for browser screenshots.
+//!
+//! A baseline is a screenshot saved under a name for one URL and viewport,
+//! stored in `.code/visual-baselines/` at the repository root so it can be
+//! committed next to the code it covers. Later screenshots of the same page
+//! are compared pixel by pixel: the result carries a similarity score, the
+//! bounding boxes of the regions that changed and a diff image in which those
+//! regions are highlighted over a faded copy of the baseline. The perceptual
+//! hashes from `image_comparison` are reported alongside, since they tell
+//! "shifted by a pixel" apart from "looks different".
+//!
+//! A failed comparison keeps the new screenshot next to the baseline as
+//! `<stem>.actual.png` so it can be accepted later (`code baselines accept`).
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use image::Rgba;
+use image::RgbaImage;
+use serde::Deserialize;
+use serde::Serialize;
+use sha1::Digest;
+use sha1::Sha1;
+
+/// Directory, relative to the repository root, that holds all baselines.
+pub const BASELINES_DIR: &str = ".code/visual-baselines";
+
+/// Similarity at or above which a comparison passes by default.
+pub const DEFAULT_MIN_SIMILARITY: f64 = 0.999;
+
+/// Largest per-channel difference still treated as the same pixel; absorbs
+/// anti-aliasing and color-profile noise.
+const PIXEL_TOLERANCE: u8 = 24;
+
+/// Changed pixels are grouped on a grid of cells this many pixels wide
+/// before regions are extracted.
+const REGION_CELL: u32 = 8;
+
+/// Upper bound on the number of regions reported for one comparison.
+const MAX_REGIONS: usize = 20;
+
+const HIGHLIGHT: Rgba<u8> = Rgba([255, 0, 0, 255]);
+const OUTSIDE: Rgba<u8> = Rgba([255, 0, 255, 255]);
+
+/// Identifies one baseline: a name chosen by the caller plus the page and
+/// viewport it was taken at.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaselineKey {
+    pub name: String,
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BaselineKey {
+    /// File stem shared by the baseline image, its metadata and any pending
+    /// or diff images: `<name>/<width>x<height>-<url slug>-<hash>`.
+    fn stem(&self) -> PathBuf {
+        let slug: String = self
+            .url
+            .split_once("://")
+            .map_or(self.url.as_str(), |(_, rest)| rest)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .take(60)
+            .collect();
+        let hash = format!("{:x}", Sha1::digest(self.url.as_bytes()));
+        Path::new(&sanitize(&self.name)).join(format!(
+            "{}x{}-{}-{}",
+            self.width,
+            self.height,
+            slug.trim_matches('_'),
+            &hash[..8]
+        ))
+    }
+}
+
+/// A stored baseline as reported by [`BaselineStore::list`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    #[serde(flatten)]
+    pub key: BaselineKey,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Path of the baseline image.
+    #[serde(skip)]
+    pub path: PathBuf,
+    /// Whether a failed comparison left a screenshot waiting to be accepted.
+    #[serde(skip)]
+    pub pending: bool,
+}
+
+/// A rectangle, in screenshot pixels, that contains changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DiffRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Pixel-level differences between two images.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffStats {
+    pub changed_pixels: u64,
+    pub total_pixels: u64,
+    /// Fraction of pixels that match, from 0.0 to 1.0.
+    pub similarity: f64,
+    /// Largest regions first, at most [`MAX_REGIONS`].
+    pub regions: Vec<DiffRegion>,
+    /// Regions dropped because of the cap.
+    pub omitted_regions: usize,
+}
+
+/// Outcome of comparing a screenshot against its baseline.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub baseline_path: PathBuf,
+    pub stats: DiffStats,
+    /// Baseline and screenshot dimensions when they differ.
+    pub size_changed: Option<((u32, u32), (u32, u32))>,
+    /// Whether the perceptual hashes consider the images the same.
+    pub perceptual_match: bool,
+    /// Highlighted diff image, written only when pixels changed.
+    pub diff_path: Option<PathBuf>,
+    pub passed: bool,
+}
+
+/// Baselines stored under one repository.
+#[derive(Debug, Clone)]
+pub struct BaselineStore {
+    dir: PathBuf,
+}
+
+impl BaselineStore {
+    /// Store rooted at the git repository containing `cwd`, or at `cwd`
+    /// itself outside a repository.
+    pub fn for_workspace(cwd: &Path) -> Self {
+        let root = cwd
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .unwrap_or(cwd);
+        Self {
+            dir: root.join(BASELINES_DIR),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Saves `screenshot` as the baseline for `key`, replacing any previous
+    /// one and discarding a pending screenshot.
+    pub fn save(&self, key: &BaselineKey, screenshot: &Path) -> anyhow::Result<PathBuf> {
+        let stem = self.dir.join(key.stem());
+        if let Some(parent) = stem.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let path = with_suffix(&stem, ".png");
+        std::fs::copy(screenshot, &path)
+            .with_context(|| format!("failed to write baseline {}", path.display()))?;
+        let entry = BaselineEntry {
+            key: key.clone(),
+            created_at: chrono::Utc::now(),
+            path: path.clone(),
+            pending: false,
+        };
+        std::fs::write(
+            with_suffix(&stem, ".json"),
+            serde_json::to_string_pretty(&entry)?,
+        )?;
+        for suffix in [".actual.png", ".diff.png"] {
+            let _ = std::fs::remove_file(with_suffix(&stem, suffix));
+        }
+        Ok(path)
+    }
+
+    /// Compares `screenshot` with the baseline for `key`. Returns `None`
+    /// when no baseline has been saved for it yet.
+    pub fn compare(
+        &self,
+        key: &BaselineKey,
+        screenshot: &Path,
+        min_similarity: f64,
+    ) -> anyhow::Result<Option<Comparison>> {
+        let stem = self.dir.join(key.stem());
+        let baseline_path = with_suffix(&stem, ".png");
+        if !baseline_path.exists() {
+            return Ok(None);
+        }
+        let baseline = load_rgba(&baseline_path)?;
+        let actual = load_rgba(screenshot)?;
+        let (diff, stats) = diff_images(&baseline, &actual);
+
+        let perceptual_match = match (
+            crate::image_comparison::compute_image_hash(&baseline_path),
+            crate::image_comparison::compute_image_hash(screenshot),
+        ) {
+            (Ok((p1, d1)), Ok((p2, d2))) => {
+                crate::image_comparison::are_hashes_similar(&p1, &d1, &p2, &d2)
+            }
+            _ => false,
+        };
+        let size_changed = (baseline.dimensions() != actual.dimensions())
+            .then(|| (baseline.dimensions(), actual.dimensions()));
+        let passed = size_changed.is_none() && stats.similarity >= min_similarity;
+
+        let diff_path = with_suffix(&stem, ".diff.png");
+        let actual_path = with_suffix(&stem, ".actual.png");
+        let diff_path = if stats.changed_pixels > 0 {
+            diff.save(&diff_path)
+                .with_context(|| format!("failed to write {}", diff_path.display()))?;
+            Some(diff_path)
+        } else {
+            let _ = std::fs::remove_file(&diff_path);
+            None
+        };
+        if passed {
+            let _ = std::fs::remove_file(&actual_path);
+        } else {
+            std::fs::copy(screenshot, &actual_path)?;
+        }
+
+        Ok(Some(Comparison {
+            baseline_path,
+            stats,
+            size_changed,
+            perceptual_match,
+            diff_path,
+            passed,
+        }))
+    }
+
+    /// All baselines, optionally only those saved under `name`.
+    pub fn list(&self, name: Option<&str>) -> anyhow::Result<Vec<BaselineEntry>> {
+        let mut entries = Vec::new();
+        let dirs: Vec<PathBuf> = match name {
+            Some(name) => vec![self.dir.join(sanitize(name))],
+            None => match std::fs::read_dir(&self.dir) {
+                Ok(rd) => rd.filter_map(|e| e.ok().map(|e| e.path())).collect(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => return Err(e.into()),
+            },
+        };
+        for dir in dirs.into_iter().filter(|d| d.is_dir()) {
+            for file in std::fs::read_dir(&dir)?.filter_map(Result::ok) {
+                let meta_path = file.path();
+                if meta_path.extension().is_none_or(|ext| ext != "json") {
+                    continue;
+                }
+                let Ok(mut entry) = std::fs::read_to_string(&meta_path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|s| Ok(serde_json::from_str::<BaselineEntry>(&s)?))
+                else {
+                    continue;
+                };
+                let stem = meta_path.with_extension("");
+                entry.path = with_suffix(&stem, ".png");
+                entry.pending = with_suffix(&stem, ".actual.png").exists();
+                entries.push(entry);
+            }
+        }
+        entries.sort_by(|a, b| (&a.key.name, &a.key.url).cmp(&(&b.key.name, &b.key.url)));
+        Ok(entries)
+    }
+
+    /// Promotes pending screenshots from failed comparisons to baselines.
+    /// Returns the baselines that were updated.
+    pub fn accept(&self, name: Option<&str>) -> anyhow::Result<Vec<BaselineEntry>> {
+        let mut accepted = Vec::new();
+        for entry in self.list(name)?.into_iter().filter(|e| e.pending) {
+            let stem = self.dir.join(entry.key.stem());
+            let actual = with_suffix(&stem, ".actual.png");
+            self.save(&entry.key, &actual)?;
+            accepted.push(entry);
+        }
+        Ok(accepted)
+    }
+
+    /// Deletes baselines (all of them, or those saved under `name`) together
+    /// with their pending and diff images. Returns how many were removed.
+    pub fn reset(&self, name: Option<&str>) -> anyhow::Result<usize> {
+        let count = self.list(name)?.len();
+        let target = match name {
+            Some(name) => self.dir.join(sanitize(name)),
+            None => self.dir.clone(),
+        };
+        match std::fs::remove_dir_all(&target) {
+            Ok(()) => Ok(count),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Compares two images pixel by pixel. Pixels present in only one image
+/// (when the sizes differ) count as changed. The returned image highlights
+/// changed pixels and outlines the changed regions over a faded copy of
+/// `baseline`.
+pub fn diff_images(baseline: &RgbaImage, actual: &RgbaImage) -> (RgbaImage, DiffStats) {
+    let width = baseline.width().max(actual.width());
+    let height = baseline.height().max(actual.height());
+    let mut diff = RgbaImage::new(width, height);
+    let cols = width.div_ceil(REGION_CELL) as usize;
+    let rows = height.div_ceil(REGION_CELL) as usize;
+    let mut cells = vec![false; cols * rows];
+    let mut changed_pixels = 0u64;
+
+    for y in 0..height {
+        for x in 0..width {
+            let (out, changed) = match (
+                baseline.get_pixel_checked(x, y),
+                actual.get_pixel_checked(x, y),
+            ) {
+                (Some(a), Some(b)) => {
+                    if pixels_differ(a, b) {
+                        (HIGHLIGHT, true)
+                    } else {
+                        (faded(a), false)
+                    }
+                }
+                _ => (OUTSIDE, true),
+            };
+            diff.put_pixel(x, y, out);
+            if changed {
+                changed_pixels += 1;
+                let cell = (y / REGION_CELL) as usize * cols + (x / REGION_CELL) as usize;
+                cells[cell] = true;
+            }
+        }
+    }
+
+    let mut regions = regions_from_cells(&cells, cols, rows, width, height);
+    regions.sort_by_key(|r| std::cmp::Reverse(u64::from(r.width) * u64::from(r.height)));
+    let omitted_regions = regions.len().saturating_sub(MAX_REGIONS);
+    regions.truncate(MAX_REGIONS);
+    for region in &regions {
+        outline(&mut diff, region);
+    }
+
+    let total_pixels = u64::from(width) * u64::from(height);
+    let similarity = if total_pixels == 0 {
+        1.0
+    } else {
+        1.0 - changed_pixels as f64 / total_pixels as f64
+    };
+    (
+        diff,
+        DiffStats {
+            changed_pixels,
+            total_pixels,
+            similarity,
+            regions,
+            omitted_regions,
+        },
+    )
+}
+
+fn pixels_differ(a: &Rgba<u8>, b: &Rgba<u8>) -> bool {
+    a.0.iter()
+        .zip(b.0.iter())
+        .any(|(x, y)| x.abs_diff(*y) > PIXEL_TOLERANCE)
+}
+
+/// Light grayscale version of a pixel, so highlights stand out.
+fn faded(p: &Rgba<u8>) -> Rgba<u8> {
+    let [r, g, b, _] = p.0;
+    let luma = (u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114) / 1000;
+    let v = (170 + luma / 3) as u8;
+    Rgba([v, v, v, 255])
+}
+
+/// Groups 8-connected changed cells into pixel bounding boxes.
+fn regions_from_cells(
+    cells: &[bool],
+    cols: usize,
+    rows: usize,
+    width: u32,
+    height: u32,
+) -> Vec<DiffRegion> {
+    let mut seen = vec![false; cells.len()];
+    let mut regions = Vec::new();
+    for start in 0..cells.len() {
+        if !cells[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut stack = vec![start];
+        let (mut min_c, mut min_r, mut max_c, mut max_r) = (cols, rows, 0, 0);
+        while let Some(i) = stack.pop() {
+            let (c, r) = (i % cols, i / cols);
+            min_c = min_c.min(c);
+            max_c = max_c.max(c);
+            min_r = min_r.min(r);
+            max_r = max_r.max(r);
+            for nr in r.saturating_sub(1)..=(r + 1).min(rows - 1) {
+                for nc in c.saturating_sub(1)..=(c + 1).min(cols - 1) {
+                    let j = nr * cols + nc;
+                    if cells[j] && !seen[j] {
+                        seen[j] = true;
+                        stack.push(j);
+                    }
+                }
+            }
+        }
+        let x = min_c as u32 * REGION_CELL;
+        let y = min_r as u32 * REGION_CELL;
+        regions.push(DiffRegion {
+            x,
+            y,
+            width: ((max_c as u32 + 1) * REGION_CELL).min(width) - x,
+            height: ((max_r as u32 + 1) * REGION_CELL).min(height) - y,
+        });
+    }
+    regions
+}
+
+fn outline(img: &mut RgbaImage, region: &DiffRegion) {
+    let x1 = region.x + region.width - 1;
+    let y1 = region.y + region.height - 1;
+    for x in region.x..=x1 {
+        img.put_pixel(x, region.y, HIGHLIGHT);
+        img.put_pixel(x, y1, HIGHLIGHT);
+    }
+    for y in region.y..=y1 {
+        img.put_pixel(region.x, y, HIGHLIGHT);
+        img.put_pixel(x1, y, HIGHLIGHT);
+    }
+}
+
+fn load_rgba(path: &Path) -> anyhow::Result<RgbaImage> {
+    Ok(image::open(path)
+        .with_context(|| format!("failed to read image {}", path.display()))?
+        .to_rgba8())
+}
+
+fn with_suffix(stem: &Path, suffix: &str) -> PathBuf {
+    let mut s = stem.as_os_str().to_os_string();
+    s.push(suffix);
+    PathBuf::from(s)
+}
+
+/// Keeps baseline names usable as a single directory component.
+fn sanitize(name: &str) -> String {
+    let s: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if s.is_empty() {
+        "default".to_string()
+    } else {
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, color: [u8; 4]) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba(color))
+    }
+
+    #[test]
+    fn reports_changed_regions_and_similarity() {
+        let baseline = solid(64, 64, [255, 255, 255, 255]);
+        let mut actual = baseline.clone();
+        // Two separate changes plus sub-tolerance noise.
+        for y in 4..12 {
+            for x in 4..20 {
+                actual.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        actual.put_pixel(60, 60, Rgba([0, 0, 255, 255]));
+        actual.put_pixel(30, 30, Rgba([250, 250, 250, 255]));
+
+        let (diff, stats) = diff_images(&baseline, &actual);
+        assert_eq!(stats.changed_pixels, 8 * 16 + 1);
+        assert_eq!(stats.total_pixels, 64 * 64);
+        assert_eq!(
+            stats.regions,
+            vec![
+                DiffRegion {
+                    x: 0,
+                    y: 0,
+                    width: 24,
+                    height: 16
+                },
+                DiffRegion {
+                    x: 56,
+                    y: 56,
+                    width: 8,
+                    height: 8
+                },
+            ]
+        );
+        assert_eq!(diff.get_pixel(10, 8), &HIGHLIGHT);
+        assert_ne!(diff.get_pixel(30, 30), &HIGHLIGHT);
+
+        let (_, same) = diff_images(&baseline, &baseline);
+        assert_eq!((same.changed_pixels, same.similarity), (0, 1.0));
+    }
+
+    #[test]
+    fn counts_pixels_outside_smaller_image_as_changed() {
+        let baseline = solid(10, 10, [0, 0, 0, 255]);
+        let actual = solid(10, 12, [0, 0, 0, 255]);
+        let (diff, stats) = diff_images(&baseline, &actual);
+        assert_eq!(diff.dimensions(), (10, 12));
+        assert_eq!(stats.changed_pixels, 20);
+    }
+
+    #[expect(clippy::unwrap_used)]
+    #[test]
+    fn saves_compares_and_accepts_baselines() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let store = BaselineStore::for_workspace(tmp.path());
+        let key = BaselineKey {
+            name: "home page".to_string(),
+            url: "http://localhost:3000/".to_string(),
+            width: 32,
+            height: 32,
+        };
+        let shot = tmp.path().join("shot.png");
+        solid(32, 32, [255, 255, 255, 255]).save(&shot).unwrap();
+
+        assert!(store.compare(&key, &shot, 1.0).unwrap().is_none());
+        store.save(&key, &shot).unwrap();
+        let same = store.compare(&key, &shot, 1.0).unwrap().unwrap();
+        assert!(same.passed && same.diff_path.is_none());
+
+        let changed = tmp.path().join("changed.png");
+        solid(32, 32, [0, 0, 0, 255]).save(&changed).unwrap();
+        let result = store.compare(&key, &changed, 1.0).unwrap().unwrap();
+        assert!(!result.passed);
+        assert!(result.diff_path.unwrap().exists());
+        assert!(store.list(None).unwrap()[0].pending);
+
+        let accepted = store.accept(Some("home page")).unwrap();
+        assert_eq!(accepted.len(), 1);
+        assert!(store.compare(&key, &changed, 1.0).unwrap().unwrap().passed);
+
+        assert_eq!(store.reset(None).unwrap(), 1);
+        assert!(store.list(None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+#![cfg(unix)]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use codex_core::background_process::BackgroundProcessManager;
+use codex_core::background_process::ProcessState;
+use codex_core::exec::SandboxType;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::SandboxPolicy;
+use tempfile::TempDir;
+
+fn test_env() -> HashMap<String, String> {
+    std::env::vars().filter(|(k, _)| k == "PATH").collect()
+}
+
+#[expect(clippy::expect_used, clippy::unwrap_used)]
+#[tokio::test]
+async fn wait_for_pattern_then_stop() {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let (tx_event, rx_event) = async_channel::unbounded();
+    let manager = Arc::new(BackgroundProcessManager::new(tx_event));
+
+    let process = manager
+        .start(
+            "echo booting; sleep 0.2; echo 'listening on :4321'; sleep 30".to_string(),
+            tmp.path().to_path_buf(),
+            test_env(),
+            SandboxType::None,
+            &SandboxPolicy::DangerFullAccess,
+            &None,
+            "sub".to_string(),
+            None,
+        )
+        .await
+        .expect("start background process");
+    assert!(process.is_running());
+
+    let pattern = regex_lite::Regex::new(r"listening on :(\d+)").unwrap();
+    let line = process
+        .wait_for_pattern(&pattern, 0, Duration::from_secs(10))
+        .await;
+    assert_eq!(line.as_deref(), Some("listening on :4321"));
+    // Login shells may print profile noise first; only check our own lines.
+    assert!(process.tail(10).ends_with("booting\nlistening on :4321"));
+
+    let stopped = manager.stop(&process.id).await.expect("known process");
+    assert_eq!(stopped.state(), ProcessState::Stopped);
+    assert!(manager.get(&process.id).is_none());
+
+    let event = tokio::time::timeout(Duration::from_secs(5), rx_event.recv())
+        .await
+        .expect("update event")
+        .unwrap();
+    assert!(matches!(event.msg, EventMsg::BackgroundProcessUpdate(_)));
+}
+
+#[expect(clippy::expect_used)]
+#[tokio::test]
+async fn exit_code_is_recorded() {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let (tx_event, _rx_event) = async_channel::unbounded();
+    let manager = Arc::new(BackgroundProcessManager::new(tx_event));
+
+    let process = manager
+        .start(
+            "echo oops >&2; exit 3".to_string(),
+            tmp.path().to_path_buf(),
+            test_env(),
+            SandboxType::None,
+            &SandboxPolicy::DangerFullAccess,
+            &None,
+            "sub".to_string(),
+            None,
+        )
+        .await
+        .expect("start background process");
+
+    // Waiting on a port that never opens returns early once the process exits.
+    assert!(
+        !process
+            .wait_for_port("127.0.0.1", 1, Duration::from_secs(10))
+            .await
+    );
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while process.is_running() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("process should exit");
+    assert_eq!(process.state(), ProcessState::Exited(3));
+    assert!(process.tail(10).ends_with("[stderr] oops"));
+}
@@ -0,0 +1,207 @@
+#![cfg(target_os = "linux")]
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::exec::ExecParams;
+use codex_core::exec::SandboxType;
+use codex_core::exec::process_exec_tool_call;
+use codex_core::exec_snapshot;
+use codex_core::exec_snapshot::ExecSnapshot;
+use codex_core::exec_snapshot::Layer;
+use codex_core::exec_snapshot::OverlayMount;
+use codex_core::exec_snapshot::SnapshotChange;
+use codex_core::protocol::SandboxPolicy;
+use tempfile::TempDir;
+
+struct Fixture {
+    _tmp: TempDir,
+    root: PathBuf,
+    layers: PathBuf,
+}
+
+/// Returns `None` when this system cannot mount an overlay in a user
+/// namespace, in which case the test is skipped.
+#[expect(clippy::expect_used)]
+fn fixture() -> Option<Fixture> {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let root = tmp.path().join("root");
+    let layers = tmp.path().join("layers");
+    std::fs::create_dir(&root).expect("create root");
+    if let Err(e) = exec_snapshot::overlay_supported(&layers) {
+        eprintln!("overlayfs in a user namespace is not available, skipping test: {e}");
+        return None;
+    }
+    std::fs::write(root.join("keep.txt"), "keep\n").expect("write");
+    std::fs::write(root.join("edit.txt"), "before\n").expect("write");
+    std::fs::write(root.join("gone.txt"), "gone\n").expect("write");
+    Some(Fixture {
+        _tmp: tmp,
+        root,
+        layers,
+    })
+}
+
+/// Runs `script` on a fresh layer stacked on `lower` and returns what it
+/// changed.
+#[expect(clippy::expect_used)]
+async fn run(
+    fixture: &Fixture,
+    lower: &[&ExecSnapshot],
+    id: &str,
+    script: &str,
+) -> Option<ExecSnapshot> {
+    let lower = lower.iter().rev().map(|s| s.layer.clone()).collect();
+    let layer = Layer::new(&fixture.layers).expect("create layer");
+    let overlay = OverlayMount::new(fixture.root.clone(), lower, layer);
+    let params = ExecParams {
+        command: vec!["/bin/sh".to_string(), "-c".to_string(), script.to_string()],
+        cwd: fixture.root.clone(),
+        timeout_ms: Some(10_000),
+        env: std::env::vars().filter(|(k, _)| k == "PATH").collect(),
+        with_escalated_permissions: None,
+        justification: None,
+        overlay: Some(overlay.clone()),
+    };
+    let output = process_exec_tool_call(
+        params,
+        SandboxType::None,
+        &SandboxPolicy::DangerFullAccess,
+        &None,
+        None,
+    )
+    .await
+    .expect("run command");
+    assert_eq!(output.exit_code, 0, "{}", output.stderr.text);
+    ExecSnapshot::from_mount(id.to_string(), "call".to_string(), Vec::new(), &overlay)
+        .expect("read layer")
+}
+
+fn read(root: &Path, name: &str) -> Option<String> {
+    std::fs::read_to_string(root.join(name)).ok()
+}
+
+#[expect(clippy::expect_used, clippy::unwrap_used)]
+#[tokio::test]
+async fn writes_land_in_the_layer_until_committed() {
+    let Some(fixture) = fixture() else {
+        return;
+    };
+    let root = &fixture.root;
+
+    let snapshot = run(
+        &fixture,
+        &[],
+        "snap-1",
+        "echo after > edit.txt; rm gone.txt; mkdir dir; echo new > dir/new.txt",
+    )
+    .await
+    .expect("changes");
+    assert_eq!(
+        snapshot.changes,
+        vec![
+            (SnapshotChange::Added, PathBuf::from("dir/new.txt")),
+            (SnapshotChange::Modified, PathBuf::from("edit.txt")),
+            (SnapshotChange::Deleted, PathBuf::from("gone.txt")),
+        ]
+    );
+    assert!(snapshot.unified_diff.contains("+after"));
+
+    // The real tree is untouched until the snapshot is committed.
+    assert_eq!(read(root, "edit.txt").as_deref(), Some("before\n"));
+    assert_eq!(read(root, "gone.txt").as_deref(), Some("gone\n"));
+    assert!(!root.join("dir").exists());
+
+    snapshot.commit().unwrap();
+    assert_eq!(read(root, "edit.txt").as_deref(), Some("after\n"));
+    assert_eq!(read(root, "gone.txt"), None);
+    assert_eq!(read(root, "dir/new.txt").as_deref(), Some("new\n"));
+    assert_eq!(read(root, "keep.txt").as_deref(), Some("keep\n"));
+}
+
+#[tokio::test]
+async fn commands_that_change_nothing_leave_no_snapshot() {
+    let Some(fixture) = fixture() else {
+        return;
+    };
+    // Opening a file for writing copies it up even though it stays the same.
+    let snapshot = run(&fixture, &[], "snap-1", ": >> keep.txt").await;
+    assert!(snapshot.is_none());
+}
+
+#[expect(clippy::expect_used, clippy::unwrap_used)]
+#[tokio::test]
+async fn pending_layers_are_visible_to_later_commands() {
+    let Some(fixture) = fixture() else {
+        return;
+    };
+    let root = &fixture.root;
+
+    let first = run(&fixture, &[], "snap-1", "echo first > edit.txt")
+        .await
+        .expect("changes");
+    let second = run(
+        &fixture,
+        &[&first],
+        "snap-2",
+        "cat edit.txt > copy.txt; rm keep.txt",
+    )
+    .await
+    .expect("changes");
+    assert_eq!(
+        second.changes,
+        vec![
+            (SnapshotChange::Added, PathBuf::from("copy.txt")),
+            (SnapshotChange::Deleted, PathBuf::from("keep.txt")),
+        ]
+    );
+
+    first.commit().unwrap();
+    let mut second = second;
+    second.refresh_stamps(&first);
+    second.commit().unwrap();
+    assert_eq!(read(root, "edit.txt").as_deref(), Some("first\n"));
+    assert_eq!(read(root, "copy.txt").as_deref(), Some("first\n"));
+    assert_eq!(read(root, "keep.txt"), None);
+}
+
+#[expect(clippy::expect_used, clippy::unwrap_used)]
+#[tokio::test]
+async fn commit_refuses_when_files_changed_outside_the_snapshot() {
+    let Some(fixture) = fixture() else {
+        return;
+    };
+    let root = &fixture.root;
+
+    let snapshot = run(&fixture, &[], "snap-1", "echo after > edit.txt")
+        .await
+        .expect("changes");
+    std::fs::write(root.join("edit.txt"), "edited by hand\n").unwrap();
+
+    let err = snapshot.commit().unwrap_err();
+    assert!(err.to_string().contains("edit.txt"), "{err}");
+    assert_eq!(read(root, "edit.txt").as_deref(), Some("edited by hand\n"));
+}
+
+#[expect(clippy::expect_used, clippy::unwrap_used)]
+#[tokio::test]
+async fn rolled_back_layers_are_removed() {
+    let Some(fixture) = fixture() else {
+        return;
+    };
+
+    let snapshot = run(&fixture, &[], "snap-1", "echo after > edit.txt")
+        .await
+        .expect("changes");
+    let upper = snapshot.layer.upper();
+    assert!(upper.join("edit.txt").exists());
+
+    // Rolling back is dropping the last reference to the layer.
+    let layer = Arc::downgrade(&snapshot.layer);
+    drop(snapshot);
+    assert!(layer.upgrade().is_none());
+    assert!(!upper.exists());
+    assert_eq!(read(&fixture.root, "edit.txt").as_deref(), Some("before\n"));
+    assert!(std::fs::read_dir(&fixture.layers).unwrap().next().is_none());
+}
@@ -53,6 +53,7 @@ async fn test_exec_stdout_stream_events_echo() {
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        overlay: None,
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
@@ -103,6 +104,7 @@ async fn test_exec_stderr_stream_events_echo() {
         env: HashMap::new(),
         with_escalated_permissions: None,
         justification: None,
+        overlay: None,
     };
 
     let policy = SandboxPolicy::new_read_only_policy();
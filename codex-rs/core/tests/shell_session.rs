@@ -0,0 +1,102 @@
+#![cfg(unix)]
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use codex_core::exec::SandboxType;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::shell_session::ShellSessionManager;
+use tempfile::TempDir;
+
+#[expect(clippy::expect_used, clippy::unwrap_used)]
+#[tokio::test]
+async fn state_persists_between_commands() {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let sub = tmp.path().join("sub");
+    std::fs::create_dir(&sub).unwrap();
+
+    let env: HashMap<String, String> = std::env::vars()
+        .filter(|(k, _)| k == "PATH" || k == "HOME")
+        .collect();
+    let manager = ShellSessionManager::default();
+    let session = manager
+        .open(
+            tmp.path().to_path_buf(),
+            env,
+            SandboxType::None,
+            &SandboxPolicy::DangerFullAccess,
+            &None,
+            None,
+        )
+        .await
+        .expect("open shell session");
+
+    let timeout = Duration::from_secs(10);
+    let out = session
+        .exec("cd sub && export GREETING=hi", timeout, None)
+        .await
+        .unwrap();
+    assert_eq!(out.exit_code, Some(0));
+
+    let out = session
+        .exec("pwd; echo \"$GREETING!\"", timeout, None)
+        .await
+        .unwrap();
+    assert_eq!(out.exit_code, Some(0));
+    let canonical = sub.canonicalize().unwrap();
+    assert_eq!(
+        out.output,
+        format!("{}\nhi!\n", canonical.display()),
+        "unexpected output: {:?}",
+        out.output
+    );
+
+    let out = session.exec("false", timeout, None).await.unwrap();
+    assert_eq!(out.exit_code, Some(1));
+
+    assert!(manager.close(&session.id).await.is_some());
+    assert!(manager.get(&session.id).await.is_none());
+}
+
+#[expect(clippy::expect_used, clippy::unwrap_used)]
+#[tokio::test]
+async fn long_running_command_can_be_read_later() {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let env: HashMap<String, String> = std::env::vars().filter(|(k, _)| k == "PATH").collect();
+    let manager = ShellSessionManager::default();
+    let session = manager
+        .open(
+            tmp.path().to_path_buf(),
+            env,
+            SandboxType::None,
+            &SandboxPolicy::DangerFullAccess,
+            &None,
+            None,
+        )
+        .await
+        .expect("open shell session");
+
+    let out = session
+        .exec(
+            "echo start; sleep 1; echo done",
+            Duration::from_millis(200),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(out.exit_code, None);
+    assert!(session.is_running());
+
+    // A second command is refused while the first is still running.
+    assert!(
+        session
+            .exec("true", Duration::from_millis(100), None)
+            .await
+            .is_err()
+    );
+
+    let out = session.read(Duration::from_secs(10), None).await.unwrap();
+    assert_eq!(out.exit_code, Some(0));
+    assert!(out.output.contains("done"), "output: {:?}", out.output);
+    assert!(!session.is_running());
+}
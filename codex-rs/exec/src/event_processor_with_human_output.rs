@@ -331,6 +331,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     format_mcp_invocation(&invocation).style(self.bold),
                 );
             }
+            EventMsg::McpToolCallProgress(_) => {}
             EventMsg::McpToolCallEnd(tool_call_end_event) => {
                 let is_success = tool_call_end_event.is_success();
                 let McpToolCallEndEvent {
@@ -532,6 +533,34 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::AgentStatusUpdate(_) => {
                 // Currently ignored in exec output.
             }
+            EventMsg::BackgroundProcessUpdate(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::ExecSnapshot(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::McpPrompts(_) | EventMsg::McpListServersResponse(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::McpElicitationRequest(request) => {
+                // Declined by the main loop; set `mcp_requests.elicitation` to
+                // answer differently.
+                ts_println!(
+                    self,
+                    "{} {}",
+                    format!("declined input request from {}:", request.server).style(self.dimmed),
+                    request.message
+                );
+            }
+            EventMsg::McpSamplingRequest(request) => {
+                // Denied by the main loop; set `mcp_requests.sampling =
+                // "approve"` to let servers sample.
+                ts_println!(
+                    self,
+                    "{}",
+                    format!("denied sampling request from {}", request.server).style(self.dimmed)
+                );
+            }
             EventMsg::ShutdownComplete => return CodexStatus::Shutdown,
             EventMsg::CustomToolCallBegin(event) => {
                 ts_println!(
@@ -14,10 +14,12 @@ use codex_core::NewConversation;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 use codex_core::protocol::AskForApproval;
+use codex_core::protocol::ElicitationAction;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::util::is_inside_git_repo;
 use codex_ollama::DEFAULT_OSS_MODEL;
@@ -262,6 +264,30 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
 
     // Run the loop until the task is complete.
     while let Some(event) = rx.recv().await {
+        // Nobody can answer MCP server requests here. The `mcp_requests`
+        // config answers them in core; what is left to ask about is refused.
+        match &event.msg {
+            EventMsg::McpElicitationRequest(request) => {
+                conversation
+                    .submit(Op::McpElicitationResponse {
+                        server: request.server.clone(),
+                        request_id: request.request_id.clone(),
+                        action: ElicitationAction::Decline,
+                        content: None,
+                    })
+                    .await?;
+            }
+            EventMsg::McpSamplingRequest(request) => {
+                conversation
+                    .submit(Op::McpSamplingApproval {
+                        server: request.server.clone(),
+                        request_id: request.request_id.clone(),
+                        decision: ReviewDecision::Denied,
+                    })
+                    .await?;
+            }
+            _ => {}
+        }
         let shutdown: CodexStatus = event_processor.process_event(event);
         match shutdown {
             CodexStatus::Running => continue,
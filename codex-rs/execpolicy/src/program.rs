@@ -134,6 +134,18 @@ impl ProgramSpec {
                     }
                     None => {
                         // It could be an --option=value style flag...
+                        if let Some((name, value)) =
+                            split_combined_option(arg).filter(|_| self.combined_format)
+                        {
+                            matched_opts.push(self.check_combined_option(name, value)?);
+                            continue;
+                        }
+                        // ...or several short options bundled together.
+                        if self.option_bundling && is_bundle(arg) {
+                            expecting_option_value =
+                                self.check_bundle(arg, &mut matched_flags, &mut matched_opts)?;
+                            continue;
+                        }
                     }
                 }
 
@@ -194,6 +206,55 @@ impl ProgramSpec {
         }
     }
 
+    /// Matches `--name=value` against an option declared with `opt()`.
+    fn check_combined_option(&self, name: &str, value: &str) -> Result<MatchedOpt> {
+        match self.allowed_options.get(name).map(|opt| &opt.meta) {
+            Some(OptMeta::Value(arg_type)) => MatchedOpt::new(name, value, arg_type.clone()),
+            Some(OptMeta::Flag) => Err(Error::FlagDoesNotTakeValue {
+                program: self.program.clone(),
+                flag: name.to_string(),
+                value: value.to_string(),
+            }),
+            None => Err(Error::UnknownOption {
+                program: self.program.clone(),
+                option: name.to_string(),
+            }),
+        }
+    }
+
+    /// Expands a bundle such as `-al` or `-n5` the way `getopt(3)` does: each
+    /// character is a short option, and the first one that takes a value
+    /// consumes the rest of the bundle, or the next argument if nothing is
+    /// left. Returns the option still waiting for its value, if any.
+    fn check_bundle(
+        &self,
+        arg: &str,
+        matched_flags: &mut Vec<MatchedFlag>,
+        matched_opts: &mut Vec<MatchedOpt>,
+    ) -> Result<Option<(String, ArgType)>> {
+        for (offset, c) in arg.char_indices().skip(1) {
+            let name = format!("-{c}");
+            match self.allowed_options.get(&name).map(|opt| &opt.meta) {
+                Some(OptMeta::Flag) => matched_flags.push(MatchedFlag { name }),
+                Some(OptMeta::Value(arg_type)) => {
+                    let value = &arg[offset + c.len_utf8()..];
+                    if value.is_empty() {
+                        return Ok(Some((name, arg_type.clone())));
+                    }
+                    matched_opts.push(MatchedOpt::new(&name, value, arg_type.clone())?);
+                    return Ok(None);
+                }
+                None => {
+                    return Err(Error::UnknownOption {
+                        program: self.program.clone(),
+                        option: name,
+                    });
+                }
+            }
+        }
+        Ok(None)
+    }
+
     pub fn verify_should_match_list(&self) -> Vec<PositiveExampleFailedCheck> {
         let mut violations = Vec::new();
         for good in &self.should_match {
@@ -233,6 +294,20 @@ impl ProgramSpec {
     }
 }
 
+/// Splits `--name=value` into its parts. Only long options use this form.
+fn split_combined_option(arg: &str) -> Option<(&str, &str)> {
+    if !arg.starts_with("--") {
+        return None;
+    }
+    arg.split_once('=')
+}
+
+/// Whether `arg` looks like bundled short options, e.g. `-al` but not `-a`,
+/// `--all` or a lone `-`.
+fn is_bundle(arg: &str) -> bool {
+    !arg.starts_with("--") && arg.chars().count() > 2
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct PositiveExampleFailedCheck {
     pub program: String,
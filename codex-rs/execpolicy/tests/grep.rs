@@ -0,0 +1,110 @@
+extern crate codex_execpolicy;
+
+use codex_execpolicy::ArgMatcher;
+use codex_execpolicy::ArgType;
+use codex_execpolicy::Error;
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::MatchedArg;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::MatchedFlag;
+use codex_execpolicy::MatchedOpt;
+use codex_execpolicy::Policy;
+use codex_execpolicy::Result;
+use codex_execpolicy::ValidExec;
+use codex_execpolicy::get_default_policy;
+
+#[expect(clippy::expect_used)]
+fn setup() -> Policy {
+    get_default_policy().expect("failed to load default policy")
+}
+
+fn grep_system_path() -> Vec<String> {
+    vec!["/bin/grep".to_string(), "/usr/bin/grep".to_string()]
+}
+
+#[test]
+fn test_grep_bundled_flags() -> Result<()> {
+    let policy = setup();
+    let grep = ExecCall::new("grep", &["-rn", "TODO", "src"]);
+    assert_eq!(
+        Ok(MatchedExec::Match {
+            exec: ValidExec {
+                program: "grep".to_string(),
+                flags: vec![MatchedFlag::new("-r"), MatchedFlag::new("-n")],
+                opts: vec![],
+                args: vec![
+                    MatchedArg::new(1, ArgType::OpaqueNonFile, "TODO")?,
+                    MatchedArg::new(2, ArgType::ReadableFile, "src")?,
+                ],
+                system_path: grep_system_path(),
+            }
+        }),
+        policy.check(&grep)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_grep_bundle_ending_in_option_takes_next_arg() -> Result<()> {
+    let policy = setup();
+    let grep = ExecCall::new("grep", &["-nC", "3", "--include=*.rs", "fn main", "."]);
+    assert_eq!(
+        Ok(MatchedExec::Match {
+            exec: ValidExec {
+                program: "grep".to_string(),
+                flags: vec![MatchedFlag::new("-n")],
+                opts: vec![
+                    MatchedOpt::new("-C", "3", ArgType::PositiveInteger)?,
+                    MatchedOpt::new("--include", "*.rs", ArgType::OpaqueNonFile)?,
+                ],
+                args: vec![
+                    MatchedArg::new(3, ArgType::OpaqueNonFile, "fn main")?,
+                    MatchedArg::new(4, ArgType::ReadableFile, ".")?,
+                ],
+                system_path: grep_system_path(),
+            }
+        }),
+        policy.check(&grep)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_grep_flag_does_not_take_value() {
+    let policy = setup();
+    let grep = ExecCall::new("grep", &["--recursive=yes", "TODO", "src"]);
+    assert_eq!(
+        Err(Error::FlagDoesNotTakeValue {
+            program: "grep".to_string(),
+            flag: "--recursive".to_string(),
+            value: "yes".to_string(),
+        }),
+        policy.check(&grep)
+    );
+}
+
+#[test]
+fn test_grep_equals_is_not_an_option_inside_a_bundle() {
+    let policy = setup();
+    let grep = ExecCall::new("grep", &["-r=1", "TODO", "src"]);
+    assert_eq!(
+        Err(Error::UnknownOption {
+            program: "grep".to_string(),
+            option: "-=".to_string(),
+        }),
+        policy.check(&grep)
+    );
+}
+
+#[test]
+fn test_grep_requires_a_file() {
+    let policy = setup();
+    let grep = ExecCall::new("grep", &["-rn", "TODO"]);
+    assert_eq!(
+        Err(Error::VarargMatcherDidNotMatchAnything {
+            program: "grep".to_string(),
+            matcher: ArgMatcher::ReadableFiles,
+        }),
+        policy.check(&grep)
+    );
+}
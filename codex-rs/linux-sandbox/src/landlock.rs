@@ -35,14 +35,7 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
         install_network_seccomp_filter_on_current_thread()?;
     }
 
-    if !sandbox_policy.has_full_disk_write_access() {
-        let writable_roots = sandbox_policy
-            .get_writable_roots_with_cwd(cwd)
-            .into_iter()
-            .map(|writable_root| writable_root.root)
-            .collect();
-        install_filesystem_landlock_rules_on_current_thread(writable_roots)?;
-    }
+    apply_filesystem_policy_to_current_thread(sandbox_policy, cwd)?;
 
     // TODO(ragona): Add appropriate restrictions if
     // `sandbox_policy.has_full_disk_read_access()` is `false`.
@@ -50,6 +43,49 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
     Ok(())
 }
 
+/// The Landlock half of [`apply_sandbox_policy_to_current_thread`].
+pub(crate) fn apply_filesystem_policy_to_current_thread(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+) -> Result<()> {
+    if !sandbox_policy.has_full_disk_write_access() {
+        install_filesystem_landlock_rules_on_current_thread(writable_roots(sandbox_policy, cwd))?;
+    }
+    Ok(())
+}
+
+/// Directories Landlock lets the command write to, besides `/dev/null`.
+pub(crate) fn writable_roots(sandbox_policy: &SandboxPolicy, cwd: &Path) -> Vec<PathBuf> {
+    sandbox_policy
+        .get_writable_roots_with_cwd(cwd)
+        .into_iter()
+        .map(|writable_root| writable_root.root)
+        .collect()
+}
+
+/// Network syscalls the seccomp filter denies outright. `socket` and
+/// `socketpair` are only denied for domains other than `AF_UNIX`.
+pub(crate) const DENIED_NETWORK_SYSCALLS: &[(i64, &str)] = &[
+    (libc::SYS_connect, "connect"),
+    (libc::SYS_accept, "accept"),
+    (libc::SYS_accept4, "accept4"),
+    (libc::SYS_bind, "bind"),
+    (libc::SYS_listen, "listen"),
+    (libc::SYS_getpeername, "getpeername"),
+    (libc::SYS_getsockname, "getsockname"),
+    (libc::SYS_shutdown, "shutdown"),
+    (libc::SYS_sendto, "sendto"),
+    (libc::SYS_sendmsg, "sendmsg"),
+    (libc::SYS_sendmmsg, "sendmmsg"),
+    // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
+    // with their socketpair + child processes for sub-proc management
+    // (libc::SYS_recvfrom, "recvfrom"),
+    (libc::SYS_recvmsg, "recvmsg"),
+    (libc::SYS_recvmmsg, "recvmmsg"),
+    (libc::SYS_getsockopt, "getsockopt"),
+    (libc::SYS_setsockopt, "setsockopt"),
+];
+
 /// Installs Landlock file-system rules on the current thread allowing read
 /// access to the entire file-system while restricting write access to
 /// `/dev/null` and the provided list of `writable_roots`.
@@ -84,7 +120,8 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
 
 /// Installs a seccomp filter that blocks outbound network access except for
 /// AF_UNIX domain sockets.
-fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
+pub(crate) fn install_network_seccomp_filter_on_current_thread()
+-> std::result::Result<(), SandboxErr> {
     // Build rule map.
     let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
 
@@ -93,24 +130,9 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         rules.insert(nr, vec![]); // empty rule vec = unconditional match
     };
 
-    deny_syscall(libc::SYS_connect);
-    deny_syscall(libc::SYS_accept);
-    deny_syscall(libc::SYS_accept4);
-    deny_syscall(libc::SYS_bind);
-    deny_syscall(libc::SYS_listen);
-    deny_syscall(libc::SYS_getpeername);
-    deny_syscall(libc::SYS_getsockname);
-    deny_syscall(libc::SYS_shutdown);
-    deny_syscall(libc::SYS_sendto);
-    deny_syscall(libc::SYS_sendmsg);
-    deny_syscall(libc::SYS_sendmmsg);
-    // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
-    // with their socketpair + child processes for sub-proc management
-    // deny_syscall(libc::SYS_recvfrom);
-    deny_syscall(libc::SYS_recvmsg);
-    deny_syscall(libc::SYS_recvmmsg);
-    deny_syscall(libc::SYS_getsockopt);
-    deny_syscall(libc::SYS_setsockopt);
+    for (nr, _) in DENIED_NETWORK_SYSCALLS {
+        deny_syscall(*nr);
+    }
     deny_syscall(libc::SYS_ptrace);
 
     // For `socket` we allow AF_UNIX (arg0 == AF_UNIX) and deny everything else.
@@ -2,6 +2,8 @@
 mod landlock;
 #[cfg(target_os = "linux")]
 mod linux_run_main;
+#[cfg(target_os = "linux")]
+mod supervisor;
 
 #[cfg(target_os = "linux")]
 pub fn run_main() -> ! {
@@ -3,9 +3,16 @@ use std::ffi::CString;
 use std::path::PathBuf;
 
 use crate::landlock::apply_sandbox_policy_to_current_thread;
+use crate::supervisor::run_supervised;
 
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
+    /// Append what the sandbox blocked to this file, one JSON object per
+    /// line. The file is unlinked once opened, so the caller must keep its
+    /// own handle to read it back. Without it the command is exec'd directly.
+    #[arg(long)]
+    pub denial_log: Option<PathBuf>,
+
     /// It is possible that the cwd used in the context of the sandbox policy
     /// is different from the cwd of the process to spawn.
     pub sandbox_policy_cwd: PathBuf,
@@ -19,19 +26,31 @@ pub struct LandlockCommand {
 
 pub fn run_main() -> ! {
     let LandlockCommand {
+        denial_log,
         sandbox_policy_cwd,
         sandbox_policy,
         command,
     } = LandlockCommand::parse();
 
+    if command.is_empty() {
+        panic!("No command specified to execute.");
+    }
+
+    // Only returns when this system cannot supervise the command, in which
+    // case it runs without reporting denials.
+    if let Some(denial_log) = denial_log {
+        run_supervised(&sandbox_policy, &sandbox_policy_cwd, &denial_log, &command);
+    }
+
     if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd) {
         panic!("error running landlock: {e:?}");
     }
 
-    if command.is_empty() {
-        panic!("No command specified to execute.");
-    }
+    exec_command(&command)
+}
 
+/// Replaces the current process with `command`.
+pub(crate) fn exec_command(command: &[String]) -> ! {
     #[expect(clippy::expect_used)]
     let c_command =
         CString::new(command[0].as_str()).expect("Failed to convert command to CString");
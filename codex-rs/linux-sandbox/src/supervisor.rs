@@ -0,0 +1,698 @@
+//! Runs the command under a seccomp user-notification listener so the
+//! sandbox itself can report what it blocked.
+//!
+//! The helper forks the command with a shared file table. The child applies
+//! Landlock, installs a filter that hands network syscalls and write-like
+//! file syscalls to a listener, passes the listener fd back over a pipe and
+//! execs. The parent stays behind as the supervisor:
+//!
+//! - network syscalls are answered with `EPERM`, exactly like the plain
+//!   filter in [`crate::landlock`], and recorded;
+//! - file syscalls are allowed to continue so Landlock makes the decision,
+//!   and the ones whose target lies outside every writable root are recorded.
+//!
+//! Every denial is appended to the log as one JSON line that deserializes to
+//! [`SandboxDenial`]. The supervisor exits with the command's status. Any
+//! descendant that outlives the command loses the listener, and the kernel
+//! then fails its watched syscalls with `ENOSYS`. Long-running commands use
+//! background processes and shell sessions, which are never supervised.
+
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::fd::FromRawFd;
+use std::os::fd::OwnedFd;
+use std::os::fd::RawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::protocol::SandboxPolicy;
+use codex_core::sandbox_denial::SandboxDenial;
+use codex_core::sandbox_denial::SandboxOperation;
+
+use crate::landlock::DENIED_NETWORK_SYSCALLS;
+use crate::landlock::apply_filesystem_policy_to_current_thread;
+use crate::landlock::install_network_seccomp_filter_on_current_thread;
+use crate::landlock::writable_roots;
+use crate::linux_run_main::exec_command;
+
+// linux/seccomp.h ioctls, which libc does not export.
+const SECCOMP_IOCTL_NOTIF_RECV: u32 = 0xc050_2100;
+const SECCOMP_IOCTL_NOTIF_SEND: u32 = 0xc018_2101;
+// The original `_IOR` encoding, accepted by every kernel with a listener.
+const SECCOMP_IOCTL_NOTIF_ID_VALID: u32 = 0x8008_2102;
+
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e;
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: u32 = 0xc000_00b7;
+
+/// `SECCOMP_USER_NOTIF_FLAG_CONTINUE` was added in Linux 5.5.
+const MIN_KERNEL: (u32, u32) = (5, 5);
+
+const WRITE_OPEN_FLAGS: u32 =
+    (libc::O_WRONLY | libc::O_RDWR | libc::O_CREAT | libc::O_TRUNC) as u32;
+
+/// A file syscall that may modify the filesystem.
+struct WriteSyscall {
+    nr: i64,
+    name: &'static str,
+    /// Argument holding the open flags, for `open`-like syscalls.
+    flags: Option<usize>,
+    /// `(dirfd argument, path argument)` for every path the syscall writes.
+    paths: &'static [(Option<usize>, usize)],
+}
+
+const WRITE_SYSCALLS: &[WriteSyscall] = &[
+    WriteSyscall {
+        nr: libc::SYS_openat,
+        name: "openat",
+        flags: Some(2),
+        paths: &[(Some(0), 1)],
+    },
+    WriteSyscall {
+        nr: libc::SYS_mkdirat,
+        name: "mkdirat",
+        flags: None,
+        paths: &[(Some(0), 1)],
+    },
+    WriteSyscall {
+        nr: libc::SYS_mknodat,
+        name: "mknodat",
+        flags: None,
+        paths: &[(Some(0), 1)],
+    },
+    WriteSyscall {
+        nr: libc::SYS_unlinkat,
+        name: "unlinkat",
+        flags: None,
+        paths: &[(Some(0), 1)],
+    },
+    WriteSyscall {
+        nr: libc::SYS_renameat,
+        name: "renameat",
+        flags: None,
+        paths: &[(Some(0), 1), (Some(2), 3)],
+    },
+    WriteSyscall {
+        nr: libc::SYS_renameat2,
+        name: "renameat2",
+        flags: None,
+        paths: &[(Some(0), 1), (Some(2), 3)],
+    },
+    WriteSyscall {
+        nr: libc::SYS_linkat,
+        name: "linkat",
+        flags: None,
+        paths: &[(Some(2), 3)],
+    },
+    WriteSyscall {
+        nr: libc::SYS_symlinkat,
+        name: "symlinkat",
+        flags: None,
+        paths: &[(Some(1), 2)],
+    },
+    WriteSyscall {
+        nr: libc::SYS_truncate,
+        name: "truncate",
+        flags: None,
+        paths: &[(None, 0)],
+    },
+    #[cfg(target_arch = "x86_64")]
+    WriteSyscall {
+        nr: libc::SYS_open,
+        name: "open",
+        flags: Some(1),
+        paths: &[(None, 0)],
+    },
+    #[cfg(target_arch = "x86_64")]
+    WriteSyscall {
+        nr: libc::SYS_creat,
+        name: "creat",
+        flags: None,
+        paths: &[(None, 0)],
+    },
+    #[cfg(target_arch = "x86_64")]
+    WriteSyscall {
+        nr: libc::SYS_mkdir,
+        name: "mkdir",
+        flags: None,
+        paths: &[(None, 0)],
+    },
+    #[cfg(target_arch = "x86_64")]
+    WriteSyscall {
+        nr: libc::SYS_mknod,
+        name: "mknod",
+        flags: None,
+        paths: &[(None, 0)],
+    },
+    #[cfg(target_arch = "x86_64")]
+    WriteSyscall {
+        nr: libc::SYS_rmdir,
+        name: "rmdir",
+        flags: None,
+        paths: &[(None, 0)],
+    },
+    #[cfg(target_arch = "x86_64")]
+    WriteSyscall {
+        nr: libc::SYS_unlink,
+        name: "unlink",
+        flags: None,
+        paths: &[(None, 0)],
+    },
+    #[cfg(target_arch = "x86_64")]
+    WriteSyscall {
+        nr: libc::SYS_rename,
+        name: "rename",
+        flags: None,
+        paths: &[(None, 0), (None, 1)],
+    },
+    #[cfg(target_arch = "x86_64")]
+    WriteSyscall {
+        nr: libc::SYS_link,
+        name: "link",
+        flags: None,
+        paths: &[(None, 1)],
+    },
+    #[cfg(target_arch = "x86_64")]
+    WriteSyscall {
+        nr: libc::SYS_symlink,
+        name: "symlink",
+        flags: None,
+        paths: &[(None, 1)],
+    },
+];
+
+/// Runs `command` sandboxed by `sandbox_policy`, logging denials to
+/// `denial_log`, and exits with its status.
+///
+/// Only returns if supervision cannot be set up on this system, before the
+/// command has been started, so the caller can sandbox the command itself.
+pub(crate) fn run_supervised(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+    denial_log: &Path,
+    command: &[String],
+) {
+    let watch_network = !sandbox_policy.has_full_network_access();
+    let watch_writes = !sandbox_policy.has_full_disk_write_access();
+    if !(watch_network || watch_writes) || kernel_version().is_none_or(|v| v < MIN_KERNEL) {
+        return;
+    }
+    let Ok(log) = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(denial_log)
+    else {
+        return;
+    };
+    // Sandboxed commands may write to the directory holding the log; once
+    // it is unlinked they cannot forge entries. The caller reads it back
+    // through the handle it already holds.
+    let _ = std::fs::remove_file(denial_log);
+    let mut pipe: [RawFd; 2] = [-1; 2];
+    if unsafe { libc::pipe2(pipe.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return;
+    }
+    let filter = build_filter(watch_network, watch_writes);
+
+    // The helper is still single-threaded, so a raw clone that shares the
+    // file table is as safe as fork(2). Sharing the table is what lets the
+    // parent use the listener fd the child creates.
+    let pid = unsafe {
+        libc::syscall(
+            libc::SYS_clone,
+            libc::CLONE_FILES | libc::SIGCHLD,
+            0,
+            0,
+            0,
+            0,
+        )
+    };
+    match pid {
+        -1 => unsafe {
+            libc::close(pipe[0]);
+            libc::close(pipe[1]);
+        },
+        0 => run_child(
+            sandbox_policy,
+            cwd,
+            &filter,
+            watch_network,
+            pipe[1],
+            command,
+        ),
+        pid => {
+            let pid = pid as libc::pid_t;
+            let listener = receive_listener(pid, pipe[0]);
+            unsafe {
+                libc::close(pipe[0]);
+                libc::close(pipe[1]);
+            }
+            if let Some(listener) = listener {
+                let supervisor = Supervisor::new(sandbox_policy, cwd, log);
+                std::thread::spawn(move || supervisor.run(listener));
+            }
+            exit_like(wait_for(pid));
+        }
+    }
+}
+
+fn run_child(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+    filter: &[libc::sock_filter],
+    watch_network: bool,
+    pipe: RawFd,
+    command: &[String],
+) -> ! {
+    unsafe {
+        libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+        if libc::getppid() == 1 {
+            libc::_exit(1);
+        }
+    }
+
+    if let Err(e) = apply_filesystem_policy_to_current_thread(sandbox_policy, cwd) {
+        panic!("error running landlock: {e:?}");
+    }
+
+    let prog = libc::sock_fprog {
+        len: filter.len() as libc::c_ushort,
+        filter: filter.as_ptr().cast_mut(),
+    };
+    let listener = unsafe {
+        if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+            -1
+        } else {
+            libc::syscall(
+                libc::SYS_seccomp,
+                libc::SECCOMP_SET_MODE_FILTER,
+                libc::SECCOMP_FILTER_FLAG_NEW_LISTENER,
+                &prog as *const libc::sock_fprog,
+            ) as i32
+        }
+    };
+    // A listener cannot be created when an ancestor already installed one
+    // (e.g. when Codex runs inside another supervised sandbox); fall back to
+    // the plain filter and run without reporting.
+    if listener < 0
+        && watch_network
+        && let Err(e) = install_network_seccomp_filter_on_current_thread()
+    {
+        panic!("error installing seccomp filter: {e:?}");
+    }
+
+    let bytes = listener.to_ne_bytes();
+    unsafe {
+        libc::write(pipe, bytes.as_ptr().cast(), bytes.len());
+    }
+    exec_command(command)
+}
+
+/// Waits for the child to report its listener fd. Returns `None` when the
+/// child could not create one or exited before reporting.
+fn receive_listener(pid: libc::pid_t, pipe: RawFd) -> Option<OwnedFd> {
+    // The write end lives in the shared file table, so the pipe never
+    // reports EOF; poll for the child exiting instead.
+    loop {
+        let mut pfd = libc::pollfd {
+            fd: pipe,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        if unsafe { libc::poll(&mut pfd, 1, 100) } > 0 {
+            let mut bytes = [0u8; 4];
+            let n = unsafe { libc::read(pipe, bytes.as_mut_ptr().cast(), bytes.len()) };
+            let fd = i32::from_ne_bytes(bytes);
+            return (n == 4 && fd >= 0).then(|| unsafe { OwnedFd::from_raw_fd(fd) });
+        }
+        // Leave the child unreaped so `wait_for` still sees its status.
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        let rc = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOHANG | libc::WNOWAIT,
+            )
+        };
+        if rc != 0 || unsafe { info.si_pid() } != 0 {
+            return None;
+        }
+    }
+}
+
+fn wait_for(pid: libc::pid_t) -> libc::c_int {
+    let mut status = 0;
+    loop {
+        if unsafe { libc::waitpid(pid, &mut status, 0) } == pid {
+            return status;
+        }
+        if std::io::Error::last_os_error().raw_os_error() != Some(libc::EINTR) {
+            return 1 << 8;
+        }
+    }
+}
+
+/// Exits the way the command did, re-raising its terminating signal.
+fn exit_like(status: libc::c_int) -> ! {
+    if libc::WIFSIGNALED(status) {
+        let signal = libc::WTERMSIG(status);
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::kill(libc::getpid(), signal);
+        }
+        std::process::exit(128 + signal);
+    }
+    std::process::exit(libc::WEXITSTATUS(status));
+}
+
+fn kernel_version() -> Option<(u32, u32)> {
+    let mut uts: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return None;
+    }
+    let release: Vec<u8> = uts
+        .release
+        .iter()
+        .take_while(|c| **c != 0)
+        .map(|c| *c as u8)
+        .collect();
+    let release = String::from_utf8_lossy(&release);
+    let mut parts = release.split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+fn stmt(code: u32, k: u32) -> libc::sock_filter {
+    libc::sock_filter {
+        code: code as u16,
+        jt: 0,
+        jf: 0,
+        k,
+    }
+}
+
+fn jump(code: u32, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+    libc::sock_filter {
+        code: code as u16,
+        jt,
+        jf,
+        k,
+    }
+}
+
+/// Offset of the low 32 bits of syscall argument `index` in `seccomp_data`.
+fn arg_offset(index: usize) -> u32 {
+    16 + 8 * index as u32
+}
+
+/// Builds the BPF program: every watched syscall is handed to the listener,
+/// `ptrace` is denied as in the plain filter, everything else is allowed.
+fn build_filter(watch_network: bool, watch_writes: bool) -> Vec<libc::sock_filter> {
+    let load = libc::BPF_LD | libc::BPF_W | libc::BPF_ABS;
+    let ret = libc::BPF_RET | libc::BPF_K;
+    let notify = libc::SECCOMP_RET_USER_NOTIF;
+    let allow = libc::SECCOMP_RET_ALLOW;
+
+    let mut rules: Vec<(i64, Vec<libc::sock_filter>)> = Vec::new();
+    if watch_network {
+        for (nr, _) in DENIED_NETWORK_SYSCALLS {
+            rules.push((*nr, vec![stmt(ret, notify)]));
+        }
+        for nr in [libc::SYS_socket, libc::SYS_socketpair] {
+            rules.push((
+                nr,
+                vec![
+                    stmt(load, arg_offset(0)),
+                    jump(
+                        libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K,
+                        libc::AF_UNIX as u32,
+                        0,
+                        1,
+                    ),
+                    stmt(ret, allow),
+                    stmt(ret, notify),
+                ],
+            ));
+        }
+        rules.push((
+            libc::SYS_ptrace,
+            vec![stmt(ret, libc::SECCOMP_RET_ERRNO | libc::EPERM as u32)],
+        ));
+    }
+    if watch_writes {
+        for syscall in WRITE_SYSCALLS {
+            let body = match syscall.flags {
+                Some(index) => vec![
+                    stmt(load, arg_offset(index)),
+                    jump(
+                        libc::BPF_JMP | libc::BPF_JSET | libc::BPF_K,
+                        WRITE_OPEN_FLAGS,
+                        0,
+                        1,
+                    ),
+                    stmt(ret, notify),
+                    stmt(ret, allow),
+                ],
+                None => vec![stmt(ret, notify)],
+            };
+            rules.push((syscall.nr, body));
+        }
+    }
+
+    let mut program = vec![
+        stmt(load, 4), // seccomp_data.arch
+        jump(
+            libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K,
+            AUDIT_ARCH,
+            1,
+            0,
+        ),
+        stmt(ret, libc::SECCOMP_RET_KILL_PROCESS),
+        stmt(load, 0), // seccomp_data.nr
+    ];
+    for (nr, body) in rules {
+        program.push(jump(
+            libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K,
+            nr as u32,
+            0,
+            body.len() as u8,
+        ));
+        program.extend(body);
+    }
+    program.push(stmt(ret, allow));
+    program
+}
+
+struct Supervisor {
+    cwd: PathBuf,
+    writable_roots: Vec<PathBuf>,
+    log: File,
+    logged: HashSet<String>,
+}
+
+impl Supervisor {
+    fn new(sandbox_policy: &SandboxPolicy, cwd: &Path, log: File) -> Self {
+        // Landlock resolves symlinks when it opens the roots, so compare
+        // against their canonical form.
+        let writable_roots = writable_roots(sandbox_policy, cwd)
+            .into_iter()
+            .filter_map(|root| root.canonicalize().ok())
+            .collect();
+        Self {
+            cwd: cwd.to_path_buf(),
+            writable_roots,
+            log,
+            logged: HashSet::new(),
+        }
+    }
+
+    fn run(mut self, listener: OwnedFd) {
+        use std::os::fd::AsRawFd;
+
+        let fd = listener.as_raw_fd();
+        loop {
+            let mut req: libc::seccomp_notif = unsafe { std::mem::zeroed() };
+            if unsafe { libc::ioctl(fd, SECCOMP_IOCTL_NOTIF_RECV as _, &mut req) } != 0 {
+                match std::io::Error::last_os_error().raw_os_error() {
+                    // Interrupted, or the task died before we picked it up.
+                    Some(libc::EINTR) | Some(libc::ENOENT) => continue,
+                    _ => return,
+                }
+            }
+
+            let mut resp = libc::seccomp_notif_resp {
+                id: req.id,
+                val: 0,
+                error: 0,
+                flags: 0,
+            };
+            let nr = req.data.nr as i64;
+            if let Some(syscall) = WRITE_SYSCALLS.iter().find(|s| s.nr == nr) {
+                if let Some(denial) = self.check_write(fd, &req, syscall) {
+                    self.record(denial);
+                }
+                resp.flags = libc::SECCOMP_USER_NOTIF_FLAG_CONTINUE as u32;
+            } else {
+                let name = DENIED_NETWORK_SYSCALLS
+                    .iter()
+                    .find(|(n, _)| *n == nr)
+                    .map(|(_, name)| *name)
+                    .unwrap_or(if nr == libc::SYS_socketpair {
+                        "socketpair"
+                    } else {
+                        "socket"
+                    });
+                self.record(SandboxDenial {
+                    operation: SandboxOperation::Network,
+                    path: None,
+                    syscall: Some(name.to_string()),
+                    inferred: false,
+                });
+                resp.error = -libc::EPERM;
+            }
+            unsafe {
+                libc::ioctl(fd, SECCOMP_IOCTL_NOTIF_SEND as _, &mut resp);
+            }
+        }
+    }
+
+    /// Returns a denial when `req` writes outside every writable root.
+    fn check_write(
+        &self,
+        fd: RawFd,
+        req: &libc::seccomp_notif,
+        syscall: &WriteSyscall,
+    ) -> Option<SandboxDenial> {
+        let pid = req.pid as libc::pid_t;
+        let args = req.data.args;
+        for (dirfd, path) in syscall.paths {
+            let raw = read_c_string(pid, args[*path])?;
+            // The task may have died and its pid been reused while we read.
+            let id = req.id;
+            if unsafe { libc::ioctl(fd, SECCOMP_IOCTL_NOTIF_ID_VALID as _, &id) } != 0 {
+                return None;
+            }
+            let Some(path) = self.resolve(pid, dirfd.map(|i| args[i] as i32), &raw) else {
+                continue;
+            };
+            let only_opens = syscall.flags.is_some_and(|i| {
+                args[i] as u32 & (libc::O_WRONLY | libc::O_RDWR | libc::O_TRUNC) as u32 == 0
+            });
+            if only_opens && path.exists() {
+                continue;
+            }
+            if !self
+                .writable_roots
+                .iter()
+                .any(|root| path.starts_with(root))
+            {
+                return Some(SandboxDenial {
+                    operation: SandboxOperation::FileWrite,
+                    path: Some(path),
+                    syscall: Some(syscall.name.to_string()),
+                    inferred: false,
+                });
+            }
+        }
+        None
+    }
+
+    /// Turns the path argument of a syscall made by `pid` into the absolute
+    /// path it targets. Returns `None` for pseudo filesystems, where a
+    /// writable root would not help.
+    fn resolve(&self, pid: libc::pid_t, dirfd: Option<i32>, raw: &[u8]) -> Option<PathBuf> {
+        let path = Path::new(OsStr::from_bytes(raw));
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            let base = match dirfd {
+                Some(dirfd) if dirfd != libc::AT_FDCWD => {
+                    std::fs::read_link(format!("/proc/{pid}/fd/{dirfd}")).ok()?
+                }
+                _ => std::fs::read_link(format!("/proc/{pid}/cwd"))
+                    .unwrap_or_else(|_| self.cwd.clone()),
+            };
+            base.join(path)
+        };
+        let path = normalize(&path);
+        if path.starts_with("/proc") || path.starts_with("/dev") {
+            return None;
+        }
+
+        // The target itself may not exist yet; resolve symlinks in the
+        // deepest ancestor that does.
+        let mut existing = path.parent()?.to_path_buf();
+        let mut rest = vec![path.file_name()?.to_os_string()];
+        loop {
+            if let Ok(canonical) = existing.canonicalize() {
+                return Some(rest.iter().rev().fold(canonical, |p, c| p.join(c)));
+            }
+            rest.push(existing.file_name()?.to_os_string());
+            existing = existing.parent()?.to_path_buf();
+        }
+    }
+
+    fn record(&mut self, denial: SandboxDenial) {
+        let Ok(line) = serde_json::to_string(&denial) else {
+            return;
+        };
+        if self.logged.insert(line.clone()) {
+            let _ = writeln!(self.log, "{line}");
+        }
+    }
+}
+
+/// Reads a NUL-terminated string from the memory of `pid`, one page at a
+/// time so an unmapped page after the string does not fail the read.
+fn read_c_string(pid: libc::pid_t, addr: u64) -> Option<Vec<u8>> {
+    const PAGE: u64 = 4096;
+    let mut out = Vec::new();
+    let mut addr = addr;
+    while out.len() < libc::PATH_MAX as usize {
+        let len = (PAGE - addr % PAGE) as usize;
+        let mut buf = vec![0u8; len];
+        let local = libc::iovec {
+            iov_base: buf.as_mut_ptr().cast(),
+            iov_len: len,
+        };
+        let remote = libc::iovec {
+            iov_base: addr as *mut libc::c_void,
+            iov_len: len,
+        };
+        let n = unsafe { libc::process_vm_readv(pid, &local, 1, &remote, 1, 0) };
+        if n <= 0 {
+            return None;
+        }
+        let n = n as usize;
+        if let Some(end) = buf[..n].iter().position(|b| *b == 0) {
+            out.extend_from_slice(&buf[..end]);
+            return Some(out);
+        }
+        out.extend_from_slice(&buf[..n]);
+        addr += n as u64;
+    }
+    None
+}
+
+/// Resolves `.` and `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for comp in path.components() {
+        match comp {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
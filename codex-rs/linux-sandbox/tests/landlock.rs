@@ -7,6 +7,8 @@ use codex_core::exec::SandboxType;
 use codex_core::exec::process_exec_tool_call;
 use codex_core::exec_env::create_env;
 use codex_core::protocol::SandboxPolicy;
+use codex_core::sandbox_denial::SandboxDenial;
+use codex_core::sandbox_denial::SandboxOperation;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use tempfile::NamedTempFile;
@@ -42,6 +44,7 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        overlay: None,
     };
 
     let sandbox_policy = SandboxPolicy::WorkspaceWrite {
@@ -146,6 +149,102 @@ async fn test_timeout() {
     run_cmd(&["sleep", "2"], &[], 50).await;
 }
 
+#[tokio::test]
+async fn sandbox_denial_names_blocked_path() {
+    if !can_run_linux_sandbox().await {
+        eprintln!("Skipping sandbox_denial_names_blocked_path: exec denied in sandbox");
+        return;
+    }
+    let tmpfile = NamedTempFile::new().unwrap();
+    let tmpfile_path = tmpfile.path().to_path_buf();
+    let params = ExecParams {
+        command: vec![
+            "bash".to_string(),
+            "-c".to_string(),
+            format!("echo blah > {}", tmpfile_path.to_string_lossy()),
+        ],
+        cwd: std::env::current_dir().unwrap(),
+        timeout_ms: Some(LONG_TIMEOUT_MS),
+        env: create_env_from_core_vars(),
+        with_escalated_permissions: None,
+        justification: None,
+        overlay: None,
+    };
+    let sandbox_policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![],
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
+    let codex_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
+    let result = process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        &sandbox_policy,
+        &codex_linux_sandbox_exe,
+        None,
+    )
+    .await;
+
+    let Err(CodexErr::Sandbox(SandboxErr::Denied(output))) = result else {
+        panic!("expected sandbox denied error, got: {result:?}");
+    };
+    assert_eq!(
+        output.sandbox_denials,
+        vec![SandboxDenial {
+            operation: SandboxOperation::FileWrite,
+            path: Some(tmpfile_path),
+            syscall: Some("openat".to_string()),
+            inferred: false,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn sandbox_denial_names_blocked_network_syscall() {
+    if !can_run_linux_sandbox().await {
+        eprintln!("Skipping sandbox_denial_names_blocked_network_syscall: exec denied in sandbox");
+        return;
+    }
+    let params = ExecParams {
+        command: vec![
+            "bash".to_string(),
+            "-c".to_string(),
+            "exec 3<>/dev/tcp/127.0.0.1/80".to_string(),
+        ],
+        cwd: std::env::current_dir().unwrap(),
+        timeout_ms: Some(LONG_TIMEOUT_MS),
+        env: create_env_from_core_vars(),
+        with_escalated_permissions: None,
+        justification: None,
+        overlay: None,
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
+    let codex_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
+    let result = process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        &SandboxPolicy::new_read_only_policy(),
+        &codex_linux_sandbox_exe,
+        None,
+    )
+    .await;
+
+    let Err(CodexErr::Sandbox(SandboxErr::Denied(output))) = result else {
+        panic!("expected sandbox denied error, got: {result:?}");
+    };
+    assert_eq!(
+        output.sandbox_denials,
+        vec![SandboxDenial {
+            operation: SandboxOperation::Network,
+            path: None,
+            syscall: Some("socket".to_string()),
+            inferred: false,
+        }]
+    );
+}
+
 /// Helper that runs `cmd` under the Linux sandbox and asserts that the command
 /// does NOT succeed (i.e. returns a non‑zero exit code) **unless** the binary
 /// is missing in which case we silently treat it as an accepted skip so the
@@ -162,6 +261,7 @@ async fn assert_network_blocked(cmd: &[&str]) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        overlay: None,
     };
 
     let sandbox_policy = SandboxPolicy::new_read_only_policy();
@@ -178,8 +278,8 @@ async fn assert_network_blocked(cmd: &[&str]) {
 
     let (exit_code, stdout, stderr) = match result {
         Ok(output) => (output.exit_code, output.stdout.text, output.stderr.text),
-        Err(CodexErr::Sandbox(SandboxErr::Denied(exit_code, stdout, stderr))) => {
-            (exit_code, stdout, stderr)
+        Err(CodexErr::Sandbox(SandboxErr::Denied(output))) => {
+            (output.exit_code, output.stdout.text, output.stderr.text)
         }
         _ => {
             panic!("expected sandbox denied error, got: {result:?}");
@@ -287,6 +387,7 @@ async fn can_run_linux_sandbox() -> bool {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        overlay: None,
     };
     let sandbox_policy = SandboxPolicy::new_read_only_policy();
     let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
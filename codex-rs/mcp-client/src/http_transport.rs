@@ -0,0 +1,638 @@
+//! MCP over HTTP.
+//!
+//! Servers are first spoken to with the Streamable HTTP transport (protocol
+//! revision 2025-03-26 and later): every client message is POSTed to the
+//! server URL, replies come back either as a JSON body or as an SSE stream,
+//! and the session id the server assigns on `initialize` is echoed on every
+//! later request. Messages the server sends outside of a request arrive on a
+//! GET stream. If the server answers the `initialize` POST with 404 or 405,
+//! the client falls back to the legacy HTTP+SSE transport (2024-11-05), where
+//! a long-lived GET stream first names the endpoint to POST messages to and
+//! then carries every server message. Other errors, such as 401 or 403, are
+//! reported as they are.
+//!
+//! Dropped streams are resumed with `Last-Event-ID`, and a session the server
+//! no longer knows is re-established by replaying the handshake.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use anyhow::bail;
+use eventsource_stream::Event;
+use eventsource_stream::EventStreamError;
+use eventsource_stream::Eventsource;
+use futures::Stream;
+use futures::StreamExt;
+use mcp_types::InitializeRequest;
+use mcp_types::InitializedNotification;
+use mcp_types::JSONRPC_VERSION;
+use mcp_types::JSONRPCError;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::JSONRPCMessage;
+use mcp_types::ModelContextProtocolNotification;
+use mcp_types::ModelContextProtocolRequest;
+use mcp_types::RequestId;
+use reqwest::StatusCode;
+use reqwest::Url;
+use reqwest::header::ACCEPT;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::header::HeaderMap;
+use tokio::sync::Mutex;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+
+const MCP_SESSION_ID: &str = "mcp-session-id";
+const MCP_PROTOCOL_VERSION: &str = "mcp-protocol-version";
+const LAST_EVENT_ID: &str = "last-event-id";
+
+/// Prefix of the ids given to replayed `initialize` requests; responses to
+/// them are consumed here rather than handed to the client.
+const REPLAY_ID_PREFIX: &str = "codex-replay-";
+
+/// Consecutive failures after which a dropped stream is given up on.
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// Delay before the first reconnection attempt; doubled after each failure.
+const RECONNECT_DELAY: Duration = Duration::from_millis(250);
+
+/// Time allowed for a legacy server to announce its message endpoint.
+const ENDPOINT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+type EventStream =
+    Pin<Box<dyn Stream<Item = Result<Event, EventStreamError<reqwest::Error>>> + Send>>;
+
+/// Sends client messages to an MCP server over HTTP and forwards every
+/// message from the server to `incoming`.
+pub(crate) struct HttpTransport {
+    http: reqwest::Client,
+    url: Url,
+    headers: HeaderMap,
+    incoming: mpsc::Sender<JSONRPCMessage>,
+    state: Mutex<State>,
+    replay_counter: AtomicU64,
+}
+
+#[derive(Default)]
+struct State {
+    /// Set once a POST succeeded, after which a rejected request is an error
+    /// rather than a reason to try the legacy transport.
+    streamable: bool,
+    /// Where messages are POSTed once the legacy transport is in use.
+    legacy_endpoint: Option<Url>,
+    session_id: Option<String>,
+    protocol_version: Option<String>,
+    initialize_id: Option<RequestId>,
+    /// The `initialize` request and `notifications/initialized`, replayed to
+    /// re-establish a session.
+    handshake: Vec<JSONRPCMessage>,
+    /// Task reading the GET stream (Streamable HTTP) or the legacy stream.
+    stream_task: Option<JoinHandle<()>>,
+}
+
+impl HttpTransport {
+    /// Starts delivering messages from `outgoing` to the server at `url`
+    /// until the sender is dropped.
+    pub(crate) fn spawn(
+        url: &str,
+        headers: HeaderMap,
+        outgoing: mpsc::Receiver<JSONRPCMessage>,
+        incoming: mpsc::Sender<JSONRPCMessage>,
+    ) -> Result<()> {
+        let url = Url::parse(url).with_context(|| format!("invalid MCP server URL `{url}`"))?;
+        let http = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()?;
+        let transport = Arc::new(Self {
+            http,
+            url,
+            headers,
+            incoming,
+            state: Mutex::new(State::default()),
+            replay_counter: AtomicU64::new(0),
+        });
+        tokio::spawn(transport.run(outgoing));
+        Ok(())
+    }
+
+    async fn run(self: Arc<Self>, mut outgoing: mpsc::Receiver<JSONRPCMessage>) {
+        while let Some(message) = outgoing.recv().await {
+            if is_request(&message, InitializeRequest::METHOD)
+                || is_notification(&message, InitializedNotification::METHOD)
+            {
+                // The handshake is sent in order, before anything else.
+                {
+                    let mut state = self.state.lock().await;
+                    if let JSONRPCMessage::Request(request) = &message {
+                        state.handshake.clear();
+                        state.initialize_id = Some(request.id.clone());
+                    }
+                    state.handshake.push(message.clone());
+                }
+                self.deliver(message).await;
+            } else {
+                let transport = Arc::clone(&self);
+                tokio::spawn(async move { transport.deliver(message).await });
+            }
+        }
+
+        // The client is gone; stop listening for server messages.
+        if let Some(task) = self.state.lock().await.stream_task.take() {
+            task.abort();
+        }
+    }
+
+    /// Sends `message`, turning a failure to deliver a request into an error
+    /// response so the caller does not wait forever.
+    async fn deliver(self: &Arc<Self>, message: JSONRPCMessage) {
+        let Err(e) = self.send(&message).await else {
+            return;
+        };
+        warn!("failed to send MCP message to {}: {e:#}", self.url);
+        if let JSONRPCMessage::Request(request) = message {
+            let error = JSONRPCMessage::Error(JSONRPCError {
+                error: JSONRPCErrorError {
+                    code: -32603,
+                    data: None,
+                    message: format!("{e:#}"),
+                },
+                id: request.id,
+                jsonrpc: JSONRPC_VERSION.to_string(),
+            });
+            let _ = self.incoming.send(error).await;
+        }
+    }
+
+    async fn send(self: &Arc<Self>, message: &JSONRPCMessage) -> Result<()> {
+        let (legacy_endpoint, streamable, has_session) = {
+            let state = self.state.lock().await;
+            (
+                state.legacy_endpoint.clone(),
+                state.streamable,
+                state.session_id.is_some(),
+            )
+        };
+        if let Some(endpoint) = legacy_endpoint {
+            return self.post_legacy(&endpoint, message).await;
+        }
+
+        let initialize = is_request(message, InitializeRequest::METHOD);
+        let response = self.post(message).await?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND && has_session && !initialize {
+            info!("MCP session at {} expired; starting a new one", self.url);
+            self.reinitialize().await?;
+            let response = self.post(message).await?;
+            return self.handle_response(message, response).await;
+        }
+        // Only a server that does not know the endpoint or the method may be
+        // a legacy one; anything else (e.g. 401/403) is the real answer.
+        let legacy = matches!(
+            status,
+            StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED
+        );
+        if initialize && !streamable && legacy {
+            info!(
+                "{} rejected a Streamable HTTP initialize ({status}); trying HTTP+SSE",
+                self.url
+            );
+            let endpoint = self
+                .connect_legacy()
+                .await
+                .with_context(|| format!("server rejected initialize with HTTP {status}"))?;
+            return self.post_legacy(&endpoint, message).await;
+        }
+        self.handle_response(message, response).await
+    }
+
+    async fn post(&self, message: &JSONRPCMessage) -> Result<reqwest::Response> {
+        let request = self
+            .http
+            .post(self.url.clone())
+            .headers(self.headers.clone())
+            .header(ACCEPT, "application/json, text/event-stream")
+            .json(message);
+        let request = self.with_session(request).await;
+        Ok(request.send().await?)
+    }
+
+    /// Opens a GET stream, resuming after `last_event_id` if given.
+    async fn get_stream(&self, last_event_id: Option<&str>) -> Result<reqwest::Response> {
+        let mut request = self
+            .http
+            .get(self.url.clone())
+            .headers(self.headers.clone())
+            .header(ACCEPT, "text/event-stream");
+        if let Some(id) = last_event_id {
+            request = request.header(LAST_EVENT_ID, id);
+        }
+        let request = self.with_session(request).await;
+        Ok(request.send().await?)
+    }
+
+    async fn with_session(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let state = self.state.lock().await;
+        if let Some(id) = &state.session_id {
+            request = request.header(MCP_SESSION_ID, id);
+        }
+        if let Some(version) = &state.protocol_version {
+            request = request.header(MCP_PROTOCOL_VERSION, version);
+        }
+        request
+    }
+
+    async fn handle_response(
+        self: &Arc<Self>,
+        message: &JSONRPCMessage,
+        response: reqwest::Response,
+    ) -> Result<()> {
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!("HTTP {status} from MCP server: {}", body.trim());
+        }
+        {
+            let mut state = self.state.lock().await;
+            state.streamable = true;
+            if is_request(message, InitializeRequest::METHOD) {
+                state.session_id = session_id(&response);
+            }
+        }
+        if is_notification(message, InitializedNotification::METHOD) {
+            self.start_listening().await;
+        }
+
+        let awaited = match message {
+            JSONRPCMessage::Request(request) => Some(request.id.clone()),
+            _ => None,
+        };
+        if is_event_stream(&response) {
+            self.read_reply_stream(response, awaited).await
+        } else {
+            let body = response.bytes().await?;
+            if !body.is_empty() {
+                for message in parse_messages(&body)? {
+                    self.forward(message).await;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Reads the SSE reply to a POST. If the stream drops before the reply to
+    /// `awaited` arrived, it is resumed with a GET carrying the last event id.
+    async fn read_reply_stream(
+        &self,
+        response: reqwest::Response,
+        awaited: Option<RequestId>,
+    ) -> Result<()> {
+        let mut last_event_id = None;
+        let mut events = event_stream(response);
+        let mut failures = 0;
+        loop {
+            match self
+                .read_events(events, &mut last_event_id, awaited.as_ref())
+                .await
+            {
+                Ok(true) => return Ok(()),
+                Ok(false) => {}
+                Err(e) => debug!("MCP reply stream from {} failed: {e:#}", self.url),
+            }
+            let Some(awaited) = &awaited else {
+                return Ok(());
+            };
+            let Some(event_id) = last_event_id.clone() else {
+                bail!("reply stream closed before the response to request {awaited:?}");
+            };
+            loop {
+                failures += 1;
+                if failures > MAX_RECONNECT_ATTEMPTS {
+                    bail!("could not resume the reply stream for request {awaited:?}");
+                }
+                tokio::time::sleep(reconnect_delay(failures)).await;
+                match self.get_stream(Some(&event_id)).await {
+                    Ok(response) if response.status().is_success() => {
+                        events = event_stream(response);
+                        break;
+                    }
+                    Ok(response) => debug!("resuming reply stream: HTTP {}", response.status()),
+                    Err(e) => debug!("resuming reply stream: {e:#}"),
+                }
+            }
+        }
+    }
+
+    /// Forwards the messages on `events` until it ends. Returns `true` as
+    /// soon as the reply to `awaited` was forwarded.
+    async fn read_events(
+        &self,
+        mut events: EventStream,
+        last_event_id: &mut Option<String>,
+        awaited: Option<&RequestId>,
+    ) -> Result<bool> {
+        while let Some(event) = events.next().await {
+            let event = event.map_err(|e| anyhow!("{e}"))?;
+            if !event.id.is_empty() {
+                *last_event_id = Some(event.id.clone());
+            }
+            if event.event != "message" || event.data.trim().is_empty() {
+                continue;
+            }
+            let messages = match parse_messages(event.data.as_bytes()) {
+                Ok(messages) => messages,
+                Err(e) => {
+                    warn!("ignoring malformed MCP event from {}: {e:#}", self.url);
+                    continue;
+                }
+            };
+            for message in messages {
+                let answers = awaited.is_some() && response_id(&message) == awaited;
+                self.forward(message).await;
+                if answers {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    /// Starts reading server-initiated messages from the GET stream, once.
+    async fn start_listening(self: &Arc<Self>) {
+        let mut state = self.state.lock().await;
+        if state.stream_task.is_none() {
+            state.stream_task = Some(tokio::spawn(Arc::clone(self).listen()));
+        }
+    }
+
+    /// Keeps the GET stream open, resuming it when it drops. Servers that do
+    /// not offer one answer 405.
+    async fn listen(self: Arc<Self>) {
+        let mut last_event_id = None;
+        let mut failures = 0;
+        loop {
+            match self.get_stream(last_event_id.as_deref()).await {
+                Ok(response) if response.status() == StatusCode::METHOD_NOT_ALLOWED => {
+                    debug!("{} does not offer a server message stream", self.url);
+                    return;
+                }
+                Ok(response) if response.status().is_success() => {
+                    failures = 0;
+                    let events = event_stream(response);
+                    if let Err(e) = self.read_events(events, &mut last_event_id, None).await {
+                        debug!("MCP message stream from {} failed: {e:#}", self.url);
+                    }
+                }
+                Ok(response) => debug!("MCP message stream: HTTP {}", response.status()),
+                Err(e) => debug!("MCP message stream: {e:#}"),
+            }
+            failures += 1;
+            if failures > MAX_RECONNECT_ATTEMPTS {
+                warn!("giving up on the MCP message stream from {}", self.url);
+                return;
+            }
+            tokio::time::sleep(reconnect_delay(failures)).await;
+        }
+    }
+
+    /// Starts a new session after the server forgot ours, by replaying the
+    /// handshake.
+    async fn reinitialize(&self) -> Result<()> {
+        let handshake = {
+            let mut state = self.state.lock().await;
+            state.session_id = None;
+            state.handshake.clone()
+        };
+        for message in handshake {
+            let message = self.replay_copy(message);
+            let response = self.post(&message).await?;
+            let status = response.status();
+            if !status.is_success() {
+                bail!("HTTP {status} replaying the MCP handshake");
+            }
+            if let JSONRPCMessage::Request(request) = &message {
+                self.state.lock().await.session_id = session_id(&response);
+                if is_event_stream(&response) {
+                    let events = event_stream(response);
+                    self.read_events(events, &mut None, Some(&request.id))
+                        .await?;
+                    continue;
+                }
+            }
+            response.bytes().await?;
+        }
+        Ok(())
+    }
+
+    /// Opens the legacy SSE stream and keeps reading it in the background.
+    /// Returns the endpoint messages are POSTed to.
+    async fn connect_legacy(self: &Arc<Self>) -> Result<Url> {
+        let (endpoint, events) = self.open_legacy_stream().await?;
+        let mut state = self.state.lock().await;
+        state.legacy_endpoint = Some(endpoint.clone());
+        state.stream_task = Some(tokio::spawn(Arc::clone(self).read_legacy(events)));
+        Ok(endpoint)
+    }
+
+    async fn open_legacy_stream(&self) -> Result<(Url, EventStream)> {
+        let response = self.get_stream(None).await?;
+        let status = response.status();
+        if !status.is_success() || !is_event_stream(&response) {
+            bail!("HTTP {status} opening the SSE stream at {}", self.url);
+        }
+        let mut events = event_stream(response);
+        let endpoint = tokio::time::timeout(ENDPOINT_TIMEOUT, async {
+            while let Some(event) = events.next().await {
+                let event = event.map_err(|e| anyhow!("{e}"))?;
+                if event.event == "endpoint" {
+                    return Ok(self.url.join(event.data.trim())?);
+                }
+            }
+            Err(anyhow!("SSE stream closed before naming an endpoint"))
+        })
+        .await
+        .map_err(|_| anyhow!("SSE stream did not name an endpoint"))??;
+        Ok((endpoint, events))
+    }
+
+    /// Forwards messages from the legacy stream. The session lives as long
+    /// as the stream, so when it drops a new one is opened and the handshake
+    /// replayed.
+    async fn read_legacy(self: Arc<Self>, mut events: EventStream) {
+        loop {
+            if let Err(e) = self.read_events(events, &mut None, None).await {
+                debug!("MCP SSE stream from {} failed: {e:#}", self.url);
+            }
+            info!("MCP SSE stream from {} closed; reconnecting", self.url);
+            let mut failures = 0;
+            events = loop {
+                failures += 1;
+                if failures > MAX_RECONNECT_ATTEMPTS {
+                    warn!("giving up on the MCP SSE stream from {}", self.url);
+                    return;
+                }
+                tokio::time::sleep(reconnect_delay(failures)).await;
+                match self.reopen_legacy().await {
+                    Ok(events) => break events,
+                    Err(e) => debug!("reconnecting MCP SSE stream: {e:#}"),
+                }
+            };
+        }
+    }
+
+    async fn reopen_legacy(&self) -> Result<EventStream> {
+        let (endpoint, events) = self.open_legacy_stream().await?;
+        let handshake = {
+            let mut state = self.state.lock().await;
+            state.legacy_endpoint = Some(endpoint.clone());
+            state.handshake.clone()
+        };
+        for message in handshake {
+            self.post_legacy(&endpoint, &self.replay_copy(message))
+                .await?;
+        }
+        Ok(events)
+    }
+
+    async fn post_legacy(&self, endpoint: &Url, message: &JSONRPCMessage) -> Result<()> {
+        let response = self
+            .http
+            .post(endpoint.clone())
+            .headers(self.headers.clone())
+            .json(message)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!("HTTP {status} from MCP server: {}", body.trim());
+        }
+        Ok(())
+    }
+
+    /// Hands a server message to the client, except replies to replayed
+    /// handshake requests.
+    async fn forward(&self, message: JSONRPCMessage) {
+        let replayed = matches!(
+            response_id(&message),
+            Some(RequestId::String(id)) if id.starts_with(REPLAY_ID_PREFIX)
+        );
+        if replayed {
+            return;
+        }
+        if let JSONRPCMessage::Response(response) = &message {
+            let mut state = self.state.lock().await;
+            if state.initialize_id.as_ref() == Some(&response.id) {
+                state.protocol_version = response
+                    .result
+                    .get("protocolVersion")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string);
+            }
+        }
+        if self.incoming.send(message).await.is_err() {
+            debug!("MCP client dropped; discarding message");
+        }
+    }
+
+    /// `message` with a fresh id if it is a request.
+    fn replay_copy(&self, message: JSONRPCMessage) -> JSONRPCMessage {
+        match message {
+            JSONRPCMessage::Request(mut request) => {
+                let n = self.replay_counter.fetch_add(1, Ordering::Relaxed);
+                request.id = RequestId::String(format!("{REPLAY_ID_PREFIX}{n}"));
+                JSONRPCMessage::Request(request)
+            }
+            other => other,
+        }
+    }
+}
+
+fn is_request(message: &JSONRPCMessage, method: &str) -> bool {
+    matches!(message, JSONRPCMessage::Request(request) if request.method == method)
+}
+
+fn is_notification(message: &JSONRPCMessage, method: &str) -> bool {
+    matches!(message, JSONRPCMessage::Notification(notification) if notification.method == method)
+}
+
+fn response_id(message: &JSONRPCMessage) -> Option<&RequestId> {
+    match message {
+        JSONRPCMessage::Response(response) => Some(&response.id),
+        JSONRPCMessage::Error(error) => Some(&error.id),
+        _ => None,
+    }
+}
+
+fn session_id(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get(MCP_SESSION_ID)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+fn is_event_stream(response: &reqwest::Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"))
+}
+
+fn event_stream(response: reqwest::Response) -> EventStream {
+    Box::pin(response.bytes_stream().eventsource())
+}
+
+/// Parses a single JSON-RPC message or a batch.
+fn parse_messages(body: &[u8]) -> Result<Vec<JSONRPCMessage>> {
+    let value: serde_json::Value = serde_json::from_slice(body)?;
+    match value {
+        serde_json::Value::Array(batch) => batch
+            .into_iter()
+            .map(|m| Ok(serde_json::from_value(m)?))
+            .collect(),
+        single => Ok(vec![serde_json::from_value(single)?]),
+    }
+}
+
+fn reconnect_delay(failures: u32) -> Duration {
+    RECONNECT_DELAY * 2u32.pow(failures.saturating_sub(1).min(4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn batches_and_single_messages_parse() {
+        let single = br#"{"jsonrpc":"2.0","id":1,"result":{}}"#;
+        assert_eq!(parse_messages(single).unwrap_or_default().len(), 1);
+
+        let batch = br#"[{"jsonrpc":"2.0","id":1,"result":{}},{"jsonrpc":"2.0","method":"notifications/tools/list_changed"}]"#;
+        let messages = parse_messages(batch).unwrap_or_default();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(response_id(&messages[0]), Some(&RequestId::Integer(1)));
+        assert!(is_notification(
+            &messages[1],
+            "notifications/tools/list_changed"
+        ));
+    }
+
+    #[test]
+    fn reconnect_delay_backs_off_and_caps() {
+        assert_eq!(reconnect_delay(1), RECONNECT_DELAY);
+        assert_eq!(reconnect_delay(2), RECONNECT_DELAY * 2);
+        assert_eq!(reconnect_delay(10), RECONNECT_DELAY * 16);
+    }
+}
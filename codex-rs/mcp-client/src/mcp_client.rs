@@ -2,10 +2,19 @@
 //!
 //! The client is intentionally lightweight – it is only capable of:
 //!   1. Spawning a subprocess that launches a conforming MCP server that
-//!      communicates over stdio.
+//!      communicates over stdio, or connecting to one served over HTTP.
 //!   2. Sending MCP requests and pairing them with their corresponding
 //!      responses.
-//!   3. Offering a convenience helper for the common `tools/list` request.
+//!   3. Offering convenience helpers for the common `tools/*`, `resources/*`
+//!      and `prompts/*` requests.
+//!   4. Broadcasting server-initiated notifications (such as
+//!      `notifications/tools/list_changed`) to subscribers.
+//!   5. Handing server-initiated requests (such as `elicitation/create`) to a
+//!      registered handler, which answers them with
+//!      [`McpClient::send_response`].
+//!   6. Forwarding `notifications/progress` for a request to its caller and
+//!      sending `notifications/cancelled` for requests abandoned before the
+//!      server replied.
 //!
 //! The crate hides all JSON‐RPC framing details behind a typed API. Users
 //! interact with the [`ModelContextProtocolRequest`] trait from `mcp-types` to
@@ -23,20 +32,43 @@ use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::CallToolRequest;
 use mcp_types::CallToolRequestParams;
+use mcp_types::CancelledNotification;
+use mcp_types::CancelledNotificationParams;
+use mcp_types::GetPromptRequest;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
 use mcp_types::InitializeRequest;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializedNotification;
 use mcp_types::JSONRPC_VERSION;
+use mcp_types::JSONRPCError;
+use mcp_types::JSONRPCErrorError;
 use mcp_types::JSONRPCMessage;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ListPromptsRequest;
+use mcp_types::ListPromptsRequestParams;
+use mcp_types::ListPromptsResult;
+use mcp_types::ListResourcesRequest;
+use mcp_types::ListResourcesRequestParams;
+use mcp_types::ListResourcesResult;
 use mcp_types::ListToolsRequest;
 use mcp_types::ListToolsRequestParams;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolNotification;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::PingRequest;
+use mcp_types::ProgressNotification;
+use mcp_types::ProgressNotificationParams;
+use mcp_types::ProgressToken;
+use mcp_types::ReadResourceRequest;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderName;
+use reqwest::header::HeaderValue;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use tokio::io::AsyncBufReadExt;
@@ -44,28 +76,47 @@ use tokio::io::AsyncWriteExt;
 use tokio::io::BufReader;
 use tokio::process::Command;
 use tokio::sync::Mutex;
+use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
+use tokio::sync::watch;
 use tokio::time;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use crate::http_transport::HttpTransport;
+
 /// Capacity of the bounded channels used for transporting messages between the
 /// client API and the IO tasks.
 const CHANNEL_CAPACITY: usize = 128;
 
+/// Notifications buffered per subscriber before the oldest are dropped.
+const NOTIFICATION_CAPACITY: usize = 64;
+
+/// JSON-RPC error code for server requests nobody handles.
+const METHOD_NOT_FOUND_ERROR_CODE: i64 = -32601;
+
 /// Internal representation of a pending request sender.
 type PendingSender = oneshot::Sender<JSONRPCMessage>;
 
+/// Where server-initiated requests go, see
+/// [`McpClient::handle_server_requests`].
+type ServerRequestSender = Arc<std::sync::Mutex<Option<mpsc::UnboundedSender<JSONRPCRequest>>>>;
+
+/// Map of `progressToken -> sender` for requests whose caller wants to hear
+/// about `notifications/progress`. Tokens are the request IDs.
+type ProgressSenders =
+    Arc<std::sync::Mutex<HashMap<i64, mpsc::UnboundedSender<ProgressNotificationParams>>>>;
+
 /// A running MCP client instance.
 pub struct McpClient {
     /// Retain this child process until the client is dropped. The Tokio runtime
     /// will make a "best effort" to reap the process after it exits, but it is
     /// not a guarantee. See the `kill_on_drop` documentation for details.
-    #[allow(dead_code)]
-    child: tokio::process::Child,
+    /// `None` for servers reached over HTTP.
+    child: Option<tokio::process::Child>,
 
     /// Channel for sending JSON-RPC messages *to* the background writer task.
     outgoing_tx: mpsc::Sender<JSONRPCMessage>,
@@ -74,8 +125,22 @@ pub struct McpClient {
     /// to the originating caller.
     pending: Arc<Mutex<HashMap<i64, PendingSender>>>,
 
+    /// Progress subscribers of in-flight requests.
+    progress: ProgressSenders,
+
     /// Monotonically increasing counter used to generate request IDs.
     id_counter: AtomicI64,
+
+    /// Server-initiated notifications, see
+    /// [`subscribe_notifications`](Self::subscribe_notifications).
+    notifications: broadcast::Sender<JSONRPCNotification>,
+
+    /// Handler for server-initiated requests, see
+    /// [`handle_server_requests`](Self::handle_server_requests).
+    server_requests: ServerRequestSender,
+
+    /// Changes once the connection has ended, see [`closed`](Self::closed).
+    closed: watch::Receiver<()>,
 }
 
 impl McpClient {
@@ -111,6 +176,10 @@ impl McpClient {
 
         let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
         let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::new(Mutex::new(HashMap::new()));
+        let progress = ProgressSenders::default();
+        let (notifications, _) = broadcast::channel(NOTIFICATION_CAPACITY);
+        let server_requests = ServerRequestSender::default();
+        let (closed_tx, closed) = watch::channel(());
 
         // Spawn writer task. It listens on the `outgoing_rx` channel and
         // writes messages to the child's STDIN.
@@ -144,6 +213,12 @@ impl McpClient {
         // STDOUT and dispatches responses to the pending map.
         let reader_handle = {
             let pending = pending.clone();
+            let progress = progress.clone();
+            let notifications = notifications.clone();
+            let server_requests = server_requests.clone();
+            // A weak sender so replies to server requests do not keep the
+            // writer task alive once the client is dropped.
+            let outgoing = outgoing_tx.downgrade();
             // Use a larger buffer size (1MB) to handle large tool responses
             let mut lines = BufReader::with_capacity(1024 * 1024, stdout).lines();
 
@@ -151,26 +226,26 @@ impl McpClient {
                 while let Ok(Some(line)) = lines.next_line().await {
                     debug!("MCP message from server: {line}");
                     match serde_json::from_str::<JSONRPCMessage>(&line) {
-                        Ok(JSONRPCMessage::Response(resp)) => {
-                            Self::dispatch_response(resp, &pending).await;
-                        }
-                        Ok(JSONRPCMessage::Error(err)) => {
-                            Self::dispatch_error(err, &pending).await;
-                        }
-                        Ok(JSONRPCMessage::Notification(JSONRPCNotification { .. })) => {
-                            // For now we only log server-initiated notifications.
-                            info!("<- notification: {}", line);
-                        }
-                        Ok(other) => {
-                            // Batch responses and requests are currently not
-                            // expected from the server – log and ignore.
-                            info!("<- unhandled message: {:?}", other);
+                        Ok(msg) => {
+                            Self::dispatch_message(
+                                msg,
+                                &pending,
+                                &progress,
+                                &notifications,
+                                &server_requests,
+                                &outgoing,
+                            )
+                            .await
                         }
                         Err(e) => {
                             error!("failed to deserialize JSONRPCMessage: {e}; line = {}", line)
                         }
                     }
                 }
+                // The server is gone: fail the requests still waiting for a
+                // reply, then let `closed` resolve.
+                pending.lock().await.clear();
+                drop(closed_tx);
             })
         };
 
@@ -181,10 +256,79 @@ impl McpClient {
         let _ = (writer_handle, reader_handle);
 
         Ok(Self {
-            child,
+            child: Some(child),
+            outgoing_tx,
+            pending,
+            progress,
+            id_counter: AtomicI64::new(1),
+            notifications,
+            server_requests,
+            closed,
+        })
+    }
+
+    /// Connect to an MCP server served over HTTP at `url`, sending `headers`
+    /// with every request. Uses the Streamable HTTP transport and falls back
+    /// to the legacy HTTP+SSE transport for servers that reject it. As with
+    /// [`new_stdio_client`](Self::new_stdio_client), the caller is
+    /// responsible for sending the `initialize` request.
+    pub async fn new_streamable_http_client(
+        url: &str,
+        headers: HashMap<String, String>,
+    ) -> Result<Self> {
+        let mut header_map = HeaderMap::with_capacity(headers.len());
+        for (name, value) in headers {
+            let header_name = HeaderName::from_bytes(name.as_bytes())
+                .with_context(|| format!("invalid HTTP header name `{name}`"))?;
+            let header_value = HeaderValue::from_str(&value)
+                .with_context(|| format!("invalid value for HTTP header `{name}`"))?;
+            header_map.insert(header_name, header_value);
+        }
+
+        let (outgoing_tx, outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
+        let (incoming_tx, mut incoming_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
+        let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::new(Mutex::new(HashMap::new()));
+        let progress = ProgressSenders::default();
+        let (notifications, _) = broadcast::channel(NOTIFICATION_CAPACITY);
+        let server_requests = ServerRequestSender::default();
+        let (closed_tx, closed) = watch::channel(());
+
+        HttpTransport::spawn(url, header_map, outgoing_rx, incoming_tx)?;
+
+        // Dispatch task. It ends once the transport drops its sender, which
+        // happens after this client (and with it `outgoing_tx`) is dropped.
+        {
+            let pending = pending.clone();
+            let progress = progress.clone();
+            let notifications = notifications.clone();
+            let server_requests = server_requests.clone();
+            let outgoing = outgoing_tx.downgrade();
+            tokio::spawn(async move {
+                while let Some(msg) = incoming_rx.recv().await {
+                    Self::dispatch_message(
+                        msg,
+                        &pending,
+                        &progress,
+                        &notifications,
+                        &server_requests,
+                        &outgoing,
+                    )
+                    .await;
+                }
+                pending.lock().await.clear();
+                drop(closed_tx);
+            });
+        }
+
+        Ok(Self {
+            child: None,
             outgoing_tx,
             pending,
+            progress,
             id_counter: AtomicI64::new(1),
+            notifications,
+            server_requests,
+            closed,
         })
     }
 
@@ -193,11 +337,33 @@ impl McpClient {
     /// If `timeout` is `None` the call waits indefinitely. If `Some(duration)`
     /// is supplied and no response is received within the given period, a
     /// timeout error is returned.
+    ///
+    /// Dropping the returned future before the server replies (or timing out)
+    /// tells the server to stop with `notifications/cancelled`.
     pub async fn send_request<R>(
         &self,
         params: R::Params,
         timeout: Option<Duration>,
     ) -> Result<R::Result>
+    where
+        R: ModelContextProtocolRequest,
+        R::Params: Serialize,
+        R::Result: DeserializeOwned,
+    {
+        self.send_request_with_progress::<R>(params, timeout, None)
+            .await
+    }
+
+    /// Like [`send_request`](Self::send_request), but when `progress` is set
+    /// the request carries a progress token and every
+    /// `notifications/progress` the server sends for it is forwarded to
+    /// `progress` until the reply arrives.
+    pub async fn send_request_with_progress<R>(
+        &self,
+        params: R::Params,
+        timeout: Option<Duration>,
+        progress: Option<mpsc::UnboundedSender<ProgressNotificationParams>>,
+    ) -> Result<R::Result>
     where
         R: ModelContextProtocolRequest,
         R::Params: Serialize,
@@ -209,7 +375,13 @@ impl McpClient {
 
         // Serialize params -> JSON. For many request types `Params` is
         // `Option<T>` and `None` should be encoded as *absence* of the field.
-        let params_json = serde_json::to_value(&params)?;
+        let mut params_json = serde_json::to_value(&params)?;
+        if let Some(progress) = progress {
+            with_progress_token(&mut params_json, id)?;
+            if let Ok(mut senders) = self.progress.lock() {
+                senders.insert(id, progress);
+            }
+        }
         let params_field = if params_json.is_null() {
             None
         } else {
@@ -234,6 +406,14 @@ impl McpClient {
             let mut guard = self.pending.lock().await;
             guard.insert(id, tx);
         }
+        let mut in_flight = InFlightRequest {
+            id,
+            pending: self.pending.clone(),
+            progress: self.progress.clone(),
+            outgoing: self.outgoing_tx.clone(),
+            reason: "client stopped waiting for the response",
+            answered: false,
+        };
 
         // Send to writer task.
         if self.outgoing_tx.send(message).await.is_err() {
@@ -248,25 +428,28 @@ impl McpClient {
                 match time::timeout(duration, rx).await {
                     Ok(Ok(msg)) => msg,
                     Ok(Err(_)) => {
-                        // Channel closed without a reply – remove the pending entry.
-                        let mut guard = self.pending.lock().await;
-                        guard.remove(&id);
+                        // Channel closed without a reply – the connection is
+                        // gone, so there is nobody left to cancel.
+                        in_flight.answered = true;
                         return Err(anyhow!(
                             "response channel closed before a reply was received"
                         ));
                     }
                     Err(_) => {
-                        // Timed out. Remove the pending entry so we don't leak.
-                        let mut guard = self.pending.lock().await;
-                        guard.remove(&id);
+                        // Timed out. Dropping `in_flight` removes the pending
+                        // entry and cancels the request on the server.
+                        in_flight.reason = "request timed out";
                         return Err(anyhow!("request timed out"));
                     }
                 }
             }
-            None => rx
-                .await
-                .map_err(|_| anyhow!("response channel closed before a reply was received"))?,
+            None => {
+                let reply = rx.await;
+                in_flight.answered = true;
+                reply.map_err(|_| anyhow!("response channel closed before a reply was received"))?
+            }
         };
+        in_flight.answered = true;
 
         match msg {
             JSONRPCMessage::Response(JSONRPCResponse { result, .. }) => {
@@ -351,6 +534,193 @@ impl McpClient {
         self.send_request::<CallToolRequest>(params, timeout).await
     }
 
+    /// Like [`call_tool`](Self::call_tool), forwarding the server's progress
+    /// reports for the call to `progress`.
+    pub async fn call_tool_with_progress(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+        progress: mpsc::UnboundedSender<ProgressNotificationParams>,
+    ) -> Result<mcp_types::CallToolResult> {
+        let params = CallToolRequestParams { name, arguments };
+        debug!("MCP tool call with progress: {params:?}");
+        self.send_request_with_progress::<CallToolRequest>(params, timeout, Some(progress))
+            .await
+    }
+
+    /// Convenience wrapper around `resources/list`.
+    pub async fn list_resources(
+        &self,
+        params: Option<ListResourcesRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListResourcesResult> {
+        self.send_request::<ListResourcesRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `resources/read`.
+    pub async fn read_resource(
+        &self,
+        uri: String,
+        timeout: Option<Duration>,
+    ) -> Result<ReadResourceResult> {
+        let params = ReadResourceRequestParams { uri };
+        self.send_request::<ReadResourceRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/list`.
+    pub async fn list_prompts(
+        &self,
+        params: Option<ListPromptsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListPromptsResult> {
+        self.send_request::<ListPromptsRequest>(params, timeout)
+            .await
+    }
+
+    /// Convenience wrapper around `prompts/get`.
+    pub async fn get_prompt(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<GetPromptResult> {
+        let params = GetPromptRequestParams { name, arguments };
+        self.send_request::<GetPromptRequest>(params, timeout).await
+    }
+
+    /// Receive the notifications the server sends from now on. The receiver
+    /// closes once the client is dropped and the connection has shut down.
+    pub fn subscribe_notifications(&self) -> broadcast::Receiver<JSONRPCNotification> {
+        self.notifications.subscribe()
+    }
+
+    /// Resolves once the connection to the server has ended, e.g. because the
+    /// server process exited. The future does not borrow the client, so it
+    /// can be awaited by a task that must not keep the client alive.
+    pub fn closed(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut closed = self.closed.clone();
+        async move { while closed.changed().await.is_ok() {} }
+    }
+
+    /// Receive the requests the server sends from now on (`ping` excepted,
+    /// which is answered automatically). Every request must be answered with
+    /// [`send_response`](Self::send_response) or
+    /// [`send_error_response`](Self::send_error_response). Replaces any
+    /// previously registered handler; while none is registered, requests are
+    /// rejected with "method not found".
+    pub fn handle_server_requests(&self) -> mpsc::UnboundedReceiver<JSONRPCRequest> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        if let Ok(mut handler) = self.server_requests.lock() {
+            *handler = Some(tx);
+        }
+        rx
+    }
+
+    /// Answer the server request `id` with `result`.
+    pub async fn send_response(&self, id: RequestId, result: serde_json::Value) -> Result<()> {
+        let response = JSONRPCMessage::Response(JSONRPCResponse {
+            id,
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            result,
+        });
+        self.outgoing_tx
+            .send(response)
+            .await
+            .context("failed to send response to writer task")
+    }
+
+    /// Reject the server request `id` with a JSON-RPC error.
+    pub async fn send_error_response(
+        &self,
+        id: RequestId,
+        code: i64,
+        message: String,
+    ) -> Result<()> {
+        self.outgoing_tx
+            .send(error_response(id, code, message))
+            .await
+            .context("failed to send error response to writer task")
+    }
+
+    /// Internal helper: route a message received from the server.
+    async fn dispatch_message(
+        msg: JSONRPCMessage,
+        pending: &Arc<Mutex<HashMap<i64, PendingSender>>>,
+        progress: &ProgressSenders,
+        notifications: &broadcast::Sender<JSONRPCNotification>,
+        server_requests: &ServerRequestSender,
+        outgoing: &mpsc::WeakSender<JSONRPCMessage>,
+    ) {
+        match msg {
+            JSONRPCMessage::Response(resp) => Self::dispatch_response(resp, pending).await,
+            JSONRPCMessage::Error(err) => Self::dispatch_error(err, pending).await,
+            JSONRPCMessage::Notification(notification) => {
+                info!("<- notification: {}", notification.method);
+                if notification.method == ProgressNotification::METHOD {
+                    Self::dispatch_progress(&notification, progress);
+                }
+                // Nobody may be subscribed; that is not an error.
+                let _ = notifications.send(notification);
+            }
+            JSONRPCMessage::Request(request) => {
+                info!("<- request: {}", request.method);
+                let reply = if request.method == PingRequest::METHOD {
+                    JSONRPCMessage::Response(JSONRPCResponse {
+                        id: request.id,
+                        jsonrpc: JSONRPC_VERSION.to_string(),
+                        result: serde_json::json!({}),
+                    })
+                } else {
+                    let handler = server_requests
+                        .lock()
+                        .ok()
+                        .and_then(|handler| handler.clone());
+                    let request = match handler {
+                        Some(handler) => match handler.send(request) {
+                            Ok(()) => return,
+                            Err(mpsc::error::SendError(request)) => request,
+                        },
+                        None => request,
+                    };
+                    error_response(
+                        request.id,
+                        METHOD_NOT_FOUND_ERROR_CODE,
+                        format!("client does not handle `{}` requests", request.method),
+                    )
+                };
+                if let Some(outgoing) = outgoing.upgrade() {
+                    let _ = outgoing.send(reply).await;
+                }
+            }
+        }
+    }
+
+    /// Internal helper: hand a `notifications/progress` to the caller of the
+    /// request its token belongs to.
+    fn dispatch_progress(notification: &JSONRPCNotification, progress: &ProgressSenders) {
+        let Some(params) = notification
+            .params
+            .clone()
+            .and_then(|params| serde_json::from_value::<ProgressNotificationParams>(params).ok())
+        else {
+            warn!("malformed progress notification");
+            return;
+        };
+        let ProgressToken::Integer(token) = params.progress_token else {
+            // We only ever hand out integer tokens.
+            return;
+        };
+        let Ok(senders) = progress.lock() else {
+            return;
+        };
+        if let Some(tx) = senders.get(&token) {
+            let _ = tx.send(params);
+        }
+    }
+
     /// Internal helper: route a JSON-RPC *response* object to the pending map.
     async fn dispatch_response(
         resp: JSONRPCResponse,
@@ -390,13 +760,99 @@ impl McpClient {
     }
 }
 
+/// A request awaiting its reply. Dropped before the reply arrived, it removes
+/// the request from the pending map and sends `notifications/cancelled` so the
+/// server can stop working on it.
+struct InFlightRequest {
+    id: i64,
+    pending: Arc<Mutex<HashMap<i64, PendingSender>>>,
+    progress: ProgressSenders,
+    outgoing: mpsc::Sender<JSONRPCMessage>,
+    reason: &'static str,
+    answered: bool,
+}
+
+impl Drop for InFlightRequest {
+    fn drop(&mut self) {
+        if let Ok(mut senders) = self.progress.lock() {
+            senders.remove(&self.id);
+        }
+        if self.answered {
+            return;
+        }
+        // Dropping happens outside async context (e.g. when a task is
+        // aborted), so the cancellation is sent from a task of its own.
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let id = self.id;
+        let pending = self.pending.clone();
+        let outgoing = self.outgoing.clone();
+        let reason = self.reason;
+        runtime.spawn(async move {
+            // A reply that raced the drop already removed the entry; there is
+            // nothing left to cancel then.
+            if pending.lock().await.remove(&id).is_none() {
+                return;
+            }
+            info!(id, reason, "cancelling MCP request");
+            let params = CancelledNotificationParams {
+                reason: Some(reason.to_string()),
+                request_id: RequestId::Integer(id),
+            };
+            let Ok(params) = serde_json::to_value(params) else {
+                return;
+            };
+            let notification = JSONRPCMessage::Notification(JSONRPCNotification {
+                jsonrpc: JSONRPC_VERSION.to_string(),
+                method: CancelledNotification::METHOD.to_string(),
+                params: Some(params),
+            });
+            let _ = outgoing.send(notification).await;
+        });
+    }
+}
+
+/// Add `_meta.progressToken` to serialized request params, creating the
+/// params object when the request has none.
+fn with_progress_token(params: &mut serde_json::Value, token: i64) -> Result<()> {
+    if params.is_null() {
+        *params = serde_json::json!({});
+    }
+    let params = params
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("progress tokens need object params"))?;
+    let meta = params
+        .entry("_meta")
+        .or_insert_with(|| serde_json::json!({}));
+    let meta = meta
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("`_meta` must be an object"))?;
+    meta.insert("progressToken".to_string(), serde_json::json!(token));
+    Ok(())
+}
+
+fn error_response(id: RequestId, code: i64, message: String) -> JSONRPCMessage {
+    JSONRPCMessage::Error(JSONRPCError {
+        error: JSONRPCErrorError {
+            code,
+            data: None,
+            message,
+        },
+        id,
+        jsonrpc: JSONRPC_VERSION.to_string(),
+    })
+}
+
 impl Drop for McpClient {
     fn drop(&mut self) {
         // Even though we have already tagged this process with
         // `kill_on_drop(true)` above, this extra check has the benefit of
         // forcing the process to be reaped immediately if it has already exited
         // instead of waiting for the Tokio runtime to reap it later.
-        let _ = self.child.try_wait();
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.try_wait();
+        }
     }
 }
 
@@ -470,4 +926,15 @@ mod tests {
         assert!(mcp_server_env.contains_key("PATH"));
         assert_eq!(Some(&env_var_new_value), mcp_server_env.get(env_var));
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn closed_resolves_when_the_server_exits() {
+        let client = McpClient::new_stdio_client("true".into(), Vec::new(), None)
+            .await
+            .expect("spawn `true`");
+        time::timeout(Duration::from_secs(5), client.closed())
+            .await
+            .expect("connection should close once the process exits");
+    }
 }
@@ -0,0 +1,556 @@
+//! Runs `McpClient` against a local MCP server speaking either the
+//! Streamable HTTP transport or the legacy HTTP+SSE transport.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use codex_mcp_client::McpClient;
+use mcp_types::ClientCapabilities;
+use mcp_types::ContentBlock;
+use mcp_types::Implementation;
+use mcp_types::InitializeRequestParams;
+use mcp_types::MCP_SCHEMA_VERSION;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+const TIMEOUT: Option<Duration> = Some(Duration::from_secs(10));
+
+#[derive(Clone, Copy, PartialEq)]
+enum Transport {
+    StreamableHttp,
+    LegacySse,
+    /// Rejects every request as unauthorized.
+    Unauthorized,
+}
+
+#[derive(Default)]
+struct ServerState {
+    sessions: HashSet<String>,
+    initializes: usize,
+    /// Replies held back from a dropped reply stream, by the event id the
+    /// client resumes after.
+    parked: HashMap<String, Value>,
+    /// Requests without the configured authorization header.
+    unauthorized: usize,
+    /// GET requests, i.e. attempts to open an event stream.
+    stream_requests: usize,
+    /// Requests after `initialize` that lacked the protocol version header.
+    missing_protocol_version: usize,
+    legacy_stream: Option<mpsc::UnboundedSender<Value>>,
+    /// Responses the client posted to requests the server sent it.
+    client_responses: Option<mpsc::UnboundedSender<Value>>,
+    /// Notifications the client posted.
+    client_notifications: Option<mpsc::UnboundedSender<Value>>,
+    /// `tools/call` requests for `index`, which the server never answers.
+    held_calls: Option<mpsc::UnboundedSender<Value>>,
+}
+
+struct TestServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<ServerState>>,
+}
+
+impl TestServer {
+    async fn start(transport: Transport) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        let state = Arc::new(Mutex::new(ServerState::default()));
+        let server_state = Arc::clone(&state);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = Arc::clone(&server_state);
+                tokio::spawn(async move { handle(stream, transport, state).await });
+            }
+        });
+        Self { addr, state }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}/mcp", self.addr)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, ServerState> {
+        self.state.lock().expect("server state")
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+async fn read_request(reader: &mut BufReader<TcpStream>) -> Option<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line).await.ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+    let mut headers = HashMap::new();
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).await.ok()?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let (name, value) = header.split_once(':')?;
+        headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+    let length = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await.ok()?;
+    Some(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, headers: &[(&str, &str)], body: &str) {
+    let mut out = format!("HTTP/1.1 {status}\r\nContent-Length: {}\r\n", body.len());
+    for (name, value) in headers {
+        out.push_str(&format!("{name}: {value}\r\n"));
+    }
+    out.push_str("Connection: close\r\n\r\n");
+    out.push_str(body);
+    let _ = stream.write_all(out.as_bytes()).await;
+}
+
+/// Starts an SSE body that lasts until the connection is closed.
+async fn start_event_stream(stream: &mut TcpStream) {
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n";
+    let _ = stream.write_all(head.as_bytes()).await;
+}
+
+async fn send_event(stream: &mut TcpStream, id: Option<&str>, event: Option<&str>, data: &str) {
+    let mut out = String::new();
+    if let Some(id) = id {
+        out.push_str(&format!("id: {id}\n"));
+    }
+    if let Some(event) = event {
+        out.push_str(&format!("event: {event}\n"));
+    }
+    out.push_str(&format!("data: {data}\n\n"));
+    let _ = stream.write_all(out.as_bytes()).await;
+    let _ = stream.flush().await;
+}
+
+/// The result the test server gives for `method`.
+fn result_for(method: &str) -> Value {
+    match method {
+        "initialize" => json!({
+            "protocolVersion": MCP_SCHEMA_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "docs", "version": "1.0.0" },
+        }),
+        "tools/list" => json!({
+            "tools": [{ "name": "search", "inputSchema": { "type": "object" } }],
+        }),
+        "tools/call" => json!({
+            "content": [{ "type": "text", "text": "found 3 pages" }],
+        }),
+        other => json!({ "unexpected": other }),
+    }
+}
+
+fn reply(message: &Value) -> Value {
+    let method = message["method"].as_str().unwrap_or_default();
+    json!({ "jsonrpc": "2.0", "id": message["id"], "result": result_for(method) })
+}
+
+async fn handle(stream: TcpStream, transport: Transport, state: Arc<Mutex<ServerState>>) {
+    let mut reader = BufReader::new(stream);
+    let Some(request) = read_request(&mut reader).await else {
+        return;
+    };
+    let mut stream = reader.into_inner();
+    {
+        let mut state = state.lock().expect("state");
+        if request.headers.get("authorization").map(String::as_str) != Some("Bearer secret") {
+            state.unauthorized += 1;
+        }
+        if request.method == "GET" {
+            state.stream_requests += 1;
+        }
+    }
+    match transport {
+        Transport::StreamableHttp => handle_streamable(&mut stream, request, &state).await,
+        Transport::LegacySse => handle_legacy(&mut stream, request, &state).await,
+        Transport::Unauthorized => {
+            respond(&mut stream, "401 Unauthorized", &[], "invalid token").await;
+        }
+    }
+}
+
+async fn handle_streamable(
+    stream: &mut TcpStream,
+    request: Request,
+    state: &Arc<Mutex<ServerState>>,
+) {
+    if request.method == "GET" {
+        let resumed = request
+            .headers
+            .get("last-event-id")
+            .and_then(|id| state.lock().expect("state").parked.remove(id));
+        match resumed {
+            Some(message) => {
+                start_event_stream(stream).await;
+                send_event(stream, Some("2"), None, &message.to_string()).await;
+            }
+            None => respond(stream, "405 Method Not Allowed", &[], "").await,
+        }
+        return;
+    }
+
+    let message: Value = serde_json::from_slice(&request.body).expect("JSON-RPC body");
+    let method = message["method"].as_str().unwrap_or_default().to_string();
+    if method == "initialize" {
+        let session = {
+            let mut state = state.lock().expect("state");
+            state.initializes += 1;
+            let session = format!("session-{}", state.initializes);
+            state.sessions.insert(session.clone());
+            session
+        };
+        let body = reply(&message).to_string();
+        let headers = [
+            ("Content-Type", "application/json"),
+            ("Mcp-Session-Id", session.as_str()),
+        ];
+        respond(stream, "200 OK", &headers, &body).await;
+        return;
+    }
+
+    let known = {
+        let mut state = state.lock().expect("state");
+        if request
+            .headers
+            .get("mcp-protocol-version")
+            .map(String::as_str)
+            != Some(MCP_SCHEMA_VERSION)
+        {
+            state.missing_protocol_version += 1;
+        }
+        request
+            .headers
+            .get("mcp-session-id")
+            .is_some_and(|id| state.sessions.contains(id))
+    };
+    if !known {
+        respond(stream, "404 Not Found", &[], "unknown session").await;
+        return;
+    }
+
+    match method.as_str() {
+        // Notifications are accepted without a body.
+        _ if message.get("id").is_none() => respond(stream, "202 Accepted", &[], "").await,
+        "tools/call" => {
+            // Drop the reply stream after a priming event; the reply is sent
+            // once the client resumes with its id.
+            state
+                .lock()
+                .expect("state")
+                .parked
+                .insert("1".to_string(), reply(&message));
+            start_event_stream(stream).await;
+            send_event(stream, Some("1"), None, "").await;
+        }
+        _ => {
+            // Server notifications may precede the reply on its stream.
+            let changed = json!({ "jsonrpc": "2.0", "method": "notifications/tools/list_changed" });
+            start_event_stream(stream).await;
+            send_event(stream, Some("1"), None, &changed.to_string()).await;
+            send_event(stream, Some("2"), None, &reply(&message).to_string()).await;
+        }
+    }
+}
+
+async fn handle_legacy(stream: &mut TcpStream, request: Request, state: &Arc<Mutex<ServerState>>) {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/mcp") => {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            state.lock().expect("state").legacy_stream = Some(tx);
+            start_event_stream(stream).await;
+            send_event(stream, None, Some("endpoint"), "/messages?session_id=1").await;
+            while let Some(message) = rx.recv().await {
+                send_event(stream, None, Some("message"), &message.to_string()).await;
+            }
+        }
+        ("POST", "/messages?session_id=1") => {
+            let message: Value = serde_json::from_slice(&request.body).expect("JSON-RPC body");
+            let sender = {
+                let mut state = state.lock().expect("state");
+                if message["method"] == "initialize" {
+                    state.initializes += 1;
+                }
+                state.legacy_stream.clone()
+            };
+            if message.get("method").is_none() {
+                let responses = state.lock().expect("state").client_responses.clone();
+                if let Some(responses) = responses {
+                    let _ = responses.send(message);
+                }
+            } else if message.get("id").is_none() {
+                let notifications = state.lock().expect("state").client_notifications.clone();
+                if let Some(notifications) = notifications {
+                    let _ = notifications.send(message);
+                }
+            } else if message["method"] == "tools/call" && message["params"]["name"] == "index" {
+                let held = state.lock().expect("state").held_calls.clone();
+                if let Some(held) = held {
+                    let _ = held.send(message);
+                }
+            } else if message.get("id").is_some() {
+                if let Some(sender) = sender {
+                    let _ = sender.send(reply(&message));
+                }
+            }
+            respond(stream, "202 Accepted", &[], "").await;
+        }
+        // Streamable HTTP requests are not understood.
+        _ => respond(stream, "405 Method Not Allowed", &[], "").await,
+    }
+}
+
+async fn connect(server: &TestServer) -> McpClient {
+    let headers = HashMap::from([("Authorization".to_string(), "Bearer secret".to_string())]);
+    let client = McpClient::new_streamable_http_client(&server.url(), headers)
+        .await
+        .expect("create client");
+    let initialized = client
+        .initialize(initialize_params(), None, TIMEOUT)
+        .await
+        .expect("initialize");
+    assert_eq!(initialized.server_info.name, "docs");
+    client
+}
+
+fn initialize_params() -> InitializeRequestParams {
+    InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: None,
+            sampling: None,
+            elicitation: None,
+        },
+        client_info: Implementation {
+            name: "codex-mcp-client-test".to_string(),
+            version: "0.0.0".to_string(),
+            title: None,
+        },
+        protocol_version: MCP_SCHEMA_VERSION.to_string(),
+    }
+}
+
+fn tool_names(result: &mcp_types::ListToolsResult) -> Vec<String> {
+    result.tools.iter().map(|t| t.name.clone()).collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn streamable_http_resumes_streams_and_expired_sessions() {
+    let server = TestServer::start(Transport::StreamableHttp).await;
+    let client = connect(&server).await;
+
+    // Replies streamed as SSE, after a notification on the same stream.
+    let mut notifications = client.subscribe_notifications();
+    let tools = client.list_tools(None, TIMEOUT).await.expect("tools/list");
+    assert_eq!(tool_names(&tools), vec!["search".to_string()]);
+    let notification = notifications.recv().await.expect("notification");
+    assert_eq!(notification.method, "notifications/tools/list_changed");
+
+    // A reply stream that drops before the reply is resumed.
+    let result = client
+        .call_tool("search".to_string(), Some(json!({ "q": "auth" })), TIMEOUT)
+        .await
+        .expect("tools/call");
+    let ContentBlock::TextContent(text) = &result.content[0] else {
+        panic!("expected text content, got {:?}", result.content);
+    };
+    assert_eq!(text.text, "found 3 pages");
+
+    // The server restarts and forgets the session.
+    server.state().sessions.clear();
+    let tools = client.list_tools(None, TIMEOUT).await.expect("tools/list");
+    assert_eq!(tool_names(&tools), vec!["search".to_string()]);
+
+    let state = server.state();
+    assert_eq!(state.initializes, 2);
+    assert_eq!(state.unauthorized, 0);
+    assert_eq!(state.missing_protocol_version, 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn falls_back_to_legacy_sse() {
+    let server = TestServer::start(Transport::LegacySse).await;
+    let client = connect(&server).await;
+
+    let tools = client.list_tools(None, TIMEOUT).await.expect("tools/list");
+    assert_eq!(tool_names(&tools), vec!["search".to_string()]);
+
+    let state = server.state();
+    assert_eq!(state.initializes, 1);
+    assert_eq!(state.unauthorized, 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn unauthorized_initialize_does_not_fall_back_to_legacy_sse() {
+    let server = TestServer::start(Transport::Unauthorized).await;
+    let client = McpClient::new_streamable_http_client(&server.url(), HashMap::new())
+        .await
+        .expect("create client");
+
+    let err = client
+        .initialize(initialize_params(), None, TIMEOUT)
+        .await
+        .expect_err("server rejects the client");
+    assert!(err.to_string().contains("HTTP 401"), "{err}");
+    assert_eq!(server.state().stream_requests, 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn server_requests_reach_the_registered_handler() {
+    let server = TestServer::start(Transport::LegacySse).await;
+    let client = connect(&server).await;
+    let (responses_tx, mut responses) = mpsc::unbounded_channel();
+    server.state().client_responses = Some(responses_tx);
+    let to_client = server.state().legacy_stream.clone().expect("event stream");
+    let elicit = |id: i64| {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "elicitation/create",
+            "params": {
+                "message": "Which repository?",
+                "requestedSchema": { "type": "object", "properties": {} },
+            },
+        })
+    };
+
+    // Pings are answered without a handler.
+    to_client
+        .send(json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" }))
+        .expect("send ping");
+    let pong = responses.recv().await.expect("ping response");
+    assert_eq!(pong, json!({ "jsonrpc": "2.0", "id": 1, "result": {} }));
+
+    // Without a handler, other requests are rejected.
+    to_client.send(elicit(2)).expect("send request");
+    let rejected = responses.recv().await.expect("error response");
+    assert_eq!(rejected["id"], json!(2));
+    assert_eq!(rejected["error"]["code"], json!(-32601));
+
+    let mut requests = client.handle_server_requests();
+    to_client.send(elicit(3)).expect("send request");
+    let request = requests.recv().await.expect("server request");
+    assert_eq!(request.method, "elicitation/create");
+    client
+        .send_response(request.id, json!({ "action": "decline" }))
+        .await
+        .expect("send response");
+    let answered = responses.recv().await.expect("handler response");
+    assert_eq!(
+        answered,
+        json!({ "jsonrpc": "2.0", "id": 3, "result": { "action": "decline" } })
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tool_calls_report_progress_and_are_cancelled_when_dropped() {
+    let server = TestServer::start(Transport::LegacySse).await;
+    let client = Arc::new(connect(&server).await);
+    let (held_tx, mut held) = mpsc::unbounded_channel();
+    let (notifications_tx, mut notifications) = mpsc::unbounded_channel();
+    server.state().held_calls = Some(held_tx);
+    server.state().client_notifications = Some(notifications_tx);
+    let to_client = server.state().legacy_stream.clone().expect("event stream");
+
+    let (progress_tx, mut progress) = mpsc::unbounded_channel();
+    let call = tokio::spawn({
+        let client = Arc::clone(&client);
+        async move {
+            client
+                .call_tool_with_progress("index".to_string(), None, None, progress_tx)
+                .await
+        }
+    });
+
+    // The request carries its own id as the progress token.
+    let request = held.recv().await.expect("held tools/call");
+    let id = request["id"].clone();
+    assert_eq!(request["params"]["_meta"]["progressToken"], id);
+
+    to_client
+        .send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": { "progressToken": id, "progress": 2, "total": 5, "message": "parsing" },
+        }))
+        .expect("send progress");
+    // Progress for some other request is not forwarded.
+    to_client
+        .send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": { "progressToken": "elsewhere", "progress": 1 },
+        }))
+        .expect("send progress");
+    to_client
+        .send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": { "progressToken": id, "progress": 5, "total": 5 },
+        }))
+        .expect("send progress");
+
+    let first = progress.recv().await.expect("first progress");
+    assert_eq!(
+        (first.progress, first.total, first.message.as_deref()),
+        (2.0, Some(5.0), Some("parsing"))
+    );
+    let second = progress.recv().await.expect("second progress");
+    assert_eq!((second.progress, second.message), (5.0, None));
+
+    // Aborting the caller, as an interrupted turn does, cancels the request.
+    call.abort();
+    let cancelled = tokio::time::timeout(Duration::from_secs(10), notifications.recv())
+        .await
+        .expect("cancellation in time")
+        .expect("cancelled notification");
+    assert_eq!(cancelled["method"], json!("notifications/cancelled"));
+    assert_eq!(cancelled["params"]["requestId"], id);
+    assert!(progress.recv().await.is_none());
+}
+
+#[tokio::test]
+async fn unreachable_servers_fail_requests_instead_of_hanging() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let url = format!("http://{}/mcp", listener.local_addr().expect("local addr"));
+    drop(listener);
+
+    let client = McpClient::new_streamable_http_client(&url, HashMap::new())
+        .await
+        .expect("create client");
+    let err = client
+        .list_tools(None, TIMEOUT)
+        .await
+        .expect_err("nothing is listening");
+    assert!(err.to_string().contains("JSON-RPC error"), "{err}");
+}
@@ -5,10 +5,16 @@ use std::time::Duration;
 
 use codex_core::CodexConversation;
 use codex_core::ConversationManager;
+use codex_core::ConversationsPage;
 use codex_core::NewConversation;
+use codex_core::archive_rollout;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_core::find_rollout;
+use codex_core::fork_rollout;
 use codex_core::git_info::git_diff_to_remote;
+use codex_core::list_conversations;
+use codex_core::read_rollout_session_id;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
@@ -21,6 +27,7 @@ use tokio::sync::oneshot;
 use tracing::error;
 use uuid::Uuid;
 
+use crate::codex_resources::CodexResources;
 use crate::error_code::INTERNAL_ERROR_CODE;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
 use crate::json_to_toml::json_to_toml;
@@ -37,23 +44,33 @@ use codex_login::run_login_server;
 use codex_protocol::mcp_protocol::APPLY_PATCH_APPROVAL_METHOD;
 use codex_protocol::mcp_protocol::AddConversationListenerParams;
 use codex_protocol::mcp_protocol::AddConversationSubscriptionResponse;
+use codex_protocol::mcp_protocol::ArchiveConversationParams;
+use codex_protocol::mcp_protocol::ArchiveConversationResponse;
 use codex_protocol::mcp_protocol::ApplyPatchApprovalParams;
 use codex_protocol::mcp_protocol::ApplyPatchApprovalResponse;
 use codex_protocol::mcp_protocol::ClientRequest;
 use codex_protocol::mcp_protocol::ConversationId;
+use codex_protocol::mcp_protocol::ConversationSummary;
 use codex_protocol::mcp_protocol::EXEC_COMMAND_APPROVAL_METHOD;
 use codex_protocol::mcp_protocol::ExecCommandApprovalParams;
 use codex_protocol::mcp_protocol::ExecCommandApprovalResponse;
+use codex_protocol::mcp_protocol::ForkConversationParams;
+use codex_protocol::mcp_protocol::ForkConversationResponse;
 use codex_protocol::mcp_protocol::InputItem as WireInputItem;
 use codex_protocol::mcp_protocol::InterruptConversationParams;
 use codex_protocol::mcp_protocol::InterruptConversationResponse;
+use codex_protocol::mcp_protocol::ListConversationsParams;
+use codex_protocol::mcp_protocol::ListConversationsResponse;
 use codex_protocol::mcp_protocol::LoginChatGptResponse;
 use codex_protocol::mcp_protocol::GitDiffToRemoteParams;
 use codex_protocol::mcp_protocol::GitDiffToRemoteResponse;
+use codex_protocol::mcp_protocol::GitSha;
 use codex_protocol::mcp_protocol::NewConversationParams;
 use codex_protocol::mcp_protocol::NewConversationResponse;
 use codex_protocol::mcp_protocol::RemoveConversationListenerParams;
 use codex_protocol::mcp_protocol::RemoveConversationSubscriptionResponse;
+use codex_protocol::mcp_protocol::ResumeConversationParams;
+use codex_protocol::mcp_protocol::ResumeConversationResponse;
 use codex_protocol::mcp_protocol::SendUserMessageParams;
 use codex_protocol::mcp_protocol::SendUserMessageResponse;
 use codex_protocol::mcp_protocol::SendUserTurnParams;
@@ -62,6 +79,9 @@ use codex_protocol::mcp_protocol::SendUserTurnResponse;
 // Duration before a ChatGPT login attempt is abandoned.
 const LOGIN_CHATGPT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
 
+const DEFAULT_LIST_CONVERSATIONS_PAGE_SIZE: usize = 25;
+const MAX_LIST_CONVERSATIONS_PAGE_SIZE: usize = 100;
+
 struct ActiveLogin {
     shutdown_handle: ShutdownHandle,
     login_id: Uuid,
@@ -78,10 +98,12 @@ pub(crate) struct CodexMessageProcessor {
     conversation_manager: Arc<ConversationManager>,
     outgoing: Arc<OutgoingMessageSender>,
     codex_linux_sandbox_exe: Option<PathBuf>,
+    config: Arc<Config>,
     conversation_listeners: HashMap<Uuid, oneshot::Sender<()>>,
     active_login: Arc<Mutex<Option<ActiveLogin>>>,
     // Queue of pending interrupt requests per conversation. We reply when TurnAborted arrives.
     pending_interrupts: Arc<Mutex<HashMap<Uuid, Vec<RequestId>>>>,
+    resources: Arc<CodexResources>,
 }
 
 impl CodexMessageProcessor {
@@ -89,14 +111,18 @@ impl CodexMessageProcessor {
         conversation_manager: Arc<ConversationManager>,
         outgoing: Arc<OutgoingMessageSender>,
         codex_linux_sandbox_exe: Option<PathBuf>,
+        config: Arc<Config>,
+        resources: Arc<CodexResources>,
     ) -> Self {
         Self {
             conversation_manager,
             outgoing,
             codex_linux_sandbox_exe,
+            config,
             conversation_listeners: HashMap::new(),
             active_login: Arc::new(Mutex::new(None)),
             pending_interrupts: Arc::new(Mutex::new(HashMap::new())),
+            resources,
         }
     }
 
@@ -120,6 +146,19 @@ impl CodexMessageProcessor {
             ClientRequest::RemoveConversationListener { request_id, params } => {
                 self.remove_conversation_listener(request_id, params).await;
             }
+            ClientRequest::ListConversations { request_id, params } => {
+                self.list_conversations(request_id, params).await;
+            }
+            ClientRequest::ResumeConversation { request_id, params } => {
+                // Like NewConversation, finish before processing later messages.
+                self.resume_conversation(request_id, params).await;
+            }
+            ClientRequest::ForkConversation { request_id, params } => {
+                self.fork_conversation(request_id, params).await;
+            }
+            ClientRequest::ArchiveConversation { request_id, params } => {
+                self.archive_conversation(request_id, params).await;
+            }
             ClientRequest::SendUserTurn { request_id, params } => {
                 self.send_user_turn_compat(request_id, params).await;
             }
@@ -292,6 +331,7 @@ impl CodexMessageProcessor {
             .insert(subscription_id, cancel_tx);
         let outgoing_for_task = self.outgoing.clone();
         let pending_interrupts = self.pending_interrupts.clone();
+        let resources = self.resources.clone();
         tokio::spawn(async move {
             loop {
                 tokio::select! {
@@ -332,6 +372,10 @@ impl CodexMessageProcessor {
                         })
                         .await;
 
+                        if let EventMsg::TurnDiff(turn_diff) = &event.msg {
+                            resources.record_turn_diff(conversation_id.0, turn_diff.unified_diff.clone()).await;
+                        }
+
                         apply_bespoke_event_handling(event.clone(), conversation_id, conversation.clone(), outgoing_for_task.clone(), pending_interrupts.clone()).await;
                     }
                 }
@@ -365,6 +409,229 @@ impl CodexMessageProcessor {
         }
     }
 
+    async fn list_conversations(&self, request_id: RequestId, params: ListConversationsParams) {
+        let ListConversationsParams { page_size, cursor } = params;
+        let page_size = page_size
+            .unwrap_or(DEFAULT_LIST_CONVERSATIONS_PAGE_SIZE)
+            .clamp(1, MAX_LIST_CONVERSATIONS_PAGE_SIZE);
+        let ConversationsPage { items, next_cursor } =
+            match list_conversations(&self.config.codex_home, page_size, cursor.as_deref()) {
+                Ok(page) => page,
+                Err(err) => {
+                    let error = JSONRPCErrorError {
+                        code: INTERNAL_ERROR_CODE,
+                        message: format!("failed to list conversations: {err}"),
+                        data: None,
+                    };
+                    self.outgoing.send_error(request_id, error).await;
+                    return;
+                }
+            };
+
+        let items = items
+            .into_iter()
+            .map(|summary| {
+                let git = summary.git.as_ref();
+                ConversationSummary {
+                    conversation_id: ConversationId(summary.id),
+                    git_sha: git
+                        .and_then(|git| git.commit_hash.as_deref())
+                        .map(GitSha::new),
+                    git_branch: git.and_then(|git| git.branch.clone()),
+                    git_origin_url: git.and_then(|git| git.repository_url.clone()),
+                    path: summary.path,
+                    timestamp: summary.timestamp,
+                    preview: summary.preview,
+                }
+            })
+            .collect();
+        let response = ListConversationsResponse { items, next_cursor };
+        self.outgoing.send_response(request_id, response).await;
+    }
+
+    async fn resume_conversation(&self, request_id: RequestId, params: ResumeConversationParams) {
+        let ResumeConversationParams {
+            conversation_id,
+            path,
+            overrides,
+        } = params;
+        let resumed = match self.resolve_rollout(conversation_id, path) {
+            Ok(path) => self.spawn_from_rollout(path, overrides).await,
+            Err(error) => Err(error),
+        };
+        match resumed {
+            Ok(NewConversation {
+                conversation_id,
+                session_configured,
+                ..
+            }) => {
+                let response = ResumeConversationResponse {
+                    conversation_id: ConversationId(conversation_id),
+                    model: session_configured.model,
+                };
+                self.outgoing.send_response(request_id, response).await;
+            }
+            Err(error) => self.outgoing.send_error(request_id, error).await,
+        }
+    }
+
+    async fn fork_conversation(&self, request_id: RequestId, params: ForkConversationParams) {
+        let ForkConversationParams {
+            conversation_id,
+            path,
+            turn_index,
+            overrides,
+        } = params;
+        let path = match self.resolve_rollout(conversation_id, path) {
+            Ok(path) => path,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+        let fork_path = match fork_rollout(&self.config.codex_home, &path, turn_index) {
+            Ok((_, fork_path)) => fork_path,
+            Err(err) => {
+                let code = if err.kind() == std::io::ErrorKind::InvalidInput {
+                    INVALID_REQUEST_ERROR_CODE
+                } else {
+                    INTERNAL_ERROR_CODE
+                };
+                let error = JSONRPCErrorError {
+                    code,
+                    message: format!("failed to fork {}: {err}", path.display()),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+        match self.spawn_from_rollout(fork_path.clone(), overrides).await {
+            Ok(NewConversation {
+                conversation_id,
+                session_configured,
+                ..
+            }) => {
+                let response = ForkConversationResponse {
+                    conversation_id: ConversationId(conversation_id),
+                    model: session_configured.model,
+                    path: fork_path,
+                };
+                self.outgoing.send_response(request_id, response).await;
+            }
+            Err(error) => self.outgoing.send_error(request_id, error).await,
+        }
+    }
+
+    async fn archive_conversation(&self, request_id: RequestId, params: ArchiveConversationParams) {
+        let ArchiveConversationParams {
+            conversation_id,
+            path,
+        } = params;
+        let path = match self.resolve_rollout(conversation_id, path) {
+            Ok(path) => path,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        // Stop a running conversation so nothing appends to the archived file.
+        if let Ok(session_id) = read_rollout_session_id(&path) {
+            if let Some(conversation) = self
+                .conversation_manager
+                .remove_conversation(session_id)
+                .await
+            {
+                let _ = conversation.submit(Op::Shutdown).await;
+            }
+        }
+
+        match archive_rollout(&self.config.codex_home, &path) {
+            Ok(archived_path) => {
+                let response = ArchiveConversationResponse { archived_path };
+                self.outgoing.send_response(request_id, response).await;
+            }
+            Err(err) => {
+                let code = if err.kind() == std::io::ErrorKind::InvalidInput {
+                    INVALID_REQUEST_ERROR_CODE
+                } else {
+                    INTERNAL_ERROR_CODE
+                };
+                let error = JSONRPCErrorError {
+                    code,
+                    message: format!("failed to archive {}: {err}", path.display()),
+                    data: None,
+                };
+                self.outgoing.send_error(request_id, error).await;
+            }
+        }
+    }
+
+    /// Find the rollout named by exactly one of a conversation id or a path.
+    fn resolve_rollout(
+        &self,
+        conversation_id: Option<ConversationId>,
+        path: Option<PathBuf>,
+    ) -> Result<PathBuf, JSONRPCErrorError> {
+        let message = match (conversation_id, path) {
+            (None, Some(path)) => return Ok(path),
+            (Some(conversation_id), None) => {
+                match find_rollout(&self.config.codex_home, &conversation_id.0) {
+                    Some(path) => return Ok(path),
+                    None => format!("no recorded conversation with id {conversation_id}"),
+                }
+            }
+            _ => "expected exactly one of `conversationId` or `path`".to_string(),
+        };
+        Err(JSONRPCErrorError {
+            code: INVALID_REQUEST_ERROR_CODE,
+            message,
+            data: None,
+        })
+    }
+
+    /// Start a conversation that replays the rollout at `path`. The
+    /// conversation keeps the session id recorded in the rollout.
+    async fn spawn_from_rollout(
+        &self,
+        path: PathBuf,
+        overrides: Option<NewConversationParams>,
+    ) -> Result<NewConversation, JSONRPCErrorError> {
+        let invalid_request = |message: String| JSONRPCErrorError {
+            code: INVALID_REQUEST_ERROR_CODE,
+            message,
+            data: None,
+        };
+        let mut config = derive_config_from_params(
+            overrides.unwrap_or_default(),
+            self.codex_linux_sandbox_exe.clone(),
+        )
+        .map_err(|err| invalid_request(format!("error deriving config: {err}")))?;
+        let session_id = read_rollout_session_id(&path)
+            .map_err(|err| invalid_request(format!("failed to read {}: {err}", path.display())))?;
+        if self
+            .conversation_manager
+            .get_conversation(session_id)
+            .await
+            .is_ok()
+        {
+            return Err(invalid_request(format!(
+                "conversation is already running: {session_id}"
+            )));
+        }
+
+        config.experimental_resume = Some(path);
+        self.conversation_manager
+            .new_conversation(config)
+            .await
+            .map_err(|err| JSONRPCErrorError {
+                code: INTERNAL_ERROR_CODE,
+                message: format!("error resuming conversation: {err}"),
+                data: None,
+            })
+    }
+
     async fn git_diff_to_origin(&self, request_id: RequestId, cwd: PathBuf) {
         let diff = git_diff_to_remote(&cwd).await;
         match diff {
@@ -495,6 +762,7 @@ async fn apply_bespoke_event_handling(
             command,
             cwd,
             reason,
+            grant_root: _,
         }) => {
             let params = ExecCommandApprovalParams {
                 conversation_id,
@@ -0,0 +1,203 @@
+//! The `/plan`, `/solve` and `/code` slash commands, exposed as MCP prompts so
+//! clients can expand them without going through a Codex session.
+
+use codex_core::config_types::AgentConfig;
+use codex_core::slash_commands::format_code_command;
+use codex_core::slash_commands::format_plan_command;
+use codex_core::slash_commands::format_solve_command;
+use codex_core::slash_commands::get_enabled_agents;
+use mcp_types::ContentBlock;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::GetPromptResult;
+use mcp_types::Prompt;
+use mcp_types::PromptArgument;
+use mcp_types::PromptMessage;
+use mcp_types::Role;
+use mcp_types::TextContent;
+
+type PromptFormatter = fn(&str, Option<Vec<String>>, Option<&[AgentConfig]>) -> String;
+
+struct CodexPrompt {
+    name: &'static str,
+    title: &'static str,
+    description: &'static str,
+    task_description: &'static str,
+    format: PromptFormatter,
+}
+
+const PROMPTS: &[CodexPrompt] = &[
+    CodexPrompt {
+        name: "plan",
+        title: "Plan",
+        description: "Create a comprehensive plan with multiple agents working in parallel",
+        task_description: "The task to plan",
+        format: format_plan_command,
+    },
+    CodexPrompt {
+        name: "solve",
+        title: "Solve",
+        description: "Solve a complicated problem by racing multiple agents",
+        task_description: "The problem to solve",
+        format: format_solve_command,
+    },
+    CodexPrompt {
+        name: "code",
+        title: "Code",
+        description: "Perform a coding task with multiple agents and merge the best result",
+        task_description: "The coding task to perform",
+        format: format_code_command,
+    },
+];
+
+pub(crate) fn list_prompts() -> Vec<Prompt> {
+    PROMPTS
+        .iter()
+        .map(|prompt| Prompt {
+            arguments: Some(vec![
+                PromptArgument {
+                    description: Some(prompt.task_description.to_string()),
+                    name: "task".to_string(),
+                    required: Some(true),
+                    title: Some("Task".to_string()),
+                },
+                PromptArgument {
+                    description: Some(
+                        "Comma-separated agents to use; defaults to the enabled agents".to_string(),
+                    ),
+                    name: "models".to_string(),
+                    required: Some(false),
+                    title: Some("Models".to_string()),
+                },
+            ]),
+            description: Some(prompt.description.to_string()),
+            name: prompt.name.to_string(),
+            title: Some(prompt.title.to_string()),
+        })
+        .collect()
+}
+
+/// Expand a prompt into the single user message the slash command would
+/// submit. Errors are human-readable and meant for `invalid params`.
+pub(crate) fn get_prompt(
+    params: GetPromptRequestParams,
+    agents: &[AgentConfig],
+) -> Result<GetPromptResult, String> {
+    let GetPromptRequestParams { arguments, name } = params;
+    let prompt = PROMPTS
+        .iter()
+        .find(|prompt| prompt.name == name)
+        .ok_or_else(|| format!("unknown prompt: {name}"))?;
+
+    let argument = |key: &str| {
+        arguments
+            .as_ref()
+            .and_then(|arguments| arguments.get(key))
+            .and_then(|value| value.as_str())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+    let task = argument("task").ok_or_else(|| format!("prompt `{name}` requires a `task`"))?;
+    let models = argument("models").map(|models| {
+        models
+            .split(',')
+            .map(str::trim)
+            .filter(|model| !model.is_empty())
+            .map(str::to_string)
+            .collect()
+    });
+
+    Ok(GetPromptResult {
+        description: Some(prompt.description.to_string()),
+        messages: vec![PromptMessage {
+            content: ContentBlock::TextContent(TextContent {
+                annotations: None,
+                text: (prompt.format)(task, models, Some(agents)),
+                r#type: "text".to_string(),
+            }),
+            role: Role::User,
+        }],
+    })
+}
+
+/// Complete the last entry of a comma-separated `models` argument from the
+/// enabled agents. Other arguments have no completions.
+pub(crate) fn complete_prompt_argument(
+    prompt: &str,
+    argument: &str,
+    value: &str,
+    agents: &[AgentConfig],
+) -> Vec<String> {
+    if argument != "models" || !PROMPTS.iter().any(|p| p.name == prompt) {
+        return Vec::new();
+    }
+    let partial = value.rsplit(',').next().unwrap_or(value).trim_start();
+    let chosen = &value[..value.len() - partial.len()];
+    get_enabled_agents(agents)
+        .into_iter()
+        .filter(|agent| agent.starts_with(partial))
+        .map(|agent| format!("{chosen}{agent}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn agent(name: &str, enabled: bool) -> AgentConfig {
+        serde_json::from_value(json!({ "name": name, "command": name, "enabled": enabled }))
+            .expect("agent config")
+    }
+
+    #[test]
+    fn get_prompt_expands_task_and_models() {
+        let result = get_prompt(
+            GetPromptRequestParams {
+                arguments: Some(json!({ "task": "fix the bug", "models": "claude, gemini" })),
+                name: "solve".to_string(),
+            },
+            &[],
+        )
+        .expect("prompt");
+
+        let [
+            PromptMessage {
+                content: ContentBlock::TextContent(text),
+                role: Role::User,
+            },
+        ] = result.messages.as_slice()
+        else {
+            panic!("expected a single user text message: {result:?}");
+        };
+        assert!(text.text.contains("fix the bug"));
+        assert!(text.text.contains("\"claude\", \"gemini\""));
+    }
+
+    #[test]
+    fn get_prompt_requires_task() {
+        let err = get_prompt(
+            GetPromptRequestParams {
+                arguments: None,
+                name: "plan".to_string(),
+            },
+            &[],
+        )
+        .expect_err("missing task");
+        assert_eq!(err, "prompt `plan` requires a `task`");
+    }
+
+    #[test]
+    fn completes_last_model_from_enabled_agents() {
+        let agents = [
+            agent("claude", true),
+            agent("codex", true),
+            agent("cursor", false),
+        ];
+        assert_eq!(
+            complete_prompt_argument("code", "models", "claude, c", &agents),
+            vec!["claude, claude".to_string(), "claude, codex".to_string()]
+        );
+        assert!(complete_prompt_argument("code", "task", "c", &agents).is_empty());
+    }
+}
@@ -0,0 +1,402 @@
+//! Resources served over MCP: the `AGENTS.md` files that apply to the
+//! server's working directory, session rollouts recorded under
+//! `$CODEX_HOME/sessions`, and the latest turn diff of every conversation
+//! running in this server.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use codex_core::config::Config;
+use codex_core::find_rollout;
+use codex_core::project_doc::discover_project_doc_paths;
+use codex_core::recorded_rollouts;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::ModelContextProtocolNotification;
+use mcp_types::ReadResourceResultContents;
+use mcp_types::Resource;
+use mcp_types::ResourceListChangedNotification;
+use mcp_types::ResourceTemplate;
+use mcp_types::ResourceUpdatedNotification;
+use mcp_types::ResourceUpdatedNotificationParams;
+use mcp_types::TextResourceContents;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::error_code::INTERNAL_ERROR_CODE;
+use crate::error_code::RESOURCE_NOT_FOUND_ERROR_CODE;
+use crate::outgoing_message::OutgoingMessageSender;
+use crate::outgoing_message::OutgoingNotification;
+
+const SESSION_URI_PREFIX: &str = "codex://sessions/";
+const CONVERSATION_URI_PREFIX: &str = "codex://conversations/";
+const TURN_DIFF_URI_SUFFIX: &str = "/turn-diff";
+
+/// Only the most recent rollouts are listed; older ones remain readable
+/// through the `codex://sessions/{session_id}` template.
+const MAX_LISTED_SESSIONS: usize = 50;
+
+/// Maximum number of values returned for a single completion request.
+const MAX_COMPLETIONS: usize = 100;
+
+/// How often file-backed subscriptions check for changes.
+const SUBSCRIPTION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A resource URI resolved to what backs it.
+#[derive(Debug, Clone, PartialEq)]
+enum ResourceKind {
+    ProjectDoc(PathBuf),
+    Session(Uuid),
+    TurnDiff(Uuid),
+}
+
+pub(crate) struct CodexResources {
+    config: Arc<Config>,
+    outgoing: Arc<OutgoingMessageSender>,
+    /// Latest unified diff per conversation, as reported by `TurnDiff` events.
+    turn_diffs: Mutex<HashMap<Uuid, String>>,
+    /// Subscribed URIs. File-backed resources carry the task polling them.
+    subscriptions: Mutex<HashMap<String, Option<JoinHandle<()>>>>,
+}
+
+impl CodexResources {
+    pub(crate) fn new(config: Arc<Config>, outgoing: Arc<OutgoingMessageSender>) -> Self {
+        Self {
+            config,
+            outgoing,
+            turn_diffs: Mutex::new(HashMap::new()),
+            subscriptions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) async fn list(&self) -> Vec<Resource> {
+        let mut resources = Vec::new();
+
+        for path in self.project_doc_paths() {
+            let size = std::fs::metadata(&path).ok().map(|m| m.len() as i64);
+            resources.push(Resource {
+                annotations: None,
+                description: Some(format!("Project instructions from {}", path.display())),
+                mime_type: Some("text/markdown".to_string()),
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                size,
+                title: None,
+                uri: file_uri(&path),
+            });
+        }
+
+        for (session_id, path) in recorded_rollouts(&self.config.codex_home)
+            .into_iter()
+            .take(MAX_LISTED_SESSIONS)
+        {
+            let size = std::fs::metadata(&path).ok().map(|m| m.len() as i64);
+            resources.push(Resource {
+                annotations: None,
+                description: Some("Recorded session rollout (JSONL)".to_string()),
+                mime_type: Some("application/x-ndjson".to_string()),
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                size,
+                title: None,
+                uri: session_uri(&session_id),
+            });
+        }
+
+        let turn_diffs = self.turn_diffs.lock().await;
+        let mut conversation_ids: Vec<&Uuid> = turn_diffs.keys().collect();
+        conversation_ids.sort();
+        for conversation_id in conversation_ids {
+            resources.push(Resource {
+                annotations: None,
+                description: Some(
+                    "Changes made to the workspace during the conversation's current turn"
+                        .to_string(),
+                ),
+                mime_type: Some("text/x-diff".to_string()),
+                name: format!("turn-diff-{conversation_id}"),
+                size: turn_diffs
+                    .get(conversation_id)
+                    .map(|diff| diff.len() as i64),
+                title: None,
+                uri: turn_diff_uri(conversation_id),
+            });
+        }
+
+        resources
+    }
+
+    pub(crate) fn templates() -> Vec<ResourceTemplate> {
+        vec![
+            ResourceTemplate {
+                annotations: None,
+                description: Some("Rollout of a recorded session (JSONL)".to_string()),
+                mime_type: Some("application/x-ndjson".to_string()),
+                name: "session".to_string(),
+                title: Some("Session rollout".to_string()),
+                uri_template: format!("{SESSION_URI_PREFIX}{{session_id}}"),
+            },
+            ResourceTemplate {
+                annotations: None,
+                description: Some(
+                    "Latest turn diff of a conversation running in this server".to_string(),
+                ),
+                mime_type: Some("text/x-diff".to_string()),
+                name: "turn-diff".to_string(),
+                title: Some("Turn diff".to_string()),
+                uri_template: format!(
+                    "{CONVERSATION_URI_PREFIX}{{conversation_id}}{TURN_DIFF_URI_SUFFIX}"
+                ),
+            },
+        ]
+    }
+
+    pub(crate) async fn read(
+        &self,
+        uri: &str,
+    ) -> Result<ReadResourceResultContents, JSONRPCErrorError> {
+        let (text, mime_type) = match self.resolve(uri)? {
+            ResourceKind::ProjectDoc(path) => (read_to_string(&path, uri).await?, "text/markdown"),
+            ResourceKind::Session(session_id) => {
+                let path = find_rollout(&self.config.codex_home, &session_id)
+                    .ok_or_else(|| resource_not_found(uri))?;
+                (read_to_string(&path, uri).await?, "application/x-ndjson")
+            }
+            ResourceKind::TurnDiff(conversation_id) => {
+                let diff = self
+                    .turn_diffs
+                    .lock()
+                    .await
+                    .get(&conversation_id)
+                    .cloned()
+                    .ok_or_else(|| resource_not_found(uri))?;
+                (diff, "text/x-diff")
+            }
+        };
+
+        Ok(ReadResourceResultContents::TextResourceContents(
+            TextResourceContents {
+                mime_type: Some(mime_type.to_string()),
+                text,
+                uri: uri.to_string(),
+            },
+        ))
+    }
+
+    /// Start pushing `notifications/resources/updated` for `uri`. Turn diffs
+    /// are pushed as their events arrive; files are polled for changes.
+    pub(crate) async fn subscribe(self: &Arc<Self>, uri: &str) -> Result<(), JSONRPCErrorError> {
+        let watched_path = match self.resolve(uri)? {
+            ResourceKind::ProjectDoc(path) => Some(path),
+            ResourceKind::Session(session_id) => Some(
+                find_rollout(&self.config.codex_home, &session_id)
+                    .ok_or_else(|| resource_not_found(uri))?,
+            ),
+            ResourceKind::TurnDiff(_) => None,
+        };
+
+        let mut subscriptions = self.subscriptions.lock().await;
+        if subscriptions.contains_key(uri) {
+            return Ok(());
+        }
+        let poller = watched_path.map(|path| {
+            let outgoing = self.outgoing.clone();
+            let uri = uri.to_string();
+            tokio::spawn(poll_file_for_changes(path, uri, outgoing))
+        });
+        subscriptions.insert(uri.to_string(), poller);
+        Ok(())
+    }
+
+    pub(crate) async fn unsubscribe(&self, uri: &str) {
+        if let Some(Some(poller)) = self.subscriptions.lock().await.remove(uri) {
+            poller.abort();
+        }
+    }
+
+    /// Record the latest diff for a conversation, announcing the resource the
+    /// first time it appears and notifying subscribers on every change.
+    pub(crate) async fn record_turn_diff(&self, conversation_id: Uuid, unified_diff: String) {
+        let previous = self
+            .turn_diffs
+            .lock()
+            .await
+            .insert(conversation_id, unified_diff.clone());
+
+        match previous {
+            None => {
+                self.outgoing
+                    .send_notification(OutgoingNotification {
+                        method: ResourceListChangedNotification::METHOD.to_string(),
+                        params: None,
+                    })
+                    .await;
+            }
+            Some(previous) if previous == unified_diff => return,
+            Some(_) => {}
+        }
+
+        let uri = turn_diff_uri(&conversation_id);
+        if self.subscriptions.lock().await.contains_key(&uri) {
+            send_resource_updated(&self.outgoing, uri).await;
+        }
+    }
+
+    /// Complete a `session_id` or `conversation_id` template argument by
+    /// prefix.
+    pub(crate) async fn complete_argument(&self, name: &str, value: &str) -> Vec<String> {
+        let candidates: Vec<String> = match name {
+            "session_id" => recorded_rollouts(&self.config.codex_home)
+                .into_iter()
+                .map(|(session_id, _)| session_id.to_string())
+                .collect(),
+            "conversation_id" => self
+                .turn_diffs
+                .lock()
+                .await
+                .keys()
+                .map(Uuid::to_string)
+                .collect(),
+            _ => Vec::new(),
+        };
+        candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(value))
+            .take(MAX_COMPLETIONS)
+            .collect()
+    }
+
+    fn project_doc_paths(&self) -> Vec<PathBuf> {
+        match discover_project_doc_paths(&self.config) {
+            Ok(paths) => paths,
+            Err(e) => {
+                tracing::warn!("failed to discover project docs: {e}");
+                Vec::new()
+            }
+        }
+    }
+
+    fn resolve(&self, uri: &str) -> Result<ResourceKind, JSONRPCErrorError> {
+        if let Some(path) = uri.strip_prefix("file://") {
+            // Only the project docs that apply to this server are readable.
+            let path = PathBuf::from(path);
+            return if self.project_doc_paths().contains(&path) {
+                Ok(ResourceKind::ProjectDoc(path))
+            } else {
+                Err(resource_not_found(uri))
+            };
+        }
+        parse_codex_uri(uri).ok_or_else(|| resource_not_found(uri))
+    }
+}
+
+fn parse_codex_uri(uri: &str) -> Option<ResourceKind> {
+    if let Some(session_id) = uri.strip_prefix(SESSION_URI_PREFIX) {
+        return Uuid::parse_str(session_id).ok().map(ResourceKind::Session);
+    }
+    let conversation_id = uri
+        .strip_prefix(CONVERSATION_URI_PREFIX)?
+        .strip_suffix(TURN_DIFF_URI_SUFFIX)?;
+    Uuid::parse_str(conversation_id)
+        .ok()
+        .map(ResourceKind::TurnDiff)
+}
+
+fn file_uri(path: &Path) -> String {
+    format!("file://{}", path.display())
+}
+
+fn session_uri(session_id: &Uuid) -> String {
+    format!("{SESSION_URI_PREFIX}{session_id}")
+}
+
+fn turn_diff_uri(conversation_id: &Uuid) -> String {
+    format!("{CONVERSATION_URI_PREFIX}{conversation_id}{TURN_DIFF_URI_SUFFIX}")
+}
+
+fn resource_not_found(uri: &str) -> JSONRPCErrorError {
+    JSONRPCErrorError {
+        code: RESOURCE_NOT_FOUND_ERROR_CODE,
+        message: format!("resource not found: {uri}"),
+        data: Some(serde_json::json!({ "uri": uri })),
+    }
+}
+
+async fn read_to_string(path: &Path, uri: &str) -> Result<String, JSONRPCErrorError> {
+    tokio::fs::read_to_string(path).await.map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            resource_not_found(uri)
+        } else {
+            JSONRPCErrorError {
+                code: INTERNAL_ERROR_CODE,
+                message: format!("failed to read {}: {e}", path.display()),
+                data: None,
+            }
+        }
+    })
+}
+
+async fn poll_file_for_changes(path: PathBuf, uri: String, outgoing: Arc<OutgoingMessageSender>) {
+    let fingerprint = |path: &Path| -> Option<(u64, SystemTime)> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some((metadata.len(), metadata.modified().ok()?))
+    };
+
+    let mut last = fingerprint(&path);
+    loop {
+        tokio::time::sleep(SUBSCRIPTION_POLL_INTERVAL).await;
+        let current = fingerprint(&path);
+        if current != last {
+            last = current;
+            send_resource_updated(&outgoing, uri.clone()).await;
+        }
+    }
+}
+
+async fn send_resource_updated(outgoing: &OutgoingMessageSender, uri: String) {
+    let params = match serde_json::to_value(ResourceUpdatedNotificationParams { uri }) {
+        Ok(params) => params,
+        Err(e) => {
+            tracing::error!("failed to serialize resource update: {e}");
+            return;
+        }
+    };
+    outgoing
+        .send_notification(OutgoingNotification {
+            method: ResourceUpdatedNotification::METHOD.to_string(),
+            params: Some(params),
+        })
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_session_and_turn_diff_uris() {
+        let id = Uuid::new_v4();
+        assert_eq!(
+            parse_codex_uri(&session_uri(&id)),
+            Some(ResourceKind::Session(id))
+        );
+        assert_eq!(
+            parse_codex_uri(&turn_diff_uri(&id)),
+            Some(ResourceKind::TurnDiff(id))
+        );
+        assert_eq!(parse_codex_uri("codex://sessions/not-a-uuid"), None);
+        assert_eq!(
+            parse_codex_uri(&format!("{CONVERSATION_URI_PREFIX}{id}")),
+            None
+        );
+    }
+}
@@ -18,6 +18,7 @@ use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
 use codex_core::protocol::Submission;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TurnDiffEvent;
 use mcp_types::CallToolResult;
 use mcp_types::ContentBlock;
 use mcp_types::RequestId;
@@ -26,7 +27,10 @@ use serde_json::json;
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+use crate::codex_resources::CodexResources;
 use crate::exec_approval::handle_exec_approval_request;
+use crate::mcp_client_requests::decline_mcp_elicitation;
+use crate::mcp_client_requests::deny_mcp_sampling;
 use crate::outgoing_message::OutgoingMessageSender;
 use crate::outgoing_message::OutgoingNotificationMeta;
 use crate::patch_approval::handle_patch_approval_request;
@@ -44,6 +48,7 @@ pub async fn run_codex_tool_session(
     outgoing: Arc<OutgoingMessageSender>,
     conversation_manager: Arc<ConversationManager>,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, Uuid>>>,
+    resources: Arc<CodexResources>,
 ) {
     let NewConversation {
         conversation_id,
@@ -107,9 +112,11 @@ pub async fn run_codex_tool_session(
 
     run_codex_tool_session_inner(
         conversation,
+        conversation_id,
         outgoing,
         id,
         running_requests_id_to_codex_uuid,
+        resources,
     )
     .await;
 }
@@ -121,6 +128,7 @@ pub async fn run_codex_tool_session_reply(
     prompt: String,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, Uuid>>>,
     session_id: Uuid,
+    resources: Arc<CodexResources>,
 ) {
     running_requests_id_to_codex_uuid
         .lock()
@@ -143,18 +151,22 @@ pub async fn run_codex_tool_session_reply(
 
     run_codex_tool_session_inner(
         conversation,
+        session_id,
         outgoing,
         request_id,
         running_requests_id_to_codex_uuid,
+        resources,
     )
     .await;
 }
 
 async fn run_codex_tool_session_inner(
     codex: Arc<CodexConversation>,
+    conversation_id: Uuid,
     outgoing: Arc<OutgoingMessageSender>,
     request_id: RequestId,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, Uuid>>>,
+    resources: Arc<CodexResources>,
 ) {
     let request_id_str = match &request_id {
         RequestId::String(s) => s.clone(),
@@ -179,6 +191,7 @@ async fn run_codex_tool_session_inner(
                         cwd,
                         call_id,
                         reason: _,
+                        grant_root: _,
                     }) => {
                         handle_exec_approval_request(
                             command,
@@ -221,6 +234,12 @@ async fn run_codex_tool_session_inner(
                         .await;
                         continue;
                     }
+                    EventMsg::McpElicitationRequest(request) => {
+                        decline_mcp_elicitation(&codex, request).await;
+                    }
+                    EventMsg::McpSamplingRequest(request) => {
+                        deny_mcp_sampling(&codex, request).await;
+                    }
                     EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
                         let text = match last_agent_message {
                             Some(msg) => msg.clone(),
@@ -243,6 +262,11 @@ async fn run_codex_tool_session_inner(
                             .remove(&request_id);
                         break;
                     }
+                    EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => {
+                        resources
+                            .record_turn_diff(conversation_id, unified_diff)
+                            .await;
+                    }
                     EventMsg::SessionConfigured(_) => {
                         tracing::error!("unexpected SessionConfigured event");
                     }
@@ -263,6 +287,7 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::AgentReasoning(_)
                     | EventMsg::AgentReasoningSectionBreak(_)
                     | EventMsg::McpToolCallBegin(_)
+                    | EventMsg::McpToolCallProgress(_)
                     | EventMsg::McpToolCallEnd(_)
                     // | EventMsg::McpListToolsResponse(_)
                     | EventMsg::ExecCommandBegin(_)
@@ -271,11 +296,14 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::BackgroundEvent(_)
                     | EventMsg::PatchApplyBegin(_)
                     | EventMsg::PatchApplyEnd(_)
-                    | EventMsg::TurnDiff(_)
                     | EventMsg::GetHistoryEntryResponse(_)
                     | EventMsg::PlanUpdate(_)
                     | EventMsg::BrowserScreenshotUpdate(_)
                     | EventMsg::AgentStatusUpdate(_)
+                    | EventMsg::BackgroundProcessUpdate(_)
+                    | EventMsg::ExecSnapshot(_)
+                    | EventMsg::McpPrompts(_)
+                    | EventMsg::McpListServersResponse(_)
                     | EventMsg::ShutdownComplete
                     | EventMsg::CustomToolCallBegin(_)
                     | EventMsg::CustomToolCallEnd(_) => {
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
 use crate::exec_approval::handle_exec_approval_request;
+use crate::mcp_client_requests::decline_mcp_elicitation;
+use crate::mcp_client_requests::deny_mcp_sampling;
 use crate::outgoing_message::OutgoingMessageSender;
 use crate::outgoing_message::OutgoingNotificationMeta;
 use crate::patch_approval::handle_patch_approval_request;
@@ -40,6 +42,7 @@ pub async fn run_conversation_loop(
                         cwd,
                         call_id,
                         reason: _,
+                        grant_root: _,
                     }) => {
                         handle_exec_approval_request(
                             command,
@@ -77,6 +80,12 @@ pub async fn run_conversation_loop(
                         .await;
                         continue;
                     }
+                    EventMsg::McpElicitationRequest(request) => {
+                        decline_mcp_elicitation(&codex, request).await;
+                    }
+                    EventMsg::McpSamplingRequest(request) => {
+                        deny_mcp_sampling(&codex, request).await;
+                    }
                     EventMsg::TaskComplete(_) => {}
                     EventMsg::SessionConfigured(_) => {
                         tracing::error!("unexpected SessionConfigured event");
@@ -98,6 +107,7 @@ pub async fn run_conversation_loop(
                     | EventMsg::AgentReasoning(_)
                     | EventMsg::AgentReasoningSectionBreak(_)
                     | EventMsg::McpToolCallBegin(_)
+                    | EventMsg::McpToolCallProgress(_)
                     | EventMsg::McpToolCallEnd(_)
                     | EventMsg::ExecCommandBegin(_)
                     | EventMsg::ExecCommandEnd(_)
@@ -110,6 +120,10 @@ pub async fn run_conversation_loop(
                     | EventMsg::PlanUpdate(_)
                     | EventMsg::BrowserScreenshotUpdate(_)
                     | EventMsg::AgentStatusUpdate(_)
+                    | EventMsg::BackgroundProcessUpdate(_)
+                    | EventMsg::ExecSnapshot(_)
+                    | EventMsg::McpPrompts(_)
+                    | EventMsg::McpListServersResponse(_)
                     | EventMsg::ShutdownComplete
                     | EventMsg::CustomToolCallBegin(_)
                     | EventMsg::CustomToolCallEnd(_) => {
@@ -1,2 +1,5 @@
 pub(crate) const INVALID_REQUEST_ERROR_CODE: i64 = -32600;
 pub(crate) const INTERNAL_ERROR_CODE: i64 = -32603;
+/// MCP's error code for `resources/read` and `resources/subscribe` on an
+/// unknown URI.
+pub(crate) const RESOURCE_NOT_FOUND_ERROR_CODE: i64 = -32002;
@@ -4,6 +4,7 @@
 use std::io::ErrorKind;
 use std::io::Result as IoResult;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use codex_common::CliConfigOverrides;
 use codex_core::config::Config;
@@ -19,17 +20,27 @@ use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
 mod codex_message_processor;
+mod codex_prompts;
+mod codex_resources;
 mod codex_tool_config;
 mod codex_tool_runner;
 mod error_code;
 mod exec_approval;
 mod json_to_toml;
+mod mcp_client_requests;
+mod mcp_logging;
 pub(crate) mod message_processor;
 mod outgoing_message;
 mod patch_approval;
+mod standalone_tools;
 
+use crate::mcp_logging::ClientLogLevel;
+use crate::mcp_logging::McpLoggingLayer;
 use crate::message_processor::MessageProcessor;
 use crate::outgoing_message::OutgoingMessage;
 use crate::outgoing_message::OutgoingMessageSender;
@@ -40,6 +51,10 @@ pub use crate::exec_approval::ExecApprovalElicitRequestParams;
 pub use crate::exec_approval::ExecApprovalResponse;
 pub use crate::patch_approval::PatchApprovalElicitRequestParams;
 pub use crate::patch_approval::PatchApprovalResponse;
+pub use crate::standalone_tools::ApplyPatchToolParam;
+pub use crate::standalone_tools::CodeSearchToolParam;
+pub use crate::standalone_tools::FileSearchToolParam;
+pub use crate::standalone_tools::ParseCommandToolParam;
 
 /// Size of the bounded channels used to communicate between tasks. The value
 /// is a balance between throughput and memory usage – 128 messages should be
@@ -50,17 +65,26 @@ pub async fn run_main(
     codex_linux_sandbox_exe: Option<PathBuf>,
     cli_config_overrides: CliConfigOverrides,
 ) -> IoResult<()> {
-    // Install a simple subscriber so `tracing` output is visible.  Users can
-    // control the log level with `RUST_LOG`.
-    tracing_subscriber::fmt()
-        .with_writer(std::io::stderr)
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
-
     // Set up channels.
     let (incoming_tx, mut incoming_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
     let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<OutgoingMessage>(CHANNEL_CAPACITY);
 
+    // Install a simple subscriber so `tracing` output is visible.  Users can
+    // control the stderr log level with `RUST_LOG`; clients pick the level of
+    // the `notifications/message` stream with `logging/setLevel`.
+    let client_log_level = Arc::new(ClientLogLevel::default());
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_filter(EnvFilter::from_default_env()),
+        )
+        .with(McpLoggingLayer::new(
+            client_log_level.clone(),
+            outgoing_tx.downgrade(),
+        ))
+        .init();
+
     // Task: read from stdin, push to `incoming_tx`.
     let stdin_reader_handle = tokio::spawn({
         let incoming_tx = incoming_tx.clone();
@@ -104,7 +128,8 @@ pub async fn run_main(
         let mut processor = MessageProcessor::new(
             outgoing_message_sender,
             codex_linux_sandbox_exe,
-            std::sync::Arc::new(config),
+            Arc::new(config),
+            client_log_level,
         );
         async move {
             while let Some(msg) = incoming_rx.recv().await {
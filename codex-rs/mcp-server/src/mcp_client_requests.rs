@@ -0,0 +1,37 @@
+//! Requests from the MCP servers Codex itself connects to (elicitation,
+//! sampling) are answered for the user of the front-end. When Codex runs as
+//! an MCP server there is no such user to ask, so they are refused.
+
+use codex_core::CodexConversation;
+use codex_core::protocol::ElicitationAction;
+use codex_core::protocol::McpElicitationRequestEvent;
+use codex_core::protocol::McpSamplingRequestEvent;
+use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
+use tracing::error;
+
+pub(crate) async fn decline_mcp_elicitation(
+    codex: &CodexConversation,
+    event: McpElicitationRequestEvent,
+) {
+    let op = Op::McpElicitationResponse {
+        server: event.server,
+        request_id: event.request_id,
+        action: ElicitationAction::Decline,
+        content: None,
+    };
+    if let Err(err) = codex.submit(op).await {
+        error!("failed to submit McpElicitationResponse: {err}");
+    }
+}
+
+pub(crate) async fn deny_mcp_sampling(codex: &CodexConversation, event: McpSamplingRequestEvent) {
+    let op = Op::McpSamplingApproval {
+        server: event.server,
+        request_id: event.request_id,
+        decision: ReviewDecision::Denied,
+    };
+    if let Err(err) = codex.submit(op).await {
+        error!("failed to submit McpSamplingApproval: {err}");
+    }
+}
@@ -0,0 +1,190 @@
+//! Forwards `tracing` events to the client as `notifications/message` once it
+//! has opted in with `logging/setLevel`.
+
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use mcp_types::LoggingLevel;
+use mcp_types::LoggingMessageNotification;
+use mcp_types::LoggingMessageNotificationParams;
+use mcp_types::ModelContextProtocolNotification;
+use serde_json::Map;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tracing::Event;
+use tracing::Level;
+use tracing::Subscriber;
+use tracing::field::Field;
+use tracing::field::Visit;
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+
+use crate::outgoing_message::OutgoingMessage;
+use crate::outgoing_message::OutgoingNotification;
+
+/// Minimum severity requested by the client. Nothing is forwarded until the
+/// client sends `logging/setLevel`.
+#[derive(Debug, Default)]
+pub(crate) struct ClientLogLevel {
+    level: RwLock<Option<LoggingLevel>>,
+}
+
+impl ClientLogLevel {
+    pub(crate) fn set(&self, level: LoggingLevel) {
+        if let Ok(mut guard) = self.level.write() {
+            *guard = Some(level);
+        }
+    }
+
+    fn allows(&self, level: &LoggingLevel) -> bool {
+        match self.level.read() {
+            Ok(guard) => guard
+                .as_ref()
+                .is_some_and(|minimum| severity(level) >= severity(minimum)),
+            Err(_) => false,
+        }
+    }
+}
+
+/// `tracing` layer that mirrors events from the workspace crates to the
+/// client. Holds a weak sender so it never keeps the stdout writer alive
+/// during shutdown, and drops messages rather than block when the channel is
+/// full.
+pub(crate) struct McpLoggingLayer {
+    level: Arc<ClientLogLevel>,
+    outgoing: mpsc::WeakSender<OutgoingMessage>,
+}
+
+impl McpLoggingLayer {
+    pub(crate) fn new(
+        level: Arc<ClientLogLevel>,
+        outgoing: mpsc::WeakSender<OutgoingMessage>,
+    ) -> Self {
+        Self { level, outgoing }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for McpLoggingLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let target = metadata.target();
+        if !(target.starts_with("codex") || target.starts_with("mcp")) {
+            return;
+        }
+        let level = logging_level(metadata.level());
+        if !self.level.allows(&level) {
+            return;
+        }
+        let Some(outgoing) = self.outgoing.upgrade() else {
+            return;
+        };
+
+        let mut visitor = JsonVisitor::default();
+        event.record(&mut visitor);
+        let params = LoggingMessageNotificationParams {
+            data: Value::Object(visitor.fields),
+            level,
+            logger: Some(target.to_string()),
+        };
+        let Ok(params) = serde_json::to_value(params) else {
+            return;
+        };
+        // Logging from here would recurse into this layer, so a full or
+        // closed channel silently drops the message.
+        let _ = outgoing.try_send(OutgoingMessage::Notification(OutgoingNotification {
+            method: LoggingMessageNotification::METHOD.to_string(),
+            params: Some(params),
+        }));
+    }
+}
+
+#[derive(Default)]
+struct JsonVisitor {
+    fields: Map<String, Value>,
+}
+
+impl Visit for JsonVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.fields.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.fields.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.fields.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.fields
+            .insert(field.name().to_string(), format!("{value:?}").into());
+    }
+}
+
+fn logging_level(level: &Level) -> LoggingLevel {
+    match *level {
+        Level::ERROR => LoggingLevel::Error,
+        Level::WARN => LoggingLevel::Warning,
+        Level::INFO => LoggingLevel::Info,
+        Level::DEBUG | Level::TRACE => LoggingLevel::Debug,
+    }
+}
+
+/// RFC-5424 ordering, least severe first.
+fn severity(level: &LoggingLevel) -> u8 {
+    match level {
+        LoggingLevel::Debug => 0,
+        LoggingLevel::Info => 1,
+        LoggingLevel::Notice => 2,
+        LoggingLevel::Warning => 3,
+        LoggingLevel::Error => 4,
+        LoggingLevel::Critical => 5,
+        LoggingLevel::Alert => 6,
+        LoggingLevel::Emergency => 7,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn forwards_events_at_or_above_the_client_level() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let level = Arc::new(ClientLogLevel::default());
+        let subscriber = tracing_subscriber::registry()
+            .with(McpLoggingLayer::new(level.clone(), tx.downgrade()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::error!(target: "codex_core", "before setLevel");
+            level.set(LoggingLevel::Warning);
+            tracing::info!(target: "codex_core", "below the level");
+            tracing::warn!(target: "hyper", "not a workspace crate");
+            tracing::warn!(target: "codex_core", attempt = 2, "retrying");
+        });
+
+        let Ok(OutgoingMessage::Notification(notification)) = rx.try_recv() else {
+            panic!("expected a log notification");
+        };
+        assert_eq!(notification.method, "notifications/message");
+        assert_eq!(
+            notification.params,
+            Some(serde_json::json!({
+                "data": { "message": "retrying", "attempt": 2 },
+                "level": "warning",
+                "logger": "codex_core",
+            }))
+        );
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -2,11 +2,20 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::codex_message_processor::CodexMessageProcessor;
+use crate::codex_prompts::complete_prompt_argument;
+use crate::codex_prompts::get_prompt;
+use crate::codex_prompts::list_prompts;
+use crate::codex_resources::CodexResources;
 use crate::codex_tool_config::CodexToolCallParam;
 use crate::codex_tool_config::CodexToolCallReplyParam;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_param;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_reply_param;
+use crate::codex_tool_runner::INVALID_PARAMS_ERROR_CODE;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
+use crate::mcp_logging::ClientLogLevel;
+use crate::standalone_tools::create_standalone_tools;
+use crate::standalone_tools::is_standalone_tool;
+use crate::standalone_tools::run_standalone_tool;
 use crate::outgoing_message::OutgoingMessageSender;
 use codex_protocol::mcp_protocol::ClientRequest;
 
@@ -16,15 +25,24 @@ use codex_core::protocol::Submission;
 use mcp_types::CallToolRequestParams;
 use mcp_types::CallToolResult;
 use mcp_types::ClientRequest as McpClientRequest;
+use mcp_types::CompleteRequestParamsRef;
+use mcp_types::CompleteResult;
+use mcp_types::CompleteResultCompletion;
 use mcp_types::ContentBlock;
 use mcp_types::JSONRPCError;
 use mcp_types::JSONRPCErrorError;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ListPromptsResult;
+use mcp_types::ListResourceTemplatesResult;
+use mcp_types::ListResourcesResult;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ReadResourceResult;
 use mcp_types::RequestId;
+use mcp_types::ServerCapabilitiesPrompts;
+use mcp_types::ServerCapabilitiesResources;
 use mcp_types::ServerCapabilitiesTools;
 use mcp_types::ServerNotification;
 use mcp_types::TextContent;
@@ -41,6 +59,9 @@ pub(crate) struct MessageProcessor {
     codex_linux_sandbox_exe: Option<PathBuf>,
     conversation_manager: Arc<ConversationManager>,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, Uuid>>>,
+    config: Arc<Config>,
+    resources: Arc<CodexResources>,
+    client_log_level: Arc<ClientLogLevel>,
 }
 
 impl MessageProcessor {
@@ -49,14 +70,18 @@ impl MessageProcessor {
     pub(crate) fn new(
         outgoing: OutgoingMessageSender,
         codex_linux_sandbox_exe: Option<PathBuf>,
-        _config: Arc<Config>,
+        config: Arc<Config>,
+        client_log_level: Arc<ClientLogLevel>,
     ) -> Self {
         let outgoing = Arc::new(outgoing);
         let conversation_manager = Arc::new(ConversationManager::default());
+        let resources = Arc::new(CodexResources::new(config.clone(), outgoing.clone()));
         let codex_message_processor = CodexMessageProcessor::new(
             conversation_manager.clone(),
             outgoing.clone(),
             codex_linux_sandbox_exe.clone(),
+            config.clone(),
+            resources.clone(),
         );
         Self {
             codex_message_processor,
@@ -65,6 +90,9 @@ impl MessageProcessor {
             codex_linux_sandbox_exe,
             conversation_manager,
             running_requests_id_to_codex_uuid: Arc::new(Mutex::new(HashMap::new())),
+            config,
+            resources,
+            client_log_level,
         }
     }
 
@@ -100,25 +128,25 @@ impl MessageProcessor {
                 self.handle_ping(request_id, params).await;
             }
             McpClientRequest::ListResourcesRequest(params) => {
-                self.handle_list_resources(params);
+                self.handle_list_resources(request_id, params).await;
             }
             McpClientRequest::ListResourceTemplatesRequest(params) => {
-                self.handle_list_resource_templates(params);
+                self.handle_list_resource_templates(request_id, params).await;
             }
             McpClientRequest::ReadResourceRequest(params) => {
-                self.handle_read_resource(params);
+                self.handle_read_resource(request_id, params).await;
             }
             McpClientRequest::SubscribeRequest(params) => {
-                self.handle_subscribe(params);
+                self.handle_subscribe(request_id, params).await;
             }
             McpClientRequest::UnsubscribeRequest(params) => {
-                self.handle_unsubscribe(params);
+                self.handle_unsubscribe(request_id, params).await;
             }
             McpClientRequest::ListPromptsRequest(params) => {
-                self.handle_list_prompts(params);
+                self.handle_list_prompts(request_id, params).await;
             }
             McpClientRequest::GetPromptRequest(params) => {
-                self.handle_get_prompt(params);
+                self.handle_get_prompt(request_id, params).await;
             }
             McpClientRequest::ListToolsRequest(params) => {
                 self.handle_list_tools(request_id, params).await;
@@ -127,10 +155,10 @@ impl MessageProcessor {
                 self.handle_call_tool(request_id, params).await;
             }
             McpClientRequest::SetLevelRequest(params) => {
-                self.handle_set_level(params);
+                self.handle_set_level(request_id, params).await;
             }
             McpClientRequest::CompleteRequest(params) => {
-                self.handle_complete(params);
+                self.handle_complete(request_id, params).await;
             }
         }
     }
@@ -207,11 +235,16 @@ impl MessageProcessor {
         // Build a minimal InitializeResult. Fill with placeholders.
         let result = mcp_types::InitializeResult {
             capabilities: mcp_types::ServerCapabilities {
-                completions: None,
+                completions: Some(json!({})),
                 experimental: None,
-                logging: None,
-                prompts: None,
-                resources: None,
+                logging: Some(json!({})),
+                prompts: Some(ServerCapabilitiesPrompts {
+                    list_changed: Some(false),
+                }),
+                resources: Some(ServerCapabilitiesResources {
+                    list_changed: Some(true),
+                    subscribe: Some(true),
+                }),
                 tools: Some(ServerCapabilitiesTools {
                     list_changed: Some(true),
                 }),
@@ -247,54 +280,113 @@ impl MessageProcessor {
             .await;
     }
 
-    fn handle_list_resources(
+    async fn handle_list_resources(
         &self,
+        id: RequestId,
         params: <mcp_types::ListResourcesRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("resources/list -> params: {:?}", params);
+        let result = ListResourcesResult {
+            next_cursor: None,
+            resources: self.resources.list().await,
+        };
+        self.send_response::<mcp_types::ListResourcesRequest>(id, result)
+            .await;
     }
 
-    fn handle_list_resource_templates(
+    async fn handle_list_resource_templates(
         &self,
+        id: RequestId,
         params:
             <mcp_types::ListResourceTemplatesRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("resources/templates/list -> params: {:?}", params);
+        let result = ListResourceTemplatesResult {
+            next_cursor: None,
+            resource_templates: CodexResources::templates(),
+        };
+        self.send_response::<mcp_types::ListResourceTemplatesRequest>(id, result)
+            .await;
     }
 
-    fn handle_read_resource(
+    async fn handle_read_resource(
         &self,
+        id: RequestId,
         params: <mcp_types::ReadResourceRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("resources/read -> params: {:?}", params);
+        match self.resources.read(&params.uri).await {
+            Ok(contents) => {
+                let result = ReadResourceResult {
+                    contents: vec![contents],
+                };
+                self.send_response::<mcp_types::ReadResourceRequest>(id, result)
+                    .await;
+            }
+            Err(error) => self.outgoing.send_error(id, error).await,
+        }
     }
 
-    fn handle_subscribe(
+    async fn handle_subscribe(
         &self,
+        id: RequestId,
         params: <mcp_types::SubscribeRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("resources/subscribe -> params: {:?}", params);
+        match self.resources.subscribe(&params.uri).await {
+            Ok(()) => {
+                self.send_response::<mcp_types::SubscribeRequest>(id, json!({}))
+                    .await;
+            }
+            Err(error) => self.outgoing.send_error(id, error).await,
+        }
     }
 
-    fn handle_unsubscribe(
+    async fn handle_unsubscribe(
         &self,
+        id: RequestId,
         params: <mcp_types::UnsubscribeRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("resources/unsubscribe -> params: {:?}", params);
+        self.resources.unsubscribe(&params.uri).await;
+        self.send_response::<mcp_types::UnsubscribeRequest>(id, json!({}))
+            .await;
     }
 
-    fn handle_list_prompts(
+    async fn handle_list_prompts(
         &self,
+        id: RequestId,
         params: <mcp_types::ListPromptsRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("prompts/list -> params: {:?}", params);
+        let result = ListPromptsResult {
+            next_cursor: None,
+            prompts: list_prompts(),
+        };
+        self.send_response::<mcp_types::ListPromptsRequest>(id, result)
+            .await;
     }
 
-    fn handle_get_prompt(
+    async fn handle_get_prompt(
         &self,
+        id: RequestId,
         params: <mcp_types::GetPromptRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("prompts/get -> params: {:?}", params);
+        match get_prompt(params, &self.config.agents) {
+            Ok(result) => {
+                self.send_response::<mcp_types::GetPromptRequest>(id, result)
+                    .await;
+            }
+            Err(message) => {
+                let error = JSONRPCErrorError {
+                    code: INVALID_PARAMS_ERROR_CODE,
+                    message,
+                    data: None,
+                };
+                self.outgoing.send_error(id, error).await;
+            }
+        }
     }
 
     async fn handle_list_tools(
@@ -303,11 +395,13 @@ impl MessageProcessor {
         params: <mcp_types::ListToolsRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::trace!("tools/list -> {params:?}");
+        let mut tools = vec![
+            create_tool_for_codex_tool_call_param(),
+            create_tool_for_codex_tool_call_reply_param(),
+        ];
+        tools.extend(create_standalone_tools());
         let result = ListToolsResult {
-            tools: vec![
-                create_tool_for_codex_tool_call_param(),
-                create_tool_for_codex_tool_call_reply_param(),
-            ],
+            tools,
             next_cursor: None,
         };
 
@@ -329,6 +423,17 @@ impl MessageProcessor {
                 self.handle_tool_call_codex_session_reply(id, arguments)
                     .await
             }
+            _ if is_standalone_tool(&name) => {
+                // Approval requests wait on the client, so never block the
+                // message-processing loop.
+                task::spawn(run_standalone_tool(
+                    id,
+                    name,
+                    arguments,
+                    self.config.clone(),
+                    self.outgoing.clone(),
+                ));
+            }
             _ => {
                 let result = CallToolResult {
                     content: vec![ContentBlock::TextContent(TextContent {
@@ -403,6 +508,7 @@ impl MessageProcessor {
         let outgoing = self.outgoing.clone();
         let conversation_manager = self.conversation_manager.clone();
         let running_requests_id_to_codex_uuid = self.running_requests_id_to_codex_uuid.clone();
+        let resources = self.resources.clone();
 
         // Spawn an async task to handle the Codex session so that we do not
         // block the synchronous message-processing loop.
@@ -415,6 +521,7 @@ impl MessageProcessor {
                 outgoing,
                 conversation_manager,
                 running_requests_id_to_codex_uuid,
+                resources,
             )
             .await;
         });
@@ -512,6 +619,7 @@ impl MessageProcessor {
             let outgoing = outgoing.clone();
             let prompt = prompt.clone();
             let running_requests_id_to_codex_uuid = running_requests_id_to_codex_uuid.clone();
+            let resources = self.resources.clone();
 
             async move {
                 crate::codex_tool_runner::run_codex_tool_session_reply(
@@ -521,24 +629,53 @@ impl MessageProcessor {
                     prompt,
                     running_requests_id_to_codex_uuid,
                     session_id,
+                    resources,
                 )
                 .await;
             }
         });
     }
 
-    fn handle_set_level(
+    async fn handle_set_level(
         &self,
+        id: RequestId,
         params: <mcp_types::SetLevelRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("logging/setLevel -> params: {:?}", params);
+        self.client_log_level.set(params.level);
+        self.send_response::<mcp_types::SetLevelRequest>(id, json!({}))
+            .await;
     }
 
-    fn handle_complete(
+    async fn handle_complete(
         &self,
+        id: RequestId,
         params: <mcp_types::CompleteRequest as mcp_types::ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("completion/complete -> params: {:?}", params);
+        let argument = params.argument;
+        let values = match params.r#ref {
+            CompleteRequestParamsRef::PromptReference(prompt) => complete_prompt_argument(
+                &prompt.name,
+                &argument.name,
+                &argument.value,
+                &self.config.agents,
+            ),
+            CompleteRequestParamsRef::ResourceTemplateReference(_) => {
+                self.resources
+                    .complete_argument(&argument.name, &argument.value)
+                    .await
+            }
+        };
+        let result = CompleteResult {
+            completion: CompleteResultCompletion {
+                has_more: Some(false),
+                total: Some(values.len() as i64),
+                values,
+            },
+        };
+        self.send_response::<mcp_types::CompleteRequest>(id, result)
+            .await;
     }
 
     // ---------------------------------------------------------------------
@@ -0,0 +1,583 @@
+//! Standalone MCP tools that expose individual Codex capabilities — the
+//! apply-patch engine, fuzzy file search, the semantic code index and the
+//! command parser — without starting a whole conversation.
+//!
+//! Every tool runs against the server's base configuration: paths resolve
+//! relative to its `cwd`, patches go through the same safety assessment as a
+//! session (asking the client via elicitation when approval is required, and
+//! whenever a patch reaches outside the writable roots), and
+//! `parse_command` reports what the approval policy would decide.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::ApplyPatchError;
+use codex_apply_patch::ApplyPatchFileChange;
+use codex_apply_patch::Hunk;
+use codex_apply_patch::MaybeApplyPatchVerified;
+use codex_apply_patch::apply_hunks;
+use codex_apply_patch::maybe_parse_apply_patch_verified;
+use codex_apply_patch::parse_patch;
+use codex_core::SafetyCheck;
+use codex_core::assess_command_safety;
+use codex_core::assess_patch_safety;
+use codex_core::built_in_model_providers;
+use codex_core::config::Config;
+use codex_core::is_write_patch_constrained_to_writable_paths;
+use codex_core::memory::code_index::ensure_code_index;
+use codex_core::memory::openai_embeddings::OpenAiEmbeddingClient;
+use codex_core::memory::openai_embeddings::has_openai_api_key;
+use codex_core::protocol::FileChange;
+use codex_core::protocol::ReviewDecision;
+use codex_core::util::repo_key;
+use codex_memory::embedding::EmbeddingProvider;
+use codex_memory::store::jsonl::JsonlVectorStore;
+use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use mcp_types::ElicitRequest;
+use mcp_types::ElicitRequestParamsRequestedSchema;
+use mcp_types::ModelContextProtocolRequest;
+use mcp_types::RequestId;
+use mcp_types::TextContent;
+use mcp_types::Tool;
+use mcp_types::ToolInputSchema;
+use schemars::JsonSchema;
+use schemars::r#gen::SchemaSettings;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::outgoing_message::OutgoingMessageSender;
+use crate::patch_approval::PatchApprovalElicitRequestParams;
+use crate::patch_approval::PatchApprovalResponse;
+
+pub(crate) const APPLY_PATCH_TOOL_NAME: &str = "apply_patch";
+pub(crate) const FILE_SEARCH_TOOL_NAME: &str = "file_search";
+pub(crate) const CODE_SEARCH_TOOL_NAME: &str = "code_search";
+pub(crate) const PARSE_COMMAND_TOOL_NAME: &str = "parse_command";
+
+const DEFAULT_FILE_SEARCH_LIMIT: usize = 20;
+const MAX_FILE_SEARCH_LIMIT: usize = 200;
+
+/// Arguments for the `apply_patch` tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyPatchToolParam {
+    /// The patch, in the `*** Begin Patch` / `*** End Patch` format. Relative
+    /// paths resolve against the server's working directory.
+    pub patch: String,
+
+    /// Report the changes and the approval decision without touching the
+    /// filesystem.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Arguments for the `file_search` tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSearchToolParam {
+    /// Fuzzy pattern matched against paths under the working directory.
+    pub pattern: String,
+
+    /// Maximum number of matches to return (default 20, at most 200).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+
+    /// Glob patterns to exclude from the search.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+/// Arguments for the `code_search` tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeSearchToolParam {
+    /// Natural-language description of the code to find.
+    pub query: String,
+
+    /// Number of chunks to return; defaults to `memory.code_index.top_k`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<usize>,
+}
+
+/// Arguments for the `parse_command` tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseCommandToolParam {
+    /// The command as an argv array, e.g. `["bash", "-lc", "rg foo | head"]`.
+    pub command: Vec<String>,
+}
+
+pub(crate) fn is_standalone_tool(name: &str) -> bool {
+    matches!(
+        name,
+        APPLY_PATCH_TOOL_NAME
+            | FILE_SEARCH_TOOL_NAME
+            | CODE_SEARCH_TOOL_NAME
+            | PARSE_COMMAND_TOOL_NAME
+    )
+}
+
+pub(crate) fn create_standalone_tools() -> Vec<Tool> {
+    vec![
+        create_tool::<ApplyPatchToolParam>(
+            APPLY_PATCH_TOOL_NAME,
+            "Apply Patch",
+            "Apply a patch with Codex's apply-patch engine, subject to the server's sandbox and approval policy.",
+        ),
+        create_tool::<FileSearchToolParam>(
+            FILE_SEARCH_TOOL_NAME,
+            "File Search",
+            "Fuzzy-find files by path under the server's working directory.",
+        ),
+        create_tool::<CodeSearchToolParam>(
+            CODE_SEARCH_TOOL_NAME,
+            "Code Search",
+            "Semantic search over the repository's code index.",
+        ),
+        create_tool::<ParseCommandToolParam>(
+            PARSE_COMMAND_TOOL_NAME,
+            "Parse Command",
+            "Summarize what a shell command does and whether the server's approval policy would run it.",
+        ),
+    ]
+}
+
+fn create_tool<T: JsonSchema>(name: &str, title: &str, description: &str) -> Tool {
+    let schema = SchemaSettings::draft2019_09()
+        .with(|s| {
+            s.inline_subschemas = true;
+            s.option_add_null_type = false;
+        })
+        .into_generator()
+        .into_root_schema_for::<T>();
+
+    #[expect(clippy::expect_used)]
+    let schema_value = serde_json::to_value(&schema).expect("tool schema should serialise to JSON");
+
+    let tool_input_schema =
+        serde_json::from_value::<ToolInputSchema>(schema_value).unwrap_or_else(|e| {
+            panic!("failed to create Tool from schema: {e}");
+        });
+
+    Tool {
+        name: name.to_string(),
+        title: Some(title.to_string()),
+        input_schema: tool_input_schema,
+        output_schema: None,
+        description: Some(description.to_string()),
+        annotations: None,
+    }
+}
+
+/// Run one of the standalone tools and send its `tools/call` response.
+pub(crate) async fn run_standalone_tool(
+    id: RequestId,
+    name: String,
+    arguments: Option<Value>,
+    config: Arc<Config>,
+    outgoing: Arc<OutgoingMessageSender>,
+) {
+    let result = match name.as_str() {
+        APPLY_PATCH_TOOL_NAME => match parse_arguments::<ApplyPatchToolParam>(arguments) {
+            Ok(param) => apply_patch(param, &config, &outgoing, &id).await,
+            Err(e) => Err(e),
+        },
+        FILE_SEARCH_TOOL_NAME => match parse_arguments::<FileSearchToolParam>(arguments) {
+            Ok(param) => file_search(param, &config).await,
+            Err(e) => Err(e),
+        },
+        CODE_SEARCH_TOOL_NAME => match parse_arguments::<CodeSearchToolParam>(arguments) {
+            Ok(param) => code_search(param, &config).await,
+            Err(e) => Err(e),
+        },
+        PARSE_COMMAND_TOOL_NAME => parse_arguments::<ParseCommandToolParam>(arguments)
+            .and_then(|param| parse_command(param, &config)),
+        _ => Err(format!("Unknown tool '{name}'")),
+    };
+
+    let result = match result {
+        Ok(value) => CallToolResult {
+            content: vec![text_content(
+                serde_json::to_string_pretty(&value).unwrap_or_else(|_| value.to_string()),
+            )],
+            is_error: None,
+            structured_content: Some(value),
+        },
+        Err(message) => CallToolResult {
+            content: vec![text_content(message)],
+            is_error: Some(true),
+            structured_content: None,
+        },
+    };
+    outgoing.send_response(id, result).await;
+}
+
+fn text_content(text: String) -> ContentBlock {
+    ContentBlock::TextContent(TextContent {
+        r#type: "text".to_string(),
+        text,
+        annotations: None,
+    })
+}
+
+fn parse_arguments<T: DeserializeOwned>(arguments: Option<Value>) -> Result<T, String> {
+    serde_json::from_value(arguments.unwrap_or_else(|| json!({})))
+        .map_err(|e| format!("Invalid arguments: {e}"))
+}
+
+async fn apply_patch(
+    param: ApplyPatchToolParam,
+    config: &Config,
+    outgoing: &OutgoingMessageSender,
+    id: &RequestId,
+) -> Result<Value, String> {
+    let ApplyPatchToolParam { patch, dry_run } = param;
+    let argv = vec![APPLY_PATCH_TOOL_NAME.to_string(), patch];
+    let action = match maybe_parse_apply_patch_verified(&argv, &config.cwd) {
+        MaybeApplyPatchVerified::Body(action) => action,
+        MaybeApplyPatchVerified::CorrectnessError(e) => return Err(format!("invalid patch: {e}")),
+        MaybeApplyPatchVerified::ShellParseError(e) => {
+            return Err(format!("failed to parse patch: {e:?}"));
+        }
+        MaybeApplyPatchVerified::NotApplyPatch => {
+            return Err("not a patch: expected `*** Begin Patch`".to_string());
+        }
+    };
+
+    let changes = file_changes(&action);
+    let safety = match assess_patch_safety(
+        &action,
+        config.approval_policy,
+        &config.sandbox_policy,
+        &config.cwd,
+    ) {
+        // A session auto-approves some patches that reach outside the writable
+        // roots (e.g. under `on-failure`) because it applies them inside the
+        // sandbox. This tool writes from the server process, so only patches
+        // that stay within the writable roots go through unasked.
+        SafetyCheck::AutoApprove { .. }
+            if !is_write_patch_constrained_to_writable_paths(
+                &action,
+                &config.sandbox_policy,
+                &config.cwd,
+            ) =>
+        {
+            SafetyCheck::AskUser
+        }
+        safety => safety,
+    };
+    if dry_run {
+        return Ok(json!({
+            "applied": false,
+            "approval": approval_json(&safety),
+            "changes": changes,
+        }));
+    }
+
+    match safety {
+        SafetyCheck::AutoApprove { .. } => {}
+        SafetyCheck::AskUser => {
+            if !request_patch_approval(outgoing, id, changes.clone()).await {
+                return Err("patch rejected by user".to_string());
+            }
+        }
+        SafetyCheck::Reject { reason } => return Err(format!("patch rejected: {reason}")),
+    }
+
+    write_changes(&action).map_err(|e| format!("failed to apply patch: {e}"))?;
+    Ok(json!({
+        "applied": true,
+        "changes": changes,
+    }))
+}
+
+fn file_changes(action: &ApplyPatchAction) -> HashMap<PathBuf, FileChange> {
+    action
+        .changes()
+        .iter()
+        .map(|(path, change)| {
+            let change = match change {
+                ApplyPatchFileChange::Add { content } => FileChange::Add {
+                    content: content.clone(),
+                },
+                ApplyPatchFileChange::Delete => FileChange::Delete,
+                ApplyPatchFileChange::Update {
+                    unified_diff,
+                    move_path,
+                    new_content: _,
+                } => FileChange::Update {
+                    unified_diff: unified_diff.clone(),
+                    move_path: move_path.clone(),
+                },
+            };
+            (path.clone(), change)
+        })
+        .collect()
+}
+
+/// Apply the patch with the apply-patch engine. The patch text may use paths
+/// relative to the action's `cwd`, which need not be the server's own working
+/// directory, so every hunk is resolved against it first.
+fn write_changes(action: &ApplyPatchAction) -> Result<(), ApplyPatchError> {
+    let hunks = parse_patch(&action.patch)?
+        .hunks
+        .into_iter()
+        .map(|hunk| match hunk {
+            Hunk::AddFile { path, contents } => Hunk::AddFile {
+                path: action.cwd.join(path),
+                contents,
+            },
+            Hunk::DeleteFile { path } => Hunk::DeleteFile {
+                path: action.cwd.join(path),
+            },
+            Hunk::UpdateFile {
+                path,
+                move_path,
+                chunks,
+            } => Hunk::UpdateFile {
+                path: action.cwd.join(path),
+                move_path: move_path.map(|p| action.cwd.join(p)),
+                chunks,
+            },
+        })
+        .collect::<Vec<_>>();
+    apply_hunks(&hunks, &mut std::io::sink(), &mut std::io::sink())
+}
+
+async fn request_patch_approval(
+    outgoing: &OutgoingMessageSender,
+    id: &RequestId,
+    changes: HashMap<PathBuf, FileChange>,
+) -> bool {
+    let call_id = match id {
+        RequestId::String(s) => s.clone(),
+        RequestId::Integer(n) => n.to_string(),
+    };
+    let params = PatchApprovalElicitRequestParams {
+        message: "Allow Codex to apply proposed code changes?".to_string(),
+        requested_schema: ElicitRequestParamsRequestedSchema {
+            r#type: "object".to_string(),
+            properties: json!({}),
+            required: None,
+        },
+        codex_elicitation: "patch-approval".to_string(),
+        codex_mcp_tool_call_id: call_id.clone(),
+        codex_event_id: call_id.clone(),
+        codex_call_id: call_id,
+        codex_reason: None,
+        codex_grant_root: None,
+        codex_changes: changes,
+    };
+    let params = match serde_json::to_value(&params) {
+        Ok(params) => params,
+        Err(e) => {
+            tracing::error!("failed to serialize PatchApprovalElicitRequestParams: {e}");
+            return false;
+        }
+    };
+
+    let on_response = outgoing
+        .send_request(ElicitRequest::METHOD, Some(params))
+        .await;
+    let Ok(value) = on_response.await else {
+        return false;
+    };
+    match serde_json::from_value::<PatchApprovalResponse>(value) {
+        Ok(response) => matches!(
+            response.decision,
+            ReviewDecision::Approved | ReviewDecision::ApprovedForSession
+        ),
+        Err(e) => {
+            tracing::error!("failed to deserialize PatchApprovalResponse: {e}");
+            false
+        }
+    }
+}
+
+fn approval_json(safety: &SafetyCheck) -> Value {
+    match safety {
+        SafetyCheck::AutoApprove { .. } => json!({ "decision": "auto_approve" }),
+        SafetyCheck::AskUser => json!({ "decision": "ask_user" }),
+        SafetyCheck::Reject { reason } => json!({ "decision": "reject", "reason": reason }),
+    }
+}
+
+async fn file_search(param: FileSearchToolParam, config: &Config) -> Result<Value, String> {
+    let FileSearchToolParam {
+        pattern,
+        limit,
+        exclude,
+    } = param;
+    let limit = limit
+        .unwrap_or(DEFAULT_FILE_SEARCH_LIMIT)
+        .min(MAX_FILE_SEARCH_LIMIT);
+    let limit = NonZeroUsize::new(limit).ok_or("`limit` must be at least 1")?;
+    let threads = NonZeroUsize::new(2).unwrap_or(NonZeroUsize::MIN);
+    let search_directory = config.cwd.clone();
+
+    let results = tokio::task::spawn_blocking(move || {
+        codex_file_search::run(
+            &pattern,
+            limit,
+            &search_directory,
+            exclude,
+            threads,
+            Arc::new(AtomicBool::new(false)),
+            false,
+        )
+    })
+    .await
+    .map_err(|e| format!("file search task failed: {e}"))?
+    .map_err(|e| format!("file search failed: {e}"))?;
+
+    Ok(json!({
+        "matches": results.matches,
+        "totalMatchCount": results.total_match_count,
+    }))
+}
+
+async fn code_search(param: CodeSearchToolParam, config: &Config) -> Result<Value, String> {
+    let memory = &config.memory;
+    if !memory.embedding.enabled || !memory.code_index.enabled {
+        return Err(
+            "code search is disabled; enable `memory.embedding` and `memory.code_index` in config.toml"
+                .to_string(),
+        );
+    }
+    if !has_openai_api_key(&config.codex_home) {
+        return Err("code search needs an OpenAI API key to embed the query".to_string());
+    }
+
+    let CodeSearchToolParam { query, top_k } = param;
+    let top_k = top_k.unwrap_or(memory.code_index.top_k).max(1);
+    let dim = memory.embedding.dim;
+    let chunk_bytes = memory.code_index.chunk_bytes;
+    let codex_home = config.codex_home.clone();
+    let cwd = config.cwd.clone();
+
+    let hits = tokio::task::spawn_blocking(move || {
+        let repo_key = repo_key(&cwd);
+        ensure_code_index(&repo_key, &codex_home, &cwd, dim, chunk_bytes);
+
+        let provider = built_in_model_providers()
+            .get("openai")
+            .cloned()
+            .ok_or("the OpenAI provider is not available")?;
+        let client = OpenAiEmbeddingClient::from_provider(&provider, &codex_home)
+            .map_err(|e| format!("failed to create embedding client: {e:?}"))?;
+        let query_vec = client
+            .embed(&[query], dim)
+            .map_err(|e| format!("failed to embed query: {e:?}"))?
+            .into_iter()
+            .next()
+            .ok_or("embedding provider returned no vector")?;
+        JsonlVectorStore::new(&codex_home)
+            .query_kind(&repo_key, "code", &query_vec, top_k)
+            .map_err(|e| format!("failed to query code index: {e}"))
+    })
+    .await
+    .map_err(|e| format!("code search task failed: {e}"))??;
+
+    Ok(json!({ "hits": hits }))
+}
+
+fn parse_command(param: ParseCommandToolParam, config: &Config) -> Result<Value, String> {
+    let ParseCommandToolParam { command } = param;
+    if command.is_empty() {
+        return Err("`command` must not be empty".to_string());
+    }
+
+    let parsed: Vec<codex_protocol::parse_command::ParsedCommand> =
+        codex_core::parse_command::parse_command(&command)
+            .into_iter()
+            .map(Into::into)
+            .collect();
+    let safety = assess_command_safety(
+        &command,
+        config.approval_policy,
+        &config.sandbox_policy,
+        &HashSet::new(),
+        false,
+    );
+    Ok(json!({
+        "approval": approval_json(&safety),
+        "parsed": parsed,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn verify_apply_patch_tool_json_schema() {
+        let tool = create_standalone_tools()
+            .into_iter()
+            .find(|tool| tool.name == APPLY_PATCH_TOOL_NAME)
+            .expect("apply_patch tool");
+        let tool_json = serde_json::to_value(&tool).expect("tool serializes");
+        assert_eq!(
+            tool_json["inputSchema"],
+            json!({
+                "properties": {
+                    "dryRun": {
+                        "default": false,
+                        "description": "Report the changes and the approval decision without touching the filesystem.",
+                        "type": "boolean"
+                    },
+                    "patch": {
+                        "description": "The patch, in the `*** Begin Patch` / `*** End Patch` format. Relative paths resolve against the server's working directory.",
+                        "type": "string"
+                    },
+                },
+                "required": ["patch"],
+                "type": "object",
+            })
+        );
+    }
+
+    #[test]
+    fn write_changes_applies_adds_updates_and_moves() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        std::fs::write(dir.path().join("keep.txt"), "one\ntwo\n").expect("write keep.txt");
+        std::fs::write(dir.path().join("old.txt"), "moved\n").expect("write old.txt");
+        let patch = r#"*** Begin Patch
+*** Add File: nested/new.txt
++hello
+*** Update File: keep.txt
+@@
+ one
+-two
++three
+*** Update File: old.txt
+*** Move to: renamed.txt
+@@
+-moved
++renamed
+*** End Patch"#;
+        let argv = vec![APPLY_PATCH_TOOL_NAME.to_string(), patch.to_string()];
+        let MaybeApplyPatchVerified::Body(action) =
+            maybe_parse_apply_patch_verified(&argv, dir.path())
+        else {
+            panic!("patch should parse");
+        };
+
+        write_changes(&action).expect("write changes");
+
+        let read = |name: &str| std::fs::read_to_string(dir.path().join(name)).expect(name);
+        assert_eq!(read("nested/new.txt"), "hello\n");
+        assert_eq!(read("keep.txt"), "one\nthree\n");
+        assert_eq!(read("renamed.txt"), "renamed\n");
+        assert!(!dir.path().join("old.txt").exists());
+    }
+}
@@ -13,9 +13,13 @@ use anyhow::Context;
 use assert_cmd::prelude::*;
 use codex_mcp_server::CodexToolCallParam;
 use codex_protocol::mcp_protocol::AddConversationListenerParams;
+use codex_protocol::mcp_protocol::ArchiveConversationParams;
+use codex_protocol::mcp_protocol::ForkConversationParams;
 use codex_protocol::mcp_protocol::InterruptConversationParams;
+use codex_protocol::mcp_protocol::ListConversationsParams;
 use codex_protocol::mcp_protocol::NewConversationParams;
 use codex_protocol::mcp_protocol::RemoveConversationListenerParams;
+use codex_protocol::mcp_protocol::ResumeConversationParams;
 use codex_protocol::mcp_protocol::SendUserMessageParams;
 use codex_protocol::mcp_protocol::SendUserTurnParams;
 
@@ -118,13 +122,22 @@ impl McpProcess {
                 id: RequestId::Integer(request_id),
                 result: json!({
                     "capabilities": {
+                        "completions": {},
+                        "logging": {},
+                        "prompts": {
+                            "listChanged": false
+                        },
+                        "resources": {
+                            "listChanged": true,
+                            "subscribe": true
+                        },
                         "tools": {
                             "listChanged": true
                         },
                     },
                     "serverInfo": {
-                        "name": "codex-mcp-server",
-                        "title": "Codex",
+                        "name": "code-mcp-server",
+                        "title": "Code",
                         "version": "0.0.0"
                     },
                     "protocolVersion": mcp_types::MCP_SCHEMA_VERSION
@@ -217,6 +230,54 @@ impl McpProcess {
         self.send_request("interruptConversation", params).await
     }
 
+    /// Send a `listConversations` JSON-RPC request.
+    pub async fn send_list_conversations_request(
+        &mut self,
+        params: ListConversationsParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("listConversations", params).await
+    }
+
+    /// Send a `resumeConversation` JSON-RPC request.
+    pub async fn send_resume_conversation_request(
+        &mut self,
+        params: ResumeConversationParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("resumeConversation", params).await
+    }
+
+    /// Send a `forkConversation` JSON-RPC request.
+    pub async fn send_fork_conversation_request(
+        &mut self,
+        params: ForkConversationParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("forkConversation", params).await
+    }
+
+    /// Send an `archiveConversation` JSON-RPC request.
+    pub async fn send_archive_conversation_request(
+        &mut self,
+        params: ArchiveConversationParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("archiveConversation", params).await
+    }
+
+    /// Send a standard MCP request such as `resources/list` or `prompts/get`.
+    pub async fn send_mcp_request<R: ModelContextProtocolRequest>(
+        &mut self,
+        params: R::Params,
+    ) -> anyhow::Result<i64> {
+        let params = match serde_json::to_value(params)? {
+            serde_json::Value::Null => None,
+            params => Some(params),
+        };
+        self.send_request(R::METHOD, params).await
+    }
+
     async fn send_request(
         &mut self,
         method: &str,
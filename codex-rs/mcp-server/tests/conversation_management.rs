@@ -0,0 +1,186 @@
+use std::path::Path;
+
+use codex_protocol::mcp_protocol::ArchiveConversationParams;
+use codex_protocol::mcp_protocol::ArchiveConversationResponse;
+use codex_protocol::mcp_protocol::ConversationId;
+use codex_protocol::mcp_protocol::ForkConversationParams;
+use codex_protocol::mcp_protocol::ForkConversationResponse;
+use codex_protocol::mcp_protocol::ListConversationsParams;
+use codex_protocol::mcp_protocol::ListConversationsResponse;
+use codex_protocol::mcp_protocol::ResumeConversationParams;
+use codex_protocol::mcp_protocol::ResumeConversationResponse;
+use mcp_test_support::McpProcess;
+use mcp_test_support::create_mock_chat_completions_server;
+use mcp_test_support::to_response;
+use mcp_types::JSONRPCResponse;
+use mcp_types::RequestId;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::time::timeout;
+use uuid::Uuid;
+
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_list_fork_resume_and_archive_conversations() {
+    // Starting a session does not call the model, so the server never answers.
+    let server = create_mock_chat_completions_server(Vec::new()).await;
+    let codex_home = TempDir::new().expect("create temp dir");
+    create_config_toml(codex_home.path(), &server.uri()).expect("write config.toml");
+    let session_id = Uuid::new_v4();
+    write_rollout(codex_home.path(), &session_id, &["first", "second"]).expect("write rollout");
+
+    let mut mcp = McpProcess::new(codex_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("init timeout")
+        .expect("init failed");
+
+    let list = list_conversations(&mut mcp).await.expect("list");
+    assert_eq!(list.items.len(), 1);
+    assert_eq!(list.items[0].conversation_id, ConversationId(session_id));
+    assert_eq!(list.items[0].preview.as_deref(), Some("first"));
+    assert_eq!(list.next_cursor, None);
+
+    // Fork after the first turn.
+    let fork_id = mcp
+        .send_fork_conversation_request(ForkConversationParams {
+            conversation_id: Some(ConversationId(session_id)),
+            turn_index: 1,
+            ..Default::default()
+        })
+        .await
+        .expect("send forkConversation");
+    let fork: ForkConversationResponse = to_response(
+        read_response(&mut mcp, fork_id)
+            .await
+            .expect("fork response"),
+    )
+    .expect("fork response");
+    assert_ne!(fork.conversation_id, ConversationId(session_id));
+    let fork_rollout = std::fs::read_to_string(&fork.path).expect("read fork rollout");
+    assert!(fork_rollout.contains("first"));
+    assert!(!fork_rollout.contains("second"));
+
+    // Resuming keeps the recorded id, and only one session may own a rollout.
+    let resume_params = ResumeConversationParams {
+        conversation_id: Some(ConversationId(session_id)),
+        ..Default::default()
+    };
+    let resume_id = mcp
+        .send_resume_conversation_request(resume_params.clone())
+        .await
+        .expect("send resumeConversation");
+    let resumed: ResumeConversationResponse = to_response(
+        read_response(&mut mcp, resume_id)
+            .await
+            .expect("resume response"),
+    )
+    .expect("resume response");
+    assert_eq!(resumed.conversation_id, ConversationId(session_id));
+    assert_eq!(resumed.model, "mock-model");
+
+    let again_id = mcp
+        .send_resume_conversation_request(resume_params)
+        .await
+        .expect("send resumeConversation");
+    let again = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_error_message(RequestId::Integer(again_id)),
+    )
+    .await
+    .expect("resume error timeout")
+    .expect("resume error");
+    assert_eq!(
+        again.error.message,
+        format!("conversation is already running: {session_id}")
+    );
+
+    let list = list_conversations(&mut mcp).await.expect("list");
+    let ids: Vec<ConversationId> = list.items.iter().map(|c| c.conversation_id).collect();
+    assert_eq!(ids, vec![fork.conversation_id, ConversationId(session_id)]);
+
+    let archive_id = mcp
+        .send_archive_conversation_request(ArchiveConversationParams {
+            conversation_id: Some(ConversationId(session_id)),
+            path: None,
+        })
+        .await
+        .expect("send archiveConversation");
+    let archived: ArchiveConversationResponse = to_response(
+        read_response(&mut mcp, archive_id)
+            .await
+            .expect("archive response"),
+    )
+    .expect("archive response");
+    assert!(archived.archived_path.exists());
+
+    let list = list_conversations(&mut mcp).await.expect("list");
+    let ids: Vec<ConversationId> = list.items.iter().map(|c| c.conversation_id).collect();
+    assert_eq!(ids, vec![fork.conversation_id]);
+}
+
+async fn list_conversations(mcp: &mut McpProcess) -> anyhow::Result<ListConversationsResponse> {
+    let request_id = mcp
+        .send_list_conversations_request(ListConversationsParams::default())
+        .await?;
+    to_response(read_response(mcp, request_id).await?)
+}
+
+async fn read_response(mcp: &mut McpProcess, request_id: i64) -> anyhow::Result<JSONRPCResponse> {
+    timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(request_id)),
+    )
+    .await?
+}
+
+/// Write a rollout with one user message per turn, as the recorder would.
+fn write_rollout(codex_home: &Path, session_id: &Uuid, turns: &[&str]) -> std::io::Result<()> {
+    let dir = codex_home.join("sessions/2025/01/02");
+    std::fs::create_dir_all(&dir)?;
+    let mut lines = vec![json!({ "id": session_id, "timestamp": "2025-01-02T03:04:05.000Z" })];
+    for turn in turns {
+        lines.push(json!({
+            "type": "message",
+            "role": "user",
+            "content": [{ "type": "input_text", "text": turn }],
+        }));
+        lines.push(json!({
+            "type": "message",
+            "role": "assistant",
+            "content": [{ "type": "output_text", "text": "ok" }],
+        }));
+    }
+    let text: String = lines.iter().map(|line| format!("{line}\n")).collect();
+    std::fs::write(
+        dir.join(format!("rollout-2025-01-02T03-04-05-{session_id}.jsonl")),
+        text,
+    )
+}
+
+fn create_config_toml(codex_home: &Path, server_uri: &str) -> std::io::Result<()> {
+    let config_toml = codex_home.join("config.toml");
+    std::fs::write(
+        config_toml,
+        format!(
+            r#"
+model = "mock-model"
+approval_policy = "never"
+sandbox_mode = "danger-full-access"
+
+model_provider = "mock_provider"
+
+[model_providers.mock_provider]
+name = "Mock provider for test"
+base_url = "{server_uri}/v1"
+wire_api = "chat"
+request_max_retries = 0
+stream_max_retries = 0
+"#
+        ),
+    )
+}
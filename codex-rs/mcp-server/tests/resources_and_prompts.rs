@@ -0,0 +1,190 @@
+// Support code lives in the `mcp_test_support` crate under tests/common.
+
+use mcp_test_support::McpProcess;
+use mcp_types::CompleteRequest;
+use mcp_types::CompleteRequestParams;
+use mcp_types::CompleteRequestParamsArgument;
+use mcp_types::CompleteRequestParamsRef;
+use mcp_types::GetPromptRequest;
+use mcp_types::GetPromptRequestParams;
+use mcp_types::JSONRPCResponse;
+use mcp_types::ListPromptsRequest;
+use mcp_types::ListResourcesRequest;
+use mcp_types::LoggingLevel;
+use mcp_types::ReadResourceRequest;
+use mcp_types::ReadResourceRequestParams;
+use mcp_types::RequestId;
+use mcp_types::ResourceTemplateReference;
+use mcp_types::SetLevelRequest;
+use mcp_types::SetLevelRequestParams;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::time::timeout;
+use uuid::Uuid;
+
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_session_rollouts_are_listed_read_and_completed() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    let session_id = Uuid::new_v4();
+    let day_dir = codex_home.path().join("sessions/2025/01/02");
+    std::fs::create_dir_all(&day_dir).expect("create sessions dir");
+    let rollout = "{\"id\":\"session\"}\n";
+    std::fs::write(
+        day_dir.join(format!("rollout-2025-01-02T03-04-05-{session_id}.jsonl")),
+        rollout,
+    )
+    .expect("write rollout");
+
+    let mut mcp = McpProcess::new(codex_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("init timeout")
+        .expect("init failed");
+
+    let uri = format!("codex://sessions/{session_id}");
+    let list_id = mcp
+        .send_mcp_request::<ListResourcesRequest>(None)
+        .await
+        .expect("send resources/list");
+    let list = read_response(&mut mcp, list_id).await.expect("response");
+    let uris: Vec<&str> = list.result["resources"]
+        .as_array()
+        .expect("resources array")
+        .iter()
+        .filter_map(|resource| resource["uri"].as_str())
+        .collect();
+    assert!(uris.contains(&uri.as_str()), "missing {uri} in {uris:?}");
+
+    let read_id = mcp
+        .send_mcp_request::<ReadResourceRequest>(ReadResourceRequestParams { uri: uri.clone() })
+        .await
+        .expect("send resources/read");
+    let read = read_response(&mut mcp, read_id).await.expect("response");
+    assert_eq!(
+        read.result,
+        json!({
+            "contents": [{
+                "mimeType": "application/x-ndjson",
+                "text": rollout,
+                "uri": uri,
+            }]
+        })
+    );
+
+    let complete_id = mcp
+        .send_mcp_request::<CompleteRequest>(CompleteRequestParams {
+            argument: CompleteRequestParamsArgument {
+                name: "session_id".to_string(),
+                value: session_id.to_string()[..8].to_string(),
+            },
+            context: None,
+            r#ref: CompleteRequestParamsRef::ResourceTemplateReference(ResourceTemplateReference {
+                r#type: "ref/resource".to_string(),
+                uri: "codex://sessions/{session_id}".to_string(),
+            }),
+        })
+        .await
+        .expect("send completion/complete");
+    let complete = read_response(&mut mcp, complete_id)
+        .await
+        .expect("response");
+    assert_eq!(
+        complete.result["completion"]["values"],
+        json!([session_id.to_string()])
+    );
+
+    let missing_id = mcp
+        .send_mcp_request::<ReadResourceRequest>(ReadResourceRequestParams {
+            uri: format!("codex://sessions/{}", Uuid::new_v4()),
+        })
+        .await
+        .expect("send resources/read");
+    let missing = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_error_message(RequestId::Integer(missing_id)),
+    )
+    .await
+    .expect("resources/read timeout")
+    .expect("resources/read error");
+    assert_eq!(missing.error.code, -32002);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_slash_command_prompts_and_log_level() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    let mut mcp = McpProcess::new(codex_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("init timeout")
+        .expect("init failed");
+
+    let list_id = mcp
+        .send_mcp_request::<ListPromptsRequest>(None)
+        .await
+        .expect("send prompts/list");
+    let list = read_response(&mut mcp, list_id).await.expect("response");
+    let names: Vec<&str> = list.result["prompts"]
+        .as_array()
+        .expect("prompts array")
+        .iter()
+        .filter_map(|prompt| prompt["name"].as_str())
+        .collect();
+    assert_eq!(names, vec!["plan", "solve", "code"]);
+
+    let get_id = mcp
+        .send_mcp_request::<GetPromptRequest>(GetPromptRequestParams {
+            arguments: Some(json!({ "task": "add a --verbose flag" })),
+            name: "plan".to_string(),
+        })
+        .await
+        .expect("send prompts/get");
+    let get = read_response(&mut mcp, get_id).await.expect("response");
+    let text = get.result["messages"][0]["content"]["text"]
+        .as_str()
+        .expect("prompt text");
+    assert!(text.contains("Create a comprehensive plan"));
+    assert!(text.ends_with("add a --verbose flag"));
+
+    // Once the client opts in, server logs stream as notifications.
+    let set_level_id = mcp
+        .send_mcp_request::<SetLevelRequest>(SetLevelRequestParams {
+            level: LoggingLevel::Info,
+        })
+        .await
+        .expect("send logging/setLevel");
+    read_response(&mut mcp, set_level_id)
+        .await
+        .expect("response");
+    mcp.send_mcp_request::<ListPromptsRequest>(None)
+        .await
+        .expect("send prompts/list");
+    let log = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_notification_message("notifications/message"),
+    )
+    .await
+    .expect("log notification timeout")
+    .expect("log notification");
+    let params = log.params.expect("log params");
+    assert_eq!(params["level"], "info");
+    assert!(
+        params["data"]["message"]
+            .as_str()
+            .is_some_and(|message| message.starts_with("prompts/list"))
+    );
+}
+
+async fn read_response(mcp: &mut McpProcess, request_id: i64) -> anyhow::Result<JSONRPCResponse> {
+    timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(request_id)),
+    )
+    .await?
+}
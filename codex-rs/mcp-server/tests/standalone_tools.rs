@@ -0,0 +1,218 @@
+// Support code lives in the `mcp_test_support` crate under tests/common.
+
+use std::path::Path;
+
+use codex_core::protocol::ReviewDecision;
+use codex_mcp_server::PatchApprovalResponse;
+use mcp_test_support::McpProcess;
+use mcp_types::CallToolRequest;
+use mcp_types::CallToolRequestParams;
+use mcp_types::ElicitRequest;
+use mcp_types::JSONRPCResponse;
+use mcp_types::ListToolsRequest;
+use mcp_types::ModelContextProtocolRequest;
+use mcp_types::RequestId;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::time::timeout;
+
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_standalone_tools_are_listed() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    let mut mcp = start_mcp(codex_home.path()).await.expect("start mcp");
+
+    let list_id = mcp
+        .send_mcp_request::<ListToolsRequest>(None)
+        .await
+        .expect("send tools/list");
+    let list = read_response(&mut mcp, list_id).await.expect("response");
+    let names: Vec<&str> = list.result["tools"]
+        .as_array()
+        .expect("tools array")
+        .iter()
+        .filter_map(|tool| tool["name"].as_str())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "codex",
+            "codex-reply",
+            "apply_patch",
+            "file_search",
+            "code_search",
+            "parse_command",
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_apply_patch_dry_run_then_apply() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        "approval_policy = \"never\"\nsandbox_mode = \"danger-full-access\"\n",
+    )
+    .expect("write config.toml");
+    let workspace = TempDir::new().expect("create workspace");
+    let target = workspace.path().join("hello.txt");
+    let patch = format!(
+        "*** Begin Patch\n*** Add File: {}\n+hello\n*** End Patch",
+        target.display()
+    );
+    let mut mcp = start_mcp(codex_home.path()).await.expect("start mcp");
+
+    let dry_run = call_tool(
+        &mut mcp,
+        "apply_patch",
+        json!({ "patch": patch, "dryRun": true }),
+    )
+    .await
+    .expect("tools/call");
+    assert_eq!(dry_run["applied"], json!(false));
+    assert_eq!(dry_run["approval"], json!({ "decision": "auto_approve" }));
+    assert!(!target.exists());
+
+    let applied = call_tool(&mut mcp, "apply_patch", json!({ "patch": patch }))
+        .await
+        .expect("tools/call");
+    assert_eq!(applied["applied"], json!(true));
+    assert_eq!(
+        std::fs::read_to_string(&target).expect("read patched file"),
+        "hello\n"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_apply_patch_outside_writable_roots_asks_under_on_failure() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"approval_policy = "on-failure"
+sandbox_mode = "workspace-write"
+
+[sandbox_workspace_write]
+exclude_tmpdir_env_var = true
+exclude_slash_tmp = true
+"#,
+    )
+    .expect("write config.toml");
+    // The server's cwd is this crate, so a temp dir is outside every root.
+    let outside = TempDir::new().expect("create temp dir");
+    let target = outside.path().join("escaped.txt");
+    let patch = format!(
+        "*** Begin Patch\n*** Add File: {}\n+escaped\n*** End Patch",
+        target.display()
+    );
+    let mut mcp = start_mcp(codex_home.path()).await.expect("start mcp");
+
+    let dry_run = call_tool(
+        &mut mcp,
+        "apply_patch",
+        json!({ "patch": patch, "dryRun": true }),
+    )
+    .await
+    .expect("tools/call");
+    assert_eq!(dry_run["approval"], json!({ "decision": "ask_user" }));
+
+    let request_id = mcp
+        .send_mcp_request::<CallToolRequest>(CallToolRequestParams {
+            name: "apply_patch".to_string(),
+            arguments: Some(json!({ "patch": patch })),
+        })
+        .await
+        .expect("send tools/call");
+    let elicitation = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_request_message(),
+    )
+    .await
+    .expect("elicitation timeout")
+    .expect("elicitation request");
+    assert_eq!(elicitation.method, ElicitRequest::METHOD);
+    let params = elicitation.params.expect("elicitation params");
+    assert_eq!(params["codex_elicitation"], json!("patch-approval"));
+    assert!(!target.exists());
+
+    mcp.send_response(
+        elicitation.id,
+        serde_json::to_value(PatchApprovalResponse {
+            decision: ReviewDecision::Denied,
+        })
+        .expect("serialize response"),
+    )
+    .await
+    .expect("send elicitation response");
+    let response = read_response(&mut mcp, request_id).await.expect("response");
+    assert_eq!(response.result["isError"], json!(true));
+    assert!(!target.exists());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_parse_command_reports_the_approval_decision() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        "approval_policy = \"untrusted\"\n",
+    )
+    .expect("write config.toml");
+    let mut mcp = start_mcp(codex_home.path()).await.expect("start mcp");
+
+    let safe = call_tool(
+        &mut mcp,
+        "parse_command",
+        json!({ "command": ["cat", "README.md"] }),
+    )
+    .await
+    .expect("tools/call");
+    assert_eq!(
+        safe,
+        json!({
+            "approval": { "decision": "auto_approve" },
+            "parsed": [{ "type": "read", "cmd": "cat README.md", "name": "README.md" }],
+        })
+    );
+
+    let untrusted = call_tool(
+        &mut mcp,
+        "parse_command",
+        json!({ "command": ["cargo", "build"] }),
+    )
+    .await
+    .expect("tools/call");
+    assert_eq!(untrusted["approval"], json!({ "decision": "ask_user" }));
+}
+
+async fn start_mcp(codex_home: &Path) -> anyhow::Result<McpProcess> {
+    let mut mcp = McpProcess::new(codex_home).await?;
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize()).await??;
+    Ok(mcp)
+}
+
+/// Call a tool and return its structured content, failing on tool errors.
+async fn call_tool(mcp: &mut McpProcess, name: &str, arguments: Value) -> anyhow::Result<Value> {
+    let request_id = mcp
+        .send_mcp_request::<CallToolRequest>(CallToolRequestParams {
+            name: name.to_string(),
+            arguments: Some(arguments),
+        })
+        .await?;
+    let response = read_response(mcp, request_id).await?;
+    anyhow::ensure!(
+        response.result.get("isError").is_none(),
+        "tool call failed: {}",
+        response.result
+    );
+    Ok(response.result["structuredContent"].clone())
+}
+
+async fn read_response(mcp: &mut McpProcess, request_id: i64) -> anyhow::Result<JSONRPCResponse> {
+    timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(request_id)),
+    )
+    .await?
+}
@@ -0,0 +1,66 @@
+use mcp_types::CancelledNotification;
+use mcp_types::CancelledNotificationParams;
+use mcp_types::JSONRPC_VERSION;
+use mcp_types::JSONRPCMessage;
+use mcp_types::JSONRPCNotification;
+use mcp_types::ModelContextProtocolNotification;
+use mcp_types::RequestId;
+use mcp_types::ServerNotification;
+use serde_json::json;
+
+#[test]
+fn deserialize_cancelled_notification() {
+    let raw = r#"{
+        "jsonrpc": "2.0",
+        "method": "notifications/cancelled",
+        "params": {
+            "reason": "request timed out",
+            "requestId": 4
+        }
+    }"#;
+
+    let msg: JSONRPCMessage = serde_json::from_str(raw).expect("invalid JSONRPCMessage");
+    let JSONRPCMessage::Notification(notif) = msg else {
+        unreachable!()
+    };
+
+    let server_notif: ServerNotification =
+        ServerNotification::try_from(notif).expect("conversion must succeed");
+    let ServerNotification::CancelledNotification(params) = server_notif else {
+        unreachable!()
+    };
+
+    assert_eq!(
+        params,
+        CancelledNotificationParams {
+            reason: Some("request timed out".into()),
+            request_id: RequestId::Integer(4),
+        }
+    );
+}
+
+#[test]
+fn cancelled_notification_round_trips() {
+    let params = CancelledNotificationParams {
+        reason: None,
+        request_id: RequestId::String("call-1".into()),
+    };
+    let notification = JSONRPCMessage::Notification(JSONRPCNotification {
+        jsonrpc: JSONRPC_VERSION.into(),
+        method: CancelledNotification::METHOD.into(),
+        params: Some(serde_json::to_value(&params).expect("serialize params")),
+    });
+
+    let raw = serde_json::to_value(&notification).expect("serialize notification");
+    assert_eq!(
+        raw,
+        json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/cancelled",
+            "params": { "requestId": "call-1" }
+        })
+    );
+
+    let parsed: JSONRPCMessage = serde_json::from_value(raw).expect("deserialize notification");
+    assert_eq!(parsed, notification);
+}
@@ -1,3 +1,5 @@
+use mcp_types::CallToolRequestParams;
+use mcp_types::ClientRequest;
 use mcp_types::JSONRPCMessage;
 use mcp_types::ProgressNotificationParams;
 use mcp_types::ProgressToken;
@@ -41,3 +43,61 @@ fn deserialize_progress_notification() {
 
     assert_eq!(params, expected_params);
 }
+
+#[test]
+fn progress_notification_round_trips_with_string_token() {
+    let params = ProgressNotificationParams {
+        message: None,
+        progress: 3.0,
+        progress_token: ProgressToken::String("upload-7".into()),
+        total: None,
+    };
+
+    let value = serde_json::to_value(&params).expect("serialize params");
+    assert_eq!(
+        value,
+        serde_json::json!({ "progress": 3.0, "progressToken": "upload-7" })
+    );
+
+    let round_tripped: ProgressNotificationParams =
+        serde_json::from_value(value).expect("deserialize params");
+    assert_eq!(round_tripped, params);
+}
+
+#[test]
+fn call_tool_request_accepts_progress_token_in_meta() {
+    let raw = r#"{
+        "jsonrpc": "2.0",
+        "id": 7,
+        "method": "tools/call",
+        "params": {
+            "_meta": { "progressToken": 7 },
+            "arguments": { "path": "." },
+            "name": "index"
+        }
+    }"#;
+
+    let msg: JSONRPCMessage = serde_json::from_str(raw).expect("invalid JSONRPCMessage");
+    let JSONRPCMessage::Request(request) = msg else {
+        unreachable!()
+    };
+
+    // The token survives a JSON-RPC round trip untouched.
+    let reserialized = serde_json::to_string(&JSONRPCMessage::Request(request.clone()))
+        .expect("serialize request");
+    let reparsed: JSONRPCMessage = serde_json::from_str(&reserialized).expect("reparse request");
+    assert_eq!(reparsed, JSONRPCMessage::Request(request.clone()));
+
+    let ClientRequest::CallToolRequest(params) =
+        ClientRequest::try_from(request).expect("conversion must succeed")
+    else {
+        unreachable!()
+    };
+    assert_eq!(
+        params,
+        CallToolRequestParams {
+            arguments: Some(serde_json::json!({ "path": "." })),
+            name: "index".into(),
+        }
+    );
+}
@@ -78,6 +78,26 @@ pub enum ClientRequest {
         request_id: RequestId,
         params: RemoveConversationListenerParams,
     },
+    ListConversations {
+        #[serde(rename = "id")]
+        request_id: RequestId,
+        params: ListConversationsParams,
+    },
+    ResumeConversation {
+        #[serde(rename = "id")]
+        request_id: RequestId,
+        params: ResumeConversationParams,
+    },
+    ForkConversation {
+        #[serde(rename = "id")]
+        request_id: RequestId,
+        params: ForkConversationParams,
+    },
+    ArchiveConversation {
+        #[serde(rename = "id")]
+        request_id: RequestId,
+        params: ArchiveConversationParams,
+    },
     LoginChatGpt {
         #[serde(rename = "id")]
         request_id: RequestId,
@@ -152,6 +172,123 @@ pub struct NewConversationResponse {
     pub model: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ListConversationsParams {
+    /// Maximum number of conversations to return (default 25, at most 100).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<usize>,
+
+    /// Opaque cursor from a previous response's `nextCursor`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ListConversationsResponse {
+    /// Recorded conversations, newest first.
+    pub items: Vec<ConversationSummary>,
+
+    /// Cursor for the next page; omitted on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Metadata recorded at the start of a conversation's rollout.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationSummary {
+    pub conversation_id: ConversationId,
+    /// Path to the rollout file.
+    pub path: PathBuf,
+    /// When the conversation started.
+    pub timestamp: String,
+    /// The first message the user sent, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<GitSha>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_origin_url: Option<String>,
+}
+
+/// Resume a recorded conversation, identified by exactly one of
+/// `conversationId` or `path`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeConversationParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<ConversationId>,
+
+    /// Path to a rollout file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+
+    /// Settings for the resumed session, as for `newConversation`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<NewConversationParams>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeConversationResponse {
+    pub conversation_id: ConversationId,
+    pub model: String,
+}
+
+/// Start a new conversation from the first `turnIndex` turns of a recorded
+/// one, identified by exactly one of `conversationId` or `path`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkConversationParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<ConversationId>,
+
+    /// Path to a rollout file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+
+    /// Number of user turns to keep; `0` forks an empty conversation.
+    pub turn_index: usize,
+
+    /// Settings for the forked session, as for `newConversation`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<NewConversationParams>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkConversationResponse {
+    pub conversation_id: ConversationId,
+    pub model: String,
+    /// Path to the fork's rollout file.
+    pub path: PathBuf,
+}
+
+/// Move a recorded conversation out of the session list, identified by
+/// exactly one of `conversationId` or `path`. A running conversation is shut
+/// down first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveConversationParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<ConversationId>,
+
+    /// Path to a rollout file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveConversationResponse {
+    /// Where the rollout was moved.
+    pub archived_path: PathBuf,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct AddConversationSubscriptionResponse {
@@ -411,4 +548,34 @@ mod tests {
             serde_json::to_value(&request).unwrap(),
         );
     }
+
+    #[test]
+    fn deserialize_fork_conversation() {
+        let conversation_id = Uuid::new_v4();
+        let request: ClientRequest = serde_json::from_value(json!({
+            "method": "forkConversation",
+            "id": 7,
+            "params": {
+                "conversationId": conversation_id,
+                "turnIndex": 2,
+                "overrides": { "model": "o3" }
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            request,
+            ClientRequest::ForkConversation {
+                request_id: RequestId::Integer(7),
+                params: ForkConversationParams {
+                    conversation_id: Some(ConversationId(conversation_id)),
+                    path: None,
+                    turn_index: 2,
+                    overrides: Some(NewConversationParams {
+                        model: Some("o3".to_string()),
+                        ..Default::default()
+                    }),
+                },
+            }
+        );
+    }
 }
@@ -450,6 +450,11 @@ impl App<'_> {
                     AppState::Chat { widget } => widget.submit_op(op),
                     AppState::Onboarding { .. } => {}
                 },
+                AppEvent::RunMcpPrompt(command, command_text) => {
+                    if let AppState::Chat { widget } = &mut self.app_state {
+                        widget.run_mcp_prompt(command, command_text);
+                    }
+                }
                 AppEvent::DispatchCommand(command, command_text) => {
                     // Extract command arguments by removing the slash command from the beginning
                     // e.g., "/browser status" -> "status", "/chrome 9222" -> "9222"
@@ -526,6 +531,11 @@ impl App<'_> {
                                 widget.add_diff_output(text);
                             }
                         }
+                        SlashCommand::Snapshot => {
+                            if let AppState::Chat { widget } = &mut self.app_state {
+                                widget.handle_snapshot_command(command_args);
+                            }
+                        }
                         SlashCommand::Mention => {
                             // The mention feature is handled differently in our fork
                             // For now, just add @ to the composer
@@ -538,6 +548,11 @@ impl App<'_> {
                                 widget.add_status_output();
                             }
                         }
+                        SlashCommand::Mcp => {
+                            if let AppState::Chat { widget } = &mut self.app_state {
+                                widget.submit_op(Op::ListMcpServers);
+                            }
+                        }
                         SlashCommand::Reasoning => {
                             if let AppState::Chat { widget } = &mut self.app_state {
                                 widget.handle_reasoning_command(command_args);
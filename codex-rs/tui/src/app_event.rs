@@ -11,6 +11,7 @@ use std::time::Duration;
 
 use crate::app::ChatWidgetArgs;
 use crate::bottom_pane::chrome_selection_view::ChromeLaunchOption;
+use crate::slash_command::McpPromptCommand;
 use crate::slash_command::SlashCommand;
 
 #[allow(clippy::large_enum_variant)]
@@ -50,6 +51,10 @@ pub(crate) enum AppEvent {
     /// layer so it can be handled centrally. Includes the full command text.
     DispatchCommand(SlashCommand, String),
 
+    /// Run a prompt offered by an MCP server, selected from the slash-command
+    /// popup. Includes the full command text.
+    RunMcpPrompt(McpPromptCommand, String),
+
     /// Signal that agents are about to start (triggered when /plan, /solve, /code commands are entered)
     PrepareAgents,
 
@@ -18,11 +18,13 @@ use ratatui::widgets::StatefulWidgetRef;
 use ratatui::widgets::WidgetRef;
 
 use super::chat_composer_history::ChatComposerHistory;
+use super::command_popup::CommandItem;
 use super::command_popup::CommandPopup;
 use super::file_search_popup::FileSearchPopup;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
+use crate::slash_command::McpPromptCommand;
 use crate::bottom_pane::textarea::TextArea;
 use crate::bottom_pane::textarea::TextAreaState;
 use codex_file_search::FileMatch;
@@ -114,6 +116,8 @@ pub(crate) struct ChatComposer {
     // chat ScrollDown instead of moving within the textarea, unless another
     // key is pressed in between.
     next_down_scrolls_history: bool,
+    // Prompts offered by MCP servers, listed in the slash-command popup.
+    mcp_prompts: Vec<McpPromptCommand>,
 }
 
 /// Popup state – at most one can be visible at any time.
@@ -159,9 +163,18 @@ impl ChatComposer {
             show_compression_hint: true,
             compression_enabled: false,
             next_down_scrolls_history: false,
+            mcp_prompts: Vec::new(),
         }
     }
 
+    /// Replace the MCP prompts offered as slash commands.
+    pub(crate) fn set_mcp_prompts(&mut self, mcp_prompts: Vec<McpPromptCommand>) {
+        if let ActivePopup::Command(popup) = &mut self.active_popup {
+            popup.set_mcp_prompts(mcp_prompts.clone());
+        }
+        self.mcp_prompts = mcp_prompts;
+    }
+
     pub fn set_has_chat_history(&mut self, has_history: bool) {
         self.has_chat_history = has_history;
     }
@@ -528,14 +541,15 @@ impl ChatComposer {
                 code: KeyCode::Tab, ..
             } => {
                 if let Some(cmd) = popup.selected_command() {
+                    let command = popup.command_name(cmd);
                     let first_line = self.textarea.text().lines().next().unwrap_or("");
 
                     let starts_with_cmd = first_line
                         .trim_start()
-                        .starts_with(&format!("/{}", cmd.command()));
+                        .starts_with(&format!("/{command}"));
 
                     if !starts_with_cmd {
-                        self.textarea.set_text(&format!("/{} ", cmd.command()));
+                        self.textarea.set_text(&format!("/{command} "));
                         self.textarea.set_cursor(self.textarea.text().len());
                     }
                     // After completing, place the cursor at the end of the
@@ -550,21 +564,33 @@ impl ChatComposer {
                 modifiers: KeyModifiers::NONE,
                 ..
             } => {
-                if let Some(cmd) = popup.selected_command() {
+                if let Some(item) = popup.selected_command() {
                     // Get the full command text before clearing
                     let command_text = self.textarea.text().to_string();
 
                     // Record the exact slash command that was typed
                     self.history.record_local_submission(&command_text);
 
-                    // Check if this is a prompt-expanding command that will trigger agents
-                    if cmd.is_prompt_expanding() {
-                        self.app_event_tx.send(AppEvent::PrepareAgents);
-                    }
+                    match item {
+                        CommandItem::Builtin(cmd) => {
+                            // Check if this is a prompt-expanding command that will trigger agents
+                            if cmd.is_prompt_expanding() {
+                                self.app_event_tx.send(AppEvent::PrepareAgents);
+                            }
 
-                    // Send command to the app layer with full text.
-                    self.app_event_tx
-                        .send(AppEvent::DispatchCommand(*cmd, command_text.clone()));
+                            // Send command to the app layer with full text.
+                            self.app_event_tx
+                                .send(AppEvent::DispatchCommand(cmd, command_text.clone()));
+                        }
+                        CommandItem::McpPrompt(index) => {
+                            if let Some(prompt) = popup.mcp_prompt(index) {
+                                self.app_event_tx.send(AppEvent::RunMcpPrompt(
+                                    prompt.clone(),
+                                    command_text.clone(),
+                                ));
+                            }
+                        }
+                    }
 
                     // Clear textarea so no residual text remains.
                     self.textarea.set_text("");
@@ -1124,7 +1150,10 @@ impl ChatComposer {
             }
             _ => {
                 if input_starts_with_slash {
-                    let mut command_popup = CommandPopup::new_with_filter(self.using_chatgpt_auth);
+                    let mut command_popup = CommandPopup::new_with_filter(
+                        self.using_chatgpt_auth,
+                        self.mcp_prompts.clone(),
+                    );
                     command_popup.on_composer_text_change(first_line.to_string());
                     self.active_popup = ActivePopup::Command(command_popup);
                     // Notify app: composer expanded due to slash popup
@@ -7,23 +7,33 @@ use super::popup_consts::MAX_POPUP_ROWS;
 use super::scroll_state::ScrollState;
 use super::selection_popup_common::GenericDisplayRow;
 use super::selection_popup_common::render_rows;
+use crate::slash_command::McpPromptCommand;
 use crate::slash_command::SlashCommand;
 use crate::slash_command::built_in_slash_commands;
 use codex_common::fuzzy_match::fuzzy_match;
 
+/// An entry in the popup: a built-in command or a prompt offered by an MCP
+/// server (an index into the popup's prompt list).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CommandItem {
+    Builtin(SlashCommand),
+    McpPrompt(usize),
+}
+
 pub(crate) struct CommandPopup {
     command_filter: String,
     all_commands: Vec<(&'static str, SlashCommand)>,
+    mcp_prompts: Vec<McpPromptCommand>,
     state: ScrollState,
 }
 
 impl CommandPopup {
     #[cfg(test)]
     pub(crate) fn new() -> Self {
-        Self::new_with_filter(false)
+        Self::new_with_filter(false, Vec::new())
     }
     
-    pub(crate) fn new_with_filter(hide_verbosity: bool) -> Self {
+    pub(crate) fn new_with_filter(hide_verbosity: bool, mcp_prompts: Vec<McpPromptCommand>) -> Self {
         let mut commands = built_in_slash_commands();
         if hide_verbosity {
             // Filter out the verbosity command when using ChatGPT auth
@@ -32,10 +42,53 @@ impl CommandPopup {
         Self {
             command_filter: String::new(),
             all_commands: commands,
+            mcp_prompts,
             state: ScrollState::new(),
         }
     }
 
+    /// Replace the MCP prompts offered alongside the built-in commands.
+    pub(crate) fn set_mcp_prompts(&mut self, mcp_prompts: Vec<McpPromptCommand>) {
+        self.mcp_prompts = mcp_prompts;
+        let matches_len = self.filtered_commands().len();
+        self.state.clamp_selection(matches_len);
+    }
+
+    /// The MCP prompt behind `CommandItem::McpPrompt(index)`.
+    pub(crate) fn mcp_prompt(&self, index: usize) -> Option<&McpPromptCommand> {
+        self.mcp_prompts.get(index)
+    }
+
+    /// Command string of `item`, without the leading '/'.
+    pub(crate) fn command_name(&self, item: CommandItem) -> String {
+        match item {
+            CommandItem::Builtin(cmd) => cmd.command().to_string(),
+            CommandItem::McpPrompt(index) => self
+                .mcp_prompts
+                .get(index)
+                .map(McpPromptCommand::command)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn description(&self, item: CommandItem) -> String {
+        match item {
+            CommandItem::Builtin(cmd) => cmd.description().to_string(),
+            CommandItem::McpPrompt(index) => self
+                .mcp_prompts
+                .get(index)
+                .map(McpPromptCommand::description)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn all_items(&self) -> impl Iterator<Item = CommandItem> + '_ {
+        self.all_commands
+            .iter()
+            .map(|(_, cmd)| CommandItem::Builtin(*cmd))
+            .chain((0..self.mcp_prompts.len()).map(CommandItem::McpPrompt))
+    }
+
     /// Update the filter string based on the current composer text. The text
     /// passed in is expected to start with a leading '/'. Everything after the
     /// *first* '/" on the *first* line becomes the active filter that is used
@@ -75,28 +128,31 @@ impl CommandPopup {
 
     /// Compute fuzzy-filtered matches paired with optional highlight indices and score.
     /// Sorted by ascending score, then by command name for stability.
-    fn filtered(&self) -> Vec<(&SlashCommand, Option<Vec<usize>>, i32)> {
+    fn filtered(&self) -> Vec<(CommandItem, Option<Vec<usize>>, i32)> {
         let filter = self.command_filter.trim();
-        let mut out: Vec<(&SlashCommand, Option<Vec<usize>>, i32)> = Vec::new();
+        let mut out: Vec<(CommandItem, Option<Vec<usize>>, i32)> = Vec::new();
         if filter.is_empty() {
-            for (_, cmd) in self.all_commands.iter() {
-                out.push((cmd, None, 0));
+            for item in self.all_items() {
+                out.push((item, None, 0));
             }
             // Keep the original presentation order when no filter is applied.
             return out;
         } else {
-            for (_, cmd) in self.all_commands.iter() {
-                if let Some((indices, score)) = fuzzy_match(cmd.command(), filter) {
-                    out.push((cmd, Some(indices), score));
+            for item in self.all_items() {
+                if let Some((indices, score)) = fuzzy_match(&self.command_name(item), filter) {
+                    out.push((item, Some(indices), score));
                 }
             }
         }
         // When filtering, sort by ascending score and then by command for stability.
-        out.sort_by(|a, b| a.2.cmp(&b.2).then_with(|| a.0.command().cmp(b.0.command())));
+        out.sort_by(|a, b| {
+            a.2.cmp(&b.2)
+                .then_with(|| self.command_name(a.0).cmp(&self.command_name(b.0)))
+        });
         out
     }
 
-    fn filtered_commands(&self) -> Vec<&SlashCommand> {
+    fn filtered_commands(&self) -> Vec<CommandItem> {
         self.filtered().into_iter().map(|(c, _, _)| c).collect()
     }
 
@@ -123,7 +179,7 @@ impl CommandPopup {
     }
 
     /// Return currently selected command, if any.
-    pub(crate) fn selected_command(&self) -> Option<&SlashCommand> {
+    pub(crate) fn selected_command(&self) -> Option<CommandItem> {
         let matches = self.filtered_commands();
         self.state
             .selected_idx
@@ -144,11 +200,11 @@ impl WidgetRef for CommandPopup {
         } else {
             matches
                 .into_iter()
-                .map(|(cmd, indices, _)| GenericDisplayRow {
-                    name: format!("/{}", cmd.command()),
+                .map(|(item, indices, _)| GenericDisplayRow {
+                    name: format!("/{}", self.command_name(item)),
                     match_indices: indices.map(|v| v.into_iter().map(|i| i + 1).collect()),
                     is_current: false,
-                    description: Some(cmd.description().to_string()),
+                    description: Some(self.description(item)),
                     // Slash command names should use theme primary color
                     name_color: Some(crate::colors::primary()),
                 })
@@ -180,7 +236,7 @@ mod tests {
         // one of the matches is the new "init" command.
         let matches = popup.filtered_commands();
         assert!(
-            matches.iter().any(|cmd| cmd.command() == "init"),
+            matches.contains(&CommandItem::Builtin(SlashCommand::Init)),
             "expected '/init' to appear among filtered commands"
         );
     }
@@ -194,8 +250,28 @@ mod tests {
         // command by default.
         let selected = popup.selected_command();
         match selected {
-            Some(cmd) => assert_eq!(cmd.command(), "init"),
+            Some(cmd) => assert_eq!(popup.command_name(cmd), "init"),
             None => panic!("expected a selected command for exact match"),
         }
     }
+
+    #[test]
+    fn mcp_prompts_are_listed_and_selectable() {
+        let prompt = mcp_types::Prompt {
+            arguments: None,
+            description: Some("Summarize open issues".to_string()),
+            name: "triage".to_string(),
+            title: None,
+        };
+        let mut popup = CommandPopup::new();
+        popup.set_mcp_prompts(vec![McpPromptCommand {
+            server: "github".to_string(),
+            prompt,
+        }]);
+        popup.on_composer_text_change("/github:tri".to_string());
+
+        let selected = popup.selected_command();
+        assert_eq!(selected, Some(CommandItem::McpPrompt(0)));
+        assert_eq!(popup.command_name(CommandItem::McpPrompt(0)), "github:triage");
+    }
 }
@@ -0,0 +1,497 @@
+use codex_core::protocol::ElicitationAction;
+use codex_core::protocol::McpElicitationRequestEvent;
+use codex_core::protocol::Op;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
+use mcp_types::RequestId;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Widget;
+use ratatui::widgets::Wrap;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+
+use super::BottomPane;
+use super::CancellationEvent;
+use super::bottom_pane_view::BottomPaneView;
+
+/// Kind of value a form field takes, from the field's JSON schema.
+#[derive(Debug, Clone, PartialEq)]
+enum FieldKind {
+    Text,
+    Number {
+        integer: bool,
+    },
+    Boolean,
+    /// One of `options`, shown with the matching `labels`.
+    Choice {
+        options: Vec<String>,
+        labels: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct FormField {
+    name: String,
+    label: String,
+    description: Option<String>,
+    required: bool,
+    kind: FieldKind,
+    /// Typed text for `Text` and `Number` fields.
+    text: String,
+    /// Value of a `Boolean` field.
+    checked: bool,
+    /// Selected option of a `Choice` field.
+    choice: usize,
+}
+
+impl FormField {
+    fn from_schema(name: &str, schema: &Value, required: bool) -> Self {
+        let string = |key: &str| schema.get(key).and_then(Value::as_str).map(str::to_string);
+        let options: Vec<String> = schema
+            .get("enum")
+            .and_then(Value::as_array)
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let default = schema.get("default");
+
+        let kind = if !options.is_empty() {
+            let names: Vec<String> = schema
+                .get("enumNames")
+                .and_then(Value::as_array)
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|v| v.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let labels = if names.len() == options.len() {
+                names
+            } else {
+                options.clone()
+            };
+            FieldKind::Choice { options, labels }
+        } else {
+            match schema.get("type").and_then(Value::as_str) {
+                Some("boolean") => FieldKind::Boolean,
+                Some("number") => FieldKind::Number { integer: false },
+                Some("integer") => FieldKind::Number { integer: true },
+                _ => FieldKind::Text,
+            }
+        };
+
+        let choice = match (&kind, default.and_then(Value::as_str)) {
+            (FieldKind::Choice { options, .. }, Some(default)) => {
+                options.iter().position(|o| o == default).unwrap_or(0)
+            }
+            _ => 0,
+        };
+        let text = match default {
+            Some(Value::String(s)) if kind == FieldKind::Text => s.clone(),
+            Some(Value::Number(n)) => n.to_string(),
+            _ => String::new(),
+        };
+
+        Self {
+            name: name.to_string(),
+            label: string("title").unwrap_or_else(|| name.to_string()),
+            description: string("description"),
+            required,
+            checked: default.and_then(Value::as_bool).unwrap_or(false),
+            kind,
+            text,
+            choice,
+        }
+    }
+
+    /// The field's JSON value, `None` for an optional field left empty.
+    fn value(&self) -> Result<Option<Value>, String> {
+        let value = match &self.kind {
+            FieldKind::Boolean => Value::Bool(self.checked),
+            FieldKind::Choice { options, .. } => {
+                Value::String(options.get(self.choice).cloned().unwrap_or_default())
+            }
+            _ if self.text.trim().is_empty() => {
+                return if self.required {
+                    Err(format!("{} is required", self.label))
+                } else {
+                    Ok(None)
+                };
+            }
+            FieldKind::Text => Value::String(self.text.clone()),
+            FieldKind::Number { integer: true } => self
+                .text
+                .trim()
+                .parse::<i64>()
+                .map(Value::from)
+                .map_err(|_| format!("{} must be a whole number", self.label))?,
+            FieldKind::Number { integer: false } => self
+                .text
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(|n| serde_json::Number::from_f64(n).map(Value::Number))
+                .ok_or_else(|| format!("{} must be a number", self.label))?,
+        };
+        Ok(Some(value))
+    }
+
+    fn display_value(&self) -> String {
+        match &self.kind {
+            FieldKind::Boolean => if self.checked { "[x]" } else { "[ ]" }.to_string(),
+            FieldKind::Choice { labels, .. } => {
+                format!(
+                    "‹ {} ›",
+                    labels.get(self.choice).cloned().unwrap_or_default()
+                )
+            }
+            _ => self.text.clone(),
+        }
+    }
+}
+
+/// Form asking the user for the input an MCP server requested
+/// (`elicitation/create`). Enter submits, Esc declines, Ctrl+C cancels.
+pub(crate) struct McpElicitationView {
+    server: String,
+    request_id: RequestId,
+    message: String,
+    fields: Vec<FormField>,
+    selected: usize,
+    error: Option<String>,
+    complete: bool,
+    app_event_tx: AppEventSender,
+}
+
+impl McpElicitationView {
+    pub fn new(request: McpElicitationRequestEvent, app_event_tx: AppEventSender) -> Self {
+        let McpElicitationRequestEvent {
+            server,
+            request_id,
+            message,
+            requested_schema,
+        } = request;
+        let required = requested_schema.required.unwrap_or_default();
+        let fields = requested_schema
+            .properties
+            .as_object()
+            .map(|properties| {
+                properties
+                    .iter()
+                    .map(|(name, schema)| {
+                        FormField::from_schema(name, schema, required.contains(name))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            server,
+            request_id,
+            message,
+            fields,
+            selected: 0,
+            error: None,
+            complete: false,
+            app_event_tx,
+        }
+    }
+
+    fn move_selection(&mut self, forward: bool) {
+        let len = self.fields.len();
+        if len == 0 {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % len
+        } else {
+            (self.selected + len - 1) % len
+        };
+    }
+
+    /// Toggle a boolean or step through a choice field.
+    fn cycle(&mut self, forward: bool) {
+        let Some(field) = self.fields.get_mut(self.selected) else {
+            return;
+        };
+        match &field.kind {
+            FieldKind::Boolean => field.checked = !field.checked,
+            FieldKind::Choice { options, .. } => {
+                let len = options.len();
+                field.choice = if forward {
+                    (field.choice + 1) % len
+                } else {
+                    (field.choice + len - 1) % len
+                };
+            }
+            _ => {}
+        }
+    }
+
+    fn content(&self) -> Result<Value, String> {
+        let mut content = Map::new();
+        for field in &self.fields {
+            if let Some(value) = field.value()? {
+                content.insert(field.name.clone(), value);
+            }
+        }
+        Ok(Value::Object(content))
+    }
+
+    fn submit(&mut self) {
+        match self.content() {
+            Ok(content) => self.finish(ElicitationAction::Accept, Some(content)),
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    fn finish(&mut self, action: ElicitationAction, content: Option<Value>) {
+        let verb = match action {
+            ElicitationAction::Accept => "provided",
+            ElicitationAction::Decline => "declined",
+            ElicitationAction::Cancel => "dismissed",
+        };
+        self.app_event_tx.send(AppEvent::InsertHistory(vec![
+            Line::from(vec![
+                "• ".dim(),
+                format!("You {verb} the input requested by {}", self.server).into(),
+            ]),
+            Line::from(""),
+        ]));
+        self.app_event_tx
+            .send(AppEvent::CodexOp(Op::McpElicitationResponse {
+                server: self.server.clone(),
+                request_id: self.request_id.clone(),
+                action,
+                content,
+            }));
+        self.complete = true;
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![
+            Line::from(vec![
+                "? ".fg(crate::colors::info()),
+                self.server.clone().bold(),
+                " requests input".bold(),
+            ]),
+            Line::from(self.message.clone()),
+            Line::from(""),
+        ];
+        for (i, field) in self.fields.iter().enumerate() {
+            let selected = i == self.selected;
+            let marker = if selected { "› " } else { "  " };
+            let mut label = field.label.clone();
+            if field.required {
+                label.push('*');
+            }
+            let mut value = Span::raw(field.display_value());
+            if selected {
+                value = value.style(Style::default().add_modifier(Modifier::UNDERLINED));
+            }
+            lines.push(Line::from(vec![
+                Span::styled(marker, Style::default().fg(crate::colors::primary())),
+                Span::styled(
+                    format!("{label}: "),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                value,
+            ]));
+            if let Some(description) = field.description.as_ref().filter(|_| selected) {
+                lines.push(Line::from(format!("    {description}").dim()));
+            }
+        }
+        if let Some(error) = &self.error {
+            lines.push(Line::from(error.clone().fg(crate::colors::error())));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(
+            "↑↓/Tab field · Space/←→ toggle · Enter submit · Esc decline · Ctrl+C cancel".dim(),
+        ));
+        lines
+    }
+}
+
+impl BottomPaneView<'_> for McpElicitationView {
+    fn handle_key_event(&mut self, _pane: &mut BottomPane<'_>, key_event: KeyEvent) {
+        if key_event.kind != KeyEventKind::Press {
+            return;
+        }
+        let is_text = self
+            .fields
+            .get(self.selected)
+            .is_some_and(|f| matches!(f.kind, FieldKind::Text | FieldKind::Number { .. }));
+        match key_event.code {
+            KeyCode::Up | KeyCode::BackTab => self.move_selection(false),
+            KeyCode::Down | KeyCode::Tab => self.move_selection(true),
+            KeyCode::Left => self.cycle(false),
+            KeyCode::Right => self.cycle(true),
+            KeyCode::Enter => self.submit(),
+            KeyCode::Esc => self.finish(ElicitationAction::Decline, None),
+            KeyCode::Backspace if is_text => {
+                if let Some(field) = self.fields.get_mut(self.selected) {
+                    field.text.pop();
+                }
+            }
+            KeyCode::Char(' ') if !is_text => self.cycle(true),
+            KeyCode::Char(c) if is_text && !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(field) = self.fields.get_mut(self.selected) {
+                    field.text.push(c);
+                }
+                self.error = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    fn on_ctrl_c(&mut self, _pane: &mut BottomPane<'_>) -> CancellationEvent {
+        self.finish(ElicitationAction::Cancel, None);
+        CancellationEvent::Handled
+    }
+
+    fn desired_height(&self, width: u16) -> u16 {
+        Paragraph::new(self.lines())
+            .wrap(Wrap { trim: false })
+            .line_count(width) as u16
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.lines())
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::ElicitRequestParamsRequestedSchema;
+    use serde_json::json;
+    use std::sync::mpsc::Receiver;
+    use std::sync::mpsc::channel;
+
+    fn view() -> (McpElicitationView, Receiver<AppEvent>) {
+        let (tx, rx) = channel::<AppEvent>();
+        let request = McpElicitationRequestEvent {
+            server: "github".to_string(),
+            request_id: RequestId::Integer(7),
+            message: "Where should the issue go?".to_string(),
+            requested_schema: ElicitRequestParamsRequestedSchema {
+                r#type: "object".to_string(),
+                properties: json!({
+                    "repo": { "type": "string", "title": "Repository" },
+                    "count": { "type": "integer" },
+                    "private": { "type": "boolean", "default": true },
+                    "priority": { "type": "string", "enum": ["low", "high"] },
+                }),
+                required: Some(vec!["repo".to_string()]),
+            },
+        };
+        (
+            McpElicitationView::new(request, AppEventSender::new(tx)),
+            rx,
+        )
+    }
+
+    fn pane() -> BottomPane<'static> {
+        let (tx, _rx) = channel::<AppEvent>();
+        BottomPane::new(super::super::BottomPaneParams {
+            app_event_tx: AppEventSender::new(tx),
+            has_input_focus: true,
+            enhanced_keys_supported: false,
+            using_chatgpt_auth: false,
+        })
+    }
+
+    fn press(view: &mut McpElicitationView, pane: &mut BottomPane<'static>, code: KeyCode) {
+        view.handle_key_event(pane, KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    fn sent_op(rx: &Receiver<AppEvent>) -> Option<Op> {
+        rx.try_iter().find_map(|event| match event {
+            AppEvent::CodexOp(op) => Some(op),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn required_fields_must_be_filled_before_submitting() {
+        let (mut view, rx) = view();
+        let mut pane = pane();
+        let repo = view.fields.iter().position(|f| f.name == "repo").unwrap();
+        view.selected = repo;
+
+        press(&mut view, &mut pane, KeyCode::Enter);
+        assert_eq!(view.error.as_deref(), Some("Repository is required"));
+        assert!(!view.is_complete());
+        assert!(sent_op(&rx).is_none());
+
+        for c in "openai/codex".chars() {
+            press(&mut view, &mut pane, KeyCode::Char(c));
+        }
+        let priority = view
+            .fields
+            .iter()
+            .position(|f| f.name == "priority")
+            .unwrap();
+        view.selected = priority;
+        press(&mut view, &mut pane, KeyCode::Right);
+        press(&mut view, &mut pane, KeyCode::Enter);
+
+        assert!(view.is_complete());
+        match sent_op(&rx) {
+            Some(Op::McpElicitationResponse {
+                server,
+                request_id,
+                action,
+                content,
+            }) => {
+                assert_eq!(server, "github");
+                assert_eq!(request_id, RequestId::Integer(7));
+                assert_eq!(action, ElicitationAction::Accept);
+                assert_eq!(
+                    content,
+                    Some(json!({ "repo": "openai/codex", "private": true, "priority": "high" }))
+                );
+            }
+            other => panic!("expected McpElicitationResponse, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn escape_declines() {
+        let (mut view, rx) = view();
+        let mut pane = pane();
+        press(&mut view, &mut pane, KeyCode::Esc);
+
+        assert!(view.is_complete());
+        assert!(matches!(
+            sent_op(&rx),
+            Some(Op::McpElicitationResponse {
+                action: ElicitationAction::Decline,
+                content: None,
+                ..
+            })
+        ));
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
+use crate::slash_command::McpPromptCommand;
 use crate::user_approval_widget::ApprovalRequest;
 use bottom_pane_view::BottomPaneView;
 use codex_core::protocol::TokenUsage;
@@ -20,6 +21,7 @@ mod diff_popup;
 mod command_popup;
 mod file_search_popup;
 mod live_ring_widget;
+mod mcp_elicitation_view;
 mod popup_consts;
 mod reasoning_selection_view;
 mod scroll_state;
@@ -39,6 +41,8 @@ pub(crate) use chat_composer::ChatComposer;
 pub(crate) use chat_composer::InputResult;
 
 use approval_modal_view::ApprovalModalView;
+use codex_core::protocol::McpElicitationRequestEvent;
+use mcp_elicitation_view::McpElicitationView;
 use codex_core::config_types::ReasoningEffort;
 use codex_core::config_types::TextVerbosity;
 use codex_core::config_types::ThemeName;
@@ -320,6 +324,11 @@ impl BottomPane<'_> {
         self.request_redraw();
     }
 
+    /// Replace the MCP prompts offered as slash commands in the composer.
+    pub(crate) fn set_mcp_prompts(&mut self, mcp_prompts: Vec<McpPromptCommand>) {
+        self.composer.set_mcp_prompts(mcp_prompts);
+    }
+
     /// Called when the agent requests user approval.
     pub fn push_approval_request(&mut self, request: ApprovalRequest) {
         let request = if let Some(view) = self.active_view.as_mut() {
@@ -343,6 +352,14 @@ impl BottomPane<'_> {
         self.request_redraw()
     }
 
+    /// Show the form for the input an MCP server requested.
+    pub(crate) fn show_mcp_elicitation(&mut self, request: McpElicitationRequestEvent) {
+        let view = McpElicitationView::new(request, self.app_event_tx.clone());
+        self.active_view = Some(Box::new(view));
+        self.status_view_active = false;
+        self.request_redraw()
+    }
+
     /// Show the reasoning selection UI
     pub fn show_reasoning_selection(&mut self, current_effort: ReasoningEffort) {
         let view = ReasoningSelectionView::new(current_effort, self.app_event_tx.clone());
@@ -567,6 +584,7 @@ mod tests {
             id: "1".to_string(),
             command: vec!["echo".into(), "ok".into()],
             reason: None,
+            grant_root: None,
         }
     }
 
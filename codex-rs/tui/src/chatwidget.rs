@@ -24,7 +24,10 @@ use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::AgentStatusUpdateEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::BackgroundProcessInfo;
+use codex_core::protocol::BackgroundProcessUpdateEvent;
 use codex_core::protocol::BrowserScreenshotUpdateEvent;
+use codex_core::protocol::BrowserTabSummary;
 use codex_core::protocol::CustomToolCallBeginEvent;
 use codex_core::protocol::CustomToolCallEndEvent;
 use codex_core::protocol::ErrorEvent;
@@ -33,10 +36,14 @@ use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::ExecSnapshotEvent;
+use codex_core::protocol::ExecSnapshotStatus;
 use codex_core::protocol::InputItem;
 use codex_protocol::protocol::McpListToolsResponseEvent;
+use codex_core::protocol::McpSamplingRequestEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::McpToolCallProgressEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
@@ -46,6 +53,7 @@ use codex_core::protocol::TurnDiffEvent;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use image::imageops::FilterType;
+use mcp_types::SamplingMessageContent;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Constraint;
 use ratatui::layout::Layout;
@@ -68,6 +76,7 @@ use crate::bottom_pane::CancellationEvent;
 use crate::bottom_pane::InputResult;
 use crate::bottom_pane::list_selection_view::SelectionAction;
 use crate::bottom_pane::list_selection_view::SelectionItem;
+use crate::exec_command::strip_bash_lc_and_escape;
 use crate::history_cell;
 use crate::history_cell::CommandOutput;
 use crate::history_cell::ExecCell;
@@ -152,6 +161,8 @@ pub(crate) struct ChatWidget<'a> {
     welcome_shown: bool,
     // Path to the latest browser screenshot and URL for display
     latest_browser_screenshot: Arc<Mutex<Option<(PathBuf, String)>>>,
+    // Active browser tab reported with the latest screenshot
+    browser_tab: Option<BrowserTabSummary>,
     // Cached image protocol to avoid recreating every frame (path, area, protocol)
     cached_image_protocol:
         std::cell::RefCell<Option<(PathBuf, Rect, ratatui_image::protocol::Protocol)>>,
@@ -178,6 +189,10 @@ pub(crate) struct ChatWidget<'a> {
     // Each tuple is (value, is_completed) where is_completed indicates if any agent was complete at that time
     sparkline_data: std::cell::RefCell<Vec<(u64, bool)>>,
     last_sparkline_update: std::cell::RefCell<std::time::Instant>,
+    // Background processes started via the `background_process` tool
+    background_processes: Vec<BackgroundProcessInfo>,
+    // Exec snapshots that have not been committed or rolled back yet
+    exec_snapshots: Vec<ExecSnapshotEvent>,
     // Stream controller for managing streaming content
     stream: crate::streaming::controller::StreamController,
     // Track which stream kind is currently active for grouping history inserts
@@ -567,6 +582,27 @@ impl ChatWidget<'_> {
             id,
             command: ev.command,
             reason: ev.reason,
+            grant_root: ev.grant_root,
+        });
+    }
+
+    /// Ask the user to approve an MCP server's request to sample from the model.
+    fn handle_mcp_sampling_request_now(&mut self, request: McpSamplingRequestEvent) {
+        let message = request
+            .messages
+            .last()
+            .map(|message| match &message.content {
+                SamplingMessageContent::TextContent(text) => text.text.clone(),
+                SamplingMessageContent::ImageContent(_) => "[image]".to_string(),
+                SamplingMessageContent::AudioContent(_) => "[audio]".to_string(),
+            })
+            .unwrap_or_default();
+        self.bottom_pane.push_approval_request(ApprovalRequest::McpSampling {
+            server: request.server,
+            request_id: request.request_id,
+            message,
+            system_prompt: request.system_prompt,
+            max_tokens: request.max_tokens,
         });
     }
 
@@ -765,17 +801,41 @@ impl ChatWidget<'_> {
         }
     }
 
+    /// Handle MCP tool call progress immediately
+    fn handle_mcp_progress_now(&mut self, ev: McpToolCallProgressEvent) {
+        let McpToolCallProgressEvent { call_id, progress, total, message } = ev;
+        let Some(&idx) = self.running_custom_tools.get(&call_id) else {
+            return;
+        };
+        let Some(cell) = self.history_cells.get_mut(idx) else {
+            return;
+        };
+        if let Some(running) = cell.as_any_mut().downcast_mut::<history_cell::RunningToolCallCell>() {
+            running.set_progress(progress, total, message);
+            self.invalidate_height_cache();
+            self.request_redraw();
+        }
+    }
+
     /// Handle MCP tool call end immediately
     fn handle_mcp_end_now(&mut self, ev: McpToolCallEndEvent) {
         let McpToolCallEndEvent { call_id, duration, invocation, result } = ev;
         // Determine success from result
-        let success = !result.as_ref().map(|r| r.is_error.unwrap_or(false)).unwrap_or(false);
+        let success = result.as_ref().is_ok_and(|r| !r.is_error.unwrap_or(false));
+        let font_size = self.measured_font_size();
+        let picker = self
+            .cached_picker
+            .borrow()
+            .clone()
+            .unwrap_or_else(|| Picker::from_fontsize(font_size));
         let completed = history_cell::new_completed_mcp_tool_call(
             80, // TODO: use actual terminal width
             invocation,
             duration,
             success,
             result,
+            picker,
+            font_size,
         );
         if let Some(idx) = self.running_custom_tools.remove(&call_id) {
             if idx < self.history_cells.len() {
@@ -964,7 +1024,8 @@ impl ChatWidget<'_> {
             .map(|lock| lock.is_some())
             .unwrap_or(false);
         let has_active_agents = !self.active_agents.is_empty() || self.agents_ready_to_start;
-        let hud_present = has_browser_screenshot || has_active_agents;
+        let hud_present =
+            has_browser_screenshot || has_active_agents || self.has_running_background_processes();
 
         // Centralized layout path (always enabled)
         let bottom_desired = self.bottom_pane.desired_height(area.width);
@@ -1085,6 +1146,7 @@ impl ChatWidget<'_> {
             pending_images: HashMap::new(),
             welcome_shown: false,
             latest_browser_screenshot: Arc::new(Mutex::new(None)),
+            browser_tab: None,
             cached_image_protocol: RefCell::new(None),
             cached_picker: RefCell::new(terminal_info.picker.clone()),
             cached_cell_size: std::cell::OnceCell::new(),
@@ -1098,6 +1160,8 @@ impl ChatWidget<'_> {
             agent_task: None,
             overall_task_status: "preparing".to_string(),
             sparkline_data: std::cell::RefCell::new(Vec::new()),
+            background_processes: Vec::new(),
+            exec_snapshots: Vec::new(),
             last_sparkline_update: std::cell::RefCell::new(std::time::Instant::now()),
             stream: crate::streaming::controller::StreamController::new(config.clone()),
             current_stream_kind: None,
@@ -2218,6 +2282,13 @@ impl ChatWidget<'_> {
                     },
                 );
             }
+            EventMsg::McpToolCallProgress(ev) => {
+                let ev2 = ev.clone();
+                self.defer_or_handle(
+                    |interrupts| interrupts.push_mcp_progress(ev),
+                    |this| this.handle_mcp_progress_now(ev2),
+                );
+            }
             EventMsg::McpToolCallEnd(ev) => {
                 let ev2 = ev.clone();
                 self.defer_or_handle(
@@ -2402,10 +2473,72 @@ impl ChatWidget<'_> {
                 }
                 self.request_redraw();
             }
+            EventMsg::ExecSnapshot(event) => {
+                let files = event.added.len() + event.modified.len() + event.deleted.len();
+                match event.status {
+                    ExecSnapshotStatus::Pending => {
+                        let mut msg = format!(
+                            "{} changed {files} file(s) — /snapshot commit {} to keep, /snapshot rollback {} to undo, /snapshot diff {} to review",
+                            strip_bash_lc_and_escape(&event.command),
+                            event.snapshot_id,
+                            event.snapshot_id,
+                            event.snapshot_id,
+                        );
+                        for (marker, paths) in [
+                            ("A", &event.added),
+                            ("M", &event.modified),
+                            ("D", &event.deleted),
+                        ] {
+                            for path in paths {
+                                msg.push_str(&format!("\n  {marker} {}", path.display()));
+                            }
+                        }
+                        self.add_to_history(history_cell::new_background_event(msg));
+                        self.exec_snapshots.push(event);
+                    }
+                    ExecSnapshotStatus::Committed | ExecSnapshotStatus::RolledBack => {
+                        let verb = if event.status == ExecSnapshotStatus::Committed {
+                            "Committed"
+                        } else {
+                            "Rolled back"
+                        };
+                        self.add_to_history(history_cell::new_background_event(format!(
+                            "{verb} changes to {files} file(s) from {}",
+                            event.snapshot_id
+                        )));
+                        self.exec_snapshots
+                            .retain(|snapshot| snapshot.snapshot_id != event.snapshot_id);
+                    }
+                }
+            }
+            EventMsg::McpPrompts(event) => {
+                self.bottom_pane
+                    .set_mcp_prompts(crate::slash_command::mcp_prompt_commands(event.prompts));
+            }
+            EventMsg::McpElicitationRequest(request) => {
+                self.bottom_pane.show_mcp_elicitation(request);
+            }
+            EventMsg::McpSamplingRequest(request) => {
+                self.handle_mcp_sampling_request_now(request);
+                self.request_redraw();
+            }
+            EventMsg::McpListServersResponse(event) => {
+                self.add_to_history(history_cell::new_mcp_servers_output(&event.servers));
+            }
+            EventMsg::BackgroundProcessUpdate(BackgroundProcessUpdateEvent { processes }) => {
+                self.background_processes = processes;
+                self.request_redraw();
+            }
             EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
                 screenshot_path,
                 url,
+                tab,
             }) => {
+                // Screenshots captured inside the TUI do not know the tab;
+                // keep the last one core reported.
+                if tab.is_some() {
+                    self.browser_tab = tab;
+                }
                 tracing::info!(
                     "Received browser screenshot update: {} at URL: {}",
                     screenshot_path.display(),
@@ -2693,6 +2826,93 @@ impl ChatWidget<'_> {
         }
     }
 
+/// Expand an MCP server prompt chosen from the slash-command popup and
+    /// submit it. `command_text` is what the user typed, including arguments.
+    pub(crate) fn run_mcp_prompt(
+        &mut self,
+        command: crate::slash_command::McpPromptCommand,
+        command_text: String,
+    ) {
+        let cmd_with_slash = format!("/{}", command.command());
+        let args = command_text
+            .trim()
+            .strip_prefix(&cmd_with_slash)
+            .unwrap_or_default();
+        let arguments = match command.parse_arguments(args) {
+            Ok(arguments) => arguments,
+            Err(e) => {
+                self.add_to_history(history_cell::new_error_event(format!(
+                    "{cmd_with_slash}: {e}. Usage: {cmd_with_slash} {}",
+                    command.description()
+                )));
+                return;
+            }
+        };
+        for cell in &self.history_cells {
+            cell.trigger_fade();
+        }
+        self.add_to_history(history_cell::new_user_prompt(command_text.clone()));
+        self.submit_op(Op::AddToHistory { text: command_text });
+        self.submit_op(Op::McpPrompt {
+            server: command.server,
+            name: command.prompt.name,
+            arguments,
+        });
+    }
+
+    pub(crate) fn handle_snapshot_command(&mut self, command_args: String) {
+        let mut args = command_args.split_whitespace();
+        let action = args.next().unwrap_or("list");
+        let snapshot_id = args.next().map(str::to_string);
+
+        match action {
+            "list" => {
+                let msg = if self.exec_snapshots.is_empty() {
+                    "No pending exec snapshots. Enable `exec_snapshots = true` in config.toml to record file changes made by shell commands.".to_string()
+                } else {
+                    let mut msg = "Pending exec snapshots:".to_string();
+                    for snapshot in &self.exec_snapshots {
+                        msg.push_str(&format!(
+                            "\n- {}: {} ({} file(s))",
+                            snapshot.snapshot_id,
+                            strip_bash_lc_and_escape(&snapshot.command),
+                            snapshot.added.len() + snapshot.modified.len() + snapshot.deleted.len(),
+                        ));
+                    }
+                    msg
+                };
+                self.add_to_history(history_cell::new_background_event(msg));
+            }
+            "diff" => {
+                let snapshot = match &snapshot_id {
+                    Some(id) => self.exec_snapshots.iter().find(|s| &s.snapshot_id == id),
+                    None => self.exec_snapshots.last(),
+                };
+                match snapshot {
+                    Some(snapshot) => self.add_diff_output(snapshot.unified_diff.clone()),
+                    None => self.add_to_history(history_cell::new_background_event(
+                        "No matching exec snapshot".to_string(),
+                    )),
+                }
+            }
+            "commit" | "rollback" => {
+                // The list is updated from the `ExecSnapshot` events core
+                // sends once the snapshots are actually resolved.
+                let op = if action == "commit" {
+                    Op::CommitExecSnapshot { snapshot_id }
+                } else {
+                    Op::RollbackExecSnapshot { snapshot_id }
+                };
+                self.submit_op(op);
+            }
+            other => {
+                self.add_to_history(history_cell::new_background_event(format!(
+                    "Unknown /snapshot action '{other}'. Usage: /snapshot [list|diff|commit|rollback] [id]"
+                )));
+            }
+        }
+    }
+
     pub(crate) fn handle_memory_command(&mut self, command_args: String) {
         // Show or set memory parameters.
         let defaults = codex_core::config_types::MemoryConfig::default();
@@ -2986,6 +3206,8 @@ impl ChatWidget<'_> {
             } else if let Some(tool) = cell.as_any_mut().downcast_mut::<history_cell::ToolCallCell>() {
                 tool.retint(&old, &new);
                 
+            } else if let Some(tool) = cell.as_any_mut().downcast_mut::<history_cell::McpToolResultCell>() {
+                tool.retint(&old, &new);
             } else if let Some(reason) = cell.as_any_mut().downcast_mut::<history_cell::CollapsibleReasoningCell>() {
                 history_cell::retint_lines_in_place(&mut reason.lines, &old, &new);
             } else if let Some(stream) = cell.as_any_mut().downcast_mut::<history_cell::StreamingContentCell>() {
@@ -3598,6 +3820,7 @@ impl ChatWidget<'_> {
                                                 msg: EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
                                                     screenshot_path: first_path.clone(),
                                                     url: url_inner,
+                                                    tab: None,
                                                 }),
                                             }));
                                             break;
@@ -3647,6 +3870,7 @@ impl ChatWidget<'_> {
                                                 msg: EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
                                                     screenshot_path: first_path.clone(),
                                                     url: url.unwrap_or_else(|| "Chrome".to_string()),
+                                                    tab: None,
                                                 }),
                                             }));
                                             break;
@@ -3751,6 +3975,7 @@ impl ChatWidget<'_> {
                                                                 msg: EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
                                                                     screenshot_path: first_path.clone(),
                                                                     url: url_inner,
+                                                                    tab: None,
                                                                 }),
                                                             }));
                                                             break;
@@ -3796,6 +4021,7 @@ impl ChatWidget<'_> {
                                                             msg: EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
                                                                 screenshot_path: first_path.clone(),
                                                                 url: url.unwrap_or_else(|| "Chrome".to_string()),
+                                                                tab: None,
                                                             }),
                                                         }));
                                                         break;
@@ -4145,6 +4371,7 @@ impl ChatWidget<'_> {
                                                             BrowserScreenshotUpdateEvent {
                                                                 screenshot_path: first_path.clone(),
                                                                 url: url_inner,
+                                                                tab: None,
                                                             },
                                                         ),
                                                     }),
@@ -4203,6 +4430,7 @@ impl ChatWidget<'_> {
                                                     msg: EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
                                                         screenshot_path: first_path.clone(),
                                                         url: url_inner,
+                                                        tab: None,
                                                     }),
                                                 }));
                                             }
@@ -4261,6 +4489,7 @@ impl ChatWidget<'_> {
                                                 BrowserScreenshotUpdateEvent {
                                                     screenshot_path: first_path.clone(),
                                                     url: url.unwrap_or_else(|| result.url.clone()),
+                                                    tab: None,
                                                 },
                                             ),
                                         }));
@@ -4481,6 +4710,7 @@ impl ChatWidget<'_> {
                             msg: EventMsg::BrowserScreenshotUpdate(BrowserScreenshotUpdateEvent {
                                 screenshot_path: first_path.clone(),
                                 url: url.unwrap_or_else(|| "Browser".to_string()),
+                                tab: None,
                             }),
                         }));
                     }
@@ -5037,7 +5267,14 @@ impl ChatWidget<'_> {
 }
 
 impl ChatWidget<'_> {
-    /// Render the combined HUD with browser and/or agent panels based on what's active
+    fn has_running_background_processes(&self) -> bool {
+        self.background_processes
+            .iter()
+            .any(|process| process.status == "running")
+    }
+
+    /// Render the combined HUD with browser, agent and background process
+    /// panels based on what's active
     fn render_hud(&self, area: Rect, buf: &mut Buffer) {
         // Check what's active
         let has_browser_screenshot = self
@@ -5046,6 +5283,7 @@ impl ChatWidget<'_> {
             .map(|lock| lock.is_some())
             .unwrap_or(false);
         let has_active_agents = !self.active_agents.is_empty() || self.agents_ready_to_start;
+        let has_background_processes = self.has_running_background_processes();
 
         // Add same horizontal padding as the Message input (2 chars on each side)
         let horizontal_padding = 1u16;
@@ -5058,13 +5296,22 @@ impl ChatWidget<'_> {
 
         // Determine layout based on what's active
         if has_browser_screenshot && has_active_agents {
-            // Both panels: 50/50 split
+            // Browser on the left, agents (and background processes) on the right
             let chunks =
                 Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
                     .areas::<2>(padded_area);
 
             self.render_browser_panel(chunks[0], buf);
-            self.render_agent_panel(chunks[1], buf);
+            // Background processes stack under the agents when there are any
+            if has_background_processes {
+                let [agents, background] =
+                    Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
+                        .areas(chunks[1]);
+                self.render_agent_panel(agents, buf);
+                self.render_background_process_panel(background, buf);
+            } else {
+                self.render_agent_panel(chunks[1], buf);
+            }
         } else if has_browser_screenshot {
             // Only browser: 50% width on the left side
             let chunks =
@@ -5072,7 +5319,10 @@ impl ChatWidget<'_> {
                     .areas::<2>(padded_area);
 
             self.render_browser_panel(chunks[0], buf);
-            // Right side remains empty
+            // Background processes use the right side when there are any
+            if has_background_processes {
+                self.render_background_process_panel(chunks[1], buf);
+            }
         } else if has_active_agents {
             // Only agents: 50% width on the left side
             let chunks =
@@ -5080,7 +5330,17 @@ impl ChatWidget<'_> {
                     .areas::<2>(padded_area);
 
             self.render_agent_panel(chunks[0], buf);
-            // Right side remains empty
+            // Background processes use the right side when there are any
+            if has_background_processes {
+                self.render_background_process_panel(chunks[1], buf);
+            }
+        } else if has_background_processes {
+            // Only background processes: 50% width on the left side
+            let chunks =
+                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .areas::<2>(padded_area);
+
+            self.render_background_process_panel(chunks[0], buf);
         }
     }
 
@@ -5095,7 +5355,12 @@ impl ChatWidget<'_> {
                 // Use the full area for the browser preview
                 let screenshot_block = Block::default()
                     .borders(Borders::ALL)
-                    .title(format!(" {} ", url))
+                    .title(match &self.browser_tab {
+                        Some(tab) if tab.count > 1 => {
+                            format!(" [{}/{}] {} ", tab.index, tab.count, url)
+                        }
+                        _ => format!(" {} ", url),
+                    })
                     .border_style(Style::default().fg(crate::colors::border()));
 
                 let inner_screenshot = screenshot_block.inner(area);
@@ -5107,6 +5372,49 @@ impl ChatWidget<'_> {
         }
     }
 
+    /// Render the background process panel in the HUD
+    fn render_background_process_panel(&self, area: Rect, buf: &mut Buffer) {
+        use ratatui::text::Line as RLine;
+        use ratatui::text::Span;
+        use ratatui::widgets::Block;
+        use ratatui::widgets::Borders;
+        use ratatui::widgets::Paragraph;
+        use ratatui::widgets::Widget;
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" Background ")
+            .border_style(Style::default().fg(crate::colors::border()));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let mut lines = vec![RLine::from(" ")];
+        for process in &self.background_processes {
+            let (status, color) = match (process.status.as_str(), process.exit_code) {
+                ("running", _) => ("running".to_string(), crate::colors::info()),
+                ("exited", Some(0)) => ("exited 0".to_string(), crate::colors::success()),
+                ("exited", Some(code)) => (format!("exited {code}"), crate::colors::error()),
+                (other, _) => (other.to_string(), crate::colors::text_dim()),
+            };
+            lines.push(RLine::from(vec![
+                Span::from(" "),
+                Span::styled(
+                    format!("{}: ", process.id),
+                    Style::default()
+                        .fg(crate::colors::text())
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(status, Style::default().fg(color)),
+                Span::from(" "),
+                Span::styled(
+                    process.command.clone(),
+                    Style::default().fg(crate::colors::text_dim()),
+                ),
+            ]));
+        }
+        Paragraph::new(lines).render(inner, buf);
+    }
+
     /// Render the agent status panel in the HUD
     fn render_agent_panel(&self, area: Rect, buf: &mut Buffer) {
         use ratatui::text::Line as RLine;
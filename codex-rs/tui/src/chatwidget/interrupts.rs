@@ -6,6 +6,7 @@ use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::McpToolCallProgressEvent;
 use codex_core::protocol::PatchApplyEndEvent;
 
 use super::ChatWidget;
@@ -17,6 +18,7 @@ pub(crate) enum QueuedInterrupt {
     ExecBegin(ExecCommandBeginEvent),
     ExecEnd(ExecCommandEndEvent),
     McpBegin(McpToolCallBeginEvent),
+    McpProgress(McpToolCallProgressEvent),
     McpEnd(McpToolCallEndEvent),
     PatchEnd(PatchApplyEndEvent),
 }
@@ -59,6 +61,10 @@ impl InterruptManager {
         self.queue.push_back(QueuedInterrupt::McpBegin(ev));
     }
 
+    pub(crate) fn push_mcp_progress(&mut self, ev: McpToolCallProgressEvent) {
+        self.queue.push_back(QueuedInterrupt::McpProgress(ev));
+    }
+
     pub(crate) fn push_mcp_end(&mut self, ev: McpToolCallEndEvent) {
         self.queue.push_back(QueuedInterrupt::McpEnd(ev));
     }
@@ -77,6 +83,7 @@ impl InterruptManager {
                 QueuedInterrupt::ExecBegin(ev) => chat.handle_exec_begin_now(ev),
                 QueuedInterrupt::ExecEnd(ev) => chat.handle_exec_end_now(ev),
                 QueuedInterrupt::McpBegin(ev) => chat.handle_mcp_begin_now(ev),
+                QueuedInterrupt::McpProgress(ev) => chat.handle_mcp_progress_now(ev),
                 QueuedInterrupt::McpEnd(ev) => chat.handle_mcp_end_now(ev),
                 QueuedInterrupt::PatchEnd(ev) => chat.handle_patch_apply_end_now(ev),
             }
@@ -14,6 +14,8 @@ use codex_core::plan_tool::UpdatePlanArgs;
 use codex_core::protocol::FileChange;
 use crate::diff_render::create_diff_summary;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::McpServerStatus;
+use codex_core::protocol::McpServerSummary;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::TokenUsage;
 use image::DynamicImage;
@@ -29,6 +31,8 @@ use ratatui::widgets::Block;
 use ratatui::widgets::Borders;
 use ratatui::widgets::WidgetRef;
 use ratatui::widgets::Wrap;
+use ratatui_image::picker::Picker;
+use ratatui_image::protocol::Protocol;
 use std::collections::HashMap;
 use std::io::Cursor;
 use std::path::PathBuf;
@@ -762,19 +766,145 @@ impl HistoryCell for LoadingCell {
 
 // ==================== ImageOutputCell ====================
 
+/// Tallest an inline image is drawn, in rows.
+const MAX_IMAGE_ROWS: u16 = 24;
+
+/// An image drawn inline with the terminal's image protocol, as browser
+/// screenshots are.
 pub(crate) struct ImageOutputCell {
-    #[allow(dead_code)] // Will be used for terminal image protocol support
     pub(crate) image: DynamicImage,
+    picker: Picker,
+    /// Terminal cell size (width, height) in pixels.
+    font_size: (u16, u16),
+    // Cached protocol to avoid re-encoding every frame (area, protocol)
+    protocol: std::cell::RefCell<Option<(Rect, Protocol)>>,
+}
+
+impl ImageOutputCell {
+    pub(crate) fn new(image: DynamicImage, picker: Picker, font_size: (u16, u16)) -> Self {
+        Self { image, picker, font_size, protocol: std::cell::RefCell::new(None) }
+    }
+
+    /// Columns and rows the image takes at `width`; never scaled up.
+    fn size_in_cells(&self, width: u16) -> (u16, u16) {
+        let (cw, ch) = (self.font_size.0.max(1) as u32, self.font_size.1.max(1) as u32);
+        let (img_w, img_h) = (self.image.width().max(1), self.image.height().max(1));
+        let mut cols = (width as u32).min(img_w.div_ceil(cw)).max(1);
+        let mut rows = (cols * cw * img_h) / (img_w * ch);
+        if rows > MAX_IMAGE_ROWS as u32 {
+            rows = MAX_IMAGE_ROWS as u32;
+            cols = ((rows * ch * img_w) / (img_h * cw)).max(1);
+        }
+        (cols as u16, rows.max(1) as u16)
+    }
 }
 
 impl HistoryCell for ImageOutputCell {
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any { self }
     fn kind(&self) -> HistoryCellType { HistoryCellType::Image }
     fn display_lines(&self) -> Vec<Line<'static>> {
-        vec![
-            Line::from("tool result (image output omitted)"),
-            Line::from(""),
-        ]
+        vec![Line::from(format!("[image {}x{}]", self.image.width(), self.image.height())).dim()]
+    }
+    fn desired_height(&self, width: u16) -> u16 {
+        self.size_in_cells(width).1
+    }
+    fn has_custom_render(&self) -> bool { true }
+    fn custom_render_with_skip(&self, area: Rect, buf: &mut Buffer, skip_rows: u16) {
+        use ratatui_image::Image;
+        use ratatui_image::Resize;
+
+        let (cols, rows) = self.size_in_cells(area.width);
+        // Image protocols cannot draw part of an image; show a placeholder
+        // while the image is scrolled partly out of view.
+        if skip_rows > 0 || area.height < rows {
+            Paragraph::new(Text::from(self.display_lines())).render(area, buf);
+            return;
+        }
+        let target = Rect { width: cols, height: rows, ..area };
+
+        let needs_recreate = match self.protocol.borrow().as_ref() {
+            Some((rect, _)) => *rect != target,
+            None => true,
+        };
+        if needs_recreate {
+            match self.picker.new_protocol(self.image.clone(), target, Resize::Fit(None)) {
+                Ok(protocol) => *self.protocol.borrow_mut() = Some((target, protocol)),
+                Err(e) => {
+                    error!("Failed to prepare image for display: {e:?}");
+                    Paragraph::new(Text::from(self.display_lines())).render(area, buf);
+                    return;
+                }
+            }
+        }
+        if let Some((rect, protocol)) = &*self.protocol.borrow() {
+            Image::new(protocol).render(*rect, buf);
+        }
+    }
+}
+
+// ==================== McpToolResultCell ====================
+
+/// A completed MCP tool call whose result includes images, which are drawn
+/// beneath the call's summary.
+pub(crate) struct McpToolResultCell {
+    tool: ToolCallCell,
+    images: Vec<ImageOutputCell>,
+}
+
+impl McpToolResultCell {
+    pub(crate) fn retint(&mut self, old: &crate::theme::Theme, new: &crate::theme::Theme) {
+        self.tool.retint(old, new);
+    }
+}
+
+impl HistoryCell for McpToolResultCell {
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any { self }
+    fn as_any(&self) -> &dyn std::any::Any { self }
+    fn kind(&self) -> HistoryCellType { self.tool.kind() }
+    fn display_lines(&self) -> Vec<Line<'static>> {
+        let mut lines = self.tool.display_lines_trimmed();
+        for image in &self.images {
+            lines.extend(image.display_lines());
+        }
+        lines
+    }
+    fn desired_height(&self, width: u16) -> u16 {
+        self.images
+            .iter()
+            .fold(self.tool.desired_height(width), |height, image| {
+                height.saturating_add(image.desired_height(width))
+            })
+    }
+    fn has_custom_render(&self) -> bool { true }
+    fn custom_render_with_skip(&self, area: Rect, buf: &mut Buffer, skip_rows: u16) {
+        let mut skip = skip_rows;
+        let mut y = area.y;
+        let bottom = area.bottom();
+
+        let text_height = self.tool.desired_height(area.width);
+        if skip < text_height {
+            let height = (text_height - skip).min(area.height);
+            self.tool.render_with_skip(Rect { height, ..area }, buf, skip);
+            y += height;
+            skip = 0;
+        } else {
+            skip -= text_height;
+        }
+
+        for image in &self.images {
+            let image_height = image.desired_height(area.width);
+            if skip >= image_height {
+                skip -= image_height;
+                continue;
+            }
+            if y >= bottom {
+                break;
+            }
+            let height = (image_height - skip).min(bottom - y);
+            image.custom_render_with_skip(Rect { y, height, ..area }, buf, skip);
+            y += height;
+            skip = 0;
+        }
     }
 }
 
@@ -821,6 +951,27 @@ pub(crate) struct RunningToolCallCell {
     title: String,
     start_time: Instant,
     arg_lines: Vec<Line<'static>>,
+    /// Latest progress reported by an MCP server, if any.
+    progress: Option<String>,
+}
+
+impl RunningToolCallCell {
+    /// Show the latest progress report beneath the arguments.
+    pub(crate) fn set_progress(
+        &mut self,
+        progress: f64,
+        total: Option<f64>,
+        message: Option<String>,
+    ) {
+        let amount = match total {
+            Some(total) if total > 0.0 => format!("{:.0}%", (progress / total * 100.0).min(100.0)),
+            _ => format!("{progress}"),
+        };
+        self.progress = Some(match message {
+            Some(message) if !message.is_empty() => format!("{amount} · {message}"),
+            _ => amount,
+        });
+    }
 }
 
 impl HistoryCell for RunningToolCallCell {
@@ -836,6 +987,9 @@ impl HistoryCell for RunningToolCallCell {
             Style::default().fg(crate::colors::primary()).add_modifier(Modifier::BOLD),
         ));
         lines.extend(self.arg_lines.clone());
+        if let Some(progress) = &self.progress {
+            lines.push(Line::from(format!("└ {progress}").dim()));
+        }
         lines.push(Line::from(""));
         lines
     }
@@ -1826,6 +1980,17 @@ fn browser_running_title(tool_name: &str) -> &'static str {
         "browser_status" => "Checking status...",
         "browser_history" => "Navigating...",
         "browser_inspect" => "Inspecting...",
+        "browser_snapshot" => "Reading page...",
+        "browser_screenshot" => "Comparing screenshot...",
+        "browser_network" => "Reading network...",
+        "browser_tabs" => "Switching tabs...",
+        "browser_script" => "Running browser script...",
+        "browser_perf" => "Measuring performance...",
+        "browser_state" => "Updating browser state...",
+        "browser_responsive" => "Capturing devices...",
+        "browser_dialog" => "Handling dialog...",
+        "browser_upload" => "Uploading files...",
+        "browser_downloads" => "Checking downloads...",
         "browser_console" => "Reading console...",
         "browser_move" => "Moving...",
         _ => "Working...",
@@ -1844,6 +2009,7 @@ pub(crate) fn new_running_browser_tool_call(tool_name: String, args: Option<Stri
         title: browser_running_title(&tool_name).to_string(),
         start_time: Instant::now(),
         arg_lines,
+        progress: None,
     }
 }
 
@@ -1889,6 +2055,7 @@ pub(crate) fn new_running_custom_tool_call(tool_name: String, args: Option<Strin
         title: custom_tool_running_title(&tool_name),
         start_time: Instant::now(),
         arg_lines,
+        progress: None,
     }
 }
 
@@ -1899,6 +2066,7 @@ pub(crate) fn new_running_mcp_tool_call(invocation: McpInvocation) -> RunningToo
         title: "Working...".to_string(),
         start_time: Instant::now(),
         arg_lines: vec![line],
+        progress: None,
     }
 }
 
@@ -1973,6 +2141,17 @@ fn browser_tool_title(tool_name: &str) -> &'static str {
         "browser_status" => "Browser Status",
         "browser_history" => "Browser History",
         "browser_inspect" => "Browser Inspect",
+        "browser_snapshot" => "Browser Snapshot",
+        "browser_screenshot" => "Browser Screenshot",
+        "browser_network" => "Browser Network",
+        "browser_tabs" => "Browser Tabs",
+        "browser_script" => "Browser Script",
+        "browser_perf" => "Browser Performance",
+        "browser_state" => "Browser State",
+        "browser_responsive" => "Browser Responsive",
+        "browser_dialog" => "Browser Dialog",
+        "browser_upload" => "Browser Upload",
+        "browser_downloads" => "Browser Downloads",
         "browser_console" => "Browser Console",
         "browser_cdp" => "Browser CDP",
         "browser_move" => "Browser Move",
@@ -2166,43 +2345,54 @@ fn new_completed_agent_tool_call(
     ToolCallCell { lines, state: if success { ToolState::Success } else { ToolState::Failed } }
 }
 
-// Try to create an image cell if the MCP result contains an image
-fn try_new_completed_mcp_tool_call_with_image_output(
-    result: &Result<mcp_types::CallToolResult, String>,
-) -> Option<ImageOutputCell> {
-    match result {
-        Ok(mcp_types::CallToolResult { content, .. }) => {
-            if let Some(mcp_types::ContentBlock::ImageContent(image)) = content.first() {
-                let raw_data = match base64::engine::general_purpose::STANDARD.decode(&image.data) {
-                    Ok(data) => data,
-                    Err(e) => {
-                        error!("Failed to decode image data: {e}");
-                        return None;
-                    }
-                };
-                let reader = match ImageReader::new(Cursor::new(raw_data)).with_guessed_format() {
-                    Ok(reader) => reader,
-                    Err(e) => {
-                        error!("Failed to guess image format: {e}");
-                        return None;
-                    }
-                };
-
-                let image = match reader.decode() {
-                    Ok(image) => image,
-                    Err(e) => {
-                        error!("Image decoding failed: {e}");
-                        return None;
-                    }
-                };
-
-                Some(ImageOutputCell { image })
-            } else {
-                None
+/// Decode the images in an MCP tool result: image content and embedded
+/// image blobs.
+fn decode_mcp_result_images(content: &[mcp_types::ContentBlock]) -> Vec<DynamicImage> {
+    content
+        .iter()
+        .filter_map(|block| match block {
+            mcp_types::ContentBlock::ImageContent(image) => Some(&image.data),
+            mcp_types::ContentBlock::EmbeddedResource(resource) => match &resource.resource {
+                EmbeddedResourceResource::BlobResourceContents(blob)
+                    if blob.mime_type.as_deref().is_some_and(|m| m.starts_with("image/")) =>
+                {
+                    Some(&blob.blob)
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter_map(|data| {
+            let raw_data = match base64::engine::general_purpose::STANDARD.decode(data) {
+                Ok(data) => data,
+                Err(e) => {
+                    error!("Failed to decode image data: {e}");
+                    return None;
+                }
+            };
+            let reader = match ImageReader::new(Cursor::new(raw_data)).with_guessed_format() {
+                Ok(reader) => reader,
+                Err(e) => {
+                    error!("Failed to guess image format: {e}");
+                    return None;
+                }
+            };
+            match reader.decode() {
+                Ok(image) => Some(image),
+                Err(e) => {
+                    error!("Image decoding failed: {e}");
+                    None
+                }
             }
-        }
-        _ => None,
-    }
+        })
+        .collect()
+}
+
+fn dim_preview_lines(text: &str) -> Vec<Line<'static>> {
+    build_preview_lines(text, true)
+        .into_iter()
+        .map(|l| l.style(Style::default().fg(crate::colors::text_dim())))
+        .collect()
 }
 
 pub(crate) fn new_completed_mcp_tool_call(
@@ -2211,11 +2401,9 @@ pub(crate) fn new_completed_mcp_tool_call(
     duration: Duration,
     success: bool,
     result: Result<mcp_types::CallToolResult, String>,
+    picker: Picker,
+    font_size: (u16, u16),
 ) -> Box<dyn HistoryCell> {
-    if let Some(cell) = try_new_completed_mcp_tool_call_with_image_output(&result) {
-        return Box::new(cell);
-    }
-
     let duration = format_duration(duration);
     let status_str = if success { "Complete" } else { "Error" };
     let title_line = if success {
@@ -2234,41 +2422,58 @@ pub(crate) fn new_completed_mcp_tool_call(
     lines.push(title_line);
     lines.push(format_mcp_invocation(invocation));
 
+    let mut images = Vec::new();
     match result {
-        Ok(mcp_types::CallToolResult { content, .. }) => {
-            if !content.is_empty() {
+        Ok(mcp_types::CallToolResult { content, structured_content, .. }) => {
+            images = decode_mcp_result_images(&content);
+            let has_text = content
+                .iter()
+                .any(|block| matches!(block, mcp_types::ContentBlock::TextContent(_)));
+            if !content.is_empty() || structured_content.is_some() {
                 lines.push(Line::from(""));
+            }
 
-                for tool_call_result in content {
-                    match tool_call_result {
-                        mcp_types::ContentBlock::TextContent(text) => {
-                            let mut preview = build_preview_lines(&text.text, true);
-                            preview = preview
-                                .into_iter()
-                                .map(|l| l.style(Style::default().fg(crate::colors::text_dim())))
-                                .collect();
-                            lines.extend(preview);
-                        }
-                        mcp_types::ContentBlock::ImageContent(_) => {
-                            lines.push(Line::from("<image content>".to_string()))
-                        }
-                        mcp_types::ContentBlock::AudioContent(_) => {
-                            lines.push(Line::from("<audio content>".to_string()))
-                        }
-                        mcp_types::ContentBlock::EmbeddedResource(resource) => {
-                            let uri = match resource.resource {
-                                EmbeddedResourceResource::TextResourceContents(text) => text.uri,
-                                EmbeddedResourceResource::BlobResourceContents(blob) => blob.uri,
-                            };
-                            lines.push(Line::from(format!("embedded resource: {uri}")));
-                        }
-                        mcp_types::ContentBlock::ResourceLink(ResourceLink { uri, .. }) => {
-                            lines.push(Line::from(format!("link: {uri}")));
+            for tool_call_result in content {
+                match tool_call_result {
+                    mcp_types::ContentBlock::TextContent(text) => {
+                        lines.extend(dim_preview_lines(&text.text));
+                    }
+                    mcp_types::ContentBlock::ImageContent(image) => {
+                        lines.push(Line::from(format!("image: {}", image.mime_type)))
+                    }
+                    mcp_types::ContentBlock::AudioContent(_) => {
+                        lines.push(Line::from("<audio content>".to_string()))
+                    }
+                    mcp_types::ContentBlock::EmbeddedResource(resource) => {
+                        match resource.resource {
+                            EmbeddedResourceResource::TextResourceContents(text) => {
+                                lines.push(Line::from(format!("embedded resource: {}", text.uri)));
+                                lines.extend(dim_preview_lines(&text.text));
+                            }
+                            EmbeddedResourceResource::BlobResourceContents(blob) => {
+                                let mime = blob.mime_type.unwrap_or_else(|| "binary".to_string());
+                                lines.push(Line::from(format!(
+                                    "embedded resource: {} ({mime})",
+                                    blob.uri
+                                )));
+                            }
                         }
                     }
+                    mcp_types::ContentBlock::ResourceLink(ResourceLink { uri, .. }) => {
+                        lines.push(Line::from(format!("link: {uri}")));
+                    }
                 }
             }
 
+            // Servers are asked to mirror structured content in a text block;
+            // show it only when they did not.
+            if let Some(structured) = structured_content
+                && !has_text
+            {
+                lines.push(Line::from("structured content:"));
+                lines.extend(dim_preview_lines(&structured.to_string()));
+            }
+
             lines.push(Line::from(""));
         }
         Err(e) => {
@@ -2285,7 +2490,15 @@ pub(crate) fn new_completed_mcp_tool_call(
         }
     }
 
-    Box::new(ToolCallCell { lines, state: if success { ToolState::Success } else { ToolState::Failed } })
+    let tool = ToolCallCell { lines, state: if success { ToolState::Success } else { ToolState::Failed } };
+    if images.is_empty() {
+        return Box::new(tool);
+    }
+    let images = images
+        .into_iter()
+        .map(|image| ImageOutputCell::new(image, picker.clone(), font_size))
+        .collect();
+    Box::new(McpToolResultCell { tool, images })
 }
 
 pub(crate) fn new_error_event(message: String) -> PlainHistoryCell {
@@ -2440,6 +2653,50 @@ pub(crate) fn new_prompts_output() -> PlainHistoryCell {
     PlainHistoryCell { lines, kind: HistoryCellType::Notice }
 }
 
+pub(crate) fn new_mcp_servers_output(servers: &[McpServerSummary]) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = vec![Line::from("/mcp".magenta()), Line::from("")];
+    if servers.is_empty() {
+        lines.push(Line::from(
+            "  No MCP servers configured. Add them under [mcp_servers] in config.toml.".dim(),
+        ));
+    }
+    for server in servers {
+        let status: Span<'static> = match &server.status {
+            McpServerStatus::NotStarted => "not started".dim(),
+            McpServerStatus::Starting => "starting".fg(crate::colors::warning()),
+            McpServerStatus::Running => "running".fg(crate::colors::success()),
+            McpServerStatus::Restarting { attempt } => {
+                format!("restarting (attempt {attempt})").fg(crate::colors::warning())
+            }
+            McpServerStatus::Failed { error } => {
+                format!("failed: {error}").fg(crate::colors::error())
+            }
+        };
+        let mut spans: Vec<Span<'static>> = vec!["  • ".into(), server.name.clone().bold()];
+        if server.lazy {
+            spans.push(" (lazy)".dim());
+        }
+        spans.push(" — ".dim());
+        spans.push(status);
+
+        let mut counts = format!("{} tools", server.tools);
+        let hidden = server.listed_tools.saturating_sub(server.tools);
+        if hidden > 0 {
+            counts.push_str(&format!(" ({hidden} hidden)"));
+        }
+        if server.resources > 0 {
+            counts.push_str(&format!(" · {} resources", server.resources));
+        }
+        if server.prompts > 0 {
+            counts.push_str(&format!(" · {} prompts", server.prompts));
+        }
+        spans.push(format!(" · {counts}").dim());
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::from(""));
+    PlainHistoryCell { lines, kind: HistoryCellType::Notice }
+}
+
 pub(crate) fn new_plan_update(update: UpdatePlanArgs) -> PlainHistoryCell {
     let UpdatePlanArgs { explanation, plan } = update;
     
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+
+use mcp_types::Prompt;
 use strum::IntoEnumIterator;
 use strum_macros::AsRefStr;
 use strum_macros::EnumIter;
@@ -18,8 +21,10 @@ pub enum SlashCommand {
     Init,
     Compact,
     Diff,
+    Snapshot,
     Mention,
     Status,
+    Mcp,
     Theme,
     Reasoning,
     Verbosity,
@@ -52,8 +57,10 @@ impl SlashCommand {
             SlashCommand::Compact => "summarize conversation to prevent hitting the context limit",
             SlashCommand::Quit => "exit Codex",
             SlashCommand::Diff => "show git diff (including untracked files)",
+            SlashCommand::Snapshot => "review, keep or roll back file changes made by commands",
             SlashCommand::Mention => "mention a file",
             SlashCommand::Status => "show current session configuration and token usage",
+            SlashCommand::Mcp => "show MCP servers and their tools",
             SlashCommand::Theme => "switch between color themes",
             SlashCommand::Prompts => "show example prompts",
             SlashCommand::Perf => "performance tracing (on/off/show/reset)",
@@ -116,6 +123,106 @@ pub fn built_in_slash_commands() -> Vec<(&'static str, SlashCommand)> {
     SlashCommand::iter().map(|c| (c.command(), c)).collect()
 }
 
+/// A prompt offered by an MCP server, invoked as `/<server>:<prompt>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct McpPromptCommand {
+    pub server: String,
+    pub prompt: Prompt,
+}
+
+impl McpPromptCommand {
+    /// Command string without the leading '/'.
+    pub fn command(&self) -> String {
+        format!("{}:{}", self.server, self.prompt.name)
+    }
+
+    /// User-visible description shown in the popup.
+    pub fn description(&self) -> String {
+        let description = self
+            .prompt
+            .description
+            .as_deref()
+            .or(self.prompt.title.as_deref())
+            .unwrap_or("MCP prompt");
+        let arguments: Vec<String> = self
+            .prompt
+            .arguments
+            .iter()
+            .flatten()
+            .map(|arg| match arg.required {
+                Some(true) => format!("<{}>", arg.name),
+                _ => format!("[{}]", arg.name),
+            })
+            .collect();
+        if arguments.is_empty() {
+            description.to_string()
+        } else {
+            format!("{description} {}", arguments.join(" "))
+        }
+    }
+
+    /// Parses the text typed after the command into prompt arguments. Values
+    /// are given as `name=value` or positionally in the order the prompt
+    /// declares its arguments; quote values that contain spaces.
+    pub fn parse_arguments(&self, args: &str) -> Result<HashMap<String, String>, String> {
+        let declared: Vec<&str> = self
+            .prompt
+            .arguments
+            .iter()
+            .flatten()
+            .map(|arg| arg.name.as_str())
+            .collect();
+        let words = shlex::split(args).ok_or_else(|| "unbalanced quotes".to_string())?;
+        let mut values = HashMap::new();
+        let mut positional = declared.iter();
+        for word in words {
+            match word.split_once('=') {
+                Some((name, value)) if declared.contains(&name) => {
+                    values.insert(name.to_string(), value.to_string());
+                }
+                _ => {
+                    let name = positional
+                        .by_ref()
+                        .find(|name| !values.contains_key(**name))
+                        .ok_or_else(|| format!("unexpected argument '{word}'"))?;
+                    values.insert(name.to_string(), word);
+                }
+            }
+        }
+        let missing: Vec<&str> = self
+            .prompt
+            .arguments
+            .iter()
+            .flatten()
+            .filter(|arg| arg.required == Some(true) && !values.contains_key(&arg.name))
+            .map(|arg| arg.name.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!(
+                "missing required argument(s): {}",
+                missing.join(", ")
+            ));
+        }
+        Ok(values)
+    }
+}
+
+/// Prompt commands for every prompt in `prompts` (server name -> prompts),
+/// sorted by command.
+pub fn mcp_prompt_commands(prompts: HashMap<String, Vec<Prompt>>) -> Vec<McpPromptCommand> {
+    let mut commands: Vec<McpPromptCommand> = prompts
+        .into_iter()
+        .flat_map(|(server, prompts)| {
+            prompts.into_iter().map(move |prompt| McpPromptCommand {
+                server: server.clone(),
+                prompt,
+            })
+        })
+        .collect();
+    commands.sort_by_key(McpPromptCommand::command);
+    commands
+}
+
 /// Process a message that might contain a slash command.
 /// Returns either the expanded prompt (for prompt-expanding commands) or the original message.
 pub fn process_slash_command_message(message: &str) -> ProcessedCommand {
@@ -168,3 +275,52 @@ pub enum ProcessedCommand {
     /// Error processing the command
     Error(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::PromptArgument;
+    use pretty_assertions::assert_eq;
+
+    fn review_prompt() -> McpPromptCommand {
+        let argument = |name: &str, required: bool| PromptArgument {
+            description: None,
+            name: name.to_string(),
+            required: Some(required),
+            title: None,
+        };
+        McpPromptCommand {
+            server: "github".to_string(),
+            prompt: Prompt {
+                arguments: Some(vec![argument("pr", true), argument("focus", false)]),
+                description: Some("Review a pull request".to_string()),
+                name: "review".to_string(),
+                title: None,
+            },
+        }
+    }
+
+    #[test]
+    fn mcp_prompt_arguments_parse_by_name_and_position() {
+        let command = review_prompt();
+        assert_eq!(command.command(), "github:review");
+        assert_eq!(command.description(), "Review a pull request <pr> [focus]");
+
+        let parsed = command.parse_arguments(r#"focus="error handling" 42"#);
+        assert_eq!(
+            parsed,
+            Ok(HashMap::from([
+                ("pr".to_string(), "42".to_string()),
+                ("focus".to_string(), "error handling".to_string()),
+            ]))
+        );
+        assert_eq!(
+            command.parse_arguments("focus=tests"),
+            Err("missing required argument(s): pr".to_string())
+        );
+        assert_eq!(
+            command.parse_arguments("1 2 3"),
+            Err("unexpected argument '3'".to_string())
+        );
+    }
+}
@@ -11,6 +11,7 @@ use std::sync::LazyLock;
 
 use codex_core::protocol::Op;
 use codex_core::protocol::ReviewDecision;
+use mcp_types::RequestId;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
@@ -36,12 +37,22 @@ pub(crate) enum ApprovalRequest {
         id: String,
         command: Vec<String>,
         reason: Option<String>,
+        grant_root: Option<PathBuf>,
     },
     ApplyPatch {
         id: String,
         reason: Option<String>,
         grant_root: Option<PathBuf>,
     },
+    /// An MCP server asks to sample from the model.
+    McpSampling {
+        server: String,
+        request_id: RequestId,
+        /// Text of the message the server wants the model to answer.
+        message: String,
+        system_prompt: Option<String>,
+        max_tokens: u32,
+    },
 }
 
 /// Options displayed in the *select* mode.
@@ -77,6 +88,37 @@ static COMMAND_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
     ]
 });
 
+/// Options for a command the sandbox blocked when widening it by a single
+/// writable root would let the command through.
+static GRANT_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
+    vec![
+        SelectOption {
+            label: Line::from(vec!["Y".underlined(), "es".into()]),
+            description: "Allow writes to the directory and retry the command in the sandbox",
+            key: KeyCode::Char('y'),
+            decision: ReviewDecision::Approved,
+        },
+        SelectOption {
+            label: Line::from(vec!["A".underlined(), "lways".into()]),
+            description: "Keep write access to the directory for the remainder of this session",
+            key: KeyCode::Char('a'),
+            decision: ReviewDecision::ApprovedForSession,
+        },
+        SelectOption {
+            label: Line::from(vec!["U".underlined(), "nsandboxed".into()]),
+            description: "Retry the command without the sandbox instead",
+            key: KeyCode::Char('u'),
+            decision: ReviewDecision::ApprovedWithoutSandbox,
+        },
+        SelectOption {
+            label: Line::from(vec!["N".underlined(), "o".into()]),
+            description: "Do not run the command",
+            key: KeyCode::Char('n'),
+            decision: ReviewDecision::Denied,
+        },
+    ]
+});
+
 static PATCH_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
     vec![
         SelectOption {
@@ -94,6 +136,26 @@ static PATCH_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
     ]
 });
 
+static SAMPLING_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
+    vec![
+        SelectOption {
+            label: Line::from(vec!["Y".underlined(), "es".into()]),
+            description: "Send the request to the model and return the reply to the server",
+            key: KeyCode::Char('y'),
+            decision: ReviewDecision::Approved,
+        },
+        SelectOption {
+            label: Line::from(vec!["N".underlined(), "o".into()]),
+            description: "Refuse the request",
+            key: KeyCode::Char('n'),
+            decision: ReviewDecision::Denied,
+        },
+    ]
+});
+
+/// Characters of a sampling request's message shown in the prompt.
+const SAMPLING_PREVIEW_CHARS: usize = 400;
+
 /// A modal prompting the user to approve or deny the pending request.
 pub(crate) struct UserApprovalWidget<'a> {
     approval_request: ApprovalRequest,
@@ -113,7 +175,10 @@ impl UserApprovalWidget<'_> {
     pub(crate) fn new(approval_request: ApprovalRequest, app_event_tx: AppEventSender) -> Self {
         let confirmation_prompt = match &approval_request {
             ApprovalRequest::Exec {
-                command, reason, ..
+                command,
+                reason,
+                grant_root,
+                ..
             } => {
                 let cmd = strip_bash_lc_and_escape(command);
                 // Present a single-line summary without cwd: "codex wants to run: <cmd>"
@@ -131,6 +196,15 @@ impl UserApprovalWidget<'_> {
                     contents.push(Line::from(reason.clone().italic()));
                     contents.push(Line::from(""));
                 }
+                if let Some(root) = grant_root {
+                    contents.push(Line::from(format!(
+                        "\"Yes\" runs the command in the sandbox again with write access to {}; \
+                         \"Always\" keeps that access for the remainder of this session; \
+                         \"Unsandboxed\" retries it without the sandbox.",
+                        root.display()
+                    )));
+                    contents.push(Line::from(""));
+                }
                 Paragraph::new(contents).wrap(Wrap { trim: false })
             }
             ApprovalRequest::ApplyPatch {
@@ -153,12 +227,44 @@ impl UserApprovalWidget<'_> {
 
                 Paragraph::new(contents).wrap(Wrap { trim: false })
             }
+            ApprovalRequest::McpSampling {
+                server,
+                message,
+                system_prompt,
+                max_tokens,
+                ..
+            } => {
+                let mut contents: Vec<Line> = vec![
+                    Line::from(vec![
+                        "? ".fg(crate::colors::info()),
+                        server.clone().bold(),
+                        " wants a reply from the model ".bold(),
+                        format!("(up to {max_tokens} tokens)").dim(),
+                    ]),
+                    Line::from(""),
+                ];
+                if let Some(system_prompt) = system_prompt {
+                    contents.push(Line::from(system_prompt.clone().italic().dim()));
+                }
+                let mut preview: String = message.chars().take(SAMPLING_PREVIEW_CHARS).collect();
+                if preview.len() < message.len() {
+                    preview.push('…');
+                }
+                contents.push(Line::from(preview));
+                contents.push(Line::from(""));
+                Paragraph::new(contents).wrap(Wrap { trim: false })
+            }
         };
 
         Self {
             select_options: match &approval_request {
+                ApprovalRequest::Exec {
+                    grant_root: Some(_),
+                    ..
+                } => &GRANT_SELECT_OPTIONS,
                 ApprovalRequest::Exec { .. } => &COMMAND_SELECT_OPTIONS,
                 ApprovalRequest::ApplyPatch { .. } => &PATCH_SELECT_OPTIONS,
+                ApprovalRequest::McpSampling { .. } => &SAMPLING_SELECT_OPTIONS,
             },
             approval_request,
             app_event_tx,
@@ -265,6 +371,17 @@ impl UserApprovalWidget<'_> {
                             "every time this session".bold(),
                         ]));
                     }
+                    ReviewDecision::ApprovedWithoutSandbox => {
+                        lines.push(Line::from(vec![
+                            "✔ ".fg(crate::colors::success()),
+                            "You ".into(),
+                            "approved".bold(),
+                            " codex to run ".into(),
+                            cmd_span,
+                            " ".into(),
+                            "without the sandbox".bold(),
+                        ]));
+                    }
                     ReviewDecision::Denied => {
                         lines.push(Line::from(vec![
                             "✗ ".fg(crate::colors::error()),
@@ -288,6 +405,25 @@ impl UserApprovalWidget<'_> {
             ApprovalRequest::ApplyPatch { .. } => {
                 lines.push(Line::from(format!("patch approval decision: {decision:?}")));
             }
+            ApprovalRequest::McpSampling { server, .. } => {
+                let line = match decision {
+                    ReviewDecision::Approved
+                    | ReviewDecision::ApprovedForSession
+                    | ReviewDecision::ApprovedWithoutSandbox => Line::from(vec![
+                        "✔ ".fg(crate::colors::success()),
+                        "You ".into(),
+                        "approved".bold(),
+                        format!(" the model request from {server}").into(),
+                    ]),
+                    ReviewDecision::Denied | ReviewDecision::Abort => Line::from(vec![
+                        "✗ ".fg(crate::colors::error()),
+                        "You ".into(),
+                        "refused".bold(),
+                        format!(" the model request from {server}").into(),
+                    ]),
+                };
+                lines.push(line);
+            }
         }
         if !feedback.trim().is_empty() {
             lines.push(Line::from("feedback:"));
@@ -307,6 +443,13 @@ impl UserApprovalWidget<'_> {
                 id: id.clone(),
                 decision,
             },
+            ApprovalRequest::McpSampling {
+                server, request_id, ..
+            } => Op::McpSamplingApproval {
+                server: server.clone(),
+                request_id: request_id.clone(),
+                decision,
+            },
         };
 
         self.app_event_tx.send(AppEvent::CodexOp(op));
@@ -357,6 +500,7 @@ impl WidgetRef for &UserApprovalWidget<'_> {
         let title = match &self.approval_request {
             ApprovalRequest::Exec { .. } => "Allow command?",
             ApprovalRequest::ApplyPatch { .. } => "Apply changes?",
+            ApprovalRequest::McpSampling { .. } => "Allow model request?",
         };
         Line::from(title).render(title_area, buf);
 
@@ -404,6 +548,7 @@ mod tests {
             id: "1".to_string(),
             command: vec!["echo".to_string()],
             reason: None,
+            grant_root: None,
         };
         let mut widget = UserApprovalWidget::new(req, tx);
         widget.handle_key_event(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
@@ -426,6 +571,7 @@ mod tests {
             id: "2".to_string(),
             command: vec!["echo".to_string()],
             reason: None,
+            grant_root: None,
         };
         let mut widget = UserApprovalWidget::new(req, tx);
         widget.handle_key_event(KeyEvent::new(KeyCode::Char('Y'), KeyModifiers::NONE));
@@ -439,4 +585,27 @@ mod tests {
             })
         )));
     }
+
+    #[test]
+    fn blocked_command_can_be_retried_without_the_sandbox() {
+        let (tx_raw, rx) = channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let req = ApprovalRequest::Exec {
+            id: "3".to_string(),
+            command: vec!["touch".to_string(), "/opt/cache/x".to_string()],
+            reason: None,
+            grant_root: Some(PathBuf::from("/opt/cache")),
+        };
+        let mut widget = UserApprovalWidget::new(req, tx);
+        widget.handle_key_event(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE));
+        assert!(widget.is_complete());
+        let events: Vec<AppEvent> = rx.try_iter().collect();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            AppEvent::CodexOp(Op::ExecApproval {
+                decision: ReviewDecision::ApprovedWithoutSandbox,
+                ..
+            })
+        )));
+    }
 }